@@ -0,0 +1,883 @@
+# Deutsche Texte von Image Finalizer. Zur Syntax siehe src/i18n.rs.
+
+## Errors
+
+error-decode = { $path } konnte nicht gelesen werden: { $source }
+error-encode = { $path } konnte nicht kodiert werden: { $source }
+error-invalid-path = { $path } hat keinen verwendbaren Dateinamen
+skip-output-exists = die Ausgabe gibt es schon
+preset-invalid-name = ungültiger Vorgabenname `{ $name }`
+preset-read = Vorgabe konnte nicht gelesen werden: { $error }
+preset-write = Vorgabe konnte nicht geschrieben werden: { $error }
+settings-invalid = ungültige Einstellungsdatei: { $error }
+settings-not-settings = keine Einstellungsdatei von Image Finalizer
+settings-unsupported-version = Version { $version } der Einstellungsdatei ist neuer, als diese App unterstützt ({ $supported })
+clear-same-as-input = der Ausgabeordner ist der Eingabeordner
+clear-contains-input = der Ausgabeordner enthält den Eingabeordner
+clear-root = { $path } ist das Stammverzeichnis eines Laufwerks
+clear-not-a-folder = { $path } ist kein Ordner
+
+## Name templates
+
+template-empty = die Namensvorlage ist leer
+template-empty-name = die Namensvorlage ergibt einen leeren Dateinamen
+template-unclosed = eine { in der Namensvorlage wird nie geschlossen
+template-unknown-placeholder = { $placeholder } ist kein Platzhalter
+template-invalid-sequence = { $placeholder } braucht eine Breite und einen Start, etwa {seq:3} oder {seq:3:1}
+template-sequence-overflow = {seq} zählt über { $max } hinaus; fang niedriger an
+template-invalid-date = %{ $field } ist kein Datumsfeld
+template-illegal-character = Dateinamen dürfen { $character } nicht enthalten
+template-empty-folder = das Ordnermuster ergibt einen leeren Ordnernamen
+template-not-for-run-folder = { $placeholder } kann keinen Stapelordner benennen, das können nur {date} und {preset}
+
+## Pre-flight checks
+
+check-output-is-input = Der Ausgabeordner ist der Eingabeordner; Dateien, die wie Ausgaben heißen, werden nicht verarbeitet
+check-output-inside-input = Der Ausgabeordner liegt im Eingabeordner
+check-overwrites-itself = { $path } würde von der eigenen Ausgabe überschrieben; ändere Suffix, Namensvorlage, Format oder Ausgabeordner
+check-overwrites-input = { $target } würde während der Verarbeitung von der Ausgabe von { $path } überschrieben
+check-name-mangled = { $path } wird als { $name } gespeichert, weil der Name auf diesem System nicht erlaubt ist
+check-huge-canvas = { $path } wäre { $width }×{ $height } px groß
+check-reduced-depth = { $paths }: { $bits } Bit pro Kanal, aber { $format } speichert 8
+check-taken-renamed = { $paths }: Der Ausgabename ist vergeben, daher wird eine Nummer angehängt
+check-taken-skipped = { $paths }: Der Ausgabename ist vergeben, daher wird nichts geschrieben
+check-transparent-border = Die Randfarbe ist teilweise transparent, aber { $format } kann keine Transparenz speichern
+check-missing-texture = Die Randtextur { $path } gibt es nicht
+check-collision-kept = { $paths } werden alle nach { $name } geschrieben; nur eine davon bleibt erhalten
+check-collision-renamed = { $paths } werden alle nach { $name } geschrieben; alle bis auf eine bekommen eine Nummer
+check-collision-skipped = { $paths } werden alle nach { $name } geschrieben; nur eine davon wird geschrieben
+check-template = Die Namensvorlage ist nicht verwendbar: { $error }
+check-run-folder = Das Muster für Stapelordner ist nicht verwendbar: { $error }
+check-date-folders = Das Muster für Datumsordner ist nicht verwendbar: { $error }
+check-no-input = Kein Eingabeordner gewählt
+check-no-images = Keine Bilder in { $folder } gefunden
+check-no-output = Kein Ausgabeordner gewählt
+check-not-a-directory = { $path } ist kein Ordner
+check-not-writable = In { $path } kann nicht geschrieben werden: { $error }
+check-border-range = Der Rand von { $value } % liegt nicht zwischen 0 und { $max } %
+check-border-pixels-range = Der Rand von { $value } px ist breiter als { $max } px
+check-frame-range = Der Rahmen von { $value } % liegt nicht zwischen 0 und { $max } %
+check-resize-range = Die Größe von { $value } px liegt nicht zwischen 1 und { $max } px
+
+## Settings summaries
+
+summary-border = Rand
+summary-border-square = { $percentage } % quadratische Fläche
+summary-border-symmetrical = { $percentage } % symmetrisch
+summary-border-sides = oben { $top } %, rechts { $right } %, unten { $bottom } %, links { $left } %
+summary-border-pixels-square = { $width } px quadratische Fläche
+summary-border-pixels-symmetrical = { $width } px symmetrisch
+summary-border-after-resize = { $border } nach dem Skalieren
+summary-color = Farbe
+summary-fill = Füllung
+fill-linear-summary = linearer Verlauf bis { $color }, { $angle }°
+fill-radial-summary = radialer Verlauf bis { $color }
+fill-blur-summary = unscharfes Foto, { $strength } %
+fill-blur-tinted-summary = unscharfes Foto, { $strength } %, { $tint } % getönt
+fill-texture-tile-summary = { $name }, gekachelt
+fill-texture-stretch-summary = { $name }, gestreckt
+summary-canvas = Fläche
+summary-frame = Rahmen
+summary-frame-inside = { $width } % { $color } innerhalb des Rands
+summary-frame-outside = { $width } % { $color } um den Rand
+summary-corners = Ecken
+summary-shadow = Schatten
+summary-shadow-value = { $x }, { $y } px, Unschärfe { $blur } px, { $opacity } %
+summary-keyline = Konturlinie
+summary-resize = Skalieren
+summary-resize-off = aus
+summary-format = Format
+summary-compression = Kompression
+summary-quality = Qualität
+summary-avif-quality = { $quality } Tempo { $speed }
+
+## Keyboard shortcuts
+
+shortcuts-title = Tastenkürzel
+shortcut-input = Eingabeordner wählen
+shortcut-output = Ausgabeordner wählen
+shortcut-start = Verarbeitung starten
+shortcut-escape = Offenen Dialog schließen oder laufenden Stapel abbrechen
+shortcut-browse = Vorheriges / nächstes Bild in der Vorschau, solange kein Bedienelement fokussiert ist
+shortcut-scale = Oberfläche vergrößern / verkleinern
+shortcut-scale-reset = Skalierung der Oberfläche zurücksetzen
+shortcut-undo = Einstellungsänderung rückgängig machen
+shortcut-redo = Einstellungsänderung wiederholen
+shortcut-focus = Zum nächsten / vorherigen Bedienelement springen
+shortcut-activate = Fokussierten Knopf drücken oder fokussiertes Kästchen umschalten
+shortcuts-text-fields = Solange ein Textfeld den Fokus hat, sind die Kürzel aus.
+
+## Top bar
+
+theme-system = Systemdesign
+theme-light = Helles Design
+theme-dark = Dunkles Design
+theme-switch = { $current }, klicken für { $next }
+language-system = System ({ $language })
+language-hover = Sprache
+ui-scale = Skalierung
+undo = ⟲ Rückgängig
+redo = ⟳ Wiederholen
+
+## Folder fields
+
+hint-missing-folder = Diesen Ordner gibt es nicht
+hint-not-a-folder = Das ist kein Ordner
+hint-file-not-folder = Das ist eine Datei, kein Ordner
+
+## Status messages
+
+status-scan-failed = Der Eingabeordner kann nicht gelesen werden: { $error }
+status-cancelling = Wird abgebrochen …
+status-preset-loaded = Vorgabe „{ $name }“ geladen
+status-preset-saved = Vorgabe „{ $name }“ gespeichert
+status-preset-renamed = Vorgabe „{ $from }“ in „{ $to }“ umbenannt
+status-preset-deleted = Vorgabe „{ $name }“ gelöscht
+status-preset-error = Fehler bei der Vorgabe: { $error }
+status-open-failed = { $path } kann nicht geöffnet werden: { $error }
+
+## Presets
+
+preset-select = Vorgabe wählen
+preset-name = Name:
+preset-save = Aktuelle speichern als
+preset-rename = Gewählte umbenennen
+
+## Common buttons
+
+button-reset = Zurücksetzen
+button-load = Laden
+button-delete = Löschen
+button-apply = Übernehmen
+button-cancel = Abbrechen
+button-close = Schließen
+button-open = Öffnen
+button-reveal = Anzeigen
+filter-settings = Einstellungen
+filter-images = Bilder
+
+## Batch progress
+
+counts-ok = { $count } ok
+counts-failed = { $count } fehlgeschlagen
+counts-skipped = { $count } übersprungen
+counts-total = { $count } gesamt
+
+## Results
+
+results-header = Ergebnisse ({ $count })
+reveal-hover = Datei im Dateimanager zeigen
+results-open-folder = Ausgabeordner öffnen
+show-failures = Fehler zeigen
+failures-header = Fehlgeschlagene Bilder ({ $count })
+
+## Importing settings
+
+import-title = Einstellungen importieren
+import-same = Die importierten Einstellungen gleichen den aktuellen.
+import-changes = Der Import ändert:
+import-missing-files = Diese Dateien gibt es nicht dort, wo die Einstellungen sie erwarten. Wähle sie vor dem Verarbeiten neu aus:
+compare-same = Gleiche Einstellungen
+status-import-applied = Importierte Einstellungen übernommen.
+status-copied = { $color } in die Zwischenablage kopiert
+status-load-failed = Fehler beim Laden des Originalbilds: { $error }
+status-checking = Einstellungen werden geprüft …
+inspector-copy = Klicken zum Kopieren
+
+## Pre-flight dialog
+
+preflight-blocked = Verarbeitung kann nicht starten
+preflight-confirm = Verarbeitung starten?
+preflight-run-folder = Ausgaben landen in { $folder }
+preflight-overlap = Ausgaben in den Eingabeordner schreiben
+button-start-anyway = Trotzdem starten
+
+## Clearing the output folder
+
+clear-title = Ausgabeordner leeren
+clear-refused = Es wird nichts gelöscht: { $reason }.
+clear-empty = Der Ordner ist schon leer.
+clear-summary = { $files } Dateien und { $folders } Ordner mit insgesamt { $size } werden gelöscht.
+clear-unknown = { $count } davon sehen nicht aus, als hätte dieses Programm sie geschrieben:
+clear-more = … und { $count } weitere
+clear-confirmation-word = löschen
+clear-type = Gib „{ $word }“ ein, um auch sie zu löschen:
+clear-progress = Lösche { $done } von { $total }
+clear-deleted = { $count } Dateien gelöscht.
+clear-failed = { $count } konnten nicht gelöscht werden:
+
+## Restoring a session
+
+recovery-title = Letzte Sitzung wiederherstellen
+recovery-crashed = Image Finalizer wurde letztes Mal nicht sauber beendet.
+recovery-remaining = { $remaining } von { $total } Bildern des laufenden Stapels wurden nicht verarbeitet.
+recovery-resume = Einstellungen wiederherstellen und Stapel fortsetzen
+recovery-restore = Einstellungen wiederherstellen
+recovery-discard = Verwerfen
+
+## Log panel
+
+log-verbosity = Ausführlichkeit:
+log-verbosity-hover = DEBUG führt auch jede fertige Datei eines Stapels auf
+log-filter = Filter
+log-copy = Alles kopieren
+log-copy-hover = Kopiert die gezeigten Zeilen. Markiere Zeilen, um nur diese zu kopieren.
+log-hint = Details stehen im Protokoll in { $folder }
+
+## Batches
+
+status-processing = Verarbeite { $count } Bilder nach { $folder } …
+overlap-same = ⚠ Das ist der Eingabeordner. Die Ausgaben landen neben den Eingaben; der Start muss bestätigt werden.
+overlap-inside = ⚠ Das liegt im Eingabeordner. Beim Einlesen mit Unterordnern würden die Ausgaben mitgelesen; der Start muss bestätigt werden.
+
+## Name template help
+
+template-help = {name}: Name der Eingabe ohne Endung
+    {suffix}: das Suffix neben der Vorlage
+    {ext}: Endung des Ausgabeformats
+    {format}: Ausgabeformat
+    {width}, {height}: Ausgabegröße in Pixeln
+    {date}, {date:%Y-%m-%d}: Aufnahmedatum oder Dateidatum
+    {seq}, {seq:4}, {seq:4:0}: Position im Stapel, mit Breite und Start
+    {preset}: Vorgabe, mit der das Bild verarbeitet wird
+
+## Finished batches
+
+status-preview-failed = Fehler beim Erzeugen der Vorschau: { $error }
+status-exported = Einstellungen nach { $path } exportiert
+status-export-failed = Fehler beim Exportieren der Einstellungen: { $error }
+status-import-failed = Fehler beim Importieren der Einstellungen: { $error }
+status-preview-saved = Vorschau in { $path } gespeichert
+status-preview-save-failed = Fehler beim Speichern der Vorschau: { $error }
+status-cleared = Ausgabeordner geleert, { $count } Dateien gelöscht.
+status-clear-failed = Ausgabeordner geleert, { $count } Dateien konnten nicht gelöscht werden.
+archive-zipped = In { $name } gepackt.
+archive-exists = Das Archiv gibt es schon, es wurde nicht angetastet.
+archive-failure = Das Archiv kann nicht geschrieben werden: { $error }
+archive-failed = Das Archiv konnte nicht geschrieben werden.
+batch-cancelled = Verarbeitung nach { $done } von { $total } Bildern abgebrochen: { $counts }.
+batch-failures = Verarbeitung mit Fehlern beendet: { $counts }.
+batch-complete-skipped = Verarbeitung fertig, { $size } geschrieben, { $skipped } Bilder übersprungen.
+batch-complete = Verarbeitung fertig, { $size } geschrieben.
+batch-ended = Beendet am { $time }.
+
+## Settings panel
+
+section-presets = Vorgaben
+section-preset-rules = Regeln für Vorgaben
+section-io = Ein-/Ausgabe
+section-files = Ausgabedateien
+section-border = Rand
+section-resize = Skalieren
+section-format = Format
+section-metadata = Metadaten
+section-about = Über
+rules-show = Zuordnung zeigen
+rules-run-folder = Im Stapelordner { $folder }:
+settings-export = Einstellungen exportieren …
+settings-import = Einstellungen importieren …
+input-dir = Eingabeordner:
+input-open = Eingabeordner öffnen
+input-found = { $count } Bilder gefunden
+input-rescan = Neu einlesen
+output-dir = Ausgabeordner:
+output-clear = Leeren …
+output-clear-hover = Zeigt, was im Ausgabeordner liegt, und löscht es dann
+output-open = Ausgabeordner öffnen
+names-label = Dateinamen:
+suffix-label = Suffix:
+suffix-hover = Wird mit {suffix} an den Namen gehängt; darf leer sein
+folders-subfolders = Unterordner einbeziehen
+folders-keep = Ordnerstruktur beibehalten
+folders-keep-hover = Sonst landen alle Ausgaben direkt im Ausgabeordner
+folders-by-date = Datumsordner
+folders-date-hover = Ordner nach dem Aufnahme- oder Dateidatum; / beginnt einen Unterordner. Dateien ohne Datum landen in „undated“.
+    %Y, %y: Jahr, %m: Monat, %d: Tag, %j: Tag des Jahres
+folders-run = Stapelordner
+folders-run-hover = Ein neuer Ordner im Ausgabeordner für jeden Stapel.
+    {date}, {date:%Y-%m-%d_%H%M}: wann der Stapel startet
+    {preset}: die gewählte Vorgabe oder „manual“
+folders-format = Formatordner
+folders-format-hover = Ausgaben landen in einem Ordner je Format, etwa „jpeg“
+folders-size = Größenordner
+folders-size-hover = Ausgaben landen in einem Ordner je längster Seite, etwa „2048px“
+existing-label = Vorhandene Dateien:
+existing-replace = Ersetzen
+existing-number = Nummer anhängen
+existing-number-hover = Speichert stattdessen als „name (1)“, „name (2)“, …
+existing-skip = Überspringen
+originals-label = Originale kopieren:
+originals-no = Nein
+originals-beside = Neben die Ausgaben
+originals-subfolder = In „{ $folder }“
+originals-hover = Kopiert jede Eingabe unverändert, nachdem ihre Ausgabe geschrieben ist
+manifest-label = Protokolldatei:
+manifest-none = Keine
+manifest-json-hover = Ein JSON-Objekt je Zeile
+manifest-hover = Ein Verzeichnis aller Dateien eines Stapels, im Ausgabeordner abgelegt
+placeholder-label = Platzhalter:
+placeholder-none = Keiner
+placeholder-thumbhash-hover = Behält Transparenz und die Form des Bildes
+placeholder-hover = Ein winziger, verschwommener Ersatz für jede geschriebene Datei, in der Protokolldatei vermerkt, damit Webseiten ihn zeigen, während das Bild lädt
+archive-create = Zip-Archiv erstellen
+archive-name-hover = Name des Archivs im Ausgabeordner
+archive-store = Speichern
+archive-store-hover = Am schnellsten; Bilder lassen sich ohnehin kaum packen
+name-example = Beispiel: { $name }
+border-percentage = Randbreite in Prozent
+border-in-percent = Prozent
+border-in-pixels = Pixel
+border-in-pixels-hover = Derselbe Rand auf jedem Foto eines Durchlaufs, egal wie groß es ist
+border-pixels = Breite je Seite
+border-after-resize = Nach dem Skalieren
+border-after-resize-hover = Misst die Breite an der skalierten Ausgabe, damit sie genau so breit wird
+border-after-resize-off = Nur mit eingeschaltetem Skalieren
+border-symmetrical = Symmetrischer Rand
+canvas-aspect = Fläche
+canvas-aspect-border = Nach dem Rand
+canvas-aspect-hover = Füllt das Foto mit Rand auf eine genaue Form auf, etwa 4:5 für einen Beitrag im Hochformat, und hält es mittig
+canvas-aspect-width = Breite des Seitenverhältnisses
+canvas-aspect-height = Höhe des Seitenverhältnisses
+frame = Rahmen
+frame-hover = Legt einen zweiten Rand mit eigener Breite und Farbe auf den ersten, etwa einen schmalen Rahmen in einem breiten Passepartout
+frame-width = Rahmenbreite
+frame-color = Rahmenfarbe
+frame-inside = Innerhalb des Rands
+frame-outside = Um den Rand
+corners = Runde Ecken
+corners-hover = Rundet die Ecken des Fotos ab, sodass der Rand durchscheint
+corners-radius = Eckenradius
+corners-pixels = Pixel
+corners-percent = Prozent der kürzesten Seite
+keyline = Konturlinie
+keyline-hover = Zieht eine feine Linie um das Foto, wie bei Passepartouts in Galerien
+keyline-width = Breite der Konturlinie
+keyline-color = Farbe der Konturlinie
+shadow = Schlagschatten
+shadow-hover = Wirft einen weichen Schatten unter das Foto, sodass es über dem Rand zu schweben scheint
+shadow-x = Rechts
+shadow-y = Unten
+shadow-blur = Unschärfe
+shadow-opacity = Deckkraft
+shadow-color = Schattenfarbe
+border-sides = Eigene Breite je Seite
+border-sides-hover = Für Passepartouts, die unten breiter sind, und Ähnliches. Jede Seite in Prozent der längsten Seite des Fotos
+border-sides-active = Ersetzt durch die Breiten je Seite
+border-top = Oben
+border-right = Rechts
+border-bottom = Unten
+border-left = Links
+border-color = Farbe
+border-color-hover = Füllung des Rands. Transparenz bleibt in den Formaten erhalten, die sie speichern
+border-color-white = Weiß
+border-color-black = Schwarz
+border-color-cream = Creme
+fill = Füllung
+fill-solid = Farbe
+fill-gradient = Verlauf
+fill-gradient-hover = Lässt den Rand von seiner Farbe in eine zweite übergehen
+fill-linear = Linear
+fill-radial = Radial
+fill-gradient-color = Bis
+fill-angle = Winkel
+fill-angle-hover = Richtung des Verlaufs: 0° läuft von links nach rechts, 90° von oben nach unten
+fill-blur = Unscharfes Foto
+fill-blur-hover = Füllt den Rand mit dem Foto selbst, vergrößert und weichgezeichnet, wie bei Storys im Letterbox-Format
+fill-blur-strength = Unschärfe
+fill-blur-strength-hover = Radius der Unschärfe in Prozent der längsten Seite
+fill-blur-tint = Tönung
+fill-blur-tint-hover = Wie weit die Unschärfe zur Randfarbe hin gemischt wird
+fill-texture = Textur
+fill-texture-hover = Füllt den Rand mit einer Bilddatei, etwa einem Papier- oder Leinwandscan, über der Randfarbe
+fill-texture-choose = Bild wählen…
+fill-texture-none = Kein Bild gewählt
+fill-texture-tile = Kacheln
+fill-texture-tile-hover = Wiederholt das Bild in seiner eigenen Größe
+fill-texture-stretch = Strecken
+fill-texture-stretch-hover = Skaliert das Bild auf die ganze Leinwand
+resize-images = Bilder skalieren
+resize-longest = Längste Seite:
+resize-algorithm = Skalierungsverfahren:
+filter-nearest-hint = Am schnellsten, geringste Qualität.
+filter-triangle-hint = Schnell, ordentliche Qualität.
+filter-catmullrom-hint = Gute Qualität, mittleres Tempo.
+filter-lanczos3-hint = Beste Qualität, am langsamsten.
+format-output = Ausgabeformat:
+png-compression = PNG-Kompression:
+png-fast = Schnell
+png-default = Standard
+png-best = Beste (am kleinsten)
+jpeg-quality = JPEG-Qualität (1–100):
+avif-speed = AVIF-Tempo (1–10):
+avif-speed-hover = 1 = am langsamsten, bessere Kompression, 10 = am schnellsten
+avif-quality = AVIF-Qualität (1–100):
+format-lossless = Verlustfrei, keine Optionen.
+metadata-timestamps = Zeitstempel beibehalten
+metadata-timestamps-hover = Ausgaben und Kopien erhalten die Änderungszeit ihrer Eingabe
+about-storage = Einstellungen und Vorgaben liegen in { $folder }
+about-portable = Portabler Modus: In dein Benutzerprofil wird nichts geschrieben.
+about-portable-hint = Starte mit --portable oder lege eine portable.txt neben das Programm, um stattdessen alles neben der Programmdatei abzulegen.
+
+## Batch panel
+
+start-button = Verarbeitung starten
+notify-done = Benachrichtigen, wenn fertig
+notify-done-hover = Eine Systembenachrichtigung, wenn ein Stapel endet
+archive-progress = Packe { $done } von { $total }
+log-header = Protokoll
+
+## Preview
+
+preview-heading = Vorschau
+backdrop-label = Hintergrund:
+backdrop-white = Weiß
+backdrop-gray = Grau
+backdrop-black = Schwarz
+backdrop-custom = Eigener
+compare-repin = Neu als A anheften
+compare-pin = Zum Vergleich anheften
+preview-save = Vorschau speichern …
+preview-copy = Vorschau kopieren
+preview-copy-hover = Kopiert die Vorschau mit bis zu { $side } px an der längsten Seite, zum Einfügen in einen Chat oder eine E-Mail. Ausgaben in der Liste werden in voller Größe kopiert.
+compare-swap = A/B tauschen
+compare-clear = Vergleich beenden
+info-header = Bildinfo
+histogram = Histogramm
+preview-none = Keine Vorschau vorhanden. Lade zuerst Bilder.
+
+## Preset rules
+
+rule-portrait = Hochformat
+rule-landscape = Querformat
+rule-square = Quadratisch
+rule-longest-below = Längste Seite unter
+rule-longest-at-least = Längste Seite ab
+rule-description = Regel { $number } ({ $condition })
+rule-skipped = Regel { $number } übersprungen, Vorgabe „{ $preset }“: { $error }
+applied-manual = manuelle Einstellungen
+applied-manual-unmatched = manuelle Einstellungen (keine Regel passte)
+applied-manual-size-unknown = manuelle Einstellungen (Größe unbekannt)
+applied-rule = { $rule } → Vorgabe „{ $preset }“
+rules-enabled = Vorgabe je Bild per Regel wählen
+rules-remove = Entfernen
+rules-add = Regel hinzufügen
+
+## Preview guides
+
+guides-label = Hilfslinien:
+guides-thirds = Drittel
+guides-center = Mitte
+guides-safe-margin = Sicherheitsrand
+
+## Image info
+
+info-dimensions = Abmessungen
+info-file-size = Dateigröße
+info-captured = Aufgenommen
+info-camera = Kamera
+info-lens = Objektiv
+info-iso = ISO
+info-aperture = Blende
+info-shutter = Belichtungszeit
+info-color-profile = Farbprofil
+info-orientation = Ausrichtung
+info-icc-profile = Eingebettetes ICC-Profil
+
+## Notifications
+
+notify-cancelled = Abgebrochen nach { $done } von { $total } Bildern
+notify-done-one = 1 Bild fertig
+notify-done-many = { $count } Bilder fertig
+duration-seconds = { $seconds } s
+duration-minutes = { $minutes } min
+duration-hours = { $hours } h { $minutes } min
+
+## Help
+
+help-hover = Erklären, mit Beispielen
+help-border-mode = Normalerweise wird das Foto auf eine quadratische Fläche gesetzt, sodass Quer- und Hochformate gleich geformt herauskommen, mit breiterem Rand an den kurzen Seiten. Symmetrisch fügt an jeder Seite dieselbe Breite an und behält die Form des Fotos.
+help-resize-filter = Das Verfahren bestimmt, wie beim Skalieren neue Pixel aus den alten berechnet werden. Nearest behält harte, gezackte Pixel und Triangle verwischt sie, während CatmullRom und Lanczos3 schärfer bleiben, auf Kosten von Zeit und leichten Säumen.
+help-png-compression = PNG ist verlustfrei, jede Stufe behält also genau dieselben Pixel. Höhere Stufen brauchen nur mehr Zeit, um die Datei zu verkleinern, wie bei diesem Beispiel:
+help-jpeg-quality = Geringere Qualität wirft Details weg, um die Datei zu verkleinern, zuerst als verschmierte Farben und Blöcke an Kanten. Um 80 ist kaum vom Original zu unterscheiden; über 90 wächst die Datei schnell bei wenig Gewinn.
+help-avif-quality = AVIF-Dateien sind viel kleiner als gleich aussehende JPEGs, aber geringe Qualität glättet feine Strukturen weg. Das Tempo tauscht Kodierzeit gegen Dateigröße und ändert das Aussehen kaum; Größen dieses Beispiels bei Tempo 6:
+help-border-default = Standard
+help-border-symmetrical = Symmetrisch
+help-png-best = Beste
+help-quality = Qualität { $quality }
+
+## Size estimate
+
+estimate-exact = Ausgabegröße: { $size }
+estimate-range = Geschätzte Ausgabe: etwa { $size } ({ $low } – { $high })
+estimate-hover = Eine Schätzung: { $count } Beispielbilder wurden mit den aktuellen Einstellungen kodiert und nach Eingabegröße hochgerechnet. Die echten Größen hängen vom Inhalt ab.
+estimate-failed = Keine Größenschätzung: { $error }
+estimate-pending = Ausgabegröße wird geschätzt …
+
+## Empty state and recent folders
+
+welcome-title = Willkommen bei Image Finalizer
+welcome-text = Öffne einen Ordner mit Bildern, um ihnen in einem Rutsch Ränder zu geben, sie zu skalieren und umzuwandeln.
+welcome-open = Ordner öffnen…
+welcome-drop = oder zieh einen Ordner oder ein Bild ins Fenster
+recent-heading = Zuletzt verwendete Ordner
+recent-open-hover = Diesen Ordner mit seinem Ausgabeordner öffnen
+recent-missing = Diesen Ordner gibt es nicht mehr
+recent-no-output = noch kein Ausgabeordner
+recent-remove = Aus der Liste entfernen
+
+## Thumbnails of written outputs
+
+thumbnails-header = Bisher geschrieben
+thumbnail-failed = Fehlgeschlagen: { $error }
+    Klicke, um das Original zu zeigen.
+thumbnail-hover = { $path }
+    Klicke, um sie im Dateimanager zu zeigen.
+
+## Processing order
+
+order-header = Verarbeitungsreihenfolge
+order-hint = Zieh die Bilder, um zu ändern, in welcher Reihenfolge sie verarbeitet und nummeriert werden.
+order-reset = Nach Namen sortieren
+
+## Running batches
+
+settings-locked = Die Einstellungen sind während der Verarbeitung gesperrt.
+status-paused = Pausiert. Dateien in Arbeit werden noch fertiggestellt.
+status-resumed = Verarbeitung fortgesetzt.
+button-pause = Pausieren
+button-resume = Fortsetzen
+button-pause-hover = Hält die Verarbeitung nach den Dateien in Arbeit an
+minimize-to-tray = In den Infobereich minimieren
+minimize-to-tray-hover = Ein minimiertes Fenster verschwindet in den Infobereich, der den Fortschritt zeigt
+close-title = Die Verarbeitung läuft
+close-running = Wenn du das Fenster schließt, wird die Verarbeitung abgebrochen.
+close-tray = Im Infobereich verstecken
+close-minimize = Minimiert weiterlaufen lassen
+close-cancel = Abbrechen und beenden
+close-back = Zurück
+
+## Tray icon
+
+tray-progress = Verarbeite { $done }/{ $total } ({ $percent } %)
+tray-paused = Pausiert bei { $done }/{ $total } ({ $percent } %)
+tray-show = Anzeigen
+tray-pause = Pausieren
+tray-resume = Fortsetzen
+tray-quit = Beenden
+
+## Accessibility
+
+order-up = { $name } nach oben schieben
+order-down = { $name } nach unten schieben
+progress-name = Fortschritt des Stapels
+preview-alt = Vorschau von { $name }, Ausgabe { $width } × { $height } px
+preview-previous = Vorheriges Bild
+preview-next = Nächstes Bild
+preview-file = Bild in der Vorschau
+backdrop-color = Hintergrundfarbe
+rule-condition-name = Bedingung von Regel { $number }
+rule-pixels-name = Pixel für Regel { $number }
+rule-preset-name = Vorgabe von Regel { $number }
+guides-margin-name = Breite des Sicherheitsrands
+
+## Simple and Advanced mode
+
+mode-simple = Einfach
+mode-advanced = Erweitert
+mode-advanced-active = Erweiterte Optionen aktiv
+mode-advanced-active-hover = Im erweiterten Modus gesetzt und weiterhin wirksam: { $sections }. Klicke, um sie anzuzeigen.
+
+## Disk space
+
+check-disk-short = Die Ausgaben brauchen etwa { $needed }, auf dem Laufwerk des Ausgabeordners sind aber nur { $available } frei.
+check-disk-tight = Die Ausgaben brauchen etwa { $needed } und auf dem Laufwerk des Ausgabeordners sind { $available } frei. Das lässt kaum Spielraum.
+status-disk-full = Laufwerk voll bei { $path }. Schaffe Platz und setze dann fort.
+disk-full-title = Laufwerk voll
+disk-full-text = { $path } hat nicht mehr in { $folder } gepasst, deshalb ist der Stapel pausiert. Schaffe Platz auf dem Laufwerk und setze dann fort, um es erneut zu versuchen.
+disk-full-available = Jetzt frei: { $size }
+disk-full-resume = Fortsetzen
+
+## Large batches
+
+preflight-summary-title = { $count } Bilder verarbeiten?
+preflight-summary-files = { $count } Bilder nach { $folder }
+preflight-summary-format = { $count } × { $format }, { $bits } Bit pro Kanal, { $dimensions } px
+preflight-summary-size = Insgesamt etwa { $size }
+preflight-summary-size-rough = Insgesamt höchstens etwa { $size }
+preflight-summary-duration = Dauert etwa { $duration }
+preflight-summary-threshold = Nachfragen ab dieser Anzahl Bilder:
+
+## Uploads
+
+section-upload = Hochladen
+check-upload = Hochladen nach { $bucket } nicht möglich: { $error }
+upload-no-credentials = Keine Zugangsdaten gefunden. Setze AWS_ACCESS_KEY_ID und AWS_SECRET_ACCESS_KEY oder speichere sie im Schlüsselbund.
+upload-incomplete = Endpunkt oder Bucket fehlt
+upload-keyring = Schlüsselbund: { $error }
+upload-read = Ausgabe konnte nicht gelesen werden: { $error }
+upload-status = Der Bucket hat mit HTTP { $status } geantwortet
+upload-queued = Wartet aufs Hochladen
+upload-uploading = Wird hochgeladen…
+upload-retrying = Wird hochgeladen, Versuch { $attempt }…
+upload-done = Hochgeladen
+upload-failed = Hochladen fehlgeschlagen
+upload-retry = Erneut versuchen
+upload-header = Nach { $bucket } hochgeladen ({ $done } von { $total })
+upload-copy-urls = Links kopieren
+upload-copy-keys = Pfade kopieren
+upload-retry-failed = { $count } fehlgeschlagene erneut versuchen
+upload-enabled = Ausgaben hochladen
+upload-enabled-hover = Jede Ausgabe wird hochgeladen, sobald sie geschrieben ist, in einen S3-kompatiblen Bucket wie Amazon S3, Backblaze B2 oder MinIO oder auf einen SFTP-Server.
+upload-endpoint = Endpunkt
+upload-region = Region
+upload-bucket = Bucket
+upload-prefix = Schlüsselpräfix
+upload-credentials = Die Zugangsdaten kommen aus { $access } und { $secret } oder aus dem Schlüsselbund des Systems:
+upload-access-key = Zugriffsschlüssel-ID
+upload-secret-key = Geheimer Zugriffsschlüssel
+upload-save-credentials = Im Schlüsselbund speichern
+upload-check-credentials = Prüfen
+upload-forget-credentials = Aus dem Schlüsselbund entfernen
+upload-credentials-saved = Zugangsdaten für { $bucket } im Schlüsselbund gespeichert
+upload-credentials-environment = Zugangsdaten in der Umgebung gefunden
+upload-credentials-keyring = Zugangsdaten im Schlüsselbund gefunden
+upload-credentials-forgotten = Zugangsdaten für { $bucket } aus dem Schlüsselbund entfernt
+
+## SFTP uploads
+
+upload-target-s3 = S3-Bucket
+upload-target-sftp = SFTP-Server
+upload-cancelled = Abgebrochen
+upload-cancel = Hochladen abbrechen
+sftp-incomplete = Host oder Benutzername fehlt
+sftp-unknown-host = Der Host-Schlüssel { $fingerprint } ist noch nicht vertrauenswürdig. Prüfe den Host in den Upload-Einstellungen und vertraue seinem Schlüssel.
+sftp-host-changed = Der Host-Schlüssel hat sich von { $trusted } zu { $fingerprint } geändert. Vielleicht fängt jemand die Verbindung ab; vertraue dem neuen Schlüssel nur, wenn der Server neu aufgesetzt wurde.
+sftp-rejected = Der Server hat den Schlüssel nicht angenommen
+sftp-key = Schlüsseldatei konnte nicht gelesen werden: { $error }
+sftp-agent = SSH-Agent: { $error }
+sftp-transfer = Übertragung fehlgeschlagen: { $error }
+sftp-server = Der Server hat geantwortet: { $error }
+sftp-host = Host
+sftp-port = Port
+sftp-username = Benutzername
+sftp-base-path = Ordner
+sftp-base-path-hover = Die Ausgaben landen in diesem Ordner auf dem Server, in denselben Unterordnern wie im Ausgabeordner. Pfade ohne führenden / beginnen in deinem Home-Ordner.
+sftp-retries = Wiederholungen pro Datei
+sftp-use-agent = SSH-Agent
+sftp-agent-hover = Meldet sich mit den Schlüsseln des laufenden SSH-Agents an, sodass Passphrasen bei ihm bleiben.
+sftp-key-file = Schlüsseldatei ohne Passphrase
+sftp-choose-key = Auswählen…
+sftp-check-host = Host-Schlüssel prüfen
+sftp-host-known = Diesem Host wird ein Schlüssel vertraut
+sftp-connecting = Verbinde…
+sftp-host-trusted = Dem Host-Schlüssel { $fingerprint } wird vertraut
+sftp-host-unknown = Der Host hat den Schlüssel { $fingerprint } gezeigt. Vergleiche ihn mit dem Fingerabdruck des Servers, bevor du ihm vertraust.
+sftp-trust = Diesem Schlüssel vertrauen
+sftp-trust-changed = Dem neuen Schlüssel vertrauen
+sftp-trusted = Dem Host-Schlüssel von { $address } wird jetzt vertraut
+sftp-known-hosts-error = Host-Schlüssel konnte nicht gespeichert werden: { $error }
+
+## Hook command
+
+section-hook = Befehl nach jeder Ausgabe
+check-hook = Der Befehl nach jeder Ausgabe ist ungültig: { $error }
+error-hook = { $path }: { $source }
+hook-empty = Der Befehl ist leer
+hook-unclosed-quote = Ein Anführungszeichen im Befehl ist nicht geschlossen
+hook-spawn = { $program } konnte nicht ausgeführt werden: { $error }
+hook-timeout = Der Befehl wurde nach { $seconds } s abgebrochen
+hook-exit = Der Befehl ist fehlgeschlagen ({ $status }): { $stderr }
+hook-enabled = Für jede Ausgabe einen Befehl ausführen
+hook-enabled-hover = Läuft direkt nachdem die Ausgabe geschrieben ist, bevor die nächste Datei beginnt. Was der Befehl ausgibt, landet im Protokoll.
+hook-command = Befehl
+hook-placeholders = Platzhalter: { $placeholders }. Anführungszeichen fassen Wörter zusammen; es ist keine Shell beteiligt.
+hook-timeout-label = Abbrechen nach
+hook-on-failure = Wenn er fehlschlägt:
+hook-warn = Nur warnen
+hook-warn-hover = Die Datei gilt als fertig und der Fehler landet im Protokoll.
+hook-fail = Datei als fehlgeschlagen markieren
+import-hook-warning = Diese Datei enthält einen Befehl, der für jede Ausgabe ausgeführt würde. Übernimm ihn nur, wenn du der Herkunft der Datei vertraust.
+import-hook-take = Befehl übernehmen
+status-exported-command = Einstellungen nach { $path } exportiert, samt dem Befehl für jede Ausgabe
+
+## Duplicates
+
+order-include = { $name } verarbeiten
+order-excluded = { $count } vom Stapel ausgenommen
+order-include-all = Alle einbeziehen
+duplicates-find = Duplikate suchen …
+duplicates-find-hover = Sucht nahezu gleiche Bilder wie Serienaufnahmen oder erneute Exporte, damit du von jedem nur eines behältst.
+duplicates-progress = { $done } von { $total } geprüft
+duplicates-title = Duplikate
+duplicates-none = Keine nahezu gleichen Bilder gefunden.
+duplicates-found = { $files } Bilder ähneln sich in { $groups } Gruppen. Wähle aus jeder das Bild, das du behalten willst.
+duplicates-keep = Automatisch behalten:
+duplicates-keep-largest = Größtes
+duplicates-keep-newest = Neuestes
+duplicates-group = Gruppe { $number }
+duplicates-threshold = Toleranz
+duplicates-threshold-hover = Wie verschieden Bilder sein dürfen, um noch als Duplikate zu gelten. Höhere Werte finden mehr, aber auch Bilder, die sich nur ähneln.
+duplicates-search-again = Erneut suchen
+duplicates-apply = Übrige ausnehmen
+duplicates-apply-hover = Entfernt das Häkchen bei allen Bildern außer dem behaltenen jeder Gruppe. Du kannst sie in der Liste wieder anhaken.
+status-duplicates-excluded = { $count } Duplikate vom Stapel ausgenommen.
+
+## Combine
+
+combine-header = Kombinieren
+combine-hint = Wähle zwei oder drei Fotos mit ⊞ in der Liste, um sie zu einem Bild zu kombinieren.
+combine-pick = { $name } kombinieren
+combine-swap = { $name } mit dem nächsten Foto tauschen
+combine-remove = { $name } nicht kombinieren
+combine-heights = Nebeneinander
+combine-heights-hover = Skaliert die Fotos auf die gleiche Höhe
+combine-widths = Übereinander
+combine-widths-hover = Skaliert die Fotos auf die gleiche Breite
+combine-gutter = Abstand
+combine-gutter-hover = Platz zwischen den Fotos in Prozent ihrer gemeinsamen Seite, in der Rahmenfarbe
+combine-template = Name
+combine-template-hover = Namensvorlage des kombinierten Bilds. Neben den üblichen Platzhaltern:
+    {name1}, {name2}, {name3}: Namen der Fotos der Reihe nach
+    {names}: alle Namen mit + verbunden
+combine-start = Kombinieren
+combine-start-hover = Schreibt ein Bild aus den gewählten Fotos mit den aktuellen Einstellungen, mit Rahmen um das Ganze
+status-combining = Kombiniere { $count } Fotos nach { $folder } …
+error-combine-count = { $path }: { $count } Fotos lassen sich nicht kombinieren, nur zwei oder drei
+
+## Split
+
+split-header = In Kacheln teilen
+split-tiles = Kacheln
+split-aspect = Form
+split-square = Quadratisch 1:1
+split-portrait = Hochformat 4:5
+split-crop = Zuschneiden
+split-crop-hover = Schneidet ab, was nicht zu den Kacheln nebeneinander passt
+split-pad = Auffüllen
+split-pad-hover = Füllt mit der Rahmenfarbe auf, was dem Foto fehlt
+split-show = Kacheln in der Vorschau zeigen
+split-show-hover = Zeigt das Foto der Vorschau mit eingezeichneten Kacheln statt des Ergebnisses
+split-start = Dieses Foto teilen
+split-start-hover = Schreibt jede Kachel des Fotos der Vorschau mit den aktuellen Einstellungen, von links nummeriert wie _1of3
+split-preview-alt = Das Foto der Vorschau, geteilt in { $count } Kacheln
+status-splitting = Teile in { $count } Kacheln nach { $folder } …
+
+## Webhook
+
+section-webhook = An Webhook melden
+webhook-url = Webhook-URL
+webhook-url-hover = Wenn ein Stapel endet, wird hierhin ein kurzer JSON-Bericht geschickt, etwa an einen Slack- oder Discord-Hook. Kommt er nicht an, landet das nur im Protokoll.
+webhook-invalid = Die URL muss mit http:// oder https:// beginnen
+webhook-export = URL in Einstellungsexporte aufnehmen
+webhook-export-hover = Webhook-URLs wirken oft wie Passwörter, deshalb lassen Exporte sie weg, außer das hier ist an.
+import-webhook = Diese Datei enthält eine Webhook-URL:
+import-webhook-take = Fertige Stapel dorthin melden
+webhook-status = Der Webhook hat mit Status { $status } geantwortet
+
+## Srcset
+
+section-srcset = Kopien für Websites
+srcset-enabled = Kleinere WebP-Kopien jeder Ausgabe schreiben
+srcset-enabled-hover = Jede Ausgabe bekommt daneben auch WebP-Kopien in den Breiten unten. srcset.json im Ausgabeordner listet sie mit der Ausgabe auf, fertig für ein srcset-Attribut. Breiten, die nicht unter der der Ausgabe liegen, fallen weg.
+srcset-widths = Breiten:
+srcset-remove = Breite { $width } entfernen
+srcset-add = Breite hinzufügen
+srcset-quality = WebP-Qualität
+srcset-quality-hover = Gilt nur für die Kopien; die Ausgabe behält ihr eigenes Format und ihre Qualität.
+srcset-names = Namen in URLs:
+srcset-percent = Prozentkodiert
+srcset-percent-hover = Kopien heißen wie ihre Ausgabe; Leerzeichen und andere Zeichen werden in den Pfaden zu %20 und Ähnlichem.
+srcset-slug = Schlicht
+srcset-slug-hover = Kopien bekommen kleingeschriebene Namen mit Bindestrichen, etwa cafe-1-480w.webp.
+srcset-html = Auch ein HTML-Schnipsel pro Ausgabe schreiben
+srcset-html-hover = Ein <img>-Element mit srcset, Breite und Höhe, als .html neben jeder Ausgabe.
+
+## Clipboard
+
+copy-image = Bild kopieren
+copy-path = Ausgabepfad kopieren
+copy-file = Als Datei kopieren
+copy-file-hover = Zum Einfügen in einen Dateimanager oder als Anhang
+copy-large-title = Großes Bild kopieren?
+copy-large-text = Dieses Bild ist { $width } × { $height } px groß, mehr als manche Apps einfügen können. Stattdessen auf { $side } px an der längsten Seite verkleinert kopieren?
+copy-large-scaled = Kleiner kopieren
+copy-large-full = In voller Größe kopieren
+status-copied-image = Ein Bild mit { $width } × { $height } px in die Zwischenablage kopiert
+status-copied-path = { $path } in die Zwischenablage kopiert
+status-copied-file = { $path } als Datei in die Zwischenablage kopiert
+status-copy-failed = Kopieren in die Zwischenablage fehlgeschlagen: { $error }
+
+## Rotate and flip
+
+section-transform = Drehen und spiegeln
+transform-hover = Wird nach der Ausrichtung der Kamera angewendet, vor dem Rand
+transform-rotation = Im Uhrzeigersinn drehen:
+transform-flip-horizontal = Horizontal spiegeln
+transform-flip-vertical = Vertikal spiegeln
+transform-overrides = { $count } einzeln in der Liste gedreht
+transform-reset-all = Wie die übrigen drehen
+transform-rotate-one = { $name } im Uhrzeigersinn drehen
+transform-rotate-cw = Im Uhrzeigersinn drehen
+transform-rotate-ccw = Gegen den Uhrzeigersinn drehen
+transform-use-batch = Wie die übrigen drehen
+transform-rotated = um { $degrees }° gedreht
+transform-flipped-horizontally = horizontal gespiegelt
+transform-flipped-vertically = vertikal gespiegelt
+summary-transform = Ausrichtung
+
+## Trim
+
+trim-enabled = Vorhandenen Rand zuerst abschneiden
+trim-enabled-hover = Schneidet einen Rand ab, den das Foto schon hat, etwa den weißen Rand eines früheren Exports, bevor der neue Rand hinzukommt
+trim-color = Randfarbe:
+trim-color-white = Weiß
+trim-color-black = Schwarz
+trim-color-corner = Wie die Ecke
+trim-tolerance = Toleranz:
+trim-tolerance-hover = Wie weit jeder Kanal von der Randfarbe abweichen darf, um noch als Rand zu zählen, von 0 bis 255
+trim-none = Auf diesem Foto wurde kein Rand gefunden
+trim-trimmed = beschnitten oben { $top }, rechts { $right }, unten { $bottom }, links { $left } px
+summary-trim = Beschnitt
+summary-trim-value = Ränder: { $color }, Toleranz { $tolerance }
+
+## Crop and straighten
+
+crop-open = Zuschneiden und ausrichten…
+crop-open-hover = Richte den Horizont aus oder schneide dieses Foto neu zu, bevor es seinen Rand bekommt
+crop-title = { $name } zuschneiden und ausrichten
+crop-angle = Ausrichten:
+crop-level = Gerade
+crop-lock-aspect = Seitenverhältnis der Fläche halten
+crop-lock-aspect-hover = Die Form der Fläche, wo die Einstellungen eine festlegen, sonst das Verhältnis des Fotos
+crop-whole = Ganzes Foto
+crop-hint = Zieh über das Foto, um es zuzuschneiden
+crop-apply = Übernehmen
+crop-clear = Zuschnitt und Ausrichtung entfernen
+crop-straightened = ausgerichtet { $degrees }°
+crop-cropped = zugeschnitten
+crop-invalid-angle = kann nicht um { $angle }° ausgerichtet werden
+
+## Print
+
+print-selected = { $count } ausgewählte drucken …
+print-selected-hover = Eine Seite pro Bild, in der Reihenfolge des Durchlaufs
+select-all = Alle auswählen
+select-none = Keine auswählen
+print-select = { $name } zum Drucken auswählen
+print-one = Drucken …
+print-title = Drucken
+print-paper = Papier
+print-paper-letter = US Letter
+print-paper-legal = US Legal
+print-portrait = Hochformat
+print-landscape = Querformat
+print-margin = Rand
+print-fill-border = Auf der Randfarbe zentrieren
+print-fill-border-hover = Füllt das Papier innerhalb der Ränder mit der Randfarbe, statt es weiß zu lassen
+print-page-alt = Seite { $page }: { $name }
+print-previous = Vorherige Seite
+print-next = Nächste Seite
+print-page = Seite { $page } von { $count }
+print-start = { $count } Seiten drucken
+status-printed = { $count } Seiten an den Drucker geschickt
+status-print-failed = Drucken fehlgeschlagen: { $error }
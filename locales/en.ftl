@@ -0,0 +1,883 @@
+# English texts of Image Finalizer. See src/i18n.rs for the syntax.
+
+## Errors
+
+error-decode = could not decode { $path }: { $source }
+error-encode = could not encode { $path }: { $source }
+error-invalid-path = { $path } has no usable file name
+skip-output-exists = the output exists already
+preset-invalid-name = invalid preset name `{ $name }`
+preset-read = could not read preset: { $error }
+preset-write = could not write preset: { $error }
+settings-invalid = invalid settings file: { $error }
+settings-not-settings = not an Image Finalizer settings file
+settings-unsupported-version = settings file version { $version } is newer than this app supports ({ $supported })
+clear-same-as-input = the output folder is the input folder
+clear-contains-input = the output folder contains the input folder
+clear-root = { $path } is the root of a drive
+clear-not-a-folder = { $path } is not a folder
+
+## Name templates
+
+template-empty = the name template is empty
+template-empty-name = the name template gives an empty file name
+template-unclosed = a { in the name template is never closed
+template-unknown-placeholder = { $placeholder } is not a placeholder
+template-invalid-sequence = { $placeholder } needs a width and start, like {seq:3} or {seq:3:1}
+template-sequence-overflow = {seq} counts past { $max }; start it lower
+template-invalid-date = %{ $field } is not a date field
+template-illegal-character = file names can't contain { $character }
+template-empty-folder = the folder pattern gives an empty folder name
+template-not-for-run-folder = { $placeholder } can't name a run folder, only {date} and {preset} can
+
+## Pre-flight checks
+
+check-output-is-input = The output directory is the input directory; files named like outputs are not processed
+check-output-inside-input = The output directory is inside the input directory
+check-overwrites-itself = { $path } would be overwritten by its own output; change the suffix, name template, format or output directory
+check-overwrites-input = { $target } would be overwritten by the output of { $path } while it is processed
+check-name-mangled = { $path } is saved as { $name }, because its name isn't allowed on this system
+check-huge-canvas = { $path } would be { $width }×{ $height } px
+check-reduced-depth = { $paths }: { $bits } bits per channel, but { $format } stores 8
+check-taken-renamed = { $paths }: the output name is taken, so a number is added
+check-taken-skipped = { $paths }: the output name is taken, so nothing is written
+check-transparent-border = The border color is partly transparent, but { $format } can't store transparency
+check-missing-texture = The border texture { $path } doesn't exist
+check-collision-kept = { $paths } are all written to { $name }; only one of them is kept
+check-collision-renamed = { $paths } are all written to { $name }; all but one of them get a number
+check-collision-skipped = { $paths } are all written to { $name }; only one of them is written
+check-template = The name template can't be used: { $error }
+check-run-folder = The run folder pattern can't be used: { $error }
+check-date-folders = The date folder pattern can't be used: { $error }
+check-no-input = No input directory selected
+check-no-images = No images found in { $folder }
+check-no-output = No output directory selected
+check-not-a-directory = { $path } is not a directory
+check-not-writable = Cannot write to { $path }: { $error }
+check-border-range = The border of { $value } % is outside 0 to { $max } %
+check-border-pixels-range = The border of { $value } px is wider than { $max } px
+check-frame-range = The frame of { $value } % is outside 0 to { $max } %
+check-resize-range = Resizing to { $value } px is outside 1 to { $max } px
+
+## Settings summaries
+
+summary-border = Border
+summary-border-square = { $percentage }% square canvas
+summary-border-symmetrical = { $percentage }% symmetrical
+summary-border-sides = top { $top }%, right { $right }%, bottom { $bottom }%, left { $left }%
+summary-border-pixels-square = { $width } px square canvas
+summary-border-pixels-symmetrical = { $width } px symmetrical
+summary-border-after-resize = { $border } after the resize
+summary-color = Color
+summary-fill = Fill
+fill-linear-summary = linear gradient to { $color }, { $angle }°
+fill-radial-summary = radial gradient to { $color }
+fill-blur-summary = blurred photo, { $strength } %
+fill-blur-tinted-summary = blurred photo, { $strength } %, { $tint } % tint
+fill-texture-tile-summary = { $name }, tiled
+fill-texture-stretch-summary = { $name }, stretched
+summary-canvas = Canvas
+summary-frame = Frame
+summary-frame-inside = { $width } % { $color } inside the border
+summary-frame-outside = { $width } % { $color } around the border
+summary-corners = Corners
+summary-shadow = Shadow
+summary-shadow-value = { $x }, { $y } px, blur { $blur } px, { $opacity } %
+summary-keyline = Keyline
+summary-resize = Resize
+summary-resize-off = off
+summary-format = Format
+summary-compression = Compression
+summary-quality = Quality
+summary-avif-quality = { $quality } speed { $speed }
+
+## Keyboard shortcuts
+
+shortcuts-title = Keyboard shortcuts
+shortcut-input = Pick the input folder
+shortcut-output = Pick the output folder
+shortcut-start = Start processing
+shortcut-escape = Close the open dialog, or cancel the running batch
+shortcut-browse = Previous / next image in the preview, while no control is focused
+shortcut-scale = Make the UI larger / smaller
+shortcut-scale-reset = Reset the UI scale
+shortcut-undo = Undo a settings change
+shortcut-redo = Redo a settings change
+shortcut-focus = Move to the next / previous control
+shortcut-activate = Press the focused button, or toggle the focused checkbox
+shortcuts-text-fields = Shortcuts are off while a text field is focused.
+
+## Top bar
+
+theme-system = System theme
+theme-light = Light theme
+theme-dark = Dark theme
+theme-switch = { $current }, click for { $next }
+language-system = System ({ $language })
+language-hover = Language
+ui-scale = UI Scale
+undo = ⟲ Undo
+redo = ⟳ Redo
+
+## Folder fields
+
+hint-missing-folder = This folder does not exist
+hint-not-a-folder = This is not a folder
+hint-file-not-folder = This is a file, not a folder
+
+## Status messages
+
+status-scan-failed = Cannot read the input directory: { $error }
+status-cancelling = Cancelling...
+status-preset-loaded = Loaded preset "{ $name }"
+status-preset-saved = Saved preset "{ $name }"
+status-preset-renamed = Renamed preset "{ $from }" to "{ $to }"
+status-preset-deleted = Deleted preset "{ $name }"
+status-preset-error = Preset error: { $error }
+status-open-failed = Could not open { $path }: { $error }
+
+## Presets
+
+preset-select = Select a preset
+preset-name = Name:
+preset-save = Save current as
+preset-rename = Rename selected
+
+## Common buttons
+
+button-reset = Reset
+button-load = Load
+button-delete = Delete
+button-apply = Apply
+button-cancel = Cancel
+button-close = Close
+button-open = Open
+button-reveal = Reveal
+filter-settings = Settings
+filter-images = Images
+
+## Batch progress
+
+counts-ok = { $count } ok
+counts-failed = { $count } failed
+counts-skipped = { $count } skipped
+counts-total = { $count } total
+
+## Results
+
+results-header = Results ({ $count })
+reveal-hover = Show the file in the file manager
+results-open-folder = Open Output Folder
+show-failures = Show Failures
+failures-header = Failed images ({ $count })
+
+## Importing settings
+
+import-title = Import settings
+import-same = The imported settings match the current ones.
+import-changes = Importing will change:
+import-missing-files = These files can't be found where the settings expect them. Choose them again before processing:
+compare-same = Same settings
+status-import-applied = Imported settings applied.
+status-copied = Copied { $color } to the clipboard
+status-load-failed = Error loading original image: { $error }
+status-checking = Checking settings...
+inspector-copy = Click to copy
+
+## Pre-flight dialog
+
+preflight-blocked = Cannot start processing
+preflight-confirm = Start processing?
+preflight-run-folder = Outputs go into { $folder }
+preflight-overlap = Write the outputs into the input folder
+button-start-anyway = Start anyway
+
+## Clearing the output folder
+
+clear-title = Clear output folder
+clear-refused = Nothing is deleted: { $reason }.
+clear-empty = The folder is empty already.
+clear-summary = { $files } files and { $folders } folders, { $size } in total, will be deleted.
+clear-unknown = { $count } of them don't look like anything this app wrote:
+clear-more = … and { $count } more
+clear-confirmation-word = delete
+clear-type = Type "{ $word }" to delete them too:
+clear-progress = Deleting { $done } of { $total }
+clear-deleted = { $count } files deleted.
+clear-failed = { $count } could not be deleted:
+
+## Restoring a session
+
+recovery-title = Restore previous session
+recovery-crashed = Image Finalizer did not exit cleanly last time.
+recovery-remaining = { $remaining } of { $total } images of the running batch were not processed.
+recovery-resume = Restore settings and resume batch
+recovery-restore = Restore settings
+recovery-discard = Discard
+
+## Log panel
+
+log-verbosity = Verbosity:
+log-verbosity-hover = DEBUG also lists every file of a batch as it is done
+log-filter = Filter
+log-copy = Copy All
+log-copy-hover = Copies the lines shown. Select lines to copy only those.
+log-hint = Details are logged in { $folder }
+
+## Batches
+
+status-processing = Processing { $count } images into { $folder }...
+overlap-same = ⚠ This is the input folder. Outputs are written beside the inputs; starting needs confirming.
+overlap-inside = ⚠ This is inside the input folder. Scans with subfolders would pick up the outputs; starting needs confirming.
+
+## Name template help
+
+template-help = {name}: input name without extension
+    {suffix}: the suffix set next to the template
+    {ext}: extension of the output format
+    {format}: output format
+    {width}, {height}: output size in pixels
+    {date}, {date:%Y-%m-%d}: capture date, or the file date
+    {seq}, {seq:4}, {seq:4:0}: position in the batch, with width and start
+    {preset}: preset the image is processed with
+
+## Finished batches
+
+status-preview-failed = Error generating preview: { $error }
+status-exported = Settings exported to { $path }
+status-export-failed = Error exporting settings: { $error }
+status-import-failed = Error importing settings: { $error }
+status-preview-saved = Preview saved to { $path }
+status-preview-save-failed = Error saving preview: { $error }
+status-cleared = Output folder cleared, { $count } files deleted.
+status-clear-failed = Output folder cleared, { $count } files could not be deleted.
+archive-zipped = Zipped into { $name }.
+archive-exists = The archive exists already and was left alone.
+archive-failure = Could not write the archive: { $error }
+archive-failed = The archive could not be written.
+batch-cancelled = Processing cancelled after { $done } of { $total } images: { $counts }.
+batch-failures = Processing finished with failures: { $counts }.
+batch-complete-skipped = Processing complete, { $size } written, { $skipped } images skipped.
+batch-complete = Processing complete, { $size } written.
+batch-ended = Ended { $time }.
+
+## Settings panel
+
+section-presets = Presets
+section-preset-rules = Preset Rules
+section-io = Input/Output
+section-files = Output Files
+section-border = Border
+section-resize = Resize
+section-format = Format
+section-metadata = Metadata
+section-about = About
+rules-show = Show assignments
+rules-run-folder = In the run folder { $folder }:
+settings-export = Export settings…
+settings-import = Import settings…
+input-dir = Input Directory:
+input-open = Open Input Directory
+input-found = Found { $count } images
+input-rescan = Rescan
+output-dir = Output Directory:
+output-clear = Clear…
+output-clear-hover = Lists what the output folder holds, then deletes it
+output-open = Open Output Directory
+names-label = File Names:
+suffix-label = Suffix:
+suffix-hover = Added to the name by {suffix}; may be empty
+folders-subfolders = Include Subfolders
+folders-keep = Keep Folder Structure
+folders-keep-hover = Without it, all outputs go into the output folder itself
+folders-by-date = Date Folders
+folders-date-hover = Folders after the capture date, or the file date; / starts a subfolder. Files without a date go into "undated".
+    %Y, %y: year, %m: month, %d: day, %j: day of the year
+folders-run = Run Folder
+folders-run-hover = A new folder in the output folder for each batch.
+    {date}, {date:%Y-%m-%d_%H%M}: when the batch starts
+    {preset}: the selected preset, or "manual"
+folders-format = Format Folders
+folders-format-hover = Outputs go into a folder per format, like "jpeg"
+folders-size = Size Folders
+folders-size-hover = Outputs go into a folder per longest side, like "2048px"
+existing-label = Existing Files:
+existing-replace = Replace
+existing-number = Add Number
+existing-number-hover = Saves as "name (1)", "name (2)", … instead
+existing-skip = Skip
+originals-label = Copy Originals:
+originals-no = No
+originals-beside = Beside Outputs
+originals-subfolder = In "{ $folder }"
+originals-hover = Copies each input as it is, after its output is written
+manifest-label = Manifest:
+manifest-none = None
+manifest-json-hover = One JSON object per line
+manifest-hover = A record of every file of a batch, written into the output folder
+placeholder-label = Placeholder:
+placeholder-none = None
+placeholder-thumbhash-hover = Keeps transparency and the shape of the image
+placeholder-hover = A tiny blurred stand-in for each written file, recorded in the manifest for web pages to show while the image loads
+archive-create = Create Zip Archive
+archive-name-hover = Name of the archive in the output folder
+archive-store = Store
+archive-store-hover = Fastest; images hardly compress anyway
+name-example = Example: { $name }
+border-percentage = Border Percentage
+border-in-percent = Percent
+border-in-pixels = Pixels
+border-in-pixels-hover = The same border on every photo of a batch, however large it is
+border-pixels = Width per side
+border-after-resize = After the resize
+border-after-resize-hover = Measures the width on the resized output, so it comes out exactly that wide
+border-after-resize-off = Only with the resize on
+border-symmetrical = Symmetrical Border
+canvas-aspect = Canvas
+canvas-aspect-border = From the border
+canvas-aspect-hover = Pads the bordered photo to an exact shape, like 4:5 for a portrait post, keeping it centered
+canvas-aspect-width = Canvas ratio width
+canvas-aspect-height = Canvas ratio height
+frame = Frame
+frame-hover = Stacks a second border of its own width and color on the main one, like a thin frame inside a wide mat
+frame-width = Frame width
+frame-color = Frame color
+frame-inside = Inside the border
+frame-outside = Around the border
+corners = Round corners
+corners-hover = Rounds the corners of the photo, letting the border show through
+corners-radius = Corner radius
+corners-pixels = Pixels
+corners-percent = Percent of the shortest side
+keyline = Keyline
+keyline-hover = Draws a thin line around the photo, as on gallery mats
+keyline-width = Keyline width
+keyline-color = Keyline color
+shadow = Drop shadow
+shadow-hover = Casts a soft shadow under the photo so it seems to float above the border
+shadow-x = Right
+shadow-y = Down
+shadow-blur = Blur
+shadow-opacity = Opacity
+shadow-color = Shadow color
+border-sides = Separate width per side
+border-sides-hover = For mats that are wider at the bottom and the like. Each side is in percent of the longest side of the photo
+border-sides-active = Replaced by the widths per side
+border-top = Top
+border-right = Right
+border-bottom = Bottom
+border-left = Left
+border-color = Color
+border-color-hover = Fill of the border. Transparency is kept by the formats that store it
+border-color-white = White
+border-color-black = Black
+border-color-cream = Cream
+fill = Fill
+fill-solid = Color
+fill-gradient = Gradient
+fill-gradient-hover = Blends the border from its color into a second one
+fill-linear = Linear
+fill-radial = Radial
+fill-gradient-color = To
+fill-angle = Angle
+fill-angle-hover = Direction of the gradient: 0° runs left to right, 90° top to bottom
+fill-blur = Blurred photo
+fill-blur-hover = Fills the border with the photo itself, scaled up and blurred, as in letterboxed stories
+fill-blur-strength = Blur
+fill-blur-strength-hover = Blur radius in percent of the longest side
+fill-blur-tint = Tint
+fill-blur-tint-hover = How far the blur is blended towards the border color
+fill-texture = Texture
+fill-texture-hover = Fills the border with an image file, like a paper or canvas scan, over the border color
+fill-texture-choose = Choose image…
+fill-texture-none = No image chosen
+fill-texture-tile = Tile
+fill-texture-tile-hover = Repeats the image at its own size
+fill-texture-stretch = Stretch
+fill-texture-stretch-hover = Scales the image to the whole canvas
+resize-images = Resize Images
+resize-longest = Longest Dimension:
+resize-algorithm = Resize Algorithm:
+filter-nearest-hint = Fastest, lowest quality.
+filter-triangle-hint = Fast, decent quality.
+filter-catmullrom-hint = Good quality, moderate speed.
+filter-lanczos3-hint = Best quality, slowest.
+format-output = Output Format:
+png-compression = PNG Compression:
+png-fast = Fast
+png-default = Default
+png-best = Best (smallest)
+jpeg-quality = JPEG Quality (1-100):
+avif-speed = AVIF Speed (1-10):
+avif-speed-hover = 1 = Slowest, better compression, 10 = Fastest
+avif-quality = AVIF Quality (1-100):
+format-lossless = Lossless, no options.
+metadata-timestamps = Keep Timestamps
+metadata-timestamps-hover = Outputs and copies get the modification time of their input
+about-storage = Settings and presets are stored in { $folder }
+about-portable = Portable mode: nothing is written to your user profile.
+about-portable-hint = Start with --portable, or put a portable.txt next to the program, to keep everything beside the executable instead.
+
+## Batch panel
+
+start-button = Start Processing
+notify-done = Notify When Done
+notify-done-hover = A system notification when a batch ends
+archive-progress = Zipping { $done } of { $total }
+log-header = Log
+
+## Preview
+
+preview-heading = Preview
+backdrop-label = Backdrop:
+backdrop-white = White
+backdrop-gray = Gray
+backdrop-black = Black
+backdrop-custom = Custom
+compare-repin = Re-pin as A
+compare-pin = Pin for comparison
+preview-save = Save preview…
+preview-copy = Copy preview
+preview-copy-hover = Copies the preview at up to { $side } px on the longest side, to paste into a chat or an email. Outputs in the list copy at full size.
+compare-swap = Swap A/B
+compare-clear = Clear comparison
+info-header = Image Info
+histogram = Histogram
+preview-none = No preview available. Load images first.
+
+## Preset rules
+
+rule-portrait = Portrait
+rule-landscape = Landscape
+rule-square = Square
+rule-longest-below = Longest side below
+rule-longest-at-least = Longest side at least
+rule-description = rule { $number } ({ $condition })
+rule-skipped = Rule { $number } skipped, preset "{ $preset }": { $error }
+applied-manual = manual settings
+applied-manual-unmatched = manual settings (no rule matched)
+applied-manual-size-unknown = manual settings (size unknown)
+applied-rule = { $rule } → preset "{ $preset }"
+rules-enabled = Choose a preset per image by rule
+rules-remove = Remove
+rules-add = Add rule
+
+## Preview guides
+
+guides-label = Guides:
+guides-thirds = Thirds
+guides-center = Center
+guides-safe-margin = Safe margin
+
+## Image info
+
+info-dimensions = Dimensions
+info-file-size = File size
+info-captured = Captured
+info-camera = Camera
+info-lens = Lens
+info-iso = ISO
+info-aperture = Aperture
+info-shutter = Shutter
+info-color-profile = Color profile
+info-orientation = Orientation
+info-icc-profile = Embedded ICC profile
+
+## Notifications
+
+notify-cancelled = Cancelled after { $done } of { $total } images
+notify-done-one = 1 image done
+notify-done-many = { $count } images done
+duration-seconds = { $seconds } s
+duration-minutes = { $minutes } min
+duration-hours = { $hours } h { $minutes } min
+
+## Help
+
+help-hover = Explain, with examples
+help-border-mode = By default the photo is centred on a square canvas, so landscape and portrait shots come out the same shape, with a wider border on the short sides. Symmetrical adds the same width on every side and keeps the photo's own shape.
+help-resize-filter = The filter decides how new pixels are computed from the old ones when an image is scaled. Nearest keeps hard, jagged pixels and Triangle blurs them, while CatmullRom and Lanczos3 stay sharper at the cost of time and faint halos.
+help-png-compression = PNG is lossless, so every level keeps exactly the same pixels. Higher levels only spend more time to make the file smaller, as for this sample:
+help-jpeg-quality = Lower quality throws away detail to make the file smaller, first as smeared colour and blocky patches around edges. Around 80 is hard to tell apart from the original; above 90 the file grows quickly for little gain.
+help-avif-quality = AVIF files are much smaller than JPEGs that look the same, but low quality smooths fine texture away. Speed trades encoding time for file size and barely changes the look; sizes of this sample at speed 6:
+help-border-default = Default
+help-border-symmetrical = Symmetrical
+help-png-best = Best
+help-quality = Quality { $quality }
+
+## Size estimate
+
+estimate-exact = Output size: { $size }
+estimate-range = Estimated output: about { $size } ({ $low } – { $high })
+estimate-hover = An estimate: { $count } sample images were encoded with the current settings and scaled up by input size. Real sizes vary with content.
+estimate-failed = No size estimate: { $error }
+estimate-pending = Estimating output size…
+
+## Empty state and recent folders
+
+welcome-title = Welcome to Image Finalizer
+welcome-text = Open a folder of images to add borders, resize and convert them in one go.
+welcome-open = Open folder…
+welcome-drop = or drop a folder or image onto the window
+recent-heading = Recent folders
+recent-open-hover = Open this folder with its output folder
+recent-missing = This folder no longer exists
+recent-no-output = no output folder yet
+recent-remove = Remove from the list
+
+## Thumbnails of written outputs
+
+thumbnails-header = Written so far
+thumbnail-failed = Failed: { $error }
+    Click to show the original.
+thumbnail-hover = { $path }
+    Click to show it in the file manager.
+
+## Processing order
+
+order-header = Processing order
+order-hint = Drag images to change the order they are processed and numbered in.
+order-reset = Sort by name
+
+## Running batches
+
+settings-locked = Settings are locked while processing.
+status-paused = Paused. Files in progress are finished first.
+status-resumed = Processing resumed.
+button-pause = Pause
+button-resume = Resume
+button-pause-hover = Holds the batch after the files in progress
+minimize-to-tray = Minimize to the tray
+minimize-to-tray-hover = A minimized window hides in the system tray, which shows the progress
+close-title = Processing is running
+close-running = Closing the window would cancel the batch.
+close-tray = Hide in the tray
+close-minimize = Keep running minimized
+close-cancel = Cancel and quit
+close-back = Back
+
+## Tray icon
+
+tray-progress = Processing { $done }/{ $total } ({ $percent }%)
+tray-paused = Paused at { $done }/{ $total } ({ $percent }%)
+tray-show = Show
+tray-pause = Pause
+tray-resume = Resume
+tray-quit = Quit
+
+## Accessibility
+
+order-up = Move { $name } up
+order-down = Move { $name } down
+progress-name = Batch progress
+preview-alt = Preview of { $name }, output { $width } × { $height } px
+preview-previous = Previous image
+preview-next = Next image
+preview-file = Previewed image
+backdrop-color = Backdrop color
+rule-condition-name = Condition of rule { $number }
+rule-pixels-name = Pixels for rule { $number }
+rule-preset-name = Preset of rule { $number }
+guides-margin-name = Safe margin inset
+
+## Simple and Advanced mode
+
+mode-simple = Simple
+mode-advanced = Advanced
+mode-advanced-active = Advanced options active
+mode-advanced-active-hover = Set in Advanced mode and still applied: { $sections }. Click to show them.
+
+## Disk space
+
+check-disk-short = The outputs need about { $needed }, but the drive of the output folder has only { $available } free.
+check-disk-tight = The outputs need about { $needed } and the drive of the output folder has { $available } free, which leaves little room to spare.
+status-disk-full = Disk full at { $path }. Free some space, then resume.
+disk-full-title = Disk full
+disk-full-text = { $path } didn't fit into { $folder }, so the batch is paused. Free some space on that drive, then resume to try it again.
+disk-full-available = Free now: { $size }
+disk-full-resume = Resume
+
+## Large batches
+
+preflight-summary-title = Start { $count } images?
+preflight-summary-files = { $count } images into { $folder }
+preflight-summary-format = { $count } × { $format }, { $bits } bits per channel, { $dimensions } px
+preflight-summary-size = About { $size } in total
+preflight-summary-size-rough = At most about { $size } in total
+preflight-summary-duration = Takes about { $duration }
+preflight-summary-threshold = Ask before batches of this many images or more:
+
+## Uploads
+
+section-upload = Upload
+check-upload = Cannot upload to { $bucket }: { $error }
+upload-no-credentials = No credentials found. Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, or save them in the keyring.
+upload-incomplete = The endpoint or bucket is missing
+upload-keyring = Keyring: { $error }
+upload-read = Could not read the output: { $error }
+upload-status = The bucket answered with HTTP { $status }
+upload-queued = Waiting to upload
+upload-uploading = Uploading…
+upload-retrying = Uploading, try { $attempt }…
+upload-done = Uploaded
+upload-failed = Upload failed
+upload-retry = Retry
+upload-header = Uploaded to { $bucket } ({ $done } of { $total })
+upload-copy-urls = Copy links
+upload-copy-keys = Copy paths
+upload-retry-failed = Retry { $count } failed
+upload-enabled = Upload outputs
+upload-enabled-hover = Each output is uploaded as soon as it is written, to an S3-compatible bucket like Amazon S3, Backblaze B2 or MinIO, or to an SFTP server.
+upload-endpoint = Endpoint
+upload-region = Region
+upload-bucket = Bucket
+upload-prefix = Key prefix
+upload-credentials = Credentials come from { $access } and { $secret }, or from the system keyring:
+upload-access-key = Access key ID
+upload-secret-key = Secret access key
+upload-save-credentials = Save in keyring
+upload-check-credentials = Check
+upload-forget-credentials = Remove from keyring
+upload-credentials-saved = Credentials for { $bucket } saved in the keyring
+upload-credentials-environment = Credentials found in the environment
+upload-credentials-keyring = Credentials found in the keyring
+upload-credentials-forgotten = Credentials for { $bucket } removed from the keyring
+
+## SFTP uploads
+
+upload-target-s3 = S3 bucket
+upload-target-sftp = SFTP server
+upload-cancelled = Cancelled
+upload-cancel = Cancel uploads
+sftp-incomplete = The host or user name is missing
+sftp-unknown-host = The host key { $fingerprint } is not trusted yet. Check the host in the Upload settings and trust its key.
+sftp-host-changed = The host key changed from { $trusted } to { $fingerprint }. Someone may be intercepting the connection; only trust the new key if the server was reinstalled.
+sftp-rejected = The server did not accept the key
+sftp-key = Could not read the key file: { $error }
+sftp-agent = SSH agent: { $error }
+sftp-transfer = Transfer failed: { $error }
+sftp-server = The server answered: { $error }
+sftp-host = Host
+sftp-port = Port
+sftp-username = User name
+sftp-base-path = Folder
+sftp-base-path-hover = The outputs go into this folder on the server, in the same subfolders as in the output folder. Paths without a leading / start in your home folder.
+sftp-retries = Retries per file
+sftp-use-agent = SSH agent
+sftp-agent-hover = Signs in with the keys of the running SSH agent, so passphrases stay with it.
+sftp-key-file = Key file without passphrase
+sftp-choose-key = Choose…
+sftp-check-host = Check host key
+sftp-host-known = A key is trusted for this host
+sftp-connecting = Connecting…
+sftp-host-trusted = The host key { $fingerprint } is trusted
+sftp-host-unknown = The host presented the key { $fingerprint }. Compare it with the fingerprint of the server before trusting it.
+sftp-trust = Trust this key
+sftp-trust-changed = Trust the new key
+sftp-trusted = The host key of { $address } is trusted now
+sftp-known-hosts-error = Could not store the host key: { $error }
+
+## Hook command
+
+section-hook = Command after each output
+check-hook = The hook command is invalid: { $error }
+error-hook = { $path }: { $source }
+hook-empty = The command is empty
+hook-unclosed-quote = A quote in the command is not closed
+hook-spawn = Could not run { $program }: { $error }
+hook-timeout = The command was stopped after { $seconds } s
+hook-exit = The command failed ({ $status }): { $stderr }
+hook-enabled = Run a command for each output
+hook-enabled-hover = Runs right after the output is written, before the next file starts. What the command prints goes to the log.
+hook-command = Command
+hook-placeholders = Placeholders: { $placeholders }. Quotes group words; no shell is involved.
+hook-timeout-label = Stop after
+hook-on-failure = When it fails:
+hook-warn = Only warn
+hook-warn-hover = The file counts as done and the failure goes to the log.
+hook-fail = Mark the file failed
+import-hook-warning = This file contains a command that would run for every output. Only take it over if you trust where the file came from.
+import-hook-take = Take over the command
+status-exported-command = Settings exported to { $path }, including the command run for each output
+
+## Duplicates
+
+order-include = Process { $name }
+order-excluded = { $count } left out of the batch
+order-include-all = Include all
+duplicates-find = Find duplicates…
+duplicates-find-hover = Looks for near-identical images, like burst frames or re-exports, so you can keep one of each.
+duplicates-progress = Checked { $done } of { $total }
+duplicates-title = Duplicates
+duplicates-none = No near-duplicates found.
+duplicates-found = { $files } images look alike in { $groups } groups. Pick the one to keep of each.
+duplicates-keep = Keep automatically:
+duplicates-keep-largest = Largest
+duplicates-keep-newest = Newest
+duplicates-group = Group { $number }
+duplicates-threshold = Tolerance
+duplicates-threshold-hover = How different images may be and still count as duplicates. Higher finds more, but also images that only look similar.
+duplicates-search-again = Search again
+duplicates-apply = Leave out the others
+duplicates-apply-hover = Unchecks every image but the kept one of each group. You can check them again in the list.
+status-duplicates-excluded = { $count } duplicates left out of the batch.
+
+## Combine
+
+combine-header = Combine
+combine-hint = Pick two or three photos with ⊞ in the list to combine them into one image.
+combine-pick = Combine { $name }
+combine-swap = Swap { $name } with the next photo
+combine-remove = Don't combine { $name }
+combine-heights = Side by side
+combine-heights-hover = Scales the photos to the same height
+combine-widths = Stacked
+combine-widths-hover = Scales the photos to the same width
+combine-gutter = Gap
+combine-gutter-hover = Space between the photos in percent of their shared side, in the border color
+combine-template = Name
+combine-template-hover = Name template of the combined image. Besides the usual placeholders:
+    {name1}, {name2}, {name3}: names of the photos in order
+    {names}: all names joined by +
+combine-start = Combine
+combine-start-hover = Writes one image of the picked photos with the current settings, border around the whole set
+status-combining = Combining { $count } photos into { $folder }...
+error-combine-count = { $path }: { $count } photos can't be combined, only two or three
+
+## Split
+
+split-header = Split into tiles
+split-tiles = Tiles
+split-aspect = Shape
+split-square = Square 1:1
+split-portrait = Portrait 4:5
+split-crop = Crop
+split-crop-hover = Cuts off what doesn't fit the tiles side by side
+split-pad = Pad
+split-pad-hover = Fills what the photo lacks with the border color
+split-show = Show tiles in the preview
+split-show-hover = Shows the previewed photo with the tiles drawn on it instead of the result
+split-start = Split this photo
+split-start-hover = Writes each tile of the previewed photo with the current settings, numbered from the left like _1of3
+split-preview-alt = The previewed photo split into { $count } tiles
+status-splitting = Splitting into { $count } tiles in { $folder }...
+
+## Webhook
+
+section-webhook = Report to a webhook
+webhook-url = Webhook URL
+webhook-url-hover = When a batch ends, a short JSON report of it is posted here, such as to a Slack or Discord hook. If it can't be delivered, that only goes to the log.
+webhook-invalid = The URL needs to start with http:// or https://
+webhook-export = Include the URL in settings exports
+webhook-export-hover = Webhook URLs often work as passwords, so exports leave them out unless this is on.
+import-webhook = This file contains a webhook URL:
+import-webhook-take = Report finished batches there
+webhook-status = The webhook answered with status { $status }
+
+## Srcset
+
+section-srcset = Copies for websites
+srcset-enabled = Write smaller WebP copies of every output
+srcset-enabled-hover = Each output also gets WebP copies at the widths below, next to it. srcset.json in the output folder lists them with the output, ready for a srcset attribute. Widths not below the output's own are left out.
+srcset-widths = Widths:
+srcset-remove = Remove the width { $width }
+srcset-add = Add a width
+srcset-quality = WebP quality
+srcset-quality-hover = Applies to the copies only; the output keeps its own format and quality.
+srcset-names = Names in URLs:
+srcset-percent = Percent-encoded
+srcset-percent-hover = Copies are named like their output; spaces and other characters become %20 and the like in the paths.
+srcset-slug = Plain
+srcset-slug-hover = Copies get lower-case names with dashes, like cafe-1-480w.webp.
+srcset-html = Also write an HTML snippet per output
+srcset-html-hover = An <img> element with srcset, width and height, next to each output as .html.
+
+## Clipboard
+
+copy-image = Copy image
+copy-path = Copy output path
+copy-file = Copy as file
+copy-file-hover = To paste into a file manager, or as an attachment
+copy-large-title = Copy a large image?
+copy-large-text = This image is { $width } × { $height } px, more than some apps can paste. Copy it scaled to { $side } px on the longest side instead?
+copy-large-scaled = Copy smaller
+copy-large-full = Copy full size
+status-copied-image = Copied a { $width } × { $height } px image to the clipboard
+status-copied-path = Copied { $path } to the clipboard
+status-copied-file = Copied { $path } to the clipboard as a file
+status-copy-failed = Couldn't copy to the clipboard: { $error }
+
+## Rotate and flip
+
+section-transform = Rotate and flip
+transform-hover = Applied after the camera's own orientation, before the border
+transform-rotation = Rotate clockwise:
+transform-flip-horizontal = Flip horizontally
+transform-flip-vertical = Flip vertically
+transform-overrides = { $count } turned on their own in the list
+transform-reset-all = Turn them like the rest
+transform-rotate-one = Rotate { $name } clockwise
+transform-rotate-cw = Rotate clockwise
+transform-rotate-ccw = Rotate counterclockwise
+transform-use-batch = Turn like the rest
+transform-rotated = rotated { $degrees }°
+transform-flipped-horizontally = flipped horizontally
+transform-flipped-vertically = flipped vertically
+summary-transform = Orientation
+
+## Trim
+
+trim-enabled = Trim existing border first
+trim-enabled-hover = Cuts off a margin the photo already has, like the white border of an earlier export, before the new border is added
+trim-color = Margin color:
+trim-color-white = White
+trim-color-black = Black
+trim-color-corner = Like the corner
+trim-tolerance = Tolerance:
+trim-tolerance-hover = How far each channel may be from the margin color and still count as margin, from 0 to 255
+trim-none = No margin found on this photo
+trim-trimmed = trimmed top { $top }, right { $right }, bottom { $bottom }, left { $left } px
+summary-trim = Trim
+summary-trim-value = { $color } margins, tolerance { $tolerance }
+
+## Crop and straighten
+
+crop-open = Crop and straighten…
+crop-open-hover = Straighten the horizon or recrop this photo before it gets its border
+crop-title = Crop and straighten { $name }
+crop-angle = Straighten:
+crop-level = Level
+crop-lock-aspect = Lock to canvas ratio
+crop-lock-aspect-hover = The shape of the canvas where the settings give it one, the ratio of the photo otherwise
+crop-whole = Whole photo
+crop-hint = Drag over the photo to crop it
+crop-apply = Apply
+crop-clear = Clear crop and straightening
+crop-straightened = straightened { $degrees }°
+crop-cropped = cropped
+crop-invalid-angle = can't straighten by { $angle }°
+
+## Print
+
+print-selected = Print { $count } selected…
+print-selected-hover = One page per image, in batch order
+select-all = Select all
+select-none = Select none
+print-select = Select { $name } for printing
+print-one = Print…
+print-title = Print
+print-paper = Paper
+print-paper-letter = US Letter
+print-paper-legal = US Legal
+print-portrait = Portrait
+print-landscape = Landscape
+print-margin = Margin
+print-fill-border = Center on the border color
+print-fill-border-hover = Fills the paper inside the margins with the border color instead of leaving it white
+print-page-alt = Page { $page }: { $name }
+print-previous = Previous page
+print-next = Next page
+print-page = Page { $page } of { $count }
+print-start = Print { $count } pages
+status-printed = Sent { $count } pages to the printer
+status-print-failed = Printing failed: { $error }
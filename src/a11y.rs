@@ -0,0 +1,33 @@
+//! Names for screen readers on controls whose visible text doesn't say
+//! what they do, like icon buttons and fields beside a separate label.
+//! tests/accessibility.rs fails on controls created without one.
+
+use egui::{Rect, Response, Sense, Ui, Vec2, WidgetInfo, WidgetType};
+
+/// Gives the control of `response` the accessible name `name`, keeping its
+/// role and value.
+pub fn named(response: Response, name: &str) -> Response {
+    response.ctx.accesskit_node_builder(response.id, |node| {
+        node.set_label(name);
+    });
+    response
+}
+
+/// A button showing only `icon`, announced and explained on hover as
+/// `name`.
+pub fn icon_button(ui: &mut Ui, icon: &str, name: &str) -> Response {
+    named(ui.button(icon), name).on_hover_text(name)
+}
+
+/// Like [`icon_button`], without padding.
+pub fn small_icon_button(ui: &mut Ui, icon: &str, name: &str) -> Response {
+    named(ui.small_button(icon), name).on_hover_text(name)
+}
+
+/// Space for a button painted by hand, announced as `name`.
+pub fn allocate_button(ui: &mut Ui, size: Vec2, name: &str) -> (Rect, Response) {
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+    let enabled = ui.is_enabled();
+    response.widget_info(|| WidgetInfo::labeled(WidgetType::Button, enabled, name));
+    (rect, response)
+}
@@ -0,0 +1,119 @@
+//! Zip archives of the outputs of a batch, for delivery as a single file.
+
+use std::{
+    fs,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    naming::{self, NameRules, OverwritePolicy},
+    process::write_output,
+    ProcessError,
+};
+
+/// Name of archives whose name is left empty.
+pub const DEFAULT_ARCHIVE_NAME: &str = "outputs";
+
+/// How files are stored in the archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ZipCompression {
+    /// As they are. Fast, and images hardly compress anyway.
+    #[default]
+    Store,
+    Deflate,
+}
+
+/// Zip archive written after a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveSettings {
+    /// File name without `.zip`.
+    pub name: String,
+    pub compression: ZipCompression,
+}
+
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        ArchiveSettings {
+            name: DEFAULT_ARCHIVE_NAME.to_string(),
+            compression: ZipCompression::default(),
+        }
+    }
+}
+
+/// Streams `outputs` into `output_dir/<name>.zip`, keeping their folders
+/// relative to `output_dir`. A taken archive name is handled by `policy`;
+/// `None` means the archive was skipped. `on_progress` is called with the
+/// number of files stored so far and the total.
+pub fn create_zip(
+    output_dir: &Path,
+    outputs: &[PathBuf],
+    settings: &ArchiveSettings,
+    policy: OverwritePolicy,
+    on_progress: impl FnMut(usize, usize),
+) -> Result<Option<PathBuf>, ProcessError> {
+    let name = match settings.name.trim() {
+        "" => DEFAULT_ARCHIVE_NAME,
+        name => name,
+    };
+    let name = naming::sanitize_file_name(format!("{}.zip", name).as_ref(), NameRules::native());
+    fs::create_dir_all(output_dir).map_err(|e| ProcessError::io(output_dir, e))?;
+    let path = output_dir.join(&name.name);
+    write_output(output_dir, &name.name, policy, |file| {
+        write_zip(file, output_dir, outputs, settings, &path, on_progress)
+    })
+}
+
+/// Streams `outputs` into the zip `file`, which becomes `path`.
+fn write_zip(
+    file: &mut fs::File,
+    output_dir: &Path,
+    outputs: &[PathBuf],
+    settings: &ArchiveSettings,
+    path: &Path,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<(), ProcessError> {
+    let method = match settings.compression {
+        ZipCompression::Store => CompressionMethod::Stored,
+        ZipCompression::Deflate => CompressionMethod::Deflated,
+    };
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    for (done, output) in outputs.iter().enumerate() {
+        let entry = entry_name(output_dir, output);
+        let mut source = fs::File::open(output).map_err(|e| ProcessError::io(output, e))?;
+        let size = source
+            .metadata()
+            .map_err(|e| ProcessError::io(output, e))?
+            .len();
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(size >= u32::MAX as u64);
+        zip.start_file(entry, options)
+            .map_err(|e| ProcessError::io(path, e.into()))?;
+        io::copy(&mut source, &mut zip).map_err(|e| ProcessError::io(path, e))?;
+        on_progress(done + 1, outputs.len());
+    }
+    let mut writer = zip.finish().map_err(|e| ProcessError::io(path, e.into()))?;
+    io::Write::flush(&mut writer).map_err(|e| ProcessError::io(path, e))
+}
+
+/// Path of `output` inside the archive: relative to `output_dir`, with `/`
+/// between folders as zip requires.
+fn entry_name(output_dir: &Path, output: &Path) -> String {
+    let relative = output
+        .strip_prefix(output_dir)
+        .ok()
+        .or_else(|| output.file_name().map(Path::new))
+        .unwrap_or(output);
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
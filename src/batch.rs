@@ -0,0 +1,236 @@
+//! Processing of whole folders.
+
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::{
+    choose_settings,
+    combine::{self, CombineSettings},
+    edit::ImageEdit,
+    naming::{BatchNaming, FileNaming},
+    process::{self, process_combined, process_file_named, process_split},
+    rules::ResolvedRule,
+    split::SplitSettings,
+    ChosenSettings, ProcessError, ProcessInfo, ProcessOutcome,
+};
+
+/// Processes every file of `paths` into `output_dir`, spread over the
+/// available cores. Each file uses the settings `rules` pick for it, or
+/// `manual`. `on_file` is called from the worker threads as each file
+/// finishes, with the settings used and what was written.
+/// Outputs get the default names.
+pub fn run_batch<F>(
+    paths: &[PathBuf],
+    output_dir: &Path,
+    rules: &[ResolvedRule],
+    manual: ProcessInfo,
+    on_file: F,
+) where
+    F: Fn(&Path, &ChosenSettings, Result<ProcessOutcome, ProcessError>) + Sync,
+{
+    run_batch_cancellable(
+        paths,
+        output_dir,
+        rules,
+        manual,
+        &BatchNaming::default(),
+        &AtomicBool::new(false),
+        on_file,
+    );
+}
+
+/// Like `run_batch`, but names the outputs after `naming`, numbering them
+/// in the order of `paths`, and stops starting new files once `cancel` is
+/// set. Files that are already being processed are finished and reported.
+/// Temporary outputs that crashed runs left in `output_dir` are removed
+/// first.
+/// Returns whether the batch was cancelled before every file was started.
+pub fn run_batch_cancellable<F>(
+    paths: &[PathBuf],
+    output_dir: &Path,
+    rules: &[ResolvedRule],
+    manual: ProcessInfo,
+    naming: &BatchNaming,
+    cancel: &AtomicBool,
+    on_file: F,
+) -> bool
+where
+    F: Fn(&Path, &ChosenSettings, Result<ProcessOutcome, ProcessError>) + Sync,
+{
+    run_batch_retrying(
+        paths,
+        output_dir,
+        rules,
+        manual,
+        &HashMap::new(),
+        naming,
+        cancel,
+        |_| false,
+        on_file,
+    )
+}
+
+/// Like `run_batch_cancellable`, but a file that fails for lack of disk
+/// space is first passed to `on_disk_full`, on the thread that processed
+/// it. The file is tried again if that returns true, for example once
+/// space was freed, and reported to `on_file` as failed otherwise. Files
+/// in `edits` are turned and cropped as set there instead of as their
+/// settings say.
+#[allow(clippy::too_many_arguments)]
+pub fn run_batch_retrying<D, F>(
+    paths: &[PathBuf],
+    output_dir: &Path,
+    rules: &[ResolvedRule],
+    manual: ProcessInfo,
+    edits: &HashMap<PathBuf, ImageEdit>,
+    naming: &BatchNaming,
+    cancel: &AtomicBool,
+    on_disk_full: D,
+    on_file: F,
+) -> bool
+where
+    D: Fn(&Path) -> bool + Sync,
+    F: Fn(&Path, &ChosenSettings, Result<ProcessOutcome, ProcessError>) + Sync,
+{
+    process::forget_swept_folders();
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(paths.len());
+    let next = AtomicUsize::new(0);
+    let layout = naming
+        .parse_template()
+        .and_then(|template| Ok((template, naming.parse_date_folders()?)));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while !cancel.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let chosen =
+                        choose_settings(path, rules, manual.clone()).with_edit(edits.get(path));
+                    let process = || match &layout {
+                        Ok((template, date_folders)) => {
+                            let folder = naming.output_folder(path, date_folders.as_ref());
+                            let naming = FileNaming::for_batch(
+                                naming,
+                                template,
+                                index,
+                                chosen.preset.as_deref(),
+                                &folder,
+                            );
+                            process_file_named(path, chosen.info.clone(), output_dir, &naming)
+                        }
+                        Err(source) => Err(ProcessError::Template {
+                            path: path.clone(),
+                            source: source.clone(),
+                        }),
+                    };
+                    let result = loop {
+                        match process() {
+                            Err(e) if e.is_disk_full() && on_disk_full(path) => continue,
+                            result => break result,
+                        }
+                    };
+                    on_file(path, &chosen, result);
+                }
+            });
+        }
+    });
+
+    next.load(Ordering::Relaxed) < paths.len()
+}
+
+/// Combines `paths` into one output in `output_dir` after `combine`, with
+/// the `manual` settings, as rules pick settings per photo. The output is
+/// named after the template of `combine` and the rest of `naming`, with
+/// the first path standing in for the others where only one can.
+pub fn run_combined(
+    paths: &[PathBuf],
+    output_dir: &Path,
+    manual: ProcessInfo,
+    combine: &CombineSettings,
+    naming: &BatchNaming,
+) -> (ChosenSettings, Result<ProcessOutcome, ProcessError>) {
+    let first = paths.first().cloned().unwrap_or_default();
+    let chosen = choose_settings(&first, &[], manual.clone());
+    if !(combine::MIN_IMAGES..=combine::MAX_IMAGES).contains(&paths.len()) {
+        let count = paths.len();
+        return (
+            chosen,
+            Err(ProcessError::CombineCount { path: first, count }),
+        );
+    }
+    process::forget_swept_folders();
+    let naming = BatchNaming {
+        template: combine.template.clone(),
+        ..naming.clone()
+    };
+    let layout = naming
+        .parse_template()
+        .and_then(|template| Ok((template, naming.parse_date_folders()?)));
+    let result = match layout {
+        Ok((template, date_folders)) => {
+            let folder = naming.output_folder(&first, date_folders.as_ref());
+            let file_naming = FileNaming::for_batch(&naming, &template, 0, None, &folder);
+            process_combined(paths, manual, combine, output_dir, &file_naming)
+        }
+        Err(source) => Err(ProcessError::Template {
+            path: first,
+            source,
+        }),
+    };
+    (chosen, result)
+}
+
+/// Splits every file of `paths` into tiles in `output_dir` after `split`,
+/// with the `manual` settings, one after another as the tiles of a file
+/// already keep the cores busy. Files in `edits` are turned and cropped
+/// as set there. Outputs are named after `naming`, and `on_file` is called as
+/// each file finishes. Stops starting new files once `cancel` is set, and
+/// returns whether it was.
+#[allow(clippy::too_many_arguments)]
+pub fn run_split<F>(
+    paths: &[PathBuf],
+    output_dir: &Path,
+    manual: ProcessInfo,
+    edits: &HashMap<PathBuf, ImageEdit>,
+    split: &SplitSettings,
+    naming: &BatchNaming,
+    cancel: &AtomicBool,
+    on_file: F,
+) -> bool
+where
+    F: Fn(&Path, &ChosenSettings, Result<ProcessOutcome, ProcessError>),
+{
+    process::forget_swept_folders();
+    let layout = naming
+        .parse_template()
+        .and_then(|template| Ok((template, naming.parse_date_folders()?)));
+    for (index, path) in paths.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return true;
+        }
+        let chosen = choose_settings(path, &[], manual.clone()).with_edit(edits.get(path));
+        let result = match &layout {
+            Ok((template, date_folders)) => {
+                let folder = naming.output_folder(path, date_folders.as_ref());
+                let file_naming = FileNaming::for_batch(naming, template, index, None, &folder);
+                process_split(path, chosen.info.clone(), split, output_dir, &file_naming)
+            }
+            Err(source) => Err(ProcessError::Template {
+                path: path.clone(),
+                source: source.clone(),
+            }),
+        };
+        on_file(path, &chosen, result);
+    }
+    false
+}
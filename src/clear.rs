@@ -0,0 +1,189 @@
+//! Emptying the output folder before a fresh export, without touching
+//! anything outside of it.
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    manifest::{self, MANIFEST_PREFIX},
+    naming,
+    process::is_temp_name,
+    t,
+    validation::canonical,
+    ProcessError,
+};
+
+/// Why the output folder can't be cleared.
+#[derive(Debug, thiserror::Error)]
+pub enum ClearError {
+    #[error("{}", t!("clear-same-as-input"))]
+    SameAsInput,
+    #[error("{}", t!("clear-contains-input"))]
+    ContainsInput,
+    #[error("{}", t!("clear-root", path = .0.display()))]
+    Root(PathBuf),
+    #[error("{}", t!("clear-not-a-folder", path = .0.display()))]
+    NotAFolder(PathBuf),
+    #[error(transparent)]
+    Io(#[from] ProcessError),
+}
+
+/// A file or link that clearing deletes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearEntry {
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Whether it looks like something this app wrote: an output with the
+    /// suffix, a file listed in a manifest, a manifest or the archive.
+    pub known: bool,
+}
+
+/// Everything in an output folder, as found by `plan_clear`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClearPlan {
+    pub entries: Vec<ClearEntry>,
+    /// Subfolders, removed once they are empty, deepest first.
+    pub folders: Vec<PathBuf>,
+}
+
+impl ClearPlan {
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.bytes).sum()
+    }
+
+    /// Entries that don't look like anything this app wrote.
+    pub fn unknown(&self) -> impl Iterator<Item = &ClearEntry> {
+        self.entries.iter().filter(|entry| !entry.known)
+    }
+}
+
+/// Lists what clearing `output_dir` would delete. Outputs are recognised by
+/// `suffix`, the manifests in the folder and the archive `archive_name`.
+/// Refuses folders that are or hold `input_dir`, and roots. Links are
+/// listed themselves and never followed.
+pub fn plan_clear(
+    output_dir: &Path,
+    input_dir: &Path,
+    suffix: &str,
+    archive_name: &str,
+) -> Result<ClearPlan, ClearError> {
+    let output = canonical(output_dir);
+    if output.parent().is_none() {
+        return Err(ClearError::Root(output));
+    }
+    if !input_dir.as_os_str().is_empty() {
+        let input = canonical(input_dir);
+        if input == output {
+            return Err(ClearError::SameAsInput);
+        }
+        if input.starts_with(&output) {
+            return Err(ClearError::ContainsInput);
+        }
+    }
+    if !output_dir.exists() {
+        return Ok(ClearPlan::default());
+    }
+    if !output_dir.is_dir() {
+        return Err(ClearError::NotAFolder(output_dir.to_path_buf()));
+    }
+
+    let mut files = vec![];
+    let mut plan = ClearPlan::default();
+    let mut folders = vec![output_dir.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let entries = fs::read_dir(&folder).map_err(|e| ProcessError::io(&folder, e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| ProcessError::io(&folder, e))?;
+            let path = entry.path();
+            // Not followed: a link is a file of its own here.
+            let kind = entry.file_type().map_err(|e| ProcessError::io(&path, e))?;
+            if kind.is_dir() {
+                folders.push(path.clone());
+                plan.folders.push(path);
+            } else {
+                let bytes = if kind.is_file() {
+                    entry.metadata().map_or(0, |metadata| metadata.len())
+                } else {
+                    0
+                };
+                files.push((path, bytes, kind.is_symlink()));
+            }
+        }
+    }
+
+    let archive = format!("{}.zip", archive_name.trim());
+    let mut listed = HashSet::new();
+    for (path, _, link) in &files {
+        if !link && is_manifest(path) {
+            match manifest::listed_outputs(path) {
+                Ok(outputs) => listed.extend(outputs.iter().map(|output| canonical(output))),
+                Err(e) => tracing::warn!("could not read {}: {}", path.display(), e),
+            }
+        }
+    }
+    plan.entries = files
+        .into_iter()
+        .map(|(path, bytes, link)| {
+            let name = path.file_name().unwrap_or_default();
+            let known = !link
+                && (naming::has_output_suffix(&path, suffix)
+                    || is_manifest(&path)
+                    || is_temp_name(name)
+                    || name == archive.as_str()
+                    || listed.contains(&canonical(&path)));
+            ClearEntry { path, bytes, known }
+        })
+        .collect();
+    // Folders were found parents first.
+    plan.folders.reverse();
+    Ok(plan)
+}
+
+fn is_manifest(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with(MANIFEST_PREFIX) && (name.ends_with(".csv") || name.ends_with(".jsonl"))
+}
+
+/// What `clear` did.
+#[derive(Debug, Default)]
+pub struct ClearOutcome {
+    /// Files and links deleted.
+    pub deleted: usize,
+    /// What could not be deleted.
+    pub errors: Vec<ProcessError>,
+}
+
+/// Deletes what `plan` lists, and nothing added since, calling
+/// `on_progress` with the number of entries done so far and the total.
+/// Folders that aren't empty afterwards are kept.
+pub fn clear(plan: &ClearPlan, mut on_progress: impl FnMut(usize, usize)) -> ClearOutcome {
+    let mut outcome = ClearOutcome::default();
+    for (done, entry) in plan.entries.iter().enumerate() {
+        match remove_entry(&entry.path) {
+            Ok(()) => outcome.deleted += 1,
+            Err(e) => outcome.errors.push(ProcessError::io(&entry.path, e)),
+        }
+        on_progress(done + 1, plan.entries.len());
+    }
+    for folder in &plan.folders {
+        match fs::remove_dir(folder) {
+            Ok(()) => {}
+            // Holds a file that could not be deleted, or a new one.
+            Err(_) if fs::read_dir(folder).is_ok_and(|mut entries| entries.next().is_some()) => {}
+            Err(e) => outcome.errors.push(ProcessError::io(folder, e)),
+        }
+    }
+    outcome
+}
+
+/// Removes the file or link at `path`. Windows removes links to folders as
+/// folders.
+fn remove_entry(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if cfg!(windows) && path.is_symlink() => fs::remove_dir(path).map_err(|_| e),
+        result => result,
+    }
+}
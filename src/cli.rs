@@ -0,0 +1,594 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use image::imageops::FilterType;
+
+use image_finalizer::{
+    archive::{ArchiveSettings, ZipCompression, DEFAULT_ARCHIVE_NAME},
+    corners::CornerRadius,
+    fill::{BlurFill, BorderFill, Gradient, GradientShape, TextureFill, TextureMode},
+    format::OutputFormat,
+    hook::{HookFailure, HookSettings},
+    manifest::ManifestFormat,
+    naming::{OriginalsPlacement, OverwritePolicy, DEFAULT_DATE_FOLDERS, DEFAULT_RUN_FOLDER},
+    placeholder::PlaceholderKind,
+    serialization::{filter_type, hex_color},
+    transform::Rotation,
+    trim::MarginColor,
+    BorderSides, CanvasAspect, FramePlacement, PixelBorder,
+};
+
+use crate::config::Config;
+
+/// Prepare photos for online distribution by adding borders, resizing, and
+/// re-encoding them.
+#[derive(Debug, Parser)]
+#[command(name = "image-finalizer", version)]
+pub struct Cli {
+    /// Config file to load instead of the default location
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Keep settings and presets in a folder next to the executable. Also
+    /// enabled by a portable.txt file there.
+    #[arg(long)]
+    pub portable: bool,
+
+    /// Print the effective configuration and exit
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Folder containing the images to process
+    #[arg(long, value_name = "DIR")]
+    pub input: Option<PathBuf>,
+
+    /// Folder the processed images are written to
+    #[arg(long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+
+    /// Output file name template, "{name}{suffix}.{ext}" by default
+    ///
+    /// Placeholders: {name}, {suffix}, {ext}, {format}, {width}, {height},
+    /// {date[:PATTERN]}, {seq[:WIDTH[:START]]} and {preset}.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub name_template: Option<String>,
+
+    /// Text of {suffix} in the name template, "_bordered" by default; may
+    /// be empty
+    #[arg(long, value_name = "TEXT", allow_hyphen_values = true)]
+    pub suffix: Option<String>,
+
+    /// Also process the images in subfolders of the input folder
+    #[arg(long)]
+    pub recursive: bool,
+
+    /// Recreate the input's subfolders in the output folder instead of
+    /// writing every output into it
+    #[arg(long, requires = "recursive")]
+    pub preserve_folders: bool,
+
+    /// Sort the outputs into folders after their capture date, "%Y/%Y-%m-%d"
+    /// without a pattern
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_DATE_FOLDERS
+    )]
+    pub date_folders: Option<String>,
+
+    /// Write the batch into a new folder in the output folder, named after
+    /// PATTERN, "{date:%Y-%m-%d_%H%M}_{preset}" without one
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_RUN_FOLDER
+    )]
+    pub run_folder: Option<String>,
+
+    /// Record every file of the batch in a manifest in the output folder
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub manifest: Option<ManifestFormat>,
+
+    /// Add a BlurHash or ThumbHash of each output to the manifest, if one
+    /// is recorded
+    #[arg(long, value_enum, value_name = "KIND")]
+    pub placeholder: Option<PlaceholderKind>,
+
+    /// Zip the outputs into NAME.zip in the output folder after the batch,
+    /// "outputs.zip" without a name
+    #[arg(
+        long,
+        value_name = "NAME",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_ARCHIVE_NAME
+    )]
+    pub zip: Option<String>,
+
+    /// How files are stored in the zip archive
+    #[arg(long, value_enum, requires = "zip")]
+    pub zip_compression: Option<ZipCompression>,
+
+    /// Run COMMAND for every output, with {output}, {source}, {format},
+    /// {width} and {height} filled in
+    #[arg(long, value_name = "COMMAND")]
+    pub hook: Option<String>,
+
+    /// Whether a failing hook only warns or fails its file
+    #[arg(long, value_enum, requires = "hook")]
+    pub hook_failure: Option<HookFailure>,
+
+    /// What to do when an output's name is taken: replace the file, add a
+    /// number to the new name, or skip the input
+    #[arg(long, value_enum)]
+    pub overwrite: Option<OverwritePolicy>,
+
+    /// Copy each input as it is beside its output, or into an "originals"
+    /// subfolder; beside without a value
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHERE",
+        num_args = 0..=1,
+        default_missing_value = "beside"
+    )]
+    pub copy_originals: Option<OriginalsPlacement>,
+
+    /// Give outputs and copied originals the modification time of their
+    /// input
+    #[arg(long)]
+    pub preserve_timestamps: bool,
+
+    /// Sort the outputs into a folder per format, like "jpeg"
+    #[arg(long)]
+    pub format_folders: bool,
+
+    /// Sort the outputs into a folder per longest side, like "2048px"
+    #[arg(long)]
+    pub size_folders: bool,
+
+    /// Start processing as soon as the window opens
+    #[arg(long)]
+    pub auto_start: bool,
+
+    /// Process the input folder without opening a window, then exit.
+    ///
+    /// Exits with 1 when some images failed, 3 when the pre-flight checks
+    /// found errors and 4 when a large batch was not confirmed.
+    #[arg(long, conflicts_with = "auto_start")]
+    pub headless: bool,
+
+    /// Start headless batches of 200 images or more without asking first.
+    ///
+    /// Without it, a headless run from a terminal summarizes such a batch
+    /// and waits for a "y". Runs without a terminal never ask.
+    #[arg(long, short = 'y', requires = "headless")]
+    pub yes: bool,
+
+    /// Trust the host key of the SFTP server in the config on first use.
+    ///
+    /// Without it, a headless run refuses a server whose key was not
+    /// trusted before and prints its fingerprint. A key that differs from
+    /// the trusted one is refused either way.
+    #[arg(long, requires = "headless")]
+    pub trust_host_key: bool,
+
+    /// Turn every photo clockwise by 90, 180 or 270 degrees, after its
+    /// EXIF orientation
+    #[arg(long, value_name = "DEGREES", value_parser = parse_rotation)]
+    pub rotate: Option<Rotation>,
+
+    /// Mirror every photo left to right, after turning it
+    #[arg(long)]
+    pub flip_horizontal: bool,
+
+    /// Mirror every photo top to bottom, after turning it
+    #[arg(long)]
+    pub flip_vertical: bool,
+
+    /// Cut off the margin a photo already has before adding the border
+    #[arg(long)]
+    pub trim: bool,
+
+    /// Color a margin must have to be trimmed; corner takes that of the
+    /// top left pixel
+    #[arg(long, value_enum, value_name = "COLOR")]
+    pub trim_color: Option<MarginColor>,
+
+    /// How far each channel may be from the margin color (0-255)
+    #[arg(long, value_name = "LEVELS")]
+    pub trim_tolerance: Option<u8>,
+
+    /// Border size in percent of the longest side
+    #[arg(long, value_name = "PERCENT")]
+    pub border: Option<f32>,
+
+    /// Add the same border width on every side instead of a square canvas
+    #[arg(long)]
+    pub symmetrical: Option<bool>,
+
+    /// Border width in pixels on every side instead of --border
+    #[arg(long, value_name = "PIXELS")]
+    pub border_pixels: Option<u32>,
+
+    /// Measure --border-pixels on the resized output
+    #[arg(long, requires = "border_pixels")]
+    pub border_after_resize: bool,
+
+    /// Border width of every side in percent of the longest side, as
+    /// TOP,RIGHT,BOTTOM,LEFT; replaces --border and --symmetrical
+    #[arg(long, value_name = "T,R,B,L", value_parser = parse_sides)]
+    pub border_sides: Option<BorderSides>,
+
+    /// Pad every canvas to this aspect ratio, like 4:5, instead of a square
+    #[arg(long, value_name = "W:H")]
+    pub aspect: Option<CanvasAspect>,
+
+    /// Round the corners of the photo, in pixels like 24 or in percent of
+    /// the shortest side like 5%
+    #[arg(long, value_name = "RADIUS")]
+    pub corner_radius: Option<CornerRadius>,
+
+    /// Add a second border of this width per side, in percent of the
+    /// longest side of what it goes around
+    #[arg(long, value_name = "PERCENT")]
+    pub frame: Option<f32>,
+
+    /// Frame color as #RRGGBB or #RRGGBBAA
+    #[arg(long, value_name = "HEX", value_parser = parse_color, requires = "frame")]
+    pub frame_color: Option<image::Rgba<u8>>,
+
+    /// Whether the frame goes between photo and border or around the border
+    #[arg(long, value_enum, requires = "frame")]
+    pub frame_placement: Option<FramePlacement>,
+
+    /// Cast a shadow under the photo
+    #[arg(long)]
+    pub shadow: bool,
+
+    /// How far the shadow falls in pixels, as X,Y
+    #[arg(long, value_name = "X,Y", value_parser = parse_offset, requires = "shadow")]
+    pub shadow_offset: Option<(f32, f32)>,
+
+    /// Blur radius of the shadow in pixels
+    #[arg(long, value_name = "PIXELS", requires = "shadow")]
+    pub shadow_blur: Option<f32>,
+
+    /// Opacity of the shadow in percent
+    #[arg(long, value_name = "PERCENT", requires = "shadow")]
+    pub shadow_opacity: Option<f32>,
+
+    /// Shadow color as #RRGGBB or #RRGGBBAA
+    #[arg(long, value_name = "HEX", value_parser = parse_color, requires = "shadow")]
+    pub shadow_color: Option<image::Rgba<u8>>,
+
+    /// Draw a line this many pixels wide around the photo
+    #[arg(long, value_name = "PIXELS")]
+    pub keyline: Option<f32>,
+
+    /// Color of the keyline as #RRGGBB or #RRGGBBAA
+    #[arg(long, value_name = "HEX", value_parser = parse_color, requires = "keyline")]
+    pub keyline_color: Option<image::Rgba<u8>>,
+
+    /// Border color as #RRGGBB or #RRGGBBAA
+    #[arg(long, value_name = "HEX", value_parser = parse_color)]
+    pub border_color: Option<image::Rgba<u8>>,
+
+    /// Fill the border with a gradient from the border color
+    #[arg(long, value_enum, value_name = "SHAPE", conflicts_with = "blur_fill")]
+    pub gradient: Option<GradientShape>,
+
+    /// Color the gradient ends in, as #RRGGBB or #RRGGBBAA
+    #[arg(long, value_name = "HEX", value_parser = parse_color, requires = "gradient")]
+    pub gradient_color: Option<image::Rgba<u8>>,
+
+    /// Direction of a linear gradient in degrees; 0 runs left to right,
+    /// 90 top to bottom
+    #[arg(long, value_name = "DEGREES", requires = "gradient")]
+    pub gradient_angle: Option<f32>,
+
+    /// Fill the border with a blurred copy of the photo
+    #[arg(long)]
+    pub blur_fill: bool,
+
+    /// Blur radius of --blur-fill in percent of the longest side
+    #[arg(long, value_name = "PERCENT", requires = "blur_fill")]
+    pub blur_strength: Option<f32>,
+
+    /// How far --blur-fill is tinted towards the border color, in percent
+    #[arg(long, value_name = "PERCENT", requires = "blur_fill")]
+    pub blur_tint: Option<f32>,
+
+    /// Fill the border with an image file, like a paper texture, over the
+    /// border color
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["gradient", "blur_fill"])]
+    pub texture: Option<PathBuf>,
+
+    /// Whether --texture is tiled at its own size or stretched to the
+    /// canvas
+    #[arg(long, value_enum, value_name = "MODE", requires = "texture")]
+    pub texture_mode: Option<TextureMode>,
+
+    /// Resize so the longest side has this many pixels
+    #[arg(long, value_name = "PIXELS")]
+    pub resize: Option<u32>,
+
+    /// Resampling filter used when resizing
+    #[arg(long, value_parser = parse_filter)]
+    pub filter: Option<FilterType>,
+
+    /// Output format
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// JPEG or AVIF quality (1-100), depending on the format
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub quality: Option<u8>,
+
+    /// AVIF encoder speed (1-10)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=10))]
+    pub avif_speed: Option<u8>,
+}
+
+impl Cli {
+    /// Applies every flag that was given on top of `config`.
+    pub fn apply_overrides(&self, config: &mut Config) {
+        let process = &mut config.process;
+
+        if let Some(input) = &self.input {
+            config.input = Some(input.clone());
+        }
+        if let Some(output) = &self.output {
+            config.output = Some(output.clone());
+        }
+        if let Some(template) = &self.name_template {
+            config.name_template = Some(template.clone());
+        }
+        if let Some(suffix) = &self.suffix {
+            config.suffix = Some(suffix.clone());
+        }
+        if self.recursive {
+            config.recursive = Some(true);
+        }
+        if self.preserve_folders {
+            config.preserve_folders = Some(true);
+        }
+        if let Some(pattern) = &self.date_folders {
+            config.date_folders = Some(pattern.clone());
+        }
+        if let Some(pattern) = &self.run_folder {
+            config.run_folder = Some(pattern.clone());
+        }
+        if let Some(manifest) = self.manifest {
+            config.manifest = Some(manifest);
+        }
+        if let Some(placeholder) = self.placeholder {
+            config.placeholder = Some(placeholder);
+        }
+        if let Some(name) = &self.zip {
+            let archive = config.archive.get_or_insert_with(ArchiveSettings::default);
+            archive.name = name.clone();
+        }
+        if let Some(compression) = self.zip_compression {
+            let archive = config.archive.get_or_insert_with(ArchiveSettings::default);
+            archive.compression = compression;
+        }
+        if let Some(command) = &self.hook {
+            let hook = config.hook.get_or_insert_with(HookSettings::default);
+            hook.command = command.clone();
+        }
+        if let Some(failure) = self.hook_failure {
+            let hook = config.hook.get_or_insert_with(HookSettings::default);
+            hook.on_failure = failure;
+        }
+        if let Some(overwrite) = self.overwrite {
+            config.overwrite = Some(overwrite);
+        }
+        if let Some(placement) = self.copy_originals {
+            config.originals = Some(placement);
+        }
+        if self.preserve_timestamps {
+            config.preserve_timestamps = Some(true);
+        }
+        if self.format_folders {
+            config
+                .variant_folders
+                .get_or_insert_with(Default::default)
+                .by_format = true;
+        }
+        if self.size_folders {
+            config
+                .variant_folders
+                .get_or_insert_with(Default::default)
+                .by_size = true;
+        }
+        if self.trim {
+            process.trim.enabled = true;
+        }
+        if let Some(color) = self.trim_color {
+            process.trim.color = color;
+        }
+        if let Some(tolerance) = self.trim_tolerance {
+            process.trim.tolerance = tolerance;
+        }
+        if let Some(rotation) = self.rotate {
+            process.transform.rotation = rotation;
+        }
+        if self.flip_horizontal {
+            process.transform.flip_horizontal = true;
+        }
+        if self.flip_vertical {
+            process.transform.flip_vertical = true;
+        }
+        if let Some(border) = self.border {
+            process.border_percentage = border;
+        }
+        if let Some(symmetrical) = self.symmetrical {
+            process.symmetrical_border = symmetrical;
+        }
+        if let Some(width) = self.border_pixels {
+            process.border_pixels = Some(PixelBorder {
+                width,
+                after_resize: self.border_after_resize,
+            });
+        }
+        if let Some(sides) = self.border_sides {
+            process.border_sides = Some(sides);
+        }
+        if let Some(aspect) = self.aspect {
+            process.canvas_aspect = Some(aspect);
+        }
+        if let Some(corners) = self.corner_radius {
+            process.corner_radius = Some(corners);
+        }
+        if let Some(width) = self.frame {
+            let mut frame = process.frame.unwrap_or_default();
+            frame.width = width;
+            if let Some(color) = self.frame_color {
+                frame.color = color;
+            }
+            if let Some(placement) = self.frame_placement {
+                frame.placement = placement;
+            }
+            process.frame = Some(frame);
+        }
+        if self.shadow {
+            let mut shadow = process.drop_shadow.unwrap_or_default();
+            if let Some((x, y)) = self.shadow_offset {
+                shadow.offset_x = x;
+                shadow.offset_y = y;
+            }
+            if let Some(blur) = self.shadow_blur {
+                shadow.blur = blur;
+            }
+            if let Some(opacity) = self.shadow_opacity {
+                shadow.opacity = opacity;
+            }
+            if let Some(color) = self.shadow_color {
+                shadow.color = color;
+            }
+            process.drop_shadow = Some(shadow);
+        }
+        if let Some(width) = self.keyline {
+            let mut keyline = process.keyline.unwrap_or_default();
+            keyline.width = width;
+            if let Some(color) = self.keyline_color {
+                keyline.color = color;
+            }
+            process.keyline = Some(keyline);
+        }
+        if let Some(color) = self.border_color {
+            process.border_color = color;
+        }
+        if let Some(shape) = self.gradient {
+            let mut gradient = match process.border_fill {
+                BorderFill::Gradient(gradient) => gradient,
+                _ => Gradient::default(),
+            };
+            gradient.shape = shape;
+            if let Some(color) = self.gradient_color {
+                gradient.color = color;
+            }
+            if let Some(angle) = self.gradient_angle {
+                gradient.angle = angle;
+            }
+            process.border_fill = BorderFill::Gradient(gradient);
+        }
+        if self.blur_fill {
+            let mut blur = match process.border_fill {
+                BorderFill::Blur(blur) => blur,
+                _ => BlurFill::default(),
+            };
+            if let Some(strength) = self.blur_strength {
+                blur.strength = strength;
+            }
+            if let Some(tint) = self.blur_tint {
+                blur.tint = tint;
+            }
+            process.border_fill = BorderFill::Blur(blur);
+        }
+        if let Some(path) = &self.texture {
+            let mut texture = match &process.border_fill {
+                BorderFill::Texture(texture) => texture.clone(),
+                _ => TextureFill::default(),
+            };
+            texture.path = path.clone();
+            if let Some(mode) = self.texture_mode {
+                texture.mode = mode;
+            }
+            process.border_fill = BorderFill::Texture(texture);
+        }
+        if let Some(resize) = self.resize {
+            process.resize_images = true;
+            process.resize_longest_dimension = resize;
+        }
+        if let Some(filter) = self.filter {
+            process.resize_filter = filter;
+        }
+        if let Some(format) = self.format {
+            process.output_format = format;
+        }
+        if let Some(quality) = self.quality {
+            match process.output_format {
+                OutputFormat::Avif => process.format_settings.avif.quality = quality,
+                _ => process.format_settings.jpeg.quality = quality,
+            }
+        }
+        if let Some(speed) = self.avif_speed {
+            process.format_settings.avif.speed = speed;
+        }
+    }
+}
+
+fn parse_rotation(text: &str) -> Result<Rotation, String> {
+    let degrees: u16 = text
+        .parse()
+        .map_err(|_| format!("`{}` is not a number of degrees", text))?;
+    Rotation::try_from(degrees)
+}
+
+fn parse_color(text: &str) -> Result<image::Rgba<u8>, String> {
+    hex_color::parse(text).ok_or_else(|| format!("`{}` is not a #RRGGBB color", text))
+}
+
+fn parse_sides(text: &str) -> Result<BorderSides, String> {
+    let sides: Vec<f32> = text
+        .split(',')
+        .map(|side| side.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("`{}` is not a list of percentages", text))?;
+    match sides[..] {
+        [top, right, bottom, left] => Ok(BorderSides {
+            top,
+            right,
+            bottom,
+            left,
+        }),
+        _ => Err(format!(
+            "`{}` needs four percentages: top, right, bottom and left",
+            text
+        )),
+    }
+}
+
+fn parse_offset(text: &str) -> Result<(f32, f32), String> {
+    let offset: Vec<f32> = text
+        .split(',')
+        .map(|part| part.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("`{}` is not a pair of pixel offsets", text))?;
+    match offset[..] {
+        [x, y] => Ok((x, y)),
+        _ => Err(format!("`{}` needs two offsets: x and y", text)),
+    }
+}
+
+fn parse_filter(text: &str) -> Result<FilterType, String> {
+    filter_type::from_name(text).ok_or_else(|| {
+        format!(
+            "`{}` is not one of nearest, triangle, catmullrom, gaussian, lanczos3",
+            text
+        )
+    })
+}
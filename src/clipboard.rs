@@ -0,0 +1,77 @@
+//! Copying outputs to the system clipboard, to paste them straight into a
+//! chat or an email.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use image::{imageops::FilterType, DynamicImage, RgbaImage};
+
+/// Longest side up to which images are copied without asking. Beyond it,
+/// some applications refuse or crawl on the pasted image, so a copy scaled
+/// to this size is offered instead.
+pub const MAX_SIDE: u32 = 4096;
+
+/// An image to copy.
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    /// The final canvas still in memory, like that of the preview.
+    Canvas(Arc<DynamicImage>),
+    /// An output on disk.
+    File(PathBuf),
+}
+
+impl ImageSource {
+    /// Width and height, read from the header for files.
+    pub fn dimensions(&self) -> Result<(u32, u32), String> {
+        match self {
+            ImageSource::Canvas(img) => Ok((img.width(), img.height())),
+            ImageSource::File(path) => image::image_dimensions(path).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// The pixels as 8-bit RGBA, as clipboards expect them, scaled down to
+    /// `max_side` if given and larger. Blocks while decoding files.
+    pub fn load(&self, max_side: Option<u32>) -> Result<RgbaImage, String> {
+        let img = match self {
+            ImageSource::Canvas(img) => Cow::Borrowed(img.as_ref()),
+            ImageSource::File(path) => Cow::Owned(image::open(path).map_err(|e| e.to_string())?),
+        };
+        Ok(match max_side {
+            Some(side) if img.width().max(img.height()) > side => {
+                img.resize(side, side, FilterType::Triangle).into_rgba8()
+            }
+            _ => img.to_rgba8(),
+        })
+    }
+}
+
+/// The system clipboard, opened on first use. It is kept open, as on X11
+/// what was copied is only served while it is.
+#[derive(Default)]
+pub struct Clipboard(Option<arboard::Clipboard>);
+
+impl Clipboard {
+    fn open(&mut self) -> Result<&mut arboard::Clipboard, arboard::Error> {
+        if self.0.is_none() {
+            self.0 = Some(arboard::Clipboard::new()?);
+        }
+        Ok(self.0.as_mut().expect("opened above"))
+    }
+
+    pub fn set_image(&mut self, img: &RgbaImage) -> Result<(), arboard::Error> {
+        self.open()?.set_image(arboard::ImageData {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            bytes: Cow::Borrowed(img.as_raw()),
+        })
+    }
+
+    /// Copies `path` as a file, for file managers and apps that take
+    /// attachments to paste it.
+    pub fn set_file(&mut self, path: &Path) -> Result<(), arboard::Error> {
+        self.open()?.set().file_list(&[path])
+    }
+}
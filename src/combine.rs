@@ -0,0 +1,159 @@
+//! Diptychs and triptychs: two or three photos side by side or stacked on
+//! one canvas, which then gets the border like any single photo.
+
+use image::{imageops, DynamicImage, GenericImageView, ImageBuffer, Rgba};
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::bit_depth;
+
+/// Fewest and most photos that can be combined.
+pub const MIN_IMAGES: usize = 2;
+pub const MAX_IMAGES: usize = 3;
+
+/// Template of combined outputs unless set: every name, joined by `+`.
+pub const DEFAULT_COMBINED_TEMPLATE: &str = "{names}{suffix}.{ext}";
+
+/// How the photos are scaled to line up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CombineAlign {
+    /// Same height, side by side.
+    #[default]
+    Heights,
+    /// Same width, one above the other.
+    Widths,
+}
+
+/// How photos are combined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CombineSettings {
+    pub align: CombineAlign,
+    /// Space between the photos in percent of the side they share, filled
+    /// with the border color.
+    pub gutter_percentage: f32,
+    /// Text of the `NameTemplate` of the combined output.
+    pub template: String,
+}
+
+impl Default for CombineSettings {
+    fn default() -> Self {
+        CombineSettings {
+            align: CombineAlign::default(),
+            gutter_percentage: 2.0,
+            template: DEFAULT_COMBINED_TEMPLATE.to_string(),
+        }
+    }
+}
+
+/// Where each photo goes on the combined canvas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombinedLayout {
+    pub width: u32,
+    pub height: u32,
+    /// Size and position of every photo, in order.
+    pub placements: Vec<Placement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Lays out photos of `sizes` after `settings`. They are scaled down to the
+/// smallest shared side, never up, keeping their shape.
+pub fn layout(sizes: &[(u32, u32)], settings: &CombineSettings) -> CombinedLayout {
+    let shared = sizes
+        .iter()
+        .map(|&(width, height)| match settings.align {
+            CombineAlign::Heights => height,
+            CombineAlign::Widths => width,
+        })
+        .min()
+        .unwrap_or(0)
+        .max(1);
+    let gutter = (shared as f32 * settings.gutter_percentage.max(0.0) / 100.0).round() as u32;
+
+    let mut placements = vec![];
+    let mut along = 0;
+    for &(width, height) in sizes {
+        let (width, height) = match settings.align {
+            CombineAlign::Heights => (scaled(width, shared, height), shared),
+            CombineAlign::Widths => (shared, scaled(height, shared, width)),
+        };
+        if !placements.is_empty() {
+            along += gutter;
+        }
+        let (x, y) = match settings.align {
+            CombineAlign::Heights => (along, 0),
+            CombineAlign::Widths => (0, along),
+        };
+        placements.push(Placement {
+            x,
+            y,
+            width,
+            height,
+        });
+        along += match settings.align {
+            CombineAlign::Heights => width,
+            CombineAlign::Widths => height,
+        };
+    }
+
+    let (width, height) = match settings.align {
+        CombineAlign::Heights => (along, shared),
+        CombineAlign::Widths => (shared, along),
+    };
+    CombinedLayout {
+        width,
+        height,
+        placements,
+    }
+}
+
+/// `side` of a photo whose other side goes from `from` to `to`.
+fn scaled(side: u32, to: u32, from: u32) -> u32 {
+    ((side as f64 * to as f64 / from.max(1) as f64).round() as u32).max(1)
+}
+
+/// `images` on one canvas after `settings`, the gutters filled with
+/// `color`. Deep images keep their precision, as in `Border`.
+pub fn combine(
+    images: &[DynamicImage],
+    settings: &CombineSettings,
+    color: Rgba<u8>,
+) -> DynamicImage {
+    let sizes: Vec<(u32, u32)> = images.iter().map(|img| img.dimensions()).collect();
+    let layout = layout(&sizes, settings);
+    let resized = images.iter().zip(&layout.placements).map(|(img, place)| {
+        if img.dimensions() == (place.width, place.height) {
+            img.clone()
+        } else {
+            img.resize_exact(place.width, place.height, imageops::FilterType::Lanczos3)
+        }
+    });
+
+    if images.iter().any(|img| bit_depth(img.color()) > 8) {
+        let color = Rgba(color.0.map(|channel| channel as u16 * 257));
+        let mut canvas = ImageBuffer::from_pixel(layout.width, layout.height, color);
+        for (img, place) in resized.zip(&layout.placements) {
+            imageops::overlay(
+                &mut canvas,
+                &img.to_rgba16(),
+                place.x as i64,
+                place.y as i64,
+            );
+        }
+        return canvas.into();
+    }
+
+    let mut canvas: DynamicImage =
+        ImageBuffer::from_pixel(layout.width, layout.height, color).into();
+    for (img, place) in resized.zip(&layout.placements) {
+        imageops::overlay(&mut canvas, &img, place.x as i64, place.y as i64);
+    }
+    canvas
+}
@@ -0,0 +1,162 @@
+use std::{fmt, fs, io, path::Path, path::PathBuf};
+
+use image_finalizer::{
+    archive::ArchiveSettings,
+    hook::HookSettings,
+    manifest::ManifestFormat,
+    naming::{OriginalsPlacement, OverwritePolicy, VariantFolders},
+    placeholder::PlaceholderKind,
+    sftp::SftpSettings,
+    srcset::SrcsetSettings,
+    upload::UploadSettings,
+    webhook::WebhookSettings,
+    ProcessInfo,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+/// Startup configuration: the processing settings plus the folders to work
+/// on. The settings use the same schema as presets, so any preset file is a
+/// valid config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    /// Output name template, see `NameTemplate`.
+    pub name_template: Option<String>,
+    /// `{suffix}` of the name template; may be empty.
+    pub suffix: Option<String>,
+    /// Also process the images in subfolders of the input folder.
+    pub recursive: Option<bool>,
+    /// Recreate the subfolders of the input folder in the output folder.
+    pub preserve_folders: Option<bool>,
+    /// Sorts the outputs into folders after the capture date, with a
+    /// pattern like `%Y/%Y-%m-%d`.
+    pub date_folders: Option<String>,
+    /// Records every batch in a manifest of this format.
+    pub manifest: Option<ManifestFormat>,
+    /// Adds a placeholder of this kind to each written file in the
+    /// manifest.
+    pub placeholder: Option<PlaceholderKind>,
+    /// Zips the outputs of the batch into an archive in the output folder.
+    pub archive: Option<ArchiveSettings>,
+    /// Uploads the outputs to an S3-compatible bucket. Needs a build with
+    /// the `s3` feature; the credentials never go in here.
+    pub upload: Option<UploadSettings>,
+    /// Uploads the outputs to an SFTP server instead. Needs a build with the
+    /// `sftp` feature and a host key trusted before.
+    pub sftp: Option<SftpSettings>,
+    /// Writes scaled-down WebP copies of every output for websites, listed
+    /// in a `srcset.json` in the output folder.
+    pub srcset: Option<SrcsetSettings>,
+    /// Runs a command for every output, like `exiftool {output}`.
+    pub hook: Option<HookSettings>,
+    /// Posts a report to this URL when the batch ends. Needs a build with
+    /// the `webhook` feature.
+    pub webhook: Option<WebhookSettings>,
+    /// What happens to outputs whose name is taken.
+    pub overwrite: Option<OverwritePolicy>,
+    /// Copies each input as it is next to its output or into `originals/`.
+    pub originals: Option<OriginalsPlacement>,
+    /// Gives outputs and copies the modification time of their input.
+    pub preserve_timestamps: Option<bool>,
+    /// Sorts the outputs into folders per format and/or size.
+    pub variant_folders: Option<VariantFolders>,
+    /// Puts the batch into a folder of its own in the output folder, with a
+    /// pattern like `{date:%Y-%m-%d_%H%M}_{preset}`.
+    pub run_folder: Option<String>,
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl Config {
+    /// Location used when no `--config` is given.
+    pub fn default_path() -> PathBuf {
+        storage::config_dir().join("config.toml")
+    }
+
+    /// Reads a config file, returning it together with the keys that were not
+    /// recognized. Unknown keys are reported rather than rejected so files
+    /// written for newer versions still load.
+    pub fn load(path: &Path) -> Result<(Config, Vec<String>), ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        let config: Config =
+            toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+        let table: toml::Table =
+            toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+
+        let known = Config::known_keys();
+        let unknown = table
+            .keys()
+            .filter(|key| !known.contains(key))
+            .cloned()
+            .collect();
+
+        Ok((config, unknown))
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+
+    fn known_keys() -> Vec<String> {
+        let mut keys: Vec<String> = toml::Table::try_from(ProcessInfo::default())
+            .map(|table| table.keys().cloned().collect())
+            .unwrap_or_default();
+        keys.extend(
+            [
+                // Left out of the defaults above, as they do nothing there.
+                "trim",
+                "transform",
+                "crop",
+                "border_sides",
+                "border_pixels",
+                "canvas_aspect",
+                "frame",
+                "corner_radius",
+                "drop_shadow",
+                "keyline",
+                "border_fill",
+                "input",
+                "output",
+                "name_template",
+                "suffix",
+                "recursive",
+                "preserve_folders",
+                "date_folders",
+                "overwrite",
+                "originals",
+                "preserve_timestamps",
+                "variant_folders",
+                "run_folder",
+                "manifest",
+                "placeholder",
+                "archive",
+                "srcset",
+                "hook",
+                "upload",
+                "sftp",
+                "webhook",
+            ]
+            .map(String::from),
+        );
+        keys
+    }
+}
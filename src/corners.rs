@@ -0,0 +1,119 @@
+//! Rounded corners of the photo on the canvas.
+
+use std::{fmt, str::FromStr};
+
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::bit_depth;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RadiusUnit {
+    #[default]
+    Pixels,
+    /// Percent of the shortest side of the photo.
+    Percent,
+}
+
+/// How round the corners of the photo are. Written like `24` for pixels
+/// or `5%` for percent of the shortest side.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CornerRadius {
+    pub radius: f32,
+    pub unit: RadiusUnit,
+}
+
+impl Default for CornerRadius {
+    fn default() -> Self {
+        CornerRadius {
+            radius: 24.0,
+            unit: RadiusUnit::Pixels,
+        }
+    }
+}
+
+impl CornerRadius {
+    /// The radius in pixels on a `width`×`height` photo, at most half its
+    /// shortest side.
+    pub fn pixels(&self, width: u32, height: u32) -> f32 {
+        let shortest = width.min(height) as f32;
+        let radius = match self.unit {
+            RadiusUnit::Pixels => self.radius,
+            RadiusUnit::Percent => self.radius / 100.0 * shortest,
+        };
+        radius.clamp(0.0, shortest / 2.0)
+    }
+
+    /// `img` with its corners cut round and their edges faded out over a
+    /// pixel, keeping deep images deep.
+    pub fn apply(&self, img: &DynamicImage) -> DynamicImage {
+        let (width, height) = img.dimensions();
+        let radius = self.pixels(width, height);
+        if radius <= 0.0 {
+            return img.clone();
+        }
+        if bit_depth(img.color()) > 8 {
+            let mut rounded = img.to_rgba16();
+            cut(width, height, radius, |x, y, coverage| {
+                let alpha = &mut rounded.get_pixel_mut(x, y).0[3];
+                *alpha = (*alpha as f32 * coverage).round() as u16;
+            });
+            rounded.into()
+        } else {
+            let mut rounded = img.to_rgba8();
+            cut(width, height, radius, |x, y, coverage| {
+                let alpha = &mut rounded.get_pixel_mut(x, y).0[3];
+                *alpha = (*alpha as f32 * coverage).round() as u8;
+            });
+            rounded.into()
+        }
+    }
+}
+
+/// Calls `fade` with every corner pixel of a `width`×`height` image that
+/// lies partly or wholly outside the rounding, and how much of it is
+/// inside, measured from the pixel center.
+fn cut(width: u32, height: u32, radius: f32, mut fade: impl FnMut(u32, u32, f32)) {
+    let reach = radius.ceil() as u32;
+    for y in (0..reach.min(height)).chain(height.saturating_sub(reach).max(reach)..height) {
+        for x in (0..reach.min(width)).chain(width.saturating_sub(reach).max(reach)..width) {
+            // Distance from the center of the rounding on this corner.
+            let dx = (radius - (x.min(width - 1 - x) as f32 + 0.5)).max(0.0);
+            let dy = (radius - (y.min(height - 1 - y) as f32 + 0.5)).max(0.0);
+            let coverage = (radius - dx.hypot(dy) + 0.5).clamp(0.0, 1.0);
+            if coverage < 1.0 {
+                fade(x, y, coverage);
+            }
+        }
+    }
+}
+
+impl fmt::Display for CornerRadius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit {
+            RadiusUnit::Pixels => write!(f, "{}", self.radius),
+            RadiusUnit::Percent => write!(f, "{}%", self.radius),
+        }
+    }
+}
+
+impl FromStr for CornerRadius {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let trimmed = text.trim();
+        let (number, unit) = match trimmed.strip_suffix('%') {
+            Some(number) => (number, RadiusUnit::Percent),
+            None => (
+                trimmed.strip_suffix("px").unwrap_or(trimmed),
+                RadiusUnit::Pixels,
+            ),
+        };
+        match number.trim().parse::<f32>() {
+            Ok(radius) if radius.is_finite() && radius >= 0.0 => Ok(CornerRadius { radius, unit }),
+            _ => Err(format!("`{}` is not a corner radius like 24 or 5%", text)),
+        }
+    }
+}
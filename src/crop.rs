@@ -0,0 +1,228 @@
+//! Straightening and recropping single photos, set per image in the editor
+//! of the preview. Both happen after the trim and the transform, so the
+//! editor works on the photo as the preview shows it.
+
+use image::{imageops, DynamicImage, ImageBuffer, Pixel};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    error::ProcessError,
+    pipeline::{bit_depth, FileContext, ProcessingStep},
+    t,
+};
+
+/// Largest straightening angle in degrees either way. Horizons are rarely
+/// further off, and beyond it too much of the photo is lost.
+pub const MAX_ANGLE: f32 = 10.0;
+
+/// Part of a photo to keep, in fractions of its width and height, so it
+/// applies to the full size as well as to the preview.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CropRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for CropRect {
+    fn default() -> Self {
+        CropRect::FULL
+    }
+}
+
+impl CropRect {
+    /// The whole photo.
+    pub const FULL: CropRect = CropRect {
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+    };
+
+    pub fn is_full(&self) -> bool {
+        *self == CropRect::FULL
+    }
+
+    /// The rectangle dragged from `start` to `end`, in fractions of a
+    /// `width`×`height` photo and kept inside it. With a `ratio` of width
+    /// to height in pixels, the longer side of the drag is shortened to
+    /// match.
+    pub fn from_drag(
+        start: (f32, f32),
+        end: (f32, f32),
+        (width, height): (u32, u32),
+        ratio: Option<f32>,
+    ) -> CropRect {
+        let (x, y) = (start.0.clamp(0.0, 1.0), start.1.clamp(0.0, 1.0));
+        let mut dx = end.0.clamp(0.0, 1.0) - x;
+        let mut dy = end.1.clamp(0.0, 1.0) - y;
+        if let Some(ratio) = ratio.filter(|ratio| *ratio > 0.0) {
+            let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+            let (pixels_x, pixels_y) = (dx.abs() * w, dy.abs() * h);
+            let (pixels_x, pixels_y) = if pixels_x > pixels_y * ratio {
+                (pixels_y * ratio, pixels_y)
+            } else {
+                (pixels_x, pixels_x / ratio)
+            };
+            dx = (pixels_x / w).copysign(dx);
+            dy = (pixels_y / h).copysign(dy);
+        }
+        CropRect {
+            x: x.min(x + dx),
+            y: y.min(y + dy),
+            width: dx.abs(),
+            height: dy.abs(),
+        }
+    }
+
+    /// Left, top, width and height in pixels of a `width`×`height` photo,
+    /// kept inside it and at least a pixel large.
+    pub fn pixels(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let span = |start: f32, length: f32, size: u32| {
+            let from = ((start * size as f32).round().max(0.0) as u32).min(size - 1);
+            let to = (((start + length) * size as f32).round() as u32).clamp(from + 1, size);
+            (from, to - from)
+        };
+        let (x, crop_width) = span(self.x, self.width, width.max(1));
+        let (y, crop_height) = span(self.y, self.height, height.max(1));
+        (x, y, crop_width, crop_height)
+    }
+}
+
+/// A straightening and a crop of the straightened photo.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Crop {
+    /// Clockwise rotation in degrees, up to `MAX_ANGLE` either way.
+    #[serde(deserialize_with = "finite_angle")]
+    pub angle: f32,
+    pub rect: CropRect,
+}
+
+impl Crop {
+    /// Whether the photo is left as it is.
+    pub fn is_identity(&self) -> bool {
+        self.angle == 0.0 && self.rect.is_full()
+    }
+
+    /// Size of a `width`×`height` photo once straightened and cropped.
+    pub fn dimensions(&self, width: u32, height: u32) -> (u32, u32) {
+        let (width, height) = straightened_size(width, height, self.angle);
+        let (_, _, width, height) = self.rect.pixels(width, height);
+        (width, height)
+    }
+
+    pub fn apply(&self, img: DynamicImage) -> Result<DynamicImage, CropError> {
+        let img = straighten(img, self.angle)?;
+        if self.rect.is_full() {
+            return Ok(img);
+        }
+        let (x, y, width, height) = self.rect.pixels(img.width(), img.height());
+        Ok(img.crop_imm(x, y, width, height))
+    }
+
+    /// Short description like "straightened 1.5°, cropped", or `None` if
+    /// the photo is left as it is.
+    pub fn describe(&self) -> Option<String> {
+        let mut parts = vec![];
+        if self.angle != 0.0 {
+            parts.push(t!(
+                "crop-straightened",
+                degrees = format!("{:+.1}", self.angle)
+            ));
+        }
+        if !self.rect.is_full() {
+            parts.push(t!("crop-cropped").to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+impl ProcessingStep for Crop {
+    fn name(&self) -> &'static str {
+        "crop"
+    }
+
+    fn apply(&self, img: DynamicImage, ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        Crop::apply(self, img).map_err(|source| ProcessError::Crop {
+            path: ctx.path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Why a photo can't be straightened.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CropError {
+    #[error("{}", t!("crop-invalid-angle", angle = .0))]
+    InvalidAngle(f32),
+}
+
+/// Reads an angle, kept to `MAX_ANGLE` either way. Edits written by hand
+/// may hold `nan` or `inf`, which no photo can be turned by.
+fn finite_angle<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let angle = f32::deserialize(deserializer)?;
+    if !angle.is_finite() {
+        return Err(D::Error::custom(format!(
+            "invalid straightening angle `{}`",
+            angle
+        )));
+    }
+    Ok(angle.clamp(-MAX_ANGLE, MAX_ANGLE))
+}
+
+/// Size of a `width`×`height` photo turned by `angle` degrees: the largest
+/// centered rectangle of its aspect ratio that the turned photo covers, so
+/// no empty corners show.
+pub fn straightened_size(width: u32, height: u32, angle: f32) -> (u32, u32) {
+    if angle == 0.0 {
+        return (width, height);
+    }
+    let (sin, cos) = (angle.abs() as f64).to_radians().sin_cos();
+    let (w, h) = (width as f64, height as f64);
+    let scale = (w / (w * cos + h * sin)).min(h / (w * sin + h * cos));
+    // Leeway for rounding errors, so whole sides don't lose a pixel.
+    let side = |length: f64| ((length * scale + 1e-6).floor() as u32).max(1);
+    (side(w), side(h))
+}
+
+/// Turns `img` clockwise by `angle` degrees with bilinear resampling and
+/// cuts it to `straightened_size`. Fails for angles that aren't finite.
+pub fn straighten(img: DynamicImage, angle: f32) -> Result<DynamicImage, CropError> {
+    if angle == 0.0 || img.width() == 0 || img.height() == 0 {
+        return Ok(img);
+    }
+    let (width, height) = straightened_size(img.width(), img.height(), angle);
+    let turned = match img {
+        DynamicImage::ImageRgba32F(src) => turned(&src, width, height, angle).map(Into::into),
+        // Deep images keep their precision for the encoders that can store it.
+        img if bit_depth(img.color()) > 8 => {
+            turned(&img.into_rgba16(), width, height, angle).map(Into::into)
+        }
+        img => turned(&img.into_rgba8(), width, height, angle).map(Into::into),
+    };
+    turned.ok_or(CropError::InvalidAngle(angle))
+}
+
+fn turned<P: Pixel>(
+    src: &ImageBuffer<P, Vec<P::Subpixel>>,
+    width: u32,
+    height: u32,
+    angle: f32,
+) -> Option<ImageBuffer<P, Vec<P::Subpixel>>> {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (last_x, last_y) = ((src.width() - 1) as f32, (src.height() - 1) as f32);
+    let (src_x, src_y) = (last_x / 2.0, last_y / 2.0);
+    let (center_x, center_y) = ((width - 1) as f32 / 2.0, (height - 1) as f32 / 2.0);
+    let mut out = ImageBuffer::new(width, height);
+    for (x, y, pixel) in out.enumerate_pixels_mut() {
+        // Turning the output pixel back counterclockwise finds where it
+        // comes from. A NaN left by the angle stays out of the image.
+        let (dx, dy) = (x as f32 - center_x, y as f32 - center_y);
+        let sx = (src_x + dx * cos + dy * sin).clamp(0.0, last_x);
+        let sy = (src_y - dx * sin + dy * cos).clamp(0.0, last_y);
+        *pixel = imageops::interpolate_bilinear(src, sx, sy)?;
+    }
+    Some(out)
+}
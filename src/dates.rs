@@ -0,0 +1,66 @@
+//! When a photo was taken, for names and folders derived from dates.
+
+use std::{fs, io::BufReader, path::Path, time::SystemTime};
+
+use exif::{In, Tag, Value};
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+/// The EXIF capture date of `path`, or when the file was last modified, in
+/// local time.
+pub fn file_date(path: &Path) -> Option<PrimitiveDateTime> {
+    capture_date(path).or_else(|| modified_date(path))
+}
+
+/// `DateTimeOriginal` of the EXIF block, falling back to `DateTime`.
+pub fn capture_date(path: &Path) -> Option<PrimitiveDateTime> {
+    let file = fs::File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+
+    [Tag::DateTimeOriginal, Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Ascii(values) => parse_exif_date(values.first()?),
+            _ => None,
+        })
+}
+
+/// Parses EXIF's `YYYY:MM:DD HH:MM:SS`. Cameras without a clock write
+/// zeros or blanks, which don't count as a date.
+pub fn parse_exif_date(bytes: &[u8]) -> Option<PrimitiveDateTime> {
+    let text = std::str::from_utf8(bytes).ok()?.trim_end_matches('\0');
+    let (date, time) = text.split_once(' ')?;
+    let number = |parts: &mut std::str::Split<'_, char>| parts.next()?.trim().parse::<u32>().ok();
+
+    let mut date = date.split(':');
+    let year = number(&mut date)? as i32;
+    let month = Month::try_from(number(&mut date)? as u8).ok()?;
+    let day = number(&mut date)? as u8;
+    let mut time = time.split(':');
+    let (hour, minute, second) = (number(&mut time)?, number(&mut time)?, number(&mut time)?);
+
+    Some(PrimitiveDateTime::new(
+        Date::from_calendar_date(year, month, day).ok()?,
+        Time::from_hms(hour as u8, minute as u8, second as u8).ok()?,
+    ))
+}
+
+/// The current local date and time, or UTC where the offset is unknown.
+pub fn now() -> PrimitiveDateTime {
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    PrimitiveDateTime::new(now.date(), now.time())
+}
+
+fn modified_date(path: &Path) -> Option<PrimitiveDateTime> {
+    Some(local(fs::metadata(path).ok()?.modified().ok()?))
+}
+
+/// `time` in local time.
+pub fn local(time: SystemTime) -> PrimitiveDateTime {
+    // The local offset is unknown while other threads could change it, as
+    // in a batch; the date is then in UTC.
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let local = OffsetDateTime::from(time).to_offset(offset);
+    PrimitiveDateTime::new(local.date(), local.time())
+}
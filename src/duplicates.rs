@@ -0,0 +1,220 @@
+//! Finding near-duplicate inputs, like burst frames or re-exports of one
+//! photo, by a perceptual hash of each.
+
+use std::{
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::SystemTime,
+};
+
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::ProcessError;
+
+/// Bits two hashes may differ in to count as duplicates, unless set. Out of
+/// 64; re-encodes and resizes stay well below, different photos well above.
+pub const DEFAULT_THRESHOLD: u32 = 10;
+
+/// Highest threshold that still makes sense; beyond it unrelated photos
+/// start to match.
+pub const MAX_THRESHOLD: u32 = 24;
+
+/// Which file of a group is kept when the choice is made automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeepPolicy {
+    /// Most pixels, then most bytes.
+    #[default]
+    Largest,
+    /// Most recently modified.
+    Newest,
+}
+
+/// An input and what is known about it after hashing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedFile {
+    pub path: PathBuf,
+    pub hash: u64,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Inputs that look alike, in the order they were scanned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    pub files: Vec<ScannedFile>,
+}
+
+impl DuplicateGroup {
+    /// Index of the file `policy` keeps. The first one wins ties.
+    pub fn keep(&self, policy: KeepPolicy) -> usize {
+        let key = |file: &ScannedFile| match policy {
+            KeepPolicy::Largest => (
+                file.width as u64 * file.height as u64,
+                file.bytes,
+                SystemTime::UNIX_EPOCH,
+            ),
+            KeepPolicy::Newest => (0, 0, file.modified.unwrap_or(SystemTime::UNIX_EPOCH)),
+        };
+        let mut kept = 0;
+        for (index, file) in self.files.iter().enumerate() {
+            if key(file) > key(&self.files[kept]) {
+                kept = index;
+            }
+        }
+        kept
+    }
+
+    /// Every file but the one at `kept`, as left out in its favor.
+    pub fn decisions(&self, kept: usize) -> Vec<Duplicate> {
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != kept)
+            .map(|(_, file)| Duplicate {
+                path: file.path.clone(),
+                kept: self.files[kept].path.clone(),
+            })
+            .collect()
+    }
+}
+
+/// An input left out of a batch as a duplicate of `kept`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    pub path: PathBuf,
+    pub kept: PathBuf,
+}
+
+/// Difference hash of `img`: each bit tells whether a pixel of a 9×8
+/// grayscale thumbnail is brighter than its right neighbor. Survives
+/// resizing, re-encoding and small edits.
+pub fn dhash(img: &DynamicImage) -> u64 {
+    let small = img.thumbnail_exact(9, 8).into_luma8();
+    let mut hash = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = hash << 1 | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+/// Bits `a` and `b` differ in.
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Indices of the `hashes` that lie within `threshold` of each other,
+/// directly or through others, in groups of two or more. Compares every
+/// pair, which is quick enough for the thousands of files of a folder.
+pub fn group(hashes: &[u64], threshold: u32) -> Vec<Vec<usize>> {
+    // Union-find; every index points towards the first of its group.
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    fn root(parent: &mut [usize], mut index: usize) -> usize {
+        while parent[index] != index {
+            parent[index] = parent[parent[index]];
+            index = parent[index];
+        }
+        index
+    }
+    for a in 0..hashes.len() {
+        for b in a + 1..hashes.len() {
+            if distance(hashes[a], hashes[b]) <= threshold {
+                let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+                parent[ra.max(rb)] = ra.min(rb);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = vec![vec![]; hashes.len()];
+    for index in 0..hashes.len() {
+        groups[root(&mut parent, index)].push(index);
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// Decodes and hashes `path`.
+pub fn scan_file(path: &Path) -> Result<ScannedFile, ProcessError> {
+    let metadata = fs::metadata(path).map_err(|e| ProcessError::io(path, e))?;
+    let img = image::open(path).map_err(|e| ProcessError::decode(path, e))?;
+    let (width, height) = img.dimensions();
+    Ok(ScannedFile {
+        path: path.to_path_buf(),
+        hash: dhash(&img),
+        width,
+        height,
+        bytes: metadata.len(),
+        modified: metadata.modified().ok(),
+    })
+}
+
+/// Hashes `paths` spread over the available cores and groups those within
+/// `threshold` of each other. `on_progress` is called with the files done
+/// and the total as each one finishes. Files that can't be read are left
+/// out of every group. Returns `None` once `cancel` is set.
+pub fn find_duplicates(
+    paths: &[PathBuf],
+    threshold: u32,
+    cancel: &AtomicBool,
+    on_progress: impl Fn(usize, usize) + Sync,
+) -> Option<Vec<DuplicateGroup>> {
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(paths.len());
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let scanned = Mutex::new(vec![None; paths.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while !cancel.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    match scan_file(path) {
+                        Ok(file) => scanned.lock().unwrap()[index] = Some(file),
+                        Err(e) => tracing::warn!("not checked for duplicates: {}", e),
+                    }
+                    on_progress(done.fetch_add(1, Ordering::Relaxed) + 1, paths.len());
+                }
+            });
+        }
+    });
+    if cancel.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let scanned: Vec<ScannedFile> = scanned
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect();
+    let hashes: Vec<u64> = scanned.iter().map(|file| file.hash).collect();
+    Some(
+        group(&hashes, threshold)
+            .into_iter()
+            .map(|indices| DuplicateGroup {
+                files: indices
+                    .into_iter()
+                    .map(|index| scanned[index].clone())
+                    .collect(),
+            })
+            .collect(),
+    )
+}
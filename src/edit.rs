@@ -0,0 +1,39 @@
+//! What is set for single images in the list and the preview, on top of
+//! the settings of the batch.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{crop::Crop, transform::Transform};
+
+/// Per-image overrides. Kept with the session and the app settings, so
+/// running the folder again gives the same result.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageEdit {
+    /// Turns the image like this instead of as the batch does.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
+    /// Straightens and recrops the image after it is turned.
+    #[serde(skip_serializing_if = "Crop::is_identity")]
+    pub crop: Crop,
+}
+
+impl ImageEdit {
+    /// Whether the image is processed like the rest.
+    pub fn is_empty(&self) -> bool {
+        self.transform.is_none() && self.crop.is_identity()
+    }
+
+    /// Short description like "rotated 90°, cropped" for marking the image
+    /// in the list, or `None` if it is processed like the rest.
+    pub fn describe(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.transform.and_then(|transform| transform.describe()),
+            self.crop.describe(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
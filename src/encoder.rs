@@ -0,0 +1,206 @@
+//! Writing the processed image in each output format.
+
+use std::io::{Cursor, Write};
+
+use image::{
+    codecs::{
+        avif::AvifEncoder,
+        jpeg::JpegEncoder,
+        png::{self, PngEncoder},
+        tiff::TiffEncoder,
+        webp::WebPEncoder,
+    },
+    DynamicImage, ExtendedColorType, ImageEncoder, ImageResult,
+};
+
+use crate::format::FormatSettings;
+
+/// One output format. `OutputFormat::encoder` picks the implementation, so
+/// adding a format means adding an implementation here and a variant there.
+pub trait Encoder: Send + Sync {
+    /// Name shown to the user.
+    fn name(&self) -> &'static str;
+
+    /// File extension of the written files, without the dot.
+    fn extension(&self) -> &'static str;
+
+    /// Writes `img` to `writer`, using the options of this format in
+    /// `settings`.
+    fn encode(
+        &self,
+        img: &DynamicImage,
+        writer: &mut dyn Write,
+        settings: &FormatSettings,
+    ) -> ImageResult<()>;
+
+    /// Whether transparency survives encoding.
+    fn supports_alpha(&self) -> bool;
+
+    /// Whether channels of `bits` bits are written without losing precision.
+    fn supports_bit_depth(&self, bits: u8) -> bool;
+}
+
+/// Lossless, and keeps transparency and 16-bit channels.
+pub struct Png;
+
+impl Encoder for Png {
+    fn name(&self) -> &'static str {
+        "PNG"
+    }
+
+    fn extension(&self) -> &'static str {
+        "png"
+    }
+
+    fn encode(
+        &self,
+        img: &DynamicImage,
+        writer: &mut dyn Write,
+        settings: &FormatSettings,
+    ) -> ImageResult<()> {
+        let encoder = PngEncoder::new_with_quality(
+            writer,
+            settings.png.compression.to_image(),
+            png::FilterType::Adaptive,
+        );
+        img.write_with_encoder(encoder)
+    }
+
+    fn supports_alpha(&self) -> bool {
+        true
+    }
+
+    fn supports_bit_depth(&self, bits: u8) -> bool {
+        matches!(bits, 8 | 16)
+    }
+}
+
+pub struct Jpeg;
+
+impl Encoder for Jpeg {
+    fn name(&self) -> &'static str {
+        "JPEG"
+    }
+
+    fn extension(&self) -> &'static str {
+        "jpg"
+    }
+
+    fn encode(
+        &self,
+        img: &DynamicImage,
+        writer: &mut dyn Write,
+        settings: &FormatSettings,
+    ) -> ImageResult<()> {
+        let encoder = JpegEncoder::new_with_quality(writer, settings.jpeg.quality);
+        write_rgb8(encoder, img)
+    }
+
+    fn supports_alpha(&self) -> bool {
+        false
+    }
+
+    fn supports_bit_depth(&self, bits: u8) -> bool {
+        bits == 8
+    }
+}
+
+pub struct Tiff;
+
+impl Encoder for Tiff {
+    fn name(&self) -> &'static str {
+        "TIFF"
+    }
+
+    fn extension(&self) -> &'static str {
+        "tiff"
+    }
+
+    fn encode(
+        &self,
+        img: &DynamicImage,
+        writer: &mut dyn Write,
+        _settings: &FormatSettings,
+    ) -> ImageResult<()> {
+        // The TIFF encoder seeks back to write its offsets.
+        let mut buffer = Cursor::new(vec![]);
+        write_rgb8(TiffEncoder::new(&mut buffer), img)?;
+        writer.write_all(buffer.get_ref())?;
+        Ok(())
+    }
+
+    fn supports_alpha(&self) -> bool {
+        false
+    }
+
+    fn supports_bit_depth(&self, bits: u8) -> bool {
+        bits == 8
+    }
+}
+
+pub struct Avif;
+
+impl Encoder for Avif {
+    fn name(&self) -> &'static str {
+        "AVIF"
+    }
+
+    fn extension(&self) -> &'static str {
+        "avif"
+    }
+
+    fn encode(
+        &self,
+        img: &DynamicImage,
+        writer: &mut dyn Write,
+        settings: &FormatSettings,
+    ) -> ImageResult<()> {
+        let avif = settings.avif;
+        let encoder = AvifEncoder::new_with_speed_quality(writer, avif.speed, avif.quality);
+        write_rgb8(encoder, img)
+    }
+
+    fn supports_alpha(&self) -> bool {
+        false
+    }
+
+    fn supports_bit_depth(&self, bits: u8) -> bool {
+        bits == 8
+    }
+}
+
+/// Always lossless.
+pub struct Webp;
+
+impl Encoder for Webp {
+    fn name(&self) -> &'static str {
+        "WebP"
+    }
+
+    fn extension(&self) -> &'static str {
+        "webp"
+    }
+
+    fn encode(
+        &self,
+        img: &DynamicImage,
+        writer: &mut dyn Write,
+        _settings: &FormatSettings,
+    ) -> ImageResult<()> {
+        write_rgb8(WebPEncoder::new_lossless(writer), img)
+    }
+
+    fn supports_alpha(&self) -> bool {
+        false
+    }
+
+    fn supports_bit_depth(&self, bits: u8) -> bool {
+        bits == 8
+    }
+}
+
+/// Writes `img` as 8-bit RGB, dropping any transparency.
+fn write_rgb8(encoder: impl ImageEncoder, img: &DynamicImage) -> ImageResult<()> {
+    let rgb = img.to_rgb8();
+    encoder.write_image(&rgb, rgb.width(), rgb.height(), ExtendedColorType::Rgb8)
+}
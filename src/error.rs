@@ -0,0 +1,95 @@
+use std::{io, path::PathBuf};
+
+use image::ImageError;
+
+use crate::{crop::CropError, hook::HookError, naming::TemplateError, space, t};
+
+/// Why a file could not be processed. Every variant names the file involved.
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessError {
+    /// Reading, creating or writing a file or folder failed.
+    #[error("{}: {source}", .path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The input is not an image this build can decode.
+    #[error("{}", t!("error-decode", path = .path.display(), source = .source))]
+    Decode {
+        path: PathBuf,
+        #[source]
+        source: ImageError,
+    },
+    /// The encoder rejected the processed image.
+    #[error("{}", t!("error-encode", path = .path.display(), source = .source))]
+    Encode {
+        path: PathBuf,
+        #[source]
+        source: ImageError,
+    },
+    /// No output name can be derived from the path.
+    #[error("{}", t!("error-invalid-path", path = .path.display()))]
+    InvalidPath { path: PathBuf },
+    /// The name template can't name this file's output.
+    #[error("{}: {source}", .path.display())]
+    Template {
+        path: PathBuf,
+        #[source]
+        source: TemplateError,
+    },
+    /// The photo can't be straightened as its edit says.
+    #[error("{}: {source}", .path.display())]
+    Crop {
+        path: PathBuf,
+        #[source]
+        source: CropError,
+    },
+    /// The hook command failed for the output, and failures count.
+    #[error("{}", t!("error-hook", path = .path.display(), source = .source))]
+    Hook {
+        path: PathBuf,
+        #[source]
+        source: HookError,
+    },
+    /// Too few or too many photos to combine, starting with `path`.
+    #[error("{}", t!("error-combine-count", path = .path.display(), count = .count))]
+    CombineCount { path: PathBuf, count: usize },
+}
+
+impl ProcessError {
+    pub(crate) fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        ProcessError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Whether the output couldn't be written for lack of space, which
+    /// freeing some fixes for the files that are still to come.
+    pub fn is_disk_full(&self) -> bool {
+        matches!(self, ProcessError::Io { source, .. } if space::is_disk_full(source))
+    }
+
+    /// Splits decoder errors from plain IO errors such as a missing file.
+    pub(crate) fn decode(path: impl Into<PathBuf>, source: ImageError) -> Self {
+        match source {
+            ImageError::IoError(source) => ProcessError::io(path, source),
+            source => ProcessError::Decode {
+                path: path.into(),
+                source,
+            },
+        }
+    }
+
+    /// Splits encoder errors from failures to write the file.
+    pub(crate) fn encode(path: impl Into<PathBuf>, source: ImageError) -> Self {
+        match source {
+            ImageError::IoError(source) => ProcessError::io(path, source),
+            source => ProcessError::Encode {
+                path: path.into(),
+                source,
+            },
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Predicting the size of a batch's outputs before it runs, from a few
+//! inputs encoded in memory.
+
+use std::{
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    choose_settings,
+    pipeline::{FileContext, Pipeline},
+    rules::ResolvedRule,
+    transform::open_oriented,
+    ProcessError, ProcessInfo,
+};
+
+/// Predicted bytes of all outputs of a batch. `total` lies between `low`
+/// and `high` unless the samples were unusually unrepresentative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeEstimate {
+    pub total: u64,
+    pub low: u64,
+    pub high: u64,
+    /// How many inputs were encoded to get here.
+    pub sampled: usize,
+    /// Whether every input was sampled, so the total is exact.
+    pub exact: bool,
+    /// How long the batch takes on this machine, if the samples were
+    /// timed.
+    pub duration: Option<Duration>,
+}
+
+/// Indices of the inputs to sample out of inputs of `sizes` bytes: the
+/// smallest, the median and the largest, each once.
+pub fn pick_samples(sizes: &[u64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&index| sizes[index]);
+    let mut picked = vec![];
+    if let (Some(&first), Some(&last)) = (order.first(), order.last()) {
+        for index in [first, order[order.len() / 2], last] {
+            if !picked.contains(&index) {
+                picked.push(index);
+            }
+        }
+    }
+    picked
+}
+
+/// Scales the samples, pairs of input and output bytes, up to inputs of
+/// `sizes` bytes by their ratio of output to input. The range spans the
+/// smallest and largest ratio. `None` without samples.
+pub fn extrapolate(sizes: &[u64], samples: &[(u64, u64)]) -> Option<SizeEstimate> {
+    if samples.is_empty() {
+        return None;
+    }
+    if samples.len() == sizes.len() {
+        let total = samples.iter().map(|&(_, output)| output).sum();
+        return Some(SizeEstimate {
+            total,
+            low: total,
+            high: total,
+            sampled: samples.len(),
+            exact: true,
+            duration: None,
+        });
+    }
+
+    let ratios: Vec<f64> = samples
+        .iter()
+        .map(|&(input, output)| output as f64 / input.max(1) as f64)
+        .collect();
+    let input: f64 = sizes.iter().map(|&size| size as f64).sum();
+    let mean = ratios.iter().sum::<f64>() / ratios.len() as f64;
+    let low = ratios.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = ratios.iter().copied().fold(0.0, f64::max);
+    Some(SizeEstimate {
+        total: (input * mean).round() as u64,
+        low: (input * low).round() as u64,
+        high: (input * high).round() as u64,
+        sampled: samples.len(),
+        exact: false,
+        duration: None,
+    })
+}
+
+/// Scales the samples, pairs of input bytes and the time they took, up to
+/// inputs of `sizes` bytes processed `workers` at a time. `None` without
+/// samples.
+pub fn extrapolate_duration(
+    sizes: &[u64],
+    samples: &[(u64, Duration)],
+    workers: usize,
+) -> Option<Duration> {
+    if samples.is_empty() {
+        return None;
+    }
+    let sampled: u64 = samples.iter().map(|&(input, _)| input).sum();
+    let took: Duration = samples.iter().map(|&(_, took)| took).sum();
+    let input: u64 = sizes.iter().sum();
+    let seconds = took.as_secs_f64() * input as f64 / sampled.max(1) as f64;
+    Some(Duration::from_secs_f64(seconds / workers.max(1) as f64))
+}
+
+/// Bytes of the output of `path` with `info`, encoded in memory.
+pub fn encoded_size(path: &Path, info: &ProcessInfo) -> Result<u64, ProcessError> {
+    let img = open_oriented(path).map_err(|e| ProcessError::decode(path, e))?;
+    let processed = Pipeline::for_info(info).run(img, &FileContext { path })?;
+    let mut bytes = vec![];
+    info.output_format
+        .encoder()
+        .encode(&processed, &mut bytes, &info.format_settings)
+        .map_err(|e| ProcessError::encode(path, e))?;
+    Ok(bytes.len() as u64)
+}
+
+/// Estimates the outputs of `paths` processed with the settings `rules`
+/// pick, or `manual`, by encoding a few samples side by side. Returns
+/// `None` once `cancel` is set; samples already encoding are finished
+/// first.
+pub fn estimate(
+    paths: &[PathBuf],
+    rules: &[ResolvedRule],
+    manual: ProcessInfo,
+    cancel: &AtomicBool,
+) -> Result<Option<SizeEstimate>, ProcessError> {
+    let sizes = paths
+        .iter()
+        .map(|path| {
+            fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .map_err(|e| ProcessError::io(path, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let samples = thread::scope(|scope| {
+        let handles: Vec<_> = pick_samples(&sizes)
+            .into_iter()
+            .map(|index| {
+                let (path, input) = (&paths[index], sizes[index]);
+                let manual = manual.clone();
+                scope.spawn(move || {
+                    if cancel.load(Ordering::Relaxed) {
+                        return Ok(None);
+                    }
+                    let info = choose_settings(path, rules, manual).info;
+                    let started = Instant::now();
+                    encoded_size(path, &info).map(|output| Some((input, output, started.elapsed())))
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("sampling panicked"))
+            .collect::<Result<Option<Vec<_>>, _>>()
+    })?;
+
+    if cancel.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+    let Some(samples) = samples else {
+        return Ok(None);
+    };
+    let bytes: Vec<_> = samples
+        .iter()
+        .map(|&(input, output, _)| (input, output))
+        .collect();
+    let times: Vec<_> = samples
+        .iter()
+        .map(|&(input, _, took)| (input, took))
+        .collect();
+    // As many at a time as a batch processes.
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(paths.len());
+    Ok(extrapolate(&sizes, &bytes).map(|estimate| SizeEstimate {
+        duration: extrapolate_duration(&sizes, &times, workers),
+        ..estimate
+    }))
+}
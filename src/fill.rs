@@ -0,0 +1,341 @@
+//! What the border is filled with, when a flat `border_color` won't do.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use image::{imageops, imageops::FilterType, DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ProcessError, i18n, serialization::hex_color, t, transform::open_oriented};
+
+/// Fill of the canvas around the photo.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BorderFill {
+    /// The border color all over.
+    #[default]
+    Solid,
+    /// From the border color to a second one.
+    Gradient(Gradient),
+    /// The photo itself, scaled up to cover the canvas and blurred.
+    Blur(BlurFill),
+    /// An image file, like a paper scan, over the border color.
+    Texture(TextureFill),
+}
+
+impl BorderFill {
+    pub fn is_solid(&self) -> bool {
+        *self == BorderFill::Solid
+    }
+
+    /// A `width`×`height` canvas of the fill for `img`, starting from
+    /// `color`. Fails only when a texture can't be read.
+    pub fn canvas(
+        &self,
+        img: &DynamicImage,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<RgbaImage, ProcessError> {
+        Ok(match self {
+            BorderFill::Solid => RgbaImage::from_pixel(width, height, color),
+            BorderFill::Gradient(gradient) => gradient.render(width, height, color),
+            BorderFill::Blur(blur) => blur.render(img, width, height, color),
+            BorderFill::Texture(texture) => texture.render(width, height, color)?,
+        })
+    }
+
+    /// Short description like "linear gradient to #203040, 45°", or
+    /// `None` for a solid fill.
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            BorderFill::Solid => None,
+            BorderFill::Gradient(gradient) => {
+                let [r, g, b, _] = gradient.color.0;
+                let color = format!("#{:02X}{:02X}{:02X}", r, g, b);
+                Some(match gradient.shape {
+                    GradientShape::Linear => t!(
+                        "fill-linear-summary",
+                        color = color,
+                        angle = gradient.angle.round()
+                    ),
+                    GradientShape::Radial => t!("fill-radial-summary", color = color),
+                })
+            }
+            BorderFill::Blur(blur) => {
+                let strength = i18n::decimal(blur.strength as f64, 1);
+                Some(if blur.tint > 0.0 {
+                    t!(
+                        "fill-blur-tinted-summary",
+                        strength = strength,
+                        tint = i18n::decimal(blur.tint as f64, 0)
+                    )
+                } else {
+                    t!("fill-blur-summary", strength = strength)
+                })
+            }
+            BorderFill::Texture(texture) => {
+                let name = texture
+                    .path
+                    .file_name()
+                    .unwrap_or(texture.path.as_os_str())
+                    .to_string_lossy();
+                Some(match texture.mode {
+                    TextureMode::Tile => t!("fill-texture-tile-summary", name = name),
+                    TextureMode::Stretch => t!("fill-texture-stretch-summary", name = name),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum GradientShape {
+    /// Straight across the canvas at `angle`.
+    #[default]
+    Linear,
+    /// From the center out to the corners.
+    Radial,
+}
+
+/// A gradient from the border color to `color`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    /// The color the gradient ends in.
+    #[serde(with = "hex_color")]
+    pub color: Rgba<u8>,
+    /// Direction of a linear gradient in degrees clockwise: 0 runs left
+    /// to right, 90 top to bottom.
+    pub angle: f32,
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Gradient {
+            shape: GradientShape::Linear,
+            color: Rgba([0, 0, 0, 255]),
+            angle: 90.0,
+        }
+    }
+}
+
+impl Gradient {
+    /// A `width`×`height` canvas going from `from` to `color`.
+    pub fn render(&self, width: u32, height: u32, from: Rgba<u8>) -> RgbaImage {
+        let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        // Half of how far the canvas reaches along the direction, or the
+        // distance to the corners.
+        let reach = match self.shape {
+            GradientShape::Linear => (cos.abs() * width as f32 + sin.abs() * height as f32) / 2.0,
+            GradientShape::Radial => center_x.hypot(center_y),
+        }
+        .max(f32::EPSILON);
+        RgbaImage::from_fn(width, height, |x, y| {
+            let (dx, dy) = (x as f32 + 0.5 - center_x, y as f32 + 0.5 - center_y);
+            let position = match self.shape {
+                GradientShape::Linear => ((dx * cos + dy * sin) / reach + 1.0) / 2.0,
+                GradientShape::Radial => dx.hypot(dy) / reach,
+            }
+            .clamp(0.0, 1.0);
+            mix(from, self.color, position)
+        })
+    }
+}
+
+/// Side of the small copy the blur is worked out on. Heavy blurs keep
+/// only the broad colors, so doing it at full size buys nothing.
+const BLUR_SIZE: u32 = 256;
+
+/// A blurred copy of the photo behind it, as in letterboxed stories.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlurFill {
+    /// Blur radius in percent of the longest side of the canvas.
+    pub strength: f32,
+    /// How far the blur is blended towards the border color, in percent.
+    pub tint: f32,
+}
+
+impl Default for BlurFill {
+    fn default() -> Self {
+        BlurFill {
+            strength: 5.0,
+            tint: 20.0,
+        }
+    }
+}
+
+impl BlurFill {
+    /// `img` scaled to cover a `width`×`height` canvas, cropped in the
+    /// middle, blurred and tinted towards `color`.
+    pub fn render(
+        &self,
+        img: &DynamicImage,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> RgbaImage {
+        let scale = (BLUR_SIZE as f32 / width.max(height) as f32).min(1.0);
+        let small = |side: u32| ((side as f32 * scale).round() as u32).max(1);
+        let (small_width, small_height) = (small(width), small(height));
+        let mut blurred = img
+            .resize_to_fill(small_width, small_height, FilterType::Triangle)
+            .to_rgba8();
+        let sigma = self.strength / 100.0 * small_width.max(small_height) as f32;
+        if sigma > 0.0 {
+            blurred = imageops::blur(&blurred, sigma);
+        }
+        let mut canvas = imageops::resize(&blurred, width, height, FilterType::Triangle);
+        let tint = (self.tint / 100.0).clamp(0.0, 1.0);
+        if tint > 0.0 {
+            for pixel in canvas.pixels_mut() {
+                *pixel = mix(*pixel, color, tint);
+            }
+        }
+        canvas
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum TextureMode {
+    /// Repeated at its own size from the top left corner.
+    #[default]
+    Tile,
+    /// Scaled to the canvas, ignoring its shape.
+    Stretch,
+}
+
+/// An image file laid over the border color. Transparent parts of it let
+/// the color show through.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextureFill {
+    pub path: PathBuf,
+    pub mode: TextureMode,
+    /// Size tiles are drawn at relative to the texture's own, which is
+    /// less on a scaled down copy of the photo, like the preview's.
+    #[serde(skip)]
+    pub scale: f32,
+}
+
+impl Default for TextureFill {
+    fn default() -> Self {
+        TextureFill {
+            path: PathBuf::new(),
+            mode: TextureMode::Tile,
+            scale: 1.0,
+        }
+    }
+}
+
+/// A decoded texture and the modification time of its file when it was
+/// read.
+struct CachedTexture {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    image: Arc<RgbaImage>,
+}
+
+/// Textures decoded so far. A batch fills every border from the same
+/// file, which is decoded once.
+static TEXTURES: Mutex<Vec<CachedTexture>> = Mutex::new(Vec::new());
+
+/// How many textures stay decoded; switching back and forth in the
+/// preview shouldn't decode them again.
+const CACHED_TEXTURES: usize = 4;
+
+impl TextureFill {
+    /// A `width`×`height` canvas of `color` with the texture over it.
+    pub fn render(
+        &self,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<RgbaImage, ProcessError> {
+        let texture = load_texture(&self.path)?;
+        let mut canvas = RgbaImage::from_pixel(width, height, color);
+        match self.mode {
+            TextureMode::Tile => {
+                let texture = if self.scale == 1.0 {
+                    texture
+                } else {
+                    let side = |side: u32| ((side as f32 * self.scale).round() as u32).max(1);
+                    let (width, height) = texture.dimensions();
+                    Arc::new(imageops::resize(
+                        &*texture,
+                        side(width),
+                        side(height),
+                        FilterType::Triangle,
+                    ))
+                };
+                let (tile_width, tile_height) = texture.dimensions();
+                for y in (0..height).step_by(tile_height.max(1) as usize) {
+                    for x in (0..width).step_by(tile_width.max(1) as usize) {
+                        imageops::overlay(&mut canvas, &*texture, x as i64, y as i64);
+                    }
+                }
+            }
+            TextureMode::Stretch => {
+                let stretched = imageops::resize(&*texture, width, height, FilterType::Triangle);
+                imageops::overlay(&mut canvas, &stretched, 0, 0);
+            }
+        }
+        Ok(canvas)
+    }
+}
+
+/// The texture at `path`, decoded unless it was already and the file
+/// hasn't changed since.
+fn load_texture(path: &Path) -> Result<Arc<RgbaImage>, ProcessError> {
+    let modified = fs::metadata(path)
+        .map_err(|e| ProcessError::io(path, e))?
+        .modified()
+        .ok();
+    // Held while decoding, so parallel workers wait for the first one
+    // instead of all decoding the same file.
+    let mut textures = TEXTURES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(index) = textures.iter().position(|cached| cached.path == path) {
+        // Moved to the end, so the least recently used goes first.
+        let cached = textures.remove(index);
+        if cached.modified == modified {
+            let image = cached.image.clone();
+            textures.push(cached);
+            return Ok(image);
+        }
+    }
+    let image = Arc::new(
+        open_oriented(path)
+            .map_err(|e| ProcessError::decode(path, e))?
+            .to_rgba8(),
+    );
+    if textures.len() >= CACHED_TEXTURES {
+        textures.remove(0);
+    }
+    textures.push(CachedTexture {
+        path: path.to_path_buf(),
+        modified,
+        image: image.clone(),
+    });
+    Ok(image)
+}
+
+/// `from` moved `amount` of the way to `to`, channel by channel with
+/// straight alpha.
+fn mix(from: Rgba<u8>, to: Rgba<u8>, amount: f32) -> Rgba<u8> {
+    let mut pixel = from;
+    for (channel, to) in pixel.0.iter_mut().zip(to.0) {
+        *channel = (*channel as f32 + (to as f32 - *channel as f32) * amount).round() as u8;
+    }
+    pixel
+}
@@ -0,0 +1,119 @@
+//! Output formats and their encoder options.
+
+use image::codecs::png;
+use serde::{Deserialize, Serialize};
+
+use crate::encoder::{self, Encoder};
+
+/// Encoding of the written files.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    Tiff,
+    Avif,
+    Webp,
+}
+
+impl OutputFormat {
+    /// Writes files in this format.
+    pub fn encoder(self) -> &'static dyn Encoder {
+        match self {
+            OutputFormat::Png => &encoder::Png,
+            OutputFormat::Jpeg => &encoder::Jpeg,
+            OutputFormat::Tiff => &encoder::Tiff,
+            OutputFormat::Avif => &encoder::Avif,
+            OutputFormat::Webp => &encoder::Webp,
+        }
+    }
+
+    /// File extension written for this format.
+    pub fn extension(self) -> &'static str {
+        self.encoder().extension()
+    }
+}
+
+/// Encoder options for every output format, keyed by format name when
+/// serialized. Each format keeps its own values so switching formats back and
+/// forth never loses a setting. Formats without options have no entry.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatSettings {
+    pub png: PngSettings,
+    pub jpeg: JpegSettings,
+    pub avif: AvifSettings,
+}
+
+impl FormatSettings {
+    /// Quality `format` is encoded with, if it has a quality setting.
+    pub fn quality(&self, format: OutputFormat) -> Option<u8> {
+        match format {
+            OutputFormat::Jpeg => Some(self.jpeg.quality),
+            OutputFormat::Avif => Some(self.avif.quality),
+            OutputFormat::Png | OutputFormat::Tiff | OutputFormat::Webp => None,
+        }
+    }
+}
+
+/// PNG encoder options.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PngSettings {
+    pub compression: PngCompression,
+}
+
+/// Trade-off between PNG file size and encoding time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PngCompression {
+    #[default]
+    Fast,
+    Default,
+    Best,
+}
+
+impl PngCompression {
+    /// The matching option of the `image` crate.
+    pub fn to_image(self) -> png::CompressionType {
+        match self {
+            PngCompression::Fast => png::CompressionType::Fast,
+            PngCompression::Default => png::CompressionType::Default,
+            PngCompression::Best => png::CompressionType::Best,
+        }
+    }
+}
+
+/// JPEG encoder options.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JpegSettings {
+    /// 1-100.
+    pub quality: u8,
+}
+
+impl Default for JpegSettings {
+    fn default() -> Self {
+        JpegSettings { quality: 80 }
+    }
+}
+
+/// AVIF encoder options.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AvifSettings {
+    /// 1-100.
+    pub quality: u8,
+    /// 1 (slowest, smallest) to 10 (fastest).
+    pub speed: u8,
+}
+
+impl Default for AvifSettings {
+    fn default() -> Self {
+        AvifSettings {
+            quality: 80,
+            speed: 4,
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use std::{fmt, str::FromStr};
+
+use image::Rgba;
+use serde::{Deserialize, Serialize};
+
+use crate::{pipeline::Border, serialization::hex_color, ProcessInfo};
+
+/// Largest side the resize setting accepts, which is also the limit of JPEG.
+/// The dimension helpers don't overflow for sides up to this size.
+pub const MAX_DIMENSION: u32 = 65_535;
+
+/// Widest border in percent, of the whole border or of one side.
+pub const MAX_BORDER_PERCENTAGE: f32 = 50.0;
+
+/// Widest frame in percent.
+pub const MAX_FRAME_PERCENTAGE: f32 = 25.0;
+
+/// Canvas size and photo placement for a bordered image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    pub canvas_width: u32,
+    pub canvas_height: u32,
+    /// Left edge of the photo on the canvas.
+    pub x_offset: u32,
+    /// Top edge of the photo on the canvas.
+    pub y_offset: u32,
+}
+
+/// How the border is laid out around the photo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderMode {
+    /// Center the photo on a square canvas.
+    Square,
+    /// Add the same border width on every side.
+    Symmetrical,
+}
+
+/// Border width of every side, each in percent of the longest side of the
+/// photo, for mats that are wider at the bottom and the like.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BorderSides {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl BorderSides {
+    /// The sides of a symmetrical border of `percentage`, which splits it
+    /// between opposite sides.
+    pub fn symmetrical(percentage: f32) -> Self {
+        let side = percentage / 2.0;
+        BorderSides {
+            top: side,
+            right: side,
+            bottom: side,
+            left: side,
+        }
+    }
+}
+
+/// Which side of the main border a frame lies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum FramePlacement {
+    /// Between the photo and the border, like a thin line inside a mat.
+    #[default]
+    Inside,
+    /// Around the border, like a frame around a mat.
+    Outside,
+}
+
+/// A second border of its own width and color, stacked on the main one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Frame {
+    /// Width of every side in percent of the longest side of what the
+    /// frame goes around.
+    pub width: f32,
+    #[serde(with = "hex_color")]
+    pub color: Rgba<u8>,
+    pub placement: FramePlacement,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame {
+            width: 1.0,
+            color: Rgba([0, 0, 0, 255]),
+            placement: FramePlacement::Inside,
+        }
+    }
+}
+
+/// A border of the same number of pixels on every photo, however large,
+/// instead of a percentage of its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PixelBorder {
+    /// Pixels on every side.
+    pub width: u32,
+    /// Measures `width` on the resized output rather than on the photo,
+    /// when the output is resized.
+    pub after_resize: bool,
+}
+
+impl Default for PixelBorder {
+    fn default() -> Self {
+        PixelBorder {
+            width: 40,
+            after_resize: false,
+        }
+    }
+}
+
+impl PixelBorder {
+    /// Pixels on every side of a `width`×`height` photo laid out in
+    /// `mode`, when its bordered canvas is padded to `aspect` and resized
+    /// to `resize` pixels. Without a resize, or with one that leaves no
+    /// room for the photo, it is `width` itself.
+    pub fn source_width(
+        &self,
+        width: u32,
+        height: u32,
+        mode: BorderMode,
+        aspect: Option<CanvasAspect>,
+        resize: Option<u32>,
+    ) -> u32 {
+        let Some(resize) = resize.filter(|_| self.after_resize) else {
+            return self.width;
+        };
+        let (inner_width, inner_height) = match mode {
+            BorderMode::Symmetrical => (width, height),
+            BorderMode::Square => (width.max(height), width.max(height)),
+        };
+        let (unit_width, unit_height) = match aspect {
+            Some(aspect) => (aspect.width.max(1) as f64, aspect.height.max(1) as f64),
+            None => (1.0, 1.0),
+        };
+        // Either side of `side + 2b` may end up setting the longest side
+        // of the padded canvas, `(side + 2b) * stretch`, which is scaled
+        // to `resize`. So `b` becomes `width` once
+        // `b * resize / ((side + 2b) * stretch) = width` for the side
+        // that needs the widest border.
+        let widths =
+            [(inner_width, unit_width), (inner_height, unit_height)].map(|(side, unit)| {
+                let stretch = unit_width.max(unit_height) / unit;
+                let room = resize as f64 - 2.0 * self.width as f64 * stretch;
+                (room > 0.0).then(|| self.width as f64 * side as f64 * stretch / room)
+            });
+        match widths {
+            [Some(across), Some(down)] => across.max(down).round() as u32,
+            _ => self.width,
+        }
+    }
+}
+
+/// An exact shape for every canvas, like 4:5 for a portrait post.
+/// Written as "4:5" in files and on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CanvasAspect {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CanvasAspect {
+    /// The shapes offered without typing one in.
+    pub const PRESETS: [CanvasAspect; 5] = [
+        CanvasAspect::new(1, 1),
+        CanvasAspect::new(4, 5),
+        CanvasAspect::new(3, 2),
+        CanvasAspect::new(16, 9),
+        CanvasAspect::new(9, 16),
+    ];
+
+    pub const fn new(width: u32, height: u32) -> Self {
+        CanvasAspect { width, height }
+    }
+
+    /// Width divided by height.
+    pub fn ratio(&self) -> f32 {
+        self.width.max(1) as f32 / self.height.max(1) as f32
+    }
+}
+
+impl fmt::Display for CanvasAspect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.width, self.height)
+    }
+}
+
+impl FromStr for CanvasAspect {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parsed = text.split_once(':').and_then(|(width, height)| {
+            Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+        });
+        match parsed {
+            Some((width, height)) if width > 0 && height > 0 => Ok(CanvasAspect { width, height }),
+            _ => Err(format!("`{}` is not an aspect ratio like 4:5", text)),
+        }
+    }
+}
+
+impl TryFrom<String> for CanvasAspect {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<CanvasAspect> for String {
+    fn from(aspect: CanvasAspect) -> Self {
+        aspect.to_string()
+    }
+}
+
+/// Lays out a `width`×`height` photo on its bordered canvas, through
+/// every border layer, before any resize.
+pub fn compute_geometry(width: u32, height: u32, info: &ProcessInfo) -> Geometry {
+    let photo = Geometry {
+        canvas_width: width,
+        canvas_height: height,
+        x_offset: 0,
+        y_offset: 0,
+    };
+    Border::layers(info).iter().fold(photo, |inner, layer| {
+        let outer = layer.geometry(inner.canvas_width, inner.canvas_height);
+        Geometry {
+            x_offset: outer.x_offset + inner.x_offset,
+            y_offset: outer.y_offset + inner.y_offset,
+            ..outer
+        }
+    })
+}
+
+/// Lays out a `width`×`height` photo with a border of `percentage` of its
+/// longest side. The canvas is truncated to whole pixels and the photo is
+/// centered, rounding its offsets down. Negative percentages count as 0.
+pub fn border_geometry(width: u32, height: u32, mode: BorderMode, percentage: f32) -> Geometry {
+    let longest_side = width.max(height);
+    let new_size = (longest_side as f32 * (1.0 + percentage.max(0.0) / 100.0)) as u32;
+
+    let (canvas_width, canvas_height) = match mode {
+        BorderMode::Symmetrical => {
+            let delta = new_size - longest_side;
+            (width + delta, height + delta)
+        }
+        BorderMode::Square => (new_size, new_size),
+    };
+
+    Geometry {
+        canvas_width,
+        canvas_height,
+        x_offset: (canvas_width - width) / 2,
+        y_offset: (canvas_height - height) / 2,
+    }
+}
+
+/// Lays out a `width`×`height` photo with `pixels` of border on every side
+/// of its longest side, the same as a percentage border of that many
+/// pixels would.
+pub fn pixel_geometry(width: u32, height: u32, mode: BorderMode, pixels: u32) -> Geometry {
+    let longest_side = width.max(height);
+    let border = pixels.saturating_mul(2);
+    let (canvas_width, canvas_height) = match mode {
+        BorderMode::Symmetrical => (width.saturating_add(border), height.saturating_add(border)),
+        BorderMode::Square => {
+            let side = longest_side.saturating_add(border);
+            (side, side)
+        }
+    };
+    Geometry {
+        canvas_width,
+        canvas_height,
+        x_offset: (canvas_width - width) / 2,
+        y_offset: (canvas_height - height) / 2,
+    }
+}
+
+/// Lays out a `width`×`height` photo with its own border width on every
+/// side. Each side is truncated to whole pixels on its own; negative ones
+/// count as 0.
+pub fn sides_geometry(width: u32, height: u32, sides: BorderSides) -> Geometry {
+    let longest_side = width.max(height) as f32;
+    let side = |percentage: f32| (longest_side * percentage.max(0.0) / 100.0) as u32;
+    let (top, right, bottom, left) = (
+        side(sides.top),
+        side(sides.right),
+        side(sides.bottom),
+        side(sides.left),
+    );
+    Geometry {
+        canvas_width: width.saturating_add(left).saturating_add(right),
+        canvas_height: height.saturating_add(top).saturating_add(bottom),
+        x_offset: left,
+        y_offset: top,
+    }
+}
+
+/// Pads the canvas of `geometry` until it has exactly the shape of
+/// `aspect`, keeping the photo centered. The canvas becomes the smallest
+/// whole multiple of the reduced ratio around it, so a resize keeps the
+/// shape exact too; the axis that is too short gets the padding, the
+/// other at most a few pixels of it.
+pub fn fit_aspect(geometry: Geometry, aspect: CanvasAspect) -> Geometry {
+    let (mut unit_width, mut unit_height) =
+        (aspect.width.max(1) as u64, aspect.height.max(1) as u64);
+    let divisor = gcd(unit_width, unit_height);
+    unit_width /= divisor;
+    unit_height /= divisor;
+    let (width, height) = (geometry.canvas_width as u64, geometry.canvas_height as u64);
+    let units = width.div_ceil(unit_width).max(height.div_ceil(unit_height));
+    let clamp = |side: u64| side.min(u32::MAX as u64) as u32;
+    let (canvas_width, canvas_height) = (clamp(units * unit_width), clamp(units * unit_height));
+    Geometry {
+        canvas_width,
+        canvas_height,
+        x_offset: geometry.x_offset + (canvas_width - geometry.canvas_width) / 2,
+        y_offset: geometry.y_offset + (canvas_height - geometry.canvas_height) / 2,
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Dimensions that scale `width`×`height` so the longest side is `longest`,
+/// rounded the same way as `DynamicImage::resize`.
+pub fn resize_target(width: u32, height: u32, longest: u32) -> (u32, u32) {
+    let ratio = longest as f64 / width.max(height) as f64;
+    let scale = |side: u32| ((side as f64 * ratio).round() as u32).max(1);
+    (scale(width), scale(height))
+}
+
+/// Size of the processed output for a source of `width`×`height`, upright
+/// as its EXIF orientation says.
+pub fn output_dimensions(width: u32, height: u32, info: &ProcessInfo) -> (u32, u32) {
+    let (width, height) = info.transform.dimensions(width, height);
+    let (width, height) = info.crop.dimensions(width, height);
+    let geometry = compute_geometry(width, height, info);
+    if info.resize_images {
+        resize_target(
+            geometry.canvas_width,
+            geometry.canvas_height,
+            info.resize_longest_dimension,
+        )
+    } else {
+        (geometry.canvas_width, geometry.canvas_height)
+    }
+}
@@ -0,0 +1,92 @@
+use egui::{pos2, Color32, Painter, Rect, Stroke, StrokeKind, Ui};
+use image_finalizer::t;
+use serde::{Deserialize, Serialize};
+
+use crate::a11y;
+
+/// Composition overlays drawn over the preview. They are purely a display aid
+/// and are never part of the processed output.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuideSettings {
+    pub rule_of_thirds: bool,
+    pub crosshair: bool,
+    pub safe_margin: bool,
+    /// Inset of the safe-margin box, in percent of each image side.
+    pub safe_margin_inset: f32,
+}
+
+impl Default for GuideSettings {
+    fn default() -> Self {
+        GuideSettings {
+            rule_of_thirds: false,
+            crosshair: false,
+            safe_margin: false,
+            safe_margin_inset: 10.0,
+        }
+    }
+}
+
+impl GuideSettings {
+    pub fn controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(t!("guides-label"));
+            ui.checkbox(&mut self.rule_of_thirds, t!("guides-thirds"));
+            ui.checkbox(&mut self.crosshair, t!("guides-center"));
+            ui.checkbox(&mut self.safe_margin, t!("guides-safe-margin"));
+            if self.safe_margin {
+                a11y::named(
+                    ui.add(
+                        egui::DragValue::new(&mut self.safe_margin_inset)
+                            .range(0.0..=45.0)
+                            .speed(0.5)
+                            .suffix("%"),
+                    ),
+                    t!("guides-margin-name"),
+                );
+            }
+        });
+    }
+
+    /// Draws the enabled guides over `rect`, the on-screen area of the image,
+    /// so they follow however the image is scaled or positioned.
+    pub fn paint(&self, painter: &Painter, rect: Rect) {
+        let painter = painter.with_clip_rect(rect);
+        let stroke = Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 255, 255, 160));
+        let shadow = Stroke::new(3.0, Color32::from_rgba_unmultiplied(0, 0, 0, 90));
+
+        let line = |a, b| {
+            painter.line_segment([a, b], shadow);
+            painter.line_segment([a, b], stroke);
+        };
+
+        if self.rule_of_thirds {
+            for fraction in [1.0 / 3.0, 2.0 / 3.0] {
+                let x = rect.left() + rect.width() * fraction;
+                let y = rect.top() + rect.height() * fraction;
+                line(pos2(x, rect.top()), pos2(x, rect.bottom()));
+                line(pos2(rect.left(), y), pos2(rect.right(), y));
+            }
+        }
+
+        if self.crosshair {
+            let center = rect.center();
+            let arm = rect.width().min(rect.height()) * 0.05;
+            line(
+                pos2(center.x - arm, center.y),
+                pos2(center.x + arm, center.y),
+            );
+            line(
+                pos2(center.x, center.y - arm),
+                pos2(center.x, center.y + arm),
+            );
+        }
+
+        if self.safe_margin {
+            let inset = self.safe_margin_inset / 100.0;
+            let safe = rect.shrink2(rect.size() * inset);
+            painter.rect_stroke(safe, 0.0, shadow, StrokeKind::Middle);
+            painter.rect_stroke(safe, 0.0, stroke, StrokeKind::Middle);
+        }
+    }
+}
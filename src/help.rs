@@ -0,0 +1,229 @@
+//! The "?" buttons next to settings whose effect is hard to put in words.
+//! Their examples are rendered from a small embedded photo by the same
+//! code that processes real images, so they can't drift from what a batch
+//! does, and need no network.
+
+use std::{collections::HashMap, path::Path};
+
+use egui::{TextureHandle, TextureOptions, Ui};
+use image::{imageops::FilterType, DynamicImage, Rgba};
+use image_finalizer::{
+    fill::BorderFill,
+    format::{FormatSettings, OutputFormat, PngCompression},
+    i18n,
+    pipeline::{Border, FileContext, ProcessingStep, Resize},
+    t, BorderMode,
+};
+
+use crate::{a11y, to_color_image};
+
+const SAMPLE: &[u8] = include_bytes!("../assets/help-sample.png");
+
+/// How much larger than the sample examples are drawn, so its pixels show.
+const ZOOM: f32 = 3.0;
+
+/// A setting with help.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    BorderMode,
+    ResizeFilter,
+    PngCompression,
+    JpegQuality,
+    AvifQuality,
+}
+
+impl Topic {
+    fn explanation(self) -> &'static str {
+        match self {
+            Topic::BorderMode => t!("help-border-mode"),
+            Topic::ResizeFilter => t!("help-resize-filter"),
+            Topic::PngCompression => t!("help-png-compression"),
+            Topic::JpegQuality => t!("help-jpeg-quality"),
+            Topic::AvifQuality => t!("help-avif-quality"),
+        }
+    }
+}
+
+/// One rendered example: a picture, or only a caption where the result
+/// can't be shown.
+struct Example {
+    caption: String,
+    texture: Option<TextureHandle>,
+}
+
+/// The help popups, with examples rendered the first time they are opened.
+#[derive(Default)]
+pub struct Help {
+    examples: HashMap<Topic, Vec<Example>>,
+}
+
+impl Help {
+    /// A "?" button that opens the help on `topic` below it.
+    pub fn button(&mut self, ui: &mut Ui, topic: Topic) {
+        let response = a11y::small_icon_button(ui, "?", t!("help-hover"));
+        let id = ui.make_persistent_id(("help", topic));
+        if response.clicked() {
+            ui.memory_mut(|memory| memory.toggle_popup(id));
+        }
+        egui::popup::popup_below_widget(
+            ui,
+            id,
+            &response,
+            egui::PopupCloseBehavior::CloseOnClickOutside,
+            |ui| {
+                ui.set_max_width(400.0);
+                ui.label(topic.explanation());
+                let examples = self
+                    .examples
+                    .entry(topic)
+                    .or_insert_with(|| render(ui.ctx(), topic));
+                ui.horizontal_wrapped(|ui| {
+                    for example in examples.iter() {
+                        ui.vertical(|ui| {
+                            if let Some(texture) = &example.texture {
+                                ui.add(
+                                    egui::Image::new((texture.id(), texture.size_vec2() * ZOOM))
+                                        .alt_text(&example.caption)
+                                        .bg_fill(ui.visuals().extreme_bg_color),
+                                );
+                            }
+                            ui.small(&example.caption);
+                        });
+                    }
+                });
+            },
+        );
+    }
+}
+
+fn render(ctx: &egui::Context, topic: Topic) -> Vec<Example> {
+    let sample = image::load_from_memory(SAMPLE).expect("the help sample is a valid PNG");
+    let file = FileContext {
+        path: Path::new("help-sample.png"),
+    };
+    let picture = |name: &str, caption: String, img: &DynamicImage| Example {
+        caption,
+        texture: Some(ctx.load_texture(
+            format!("help_{}", name),
+            to_color_image(img),
+            TextureOptions::NEAREST,
+        )),
+    };
+    let failed = |caption: &str, e: &dyn std::fmt::Display| Example {
+        caption: format!("{}: {}", caption, e),
+        texture: None,
+    };
+
+    match topic {
+        Topic::BorderMode => [
+            (BorderMode::Square, t!("help-border-default")),
+            (BorderMode::Symmetrical, t!("help-border-symmetrical")),
+        ]
+        .into_iter()
+        .map(|(mode, caption)| {
+            let border = Border {
+                mode,
+                percentage: 15.0,
+                sides: None,
+                pixels: None,
+                resize: None,
+                aspect: None,
+                corners: None,
+                shadow: None,
+                keyline: None,
+                color: Rgba([255, 255, 255, 255]),
+                fill: BorderFill::Solid,
+            };
+            match border.apply(sample.clone(), &file) {
+                Ok(img) => picture(caption, caption.to_string(), &img),
+                Err(e) => failed(caption, &e),
+            }
+        })
+        .collect(),
+        Topic::ResizeFilter => {
+            // The fence and the tree, blown up to show how edges fare.
+            let detail = sample.crop_imm(26, 10, 16, 16);
+            [
+                (FilterType::Nearest, "Nearest"),
+                (FilterType::Triangle, "Triangle"),
+                (FilterType::CatmullRom, "CatmullRom"),
+                (FilterType::Lanczos3, "Lanczos3"),
+            ]
+            .into_iter()
+            .map(|(filter, caption)| {
+                let resize = Resize {
+                    longest_dimension: (16.0 * ZOOM) as u32,
+                    filter,
+                };
+                match resize.apply(detail.clone(), &file) {
+                    Ok(img) => picture(caption, caption.to_string(), &img),
+                    Err(e) => failed(caption, &e),
+                }
+            })
+            .collect()
+        }
+        Topic::PngCompression => [
+            (PngCompression::Fast, t!("png-fast")),
+            (PngCompression::Default, t!("png-default")),
+            (PngCompression::Best, t!("help-png-best")),
+        ]
+        .into_iter()
+        .map(|(compression, caption)| {
+            let mut settings = FormatSettings::default();
+            settings.png.compression = compression;
+            match encode(&sample, OutputFormat::Png, &settings) {
+                Ok(bytes) => Example {
+                    caption: format!("{}: {}", caption, i18n::bytes(bytes.len() as u64)),
+                    texture: None,
+                },
+                Err(e) => failed(caption, &e),
+            }
+        })
+        .collect(),
+        Topic::JpegQuality => [10, 40, 80, 95]
+            .into_iter()
+            .map(|quality| {
+                let caption = t!("help-quality", quality = quality);
+                let mut settings = FormatSettings::default();
+                settings.jpeg.quality = quality;
+                let decoded = encode(&sample, OutputFormat::Jpeg, &settings)
+                    .and_then(|bytes| Ok((bytes.len() as u64, image::load_from_memory(&bytes)?)));
+                match decoded {
+                    Ok((size, img)) => picture(
+                        &caption,
+                        format!("{}: {}", caption, i18n::bytes(size)),
+                        &img,
+                    ),
+                    Err(e) => failed(&caption, &e),
+                }
+            })
+            .collect(),
+        // No AVIF decoder is built in, so only the sizes can be shown.
+        Topic::AvifQuality => [30, 60, 80, 95]
+            .into_iter()
+            .map(|quality| {
+                let caption = t!("help-quality", quality = quality);
+                let mut settings = FormatSettings::default();
+                settings.avif.quality = quality;
+                settings.avif.speed = 6;
+                match encode(&sample, OutputFormat::Avif, &settings) {
+                    Ok(bytes) => Example {
+                        caption: format!("{}: {}", caption, i18n::bytes(bytes.len() as u64)),
+                        texture: None,
+                    },
+                    Err(e) => failed(&caption, &e),
+                }
+            })
+            .collect(),
+    }
+}
+
+fn encode(
+    img: &DynamicImage,
+    format: OutputFormat,
+    settings: &FormatSettings,
+) -> image::ImageResult<Vec<u8>> {
+    let mut bytes = vec![];
+    format.encoder().encode(img, &mut bytes, settings)?;
+    Ok(bytes)
+}
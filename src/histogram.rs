@@ -0,0 +1,84 @@
+use egui::{pos2, vec2, Color32, Rect, Sense, Shape, Stroke, Ui};
+use image::DynamicImage;
+
+const HISTOGRAM_HEIGHT: f32 = 100.0;
+
+/// Per-channel 256-bin histogram of a composited image.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    red: [u32; 256],
+    green: [u32; 256],
+    blue: [u32; 256],
+    luma: [u32; 256],
+}
+
+impl Histogram {
+    pub fn from_image(img: &DynamicImage) -> Self {
+        let mut histogram = Histogram {
+            red: [0; 256],
+            green: [0; 256],
+            blue: [0; 256],
+            luma: [0; 256],
+        };
+
+        for pixel in img.to_rgb8().pixels() {
+            let [r, g, b] = pixel.0;
+            histogram.red[r as usize] += 1;
+            histogram.green[g as usize] += 1;
+            histogram.blue[b as usize] += 1;
+
+            // Rec. 709 weights in 8-bit fixed point
+            let luma = (54 * r as u32 + 183 * g as u32 + 19 * b as u32) >> 8;
+            histogram.luma[luma as usize] += 1;
+        }
+
+        histogram
+    }
+
+    pub fn show(&self, ui: &mut Ui) {
+        let width = ui.available_width().min(512.0);
+        let (response, painter) =
+            ui.allocate_painter(vec2(width, HISTOGRAM_HEIGHT), Sense::hover());
+        let rect = response.rect;
+
+        painter.rect_filled(rect, 2.0, Color32::from_gray(24));
+
+        let max = [&self.red, &self.green, &self.blue, &self.luma]
+            .iter()
+            .flat_map(|bins| bins.iter())
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1) as f32;
+
+        let to_point = |bin: usize, count: u32| {
+            pos2(
+                rect.left() + bin as f32 / 256.0 * rect.width(),
+                rect.bottom() - count as f32 / max * rect.height(),
+            )
+        };
+
+        let bin_width = rect.width() / 256.0;
+        for (bin, &count) in self.luma.iter().enumerate() {
+            let top = to_point(bin, count);
+            painter.rect_filled(
+                Rect::from_min_max(top, pos2(top.x + bin_width, rect.bottom())),
+                0.0,
+                Color32::from_gray(90),
+            );
+        }
+
+        for (bins, color) in [
+            (&self.red, Color32::from_rgb(230, 70, 70)),
+            (&self.green, Color32::from_rgb(70, 200, 70)),
+            (&self.blue, Color32::from_rgb(80, 120, 240)),
+        ] {
+            let points = bins
+                .iter()
+                .enumerate()
+                .map(|(bin, &count)| to_point(bin, count))
+                .collect();
+            painter.add(Shape::line(points, Stroke::new(1.0, color)));
+        }
+    }
+}
@@ -0,0 +1,56 @@
+/// Undo/redo stack of settings snapshots. The oldest snapshots are dropped
+/// once `limit` is reached.
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    current: T,
+    limit: usize,
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    pub fn new(current: T, limit: usize) -> Self {
+        History {
+            undo: vec![],
+            redo: vec![],
+            current,
+            limit,
+        }
+    }
+
+    /// Makes `state` the current snapshot if it differs from the last one.
+    /// Recording a new state discards everything that could be redone.
+    pub fn record(&mut self, state: T) {
+        if state == self.current {
+            return;
+        }
+
+        let previous = std::mem::replace(&mut self.current, state);
+        self.undo.push(previous);
+        if self.undo.len() > self.limit {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self) -> Option<T> {
+        let previous = self.undo.pop()?;
+        let current = std::mem::replace(&mut self.current, previous);
+        self.redo.push(current);
+        Some(self.current.clone())
+    }
+
+    pub fn redo(&mut self) -> Option<T> {
+        let next = self.redo.pop()?;
+        let current = std::mem::replace(&mut self.current, next);
+        self.undo.push(current);
+        Some(self.current.clone())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
@@ -0,0 +1,225 @@
+//! A command run after each output is written, to chain steps of one's own
+//! like `exiftool` or a copy into a hot folder.
+//!
+//! The command is split into arguments before the placeholders are filled
+//! in, and run without a shell, so file names can't inject anything.
+
+use std::{path::Path, process::Stdio, time::Duration};
+
+use image::ImageFormat;
+use serde::{Deserialize, Serialize};
+
+use crate::{t, validation, ChosenSettings, ProcessOutcome};
+
+/// Seconds a hook may run before it is stopped, unless set.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Placeholders of a hook command, for help texts.
+pub const PLACEHOLDERS: [&str; 5] = ["{output}", "{source}", "{format}", "{width}", "{height}"];
+
+/// What a hook that fails or times out means for its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailure {
+    /// The file counts as done; the failure is logged.
+    #[default]
+    Warn,
+    /// The file counts as failed.
+    Fail,
+}
+
+/// Command run for every output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookSettings {
+    /// Program and arguments, like `exiftool -overwrite_original
+    /// "-Artist=Ana" {output}`. Quotes group words.
+    pub command: String,
+    pub timeout_secs: u64,
+    pub on_failure: HookFailure,
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        HookSettings {
+            command: String::new(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            on_failure: HookFailure::default(),
+        }
+    }
+}
+
+/// Why a hook failed.
+#[derive(Debug, thiserror::Error)]
+pub enum HookError {
+    #[error("{}", t!("hook-empty"))]
+    Empty,
+    #[error("{}", t!("hook-unclosed-quote"))]
+    UnclosedQuote,
+    #[error("{}", t!("hook-spawn", program = .program, error = .source))]
+    Spawn {
+        program: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{}", t!("hook-timeout", seconds = .0))]
+    Timeout(u64),
+    #[error("{}", t!("hook-exit", status = .status, stderr = .stderr))]
+    Exit { status: String, stderr: String },
+}
+
+/// The output a hook runs for, filling in its placeholders.
+#[derive(Debug, Clone, Copy)]
+pub struct HookFile<'a> {
+    pub output: &'a Path,
+    pub source: &'a Path,
+    /// Like `jpeg`, as in name templates.
+    pub format: &'a str,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Splits `command` into words at whitespace. Text in single or double
+/// quotes stays one word, quotes removed. Backslashes are kept as they are,
+/// for Windows paths.
+pub fn parse_command(command: &str) -> Result<Vec<String>, HookError> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(HookError::UnclosedQuote);
+    }
+    if in_word {
+        words.push(word);
+    }
+    if words.is_empty() {
+        return Err(HookError::Empty);
+    }
+    Ok(words)
+}
+
+/// `words` with the placeholders filled in for `file`.
+pub fn expand(words: &[String], file: &HookFile) -> Vec<String> {
+    words
+        .iter()
+        .map(|word| {
+            word.replace("{output}", &file.output.to_string_lossy())
+                .replace("{source}", &file.source.to_string_lossy())
+                .replace("{format}", file.format)
+                .replace("{width}", &file.width.to_string())
+                .replace("{height}", &file.height.to_string())
+        })
+        .collect()
+}
+
+/// Runs the hook of `settings` for `file` and waits for it, stopping it
+/// after the timeout. What it prints goes to the log.
+///
+/// Blocks; call it from a thread that doesn't run an async runtime.
+pub fn run(settings: &HookSettings, file: &HookFile) -> Result<(), HookError> {
+    let words = expand(&parse_command(&settings.command)?, file);
+    let (program, args) = words.split_first().ok_or(HookError::Empty)?;
+    let spawn_error = |source| HookError::Spawn {
+        program: program.clone(),
+        source,
+    };
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(spawn_error)?;
+    let output = runtime.block_on(async {
+        let mut command = tokio::process::Command::new(program);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            // Stops it when the timeout drops the future.
+            .kill_on_drop(true);
+        let timeout = Duration::from_secs(settings.timeout_secs);
+        match tokio::time::timeout(timeout, command.output()).await {
+            Ok(output) => output.map_err(spawn_error),
+            Err(_) => Err(HookError::Timeout(settings.timeout_secs)),
+        }
+    })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let output_path = file.output.display();
+    if !stdout.trim().is_empty() {
+        tracing::info!("hook for {} printed: {}", output_path, stdout.trim_end());
+    }
+    if !stderr.trim().is_empty() {
+        tracing::warn!("hook for {} reported: {}", output_path, stderr.trim_end());
+    }
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(HookError::Exit {
+            status: output.status.to_string(),
+            stderr: stderr.lines().last().unwrap_or_default().trim().to_string(),
+        })
+    }
+}
+
+/// Runs the hook of `settings` once for every file written for
+/// `outcome`: split tiles, copies for websites and kept originals too,
+/// each with its own format and size. Every file gets its run even when
+/// one fails; the first failure is returned.
+pub fn run_for(
+    settings: &HookSettings,
+    chosen: &ChosenSettings,
+    outcome: &ProcessOutcome,
+) -> Result<(), HookError> {
+    let chosen_format = chosen
+        .info
+        .output_format
+        .encoder()
+        .name()
+        .to_ascii_lowercase();
+    let mut first_error = None;
+    for output in &outcome.output_paths {
+        // Named like the encoders, `jpeg` rather than `jpg`.
+        let format = ImageFormat::from_path(output)
+            .map(|format| format!("{:?}", format).to_ascii_lowercase())
+            .unwrap_or_else(|_| chosen_format.clone());
+        // Headers of formats that can't be read back, like AVIF, only
+        // come from the main output.
+        let (width, height) = validation::read_header(output)
+            .map_or(outcome.final_dimensions, |(dimensions, _)| dimensions);
+        let result = run(
+            settings,
+            &HookFile {
+                output,
+                source: &outcome.input,
+                format: &format,
+                width,
+                height,
+            },
+        );
+        if let Err(e) = result {
+            first_error.get_or_insert(e);
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
@@ -0,0 +1,235 @@
+//! Translations of the texts shown to users. Each language is a file in
+//! `locales/`, built into the program, of `key = text` lines in the syntax
+//! of Fluent messages without selectors: `{ $name }` is replaced by the
+//! argument `name`, other braces are kept as they are, indented lines
+//! continue the text on a new line and `#` starts a comment.
+//!
+//! The language is global, as nearly every text needs it. It is English
+//! until a front end chooses another one, so the command line is unchanged.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        OnceLock,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use time::{format_description, PrimitiveDateTime};
+
+/// A bundled language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// Name of the language in itself, for the language menu.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// The bundled language of a locale such as "de-AT" or "de_DE.UTF-8",
+    /// if there is one.
+    pub fn from_locale(locale: &str) -> Option<Language> {
+        let primary = locale.split(['-', '_', '.', '@']).next()?;
+        match primary.to_ascii_lowercase().as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            _ => None,
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.ftl"),
+            Language::German => include_str!("../locales/de.ftl"),
+        }
+    }
+
+    /// `n` with the digit grouping of this language, like "12,345".
+    pub fn number(self, n: u64) -> String {
+        let separator = match self {
+            Language::English => ',',
+            Language::German => '.',
+        };
+        let digits = n.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// `value` with `decimals` digits after the decimal separator of this
+    /// language.
+    pub fn decimal(self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value);
+        match self {
+            Language::English => text,
+            Language::German => text.replace('.', ","),
+        }
+    }
+
+    /// Day and time of `time` as they are written in this language, like
+    /// "15 Oct 2026, 14:03".
+    pub fn date_time(self, time: PrimitiveDateTime) -> String {
+        let pattern = match self {
+            Language::English => "[day padding:none] [month repr:short] [year], [hour]:[minute]",
+            Language::German => "[day].[month].[year], [hour]:[minute]",
+        };
+        format_description::parse(pattern)
+            .ok()
+            .and_then(|format| time.format(&format).ok())
+            .unwrap_or_else(|| time.to_string())
+    }
+}
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+/// Makes `language` the one of every text from now on.
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    Language::ALL[LANGUAGE.load(Ordering::Relaxed) as usize]
+}
+
+/// The messages of `language`, read on first use.
+fn catalog(language: Language) -> &'static HashMap<&'static str, String> {
+    static CATALOGS: [OnceLock<HashMap<&'static str, String>>; Language::ALL.len()] =
+        [const { OnceLock::new() }; Language::ALL.len()];
+    CATALOGS[language as usize].get_or_init(|| parse(language.source()))
+}
+
+fn parse(source: &'static str) -> HashMap<&'static str, String> {
+    let mut messages = HashMap::new();
+    let mut current: Option<(&str, String)> = None;
+    for line in source.lines() {
+        if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            if let Some((_, text)) = &mut current {
+                text.push('\n');
+                text.push_str(line.trim());
+            }
+            continue;
+        }
+        messages.extend(current.take());
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, text)) = line.split_once('=') {
+            current = Some((key.trim(), text.trim().to_string()));
+        }
+    }
+    messages.extend(current);
+    messages
+}
+
+/// The text of `key` in `language`, without filling in arguments.
+pub fn lookup(language: Language, key: &str) -> Option<&'static str> {
+    catalog(language).get(key).map(String::as_str)
+}
+
+/// Every key of `language`, sorted.
+pub fn keys(language: Language) -> Vec<&'static str> {
+    let mut keys: Vec<_> = catalog(language).keys().copied().collect();
+    keys.sort_unstable();
+    keys
+}
+
+/// The text of `key` in the current language. Falls back to English, and
+/// then to the key itself, so a missing text is visible but harmless.
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(language(), key)
+        .or_else(|| lookup(Language::English, key))
+        .unwrap_or(key)
+}
+
+/// The text of `key` with each `{ $name }` replaced by its value in `args`.
+pub fn tr_args(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    let text = tr(key);
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let placeable = &rest[start..];
+        let Some(end) = placeable.find('}') else {
+            rest = placeable;
+            break;
+        };
+        let value = placeable[1..end]
+            .trim()
+            .strip_prefix('$')
+            .and_then(|name| args.iter().find(|(arg, _)| *arg == name));
+        match value {
+            Some((_, value)) => filled.push_str(&value.to_string()),
+            None => filled.push_str(&placeable[..=end]),
+        }
+        rest = &placeable[end + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// `n` with the digit grouping of the current language.
+pub fn number(n: impl TryInto<u64>) -> String {
+    language().number(n.try_into().unwrap_or(0))
+}
+
+/// `value` with `decimals` digits in the current language.
+pub fn decimal(value: f64, decimals: usize) -> String {
+    language().decimal(value, decimals)
+}
+
+/// `bytes` in the largest unit that keeps it at least 1, like "1.5 MB".
+pub fn bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{} {}", decimal(value, 1), UNITS[unit])
+    }
+}
+
+/// Day and time of `time` in the current language.
+pub fn date_time(time: PrimitiveDateTime) -> String {
+    language().date_time(time)
+}
+
+/// The text of a key in the current language: a `&'static str` for a key
+/// alone, a `String` with the named arguments filled in otherwise, like
+/// `t!("clear-deleted", count = i18n::number(deleted))`.
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr_args(
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
@@ -0,0 +1,83 @@
+//! A thin line around the photo, as on gallery mats.
+
+use image::{Pixel, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::serialization::hex_color;
+
+/// Stroke drawn on the border right around the photo.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keyline {
+    /// In pixels of the photo.
+    pub width: f32,
+    #[serde(with = "hex_color")]
+    pub color: Rgba<u8>,
+}
+
+impl Default for Keyline {
+    fn default() -> Self {
+        Keyline {
+            width: 2.0,
+            color: Rgba([0, 0, 0, 255]),
+        }
+    }
+}
+
+impl Keyline {
+    /// Draws the line around a `width`×`height` photo whose top left
+    /// corner lies at `x`, `y` of `canvas`, following corners rounded to
+    /// `radius`. Drawn before the photo, whose edge covers the inner
+    /// edge of the line.
+    pub fn draw(
+        &self,
+        canvas: &mut RgbaImage,
+        (x, y): (i64, i64),
+        (width, height): (u32, u32),
+        radius: f32,
+    ) {
+        let stroke = self.width.max(0.0);
+        if stroke == 0.0 {
+            return;
+        }
+        let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+        let (center_x, center_y) = (x as f32 + half_width, y as f32 + half_height);
+        let outer_radius = if radius > 0.0 { radius + stroke } else { 0.0 };
+
+        let reach = stroke.ceil() as i64 + 1;
+        let (canvas_width, canvas_height) = canvas.dimensions();
+        let columns = (x - reach).max(0)..(x + width as i64 + reach).min(canvas_width as i64);
+        let rows = (y - reach).max(0)..(y + height as i64 + reach).min(canvas_height as i64);
+        let [r, g, b, a] = self.color.0;
+        for py in rows {
+            for px in columns.clone() {
+                let (dx, dy) = (px as f32 + 0.5 - center_x, py as f32 + 0.5 - center_y);
+                let outer = coverage(
+                    dx,
+                    dy,
+                    half_width + stroke,
+                    half_height + stroke,
+                    outer_radius,
+                );
+                let inner = coverage(dx, dy, half_width, half_height, radius);
+                let ring = (outer - inner).max(0.0);
+                if ring > 0.0 {
+                    let alpha = (a as f32 * ring).round() as u8;
+                    canvas
+                        .get_pixel_mut(px as u32, py as u32)
+                        .blend(&Rgba([r, g, b, alpha]));
+                }
+            }
+        }
+    }
+}
+
+/// How much of the pixel at `dx`, `dy` from the center lies inside a
+/// rectangle of the half sizes with corners rounded to `radius`.
+fn coverage(dx: f32, dy: f32, half_width: f32, half_height: f32, radius: f32) -> f32 {
+    let radius = radius.min(half_width).min(half_height);
+    let qx = dx.abs() - half_width + radius;
+    let qy = dy.abs() - half_height + radius;
+    let distance = qx.max(0.0).hypot(qy.max(0.0)) + qx.max(qy).min(0.0) - radius;
+    (0.5 - distance).clamp(0.0, 1.0)
+}
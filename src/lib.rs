@@ -0,0 +1,364 @@
+//! Processing core of Image Finalizer: puts photos on a bordered canvas,
+//! optionally resizes them and encodes the result. The desktop app is a thin
+//! front end over this crate; build with `--no-default-features` to use it
+//! without any GUI dependencies.
+
+pub mod archive;
+pub mod batch;
+pub mod clear;
+pub mod combine;
+pub mod corners;
+pub mod crop;
+pub mod dates;
+pub mod duplicates;
+pub mod edit;
+pub mod encoder;
+mod error;
+pub mod estimate;
+pub mod fill;
+pub mod format;
+mod geometry;
+pub mod hook;
+pub mod i18n;
+pub mod keyline;
+pub mod manifest;
+pub mod naming;
+mod outcome;
+pub mod pipeline;
+pub mod placeholder;
+pub mod presets;
+pub mod print;
+mod process;
+pub mod rules;
+pub mod serialization;
+pub mod settings_file;
+pub mod sftp;
+pub mod shadow;
+pub mod space;
+pub mod split;
+pub mod srcset;
+pub mod transform;
+pub mod trim;
+pub mod upload;
+pub mod validation;
+pub mod webhook;
+pub mod worker;
+
+use image::{imageops::FilterType, Rgba};
+use serde::{Deserialize, Serialize};
+
+use corners::{CornerRadius, RadiusUnit};
+use crop::Crop;
+use fill::BorderFill;
+use format::{FormatSettings, OutputFormat};
+use keyline::Keyline;
+use shadow::DropShadow;
+use transform::Transform;
+use trim::Trim;
+
+pub use error::ProcessError;
+pub use geometry::{
+    border_geometry, compute_geometry, fit_aspect, output_dimensions, pixel_geometry,
+    resize_target, sides_geometry, BorderMode, BorderSides, CanvasAspect, Frame, FramePlacement,
+    Geometry, PixelBorder, MAX_BORDER_PERCENTAGE, MAX_DIMENSION, MAX_FRAME_PERCENTAGE,
+};
+pub use outcome::{ProcessOutcome, SkipReason, StageTimings, THUMBNAIL_SIZE};
+pub use process::{
+    apply_order, choose_settings, combined_file_name, is_supported_image, move_path,
+    output_file_name, prepare_photo, prepared_info, process_combined, process_file,
+    process_file_named, process_split, scan_images, scan_inputs, templated_file_name,
+    ChosenSettings,
+};
+
+/// Everything that determines how an image is processed. This is the schema
+/// of presets, config files and settings exports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessInfo {
+    /// Cuts off the margin the photo already has, before anything else.
+    /// Left out of files while off.
+    #[serde(skip_serializing_if = "Trim::is_off")]
+    pub trim: Trim,
+    /// Turns or mirrors the trimmed photo. Left out of files while it does
+    /// neither.
+    #[serde(skip_serializing_if = "Transform::is_identity")]
+    pub transform: Transform,
+    /// Straightens and recrops the turned photo. Set per image in the
+    /// editor, and left out of files while it does neither.
+    #[serde(skip_serializing_if = "Crop::is_identity")]
+    pub crop: Crop,
+    /// Add the same border width on every side instead of centering the
+    /// photo on a square canvas.
+    pub symmetrical_border: bool,
+    /// Border size in percent of the longest side.
+    pub border_percentage: f32,
+    /// A border width for every side, replacing `border_percentage` and
+    /// the border mode. Left out of files while unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_sides: Option<BorderSides>,
+    /// A border width in pixels, replacing `border_percentage`. Left out
+    /// of files while unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_pixels: Option<PixelBorder>,
+    /// Pads the bordered photo to this shape, in place of the square
+    /// canvas. Left out of files while unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canvas_aspect: Option<CanvasAspect>,
+    /// A second border inside or around this one. Left out of files while
+    /// unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<Frame>,
+    /// Rounds the corners of the photo on the canvas. Left out of files
+    /// while unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corner_radius: Option<CornerRadius>,
+    /// A shadow under the photo. Left out of files while unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drop_shadow: Option<DropShadow>,
+    /// A thin line around the photo. Left out of files while unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyline: Option<Keyline>,
+    /// Fill of the border, straight (not premultiplied) alpha.
+    #[serde(with = "serialization::hex_color")]
+    pub border_color: Rgba<u8>,
+    /// What the border is filled with, starting from `border_color`. Left
+    /// out of files while it is the plain color.
+    #[serde(skip_serializing_if = "BorderFill::is_solid")]
+    pub border_fill: BorderFill,
+    /// Scale the bordered image to `resize_longest_dimension`.
+    pub resize_images: bool,
+    /// Longest side of the output when `resize_images` is set.
+    pub resize_longest_dimension: u32,
+    /// Resampling filter used when resizing.
+    #[serde(with = "serialization::filter_type")]
+    pub resize_filter: FilterType,
+    pub output_format: OutputFormat,
+    /// Encoder options of every format; only those of `output_format` apply.
+    pub format_settings: FormatSettings,
+}
+
+impl Default for ProcessInfo {
+    fn default() -> Self {
+        ProcessInfo {
+            trim: Trim::default(),
+            transform: Transform::default(),
+            crop: Crop::default(),
+            symmetrical_border: false,
+            border_percentage: 10.0,
+            border_sides: None,
+            border_pixels: None,
+            canvas_aspect: None,
+            frame: None,
+            corner_radius: None,
+            drop_shadow: None,
+            keyline: None,
+            border_color: Rgba([255, 255, 255, 255]),
+            border_fill: BorderFill::Solid,
+            resize_images: false,
+            resize_longest_dimension: 800,
+            resize_filter: FilterType::Lanczos3,
+            output_format: OutputFormat::Png,
+            format_settings: FormatSettings::default(),
+        }
+    }
+}
+
+impl ProcessInfo {
+    /// Layout selected by `symmetrical_border`. A canvas of a set shape
+    /// starts from the symmetrical border.
+    pub fn border_mode(&self) -> BorderMode {
+        if self.symmetrical_border || self.canvas_aspect.is_some() {
+            BorderMode::Symmetrical
+        } else {
+            BorderMode::Square
+        }
+    }
+
+    /// Width divided by height of every canvas, if the settings give all
+    /// of them the same shape.
+    pub fn canvas_ratio(&self) -> Option<f32> {
+        match (self.canvas_aspect, self.border_sides) {
+            (Some(aspect), _) => Some(aspect.ratio()),
+            (None, Some(_)) => None,
+            (None, None) => (!self.symmetrical_border).then_some(1.0),
+        }
+    }
+
+    /// Longest side of the output, if it is resized.
+    pub fn resize_longest(&self) -> Option<u32> {
+        self.resize_images.then_some(self.resize_longest_dimension)
+    }
+
+    /// These settings for a copy of the photo scaled by `factor`, like
+    /// the working copy of the preview, so that lengths set in pixels
+    /// keep their size relative to the photo.
+    pub fn scaled(&self, factor: f32) -> ProcessInfo {
+        let mut info = self.clone();
+        let resized = info.resize_images;
+        if let Some(pixels) = &mut info.border_pixels {
+            // Measured on the resized output, it follows the photo already.
+            if !(pixels.after_resize && resized) {
+                pixels.width = (pixels.width as f32 * factor).round() as u32;
+            }
+        }
+        if let Some(corners) = &mut info.corner_radius {
+            if corners.unit == RadiusUnit::Pixels {
+                corners.radius *= factor;
+            }
+        }
+        if let Some(shadow) = &mut info.drop_shadow {
+            shadow.offset_x *= factor;
+            shadow.offset_y *= factor;
+            shadow.blur *= factor;
+        }
+        if let Some(keyline) = &mut info.keyline {
+            keyline.width *= factor;
+        }
+        if let BorderFill::Texture(texture) = &mut info.border_fill {
+            texture.scale *= factor;
+        }
+        info
+    }
+
+    /// Short labelled values of the settings that influence the output.
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        let [r, g, b, _] = self.border_color.0;
+        let percentage = i18n::decimal(self.border_percentage as f64, 1);
+        let border = if let Some(sides) = self.border_sides {
+            let side = |percentage: f32| i18n::decimal(percentage as f64, 1);
+            t!(
+                "summary-border-sides",
+                top = side(sides.top),
+                right = side(sides.right),
+                bottom = side(sides.bottom),
+                left = side(sides.left)
+            )
+        } else if let Some(pixels) = self.border_pixels {
+            let border = if self.symmetrical_border {
+                t!("summary-border-pixels-symmetrical", width = pixels.width)
+            } else {
+                t!("summary-border-pixels-square", width = pixels.width)
+            };
+            if pixels.after_resize && self.resize_images {
+                t!("summary-border-after-resize", border = border)
+            } else {
+                border
+            }
+        } else if self.symmetrical_border {
+            t!("summary-border-symmetrical", percentage = percentage)
+        } else {
+            t!("summary-border-square", percentage = percentage)
+        };
+
+        let mut summary = vec![];
+        if self.trim.enabled {
+            summary.push((
+                t!("summary-trim"),
+                t!(
+                    "summary-trim-value",
+                    color = self.trim.color.label(),
+                    tolerance = self.trim.tolerance
+                ),
+            ));
+        }
+        if let Some(transform) = self.transform.describe() {
+            summary.push((t!("summary-transform"), transform));
+        }
+        summary.extend([
+            (t!("summary-border"), border),
+            (t!("summary-color"), format!("#{:02X}{:02X}{:02X}", r, g, b)),
+        ]);
+        if let Some(fill) = self.border_fill.describe() {
+            summary.push((t!("summary-fill"), fill));
+        }
+        if let Some(aspect) = self.canvas_aspect {
+            summary.push((t!("summary-canvas"), aspect.to_string()));
+        }
+        if let Some(frame) = self.frame {
+            let [r, g, b, _] = frame.color.0;
+            let width = i18n::decimal(frame.width as f64, 1);
+            let color = format!("#{:02X}{:02X}{:02X}", r, g, b);
+            summary.push((
+                t!("summary-frame"),
+                match frame.placement {
+                    FramePlacement::Inside => {
+                        t!("summary-frame-inside", width = width, color = color)
+                    }
+                    FramePlacement::Outside => {
+                        t!("summary-frame-outside", width = width, color = color)
+                    }
+                },
+            ));
+        }
+        if let Some(corners) = self.corner_radius {
+            let radius = i18n::decimal(corners.radius as f64, 1);
+            let radius = match corners.unit {
+                RadiusUnit::Pixels => format!("{} px", radius),
+                RadiusUnit::Percent => format!("{} %", radius),
+            };
+            summary.push((t!("summary-corners"), radius));
+        }
+        if let Some(shadow) = self.drop_shadow {
+            let whole = |value: f32| i18n::decimal(value as f64, 0);
+            summary.push((
+                t!("summary-shadow"),
+                t!(
+                    "summary-shadow-value",
+                    x = whole(shadow.offset_x),
+                    y = whole(shadow.offset_y),
+                    blur = whole(shadow.blur),
+                    opacity = whole(shadow.opacity)
+                ),
+            ));
+        }
+        if let Some(keyline) = self.keyline {
+            let [r, g, b, _] = keyline.color.0;
+            summary.push((
+                t!("summary-keyline"),
+                format!(
+                    "{} px #{:02X}{:02X}{:02X}",
+                    i18n::decimal(keyline.width as f64, 1),
+                    r,
+                    g,
+                    b
+                ),
+            ));
+        }
+        summary.extend([
+            (
+                t!("summary-resize"),
+                if self.resize_images {
+                    format!(
+                        "{} px {:?}",
+                        self.resize_longest_dimension, self.resize_filter
+                    )
+                } else {
+                    t!("summary-resize-off").to_string()
+                },
+            ),
+            (t!("summary-format"), format!("{:?}", self.output_format)),
+        ]);
+
+        let settings = &self.format_settings;
+        match self.output_format {
+            OutputFormat::Png => summary.push((
+                t!("summary-compression"),
+                format!("{:?}", settings.png.compression),
+            )),
+            OutputFormat::Jpeg => {
+                summary.push((t!("summary-quality"), settings.jpeg.quality.to_string()))
+            }
+            OutputFormat::Avif => summary.push((
+                t!("summary-quality"),
+                t!(
+                    "summary-avif-quality",
+                    quality = settings.avif.quality,
+                    speed = settings.avif.speed
+                ),
+            )),
+            _ => {}
+        }
+
+        summary
+    }
+}
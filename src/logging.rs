@@ -0,0 +1,167 @@
+//! Diagnostics: `tracing` events go to a daily log file in
+//! `storage::log_dir` and to a bounded in-memory copy shown in the app.
+
+use std::{
+    collections::VecDeque,
+    fs, io,
+    sync::{Arc, Mutex},
+};
+
+use time::UtcOffset;
+use tracing::{Level, Metadata};
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    filter::Targets,
+    fmt::{self, format::FmtSpan, MakeWriter},
+    prelude::*,
+};
+
+use crate::storage;
+
+/// Lines kept for the log panel.
+const BUFFER_LINES: usize = 1000;
+
+/// Daily log files kept before the oldest is deleted.
+const KEPT_FILES: usize = 7;
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub level: Level,
+    pub line: String,
+}
+
+/// The most recent log lines, shared between the subscriber and the UI.
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<LogEntry>>>);
+
+impl LogBuffer {
+    /// Entries at `max_level` or more severe that contain `filter`, ignoring
+    /// case, oldest first.
+    pub fn entries(&self, max_level: Level, filter: &str) -> Vec<LogEntry> {
+        let filter = filter.trim().to_lowercase();
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.level <= max_level)
+            .filter(|entry| filter.is_empty() || entry.line.to_lowercase().contains(&filter))
+            .cloned()
+            .collect()
+    }
+
+    fn push(&self, entry: LogEntry) {
+        let mut entries = self.0.lock().unwrap();
+        if entries.len() == BUFFER_LINES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    fn make_writer_for_level(&self, level: Level) -> BufferWriter {
+        BufferWriter {
+            buffer: self.clone(),
+            level,
+            line: vec![],
+        }
+    }
+}
+
+/// Collects one formatted event and adds it to the buffer when dropped.
+pub struct BufferWriter {
+    buffer: LogBuffer,
+    level: Level,
+    line: Vec<u8>,
+}
+
+impl io::Write for BufferWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for BufferWriter {
+    fn drop(&mut self) {
+        let line = String::from_utf8_lossy(&self.line).trim_end().to_string();
+        if !line.is_empty() {
+            self.buffer.push(LogEntry {
+                level: self.level,
+                line,
+            });
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = BufferWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.make_writer_for_level(Level::INFO)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        self.make_writer_for_level(*meta.level())
+    }
+}
+
+/// Installs the global subscriber. Keep the returned guard alive until the
+/// program ends, or the last lines of the file may be lost.
+pub fn init() -> (LogBuffer, Option<WorkerGuard>) {
+    let buffer = LogBuffer::default();
+
+    // The appender complains about a missing folder when pruning old files.
+    let log_dir = storage::log_dir();
+    let _ = fs::create_dir_all(&log_dir);
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("image-finalizer")
+        .filename_suffix("log")
+        .max_log_files(KEPT_FILES)
+        .build(log_dir);
+    let (file_layer, guard) = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = fmt::layer()
+                .with_ansi(false)
+                .with_span_events(FmtSpan::CLOSE)
+                .with_writer(writer);
+            (Some(layer), Some(guard))
+        }
+        Err(e) => {
+            eprintln!("Warning: logging to a file is disabled: {}", e);
+            (None, None)
+        }
+    };
+
+    // Wall-clock time, to match the log against what happened when. The
+    // offset is looked up while there is still only one thread.
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let time_of_day = time::format_description::parse("[hour]:[minute]:[second]")
+        .expect("the time format is valid");
+    let buffer_layer = fmt::layer()
+        .with_ansi(false)
+        .with_target(false)
+        .with_timer(fmt::time::OffsetTime::new(offset, time_of_day))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(buffer.clone());
+
+    // Only this program's own events; the GUI libraries are far too chatty.
+    let filter = Targets::new()
+        .with_target("image_finalizer", Level::DEBUG)
+        .with_target("image_processor", Level::DEBUG);
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(buffer_layer)
+        .with(filter)
+        .init();
+
+    (buffer, guard)
+}
@@ -1,407 +1,5791 @@
 #![windows_subsystem = "windows"]
 
+mod a11y;
+mod cli;
+mod clipboard;
+mod config;
+mod guides;
+mod help;
+mod histogram;
+mod history;
+mod logging;
+mod metadata;
+mod notify;
+mod recent;
+mod reveal;
+mod rules_ui;
+mod session;
+mod storage;
+mod taskbar;
+mod thumbnails;
+mod tray;
+#[cfg(any(feature = "s3", feature = "sftp"))]
+mod upload_ui;
+
 use std::{
-    fs,
+    collections::{HashMap, HashSet},
+    io::{self, BufRead, IsTerminal, Write},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{atomic::AtomicBool, mpsc, Arc},
+    time::{Duration, Instant},
 };
 
+use clap::Parser;
+use cli::Cli;
+use clipboard::{Clipboard, ImageSource};
+use config::Config;
 use eframe::{run_native, App, CreationContext};
 use egui::{Color32, Context, ProgressBar, Slider, TextureHandle};
+use guides::GuideSettings;
+use help::Help;
+use histogram::Histogram;
+use history::History;
 use image::{
-    codecs::{avif::AvifEncoder, jpeg::JpegEncoder, tiff::TiffEncoder, webp::WebPEncoder},
     imageops::{self, FilterType},
-    DynamicImage, GenericImageView, ImageBuffer, ImageEncoder, ImageFormat, Rgba,
+    DynamicImage, GenericImageView, ImageBuffer, ImageFormat, Rgba, RgbaImage,
+};
+#[cfg(feature = "webhook")]
+use image_finalizer::webhook;
+use image_finalizer::{
+    apply_order,
+    archive::{ArchiveSettings, ZipCompression},
+    choose_settings,
+    clear::{self, ClearPlan},
+    combine::{self, CombineAlign, CombineSettings},
+    corners::{CornerRadius, RadiusUnit},
+    crop::{self, Crop, CropRect, MAX_ANGLE},
+    dates,
+    duplicates::{self, Duplicate, DuplicateGroup, KeepPolicy},
+    edit::ImageEdit,
+    estimate::{self, SizeEstimate},
+    fill::{BlurFill, BorderFill, Gradient, GradientShape, TextureFill, TextureMode},
+    format::{FormatSettings, OutputFormat, PngCompression},
+    hook::{self, HookFailure, HookSettings},
+    i18n::{self, Language},
+    keyline::Keyline,
+    manifest::{settings_hash, ManifestFormat},
+    move_path,
+    naming::{
+        BatchNaming, FileNaming, NameTemplate, OriginalsPlacement, OverwritePolicy, VariantFolders,
+        DEFAULT_DATE_FOLDERS, DEFAULT_RUN_FOLDER, DEFAULT_SUFFIX, DEFAULT_TEMPLATE,
+        ORIGINALS_FOLDER,
+    },
+    output_dimensions,
+    pipeline::bit_depth,
+    placeholder::PlaceholderKind,
+    prepare_photo, prepared_info,
+    presets::PresetStore,
+    print::{self, Orientation, PaperSize, PrintSettings, MAX_MARGIN},
+    rules::{ResolvedRule, RuleSet},
+    scan_inputs,
+    settings_file::SettingsFile,
+    shadow::DropShadow,
+    space,
+    split::{self, SplitFit, SplitSettings, TileAspect},
+    srcset::{SrcsetSettings, UrlNames},
+    t, templated_file_name,
+    transform::{self, Rotation, Transform},
+    trim::{MarginColor, Margins, Trim},
+    validation::{self, Overlap, Validation},
+    webhook::WebhookSettings,
+    worker::{Command, Event, Worker},
+    BorderSides, CanvasAspect, Frame, FramePlacement, PixelBorder, ProcessError, ProcessInfo,
+    ProcessOutcome, MAX_BORDER_PERCENTAGE, MAX_DIMENSION, MAX_FRAME_PERCENTAGE,
 };
+use logging::LogBuffer;
+use metadata::ImageMetadata;
+use recent::{FolderPair, RecentFolders};
 use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use session::{BatchState, Session};
+use taskbar::Taskbar;
+use thumbnails::Thumbnails;
 use tokio::{
     runtime::Runtime,
     sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
-    task::JoinHandle,
 };
+use tracing::Level;
+use tray::{Tray, TrayAction};
+
+/// eframe storage key of the processing settings of the last session.
+const PROCESS_INFO_KEY: &str = "process_info";
+
+/// eframe storage key of the output name template.
+const NAME_TEMPLATE_KEY: &str = "name_template";
+
+/// eframe storage key of the `{suffix}` of the name template.
+const SUFFIX_KEY: &str = "suffix";
+
+/// eframe storage key of the `FolderSettings`.
+const FOLDERS_KEY: &str = "folders";
+
+/// eframe storage key of the `OverwritePolicy`.
+const OVERWRITE_KEY: &str = "overwrite";
+
+/// eframe storage key of where the originals are copied, if at all.
+const ORIGINALS_KEY: &str = "originals";
+
+/// eframe storage key of whether outputs keep the time of their input.
+const PRESERVE_TIMESTAMPS_KEY: &str = "preserve_timestamps";
+
+/// eframe storage key of the manifest format.
+const MANIFEST_KEY: &str = "manifest";
+
+/// eframe storage key of the placeholder kind recorded in manifests.
+const PLACEHOLDER_KEY: &str = "placeholder";
+
+/// eframe storage key of the `ArchiveSettings`, kept while archives are off.
+const ARCHIVE_KEY: &str = "archive";
+
+/// eframe storage key of whether batches are zipped.
+const CREATE_ARCHIVE_KEY: &str = "create_archive";
+
+/// eframe storage key of the `SrcsetSettings`, kept while copies are off.
+const SRCSET_KEY: &str = "srcset";
+
+/// eframe storage key of whether copies for websites are written.
+const CREATE_SRCSET_KEY: &str = "create_srcset";
+
+/// eframe storage key of the `HookSettings`, kept while the hook is off.
+const HOOK_KEY: &str = "hook";
+
+/// eframe storage key of whether the hook runs.
+const RUN_HOOK_KEY: &str = "run_hook";
+
+/// eframe storage key of where finished batches are reported to.
+const WEBHOOK_KEY: &str = "webhook";
+
+/// eframe storage key of whether settings exports include the webhook.
+const EXPORT_WEBHOOK_KEY: &str = "export_webhook";
+
+/// eframe storage key of how duplicates are searched for.
+const DUPLICATES_KEY: &str = "duplicates";
+
+/// eframe storage key of how photos are combined.
+const COMBINE_KEY: &str = "combine";
+
+/// eframe storage key of how photos are split into tiles.
+const SPLIT_KEY: &str = "split";
+
+/// eframe storage key of whether finished batches are notified.
+const NOTIFY_KEY: &str = "notify_when_done";
+
+/// eframe storage key of the per-image preset rules.
+const PRESET_RULES_KEY: &str = "preset_rules";
+
+/// eframe storage key of what is set for single images, by path.
+const EDITS_KEY: &str = "image_edits";
+
+/// eframe storage key of how outputs are printed.
+const PRINT_KEY: &str = "print";
+
+/// eframe storage key of the recently used folders. Like the folders
+/// themselves, they are left out of exported settings.
+const RECENT_FOLDERS_KEY: &str = "recent_folders";
+
+/// Longest side of the preview texture. It is drawn scaled to fill the
+/// preview pane.
+const PREVIEW_SIZE: u32 = 800;
+
+/// Longest side of the page shown before printing.
+const PRINT_PREVIEW_SIZE: u32 = 480;
+
+/// Longest side of the working copy the preview is composited from. Twice the
+/// display size keeps the final downscale sharp even with a 0% border.
+const PREVIEW_WORKING_SIZE: u32 = PREVIEW_SIZE * 2;
+
+/// Longest side of the photo in the crop editor, small enough to
+/// straighten again on every step of the angle slider.
+const CROP_EDITOR_SIZE: u32 = 1024;
+
+/// How often the session is saved for crash recovery.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of settings changes that can be undone.
+const HISTORY_DEPTH: usize = 100;
+
+/// Range of the UI scale, on top of the scale factor of the OS.
+const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+
+/// How much Ctrl+= and Ctrl+- change the UI scale.
+const UI_SCALE_STEP: f32 = 0.1;
+
+/// How long the settings have to stay put before the output size is
+/// estimated, so dragging a slider doesn't encode samples on every step.
+const ESTIMATE_SETTLE: Duration = Duration::from_millis(800);
+
+/// Batches of this many images or more are summarized for confirmation
+/// before they start, unless the user chose another size.
+const LARGE_BATCH: usize = 200;
+
+/// Keys of the main actions and what they do, as listed by the "?" overlay.
+fn shortcuts() -> [(&'static str, &'static str); 11] {
+    [
+        ("Tab / Shift+Tab", t!("shortcut-focus")),
+        ("Space / Enter", t!("shortcut-activate")),
+        ("Ctrl+O", t!("shortcut-input")),
+        ("Ctrl+Shift+O", t!("shortcut-output")),
+        ("Ctrl+Enter", t!("shortcut-start")),
+        ("Esc", t!("shortcut-escape")),
+        ("← / →", t!("shortcut-browse")),
+        ("Ctrl+= / Ctrl+-", t!("shortcut-scale")),
+        ("Ctrl+0", t!("shortcut-scale-reset")),
+        ("Ctrl+Z", t!("shortcut-undo")),
+        ("Ctrl+Shift+Z", t!("shortcut-redo")),
+    ]
+}
 
 struct BorderApp {
     input_dir: PathBuf,
     output_dir: PathBuf,
+    /// What is typed into the folder fields, committed on Enter or when the
+    /// field loses focus.
+    input_text: String,
+    output_text: String,
+    /// Why the typed folder was not used.
+    input_hint: Option<String>,
+    output_hint: Option<String>,
+    /// How the output folder overlaps the input folder, kept up to date
+    /// as either changes.
+    overlap: Option<Overlap>,
+    trim: Trim,
+    /// Margins of `original_image`, and the trim they were found with.
+    original_margins: Option<(Trim, Margins)>,
+    transform: Transform,
     border_percentage: f32,
+    border_sides: Option<BorderSides>,
+    /// Whether the border is `pixel_border` wide rather than
+    /// `border_percentage`.
+    border_in_pixels: bool,
+    pixel_border: PixelBorder,
+    canvas_aspect: Option<CanvasAspect>,
+    /// Whether a second border, `frame`, is stacked on the main one.
+    add_frame: bool,
+    frame: Frame,
+    /// Whether the corners of the photo are `corner_radius` round.
+    rounded_corners: bool,
+    corner_radius: CornerRadius,
+    /// Whether the photo casts `shadow`.
+    drop_shadow: bool,
+    shadow: DropShadow,
+    /// Whether `keyline` is drawn around the photo.
+    draw_keyline: bool,
+    keyline: Keyline,
+    border_fill: BorderFill,
+    /// The last gradient, blur and texture, kept while the fill is
+    /// switched to another kind.
+    gradient: Gradient,
+    blur_fill: BlurFill,
+    texture: TextureFill,
     original_image: Option<Arc<DynamicImage>>,
+    preview_source: Option<Arc<DynamicImage>>,
     preview_image: Option<DynamicImage>,
+    /// Full-resolution composite the displayed preview was downscaled from.
+    preview_composite: Option<Arc<DynamicImage>>,
     preview_texture: Option<TextureHandle>,
+    preview_histogram: Option<Box<Histogram>>,
+    preview_metadata: Option<Box<ImageMetadata>>,
+    /// Settings the currently displayed preview was generated with.
+    preview_info: Option<ProcessInfo>,
+    comparison: Option<PinnedPreview>,
+    loupe_texture: Option<TextureHandle>,
     image_paths: Vec<PathBuf>,
+    /// The order the images were dragged into, kept across rescans of the
+    /// input folder. `None` keeps the order of the scan, by name.
+    manual_order: Option<Vec<PathBuf>>,
+    /// Inputs unchecked in the list, left out of batches but still
+    /// previewed.
+    excluded: HashSet<PathBuf>,
+    /// Inputs turned differently from `transform` in the list, or cropped
+    /// in the editor.
+    edits: HashMap<PathBuf, ImageEdit>,
+    crop_editor: Option<CropEditor>,
+    duplicates: DuplicateSearch,
+    combine: CombineMode,
+    split: SplitMode,
+    preview_index: usize,
     status_message: String,
+    /// Recent log lines for the log panel, and the least severe level shown.
+    log: LogBuffer,
+    log_level: Level,
+    /// Only log lines containing this are shown.
+    log_filter: String,
     context: egui::Context,
     processing: bool,
     completed_images: i32,
+    /// Bytes written by the running or last batch.
+    written_bytes: u64,
+    /// Files of the batch left alone because their output exists.
+    skipped_images: usize,
+    /// Files of the batch that could not be processed. `failures` also
+    /// holds errors that belong to no file, like that of the archive.
+    failed_images: usize,
+    /// Opens the list of failures on the next frame.
+    reveal_failures: bool,
     max_images: i32,
+    /// When the running or last batch started.
+    batch_started: Instant,
+    /// Show a system notification when a batch ends.
+    notify_when_done: bool,
+    taskbar: Taskbar,
+    tray: Tray,
+    /// Whether the window is hidden in the tray.
+    in_tray: bool,
+    /// Whether the running batch is paused.
+    paused: bool,
+    /// The input whose output didn't fit on the drive, while the batch
+    /// waits for space to be freed.
+    disk_full: Option<PathBuf>,
+    clipboard: Clipboard,
+    /// An image larger than `clipboard::MAX_SIDE` and its size, while the
+    /// user decides whether to copy a smaller one.
+    large_copy: Option<(ImageSource, (u32, u32))>,
+    /// Asks what to do with the running batch when the window is closed.
+    close_prompt: bool,
+    /// Set once closing should no longer be stopped for a running batch.
+    quitting: bool,
+    help: Help,
     symmetrical_border: bool,
+    border_color: Color32,
     resize_images: bool,
     resize_longest_dimension: u32,
     resize_filter: FilterType,
     output_format: OutputFormat,
-    jpeg_quality: u8,
-    avif_quality: u8,
-    avif_speed: u8,
+    format_settings: FormatSettings,
+    /// Text of the output name template, checked before each batch.
+    name_template: String,
+    /// `{suffix}` of the template. Outputs in the input folder are told apart
+    /// by it, so changing it rescans the folder.
+    suffix: String,
+    folders: FolderSettings,
+    overwrite: OverwritePolicy,
+    /// Where each input is copied as it is, if at all.
+    originals: Option<OriginalsPlacement>,
+    preserve_timestamps: bool,
+    /// Format of the manifest written with each batch, if any.
+    manifest: Option<ManifestFormat>,
+    /// Placeholder recorded for each written file in the manifest, if any.
+    placeholder: Option<PlaceholderKind>,
+    /// Zip the outputs of each batch into an archive after `archive`.
+    create_archive: bool,
+    archive: ArchiveSettings,
+    /// Write scaled-down copies of every output for websites after `srcset`.
+    create_srcset: bool,
+    srcset: SrcsetSettings,
+    /// Run `hook` for each output.
+    run_hook: bool,
+    hook: HookSettings,
+    /// Where finished batches are reported to; an empty URL reports none.
+    webhook: WebhookSettings,
+    /// Include `webhook` in settings exports, off unless asked for.
+    export_webhook: bool,
+    /// Files zipped so far and the total, while the archive is written.
+    archive_progress: Option<(usize, usize)>,
+    /// What became of the archive, added to the status of the batch.
+    archive_note: Option<String>,
+    #[cfg(any(feature = "s3", feature = "sftp"))]
+    uploads: upload_ui::Uploads,
+    /// Output name of the previewed file, kept until anything it depends on
+    /// changes.
+    name_example: Option<(NameExampleKey, Result<String, String>)>,
+    ui_settings: UiSettings,
+    preset_store: PresetStore,
+    presets: Vec<String>,
+    selected_preset: Option<String>,
+    preset_name: String,
+    /// Imported settings waiting for the user to confirm the changes.
+    pending_import: Option<PendingImport>,
+    preset_rules: RuleSet,
+    history: History<ProcessInfo>,
+    recent: RecentFolders,
+    /// Where each input would be written and with which settings, once
+    /// requested.
+    rule_plan: Option<Vec<PlannedFile>>,
+    /// Whether the pre-flight checks for a batch are running.
+    validating: bool,
+    /// Problems found by the pre-flight checks, shown until dismissed.
+    preflight: Option<Validation>,
+    /// Why a batch can't start, shown next to Start, and what they were
+    /// worked out for.
+    start_blockers: Vec<String>,
+    blockers_checked: Option<(PathBuf, PathBuf, usize, ProcessInfo, BatchNaming)>,
+    estimates: SizeEstimates,
+    /// Whether writing into the input folder was confirmed in the
+    /// pre-flight dialog.
+    overlap_confirmed: bool,
+    /// Clearing of the output folder, while its dialog is open.
+    clear_dialog: Option<ClearDialog>,
+    /// Whether the list of keyboard shortcuts is open.
+    show_shortcuts: bool,
+    /// Folder the batch being checked will write into.
+    planned_dir: Option<PathBuf>,
+    /// Folder the last batch wrote into: the output folder or its run
+    /// folder.
+    batch_dir: PathBuf,
+    /// Files of the running batch and which of them are done.
+    batch: Option<BatchState>,
+    /// Errors of the files of the last batch that could not be processed.
+    failures: Vec<String>,
+    /// Every input of the last batch, an output written for it and what
+    /// was done besides the border, like a trim.
+    results: Vec<(PathBuf, PathBuf, Option<String>)>,
+    /// Outputs of `results` picked for printing.
+    selected_results: HashSet<PathBuf>,
+    print_settings: PrintSettings,
+    print_dialog: Option<PrintDialog>,
+    /// The outputs of the last batch as they were written, and its failures.
+    thumbnails: Thumbnails,
+    /// Session left behind by a crashed run, until the user decides on it.
+    stale_session: Option<Session>,
+    /// Last session written to disk and when.
+    saved_session: Option<Session>,
+    last_autosave: Instant,
 
     rt: Runtime,
     tx: UnboundedSender<MessageResult>,
     rx: UnboundedReceiver<MessageResult>,
-    current_preview: Option<JoinHandle<()>>,
+    worker: Worker,
+    preview_generation: u64,
+    /// Whether the latest requested preview is still being generated.
+    preview_pending: bool,
+    /// Set by `--auto-start`; the batch is started after the first frame.
+    auto_start_pending: bool,
 }
 
 #[derive(Debug)]
 enum MessageResult {
-    PreviewResult { data: DynamicImage },
+    ClearPlanned(Result<ClearPlan, String>),
+    ClearProgress {
+        done: usize,
+        total: usize,
+    },
+    ClearDone {
+        deleted: usize,
+        errors: Vec<String>,
+    },
+    /// Opening or revealing a file failed.
+    LaunchFailed(String),
+    /// An output to show on a page before printing.
+    PrintPreview {
+        path: PathBuf,
+        image: Arc<DynamicImage>,
+    },
+    /// How many pages went to the printer.
+    Printed(Result<usize, String>),
+    PreviewResult {
+        generation: u64,
+        info: ProcessInfo,
+        composite: Arc<DynamicImage>,
+        data: DynamicImage,
+        histogram: Box<Histogram>,
+    },
+    PreviewFailed {
+        generation: u64,
+        error: ProcessError,
+    },
+    MetadataResult {
+        path: PathBuf,
+        metadata: Box<ImageMetadata>,
+    },
     InputUpdate(PathBuf),
     OutputUpdate(PathBuf),
+    /// An image picked to fill the border with.
+    TextureChosen(PathBuf),
+    PreviewSaved(Result<PathBuf, String>),
+    /// An image to copy is larger than `clipboard::MAX_SIDE`.
+    CopyTooLarge {
+        source: ImageSource,
+        dimensions: (u32, u32),
+    },
+    /// Pixels ready for the clipboard.
+    CopyImage(Result<RgbaImage, String>),
+    /// Where the export went, and whether it holds the hook command.
+    SettingsExported(Result<(PathBuf, bool), String>),
+    SettingsImported(Result<SettingsFile, String>),
+    RulePlan(Vec<PlannedFile>),
+    Validated(Validation),
+    SizeEstimate {
+        key: String,
+        result: Result<SizeEstimate, String>,
+    },
+    DuplicateProgress {
+        done: usize,
+        total: usize,
+    },
+    Duplicates(Vec<DuplicateGroup>),
+
+    Thumbnail {
+        output: PathBuf,
+        image: egui::ColorImage,
+    },
+    ImageDone(Box<ProcessOutcome>),
+    ImageFailed {
+        path: PathBuf,
+        error: ProcessError,
+    },
+    ArchiveProgress {
+        done: usize,
+        total: usize,
+    },
+    ArchiveDone(Result<Option<PathBuf>, String>),
+    BatchFinished {
+        cancelled: bool,
+    },
+    Tray(TrayAction),
+    DiskFull(PathBuf),
+    #[cfg(any(feature = "s3", feature = "sftp"))]
+    Upload(upload_ui::Message),
+}
+
+/// What of an output is copied to the clipboard.
+enum CopyKind {
+    Image,
+    Path,
+    File,
+}
+
+enum PresetAction {
+    Load(String),
+    Save(String),
+    Rename(String, String),
+    Delete(String),
+}
+
+/// Word to type before files that this app didn't write are deleted.
+fn clear_confirmation() -> &'static str {
+    t!("clear-confirmation-word")
+}
+
+/// Unknown files listed by name before the rest are only counted.
+const CLEAR_LISTED_UNKNOWN: usize = 20;
+
+/// Steps of clearing the output folder.
+enum ClearDialog {
+    Planning,
+    Refused(String),
+    Confirm { plan: ClearPlan, typed: String },
+    Clearing { done: usize, total: usize },
+    Done { deleted: usize, errors: Vec<String> },
+}
+
+/// What to do with the session of a run that crashed.
+enum Recovery {
+    Resume,
+    Restore,
+    Discard,
+}
+
+/// What to do with a running batch when the window is closed.
+enum CloseChoice {
+    Tray,
+    Minimize,
+    Quit,
+    Back,
+}
+
+/// What the example output name was computed from.
+#[derive(PartialEq)]
+struct NameExampleKey {
+    naming: BatchNaming,
+    path: PathBuf,
+    dimensions: (u32, u32),
+    bits: u8,
+    index: usize,
+    info: ProcessInfo,
+}
+
+/// Estimates of the output size per settings, and the one for the current
+/// settings.
+#[derive(Default)]
+struct SizeEstimates {
+    cache: HashMap<String, Result<SizeEstimate, String>>,
+    /// What the current estimate is for, and since when it is.
+    inputs: Option<((PathBuf, usize, ProcessInfo, RuleSet), Instant)>,
+    /// Key of the estimate for `inputs`, once requested.
+    current: Option<String>,
+    /// Key of the estimate the worker is working on.
+    pending: Option<String>,
+}
+
+/// A settings export waiting to be applied.
+struct PendingImport {
+    file: SettingsFile,
+    /// Whether the hook command of `file` is applied too. Only ever set by
+    /// the user.
+    take_hook: bool,
+    /// Whether the webhook of `file` replaces the own one.
+    take_webhook: bool,
+}
+
+/// How near-duplicates are found and which of them is kept by default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct DuplicateSettings {
+    threshold: u32,
+    keep: KeepPolicy,
+}
+
+impl Default for DuplicateSettings {
+    fn default() -> Self {
+        DuplicateSettings {
+            threshold: duplicates::DEFAULT_THRESHOLD,
+            keep: KeepPolicy::default(),
+        }
+    }
+}
+
+/// The search for near-duplicate inputs and what was decided on them.
+#[derive(Default)]
+struct DuplicateSearch {
+    settings: DuplicateSettings,
+    /// Files hashed so far and the total, while the search runs.
+    progress: Option<(usize, usize)>,
+    /// Groups found by the last search and the file kept of each, while
+    /// they are shown.
+    groups: Option<Vec<(DuplicateGroup, usize)>>,
+    /// Inputs left out in favor of another, for the manifest.
+    decisions: Vec<Duplicate>,
+}
+
+/// What a batch does with its inputs.
+enum BatchMode {
+    /// Processes each.
+    Process,
+    /// Combines them into one output.
+    Combine(CombineSettings),
+    /// Splits each into tiles.
+    Split(SplitSettings),
+}
+
+/// What a combined preview is made of: the photos, the layout and the
+/// settings applied before combining.
+type CombineKey = (Vec<PathBuf>, CombineSettings, Rgba<u8>, Trim, Transform);
+
+/// Photos picked to be combined into one output, and how.
+#[derive(Default)]
+struct CombineMode {
+    settings: CombineSettings,
+    /// Inputs in the order they were picked, which is the order they are
+    /// laid out in.
+    selection: Vec<PathBuf>,
+    /// Downscaled copies of the picked inputs for the preview.
+    sources: HashMap<PathBuf, DynamicImage>,
+    /// What the combined preview was last made of, while it is shown.
+    previewed: Option<CombineKey>,
+}
+
+/// How the previewed photo is split into tiles, and whether the tiles are
+/// shown on it.
+#[derive(Default)]
+struct SplitMode {
+    settings: SplitSettings,
+    show_tiles: bool,
+    /// The preview source the tiles are drawn on, and what it was made of.
+    texture: Option<(TextureHandle, Arc<DynamicImage>)>,
+}
+
+/// The crop and straighten editor of the previewed image, while open.
+struct CropEditor {
+    path: PathBuf,
+    /// The edit being made, applied only once confirmed.
+    crop: Crop,
+    /// Keeps dragged crops at the ratio of the canvas.
+    lock_aspect: bool,
+    /// The photo trimmed and turned, as the crop applies to it, scaled
+    /// down for editing.
+    source: DynamicImage,
+    /// `source` straightened, and the angle it was straightened by.
+    texture: Option<(TextureHandle, f32)>,
+    /// Where the drag of a new crop began, in fractions of the photo.
+    drag_start: Option<(f32, f32)>,
+}
+
+/// The outputs about to be printed, one page each, and the page shown.
+struct PrintDialog {
+    pages: Vec<PathBuf>,
+    page: usize,
+    /// The output of the shown page, scaled down, once loaded.
+    source: Option<(PathBuf, Arc<DynamicImage>)>,
+    /// The output whose loading is under way.
+    loading: Option<PathBuf>,
+    /// The page rendered from `source`, and the settings and color it was
+    /// rendered with.
+    texture: Option<(TextureHandle, PrintSettings, Rgba<u8>)>,
+    /// Whether the pages are on their way to the printer.
+    printing: bool,
+}
+
+/// A preview frozen as the "A" side of a comparison.
+struct PinnedPreview {
+    image: DynamicImage,
+    texture: TextureHandle,
+    info: ProcessInfo,
+}
+
+/// How the input folder is scanned and its outputs laid out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct FolderSettings {
+    include_subfolders: bool,
+    /// Recreate the subfolders in the output folder instead of writing
+    /// every output into it directly.
+    keep_structure: bool,
+    /// Sort the outputs into folders after `date_pattern`.
+    by_date: bool,
+    date_pattern: String,
+    variants: VariantFolders,
+    /// Put each batch into a folder of its own after `run_pattern`.
+    per_run: bool,
+    run_pattern: String,
+}
+
+impl Default for FolderSettings {
+    fn default() -> Self {
+        FolderSettings {
+            include_subfolders: false,
+            keep_structure: false,
+            by_date: false,
+            date_pattern: DEFAULT_DATE_FOLDERS.to_string(),
+            variants: VariantFolders::default(),
+            per_run: false,
+            run_pattern: DEFAULT_RUN_FOLDER.to_string(),
+        }
+    }
+}
+
+/// One input of the rule plan.
+#[derive(Debug)]
+struct PlannedFile {
+    path: PathBuf,
+    /// Output path relative to the output folder, or why it can't be named.
+    output: Result<String, String>,
+    applied: String,
+}
+
+/// Display-only preferences persisted by eframe between sessions. Nothing in
+/// here may influence the processed output.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct UiSettings {
+    preview_backdrop: PreviewBackdrop,
+    guides: GuideSettings,
+    theme: ThemeSetting,
+    /// Size of the UI relative to what the OS asks for.
+    scale: f32,
+    /// `None` follows the system.
+    language: Option<Language>,
+    /// Whether minimizing hides the window in the system tray.
+    minimize_to_tray: bool,
+    /// Whether the settings panel shows every option, rather than the
+    /// border, format and resize basics.
+    advanced: bool,
+    /// Batches of at least this many images are summarized for
+    /// confirmation before they start.
+    confirm_from: usize,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        UiSettings {
+            preview_backdrop: PreviewBackdrop::default(),
+            guides: GuideSettings::default(),
+            theme: ThemeSetting::default(),
+            scale: 1.0,
+            language: None,
+            minimize_to_tray: false,
+            advanced: false,
+            confirm_from: LARGE_BATCH,
+        }
+    }
+}
+
+impl UiSettings {
+    /// Sets the UI scale, clamped to `UI_SCALE_RANGE`, and applies it on
+    /// top of the scale factor of the OS.
+    fn set_scale(&mut self, ctx: &Context, scale: f32) {
+        // Rounded so stepping back and forth lands on 100% again.
+        let scale = (scale * 100.0).round() / 100.0;
+        self.scale = scale.clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+        ctx.set_zoom_factor(self.scale);
+    }
+
+    /// Makes the chosen language, or the one of the system, current.
+    fn apply_language(self) {
+        i18n::set_language(self.language.unwrap_or_else(system_language));
+    }
+}
+
+/// The bundled language of the system locale, English if there is none.
+fn system_language() -> Language {
+    sys_locale::get_locale()
+        .and_then(|locale| Language::from_locale(&locale))
+        .unwrap_or_default()
+}
+
+/// Light or dark widgets. The preview backdrop is chosen separately, so
+/// this never changes how an output is judged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum ThemeSetting {
+    /// Follows the system, switching along with it.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeSetting {
+    fn apply(self, ctx: &Context) {
+        ctx.set_theme(match self {
+            ThemeSetting::System => egui::ThemePreference::System,
+            ThemeSetting::Light => egui::ThemePreference::Light,
+            ThemeSetting::Dark => egui::ThemePreference::Dark,
+        });
+    }
+
+    /// The setting the top bar button switches to.
+    fn next(self) -> Self {
+        match self {
+            ThemeSetting::System => ThemeSetting::Light,
+            ThemeSetting::Light => ThemeSetting::Dark,
+            ThemeSetting::Dark => ThemeSetting::System,
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            ThemeSetting::System => "💻",
+            ThemeSetting::Light => "☀",
+            ThemeSetting::Dark => "🌙",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ThemeSetting::System => t!("theme-system"),
+            ThemeSetting::Light => t!("theme-light"),
+            ThemeSetting::Dark => t!("theme-dark"),
+        }
+    }
+}
 
-    ImageComplete,
+/// Color drawn behind the preview so the border can be judged against
+/// something other than the panel background.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+enum PreviewBackdrop {
+    White,
+    #[default]
+    Gray,
+    Black,
+    Custom(Color32),
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum OutputFormat {
-    Png,
-    Jpeg,
-    Tiff,
-    Avif,
-    Webp,
+impl PreviewBackdrop {
+    fn color(self) -> Color32 {
+        match self {
+            PreviewBackdrop::White => Color32::WHITE,
+            PreviewBackdrop::Gray => Color32::from_gray(128),
+            PreviewBackdrop::Black => Color32::BLACK,
+            PreviewBackdrop::Custom(color) => color,
+        }
+    }
 }
 
 impl BorderApp {
-    fn new(cc: &CreationContext<'_>) -> Self {
+    /// `config_file` replaces the settings persisted from the last session,
+    /// and the command line flags override both.
+    fn new(
+        cc: &CreationContext<'_>,
+        config_file: Option<Config>,
+        cli: &Cli,
+        log: LogBuffer,
+    ) -> Self {
         let rt = Runtime::new().expect("failed to create Tokio runtime");
 
         let (tx, rx) = unbounded_channel();
 
-        BorderApp {
+        let mut ui_settings: UiSettings = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        ui_settings.theme.apply(&cc.egui_ctx);
+        ui_settings.apply_language();
+        // The scale is ours to persist, so egui's own zoom keys are off.
+        cc.egui_ctx
+            .options_mut(|options| options.zoom_with_keyboard = false);
+        ui_settings.set_scale(&cc.egui_ctx, ui_settings.scale);
+
+        let mut config = config_file.unwrap_or_else(|| Config {
+            process: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, PROCESS_INFO_KEY))
+                .unwrap_or_default(),
+            ..Config::default()
+        });
+        cli.apply_overrides(&mut config);
+
+        let mut folders: FolderSettings = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, FOLDERS_KEY))
+            .unwrap_or_default();
+        if let Some(recursive) = config.recursive {
+            folders.include_subfolders = recursive;
+        }
+        if let Some(keep) = config.preserve_folders {
+            folders.keep_structure = keep;
+        }
+        if let Some(pattern) = &config.date_folders {
+            folders.by_date = true;
+            folders.date_pattern = pattern.clone();
+        }
+        if let Some(variants) = config.variant_folders {
+            folders.variants = variants;
+        }
+        if let Some(pattern) = &config.run_folder {
+            folders.per_run = true;
+            folders.run_pattern = pattern.clone();
+        }
+
+        let defaults = ProcessInfo::default();
+        let [r, g, b, a] = defaults.border_color.0;
+        let preset_store = PresetStore::new(storage::config_dir().join("presets"));
+
+        let mut app = BorderApp {
             input_dir: PathBuf::default(),
             output_dir: PathBuf::default(),
-            border_percentage: 10.0,
+            overlap: None,
+            input_text: String::new(),
+            output_text: String::new(),
+            input_hint: None,
+            output_hint: None,
+            trim: defaults.trim,
+            original_margins: None,
+            transform: defaults.transform,
+            border_percentage: defaults.border_percentage,
+            border_sides: defaults.border_sides,
+            border_in_pixels: defaults.border_pixels.is_some(),
+            pixel_border: defaults.border_pixels.unwrap_or_default(),
+            canvas_aspect: defaults.canvas_aspect,
+            add_frame: defaults.frame.is_some(),
+            frame: defaults.frame.unwrap_or_default(),
+            rounded_corners: defaults.corner_radius.is_some(),
+            corner_radius: defaults.corner_radius.unwrap_or_default(),
+            drop_shadow: defaults.drop_shadow.is_some(),
+            shadow: defaults.drop_shadow.unwrap_or_default(),
+            draw_keyline: defaults.keyline.is_some(),
+            keyline: defaults.keyline.unwrap_or_default(),
+            border_fill: defaults.border_fill.clone(),
+            gradient: match defaults.border_fill {
+                BorderFill::Gradient(gradient) => gradient,
+                _ => Gradient::default(),
+            },
+            blur_fill: match defaults.border_fill {
+                BorderFill::Blur(blur) => blur,
+                _ => BlurFill::default(),
+            },
+            texture: match &defaults.border_fill {
+                BorderFill::Texture(texture) => texture.clone(),
+                _ => TextureFill::default(),
+            },
             original_image: None,
+            preview_source: None,
             preview_image: None,
+            preview_composite: None,
             preview_texture: None,
+            preview_histogram: None,
+            preview_metadata: None,
+            preview_info: None,
+            comparison: None,
+            loupe_texture: None,
             image_paths: Vec::new(),
+            manual_order: None,
+            excluded: HashSet::new(),
+            edits: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, EDITS_KEY))
+                .unwrap_or_default(),
+            crop_editor: None,
+            duplicates: DuplicateSearch {
+                settings: cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, DUPLICATES_KEY))
+                    .unwrap_or_default(),
+                ..DuplicateSearch::default()
+            },
+            combine: CombineMode {
+                settings: cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, COMBINE_KEY))
+                    .unwrap_or_default(),
+                ..CombineMode::default()
+            },
+            split: SplitMode {
+                settings: cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, SPLIT_KEY))
+                    .unwrap_or_default(),
+                ..SplitMode::default()
+            },
+            preview_index: 0,
             status_message: String::new(),
+            log,
+            log_level: Level::INFO,
+            log_filter: String::new(),
             context: cc.egui_ctx.clone(), // Store the context
             processing: false,
             completed_images: 0,
+            written_bytes: 0,
+            skipped_images: 0,
+            failed_images: 0,
+            reveal_failures: false,
             max_images: 0,
-            symmetrical_border: false,
-            resize_images: false,
-            resize_longest_dimension: 800,
-            resize_filter: FilterType::Lanczos3,
-            output_format: OutputFormat::Png,
-            jpeg_quality: 80,
-            avif_quality: 80,
-            avif_speed: 4,
+            batch_started: Instant::now(),
+            taskbar: Taskbar::new(cc),
+            tray: Tray::new({
+                let tx = tx.clone();
+                let ctx = cc.egui_ctx.clone();
+                move |action| {
+                    // Right away, as a hidden window may not run frames.
+                    if action == TrayAction::Show {
+                        show_window(&ctx);
+                    }
+                    let _ = tx.send(MessageResult::Tray(action));
+                    ctx.request_repaint();
+                }
+            }),
+            in_tray: false,
+            paused: false,
+            disk_full: None,
+            clipboard: Clipboard::default(),
+            large_copy: None,
+            close_prompt: false,
+            quitting: false,
+            help: Help::default(),
+            notify_when_done: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, NOTIFY_KEY))
+                .unwrap_or(true),
+            symmetrical_border: defaults.symmetrical_border,
+            border_color: Color32::from_rgba_unmultiplied(r, g, b, a),
+            resize_images: defaults.resize_images,
+            resize_longest_dimension: defaults.resize_longest_dimension,
+            resize_filter: defaults.resize_filter,
+            output_format: defaults.output_format,
+            format_settings: defaults.format_settings,
+            name_template: config.name_template.clone().unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| storage.get_string(NAME_TEMPLATE_KEY))
+                    .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string())
+            }),
+            suffix: config.suffix.clone().unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| storage.get_string(SUFFIX_KEY))
+                    .unwrap_or_else(|| DEFAULT_SUFFIX.to_string())
+            }),
+            folders,
+            overwrite: config.overwrite.unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, OVERWRITE_KEY))
+                    .unwrap_or_default()
+            }),
+            originals: config.originals.or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, ORIGINALS_KEY))
+                    .flatten()
+            }),
+            preserve_timestamps: config.preserve_timestamps.unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, PRESERVE_TIMESTAMPS_KEY))
+                    .unwrap_or(false)
+            }),
+            manifest: config.manifest.or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, MANIFEST_KEY))
+                    .flatten()
+            }),
+            placeholder: config.placeholder.or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, PLACEHOLDER_KEY))
+                    .flatten()
+            }),
+            create_archive: config.archive.is_some()
+                || cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, CREATE_ARCHIVE_KEY))
+                    .unwrap_or(false),
+            archive: config.archive.clone().unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, ARCHIVE_KEY))
+                    .unwrap_or_default()
+            }),
+            create_srcset: config.srcset.is_some()
+                || cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, CREATE_SRCSET_KEY))
+                    .unwrap_or(false),
+            srcset: config.srcset.clone().unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, SRCSET_KEY))
+                    .unwrap_or_default()
+            }),
+            run_hook: config.hook.is_some()
+                || cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, RUN_HOOK_KEY))
+                    .unwrap_or(false),
+            hook: config.hook.clone().unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, HOOK_KEY))
+                    .unwrap_or_default()
+            }),
+            webhook: config.webhook.clone().unwrap_or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, WEBHOOK_KEY))
+                    .unwrap_or_default()
+            }),
+            export_webhook: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, EXPORT_WEBHOOK_KEY))
+                .unwrap_or(false),
+            archive_progress: None,
+            archive_note: None,
+            #[cfg(any(feature = "s3", feature = "sftp"))]
+            uploads: upload_ui::Uploads::load(cc.storage, &config),
+            name_example: None,
+            ui_settings,
+            presets: preset_store.list(),
+            preset_store,
+            selected_preset: None,
+            preset_name: String::new(),
+            pending_import: None,
+            preset_rules: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, PRESET_RULES_KEY))
+                .unwrap_or_default(),
+            history: History::new(defaults, HISTORY_DEPTH),
+            recent: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, RECENT_FOLDERS_KEY))
+                .unwrap_or_default(),
+            rule_plan: None,
+            validating: false,
+            preflight: None,
+            overlap_confirmed: false,
+            start_blockers: vec![],
+            blockers_checked: None,
+            estimates: SizeEstimates::default(),
+            clear_dialog: None,
+            show_shortcuts: false,
+            planned_dir: None,
+            batch_dir: PathBuf::default(),
+            batch: None,
+            failures: vec![],
+            results: vec![],
+            selected_results: HashSet::new(),
+            print_settings: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, PRINT_KEY))
+                .unwrap_or_default(),
+            print_dialog: None,
+            thumbnails: Thumbnails::default(),
+            stale_session: Session::load_stale(),
+            saved_session: None,
+            last_autosave: Instant::now(),
+            worker: spawn_worker(tx.clone(), cc.egui_ctx.clone()),
             rt,
             tx,
             rx,
 
-            current_preview: None,
+            preview_generation: 0,
+            preview_pending: false,
+            auto_start_pending: cli.auto_start,
+        };
+
+        app.apply_process_info(&config.process);
+        app.history = History::new(app.process_info(), HISTORY_DEPTH);
+        if let Some(output) = config.output {
+            app.set_output_dir(output);
+        }
+        if let Some(input) = config.input {
+            app.set_input_dir(input);
         }
-    }
 
-    fn load_images(&mut self) {
-        self.image_paths = fs::read_dir(&self.input_dir)
-            .expect("Failed to read directory")
-            .filter_map(|entry| entry.ok())
-            .map(|entry| entry.path())
-            .filter(|path| {
-                path.extension().is_some_and(|ext| {
-                    let ext_str = ext.to_str().unwrap_or("").to_lowercase();
-                    ext_str == "png"
-                        || ext_str == "jpg"
-                        || ext_str == "jpeg"
-                        || ext_str == "gif"
-                        || ext_str == "bmp"
-                        || ext_str == "tif"
-                })
-            })
-            .collect();
+        app
+    }
 
-        let paths = self.image_paths.clone();
+    fn set_input_dir(&mut self, path: PathBuf) {
+        if path != self.input_dir {
+            self.manual_order = None;
+        }
+        self.input_text = path.display().to_string();
+        self.input_hint = None;
+        self.input_dir = path;
+        self.overlap = validation::overlap(&self.input_dir, &self.output_dir);
+        self.recent.record(&self.input_dir, &self.output_dir);
+        self.load_images();
+    }
 
-        if let Some(first_image_path) = paths.first() {
-            self.load_original_image(first_image_path);
+    fn set_output_dir(&mut self, path: PathBuf) {
+        self.output_text = path.display().to_string();
+        self.output_hint = None;
+        let was_inside = validation::output_inside_input(&self.input_dir, &self.output_dir);
+        self.output_dir = path;
+        self.overlap = validation::overlap(&self.input_dir, &self.output_dir);
+        self.recent.record(&self.input_dir, &self.output_dir);
+        // Earlier outputs are only left out while they'd be picked up.
+        let is_inside = validation::output_inside_input(&self.input_dir, &self.output_dir);
+        // So is the output folder itself when subfolders are scanned.
+        if was_inside != is_inside || (self.folders.include_subfolders && is_inside) {
+            self.load_images();
+        }
+    }
 
-            if let Some(handle) = self.current_preview.take() {
-                handle.abort();
-            }
+    /// Uses the typed input folder if it is one, otherwise explains why not.
+    fn commit_input_text(&mut self) {
+        let path = typed_path(&self.input_text);
+        if path == self.input_dir {
+            self.input_hint = None;
+        } else if !path.exists() {
+            self.input_hint = Some(t!("hint-missing-folder").to_string());
+        } else if !path.is_dir() {
+            self.input_hint = Some(t!("hint-not-a-folder").to_string());
+        } else {
+            self.set_input_dir(path);
+        }
+    }
 
-            if let Some(img) = &self.original_image {
-                let img_clone = img.clone();
-                let sym = self.symmetrical_border;
-                let border_perc = self.border_percentage;
-                let tx = self.tx.clone();
-                let ctx = self.context.clone();
-                let task = self.rt.spawn(async move {
-                    let res = update_preview_image(
-                        &img_clone,
-                        BorderInfo {
-                            symmetrical_border: sym,
-                            border_percentage: border_perc,
-                        },
-                    );
-                    let _ = tx.send(MessageResult::PreviewResult { data: res });
-                    ctx.request_repaint();
-                });
-                self.current_preview = Some(task);
-            }
+    /// Uses the typed output folder unless it is a file; missing folders are
+    /// created when processing.
+    fn commit_output_text(&mut self) {
+        let path = typed_path(&self.output_text);
+        if path.exists() && !path.is_dir() {
+            self.output_hint = Some(t!("hint-file-not-folder").to_string());
+        } else {
+            self.set_output_dir(path);
         }
     }
 
-    fn load_original_image(&mut self, image_path: &Path) {
-        match image::open(image_path) {
-            Ok(img) => {
-                // Convert the image to RGBA if it's not already
-                let img = img.to_rgba8();
-                self.original_image = Some(Arc::new(DynamicImage::ImageRgba8(img)));
+    fn load_images(&mut self) {
+        let previous = self.previewed_path().cloned();
+        self.rule_plan = None;
+
+        let scan_error = match scan_inputs(
+            &self.input_dir,
+            &self.output_dir,
+            &self.suffix,
+            self.folders.include_subfolders,
+        ) {
+            Ok(paths) => {
+                self.image_paths = match &self.manual_order {
+                    Some(order) => apply_order(paths, order),
+                    None => paths,
+                };
+                None
             }
             Err(e) => {
-                self.status_message = format!("Error loading original image: {}", e);
+                self.image_paths.clear();
+                Some(e)
             }
-        }
-    }
+        };
 
-    fn update_preview_texture(&mut self) {
-        if let Some(img) = &self.preview_image {
-            let (width, height) = img.dimensions();
-            let pixels: Vec<Color32> = img
-                .to_rgba8()
-                .into_raw()
-                .chunks(4)
-                .map(|chunk| {
-                    Color32::from_rgba_unmultiplied(chunk[0], chunk[1], chunk[2], chunk[3])
-                })
-                .collect();
+        let listed: HashSet<&PathBuf> = self.image_paths.iter().collect();
+        self.excluded.retain(|path| listed.contains(path));
+        self.combine.selection.retain(|path| listed.contains(path));
+        // Found among the files before the rescan.
+        self.cancel_duplicates();
+        self.duplicates.groups = None;
 
-            let image = egui::ColorImage {
-                size: [width as usize, height as usize],
-                pixels,
-            };
+        // Keep previewing the same file across a rescan, otherwise start over
+        // from the first image of the new folder.
+        let index = previous
+            .and_then(|previous| self.image_paths.iter().position(|path| *path == previous))
+            .unwrap_or(0);
 
-            self.preview_texture = Some(self.context.load_texture(
-                "preview_image",
-                image,
-                Default::default(),
-            ));
+        if self.image_paths.is_empty() {
+            self.preview_index = 0;
+            self.clear_preview();
+            match scan_error {
+                Some(e) => self.set_status(Level::WARN, t!("status-scan-failed", error = e)),
+                None => self.set_status(
+                    Level::INFO,
+                    t!("check-no-images", folder = self.input_dir.display()),
+                ),
+            }
+        } else {
+            self.status_message.clear();
+            self.select_preview(index);
         }
     }
 
-    fn process_images(&mut self) {
-        let image_paths = self.image_paths.clone(); // Clone for thread safety
-        self.max_images = image_paths.len() as i32;
+    /// Number of inputs that are not left out.
+    fn batch_count(&self) -> usize {
+        self.image_paths.len() - self.excluded.len()
+    }
 
-        let output_dir = self.output_dir.clone();
+    /// The inputs a batch processes, in order: those not left out.
+    fn batch_paths(&self) -> Vec<PathBuf> {
+        self.image_paths
+            .iter()
+            .filter(|path| !self.excluded.contains(*path))
+            .cloned()
+            .collect()
+    }
 
-        self.status_message = "Processing images...".to_string();
-        self.processing = true;
+    /// Hashes every listed input on the worker to find near-duplicates,
+    /// shown once found.
+    fn find_duplicates(&mut self) {
+        if self.image_paths.len() < 2 {
+            return;
+        }
+        self.duplicates.groups = None;
+        self.duplicates.progress = Some((0, self.image_paths.len()));
+        self.worker.send(Command::FindDuplicates {
+            paths: self.image_paths.clone(),
+            threshold: self.duplicates.settings.threshold,
+        });
+    }
 
-        let mut tasks = vec![];
-
-        for image_path in image_paths {
-            let out_dir = output_dir.clone();
-            let info = ProcessInfo {
-                symmetrical_border: self.symmetrical_border,
-                border_percentage: self.border_percentage,
-                resize_images: self.resize_images,
-                resize_longest_dimension: self.resize_longest_dimension,
-                resize_filter: self.resize_filter,
-                output_format: self.output_format,
-                jpeg_quality: self.jpeg_quality,
-                avif_quality: self.avif_quality,
-                avif_speed: self.avif_speed,
-            };
-            let tx = self.tx.clone();
-            let ctx = self.context.clone();
-            tasks.push(self.rt.spawn(async move {
-                let output_path = Path::new(&out_dir);
-                if let Err(e) = add_border(&image_path, info, output_path) {
-                    eprintln!("Error processing {:?}: {:?}", image_path, e);
-                }
-                let _ = tx.send(MessageResult::ImageComplete);
-                ctx.request_repaint();
-            }));
+    fn cancel_duplicates(&mut self) {
+        if self.duplicates.progress.take().is_some() {
+            self.worker.send(Command::CancelDuplicates);
         }
     }
-}
 
-#[derive(Debug)]
-struct BorderInfo {
-    symmetrical_border: bool,
-    border_percentage: f32,
-}
+    /// Leaves out every file of the shown groups but the kept one, and
+    /// checks the kept ones again.
+    fn apply_duplicates(&mut self) {
+        let Some(groups) = self.duplicates.groups.take() else {
+            return;
+        };
+        let mut left_out = 0;
+        for (group, kept) in &groups {
+            self.duplicates
+                .decisions
+                .retain(|duplicate| !group.files.iter().any(|file| file.path == duplicate.path));
+            for file in &group.files {
+                self.excluded.remove(&file.path);
+            }
+            for duplicate in group.decisions(*kept) {
+                tracing::info!(
+                    "{} is left out as a duplicate of {}",
+                    duplicate.path.display(),
+                    duplicate.kept.display()
+                );
+                self.excluded.insert(duplicate.path.clone());
+                self.duplicates.decisions.push(duplicate);
+                left_out += 1;
+            }
+        }
+        self.set_status(
+            Level::INFO,
+            t!("status-duplicates-excluded", count = i18n::number(left_out)),
+        );
+    }
 
-#[derive(Debug, Clone, Copy)]
-struct ProcessInfo {
-    symmetrical_border: bool,
-    border_percentage: f32,
-    resize_images: bool,
-    resize_longest_dimension: u32,
-    resize_filter: FilterType,
-    output_format: OutputFormat,
-    jpeg_quality: u8,
-    avif_quality: u8,
-    avif_speed: u8,
-}
+    /// What screen readers say for the preview: the file and the size of
+    /// its output with `info`.
+    fn preview_alt(&self, info: &ProcessInfo) -> String {
+        let name = self
+            .previewed_path()
+            .map(|path| display_name(path))
+            .unwrap_or_default();
+        let Some((width, height)) = self.original_size(&info.trim) else {
+            return name;
+        };
+        let (width, height) = output_dimensions(width, height, info);
+        t!(
+            "preview-alt",
+            name = name,
+            width = i18n::number(width),
+            height = i18n::number(height)
+        )
+    }
 
-fn add_border(
-    image_path: &Path,
-    info: ProcessInfo,
-    output_dir: &Path,
-) -> Result<(), image::ImageError> {
-    let img = image::open(image_path)?;
-    let (width, height) = img.dimensions();
-
-    let (new_width, new_height, x_offset, y_offset) = if info.symmetrical_border {
-        let longest_side = width.max(height);
-        let new_size = (longest_side as f32 * (1.0 + info.border_percentage / 100.0)) as u32;
-        let delta = new_size - longest_side;
-        let size = { (width + delta, height + delta) };
-        let x_offset = (size.0 - width) / 2;
-        let y_offset = (size.1 - height) / 2;
-
-        (size.0, size.1, x_offset, y_offset)
-    } else {
-        let longest_side = width.max(height);
-        let new_size = (longest_side as f32 * (1.0 + info.border_percentage / 100.0)) as u32;
-        let x_offset = (new_size - width) / 2;
-        let y_offset = (new_size - height) / 2;
+    fn previewed_path(&self) -> Option<&PathBuf> {
+        self.image_paths.get(self.preview_index)
+    }
 
-        (new_size, new_size, x_offset, y_offset)
-    };
+    fn select_preview(&mut self, index: usize) {
+        let Some(path) = self.image_paths.get(index).cloned() else {
+            return;
+        };
 
-    let mut new_img: DynamicImage =
-        ImageBuffer::from_pixel(new_width, new_height, Rgba([255, 255, 255, 255_u8])).into();
+        self.preview_index = index;
+        self.preview_metadata = None;
+        self.load_original_image(&path);
+        self.request_preview();
 
-    imageops::overlay(&mut new_img, &img, x_offset as i64, y_offset as i64);
+        let tx = self.tx.clone();
+        let ctx = self.context.clone();
+        self.rt.spawn(async move {
+            let metadata = ImageMetadata::read(&path);
+            let _ = tx.send(MessageResult::MetadataResult {
+                path,
+                metadata: Box::new(metadata),
+            });
+            ctx.request_repaint();
+        });
+    }
 
-    let resized_img = if info.resize_images {
-        let (width, height) = new_img.dimensions();
+    fn process_info(&self) -> ProcessInfo {
+        ProcessInfo {
+            trim: self.trim,
+            transform: self.transform,
+            // Only ever set per image.
+            crop: Crop::default(),
+            symmetrical_border: self.symmetrical_border,
+            border_percentage: self.border_percentage,
+            border_sides: self.border_sides,
+            border_pixels: self.border_in_pixels.then_some(self.pixel_border),
+            canvas_aspect: self.canvas_aspect,
+            frame: self.add_frame.then_some(self.frame),
+            corner_radius: self.rounded_corners.then_some(self.corner_radius),
+            drop_shadow: self.drop_shadow.then_some(self.shadow),
+            keyline: self.draw_keyline.then_some(self.keyline),
+            border_color: Rgba(self.border_color.to_srgba_unmultiplied()),
+            border_fill: self.border_fill.clone(),
+            resize_images: self.resize_images,
+            resize_longest_dimension: self.resize_longest_dimension,
+            resize_filter: self.resize_filter,
+            output_format: self.output_format,
+            format_settings: self.format_settings,
+        }
+    }
 
-        let (new_width, new_height) = if width > height {
-            let ratio = height as f32 / width as f32;
-            (
-                info.resize_longest_dimension,
-                (info.resize_longest_dimension as f32 * ratio) as u32,
-            )
+    /// The settings of the previewed image, turned and cropped as set for
+    /// it. Combined photos were trimmed and turned one by one already.
+    fn previewed_info(&self) -> ProcessInfo {
+        let mut info = self.process_info();
+        if self.combine.previewed.is_some() {
+            return prepared_info(info);
+        }
+        if let Some(edit) = self.previewed_path().and_then(|path| self.edits.get(path)) {
+            if let Some(transform) = edit.transform {
+                info.transform = transform;
+            }
+            info.crop = edit.crop;
+        }
+        info
+    }
+
+    /// Changes what is set for `path` alone, forgetting it once the image
+    /// is processed like the rest, and refreshes the preview if it shows
+    /// `path`.
+    fn edit_image(&mut self, path: &Path, change: impl FnOnce(&mut ImageEdit)) {
+        let mut edit = self.edits.get(path).copied().unwrap_or_default();
+        change(&mut edit);
+        if edit.is_empty() {
+            self.edits.remove(path);
         } else {
-            let ratio = width as f32 / height as f32;
-            (
-                (info.resize_longest_dimension as f32 * ratio) as u32,
-                info.resize_longest_dimension,
-            )
+            self.edits.insert(path.to_path_buf(), edit);
+        }
+        if self.previewed_path().map(PathBuf::as_path) == Some(path) {
+            self.request_preview();
+        }
+    }
+
+    /// Size of the previewed original once trimmed with `trim`, as far as
+    /// its margins were found already.
+    fn original_size(&self, trim: &Trim) -> Option<(u32, u32)> {
+        let original = self.original_image.as_ref()?;
+        let margins = match self.original_margins {
+            Some((found_with, margins)) if found_with == *trim => margins,
+            _ => Margins::default(),
         };
+        Some(margins.inside(original.width(), original.height()))
+    }
 
-        new_img.resize(new_width, new_height, info.resize_filter)
-    } else {
-        new_img
-    };
+    fn apply_process_info(&mut self, info: &ProcessInfo) {
+        let [r, g, b, a] = info.border_color.0;
 
-    fs::create_dir_all(output_dir).expect("Failed to create output directory");
-
-    let filename = image_path.file_name().unwrap().to_str().unwrap();
-    let name = Path::new(filename).file_stem().unwrap().to_str().unwrap();
-
-    let new_img = resized_img.to_rgb8();
-    let output_path = match info.output_format {
-        OutputFormat::Png => {
-            let output_path = output_dir.join(format!("{}_bordered.png", name));
-            resized_img.save_with_format(output_path.clone(), ImageFormat::Png)?;
-            output_path
-        }
-        OutputFormat::Jpeg => {
-            let output_path = output_dir.join(format!("{}_bordered.jpg", name));
-            let file = fs::File::create(&output_path)?;
-            let mut encoder = JpegEncoder::new_with_quality(file, info.jpeg_quality);
-            encoder.encode(
-                &new_img.into_raw(),
-                resized_img.width(),
-                resized_img.height(),
-                image::ExtendedColorType::Rgb8,
-            )?;
-            output_path
-        }
-        OutputFormat::Tiff => {
-            let output_path = output_dir.join(format!("{}_bordered.tiff", name));
-            let file = fs::File::create(&output_path)?;
-            let encoder = TiffEncoder::new(file);
-            encoder.encode(
-                &new_img.into_raw(),
-                resized_img.width(),
-                resized_img.height(),
-                image::ExtendedColorType::Rgb8,
-            )?;
-            output_path
-        }
-        OutputFormat::Avif => {
-            let output_path = output_dir.join(format!("{}_bordered.avif", name));
-            let file = fs::File::create(&output_path)?;
-            let encoder =
-                AvifEncoder::new_with_speed_quality(file, info.avif_speed, info.avif_quality);
-            encoder.write_image(
-                &new_img.into_raw(),
-                resized_img.width(),
-                resized_img.height(),
-                image::ExtendedColorType::Rgb8,
-            )?;
-            output_path
-        }
-        OutputFormat::Webp => {
-            let output_path = output_dir.join(format!("{}_bordered.webp", name));
-            let file = fs::File::create(&output_path)?;
-            let encoder = WebPEncoder::new_lossless(file);
-            encoder.encode(
-                &new_img.into_raw(),
-                resized_img.width(),
-                resized_img.height(),
-                image::ExtendedColorType::Rgb8,
-            )?;
-            output_path
+        self.trim = info.trim;
+        self.transform = info.transform;
+        self.symmetrical_border = info.symmetrical_border;
+        self.border_percentage = info.border_percentage;
+        self.border_sides = info.border_sides;
+        self.border_in_pixels = info.border_pixels.is_some();
+        self.canvas_aspect = info.canvas_aspect;
+        self.add_frame = info.frame.is_some();
+        if let Some(frame) = info.frame {
+            self.frame = frame;
         }
-    };
+        self.rounded_corners = info.corner_radius.is_some();
+        if let Some(corners) = info.corner_radius {
+            self.corner_radius = corners;
+        }
+        self.drop_shadow = info.drop_shadow.is_some();
+        if let Some(shadow) = info.drop_shadow {
+            self.shadow = shadow;
+        }
+        self.draw_keyline = info.keyline.is_some();
+        if let Some(keyline) = info.keyline {
+            self.keyline = keyline;
+        }
+        if let Some(pixels) = info.border_pixels {
+            self.pixel_border = pixels;
+        }
+        self.border_color = Color32::from_rgba_unmultiplied(r, g, b, a);
+        self.border_fill = info.border_fill.clone();
+        match &info.border_fill {
+            BorderFill::Gradient(gradient) => self.gradient = *gradient,
+            BorderFill::Blur(blur) => self.blur_fill = *blur,
+            BorderFill::Texture(texture) => self.texture = texture.clone(),
+            BorderFill::Solid => {}
+        }
+        self.resize_images = info.resize_images;
+        self.resize_longest_dimension = info.resize_longest_dimension;
+        self.resize_filter = info.resize_filter;
+        self.output_format = info.output_format;
+        self.format_settings = info.format_settings;
+    }
 
-    println!("Border added to {}. Saved to {:?}", filename, output_path);
+    fn undo(&mut self) {
+        if let Some(info) = self.history.undo() {
+            self.apply_process_info(&info);
+            self.request_preview();
+        }
+    }
 
-    Ok(())
-}
+    fn redo(&mut self) {
+        if let Some(info) = self.history.redo() {
+            self.apply_process_info(&info);
+            self.request_preview();
+        }
+    }
 
-fn update_preview_image(original_img: &DynamicImage, border_info: BorderInfo) -> DynamicImage {
-    // Apply border
-    let (width, height) = original_img.dimensions();
+    fn pick_input_dir(&self) {
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn(async move {
+            let path = FileDialog::new().pick_folder();
+            if let Some(path) = path {
+                let _ = tx.send(MessageResult::InputUpdate(path));
+            }
+            ctx.request_repaint();
+        });
+    }
 
-    let (new_width, new_height, x_offset, y_offset) = if border_info.symmetrical_border {
-        let longest_side = width.max(height);
-        let new_size = (longest_side as f32 * (1.0 + border_info.border_percentage / 100.0)) as u32;
-        let delta = new_size - longest_side;
-        let size = { (width + delta, height + delta) };
-        let x_offset = (size.0 - width) / 2;
-        let y_offset = (size.1 - height) / 2;
+    fn pick_texture(&self) {
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn(async move {
+            let path = FileDialog::new()
+                .add_filter(
+                    t!("filter-images"),
+                    &["png", "jpg", "jpeg", "gif", "bmp", "tif", "tiff", "webp"],
+                )
+                .pick_file();
+            if let Some(path) = path {
+                let _ = tx.send(MessageResult::TextureChosen(path));
+            }
+            ctx.request_repaint();
+        });
+    }
 
-        (size.0, size.1, x_offset, y_offset)
-    } else {
-        let longest_side = width.max(height);
-        let new_size = (longest_side as f32 * (1.0 + border_info.border_percentage / 100.0)) as u32;
-        let x_offset = (new_size - width) / 2;
-        let y_offset = (new_size - height) / 2;
+    fn pick_output_dir(&self) {
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn(async move {
+            let path = FileDialog::new().pick_folder();
+            if let Some(path) = path {
+                let _ = tx.send(MessageResult::OutputUpdate(path));
+            }
+            ctx.request_repaint();
+        });
+    }
 
-        (new_size, new_size, x_offset, y_offset)
+    /// Opens the folders of a recent pair, keeping the current output
+    /// folder if none was chosen with it.
+    fn open_recent(&mut self, pair: FolderPair) {
+        if !pair.output.as_os_str().is_empty() {
+            self.output_text = pair.output.display().to_string();
+            self.output_hint = None;
+            self.output_dir = pair.output;
+        }
+        self.set_input_dir(pair.input);
+    }
+
+    /// The images in the order they are processed and numbered in, which
+    /// rows can be dragged to change.
+    fn show_order(&mut self, ui: &mut egui::Ui) {
+        let mut moved = None;
+        let mut reset = false;
+        let mut toggled = None;
+        let mut picked = None;
+        let mut turned = None;
+        let (mut cropping, mut uncropped) = (None, None);
+        let (mut find, mut stop_search, mut include_all) = (false, false, false);
+        egui::CollapsingHeader::new(t!("order-header")).show(ui, |ui| {
+            let count = self.image_paths.len();
+            ui.horizontal(|ui| {
+                ui.weak(t!("order-hint"));
+                reset = ui
+                    .add_enabled(
+                        self.manual_order.is_some() && !self.processing,
+                        egui::Button::new(t!("order-reset")),
+                    )
+                    .clicked();
+                if let Some((done, total)) = self.duplicates.progress {
+                    ui.add(
+                        ProgressBar::new(done as f32 / total.max(1) as f32)
+                            .desired_width(160.0)
+                            .text(t!(
+                                "duplicates-progress",
+                                done = i18n::number(done),
+                                total = i18n::number(total)
+                            )),
+                    );
+                    stop_search = ui.button(t!("button-cancel")).clicked();
+                } else {
+                    find = ui
+                        .add_enabled(
+                            count >= 2 && !self.processing,
+                            egui::Button::new(t!("duplicates-find")),
+                        )
+                        .on_hover_text(t!("duplicates-find-hover"))
+                        .clicked();
+                }
+            });
+            if !self.excluded.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.label(t!(
+                        "order-excluded",
+                        count = i18n::number(self.excluded.len())
+                    ));
+                    include_all = ui
+                        .add_enabled(!self.processing, egui::Button::new(t!("order-include-all")))
+                        .clicked();
+                });
+            }
+            let row_height = ui.spacing().interact_size.y;
+            egui::ScrollArea::vertical().max_height(200.0).show_rows(
+                ui,
+                row_height,
+                count,
+                |ui, rows| {
+                    ui.add_enabled_ui(!self.processing, |ui| {
+                        for index in rows {
+                            let path = &self.image_paths[index];
+                            let id = ui.make_persistent_id(("order", path));
+                            let name = display_name(path);
+                            let edit = self.edits.get(path).copied().unwrap_or_default();
+                            let transform = edit.transform.unwrap_or(self.transform);
+                            let response = ui
+                                .horizontal(|ui| {
+                                    let mut included = !self.excluded.contains(path);
+                                    if a11y::named(
+                                        ui.checkbox(&mut included, ""),
+                                        &t!("order-include", name = name.as_str()),
+                                    )
+                                    .changed()
+                                    {
+                                        toggled = Some((path.clone(), included));
+                                    }
+                                    let position =
+                                        self.combine.selection.iter().position(|p| p == path);
+                                    let full = self.combine.selection.len() >= combine::MAX_IMAGES;
+                                    // Numbered in the order of the combined output.
+                                    let mark = position.map_or("⊞".to_string(), |position| {
+                                        (position + 1).to_string()
+                                    });
+                                    if a11y::named(
+                                        ui.add_enabled(
+                                            position.is_some() || !full,
+                                            egui::Button::new(mark)
+                                                .small()
+                                                .selected(position.is_some()),
+                                        ),
+                                        &t!("combine-pick", name = name.as_str()),
+                                    )
+                                    .clicked()
+                                    {
+                                        picked = Some(path.clone());
+                                    }
+                                    // The keyboard way to reorder.
+                                    if ui
+                                        .add_enabled_ui(index > 0, |ui| {
+                                            a11y::small_icon_button(
+                                                ui,
+                                                "⏶",
+                                                &t!("order-up", name = name.as_str()),
+                                            )
+                                        })
+                                        .inner
+                                        .clicked()
+                                    {
+                                        moved = Some((index, index - 1));
+                                    }
+                                    if ui
+                                        .add_enabled_ui(index + 1 < count, |ui| {
+                                            a11y::small_icon_button(
+                                                ui,
+                                                "⏷",
+                                                &t!("order-down", name = name.as_str()),
+                                            )
+                                        })
+                                        .inner
+                                        .clicked()
+                                    {
+                                        moved = Some((index, index + 2));
+                                    }
+                                    if a11y::small_icon_button(
+                                        ui,
+                                        "⟳",
+                                        &t!("transform-rotate-one", name = name.as_str()),
+                                    )
+                                    .clicked()
+                                    {
+                                        turned = Some((path.clone(), transform.rotated(true)));
+                                    }
+                                    let response = ui
+                                        .dnd_drag_source(id, index, |ui| {
+                                            ui.label(format!("{}. {}", index + 1, name))
+                                                .on_hover_text(path.display().to_string());
+                                        })
+                                        .response;
+                                    if let Some(description) = edit.describe() {
+                                        ui.weak(description);
+                                    }
+                                    response
+                                })
+                                .inner;
+                            response.context_menu(|ui| {
+                                let actions = [
+                                    (t!("transform-rotate-cw"), transform.rotated(true)),
+                                    (t!("transform-rotate-ccw"), transform.rotated(false)),
+                                    (
+                                        t!("transform-flip-horizontal"),
+                                        transform.flipped_horizontally(),
+                                    ),
+                                    (
+                                        t!("transform-flip-vertical"),
+                                        transform.flipped_vertically(),
+                                    ),
+                                ];
+                                for (label, result) in actions {
+                                    if ui.button(label).clicked() {
+                                        turned = Some((path.clone(), result));
+                                        ui.close_menu();
+                                    }
+                                }
+                                if ui
+                                    .add_enabled(
+                                        edit.transform.is_some(),
+                                        egui::Button::new(t!("transform-use-batch")),
+                                    )
+                                    .clicked()
+                                {
+                                    turned = Some((path.clone(), self.transform));
+                                    ui.close_menu();
+                                }
+                                ui.separator();
+                                if ui.button(t!("crop-open")).clicked() {
+                                    cropping = Some(index);
+                                    ui.close_menu();
+                                }
+                                if ui
+                                    .add_enabled(
+                                        !edit.crop.is_identity(),
+                                        egui::Button::new(t!("crop-clear")),
+                                    )
+                                    .clicked()
+                                {
+                                    uncropped = Some(path.clone());
+                                    ui.close_menu();
+                                }
+                            });
+
+                            // Dropped on the upper half goes in front of the row.
+                            let pointer = ui.input(|i| i.pointer.interact_pos());
+                            let (Some(pointer), Some(_)) =
+                                (pointer, response.dnd_hover_payload::<usize>())
+                            else {
+                                continue;
+                            };
+                            let rect = response.rect;
+                            let target = if pointer.y < rect.center().y {
+                                index
+                            } else {
+                                index + 1
+                            };
+                            let y = if target == index {
+                                rect.top()
+                            } else {
+                                rect.bottom()
+                            };
+                            ui.painter()
+                                .hline(rect.x_range(), y, ui.visuals().selection.stroke);
+                            if let Some(from) = response.dnd_release_payload::<usize>() {
+                                moved = Some((*from, target));
+                            }
+                        }
+                    });
+                },
+            );
+        });
+
+        if let Some((from, to)) = moved {
+            let previewed = self.previewed_path().cloned();
+            move_path(&mut self.image_paths, from, to);
+            self.manual_order = Some(self.image_paths.clone());
+            if let Some(index) = previewed
+                .and_then(|previewed| self.image_paths.iter().position(|path| *path == previewed))
+            {
+                self.preview_index = index;
+            }
+        }
+        if reset {
+            self.manual_order = None;
+            self.load_images();
+        }
+        match toggled {
+            Some((path, true)) => {
+                self.excluded.remove(&path);
+            }
+            Some((path, false)) => {
+                self.excluded.insert(path);
+            }
+            None => {}
+        }
+        if include_all {
+            self.excluded.clear();
+        }
+        if let Some((path, transform)) = turned {
+            // Turned like every other image is no override at all.
+            let own = (transform.normalized() != self.transform.normalized()).then_some(transform);
+            self.edit_image(&path, |edit| edit.transform = own);
+        }
+        if let Some(path) = uncropped {
+            self.edit_image(&path, |edit| edit.crop = Crop::default());
+        }
+        if let Some(index) = cropping {
+            self.select_preview(index);
+            self.open_crop_editor();
+        }
+        if let Some(path) = picked {
+            match self.combine.selection.iter().position(|p| *p == path) {
+                Some(position) => {
+                    self.combine.selection.remove(position);
+                }
+                None => self.combine.selection.push(path),
+            }
+        }
+        if find {
+            self.find_duplicates();
+        } else if stop_search {
+            self.cancel_duplicates();
+        }
+    }
+
+    /// The photos picked to be combined, which can be swapped, and how they
+    /// are combined.
+    fn show_combine(&mut self, ui: &mut egui::Ui) {
+        let (mut swap, mut remove, mut start) = (None, None, false);
+        let can_start = self.can_start();
+        egui::CollapsingHeader::new(t!("combine-header")).show(ui, |ui| {
+            let selection = &self.combine.selection;
+            let settings = &mut self.combine.settings;
+            if selection.is_empty() {
+                ui.weak(t!("combine-hint"));
+            }
+            for (index, path) in selection.iter().enumerate() {
+                let name = display_name(path);
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}. {}", index + 1, name))
+                        .on_hover_text(path.display().to_string());
+                    if index + 1 < selection.len()
+                        && a11y::small_icon_button(
+                            ui,
+                            "⇅",
+                            &t!("combine-swap", name = name.as_str()),
+                        )
+                        .clicked()
+                    {
+                        swap = Some(index);
+                    }
+                    if a11y::small_icon_button(ui, "✖", &t!("combine-remove", name = name.as_str()))
+                        .clicked()
+                    {
+                        remove = Some(index);
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut settings.align,
+                    CombineAlign::Heights,
+                    t!("combine-heights"),
+                )
+                .on_hover_text(t!("combine-heights-hover"));
+                ui.radio_value(
+                    &mut settings.align,
+                    CombineAlign::Widths,
+                    t!("combine-widths"),
+                )
+                .on_hover_text(t!("combine-widths-hover"));
+            });
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("combine-gutter"));
+                ui.add(
+                    egui::DragValue::new(&mut settings.gutter_percentage)
+                        .range(0.0..=20.0)
+                        .speed(0.1)
+                        .suffix("%"),
+                )
+                .labelled_by(label.id)
+                .on_hover_text(t!("combine-gutter-hover"));
+            });
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("combine-template"));
+                ui.text_edit_singleline(&mut settings.template)
+                    .labelled_by(label.id)
+                    .on_hover_text(t!("combine-template-hover"));
+            });
+            let template = settings.template.parse::<NameTemplate>();
+            if let Err(e) = &template {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+            }
+            let ready = (combine::MIN_IMAGES..=combine::MAX_IMAGES).contains(&selection.len())
+                && template.is_ok()
+                && can_start;
+            start = ui
+                .add_enabled(ready, egui::Button::new(t!("combine-start")))
+                .on_hover_text(t!("combine-start-hover"))
+                .clicked();
+        });
+
+        if let Some(index) = swap {
+            self.combine.selection.swap(index, index + 1);
+        }
+        if let Some(index) = remove {
+            self.combine.selection.remove(index);
+        }
+        if start {
+            self.start_combine();
+        }
+    }
+
+    /// Shows the picked photos combined in the preview while there are
+    /// enough of them, and the previewed file again once there aren't.
+    fn check_combined_preview(&mut self) {
+        let picked = &self.combine.selection;
+        if picked.len() < combine::MIN_IMAGES {
+            if self.combine.previewed.take().is_some() {
+                self.combine.sources.clear();
+                self.select_preview(self.preview_index);
+            }
+            return;
+        }
+        let info = self.process_info();
+        let key = (
+            picked.clone(),
+            self.combine.settings.clone(),
+            info.border_color,
+            info.trim,
+            info.transform,
+        );
+        if self.combine.previewed.as_ref() == Some(&key) {
+            return;
+        }
+
+        self.combine.sources.retain(|path, _| picked.contains(path));
+        for path in picked {
+            if self.combine.sources.contains_key(path) {
+                continue;
+            }
+            match transform::open_oriented(path) {
+                Ok(img) => {
+                    let img = img.thumbnail(PREVIEW_WORKING_SIZE, PREVIEW_WORKING_SIZE);
+                    self.combine.sources.insert(path.clone(), img);
+                }
+                Err(e) => {
+                    let path = path.clone();
+                    self.combine.selection.retain(|picked| *picked != path);
+                    self.set_status(Level::ERROR, t!("status-load-failed", error = e));
+                    return;
+                }
+            }
+        }
+        let images: Result<Vec<DynamicImage>, crop::CropError> = picked
+            .iter()
+            .map(|path| Ok(prepare_photo(self.combine.sources[path].clone(), &info)?.0))
+            .collect();
+        let images = match images {
+            Ok(images) => images,
+            Err(e) => {
+                self.set_status(Level::ERROR, t!("status-preview-failed", error = e));
+                return;
+            }
+        };
+        let combined = combine::combine(&images, &key.1, key.2);
+        let combined = DynamicImage::ImageRgba8(combined.to_rgba8());
+        self.original_image = Some(Arc::new(combined.clone()));
+        self.original_margins = None;
+        self.preview_source = Some(Arc::new(combined));
+        self.preview_metadata = None;
+        self.combine.previewed = Some(key);
+        self.request_preview();
+    }
+
+    /// Combines the picked photos into one output, in a run folder if one
+    /// is set.
+    fn start_combine(&mut self) {
+        let mode = BatchMode::Combine(self.combine.settings.clone());
+        self.start_arranged(self.combine.selection.clone(), mode);
+    }
+
+    /// Runs `paths` in `mode` without the pre-flight checks of a batch, as
+    /// they make few outputs, in a run folder if one is set.
+    fn start_arranged(&mut self, paths: Vec<PathBuf>, mode: BatchMode) {
+        if !self.can_start() {
+            return;
+        }
+        let output_dir = self
+            .batch_naming()
+            .run_dir(&self.output_dir, dates::now())
+            .unwrap_or_else(|_| self.output_dir.clone());
+        self.run_batch(paths, output_dir, mode);
+    }
+
+    /// How the previewed photo is split into tiles for a carousel.
+    fn show_split(&mut self, ui: &mut egui::Ui) {
+        let mut start = false;
+        let can_start = self.can_start() && self.previewed_path().is_some();
+        egui::CollapsingHeader::new(t!("split-header")).show(ui, |ui| {
+            let split = &mut self.split;
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("split-tiles"));
+                ui.add(
+                    egui::DragValue::new(&mut split.settings.tiles)
+                        .range(split::MIN_TILES..=split::MAX_TILES),
+                )
+                .labelled_by(label.id);
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("split-aspect"));
+                ui.radio_value(
+                    &mut split.settings.aspect,
+                    TileAspect::Square,
+                    t!("split-square"),
+                );
+                ui.radio_value(
+                    &mut split.settings.aspect,
+                    TileAspect::Portrait,
+                    t!("split-portrait"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut split.settings.fit, SplitFit::Crop, t!("split-crop"))
+                    .on_hover_text(t!("split-crop-hover"));
+                ui.radio_value(&mut split.settings.fit, SplitFit::Pad, t!("split-pad"))
+                    .on_hover_text(t!("split-pad-hover"));
+            });
+            ui.checkbox(&mut split.show_tiles, t!("split-show"))
+                .on_hover_text(t!("split-show-hover"));
+            start = ui
+                .add_enabled(can_start, egui::Button::new(t!("split-start")))
+                .on_hover_text(t!("split-start-hover"))
+                .clicked();
+        });
+
+        if start {
+            if let Some(path) = self.previewed_path().cloned() {
+                self.start_arranged(vec![path], BatchMode::Split(self.split.settings));
+            }
+        }
+    }
+
+    /// Keeps the texture the tiles are drawn on in step with the preview
+    /// source while they are shown.
+    fn check_split_texture(&mut self) {
+        let source = match (&self.preview_source, self.split.show_tiles) {
+            (Some(source), true) => source,
+            _ => {
+                self.split.texture = None;
+                return;
+            }
+        };
+        if let Some((_, textured)) = &self.split.texture {
+            if Arc::ptr_eq(textured, source) {
+                return;
+            }
+        }
+        let texture = self.context.load_texture(
+            "split_source",
+            to_color_image(source),
+            egui::TextureOptions::LINEAR,
+        );
+        self.split.texture = Some((texture, source.clone()));
+    }
+
+    fn show_duplicates_dialog(&mut self, ctx: &Context) {
+        let Some(groups) = &mut self.duplicates.groups else {
+            return;
+        };
+        let settings = &mut self.duplicates.settings;
+        let (mut apply, mut close, mut search_again) = (false, false, false);
+
+        egui::Window::new(t!("duplicates-title"))
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                if groups.is_empty() {
+                    ui.label(t!("duplicates-none"));
+                } else {
+                    ui.label(t!(
+                        "duplicates-found",
+                        groups = i18n::number(groups.len()),
+                        files = i18n::number(
+                            groups
+                                .iter()
+                                .map(|(group, _)| group.files.len())
+                                .sum::<usize>()
+                        )
+                    ));
+                    ui.horizontal(|ui| {
+                        ui.label(t!("duplicates-keep"));
+                        let before = settings.keep;
+                        ui.radio_value(
+                            &mut settings.keep,
+                            KeepPolicy::Largest,
+                            t!("duplicates-keep-largest"),
+                        );
+                        ui.radio_value(
+                            &mut settings.keep,
+                            KeepPolicy::Newest,
+                            t!("duplicates-keep-newest"),
+                        );
+                        if settings.keep != before {
+                            for (group, kept) in groups.iter_mut() {
+                                *kept = group.keep(settings.keep);
+                            }
+                        }
+                    });
+                    egui::ScrollArea::vertical()
+                        .max_height(360.0)
+                        .show(ui, |ui| {
+                            for (number, (group, kept)) in groups.iter_mut().enumerate() {
+                                ui.separator();
+                                ui.strong(t!("duplicates-group", number = number + 1));
+                                for (index, file) in group.files.iter().enumerate() {
+                                    let mut details = format!(
+                                        "{} · {}×{} · {}",
+                                        display_name(&file.path),
+                                        file.width,
+                                        file.height,
+                                        i18n::bytes(file.bytes)
+                                    );
+                                    if let Some(modified) = file.modified {
+                                        details.push_str(" · ");
+                                        details.push_str(&i18n::date_time(dates::local(modified)));
+                                    }
+                                    ui.radio_value(kept, index, details)
+                                        .on_hover_text(file.path.display().to_string());
+                                }
+                            }
+                        });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let label = ui.label(t!("duplicates-threshold"));
+                    ui.add(
+                        egui::DragValue::new(&mut settings.threshold)
+                            .range(0..=duplicates::MAX_THRESHOLD),
+                    )
+                    .labelled_by(label.id)
+                    .on_hover_text(t!("duplicates-threshold-hover"));
+                    search_again = ui.button(t!("duplicates-search-again")).clicked();
+                });
+                ui.horizontal(|ui| {
+                    if !groups.is_empty() {
+                        apply = ui
+                            .button(t!("duplicates-apply"))
+                            .on_hover_text(t!("duplicates-apply-hover"))
+                            .clicked();
+                    }
+                    close = ui.button(t!("button-close")).clicked();
+                });
+            });
+
+        if apply {
+            self.apply_duplicates();
+        } else if search_again {
+            self.find_duplicates();
+        } else if close {
+            self.duplicates.groups = None;
+        }
+    }
+
+    /// Makes a folder dropped on the window the input folder, or the folder
+    /// of a dropped file.
+    fn handle_dropped_files(&mut self, ctx: &Context) {
+        if self.processing {
+            return;
+        }
+        let dropped = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .find_map(|file| file.path.clone())
+        });
+        let Some(path) = dropped else {
+            return;
+        };
+        let folder = if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent().map(Path::to_path_buf)
+        };
+        if let Some(folder) = folder.filter(|folder| folder.is_dir()) {
+            self.set_input_dir(folder);
+        }
+    }
+
+    /// What the preview pane shows before there is anything to preview:
+    /// how to open a folder, and the folders opened before.
+    fn show_empty_state(&mut self, ui: &mut egui::Ui) {
+        let mut open = None;
+        let mut remove = None;
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() * 0.1);
+            if self.input_dir.as_os_str().is_empty() {
+                ui.heading(t!("welcome-title"));
+                ui.label(t!("welcome-text"));
+            } else {
+                ui.heading(t!("check-no-images", folder = self.input_dir.display()));
+            }
+            ui.add_space(8.0);
+            if ui
+                .button(t!("welcome-open"))
+                .on_hover_text("Ctrl+O")
+                .clicked()
+            {
+                self.pick_input_dir();
+            }
+            ui.weak(t!("welcome-drop"));
+
+            if self.recent.pairs().is_empty() {
+                return;
+            }
+            ui.add_space(16.0);
+            ui.heading(t!("recent-heading"));
+            egui::Grid::new("recent_folders")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, pair) in self.recent.pairs().iter().enumerate() {
+                        let exists = pair.exists();
+                        if ui
+                            .add_enabled(exists, egui::Link::new(pair.input.display().to_string()))
+                            .on_hover_text(t!("recent-open-hover"))
+                            .on_disabled_hover_text(t!("recent-missing"))
+                            .clicked()
+                        {
+                            open = Some(pair.clone());
+                        }
+                        ui.add_enabled_ui(exists, |ui| {
+                            if pair.output.as_os_str().is_empty() {
+                                ui.weak(t!("recent-no-output"));
+                            } else {
+                                ui.label(format!("→ {}", pair.output.display()));
+                            }
+                        });
+                        if a11y::small_icon_button(ui, "✖", t!("recent-remove")).clicked() {
+                            remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some(pair) = open {
+            self.open_recent(pair);
+        }
+        if let Some(index) = remove {
+            self.recent.remove(index);
+        }
+    }
+
+    fn cancel_batch(&mut self) {
+        self.worker.send(Command::CancelBatch);
+        #[cfg(any(feature = "s3", feature = "sftp"))]
+        self.uploads.cancel();
+        self.paused = false;
+        self.disk_full = None;
+        self.set_status(Level::INFO, t!("status-cancelling"));
+    }
+
+    /// Holds the running batch after the files in progress, or lets it go
+    /// on.
+    fn set_paused(&mut self, paused: bool) {
+        if !self.processing || paused == self.paused {
+            return;
+        }
+        self.paused = paused;
+        if !paused {
+            self.disk_full = None;
+        }
+        if paused {
+            self.worker.send(Command::PauseBatch);
+            self.set_status(Level::INFO, t!("status-paused"));
+        } else {
+            self.worker.send(Command::ResumeBatch);
+            self.set_status(Level::INFO, t!("status-resumed"));
+        }
+    }
+
+    /// Hides the window in the tray, or minimizes it where there is none.
+    fn hide_to_tray(&mut self, ctx: &Context) {
+        if self.tray.is_available() {
+            self.in_tray = true;
+            self.tray.set_visible(true);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+    }
+
+    fn tray_action(&mut self, ctx: &Context, action: TrayAction) {
+        match action {
+            TrayAction::Show => {
+                self.in_tray = false;
+                self.tray.set_visible(false);
+                show_window(ctx);
+            }
+            TrayAction::Pause => self.set_paused(true),
+            TrayAction::Resume => self.set_paused(false),
+            TrayAction::Cancel => {
+                if self.processing {
+                    self.cancel_batch();
+                }
+            }
+            TrayAction::Quit => self.quit(ctx),
+        }
+    }
+
+    /// Closes the app, cancelling the running batch.
+    fn quit(&mut self, ctx: &Context) {
+        if self.processing {
+            self.cancel_batch();
+        }
+        self.quitting = true;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+
+    /// Hides a minimized window in the tray when asked to, and asks what to
+    /// do with a running batch before closing.
+    fn handle_window_events(&mut self, ctx: &Context) {
+        let (minimized, close) =
+            ctx.input(|i| (i.viewport().minimized, i.viewport().close_requested()));
+        if minimized == Some(true)
+            && self.ui_settings.minimize_to_tray
+            && self.tray.is_available()
+            && !self.in_tray
+        {
+            self.hide_to_tray(ctx);
+        }
+        if close && self.processing && !self.quitting {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.close_prompt = true;
+        }
+    }
+
+    fn show_close_prompt(&mut self, ctx: &Context) {
+        if !self.close_prompt {
+            return;
+        }
+        if !self.processing {
+            self.close_prompt = false;
+            return;
+        }
+
+        let mut choice = None;
+        egui::Window::new(t!("close-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(t!("close-running"));
+                ui.horizontal(|ui| {
+                    if self.tray.is_available() && ui.button(t!("close-tray")).clicked() {
+                        choice = Some(CloseChoice::Tray);
+                    }
+                    if ui.button(t!("close-minimize")).clicked() {
+                        choice = Some(CloseChoice::Minimize);
+                    }
+                    if ui.button(t!("close-cancel")).clicked() {
+                        choice = Some(CloseChoice::Quit);
+                    }
+                    if ui.button(t!("close-back")).clicked() {
+                        choice = Some(CloseChoice::Back);
+                    }
+                });
+            });
+
+        let Some(choice) = choice else {
+            return;
+        };
+        self.close_prompt = false;
+        match choice {
+            CloseChoice::Tray => self.hide_to_tray(ctx),
+            CloseChoice::Minimize => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            CloseChoice::Quit => self.quit(ctx),
+            CloseChoice::Back => {}
+        }
+    }
+
+    /// Asks to free space on the drive of the output folder while the batch
+    /// waits, showing how much there is now.
+    fn show_disk_full_dialog(&mut self, ctx: &Context) {
+        let Some(path) = &self.disk_full else {
+            return;
+        };
+        let folder = self.planned_dir.as_ref().unwrap_or(&self.output_dir);
+        let available = space::available_space(folder).ok();
+
+        let (mut resume, mut cancel) = (false, false);
+        egui::Window::new(t!("disk-full-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(t!(
+                    "disk-full-text",
+                    path = display_name(path),
+                    folder = folder.display()
+                ));
+                if let Some(available) = available {
+                    ui.label(t!("disk-full-available", size = i18n::bytes(available)));
+                }
+                ui.horizontal(|ui| {
+                    resume = ui.button(t!("disk-full-resume")).clicked();
+                    cancel = ui.button(t!("button-cancel")).clicked();
+                });
+            });
+        // Shows the space growing as files are deleted.
+        ctx.request_repaint_after(Duration::from_secs(1));
+
+        if resume {
+            self.set_paused(false);
+        } else if cancel {
+            self.cancel_batch();
+        }
+    }
+
+    /// Runs the action of a pressed shortcut. Shifted variants are checked
+    /// first, as `consume_shortcut` also matches them without Shift.
+    fn handle_shortcuts(&mut self, ctx: &Context) {
+        use egui::{Key, KeyboardShortcut, Modifiers};
+
+        let shortcut = |modifiers, key| {
+            ctx.input_mut(|i| i.consume_shortcut(&KeyboardShortcut::new(modifiers, key)))
+        };
+        let shifted = Modifiers::COMMAND | Modifiers::SHIFT;
+
+        let scale = self.ui_settings.scale;
+        if shortcut(Modifiers::COMMAND, Key::Equals) || shortcut(Modifiers::COMMAND, Key::Plus) {
+            self.ui_settings.set_scale(ctx, scale + UI_SCALE_STEP);
+        } else if shortcut(Modifiers::COMMAND, Key::Minus) {
+            self.ui_settings.set_scale(ctx, scale - UI_SCALE_STEP);
+        } else if shortcut(Modifiers::COMMAND, Key::Num0) {
+            self.ui_settings.set_scale(ctx, 1.0);
+        }
+
+        // Settings are locked while a batch runs.
+        if !self.processing {
+            if shortcut(shifted, Key::Z) {
+                self.redo();
+            } else if shortcut(Modifiers::COMMAND, Key::Z) {
+                self.undo();
+            }
+
+            if shortcut(shifted, Key::O) {
+                self.pick_output_dir();
+            } else if shortcut(Modifiers::COMMAND, Key::O) {
+                self.pick_input_dir();
+            }
+        }
+
+        if shortcut(Modifiers::COMMAND, Key::Enter) {
+            self.start();
+        }
+
+        if shortcut(Modifiers::NONE, Key::Escape) {
+            if self.show_shortcuts {
+                self.show_shortcuts = false;
+            } else if self.preflight.is_some() {
+                self.preflight = None;
+            } else if matches!(
+                self.clear_dialog,
+                Some(
+                    ClearDialog::Refused(_)
+                        | ClearDialog::Confirm { .. }
+                        | ClearDialog::Done { .. }
+                )
+            ) {
+                self.clear_dialog = None;
+            } else if self.processing {
+                self.cancel_batch();
+            }
+        }
+
+        // A focused slider or list takes the arrows itself.
+        let count = self.image_paths.len();
+        if count > 0 && ctx.memory(|memory| memory.focused().is_none()) {
+            if shortcut(Modifiers::NONE, Key::ArrowLeft) {
+                self.select_preview((self.preview_index + count - 1) % count);
+            } else if shortcut(Modifiers::NONE, Key::ArrowRight) {
+                self.select_preview((self.preview_index + 1) % count);
+            }
+        }
+    }
+
+    fn show_shortcuts_overlay(&mut self, ctx: &Context) {
+        egui::Window::new(t!("shortcuts-title"))
+            .open(&mut self.show_shortcuts)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+                    for (keys, action) in shortcuts() {
+                        ui.strong(keys);
+                        ui.label(action);
+                        ui.end_row();
+                    }
+                });
+                ui.label(t!("shortcuts-text-fields"));
+            });
+    }
+
+    /// The Simple/Advanced switch. Simple mode only hides options: what was
+    /// set in Advanced still applies, which a badge points out.
+    fn mode_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.ui_settings.advanced, false, t!("mode-simple"));
+            ui.selectable_value(&mut self.ui_settings.advanced, true, t!("mode-advanced"));
+            if self.ui_settings.advanced {
+                return;
+            }
+            let active = self.advanced_in_use();
+            if active.is_empty() {
+                return;
+            }
+            let badge = egui::Button::new(
+                egui::RichText::new(t!("mode-advanced-active"))
+                    .small()
+                    .color(ui.visuals().warn_fg_color),
+            )
+            .small();
+            if ui
+                .add(badge)
+                .on_hover_text(t!(
+                    "mode-advanced-active-hover",
+                    sections = active.join(", ")
+                ))
+                .clicked()
+            {
+                self.ui_settings.advanced = true;
+            }
+        });
+    }
+
+    /// Sections with options that only Advanced mode shows and that are set
+    /// to something other than their default.
+    fn advanced_in_use(&self) -> Vec<&'static str> {
+        let defaults = ProcessInfo::default();
+        let format = FormatSettings::default();
+        let files = self.name_template != DEFAULT_TEMPLATE
+            || self.suffix != DEFAULT_SUFFIX
+            || self.folders != FolderSettings::default()
+            || self.overwrite != OverwritePolicy::default()
+            || self.originals.is_some()
+            || self.manifest.is_some()
+            || self.create_archive;
+        let encoder = match self.output_format {
+            OutputFormat::Png => self.format_settings.png != format.png,
+            OutputFormat::Avif => self.format_settings.avif.speed != format.avif.speed,
+            _ => false,
+        };
+        [
+            (self.preset_rules.enabled, t!("section-preset-rules")),
+            (files, t!("section-files")),
+            (
+                self.symmetrical_border != defaults.symmetrical_border
+                    || self.border_sides.is_some()
+                    || self.trim.enabled
+                        && (self.trim.color != defaults.trim.color
+                            || self.trim.tolerance != defaults.trim.tolerance),
+                t!("section-border"),
+            ),
+            (
+                self.resize_images && self.resize_filter != defaults.resize_filter,
+                t!("section-resize"),
+            ),
+            (encoder, t!("section-format")),
+            (self.preserve_timestamps, t!("section-metadata")),
+            (self.create_srcset, t!("section-srcset")),
+            (self.run_hook, t!("section-hook")),
+            #[cfg(feature = "webhook")]
+            (self.webhook.is_complete(), t!("section-webhook")),
+            #[cfg(any(feature = "s3", feature = "sftp"))]
+            (self.uploads.enabled, t!("section-upload")),
+        ]
+        .into_iter()
+        .filter_map(|(active, section)| active.then_some(section))
+        .collect()
+    }
+
+    /// The border width, in percent of the longest side or in pixels.
+    /// Widths per side replace it.
+    fn border_width_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.add_enabled_ui(self.border_sides.is_none(), |ui| {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .radio_value(&mut self.border_in_pixels, false, t!("border-in-percent"))
+                    .changed();
+                changed |= ui
+                    .radio_value(&mut self.border_in_pixels, true, t!("border-in-pixels"))
+                    .on_hover_text(t!("border-in-pixels-hover"))
+                    .changed();
+            });
+            if self.border_in_pixels {
+                ui.horizontal(|ui| {
+                    let label = ui.label(t!("border-pixels"));
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut self.pixel_border.width)
+                                .range(0..=MAX_DIMENSION / 2)
+                                .suffix(" px"),
+                        )
+                        .labelled_by(label.id)
+                        .changed();
+                    changed |= ui
+                        .add_enabled(
+                            self.resize_images,
+                            egui::Checkbox::new(
+                                &mut self.pixel_border.after_resize,
+                                t!("border-after-resize"),
+                            ),
+                        )
+                        .on_hover_text(t!("border-after-resize-hover"))
+                        .on_disabled_hover_text(t!("border-after-resize-off"))
+                        .changed();
+                });
+            } else {
+                changed |= ui
+                    .add(
+                        Slider::new(&mut self.border_percentage, 0.0..=MAX_BORDER_PERCENTAGE)
+                            .text(t!("border-percentage")),
+                    )
+                    .changed();
+            }
+        })
+        .response
+        .on_disabled_hover_text(t!("border-sides-active"));
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// A border width for every side, starting from the symmetrical border
+    /// of the single width when turned on.
+    fn border_sides_settings(&mut self, ui: &mut egui::Ui) {
+        let mut separate = self.border_sides.is_some();
+        let mut changed = ui
+            .checkbox(&mut separate, t!("border-sides"))
+            .on_hover_text(t!("border-sides-hover"))
+            .changed();
+        if changed {
+            self.border_sides = separate.then(|| BorderSides::symmetrical(self.border_percentage));
+        }
+        if let Some(sides) = &mut self.border_sides {
+            egui::Grid::new("border_sides")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (name, side) in [
+                        (t!("border-top"), &mut sides.top),
+                        (t!("border-right"), &mut sides.right),
+                        (t!("border-bottom"), &mut sides.bottom),
+                        (t!("border-left"), &mut sides.left),
+                    ] {
+                        let label = ui.label(name);
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(side)
+                                    .range(0.0..=MAX_BORDER_PERCENTAGE)
+                                    .speed(0.1)
+                                    .suffix(" %"),
+                            )
+                            .labelled_by(label.id)
+                            .changed();
+                    }
+                    ui.end_row();
+                });
+        }
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// The shape of the canvas: square or the photo's own as the border
+    /// mode says, or one of the usual ratios or a typed one.
+    fn canvas_aspect_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            let label = ui.label(t!("canvas-aspect"));
+            let selected = match self.canvas_aspect {
+                Some(aspect) => aspect.to_string(),
+                None => t!("canvas-aspect-border").to_string(),
+            };
+            egui::ComboBox::from_id_salt("canvas_aspect")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    changed |= ui
+                        .selectable_value(&mut self.canvas_aspect, None, t!("canvas-aspect-border"))
+                        .changed();
+                    for aspect in CanvasAspect::PRESETS {
+                        changed |= ui
+                            .selectable_value(
+                                &mut self.canvas_aspect,
+                                Some(aspect),
+                                aspect.to_string(),
+                            )
+                            .changed();
+                    }
+                })
+                .response
+                .labelled_by(label.id)
+                .on_hover_text(t!("canvas-aspect-hover"));
+            if let Some(aspect) = &mut self.canvas_aspect {
+                changed |= a11y::named(
+                    ui.add(egui::DragValue::new(&mut aspect.width).range(1..=100)),
+                    t!("canvas-aspect-width"),
+                )
+                .changed();
+                ui.label(":");
+                changed |= a11y::named(
+                    ui.add(egui::DragValue::new(&mut aspect.height).range(1..=100)),
+                    t!("canvas-aspect-height"),
+                )
+                .changed();
+            }
+        });
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// A second border of its own width and color, between the photo and
+    /// the main border or around it.
+    fn frame_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui
+                .checkbox(&mut self.add_frame, t!("frame"))
+                .on_hover_text(t!("frame-hover"))
+                .changed();
+            if !self.add_frame {
+                return;
+            }
+            let frame = &mut self.frame;
+            changed |= a11y::named(
+                ui.add(
+                    egui::DragValue::new(&mut frame.width)
+                        .range(0.0..=MAX_FRAME_PERCENTAGE)
+                        .speed(0.05)
+                        .suffix(" %"),
+                ),
+                t!("frame-width"),
+            )
+            .changed();
+            let [r, g, b, a] = frame.color.0;
+            let mut color = Color32::from_rgba_unmultiplied(r, g, b, a);
+            if a11y::named(ui.color_edit_button_srgba(&mut color), t!("frame-color")).changed() {
+                frame.color = Rgba(color.to_srgba_unmultiplied());
+                changed = true;
+            }
+            for (placement, name) in [
+                (FramePlacement::Inside, t!("frame-inside")),
+                (FramePlacement::Outside, t!("frame-outside")),
+            ] {
+                changed |= ui
+                    .radio_value(&mut frame.placement, placement, name)
+                    .changed();
+            }
+        });
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// Rounded corners of the photo, in pixels or in percent of its
+    /// shortest side.
+    fn corner_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui
+                .checkbox(&mut self.rounded_corners, t!("corners"))
+                .on_hover_text(t!("corners-hover"))
+                .changed();
+            if !self.rounded_corners {
+                return;
+            }
+            let corners = &mut self.corner_radius;
+            let (range, suffix) = match corners.unit {
+                RadiusUnit::Pixels => (0.0..=(MAX_DIMENSION / 2) as f32, " px"),
+                RadiusUnit::Percent => (0.0..=50.0, " %"),
+            };
+            changed |= a11y::named(
+                ui.add(
+                    egui::DragValue::new(&mut corners.radius)
+                        .range(range)
+                        .speed(0.5)
+                        .suffix(suffix),
+                ),
+                t!("corners-radius"),
+            )
+            .changed();
+            for (unit, name) in [
+                (RadiusUnit::Pixels, t!("corners-pixels")),
+                (RadiusUnit::Percent, t!("corners-percent")),
+            ] {
+                changed |= ui.radio_value(&mut corners.unit, unit, name).changed();
+            }
+        });
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// The line around the photo: how wide and its color.
+    fn keyline_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui
+                .checkbox(&mut self.draw_keyline, t!("keyline"))
+                .on_hover_text(t!("keyline-hover"))
+                .changed();
+            if !self.draw_keyline {
+                return;
+            }
+            let keyline = &mut self.keyline;
+            changed |= a11y::named(
+                ui.add(
+                    egui::DragValue::new(&mut keyline.width)
+                        .range(0.0..=100.0)
+                        .speed(0.1)
+                        .suffix(" px"),
+                ),
+                t!("keyline-width"),
+            )
+            .changed();
+            let [r, g, b, a] = keyline.color.0;
+            let mut color = Color32::from_rgba_unmultiplied(r, g, b, a);
+            if a11y::named(ui.color_edit_button_srgba(&mut color), t!("keyline-color")).changed() {
+                keyline.color = Rgba(color.to_srgba_unmultiplied());
+                changed = true;
+            }
+        });
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// The shadow under the photo: where it falls, how soft and dark it is
+    /// and its color.
+    fn shadow_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = ui
+            .checkbox(&mut self.drop_shadow, t!("shadow"))
+            .on_hover_text(t!("shadow-hover"))
+            .changed();
+        if self.drop_shadow {
+            let shadow = &mut self.shadow;
+            egui::Grid::new("drop_shadow")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (name, value, range, suffix) in [
+                        (t!("shadow-x"), &mut shadow.offset_x, -500.0..=500.0, " px"),
+                        (t!("shadow-y"), &mut shadow.offset_y, -500.0..=500.0, " px"),
+                        (t!("shadow-blur"), &mut shadow.blur, 0.0..=500.0, " px"),
+                        (t!("shadow-opacity"), &mut shadow.opacity, 0.0..=100.0, " %"),
+                    ] {
+                        let label = ui.label(name);
+                        changed |= ui
+                            .add(egui::DragValue::new(value).range(range).suffix(suffix))
+                            .labelled_by(label.id)
+                            .changed();
+                    }
+                    ui.end_row();
+                });
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("shadow-color"));
+                let [r, g, b, a] = shadow.color.0;
+                let mut color = Color32::from_rgba_unmultiplied(r, g, b, a);
+                if ui
+                    .color_edit_button_srgba(&mut color)
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    shadow.color = Rgba(color.to_srgba_unmultiplied());
+                    changed = true;
+                }
+            });
+        }
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// The border color, with swatches of the usual ones.
+    fn border_color_settings(&mut self, ui: &mut egui::Ui) {
+        let swatches = [
+            (t!("border-color-white"), Color32::WHITE),
+            (t!("border-color-black"), Color32::BLACK),
+            (t!("border-color-cream"), Color32::from_rgb(245, 239, 224)),
+        ];
+        ui.horizontal(|ui| {
+            let label = ui.label(t!("border-color"));
+            let mut changed = ui
+                .color_edit_button_srgba(&mut self.border_color)
+                .labelled_by(label.id)
+                .on_hover_text(t!("border-color-hover"))
+                .changed();
+            for (name, color) in swatches {
+                if ui
+                    .selectable_label(self.border_color == color, name)
+                    .clicked()
+                {
+                    self.border_color = color;
+                    changed = true;
+                }
+            }
+            if changed {
+                self.request_preview();
+            }
+        });
+    }
+
+    /// What fills the border besides the plain border color: a gradient
+    /// from it to a second color, the blurred photo or an image file.
+    fn border_fill_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(t!("fill"));
+            changed |= ui
+                .radio_value(&mut self.border_fill, BorderFill::Solid, t!("fill-solid"))
+                .changed();
+            changed |= ui
+                .radio_value(
+                    &mut self.border_fill,
+                    BorderFill::Gradient(self.gradient),
+                    t!("fill-gradient"),
+                )
+                .on_hover_text(t!("fill-gradient-hover"))
+                .changed();
+            changed |= ui
+                .radio_value(
+                    &mut self.border_fill,
+                    BorderFill::Blur(self.blur_fill),
+                    t!("fill-blur"),
+                )
+                .on_hover_text(t!("fill-blur-hover"))
+                .changed();
+            changed |= ui
+                .radio_value(
+                    &mut self.border_fill,
+                    BorderFill::Texture(self.texture.clone()),
+                    t!("fill-texture"),
+                )
+                .on_hover_text(t!("fill-texture-hover"))
+                .changed();
+        });
+        if let BorderFill::Gradient(_) = self.border_fill {
+            let gradient = &mut self.gradient;
+            ui.horizontal(|ui| {
+                for (shape, name) in [
+                    (GradientShape::Linear, t!("fill-linear")),
+                    (GradientShape::Radial, t!("fill-radial")),
+                ] {
+                    changed |= ui.radio_value(&mut gradient.shape, shape, name).changed();
+                }
+                let label = ui.label(t!("fill-gradient-color"));
+                let [r, g, b, a] = gradient.color.0;
+                let mut color = Color32::from_rgba_unmultiplied(r, g, b, a);
+                if ui
+                    .color_edit_button_srgba(&mut color)
+                    .labelled_by(label.id)
+                    .changed()
+                {
+                    gradient.color = Rgba(color.to_srgba_unmultiplied());
+                    changed = true;
+                }
+            });
+            if gradient.shape == GradientShape::Linear {
+                ui.horizontal(|ui| {
+                    let label = ui.label(t!("fill-angle"));
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut gradient.angle)
+                                .range(0.0..=359.0)
+                                .speed(1.0)
+                                .suffix("°"),
+                        )
+                        .labelled_by(label.id)
+                        .on_hover_text(t!("fill-angle-hover"))
+                        .changed();
+                });
+            }
+            self.border_fill = BorderFill::Gradient(self.gradient);
+        }
+        if let BorderFill::Blur(_) = self.border_fill {
+            let blur = &mut self.blur_fill;
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("fill-blur-strength"));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut blur.strength)
+                            .range(0.0..=25.0)
+                            .speed(0.1)
+                            .suffix(" %"),
+                    )
+                    .labelled_by(label.id)
+                    .on_hover_text(t!("fill-blur-strength-hover"))
+                    .changed();
+                let label = ui.label(t!("fill-blur-tint"));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut blur.tint)
+                            .range(0.0..=100.0)
+                            .speed(1.0)
+                            .suffix(" %"),
+                    )
+                    .labelled_by(label.id)
+                    .on_hover_text(t!("fill-blur-tint-hover"))
+                    .changed();
+            });
+            self.border_fill = BorderFill::Blur(self.blur_fill);
+        }
+        if let BorderFill::Texture(_) = self.border_fill {
+            let mut pick = false;
+            ui.horizontal(|ui| {
+                pick = ui.button(t!("fill-texture-choose")).clicked();
+                match self.texture.path.file_name() {
+                    Some(name) => {
+                        ui.label(name.to_string_lossy())
+                            .on_hover_text(self.texture.path.display().to_string());
+                    }
+                    None => {
+                        ui.weak(t!("fill-texture-none"));
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                for (mode, name, hover) in [
+                    (
+                        TextureMode::Tile,
+                        t!("fill-texture-tile"),
+                        t!("fill-texture-tile-hover"),
+                    ),
+                    (
+                        TextureMode::Stretch,
+                        t!("fill-texture-stretch"),
+                        t!("fill-texture-stretch-hover"),
+                    ),
+                ] {
+                    changed |= ui
+                        .radio_value(&mut self.texture.mode, mode, name)
+                        .on_hover_text(hover)
+                        .changed();
+                }
+            });
+            self.border_fill = BorderFill::Texture(self.texture.clone());
+            if pick {
+                self.pick_texture();
+            }
+        }
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// Trim of the margins inputs already have, and what it finds on the
+    /// previewed photo. Color and tolerance are Advanced only.
+    fn trim_settings(&mut self, ui: &mut egui::Ui, advanced: bool) {
+        let mut changed = ui
+            .checkbox(&mut self.trim.enabled, t!("trim-enabled"))
+            .on_hover_text(t!("trim-enabled-hover"))
+            .changed();
+        if !self.trim.enabled {
+            if changed {
+                self.request_preview();
+            }
+            return;
+        }
+        if advanced {
+            ui.horizontal(|ui| {
+                ui.label(t!("trim-color"));
+                for color in MarginColor::ALL {
+                    changed |= ui
+                        .radio_value(&mut self.trim.color, color, color.label())
+                        .changed();
+                }
+            });
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("trim-tolerance"));
+                changed |= ui
+                    .add(egui::DragValue::new(&mut self.trim.tolerance).range(0..=255))
+                    .labelled_by(label.id)
+                    .on_hover_text(t!("trim-tolerance-hover"))
+                    .changed();
+            });
+        }
+        if changed {
+            self.request_preview();
+        }
+        if let Some((trim, margins)) = self.original_margins {
+            if trim == self.trim && self.combine.previewed.is_none() {
+                ui.weak(
+                    margins
+                        .describe()
+                        .unwrap_or_else(|| t!("trim-none").to_string()),
+                );
+            }
+        }
+    }
+
+    /// Turn and mirroring of every image, and how many are turned on their
+    /// own in the list.
+    fn transform_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(t!("transform-rotation"));
+            for rotation in Rotation::ALL {
+                changed |= ui
+                    .radio_value(
+                        &mut self.transform.rotation,
+                        rotation,
+                        format!("{}°", rotation.degrees()),
+                    )
+                    .changed();
+            }
+        })
+        .response
+        .on_hover_text(t!("transform-hover"));
+        ui.horizontal(|ui| {
+            changed |= ui
+                .checkbox(
+                    &mut self.transform.flip_horizontal,
+                    t!("transform-flip-horizontal"),
+                )
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut self.transform.flip_vertical,
+                    t!("transform-flip-vertical"),
+                )
+                .changed();
+        });
+        let turned = self
+            .edits
+            .values()
+            .filter(|edit| edit.transform.is_some())
+            .count();
+        if turned > 0 {
+            ui.horizontal(|ui| {
+                ui.label(t!("transform-overrides", count = i18n::number(turned)));
+                if ui.button(t!("transform-reset-all")).clicked() {
+                    for edit in self.edits.values_mut() {
+                        edit.transform = None;
+                    }
+                    self.edits.retain(|_, edit| !edit.is_empty());
+                    changed = true;
+                }
+            });
+        }
+        if changed {
+            self.request_preview();
+        }
+    }
+
+    /// Scaled-down copies of every output for websites, off by default.
+    fn srcset_settings(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.create_srcset, t!("srcset-enabled"))
+            .on_hover_text(t!("srcset-enabled-hover"));
+        ui.add_enabled_ui(self.create_srcset, |ui| {
+            let label = ui.label(t!("srcset-widths"));
+            let mut removed = None;
+            ui.horizontal_wrapped(|ui| {
+                let count = self.srcset.widths.len();
+                for (index, width) in self.srcset.widths.iter_mut().enumerate() {
+                    ui.add(
+                        egui::DragValue::new(width)
+                            .range(16..=MAX_DIMENSION)
+                            .suffix(" px"),
+                    )
+                    .labelled_by(label.id);
+                    if count > 1
+                        && a11y::small_icon_button(ui, "✖", &t!("srcset-remove", width = *width))
+                            .clicked()
+                    {
+                        removed = Some(index);
+                    }
+                }
+                if a11y::small_icon_button(ui, "+", t!("srcset-add")).clicked() {
+                    let last = self.srcset.widths.last().copied().unwrap_or(480);
+                    self.srcset.widths.push((last * 2).min(MAX_DIMENSION));
+                }
+            });
+            if let Some(index) = removed {
+                self.srcset.widths.remove(index);
+            }
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("srcset-quality"));
+                ui.add(egui::DragValue::new(&mut self.srcset.quality).range(1..=100))
+                    .labelled_by(label.id)
+                    .on_hover_text(t!("srcset-quality-hover"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("srcset-names"));
+                ui.radio_value(
+                    &mut self.srcset.url_names,
+                    UrlNames::Percent,
+                    t!("srcset-percent"),
+                )
+                .on_hover_text(t!("srcset-percent-hover"));
+                ui.radio_value(
+                    &mut self.srcset.url_names,
+                    UrlNames::Slug,
+                    t!("srcset-slug"),
+                )
+                .on_hover_text(t!("srcset-slug-hover"));
+            });
+            ui.checkbox(&mut self.srcset.html, t!("srcset-html"))
+                .on_hover_text(t!("srcset-html-hover"));
+        });
+    }
+
+    /// The command run for each output, off by default.
+    fn hook_settings(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.run_hook, t!("hook-enabled"))
+            .on_hover_text(t!("hook-enabled-hover"));
+        ui.add_enabled_ui(self.run_hook, |ui| {
+            let label = ui.label(t!("hook-command"));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.hook.command)
+                    .hint_text("exiftool -overwrite_original \"-Artist=Ana\" {output}")
+                    .code_editor()
+                    .desired_width(f32::INFINITY),
+            )
+            .labelled_by(label.id);
+            ui.weak(t!(
+                "hook-placeholders",
+                placeholders = hook::PLACEHOLDERS.join(" ")
+            ));
+            if self.run_hook {
+                if let Err(e) = hook::parse_command(&self.hook.command) {
+                    ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                }
+            }
+            ui.horizontal(|ui| {
+                let label = ui.label(t!("hook-timeout-label"));
+                ui.add(
+                    egui::DragValue::new(&mut self.hook.timeout_secs)
+                        .range(1..=3600)
+                        .suffix(" s"),
+                )
+                .labelled_by(label.id);
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("hook-on-failure"));
+                ui.radio_value(
+                    &mut self.hook.on_failure,
+                    HookFailure::Warn,
+                    t!("hook-warn"),
+                )
+                .on_hover_text(t!("hook-warn-hover"));
+                ui.radio_value(
+                    &mut self.hook.on_failure,
+                    HookFailure::Fail,
+                    t!("hook-fail"),
+                );
+            });
+        });
+    }
+
+    /// Where finished batches are reported to.
+    #[cfg(feature = "webhook")]
+    fn webhook_settings(&mut self, ui: &mut egui::Ui) {
+        let label = ui.label(t!("webhook-url"));
+        ui.add(
+            egui::TextEdit::singleline(&mut self.webhook.url)
+                .hint_text("https://hooks.slack.com/services/…")
+                .desired_width(f32::INFINITY),
+        )
+        .labelled_by(label.id)
+        .on_hover_text(t!("webhook-url-hover"));
+        if !self.webhook.url.trim().is_empty() && !self.webhook.is_complete() {
+            ui.colored_label(ui.visuals().error_fg_color, t!("webhook-invalid"));
+        }
+        ui.checkbox(&mut self.export_webhook, t!("webhook-export"))
+            .on_hover_text(t!("webhook-export-hover"));
+    }
+
+    /// Posts the report of the batch that just ended to the webhook, if one
+    /// is set. Delivery is only logged, whatever becomes of it.
+    #[cfg(feature = "webhook")]
+    fn send_webhook(&self, cancelled: bool) {
+        if !self.webhook.is_complete() {
+            return;
+        }
+        let done = self.completed_images.max(0) as usize;
+        let counts = webhook::BatchCounts {
+            total: self.max_images.max(0) as usize,
+            written: done.saturating_sub(self.skipped_images + self.failed_images),
+            skipped: self.skipped_images,
+            failed: self.failed_images,
+        };
+        let report = webhook::BatchReport::new(
+            &self.batch_dir,
+            cancelled,
+            counts,
+            &self.failures,
+            self.written_bytes,
+            self.batch_started.elapsed(),
+        );
+        let url = self.webhook.url.clone();
+        self.rt.spawn(async move {
+            match webhook::send(&url, &report).await {
+                Ok(()) => tracing::info!("batch reported to the webhook"),
+                Err(e) => tracing::warn!("could not report the batch to the webhook: {}", e),
+            }
+        });
+    }
+
+    /// UI scale slider and its reset. The scale is applied once a drag
+    /// ends, so the slider doesn't move away under the pointer.
+    fn scale_controls(&mut self, ui: &mut egui::Ui) {
+        let mut scale = self.ui_settings.scale;
+        let response = ui
+            .add(
+                Slider::new(&mut scale, UI_SCALE_RANGE)
+                    .step_by(0.05)
+                    .custom_formatter(|value, _| format!("{}%", i18n::decimal(value * 100.0, 0)))
+                    .custom_parser(|text| {
+                        text.trim_end_matches('%')
+                            .trim()
+                            .parse::<f64>()
+                            .ok()
+                            .map(|v| v / 100.0)
+                    })
+                    .text(t!("ui-scale")),
+            )
+            .on_hover_text("Ctrl+= / Ctrl+-");
+        if response.dragged() {
+            self.ui_settings.scale = scale;
+        } else if response.changed() || response.drag_stopped() {
+            self.ui_settings.set_scale(ui.ctx(), scale);
+        }
+        if ui
+            .add_enabled(
+                self.ui_settings.scale != 1.0,
+                egui::Button::new(t!("button-reset")),
+            )
+            .on_hover_text("Ctrl+0")
+            .clicked()
+        {
+            self.ui_settings.set_scale(ui.ctx(), 1.0);
+        }
+    }
+
+    /// Language menu. Texts that were worked out in the old language are
+    /// dropped, so they are worked out again.
+    fn language_controls(&mut self, ui: &mut egui::Ui) {
+        let name = |language: Option<Language>| match language {
+            Some(language) => language.native_name().to_string(),
+            None => t!(
+                "language-system",
+                language = system_language().native_name()
+            ),
+        };
+        let mut language = self.ui_settings.language;
+        a11y::named(
+            egui::ComboBox::from_id_salt("language")
+                .selected_text(name(language))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut language, None, name(None));
+                    for option in Language::ALL {
+                        ui.selectable_value(&mut language, Some(option), name(Some(option)));
+                    }
+                })
+                .response,
+            t!("language-hover"),
+        )
+        .on_hover_text(t!("language-hover"));
+        if language != self.ui_settings.language {
+            self.ui_settings.language = language;
+            self.ui_settings.apply_language();
+            self.blockers_checked = None;
+            self.name_example = None;
+            self.help = Help::default();
+        }
+    }
+
+    fn history_controls(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .add_enabled(
+                self.history.can_undo() && !self.processing,
+                egui::Button::new(t!("undo")),
+            )
+            .on_hover_text("Ctrl+Z")
+            .clicked()
+        {
+            self.undo();
+        }
+        if ui
+            .add_enabled(
+                self.history.can_redo() && !self.processing,
+                egui::Button::new(t!("redo")),
+            )
+            .on_hover_text("Ctrl+Shift+Z")
+            .clicked()
+        {
+            self.redo();
+        }
+    }
+
+    fn preset_controls(&mut self, ui: &mut egui::Ui) {
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            let selected_text = self
+                .selected_preset
+                .clone()
+                .unwrap_or_else(|| t!("preset-select").to_string());
+            a11y::named(
+                egui::ComboBox::from_id_salt("preset")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        for name in &self.presets {
+                            ui.selectable_value(
+                                &mut self.selected_preset,
+                                Some(name.clone()),
+                                name,
+                            );
+                        }
+                    })
+                    .response,
+                t!("section-presets"),
+            );
+
+            if let Some(selected) = &self.selected_preset {
+                if ui.button(t!("button-load")).clicked() {
+                    action = Some(PresetAction::Load(selected.clone()));
+                }
+                if ui.button(t!("button-delete")).clicked() {
+                    action = Some(PresetAction::Delete(selected.clone()));
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let label = ui.label(t!("preset-name"));
+            ui.text_edit_singleline(&mut self.preset_name)
+                .labelled_by(label.id);
+
+            let name = self.preset_name.trim().to_string();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new(t!("preset-save")))
+                .clicked()
+            {
+                action = Some(PresetAction::Save(name.clone()));
+            }
+            if let Some(selected) = &self.selected_preset {
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new(t!("preset-rename")))
+                    .clicked()
+                {
+                    action = Some(PresetAction::Rename(selected.clone(), name));
+                }
+            }
+        });
+
+        if let Some(action) = action {
+            self.apply_preset_action(action);
+        }
+    }
+
+    fn apply_preset_action(&mut self, action: PresetAction) {
+        let result = match &action {
+            PresetAction::Load(name) => self.preset_store.load(name).map(|info| {
+                self.apply_process_info(&info);
+                self.request_preview();
+                t!("status-preset-loaded", name = name)
+            }),
+            PresetAction::Save(name) => {
+                self.preset_store.save(name, &self.process_info()).map(|_| {
+                    self.selected_preset = Some(name.clone());
+                    t!("status-preset-saved", name = name)
+                })
+            }
+            PresetAction::Rename(from, to) => self.preset_store.rename(from, to).map(|_| {
+                self.selected_preset = Some(to.clone());
+                t!("status-preset-renamed", from = from, to = to)
+            }),
+            PresetAction::Delete(name) => self.preset_store.delete(name).map(|_| {
+                self.selected_preset = None;
+                t!("status-preset-deleted", name = name)
+            }),
+        };
+
+        match result {
+            Ok(message) => self.set_status(Level::INFO, message),
+            Err(e) => self.set_status(Level::WARN, t!("status-preset-error", error = e)),
+        }
+        self.presets = self.preset_store.list();
+    }
+
+    fn export_settings(&mut self) {
+        let file = SettingsFile::new(self.process_info())
+            .with_hook(self.run_hook.then(|| self.hook.clone()))
+            .with_webhook(
+                (self.export_webhook && self.webhook.is_complete()).then(|| self.webhook.clone()),
+            );
+        let with_command = file.contains_command;
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn(async move {
+            let path = FileDialog::new()
+                .add_filter(t!("filter-settings"), &["json"])
+                .set_file_name("image-finalizer-settings.json")
+                .save_file();
+            if let Some(path) = path {
+                let result = file
+                    .write(&path)
+                    .map(|_| (path, with_command))
+                    .map_err(|e| e.to_string());
+                let _ = tx.send(MessageResult::SettingsExported(result));
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// Opens `path` in its default application, or shows it in the file
+    /// manager when `reveal` is set, without blocking the UI.
+    fn launch(&self, path: &Path, reveal: bool) {
+        let path = path.to_path_buf();
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn_blocking(move || {
+            let result = if reveal {
+                reveal::reveal(&path)
+            } else {
+                reveal::open(&path)
+            };
+            if let Err(e) = result {
+                let _ = tx.send(MessageResult::LaunchFailed(t!(
+                    "status-open-failed",
+                    path = path.display(),
+                    error = e
+                )));
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Outcome of the files of the batch so far, like "497 ok · 3 failed ·
+    /// 500 total".
+    fn batch_counts(&self) -> String {
+        let done = self.completed_images as usize;
+        let ok = done.saturating_sub(self.failed_images + self.skipped_images);
+        let mut counts = t!("counts-ok", count = i18n::number(ok));
+        if self.failed_images > 0 {
+            counts.push_str(" · ");
+            counts.push_str(&t!(
+                "counts-failed",
+                count = i18n::number(self.failed_images)
+            ));
+        }
+        if self.skipped_images > 0 {
+            counts.push_str(" · ");
+            counts.push_str(&t!(
+                "counts-skipped",
+                count = i18n::number(self.skipped_images)
+            ));
+        }
+        counts.push_str(" · ");
+        counts.push_str(&t!("counts-total", count = i18n::number(self.max_images)));
+        counts
+    }
+
+    /// Progress bar of the running batch. The part for failed files is
+    /// drawn red at the end of the filled part.
+    fn show_batch_progress(&self, ui: &mut egui::Ui) {
+        let total = self.max_images.max(1) as f32;
+        let done = self.completed_images as f32 / total;
+        let response = a11y::named(
+            ui.add(
+                ProgressBar::new(done).text(format!("{}%", i18n::decimal(done as f64 * 100.0, 1))),
+            ),
+            t!("progress-name"),
+        );
+        if self.failed_images > 0 {
+            let rect = response.rect;
+            let failed = self.failed_images as f32 / total;
+            let failures = egui::Rect::from_x_y_ranges(
+                rect.left() + (done - failed) * rect.width()..=rect.left() + done * rect.width(),
+                rect.y_range(),
+            );
+            ui.painter().rect_filled(
+                failures,
+                0.0,
+                ui.visuals().error_fg_color.gamma_multiply(0.8),
+            );
+        }
+    }
+
+    /// Notifies the desktop of the batch that just ended. Where the
+    /// notification can be clicked, that opens the folder it wrote into.
+    fn notify_finished(&self, cancelled: bool) {
+        let body = notify::batch_summary(
+            self.completed_images as usize,
+            self.max_images as usize,
+            self.failed_images,
+            cancelled,
+            self.batch_started.elapsed(),
+        );
+        let output_dir = self.batch_dir.clone();
+        // Not on the runtime: its shutdown would wait for the notification
+        // to be dismissed before the app could exit.
+        std::thread::spawn(move || {
+            if let Err(e) = notify::batch_finished(&body, &output_dir) {
+                tracing::warn!("could not show a notification: {}", e);
+            }
+        });
+    }
+
+    /// Lists the outputs of the last batch with actions to look at them.
+    fn show_results(&mut self, ui: &mut egui::Ui) {
+        let mut launch = None;
+        let mut copy = None;
+        let mut print = None;
+        #[cfg(any(feature = "s3", feature = "sftp"))]
+        let mut retry = None;
+        egui::CollapsingHeader::new(t!(
+            "results-header",
+            count = i18n::number(self.results.len())
+        ))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let selected = self.selected_results.len();
+                if ui
+                    .add_enabled(
+                        selected > 0,
+                        egui::Button::new(t!("print-selected", count = i18n::number(selected))),
+                    )
+                    .on_hover_text(t!("print-selected-hover"))
+                    .clicked()
+                {
+                    // Pages follow the batch order, whatever order they
+                    // were picked in.
+                    print = Some(
+                        self.results
+                            .iter()
+                            .map(|(_, output, _)| output)
+                            .filter(|output| self.selected_results.contains(*output))
+                            .cloned()
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                if ui.button(t!("select-all")).clicked() {
+                    self.selected_results = self
+                        .results
+                        .iter()
+                        .map(|(_, output, _)| output.clone())
+                        .collect();
+                }
+                if ui
+                    .add_enabled(selected > 0, egui::Button::new(t!("select-none")))
+                    .clicked()
+                {
+                    self.selected_results.clear();
+                }
+            });
+            let row_height = ui.spacing().interact_size.y;
+            egui::ScrollArea::vertical().max_height(150.0).show_rows(
+                ui,
+                row_height,
+                self.results.len(),
+                |ui, rows| {
+                    for (input, output, adjustments) in &self.results[rows] {
+                        ui.horizontal(|ui| {
+                            let mut selected = self.selected_results.contains(output);
+                            let name = display_name(output);
+                            if a11y::named(
+                                ui.checkbox(&mut selected, ""),
+                                &t!("print-select", name = name),
+                            )
+                            .changed()
+                            {
+                                if selected {
+                                    self.selected_results.insert(output.clone());
+                                } else {
+                                    self.selected_results.remove(output);
+                                }
+                            }
+                            let mut text =
+                                format!("{} → {}", display_name(input), display_name(output));
+                            if let Some(adjustments) = adjustments {
+                                text.push_str(&format!(" ({})", adjustments));
+                            }
+                            ui.label(text)
+                                .on_hover_text(output.display().to_string())
+                                .context_menu(|ui| {
+                                    if ui.button(t!("copy-image")).clicked() {
+                                        copy = Some((output.clone(), CopyKind::Image));
+                                        ui.close_menu();
+                                    }
+                                    if ui.button(t!("copy-path")).clicked() {
+                                        copy = Some((output.clone(), CopyKind::Path));
+                                        ui.close_menu();
+                                    }
+                                    if ui
+                                        .button(t!("copy-file"))
+                                        .on_hover_text(t!("copy-file-hover"))
+                                        .clicked()
+                                    {
+                                        copy = Some((output.clone(), CopyKind::File));
+                                        ui.close_menu();
+                                    }
+                                    ui.separator();
+                                    if ui.button(t!("print-one")).clicked() {
+                                        print = Some(vec![output.clone()]);
+                                        ui.close_menu();
+                                    }
+                                });
+                            if ui.small_button(t!("button-open")).clicked() {
+                                launch = Some((output.clone(), false));
+                            }
+                            if ui
+                                .small_button(t!("button-reveal"))
+                                .on_hover_text(t!("reveal-hover"))
+                                .clicked()
+                            {
+                                launch = Some((output.clone(), true));
+                            }
+                            #[cfg(any(feature = "s3", feature = "sftp"))]
+                            if self.uploads.show_state(ui, output) {
+                                retry = Some(output.clone());
+                            }
+                        });
+                    }
+                },
+            );
+        });
+        if let Some((path, reveal)) = launch {
+            self.launch(&path, reveal);
+        }
+        if let Some((path, kind)) = copy {
+            self.copy_output(path, kind);
+        }
+        if let Some(pages) = print {
+            self.print_dialog = Some(PrintDialog {
+                pages,
+                page: 0,
+                source: None,
+                loading: None,
+                texture: None,
+                printing: false,
+            });
+        }
+        #[cfg(any(feature = "s3", feature = "sftp"))]
+        if let Some(path) = retry {
+            self.uploads.retry(&path, &self.rt, &self.tx, &self.context);
+        }
+    }
+
+    /// Paper, margins and a preview of every page before printing the
+    /// outputs picked in the results.
+    fn show_print_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.print_dialog else {
+            return;
+        };
+        let border_color = Rgba(self.border_color.to_srgba_unmultiplied());
+        let settings = &mut self.print_settings;
+
+        let shown = dialog.pages[dialog.page].clone();
+        let loaded = dialog
+            .source
+            .as_ref()
+            .is_some_and(|(path, _)| *path == shown);
+        if !loaded && dialog.loading.as_ref() != Some(&shown) {
+            dialog.loading = Some(shown.clone());
+            let path = shown.clone();
+            let ctx = self.context.clone();
+            let tx = self.tx.clone();
+            self.rt.spawn_blocking(move || {
+                // A page that can't be read shows blank; printing says why.
+                let image = transform::open_oriented(&path)
+                    .map(|img| img.thumbnail(PRINT_PREVIEW_SIZE, PRINT_PREVIEW_SIZE))
+                    .unwrap_or_default();
+                let _ = tx.send(MessageResult::PrintPreview {
+                    path,
+                    image: Arc::new(image),
+                });
+                ctx.request_repaint();
+            });
+        }
+
+        let mut open = true;
+        let (mut start, mut cancel) = (false, false);
+        egui::Window::new(t!("print-title"))
+            .id(egui::Id::new("print_dialog"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let label = ui.label(t!("print-paper"));
+                    egui::ComboBox::from_id_salt("print_paper")
+                        .selected_text(settings.paper.label())
+                        .show_ui(ui, |ui| {
+                            for paper in PaperSize::ALL {
+                                ui.selectable_value(&mut settings.paper, paper, paper.label());
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                    ui.radio_value(
+                        &mut settings.orientation,
+                        Orientation::Portrait,
+                        t!("print-portrait"),
+                    );
+                    ui.radio_value(
+                        &mut settings.orientation,
+                        Orientation::Landscape,
+                        t!("print-landscape"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    let label = ui.label(t!("print-margin"));
+                    ui.add(
+                        egui::DragValue::new(&mut settings.margin)
+                            .range(0.0..=MAX_MARGIN)
+                            .speed(0.5)
+                            .suffix(" mm"),
+                    )
+                    .labelled_by(label.id);
+                });
+                ui.checkbox(&mut settings.fill_border, t!("print-fill-border"))
+                    .on_hover_text(t!("print-fill-border-hover"));
+                ui.separator();
+
+                if let Some((_, source)) = dialog.source.as_ref().filter(|(path, _)| *path == shown)
+                {
+                    let current = (*settings, border_color);
+                    if dialog
+                        .texture
+                        .as_ref()
+                        .is_none_or(|(_, settings, color)| (*settings, *color) != current)
+                    {
+                        let (width, height) = settings.page_size();
+                        let page = print::render_page(
+                            source,
+                            settings,
+                            border_color,
+                            PRINT_PREVIEW_SIZE as f32 / width.max(height),
+                        );
+                        let texture = ui.ctx().load_texture(
+                            "print_preview",
+                            to_color_image(&page.into()),
+                            egui::TextureOptions::LINEAR,
+                        );
+                        dialog.texture = Some((texture, current.0, current.1));
+                    }
+                }
+                let size = egui::Vec2::splat(PRINT_PREVIEW_SIZE as f32);
+                match &dialog.texture {
+                    Some((texture, _, _)) => {
+                        // The guides are about the photo, not the paper.
+                        let plain = UiSettings {
+                            guides: GuideSettings::default(),
+                            ..self.ui_settings
+                        };
+                        let alt = t!(
+                            "print-page-alt",
+                            page = dialog.page + 1,
+                            name = display_name(&shown)
+                        );
+                        show_on_backdrop(ui, texture, alt, &plain, size);
+                    }
+                    None => {
+                        ui.allocate_ui(size, |ui| ui.spinner());
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    let pages = dialog.pages.len();
+                    if ui
+                        .add_enabled_ui(dialog.page > 0, |ui| {
+                            a11y::icon_button(ui, "◀", t!("print-previous"))
+                        })
+                        .inner
+                        .clicked()
+                    {
+                        dialog.page -= 1;
+                    }
+                    ui.label(t!("print-page", page = dialog.page + 1, count = pages));
+                    if ui
+                        .add_enabled_ui(dialog.page + 1 < pages, |ui| {
+                            a11y::icon_button(ui, "▶", t!("print-next"))
+                        })
+                        .inner
+                        .clicked()
+                    {
+                        dialog.page += 1;
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !dialog.printing,
+                            egui::Button::new(t!("print-start", count = dialog.pages.len())),
+                        )
+                        .clicked()
+                    {
+                        start = true;
+                    }
+                    if dialog.printing {
+                        ui.spinner();
+                    }
+                    if ui.button(t!("button-cancel")).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if !open || cancel {
+            self.print_dialog = None;
+        } else if start {
+            self.print_pages();
+        }
+    }
+
+    /// Writes the pages of the print dialog into a PDF and hands it to the
+    /// printer, reporting back with `MessageResult::Printed`.
+    fn print_pages(&mut self) {
+        let Some(dialog) = &mut self.print_dialog else {
+            return;
+        };
+        dialog.printing = true;
+        let pages = dialog.pages.clone();
+        let settings = self.print_settings;
+        let border_color = Rgba(self.border_color.to_srgba_unmultiplied());
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn_blocking(move || {
+            // The viewer printing it on Windows may still read the file
+            // once the command is done, so it is left to the system to
+            // clean up.
+            let stamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let pdf = std::env::temp_dir().join(format!("image-finalizer-print-{}.pdf", stamp));
+            let result = print::write_pdf(&pdf, &pages, &settings, border_color)
+                .map_err(|e| e.to_string())
+                .and_then(|()| {
+                    reveal::print(&pdf, settings.paper.media()).map_err(|e| e.to_string())
+                })
+                .map(|()| pages.len());
+            let _ = tx.send(MessageResult::Printed(result));
+            ctx.request_repaint();
+        });
+    }
+
+    fn import_settings(&mut self) {
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn(async move {
+            let path = FileDialog::new()
+                .add_filter(t!("filter-settings"), &["json"])
+                .pick_file();
+            if let Some(path) = path {
+                let result = SettingsFile::read(&path).map_err(|e| e.to_string());
+                let _ = tx.send(MessageResult::SettingsImported(result));
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// Lists what an import would change and applies it once confirmed.
+    fn show_import_dialog(&mut self, ctx: &Context) {
+        let Some(pending) = &self.pending_import else {
+            return;
+        };
+        let imported = pending.file.settings.clone();
+        let hook = pending.file.hook.clone();
+        let mut take_hook = pending.take_hook;
+        let webhook = pending.file.webhook.clone();
+        let mut take_webhook = pending.take_webhook;
+        let missing: Vec<PathBuf> = pending
+            .file
+            .missing_files()
+            .into_iter()
+            .map(Path::to_path_buf)
+            .collect();
+
+        let changes = describe_changes(&self.process_info(), &imported);
+        let mut decision = None;
+
+        egui::Window::new(t!("import-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                if changes.is_empty() {
+                    ui.label(t!("import-same"));
+                } else {
+                    ui.label(t!("import-changes"));
+                    for change in &changes {
+                        ui.label(format!("• {}", change));
+                    }
+                }
+                if !missing.is_empty() {
+                    ui.separator();
+                    ui.colored_label(ui.visuals().warn_fg_color, t!("import-missing-files"));
+                    for path in &missing {
+                        ui.label(format!("• {}", path.display()));
+                    }
+                }
+                if let Some(hook) = &hook {
+                    ui.separator();
+                    ui.colored_label(ui.visuals().warn_fg_color, t!("import-hook-warning"));
+                    ui.code(&hook.command);
+                    ui.checkbox(&mut take_hook, t!("import-hook-take"));
+                }
+                if let Some(webhook) = &webhook {
+                    ui.separator();
+                    ui.label(t!("import-webhook"));
+                    ui.code(&webhook.url);
+                    ui.checkbox(&mut take_webhook, t!("import-webhook-take"));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(t!("button-apply")).clicked() {
+                        decision = Some(true);
+                    }
+                    if ui.button(t!("button-cancel")).clicked() {
+                        decision = Some(false);
+                    }
+                });
+            });
+
+        if let Some(pending) = &mut self.pending_import {
+            pending.take_hook = take_hook;
+            pending.take_webhook = take_webhook;
+        }
+        match decision {
+            Some(true) => {
+                self.pending_import = None;
+                self.apply_process_info(&imported);
+                if let Some(hook) = hook.filter(|_| take_hook) {
+                    self.hook = hook;
+                    self.run_hook = true;
+                }
+                if let Some(webhook) = webhook.filter(|_| take_webhook) {
+                    self.webhook = webhook;
+                }
+                self.request_preview();
+                self.set_status(Level::INFO, t!("status-import-applied"));
+            }
+            Some(false) => self.pending_import = None,
+            None => {}
+        }
+    }
+
+    fn snapshot_preview(&self) -> Option<PinnedPreview> {
+        let image = self.preview_image.clone()?;
+        let info = self.preview_info.clone()?;
+        let texture = self.context.load_texture(
+            "pinned_preview",
+            to_color_image(&image),
+            egui::TextureOptions::LINEAR,
+        );
+
+        Some(PinnedPreview {
+            image,
+            texture,
+            info,
+        })
+    }
+
+    fn save_preview(&mut self) {
+        let Some(composite) = self.preview_composite.clone() else {
+            return;
+        };
+        let file_name = self
+            .previewed_path()
+            .and_then(|path| path.file_stem())
+            .map(|stem| format!("{}_preview.png", stem.to_string_lossy()))
+            .unwrap_or_else(|| "preview.png".to_string());
+
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn(async move {
+            let path = FileDialog::new()
+                .add_filter("PNG", &["png"])
+                .set_file_name(file_name)
+                .save_file();
+            if let Some(path) = path {
+                let result = composite
+                    .save_with_format(&path, ImageFormat::Png)
+                    .map(|_| path)
+                    .map_err(|e| e.to_string());
+                let _ = tx.send(MessageResult::PreviewSaved(result));
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// Copies `kind` of the output at `path` to the clipboard.
+    fn copy_output(&mut self, path: PathBuf, kind: CopyKind) {
+        match kind {
+            CopyKind::Image => self.copy_image(ImageSource::File(path), None),
+            CopyKind::Path => {
+                self.context.copy_text(path.display().to_string());
+                self.set_status(Level::INFO, t!("status-copied-path", path = path.display()));
+            }
+            CopyKind::File => match self.clipboard.set_file(&path) {
+                Ok(()) => {
+                    self.set_status(Level::INFO, t!("status-copied-file", path = path.display()))
+                }
+                Err(e) => self.set_status(Level::ERROR, t!("status-copy-failed", error = e)),
+            },
+        }
+    }
+
+    /// Copies the image of `source` to the clipboard, scaled down to
+    /// `max_side` if given. Without it, a smaller copy is offered first
+    /// for images larger than `clipboard::MAX_SIDE`.
+    fn copy_image(&self, source: ImageSource, max_side: Option<u32>) {
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.rt.spawn_blocking(move || {
+            let message = match source.dimensions() {
+                Ok(dimensions)
+                    if max_side.is_none()
+                        && dimensions.0.max(dimensions.1) > clipboard::MAX_SIDE =>
+                {
+                    MessageResult::CopyTooLarge { source, dimensions }
+                }
+                Ok(_) => MessageResult::CopyImage(source.load(max_side)),
+                Err(e) => MessageResult::CopyImage(Err(e)),
+            };
+            let _ = tx.send(message);
+            ctx.request_repaint();
+        });
+    }
+
+    /// Opens the crop and straighten editor on the previewed image, with
+    /// what is set for it so far.
+    fn open_crop_editor(&mut self) {
+        let (Some(path), Some(source)) = (self.previewed_path(), &self.preview_source) else {
+            return;
+        };
+        if self.combine.previewed.is_some() {
+            return;
+        }
+        let info = self.previewed_info();
+        let uncropped = ProcessInfo {
+            crop: Crop::default(),
+            ..info
+        };
+        let source = match prepare_photo(
+            source.thumbnail(CROP_EDITOR_SIZE, CROP_EDITOR_SIZE),
+            &uncropped,
+        ) {
+            Ok((source, _)) => source,
+            Err(e) => {
+                self.set_status(Level::ERROR, t!("status-preview-failed", error = e));
+                return;
+            }
+        };
+        self.crop_editor = Some(CropEditor {
+            path: path.clone(),
+            crop: info.crop,
+            lock_aspect: false,
+            source,
+            texture: None,
+            drag_start: None,
+        });
+    }
+
+    /// The crop and straighten editor, while open. The crop is dragged
+    /// over the straightened photo and only stored once applied.
+    fn show_crop_editor(&mut self, ctx: &Context) {
+        if self.crop_editor.is_none() {
+            return;
+        }
+        // The shape of the canvas if the settings give it one; otherwise
+        // the photo keeps its own.
+        let canvas_ratio = self.process_info().canvas_ratio();
+        let Some(editor) = &mut self.crop_editor else {
+            return;
+        };
+
+        let mut open = true;
+        let (mut apply, mut cancel) = (false, false);
+        egui::Window::new(t!("crop-title", name = display_name(&editor.path)))
+            .id(egui::Id::new("crop_editor"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([640.0, 560.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let label = ui.label(t!("crop-angle"));
+                    ui.add(
+                        Slider::new(&mut editor.crop.angle, -MAX_ANGLE..=MAX_ANGLE)
+                            .step_by(0.1)
+                            .suffix("°"),
+                    )
+                    .labelled_by(label.id);
+                    if ui.button(t!("crop-level")).clicked() {
+                        editor.crop.angle = 0.0;
+                    }
+                });
+
+                if editor
+                    .texture
+                    .as_ref()
+                    .is_none_or(|(_, angle)| *angle != editor.crop.angle)
+                {
+                    // The slider only gives finite angles, which always turn.
+                    let straightened = crop::straighten(editor.source.clone(), editor.crop.angle)
+                        .unwrap_or_else(|_| editor.source.clone());
+                    let texture = ctx.load_texture(
+                        "crop_editor",
+                        to_color_image(&straightened),
+                        egui::TextureOptions::LINEAR,
+                    );
+                    editor.texture = Some((texture, editor.crop.angle));
+                }
+                let Some((texture, _)) = &editor.texture else {
+                    return;
+                };
+                let [width, height] = texture.size();
+                let size = (width as u32, height as u32);
+                let locked_ratio = canvas_ratio.unwrap_or(width as f32 / height as f32);
+                let ratio = editor.lock_aspect.then_some(locked_ratio);
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .checkbox(&mut editor.lock_aspect, t!("crop-lock-aspect"))
+                        .on_hover_text(t!("crop-lock-aspect-hover"))
+                        .changed()
+                        && editor.lock_aspect
+                        && !editor.crop.rect.is_full()
+                    {
+                        let rect = editor.crop.rect;
+                        editor.crop.rect = CropRect::from_drag(
+                            (rect.x, rect.y),
+                            (rect.x + rect.width, rect.y + rect.height),
+                            size,
+                            Some(locked_ratio),
+                        );
+                    }
+                    if ui.button(t!("crop-whole")).clicked() {
+                        editor.crop.rect = CropRect::FULL;
+                    }
+                });
+                ui.weak(t!("crop-hint"));
+
+                let available =
+                    ui.available_size() - egui::vec2(0.0, 2.0 * ui.spacing().interact_size.y);
+                let scale = (available.x / width as f32)
+                    .min(available.y / height as f32)
+                    .max(0.01);
+                let response = a11y::named(
+                    ui.allocate_response(
+                        egui::vec2(width as f32, height as f32) * scale,
+                        egui::Sense::drag(),
+                    ),
+                    t!("crop-hint"),
+                );
+                let rect = response.rect;
+                let to_fraction = |pos: egui::Pos2| {
+                    (
+                        (pos.x - rect.left()) / rect.width(),
+                        (pos.y - rect.top()) / rect.height(),
+                    )
+                };
+                if response.drag_started() {
+                    editor.drag_start = response.interact_pointer_pos().map(to_fraction);
+                }
+                if let (Some(start), Some(pos)) =
+                    (editor.drag_start, response.interact_pointer_pos())
+                {
+                    if response.dragged() {
+                        editor.crop.rect =
+                            CropRect::from_drag(start, to_fraction(pos), size, ratio);
+                    }
+                }
+                if response.drag_stopped() {
+                    editor.drag_start = None;
+                    // A click rather than a drag keeps the whole photo.
+                    let (_, _, crop_width, crop_height) =
+                        editor.crop.rect.pixels(width as u32, height as u32);
+                    if crop_width < 4 || crop_height < 4 {
+                        editor.crop.rect = CropRect::FULL;
+                    }
+                }
+
+                let painter = ui.painter_at(rect);
+                painter.image(
+                    texture.id(),
+                    rect,
+                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+                let grid = egui::Stroke::new(1.0, Color32::from_white_alpha(60));
+                for step in 1..8 {
+                    let t = step as f32 / 8.0;
+                    painter.vline(rect.left() + rect.width() * t, rect.y_range(), grid);
+                    painter.hline(rect.x_range(), rect.top() + rect.height() * t, grid);
+                }
+                let kept = editor.crop.rect;
+                let kept = egui::Rect::from_min_size(
+                    rect.min + egui::vec2(kept.x * rect.width(), kept.y * rect.height()),
+                    egui::vec2(kept.width * rect.width(), kept.height * rect.height()),
+                );
+                let shade = Color32::from_black_alpha(140);
+                for outside in [
+                    egui::Rect::from_min_max(rect.min, egui::pos2(rect.right(), kept.top())),
+                    egui::Rect::from_min_max(egui::pos2(rect.left(), kept.bottom()), rect.max),
+                    egui::Rect::from_min_max(
+                        egui::pos2(rect.left(), kept.top()),
+                        egui::pos2(kept.left(), kept.bottom()),
+                    ),
+                    egui::Rect::from_min_max(
+                        egui::pos2(kept.right(), kept.top()),
+                        egui::pos2(rect.right(), kept.bottom()),
+                    ),
+                ] {
+                    painter.rect_filled(outside, 0.0, shade);
+                }
+                painter.rect_stroke(
+                    kept,
+                    0.0,
+                    egui::Stroke::new(2.0, Color32::WHITE),
+                    egui::StrokeKind::Inside,
+                );
+
+                ui.horizontal(|ui| {
+                    apply = ui.button(t!("crop-apply")).clicked();
+                    cancel = ui.button(t!("button-cancel")).clicked();
+                    if let Some(description) = editor.crop.describe() {
+                        ui.weak(description);
+                    }
+                });
+            });
+
+        if apply {
+            if let Some(editor) = self.crop_editor.take() {
+                self.edit_image(&editor.path, |edit| edit.crop = editor.crop);
+            }
+        } else if cancel || !open {
+            self.crop_editor = None;
+        }
+    }
+
+    fn show_large_copy_dialog(&mut self, ctx: &Context) {
+        let Some((_, (width, height))) = &self.large_copy else {
+            return;
+        };
+
+        let (mut scaled, mut full, mut cancel) = (false, false, false);
+        egui::Window::new(t!("copy-large-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(t!(
+                    "copy-large-text",
+                    width = width,
+                    height = height,
+                    side = clipboard::MAX_SIDE
+                ));
+                ui.horizontal(|ui| {
+                    scaled = ui.button(t!("copy-large-scaled")).clicked();
+                    full = ui.button(t!("copy-large-full")).clicked();
+                    cancel = ui.button(t!("button-cancel")).clicked();
+                });
+            });
+
+        if scaled || full || cancel {
+            let (source, (width, height)) = self.large_copy.take().expect("checked above");
+            if scaled {
+                self.copy_image(source, Some(clipboard::MAX_SIDE));
+            } else if full {
+                self.copy_image(source, Some(width.max(height)));
+            }
+        }
+    }
+
+    /// Tooltip with the output-space coordinates and color under the cursor,
+    /// sampled from the composite rather than the downscaled texture.
+    fn show_pixel_inspector(&mut self, response: &egui::Response) {
+        let (Some(pos), Some(composite), Some(info)) = (
+            response.hover_pos(),
+            self.preview_composite.clone(),
+            self.preview_info.clone(),
+        ) else {
+            return;
+        };
+        let Some((original_width, original_height)) = self.original_size(&info.trim) else {
+            return;
+        };
+
+        let rect = response.rect;
+        let u = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        let v = ((pos.y - rect.top()) / rect.height()).clamp(0.0, 1.0);
+        let to_pixel = |fraction: f32, size: u32| ((fraction * size as f32) as u32).min(size - 1);
+
+        let (x, y) = (
+            to_pixel(u, composite.width()),
+            to_pixel(v, composite.height()),
+        );
+        let (output_width, output_height) =
+            output_dimensions(original_width, original_height, &info);
+        let (output_x, output_y) = (to_pixel(u, output_width), to_pixel(v, output_height));
+
+        let [r, g, b, a] = composite.get_pixel(x, y).0;
+        let hex = format!("#{:02X}{:02X}{:02X}", r, g, b);
+
+        let loupe = loupe_image(&composite, x, y);
+        let loupe_texture = match &mut self.loupe_texture {
+            Some(texture) => {
+                texture.set(loupe, egui::TextureOptions::NEAREST);
+                texture.clone()
+            }
+            None => {
+                let texture =
+                    self.context
+                        .load_texture("pixel_loupe", loupe, egui::TextureOptions::NEAREST);
+                self.loupe_texture = Some(texture.clone());
+                texture
+            }
+        };
+
+        response.clone().on_hover_ui_at_pointer(|ui| {
+            ui.image((
+                loupe_texture.id(),
+                egui::vec2(LOUPE_DISPLAY_SIZE, LOUPE_DISPLAY_SIZE),
+            ));
+            ui.label(format!("x {}  y {}", output_x, output_y));
+            ui.label(format!("RGBA {} {} {} {}", r, g, b, a));
+            ui.label(&hex);
+            ui.weak(t!("inspector-copy"));
+        });
+
+        if response.clicked() {
+            self.context.copy_text(hex.clone());
+            self.set_status(Level::INFO, t!("status-copied", color = hex));
+        }
+    }
+
+    /// Restores the pinned settings as the live ones and pins what was live.
+    fn swap_comparison(&mut self) {
+        let Some(pinned) = self.comparison.take() else {
+            return;
+        };
+
+        self.comparison = self.snapshot_preview();
+        self.apply_process_info(&pinned.info);
+        self.preview_info = Some(pinned.info);
+        self.preview_image = Some(pinned.image);
+        self.preview_composite = None;
+        self.update_preview_texture();
+        self.request_preview();
+    }
+
+    /// Regenerates the preview from the current settings, superseding any
+    /// preview that is still being generated.
+    fn request_preview(&mut self) {
+        self.preview_generation += 1;
+        self.preview_pending = false;
+
+        // Found on the original rather than the preview source, so sizes
+        // shown for the output are exact.
+        let trim = self.previewed_info().trim;
+        if let Some(original) = &self.original_image {
+            if self
+                .original_margins
+                .is_none_or(|(found_with, _)| found_with != trim)
+            {
+                self.original_margins = Some((trim, trim.margins(original)));
+            }
+        }
+
+        if let Some(img) = &self.preview_source {
+            // The working copy is smaller than the photo, and so should
+            // be whatever is set in pixels.
+            let scale = match &self.original_image {
+                Some(original) => img.width() as f32 / original.width().max(1) as f32,
+                None => 1.0,
+            };
+            self.preview_pending = true;
+            self.worker.send(Command::RequestPreview {
+                generation: self.preview_generation,
+                path: self.previewed_path().cloned().unwrap_or_default(),
+                source: img.clone(),
+                info: self.previewed_info(),
+                scale,
+            });
+        }
+    }
+
+    /// Drops everything derived from the previously previewed file so the UI
+    /// never shows an image that is no longer part of the input.
+    fn clear_preview(&mut self) {
+        // Invalidate results of previews that are still being generated
+        self.preview_generation += 1;
+        self.preview_pending = false;
+
+        self.original_image = None;
+        self.original_margins = None;
+        self.preview_source = None;
+        self.preview_image = None;
+        self.preview_composite = None;
+        self.preview_texture = None;
+        self.preview_histogram = None;
+        self.preview_metadata = None;
+        self.preview_info = None;
+    }
+
+    fn load_original_image(&mut self, image_path: &Path) {
+        match transform::open_oriented(image_path) {
+            Ok(img) => {
+                // Convert the image to RGBA if it's not already
+                let img = DynamicImage::ImageRgba8(img.to_rgba8());
+
+                // Compositing at full resolution only to throw most of it away in
+                // the display downscale is wasted work, so the preview runs on a
+                // copy that is still comfortably larger than the preview area.
+                let preview_source = if img.width().max(img.height()) > PREVIEW_WORKING_SIZE {
+                    img.thumbnail(PREVIEW_WORKING_SIZE, PREVIEW_WORKING_SIZE)
+                } else {
+                    img.clone()
+                };
+
+                self.original_image = Some(Arc::new(img));
+                self.original_margins = None;
+                self.preview_source = Some(Arc::new(preview_source));
+            }
+            Err(e) => {
+                self.clear_preview();
+                self.set_status(Level::ERROR, t!("status-load-failed", error = e));
+            }
+        }
+    }
+
+    fn update_preview_texture(&mut self) {
+        if let Some(img) = &self.preview_image {
+            let image = to_color_image(img);
+            let size = image.size;
+
+            match &mut self.preview_texture {
+                Some(texture) if texture.size() == size => {
+                    texture.set(image, egui::TextureOptions::LINEAR);
+                }
+                _ => {
+                    self.preview_texture = Some(self.context.load_texture(
+                        "preview_image",
+                        image,
+                        egui::TextureOptions::LINEAR,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Works out `start_blockers` again when what they depend on changed.
+    /// Cheap enough for every frame otherwise, as it reads no inputs.
+    fn check_blockers(&mut self) {
+        let checked = (
+            self.input_dir.clone(),
+            self.output_dir.clone(),
+            self.batch_count(),
+            self.process_info(),
+            self.batch_naming(),
+        );
+        if self.blockers_checked.as_ref() != Some(&checked) {
+            self.start_blockers =
+                validation::blockers(&checked.0, &checked.1, checked.2, &checked.3, &checked.4);
+            self.blockers_checked = Some(checked);
+        }
+    }
+
+    /// Asks for an estimate of the output size once the settings have
+    /// settled, unless one for them is known.
+    fn check_estimate(&mut self, ctx: &Context) {
+        if self.processing || self.batch_count() == 0 {
+            return;
+        }
+        let inputs = (
+            self.input_dir.clone(),
+            self.batch_count(),
+            self.process_info(),
+            self.preset_rules.clone(),
+        );
+        let changed = match &self.estimates.inputs {
+            Some((seen, _)) => *seen != inputs,
+            None => true,
+        };
+        if changed {
+            self.estimates.inputs = Some((inputs, Instant::now()));
+            self.estimates.current = None;
+        }
+        if self.estimates.current.is_some() {
+            return;
+        }
+        let since = self
+            .estimates
+            .inputs
+            .as_ref()
+            .map_or(Instant::now(), |(_, since)| *since);
+        match ESTIMATE_SETTLE.checked_sub(since.elapsed()) {
+            Some(left) if !left.is_zero() => ctx.request_repaint_after(left),
+            _ => self.request_estimate(),
+        }
+    }
+
+    /// Estimates the output size with the current settings on the worker,
+    /// cancelling an estimate for other settings.
+    fn request_estimate(&mut self) {
+        if self.batch_count() == 0 {
+            return;
+        }
+        let (rules, _) = self.preset_rules.resolve(&self.preset_store);
+        let manual = self.process_info();
+        let mut key = format!(
+            "{}:{}:{}",
+            self.input_dir.display(),
+            self.batch_count(),
+            settings_hash(&manual)
+        );
+        for rule in &rules {
+            key.push_str(&format!(
+                ":{:?}={}",
+                rule.condition,
+                settings_hash(&rule.info)
+            ));
+        }
+        self.estimates.current = Some(key.clone());
+        if self.estimates.cache.contains_key(&key) || self.estimates.pending.as_ref() == Some(&key)
+        {
+            return;
+        }
+        self.estimates.pending = Some(key.clone());
+        self.worker.send(Command::EstimateSize {
+            key,
+            paths: self.batch_paths(),
+            rules,
+            manual,
+        });
+    }
+
+    /// The estimate for the current settings, once there is one.
+    fn current_estimate(&self) -> Option<&SizeEstimate> {
+        let key = self.estimates.current.as_ref()?;
+        self.estimates.cache.get(key)?.as_ref().ok()
+    }
+
+    /// The estimated output size next to Start, marked as an estimate.
+    fn show_estimate(&self, ui: &mut egui::Ui) {
+        let Some(key) = &self.estimates.current else {
+            return;
+        };
+        match self.estimates.cache.get(key) {
+            Some(Ok(estimate)) if estimate.exact => {
+                ui.label(t!("estimate-exact", size = i18n::bytes(estimate.total)));
+            }
+            Some(Ok(estimate)) => {
+                ui.label(t!(
+                    "estimate-range",
+                    size = i18n::bytes(estimate.total),
+                    low = i18n::bytes(estimate.low),
+                    high = i18n::bytes(estimate.high)
+                ))
+                .on_hover_text(t!("estimate-hover", count = i18n::number(estimate.sampled)));
+            }
+            Some(Err(e)) => {
+                ui.weak(t!("estimate-failed", error = e));
+            }
+            None if self.estimates.pending.as_ref() == Some(key) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.weak(t!("estimate-pending"));
+                });
+            }
+            None => {}
+        }
+    }
+
+    /// Whether a batch can be started: none is running or being checked,
+    /// and nothing blocks it.
+    fn can_start(&self) -> bool {
+        !self.processing
+            && !self.validating
+            && self.preflight.is_none()
+            && self.start_blockers.is_empty()
+    }
+
+    /// Runs the pre-flight checks off-thread. The batch starts once they
+    /// pass, or after the user confirms the warnings. Does nothing unless
+    /// `can_start`, so repeated clicks start one batch.
+    fn start(&mut self) {
+        if !self.can_start() {
+            return;
+        }
+        let (rules, rule_errors) = self.preset_rules.resolve(&self.preset_store);
+        let manual = self.process_info();
+        let input_dir = self.input_dir.clone();
+        let paths = self.batch_paths();
+        let naming = self.batch_naming();
+        // Resolved once, so the checks and the batch agree on the minute.
+        // A broken pattern is reported by the checks.
+        let output_dir = naming
+            .run_dir(&self.output_dir, dates::now())
+            .unwrap_or_else(|_| self.output_dir.clone());
+        self.planned_dir = Some(output_dir.clone());
+        let estimated = self.current_estimate().map(|estimate| estimate.high);
+        let hook = self.run_hook.then(|| self.hook.command.clone());
+        #[cfg(any(feature = "s3", feature = "sftp"))]
+        let upload = self.uploads.to_connect();
+        let tx = self.tx.clone();
+        let ctx = self.context.clone();
+
+        self.validating = true;
+        self.set_status(Level::INFO, t!("status-checking"));
+        self.rt.spawn(async move {
+            let mut validation =
+                validation::validate(&input_dir, &output_dir, &paths, &rules, manual, &naming);
+            // A rule without its preset would silently fall back to the
+            // manual settings.
+            validation.errors.extend(rule_errors);
+            if let Some(Err(e)) = hook.as_deref().map(hook::parse_command) {
+                validation.errors.push(t!("check-hook", error = e));
+            }
+            let needed = estimated.unwrap_or(validation.expected_bytes);
+            validation.check_space(&output_dir, needed);
+            #[cfg(any(feature = "s3", feature = "sftp"))]
+            if let Some(target) = upload {
+                let bucket = target.name();
+                match upload_ui::connect(target).await {
+                    Ok(destination) => {
+                        let _ = tx.send(MessageResult::Upload(upload_ui::Message::Ready(
+                            destination,
+                        )));
+                    }
+                    Err(e) => {
+                        validation
+                            .errors
+                            .push(t!("check-upload", bucket = bucket, error = e))
+                    }
+                }
+            }
+            let _ = tx.send(MessageResult::Validated(validation));
+            ctx.request_repaint();
+        });
+    }
+
+    fn show_preflight_dialog(&mut self, ctx: &Context) {
+        let Some(validation) = &self.preflight else {
+            return;
+        };
+
+        let blocked = !validation.errors.is_empty();
+        let count = self.batch_count();
+        let summarized = count >= self.ui_settings.confirm_from;
+        let estimate = self.current_estimate().copied();
+        let mut decision = None;
+
+        egui::Window::new(if blocked {
+            t!("preflight-blocked").to_string()
+        } else if summarized {
+            t!("preflight-summary-title", count = i18n::number(count))
+        } else {
+            t!("preflight-confirm").to_string()
+        })
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            if summarized {
+                let folder = self.planned_dir.as_ref().unwrap_or(&self.output_dir);
+                for line in batch_summary(validation, count, folder, estimate.as_ref()) {
+                    ui.label(line);
+                }
+                ui.horizontal(|ui| {
+                    let label = ui.label(t!("preflight-summary-threshold"));
+                    ui.add(
+                        egui::DragValue::new(&mut self.ui_settings.confirm_from)
+                            .range(1..=1_000_000),
+                    )
+                    .labelled_by(label.id);
+                });
+                ui.separator();
+            } else if let Some(dir) = self
+                .planned_dir
+                .as_ref()
+                .filter(|dir| **dir != self.output_dir)
+            {
+                ui.label(t!("preflight-run-folder", folder = dir.display()));
+            }
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for error in &validation.errors {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("• {}", error));
+                    }
+                    for warning in &validation.warnings {
+                        ui.colored_label(ui.visuals().warn_fg_color, format!("• {}", warning));
+                    }
+                });
+            if blocked {
+                ui.label(log_hint());
+            } else if validation.overlap.is_some() {
+                ui.checkbox(&mut self.overlap_confirmed, t!("preflight-overlap"));
+            }
+            let confirmed = validation.overlap.is_none() || self.overlap_confirmed;
+            ui.horizontal(|ui| {
+                if blocked {
+                    if ui.button(t!("button-close")).clicked() {
+                        decision = Some(false);
+                    }
+                } else {
+                    let start = if validation.is_clean() {
+                        t!("start-button")
+                    } else {
+                        t!("button-start-anyway")
+                    };
+                    if ui
+                        .add_enabled(confirmed, egui::Button::new(start))
+                        .clicked()
+                    {
+                        decision = Some(true);
+                    }
+                    if ui.button(t!("button-cancel")).clicked() {
+                        decision = Some(false);
+                    }
+                }
+            });
+        });
+
+        match decision {
+            Some(true) => {
+                self.preflight = None;
+                self.process_images();
+            }
+            Some(false) => self.preflight = None,
+            None => {}
+        }
+    }
+
+    /// Loads the presets the rules refer to, reporting rules that can't be used.
+    fn resolve_rules(&mut self) -> Vec<ResolvedRule> {
+        let (rules, errors) = self.preset_rules.resolve(&self.preset_store);
+        if !errors.is_empty() {
+            self.set_status(Level::WARN, errors.join("\n"));
+        }
+        rules
+    }
+
+    /// Works out where every input would be written and with which settings
+    /// without processing it.
+    fn plan_rules(&mut self) {
+        self.request_estimate();
+        let rules = self.resolve_rules();
+        let manual = self.process_info();
+        let edits = self.edits.clone();
+        let naming = self.batch_naming();
+        let paths = self.batch_paths();
+        let tx = self.tx.clone();
+        let ctx = self.context.clone();
+        self.rt.spawn(async move {
+            let template = naming.parse_template().map_err(|e| e.to_string());
+            let plan = paths
+                .into_iter()
+                .enumerate()
+                .map(|(index, path)| {
+                    let chosen =
+                        choose_settings(&path, &rules, manual.clone()).with_edit(edits.get(&path));
+                    let output = template.clone().and_then(|template| {
+                        let (dimensions, bits) = validation::read_header(&path)
+                            .map_or(((0, 0), 8), |(dimensions, color)| {
+                                (dimensions, bit_depth(color))
+                            });
+                        planned_output(
+                            &template,
+                            &naming,
+                            &path,
+                            &chosen.info,
+                            dimensions,
+                            bits,
+                            index,
+                            chosen.preset.as_deref(),
+                        )
+                    });
+                    PlannedFile {
+                        path,
+                        output,
+                        applied: chosen.applied,
+                    }
+                })
+                .collect();
+            let _ = tx.send(MessageResult::RulePlan(plan));
+            ctx.request_repaint();
+        });
+    }
+
+    fn session(&self) -> Session {
+        Session {
+            settings: self.process_info(),
+            input_dir: self.input_dir.clone(),
+            output_dir: self.output_dir.clone(),
+            edits: self.edits.clone(),
+            batch: self.batch.clone(),
+        }
+    }
+
+    fn session_changed(&self) -> bool {
+        self.saved_session.as_ref().is_none_or(|saved| {
+            saved.settings != self.process_info()
+                || saved.input_dir != self.input_dir
+                || saved.output_dir != self.output_dir
+                || saved.edits != self.edits
+                || saved.batch != self.batch
+        })
+    }
+
+    /// Saves the session for crash recovery at most every few seconds. Held
+    /// back while a stale session waits for a decision so it isn't lost.
+    fn autosave_session(&mut self) {
+        if self.stale_session.is_some() || !self.session_changed() {
+            return;
+        }
+
+        let elapsed = self.last_autosave.elapsed();
+        if elapsed < AUTOSAVE_INTERVAL {
+            self.context
+                .request_repaint_after(AUTOSAVE_INTERVAL - elapsed);
+            return;
+        }
+
+        let session = self.session();
+        if let Err(e) = session.save() {
+            tracing::warn!("could not save the session: {}", e);
+        }
+        self.saved_session = Some(session);
+        self.last_autosave = Instant::now();
+    }
+
+    /// Lists the contents of the output folder for the clear dialog.
+    fn plan_clear(&mut self) {
+        let output_dir = self.output_dir.clone();
+        let input_dir = self.input_dir.clone();
+        let suffix = self.suffix.clone();
+        let archive = self.archive.name.clone();
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        self.clear_dialog = Some(ClearDialog::Planning);
+        self.rt.spawn_blocking(move || {
+            let plan = clear::plan_clear(&output_dir, &input_dir, &suffix, &archive)
+                .map_err(|e| e.to_string());
+            let _ = tx.send(MessageResult::ClearPlanned(plan));
+            ctx.request_repaint();
+        });
+    }
+
+    fn start_clear(&mut self, plan: ClearPlan) {
+        let ctx = self.context.clone();
+        let tx = self.tx.clone();
+        tracing::info!(
+            "clearing {}: {} files",
+            self.output_dir.display(),
+            plan.entries.len()
+        );
+        self.clear_dialog = Some(ClearDialog::Clearing {
+            done: 0,
+            total: plan.entries.len(),
+        });
+        self.rt.spawn_blocking(move || {
+            let outcome = clear::clear(&plan, |done, total| {
+                let _ = tx.send(MessageResult::ClearProgress { done, total });
+                ctx.request_repaint();
+            });
+            for error in &outcome.errors {
+                tracing::warn!("could not delete {}", error);
+            }
+            let _ = tx.send(MessageResult::ClearDone {
+                deleted: outcome.deleted,
+                errors: outcome.errors.iter().map(|e| e.to_string()).collect(),
+            });
+            ctx.request_repaint();
+        });
+    }
+
+    fn show_clear_dialog(&mut self, ctx: &Context) {
+        let Some(dialog) = &mut self.clear_dialog else {
+            return;
+        };
+
+        let mut close = false;
+        let mut confirmed = false;
+        egui::Window::new(t!("clear-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(self.output_dir.display().to_string());
+                match dialog {
+                    ClearDialog::Planning => {
+                        ui.spinner();
+                    }
+                    ClearDialog::Refused(reason) => {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            t!("clear-refused", reason = reason),
+                        );
+                        close = ui.button(t!("button-close")).clicked();
+                    }
+                    ClearDialog::Confirm { plan, typed } => {
+                        if plan.entries.is_empty() && plan.folders.is_empty() {
+                            ui.label(t!("clear-empty"));
+                            close = ui.button(t!("button-close")).clicked();
+                            return;
+                        }
+                        ui.label(t!(
+                            "clear-summary",
+                            files = i18n::number(plan.entries.len()),
+                            folders = i18n::number(plan.folders.len()),
+                            size = i18n::bytes(plan.total_bytes())
+                        ));
+                        let unknown: Vec<_> = plan.unknown().collect();
+                        if !unknown.is_empty() {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                t!("clear-unknown", count = i18n::number(unknown.len())),
+                            );
+                            egui::ScrollArea::vertical()
+                                .max_height(150.0)
+                                .show(ui, |ui| {
+                                    for entry in unknown.iter().take(CLEAR_LISTED_UNKNOWN) {
+                                        ui.label(entry.path.display().to_string());
+                                    }
+                                    if unknown.len() > CLEAR_LISTED_UNKNOWN {
+                                        ui.label(t!(
+                                            "clear-more",
+                                            count =
+                                                i18n::number(unknown.len() - CLEAR_LISTED_UNKNOWN)
+                                        ));
+                                    }
+                                });
+                            ui.horizontal(|ui| {
+                                let label = ui.label(t!("clear-type", word = clear_confirmation()));
+                                ui.text_edit_singleline(typed).labelled_by(label.id);
+                            });
+                        }
+                        let allowed = unknown.is_empty() || typed.trim() == clear_confirmation();
+                        ui.horizontal(|ui| {
+                            confirmed = ui
+                                .add_enabled(allowed, egui::Button::new(t!("button-delete")))
+                                .clicked();
+                            close = ui.button(t!("button-cancel")).clicked();
+                        });
+                    }
+                    ClearDialog::Clearing { done, total } => {
+                        ui.add(
+                            ProgressBar::new(*done as f32 / (*total).max(1) as f32).text(t!(
+                                "clear-progress",
+                                done = i18n::number(*done),
+                                total = i18n::number(*total)
+                            )),
+                        );
+                    }
+                    ClearDialog::Done { deleted, errors } => {
+                        ui.label(t!("clear-deleted", count = i18n::number(*deleted)));
+                        if !errors.is_empty() {
+                            ui.colored_label(
+                                ui.visuals().error_fg_color,
+                                t!("clear-failed", count = i18n::number(errors.len())),
+                            );
+                            egui::ScrollArea::vertical()
+                                .max_height(150.0)
+                                .show(ui, |ui| {
+                                    for error in errors.iter() {
+                                        ui.colored_label(ui.visuals().error_fg_color, error);
+                                    }
+                                });
+                        }
+                        close = ui.button(t!("button-close")).clicked();
+                    }
+                }
+            });
+
+        if close {
+            self.clear_dialog = None;
+        } else if confirmed {
+            if let Some(ClearDialog::Confirm { plan, .. }) = self.clear_dialog.take() {
+                self.start_clear(plan);
+            }
+        }
+    }
+
+    fn show_recovery_dialog(&mut self, ctx: &Context) {
+        let Some(session) = &self.stale_session else {
+            return;
+        };
+
+        let remaining = session
+            .batch
+            .as_ref()
+            .map(BatchState::remaining)
+            .unwrap_or_default();
+        let mut decision = None;
+
+        egui::Window::new(t!("recovery-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(t!("recovery-crashed"));
+                if let Some(batch) = &session.batch {
+                    if !remaining.is_empty() {
+                        ui.label(t!(
+                            "recovery-remaining",
+                            remaining = i18n::number(remaining.len()),
+                            total = i18n::number(batch.files.len())
+                        ));
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if !remaining.is_empty() && ui.button(t!("recovery-resume")).clicked() {
+                        decision = Some(Recovery::Resume);
+                    }
+                    if ui.button(t!("recovery-restore")).clicked() {
+                        decision = Some(Recovery::Restore);
+                    }
+                    if ui.button(t!("recovery-discard")).clicked() {
+                        decision = Some(Recovery::Discard);
+                    }
+                });
+            });
+
+        let Some(decision) = decision else {
+            return;
+        };
+        let Some(session) = self.stale_session.take() else {
+            return;
+        };
+
+        if let Recovery::Discard = decision {
+            Session::remove();
+            return;
+        }
+
+        self.apply_process_info(&session.settings);
+        self.edits = session.edits;
+        self.set_output_dir(session.output_dir);
+        if session.input_dir.is_dir() {
+            self.set_input_dir(session.input_dir);
+        }
+        self.request_preview();
+
+        if let Recovery::Resume = decision {
+            let output_dir = session
+                .batch
+                .and_then(|batch| batch.output_dir)
+                .unwrap_or_else(|| self.output_dir.clone());
+            self.run_batch(remaining, output_dir, BatchMode::Process);
+        }
+    }
+
+    /// The recent log lines, filtered by severity.
+    /// Shows `message` in the status line and adds it to the log at `level`.
+    fn set_status(&mut self, level: Level, message: impl Into<String>) {
+        let message = message.into();
+        match level {
+            Level::ERROR => tracing::error!("{}", message),
+            Level::WARN => tracing::warn!("{}", message),
+            _ => tracing::info!("{}", message),
+        }
+        self.status_message = message;
+    }
+
+    fn show_log(&mut self, ui: &mut egui::Ui) {
+        let entries = self.log.entries(self.log_level, &self.log_filter);
+
+        ui.horizontal(|ui| {
+            let label = ui.label(t!("log-verbosity"));
+            egui::ComboBox::from_id_salt("log_level")
+                .selected_text(self.log_level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG] {
+                        ui.selectable_value(&mut self.log_level, level, level.as_str());
+                    }
+                })
+                .response
+                .labelled_by(label.id)
+                .on_hover_text(t!("log-verbosity-hover"));
+            a11y::named(
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.log_filter)
+                        .hint_text(t!("log-filter"))
+                        .desired_width(160.0),
+                ),
+                t!("log-filter"),
+            );
+            if ui
+                .button(t!("log-copy"))
+                .on_hover_text(t!("log-copy-hover"))
+                .clicked()
+            {
+                let text: Vec<&str> = entries.iter().map(|entry| entry.line.as_str()).collect();
+                ui.ctx().copy_text(text.join("\n"));
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for entry in &entries {
+                    let color = match entry.level {
+                        Level::ERROR => ui.visuals().error_fg_color,
+                        Level::WARN => ui.visuals().warn_fg_color,
+                        _ => ui.visuals().text_color(),
+                    };
+                    ui.label(egui::RichText::new(&entry.line).monospace().color(color));
+                }
+            });
+        ui.label(log_hint());
+    }
+
+    fn process_images(&mut self) {
+        let output_dir = self
+            .planned_dir
+            .take()
+            .unwrap_or_else(|| self.output_dir.clone());
+        self.run_batch(self.batch_paths(), output_dir, BatchMode::Process);
+    }
+
+    /// Counts a finished file of the running batch, whatever its outcome.
+    fn file_done(&mut self, path: PathBuf) {
+        if self.processing {
+            self.completed_images += 1;
+        }
+        if let Some(batch) = &mut self.batch {
+            batch.completed.push(path);
+        }
+    }
+
+    /// Processes `image_paths` into `output_dir`, the output folder or a run
+    /// folder in it, as `mode` says.
+    fn run_batch(&mut self, image_paths: Vec<PathBuf>, output_dir: PathBuf, mode: BatchMode) {
+        // Its counters would be reset under the running batch.
+        if self.processing {
+            tracing::debug!("a batch is already running");
+            return;
+        }
+        // A combined output counts as one file.
+        self.max_images = match mode {
+            BatchMode::Combine(_) => 1,
+            _ => image_paths.len() as i32,
+        };
+        self.completed_images = 0;
+        self.batch_started = Instant::now();
+        self.written_bytes = 0;
+        self.skipped_images = 0;
+        self.failed_images = 0;
+        self.failures.clear();
+        self.results.clear();
+        self.selected_results.clear();
+        self.thumbnails.clear();
+        #[cfg(any(feature = "s3", feature = "sftp"))]
+        self.uploads.begin(&output_dir);
+        // Combines and splits are quick to redo, so they aren't resumed.
+        self.batch = matches!(mode, BatchMode::Process).then(|| BatchState {
+            files: image_paths.clone(),
+            completed: vec![],
+            output_dir: Some(output_dir.clone()),
+        });
+        self.batch_dir = output_dir.clone();
+
+        let status = match &mode {
+            BatchMode::Combine(_) => t!(
+                "status-combining",
+                count = i18n::number(image_paths.len()),
+                folder = output_dir.display()
+            ),
+            BatchMode::Split(settings) => t!(
+                "status-splitting",
+                count = i18n::number(settings.tile_count()),
+                folder = output_dir.display()
+            ),
+            BatchMode::Process => t!(
+                "status-processing",
+                count = i18n::number(image_paths.len()),
+                folder = output_dir.display()
+            ),
+        };
+        self.set_status(Level::INFO, status);
+        self.processing = true;
+        // The batch needs the cores; the estimate is asked for again after.
+        self.worker.send(Command::CancelEstimate);
+        self.estimates.pending = None;
+        self.estimates.current = None;
+
+        let rules = self.resolve_rules();
+        self.worker.send(Command::StartBatch {
+            paths: image_paths,
+            output_dir,
+            rules,
+            manual: self.process_info(),
+            edits: self.edits.clone(),
+            naming: self.batch_naming(),
+            manifest: self.manifest,
+            placeholder: self.manifest.and(self.placeholder),
+            archive: self.create_archive.then(|| self.archive.clone()),
+            hook: self.run_hook.then(|| self.hook.clone()),
+            // Only decisions the user hasn't undone by checking the file
+            // again, and none for the picked photos of other modes.
+            duplicates: self
+                .duplicates
+                .decisions
+                .iter()
+                .filter(|duplicate| {
+                    matches!(mode, BatchMode::Process) && self.excluded.contains(&duplicate.path)
+                })
+                .cloned()
+                .collect(),
+            combine: match &mode {
+                BatchMode::Combine(settings) => Some(settings.clone()),
+                _ => None,
+            },
+            split: match mode {
+                BatchMode::Split(settings) => Some(settings),
+                _ => None,
+            },
+        });
+    }
+
+    fn batch_naming(&self) -> BatchNaming {
+        BatchNaming {
+            template: self.name_template.clone(),
+            suffix: self.suffix.clone(),
+            manual_preset: self.selected_preset.clone(),
+            mirror_root: (self.folders.include_subfolders && self.folders.keep_structure)
+                .then(|| self.input_dir.clone()),
+            date_folders: self
+                .folders
+                .by_date
+                .then(|| self.folders.date_pattern.clone()),
+            overwrite: self.overwrite,
+            originals: self.originals,
+            preserve_timestamps: self.preserve_timestamps,
+            variant_folders: self.folders.variants,
+            run_folder: self
+                .folders
+                .per_run
+                .then(|| self.folders.run_pattern.clone()),
+            srcset: self.create_srcset.then(|| self.srcset.clone()),
+        }
+    }
+
+    /// Name of the run folder a batch started now would get, or why the
+    /// pattern can't be used. `None` without a run folder.
+    fn run_folder_example(&self) -> Option<String> {
+        let naming = self.batch_naming();
+        naming.run_folder.as_ref()?;
+        Some(match naming.run_dir(Path::new(""), dates::now()) {
+            Ok(folder) => folder.display().to_string(),
+            Err(e) => e.to_string(),
+        })
+    }
+
+    /// Output name of the previewed file with the manual settings, or why
+    /// the template can't name it.
+    fn name_example(&mut self) -> Option<&Result<String, String>> {
+        let path = self.previewed_path()?.clone();
+        let info = self.previewed_info();
+        let dimensions = self.original_size(&info.trim)?;
+        let original = self.original_image.as_ref()?;
+        let key = NameExampleKey {
+            naming: self.batch_naming(),
+            dimensions,
+            bits: bit_depth(original.color()),
+            path,
+            index: self.preview_index,
+            info,
+        };
+
+        if self
+            .name_example
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != key)
+        {
+            let example = key
+                .naming
+                .parse_template()
+                .map_err(|e| e.to_string())
+                .and_then(|template| {
+                    planned_output(
+                        &template,
+                        &key.naming,
+                        &key.path,
+                        &key.info,
+                        key.dimensions,
+                        key.bits,
+                        key.index,
+                        None,
+                    )
+                });
+            self.name_example = Some((key, example));
+        }
+
+        self.name_example.as_ref().map(|(_, example)| example)
+    }
+}
+
+/// Output path of `path`, relative to the output folder, for the user. The
+/// input is `dimensions` large with `bits` per channel before processing
+/// with `info`.
+#[allow(clippy::too_many_arguments)]
+fn planned_output(
+    template: &NameTemplate,
+    naming: &BatchNaming,
+    path: &Path,
+    info: &ProcessInfo,
+    (width, height): (u32, u32),
+    bits: u8,
+    index: usize,
+    preset: Option<&str>,
+) -> Result<String, String> {
+    let date_folders = naming.parse_date_folders().map_err(|e| e.to_string())?;
+    let folder = naming.output_folder(path, date_folders.as_ref());
+    let file_naming = FileNaming::for_batch(naming, template, index, preset, &folder);
+    let dimensions = output_dimensions(width, height, info);
+    templated_file_name(path, info.output_format, dimensions, &file_naming)
+        .map(|name| {
+            file_naming
+                .output_folder(info.output_format, bits, dimensions)
+                .join(name.name)
+                .display()
+                .to_string()
+        })
+        .map_err(|e| match e {
+            ProcessError::Template { source, .. } => source.to_string(),
+            e => e.to_string(),
+        })
+}
+
+/// Points users to the log files for bug reports.
+fn log_hint() -> String {
+    t!("log-hint", folder = storage::log_dir().display())
+}
+
+/// Starts the worker, forwarding its events to the UI. Preview results are
+/// prepared for display on the worker's thread.
+fn spawn_worker(tx: UnboundedSender<MessageResult>, ctx: Context) -> Worker {
+    Worker::spawn(move |event| {
+        let message = match event {
+            Event::FileDone {
+                path,
+                applied,
+                result,
+            } => {
+                report_file(&path, &applied, &result);
+                match result {
+                    Ok(outcome) => MessageResult::ImageDone(Box::new(outcome)),
+                    Err(error) => MessageResult::ImageFailed { path, error },
+                }
+            }
+            Event::Thumbnail { output, image, .. } => MessageResult::Thumbnail {
+                output,
+                image: to_color_image(&DynamicImage::ImageRgba8(image)),
+            },
+            Event::DiskFull { path } => MessageResult::DiskFull(path),
+            Event::ArchiveProgress { done, total } => {
+                MessageResult::ArchiveProgress { done, total }
+            }
+            Event::ArchiveDone { result } => {
+                if let Err(e) = &result {
+                    tracing::error!("could not write the archive: {}", e);
+                }
+                MessageResult::ArchiveDone(result.map_err(|e| e.to_string()))
+            }
+            Event::BatchFinished { cancelled } => MessageResult::BatchFinished { cancelled },
+            Event::Preview {
+                generation,
+                info,
+                result: Ok(composite),
+            } => MessageResult::PreviewResult {
+                generation,
+                info,
+                histogram: Box::new(Histogram::from_image(&composite)),
+                data: downscale_for_display(&composite),
+                composite: Arc::new(composite),
+            },
+            Event::Preview {
+                generation,
+                result: Err(error),
+                ..
+            } => MessageResult::PreviewFailed { generation, error },
+            Event::SizeEstimate { key, result } => MessageResult::SizeEstimate {
+                key,
+                result: result.map_err(|e| e.to_string()),
+            },
+            Event::DuplicateProgress { done, total } => {
+                MessageResult::DuplicateProgress { done, total }
+            }
+            Event::Duplicates { groups } => MessageResult::Duplicates(groups),
+        };
+        let _ = tx.send(message);
+        ctx.request_repaint();
+    })
+}
+
+/// Prints the outcome of one file of a batch. Returns whether it succeeded.
+fn report_file(path: &Path, applied: &str, result: &Result<ProcessOutcome, ProcessError>) -> bool {
+    match result {
+        Ok(ProcessOutcome {
+            skipped: Some(reason),
+            ..
+        }) => {
+            tracing::info!("{} skipped: {}", path.display(), reason);
+            println!("Skipped {}: {}", path.display(), reason);
+            true
+        }
+        Ok(outcome) => {
+            let outputs: Vec<String> = outcome
+                .output_paths
+                .iter()
+                .map(|output| format!("{:?}", output))
+                .collect();
+            let adjusted = outcome
+                .adjustments()
+                .map(|adjustments| format!(", {}", adjustments))
+                .unwrap_or_default();
+            tracing::info!(
+                "border added to {} using {}{}, saved to {} ({} bytes in {:?})",
+                path.display(),
+                applied,
+                adjusted,
+                outputs.join(", "),
+                outcome.bytes_written,
+                outcome.durations.total()
+            );
+            println!(
+                "Border added to {} using {}{}. Saved to {}",
+                path.display(),
+                applied,
+                adjusted,
+                outputs.join(", ")
+            );
+            true
+        }
+        Err(e) => {
+            tracing::error!("{}", e);
+            eprintln!("Error: {}", e);
+            false
+        }
+    }
+}
+
+/// Describes each side of a comparison by only the settings that differ.
+fn describe_differences(a: &ProcessInfo, b: &ProcessInfo) -> (String, String) {
+    let (a, b) = (a.summary(), b.summary());
+
+    let describe = |own: &[(&str, String)], other: &[(&str, String)]| {
+        let parts: Vec<_> = own
+            .iter()
+            .filter(|entry| !other.contains(entry))
+            .map(|(label, value)| format!("{}: {}", label, value))
+            .collect();
+
+        if parts.is_empty() {
+            t!("compare-same").to_string()
+        } else {
+            parts.join(", ")
+        }
     };
 
-    let mut bordered_img: DynamicImage =
-        ImageBuffer::from_pixel(new_width, new_height, Rgba([255, 255, 255, 255_u8])).into();
+    (describe(&a, &b), describe(&b, &a))
+}
+
+/// One line per setting that differs between `from` and `to`.
+fn describe_changes(from: &ProcessInfo, to: &ProcessInfo) -> Vec<String> {
+    let from = from.summary();
+
+    to.summary()
+        .into_iter()
+        .filter(|entry| !from.contains(entry))
+        .map(
+            |(label, value)| match from.iter().find(|(from_label, _)| *from_label == label) {
+                Some((_, old)) => format!("{}: {} → {}", label, old, value),
+                None => format!("{}: {}", label, value),
+            },
+        )
+        .collect()
+}
+
+/// Brings the window back from the tray or the taskbar.
+fn show_window(ctx: &Context) {
+    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+}
+
+fn to_color_image(img: &DynamicImage) -> egui::ColorImage {
+    let size = [img.width() as usize, img.height() as usize];
 
-    imageops::overlay(
-        &mut bordered_img,
-        original_img,
-        x_offset as i64,
-        y_offset as i64,
+    // The compositor always produces RGBA8, so this normally borrows the
+    // buffer instead of converting it.
+    match img.as_rgba8() {
+        Some(rgba) => egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw()),
+        None => egui::ColorImage::from_rgba_unmultiplied(size, img.to_rgba8().as_raw()),
+    }
+}
+
+/// Draws the preview on its backdrop, scaled to fit `max_size` with the
+/// backdrop, and returns the response of the image. `alt` is what screen
+/// readers say for it.
+fn show_on_backdrop(
+    ui: &mut egui::Ui,
+    texture: &TextureHandle,
+    alt: String,
+    settings: &UiSettings,
+    max_size: egui::Vec2,
+) -> egui::Response {
+    const MARGIN: f32 = 16.0;
+    let image_size = (max_size - egui::Vec2::splat(2.0 * MARGIN)).max(egui::Vec2::splat(64.0));
+    egui::Frame::default()
+        .fill(settings.preview_backdrop.color())
+        .inner_margin(MARGIN)
+        .show(ui, |ui| {
+            let response = ui.add(
+                egui::Image::new(texture)
+                    .alt_text(alt)
+                    .fit_to_exact_size(image_size)
+                    .sense(egui::Sense::click()),
+            );
+            settings.guides.paint(ui.painter(), response.rect);
+            response
+        })
+        .inner
+}
+
+/// Outlines the tiles of a `size` photo shown at `rect`, shading what is
+/// cropped away, and numbers them.
+fn paint_tiles(ui: &egui::Ui, rect: egui::Rect, size: [usize; 2], settings: &SplitSettings) {
+    let layout = split::layout(size[0] as u32, size[1] as u32, settings);
+    let scale = rect.width() / size[0].max(1) as f32;
+    let area = egui::Rect::from_min_size(
+        rect.min + egui::vec2(layout.x as f32, layout.y as f32) * scale,
+        egui::vec2(layout.width as f32, layout.height as f32) * scale,
     );
+    let painter = ui.painter();
+    let shade = Color32::from_black_alpha(140);
+    for cropped in [
+        egui::Rect::from_x_y_ranges(rect.x_range(), rect.top()..=area.top()),
+        egui::Rect::from_x_y_ranges(rect.x_range(), area.bottom()..=rect.bottom()),
+        egui::Rect::from_x_y_ranges(rect.left()..=area.left(), area.y_range()),
+        egui::Rect::from_x_y_ranges(area.right()..=rect.right(), area.y_range()),
+    ] {
+        if cropped.is_positive() {
+            painter.rect_filled(cropped.intersect(rect), 0.0, shade);
+        }
+    }
+
+    let stroke = egui::Stroke::new(2.0, Color32::from_rgb(255, 200, 0));
+    painter.rect_stroke(area, 0.0, stroke, egui::StrokeKind::Middle);
+    for (index, &(x, width)) in layout.columns.iter().enumerate() {
+        let left = area.left() + x as f32 * scale;
+        if index > 0 {
+            painter.vline(left, area.y_range(), stroke);
+        }
+        painter.text(
+            egui::pos2(left + width as f32 * scale / 2.0, area.center().y),
+            egui::Align2::CENTER_CENTER,
+            (index + 1).to_string(),
+            egui::FontId::proportional(24.0),
+            stroke.color,
+        );
+    }
+}
+
+/// Marks the live preview as stale and/or still being regenerated.
+fn paint_preview_state(ui: &egui::Ui, rect: egui::Rect, busy: bool, outdated: bool) {
+    if outdated {
+        ui.painter()
+            .rect_filled(rect, 0.0, Color32::from_black_alpha(60));
+    }
+    if busy {
+        let spinner_rect = egui::Rect::from_center_size(rect.center(), egui::vec2(32.0, 32.0));
+        egui::Spinner::new().paint_at(ui, spinner_rect);
+    }
+}
+
+/// Pixels on each side of the inspected pixel shown in the loupe.
+const LOUPE_RADIUS: u32 = 7;
+const LOUPE_DISPLAY_SIZE: f32 = 120.0;
+
+/// Crops the neighborhood of (`x`, `y`), repeating edge pixels near borders.
+fn loupe_image(img: &DynamicImage, x: u32, y: u32) -> egui::ColorImage {
+    let side = LOUPE_RADIUS * 2 + 1;
+    let loupe = ImageBuffer::from_fn(side, side, |dx, dy| {
+        let sx = (x + dx).saturating_sub(LOUPE_RADIUS).min(img.width() - 1);
+        let sy = (y + dy).saturating_sub(LOUPE_RADIUS).min(img.height() - 1);
+        img.get_pixel(sx, sy)
+    });
+
+    to_color_image(&DynamicImage::ImageRgba8(loupe))
+}
+
+/// A path typed or pasted into a folder field. Surrounding whitespace and
+/// quotes, as added by Windows' "Copy as path", are removed.
+fn typed_path(text: &str) -> PathBuf {
+    let text = text.trim();
+    let unquoted = ['"', '\'']
+        .iter()
+        .find_map(|quote| text.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(text);
+    PathBuf::from(unquoted.trim())
+}
+
+/// Inline warning under the output folder about `overlap`.
+fn overlap_warning(overlap: Overlap) -> &'static str {
+    match overlap {
+        Overlap::Same => t!("overlap-same"),
+        Overlap::Inside => t!("overlap-inside"),
+    }
+}
 
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Downscales a composited preview to fit the preview area.
+fn downscale_for_display(bordered_img: &DynamicImage) -> DynamicImage {
     // Downscale the bordered image to fit the maximum preview size
     let (width, height) = bordered_img.dimensions();
-    let max_width = 500;
-    let max_height = 500;
+    let max_width = PREVIEW_SIZE;
+    let max_height = PREVIEW_SIZE;
 
     let (new_width, new_height) = if width > max_width || height > max_height {
         let width_ratio = max_width as f64 / width as f64;
@@ -419,245 +5803,1915 @@ fn update_preview_image(original_img: &DynamicImage, border_info: BorderInfo) ->
 }
 
 impl App for BorderApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.ui_settings);
+        eframe::set_value(storage, PROCESS_INFO_KEY, &self.process_info());
+        eframe::set_value(storage, PRESET_RULES_KEY, &self.preset_rules);
+        eframe::set_value(storage, EDITS_KEY, &self.edits);
+        eframe::set_value(storage, PRINT_KEY, &self.print_settings);
+        #[cfg(any(feature = "s3", feature = "sftp"))]
+        self.uploads.save(storage);
+        eframe::set_value(storage, RECENT_FOLDERS_KEY, &self.recent);
+        storage.set_string(NAME_TEMPLATE_KEY, self.name_template.clone());
+        storage.set_string(SUFFIX_KEY, self.suffix.clone());
+        eframe::set_value(storage, FOLDERS_KEY, &self.folders);
+        eframe::set_value(storage, OVERWRITE_KEY, &self.overwrite);
+        eframe::set_value(storage, ORIGINALS_KEY, &self.originals);
+        eframe::set_value(storage, PRESERVE_TIMESTAMPS_KEY, &self.preserve_timestamps);
+        eframe::set_value(storage, MANIFEST_KEY, &self.manifest);
+        eframe::set_value(storage, PLACEHOLDER_KEY, &self.placeholder);
+        eframe::set_value(storage, CREATE_ARCHIVE_KEY, &self.create_archive);
+        eframe::set_value(storage, NOTIFY_KEY, &self.notify_when_done);
+        eframe::set_value(storage, ARCHIVE_KEY, &self.archive);
+        eframe::set_value(storage, CREATE_SRCSET_KEY, &self.create_srcset);
+        eframe::set_value(storage, SRCSET_KEY, &self.srcset);
+        eframe::set_value(storage, RUN_HOOK_KEY, &self.run_hook);
+        eframe::set_value(storage, HOOK_KEY, &self.hook);
+        eframe::set_value(storage, WEBHOOK_KEY, &self.webhook);
+        eframe::set_value(storage, EXPORT_WEBHOOK_KEY, &self.export_webhook);
+        eframe::set_value(storage, DUPLICATES_KEY, &self.duplicates.settings);
+        eframe::set_value(storage, COMBINE_KEY, &self.combine.settings);
+        eframe::set_value(storage, SPLIT_KEY, &self.split.settings);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // A session file is only left behind when the app didn't get here.
+        if self.stale_session.is_none() {
+            Session::remove();
+        }
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         while let Ok(msg) = self.rx.try_recv() {
             match msg {
-                MessageResult::PreviewResult { data } => {
+                MessageResult::PreviewResult {
+                    generation,
+                    info,
+                    composite,
+                    data,
+                    histogram,
+                } => {
+                    // Results of superseded requests must not end the busy state
+                    if generation != self.preview_generation {
+                        continue;
+                    }
+                    self.preview_pending = false;
+                    self.preview_info = Some(info);
+                    self.preview_composite = Some(composite);
                     self.preview_image = Some(data);
+                    self.preview_histogram = Some(histogram);
                     self.update_preview_texture();
                 }
-                MessageResult::InputUpdate(path) => {
-                    self.input_dir = path;
-                    self.load_images();
+                MessageResult::PreviewFailed { generation, error } => {
+                    if generation != self.preview_generation {
+                        continue;
+                    }
+                    self.preview_pending = false;
+                    self.set_status(Level::WARN, t!("status-preview-failed", error = error));
+                }
+                MessageResult::MetadataResult { path, metadata } => {
+                    if self.previewed_path() == Some(&path) {
+                        self.preview_metadata = Some(metadata);
+                    }
+                }
+                MessageResult::InputUpdate(path) => self.set_input_dir(path),
+                MessageResult::OutputUpdate(path) => self.set_output_dir(path),
+                MessageResult::TextureChosen(path) => {
+                    self.texture.path = path;
+                    self.border_fill = BorderFill::Texture(self.texture.clone());
+                    self.request_preview();
+                }
+                MessageResult::SettingsExported(result) => match result {
+                    Ok((path, false)) => {
+                        self.set_status(Level::INFO, t!("status-exported", path = path.display()))
+                    }
+                    Ok((path, true)) => self.set_status(
+                        Level::WARN,
+                        t!("status-exported-command", path = path.display()),
+                    ),
+                    Err(e) => self.set_status(Level::ERROR, t!("status-export-failed", error = e)),
+                },
+                MessageResult::SettingsImported(result) => match result {
+                    Ok(file) => {
+                        self.pending_import = Some(PendingImport {
+                            file,
+                            take_hook: false,
+                            take_webhook: false,
+                        })
+                    }
+                    Err(e) => self.set_status(Level::ERROR, t!("status-import-failed", error = e)),
+                },
+                MessageResult::SizeEstimate { key, result } => {
+                    if let Err(e) = &result {
+                        tracing::warn!("could not estimate the output size: {}", e);
+                    }
+                    if self.estimates.pending.as_ref() == Some(&key) {
+                        self.estimates.pending = None;
+                    }
+                    self.estimates.cache.insert(key, result);
+                }
+                MessageResult::RulePlan(plan) => {
+                    self.rule_plan = Some(plan);
+                }
+                MessageResult::Validated(validation) => {
+                    for error in &validation.errors {
+                        tracing::warn!("pre-flight: {}", error);
+                    }
+                    for warning in &validation.warnings {
+                        tracing::info!("pre-flight: {}", warning);
+                    }
+                    self.validating = false;
+                    self.status_message.clear();
+                    let summarized = self.batch_count() >= self.ui_settings.confirm_from;
+                    if validation.is_clean() && !summarized {
+                        self.process_images();
+                    } else {
+                        self.overlap_confirmed = false;
+                        self.preflight = Some(validation);
+                    }
+                }
+                MessageResult::PreviewSaved(result) => match result {
+                    Ok(path) => self.set_status(
+                        Level::INFO,
+                        t!("status-preview-saved", path = path.display()),
+                    ),
+                    Err(e) => {
+                        self.set_status(Level::ERROR, t!("status-preview-save-failed", error = e))
+                    }
+                },
+                MessageResult::CopyTooLarge { source, dimensions } => {
+                    self.large_copy = Some((source, dimensions));
+                }
+                MessageResult::CopyImage(result) => {
+                    match result.and_then(|img| {
+                        self.clipboard
+                            .set_image(&img)
+                            .map(|()| img.dimensions())
+                            .map_err(|e| e.to_string())
+                    }) {
+                        Ok((width, height)) => self.set_status(
+                            Level::INFO,
+                            t!("status-copied-image", width = width, height = height),
+                        ),
+                        Err(e) => {
+                            self.set_status(Level::ERROR, t!("status-copy-failed", error = e))
+                        }
+                    }
+                }
+                MessageResult::LaunchFailed(message) => self.set_status(Level::WARN, message),
+                MessageResult::PrintPreview { path, image } => {
+                    if let Some(dialog) = &mut self.print_dialog {
+                        if dialog.loading.as_ref() == Some(&path) {
+                            dialog.loading = None;
+                            dialog.source = Some((path, image));
+                            dialog.texture = None;
+                        }
+                    }
+                }
+                MessageResult::Printed(result) => {
+                    match result {
+                        Ok(pages) => {
+                            self.print_dialog = None;
+                            self.set_status(
+                                Level::INFO,
+                                t!("status-printed", count = i18n::number(pages)),
+                            );
+                        }
+                        // The dialog stays open to try again.
+                        Err(e) => {
+                            if let Some(dialog) = &mut self.print_dialog {
+                                dialog.printing = false;
+                            }
+                            self.set_status(Level::ERROR, t!("status-print-failed", error = e));
+                        }
+                    }
                 }
-                MessageResult::OutputUpdate(path) => {
-                    self.output_dir = path;
+                MessageResult::ClearPlanned(result) => {
+                    self.clear_dialog = Some(match result {
+                        Ok(plan) => ClearDialog::Confirm {
+                            plan,
+                            typed: String::new(),
+                        },
+                        Err(e) => ClearDialog::Refused(e),
+                    });
                 }
-                MessageResult::ImageComplete => {
-                    if self.processing {
-                        self.completed_images += 1;
+                MessageResult::ClearProgress { done, total } => {
+                    self.clear_dialog = Some(ClearDialog::Clearing { done, total });
+                }
+                MessageResult::ClearDone { deleted, errors } => {
+                    if errors.is_empty() {
+                        self.set_status(
+                            Level::INFO,
+                            t!("status-cleared", count = i18n::number(deleted)),
+                        );
+                    } else {
+                        self.set_status(
+                            Level::WARN,
+                            t!("status-clear-failed", count = i18n::number(errors.len())),
+                        );
                     }
-
-                    if self.completed_images >= self.max_images {
-                        self.processing = false;
-                        self.status_message = "Processing complete.".to_string();
+                    self.clear_dialog = Some(ClearDialog::Done { deleted, errors });
+                    self.results.clear();
+                    self.selected_results.clear();
+                    self.thumbnails.clear();
+                }
+                MessageResult::ImageDone(outcome) => {
+                    match &outcome.skipped {
+                        Some(reason) => {
+                            tracing::debug!("{} skipped: {}", outcome.input.display(), reason)
+                        }
+                        None => tracing::debug!(
+                            "{} done in {:?}",
+                            outcome.input.display(),
+                            outcome.durations.total()
+                        ),
+                    }
+                    self.written_bytes += outcome.bytes_written;
+                    #[cfg(any(feature = "s3", feature = "sftp"))]
+                    self.uploads
+                        .queue(&outcome.output_paths, &self.rt, &self.tx, ctx);
+                    self.results
+                        .extend(outcome.output_paths.iter().map(|output| {
+                            (outcome.input.clone(), output.clone(), outcome.adjustments())
+                        }));
+                    if outcome.skipped.is_some() {
+                        self.skipped_images += 1;
+                    }
+                    self.file_done(outcome.input);
+                }
+                MessageResult::Thumbnail { output, image } => {
+                    self.thumbnails.push_output(ctx, output, image);
+                }
+                MessageResult::ImageFailed { path, error } => {
+                    tracing::warn!("{}", error);
+                    self.failed_images += 1;
+                    self.failures.push(error.to_string());
+                    self.thumbnails.push_failed(path.clone(), error.to_string());
+                    self.file_done(path);
+                }
+                MessageResult::ArchiveProgress { done, total } => {
+                    self.archive_progress = Some((done, total));
+                }
+                MessageResult::ArchiveDone(result) => {
+                    self.archive_note = Some(match result {
+                        Ok(Some(path)) => {
+                            #[cfg(any(feature = "s3", feature = "sftp"))]
+                            self.uploads.queue(
+                                std::slice::from_ref(&path),
+                                &self.rt,
+                                &self.tx,
+                                ctx,
+                            );
+                            t!("archive-zipped", name = display_name(&path))
+                        }
+                        Ok(None) => t!("archive-exists").to_string(),
+                        Err(e) => {
+                            self.failures.push(t!("archive-failure", error = e));
+                            t!("archive-failed").to_string()
+                        }
+                    });
+                }
+                MessageResult::DuplicateProgress { done, total } => {
+                    // Late progress of a cancelled search is dropped.
+                    if self.duplicates.progress.is_some() {
+                        self.duplicates.progress = Some((done, total));
+                    }
+                }
+                MessageResult::Duplicates(groups) => {
+                    if self.duplicates.progress.take().is_some() {
+                        tracing::info!("{} groups of duplicates found", groups.len());
+                        let keep = self.duplicates.settings.keep;
+                        self.duplicates.groups = Some(
+                            groups
+                                .into_iter()
+                                .map(|group| {
+                                    let kept = group.keep(keep);
+                                    (group, kept)
+                                })
+                                .collect(),
+                        );
+                    }
+                }
+                MessageResult::Tray(action) => self.tray_action(ctx, action),
+                #[cfg(any(feature = "s3", feature = "sftp"))]
+                MessageResult::Upload(message) => {
+                    if let Some((level, status)) = self.uploads.handle(message) {
+                        self.set_status(level, status);
+                    }
+                }
+                MessageResult::DiskFull(path) => {
+                    // The worker paused the batch itself.
+                    self.paused = true;
+                    if self.in_tray {
+                        self.tray_action(ctx, TrayAction::Show);
+                    }
+                    self.set_status(Level::ERROR, t!("status-disk-full", path = path.display()));
+                    self.disk_full = Some(path);
+                }
+                MessageResult::BatchFinished { cancelled } => {
+                    self.processing = false;
+                    self.paused = false;
+                    self.disk_full = None;
+                    self.batch = None;
+                    self.archive_progress = None;
+                    let mut message = if cancelled {
+                        t!(
+                            "batch-cancelled",
+                            done = i18n::number(self.completed_images),
+                            total = i18n::number(self.max_images),
+                            counts = self.batch_counts()
+                        )
+                    } else if self.failed_images > 0 {
+                        t!("batch-failures", counts = self.batch_counts())
+                    } else if self.failures.is_empty() && self.skipped_images > 0 {
+                        t!(
+                            "batch-complete-skipped",
+                            size = i18n::bytes(self.written_bytes),
+                            skipped = i18n::number(self.skipped_images)
+                        )
+                    } else {
+                        t!("batch-complete", size = i18n::bytes(self.written_bytes))
+                    };
+                    if let Some(note) = self.archive_note.take() {
+                        message = format!("{} {}", message, note);
+                    }
+                    message = format!(
+                        "{} {}",
+                        message,
+                        t!("batch-ended", time = i18n::date_time(dates::now()))
+                    );
+                    let level = if self.failures.is_empty() {
+                        Level::INFO
+                    } else {
+                        Level::WARN
+                    };
+                    self.set_status(level, message);
+                    #[cfg(feature = "webhook")]
+                    self.send_webhook(cancelled);
+                    // Hidden in the tray, the notification is all there is.
+                    if self.notify_when_done || self.in_tray {
+                        self.notify_finished(cancelled);
                     }
                 }
             }
         }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Image Finalizer");
+        if self.auto_start_pending {
+            self.auto_start_pending = false;
+            self.start();
+        }
+
+        self.check_blockers();
+        self.check_combined_preview();
+        self.check_split_texture();
+        self.check_estimate(ctx);
+        self.handle_dropped_files(ctx);
+        self.handle_window_events(ctx);
 
+        // Text fields keep their own undo, arrows and Enter.
+        if !ctx.wants_keyboard_input() {
+            self.handle_shortcuts(ctx);
+        }
+
+        egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                ui.label("Input Directory:");
-                ui.text_edit_singleline(&mut self.input_dir.to_string_lossy());
-                if ui.button("Open Input Directory").clicked() {
-                    let ctx = self.context.clone();
-                    let tx = self.tx.clone();
-                    self.rt.spawn(async move {
-                        let path = FileDialog::new().pick_folder();
-                        if let Some(path) = path {
-                            let _ = tx.send(MessageResult::InputUpdate(path));
-                        }
-                        ctx.request_repaint();
-                    });
+                ui.heading("Image Finalizer");
+                self.history_controls(ui);
+                a11y::named(
+                    ui.toggle_value(&mut self.show_shortcuts, "?"),
+                    t!("shortcuts-title"),
+                )
+                .on_hover_text(t!("shortcuts-title"));
+                let theme = self.ui_settings.theme;
+                let switch = t!(
+                    "theme-switch",
+                    current = theme.label(),
+                    next = theme.next().label()
+                );
+                if a11y::icon_button(ui, theme.icon(), &switch).clicked() {
+                    self.ui_settings.theme = theme.next();
+                    self.ui_settings.theme.apply(ui.ctx());
                 }
-                ui.label(format!(
-                    "Found {} images",
-                    fs::read_dir(&self.input_dir)
-                        .map(|e| e
-                            .filter_map(|entry| entry.ok())
-                            .map(|entry| entry.path())
-                            .filter(|path| {
-                                path.extension().is_some_and(|ext| {
-                                    let ext_str = ext.to_str().unwrap_or("").to_lowercase();
-                                    ext_str == "png"
-                                        || ext_str == "jpg"
-                                        || ext_str == "jpeg"
-                                        || ext_str == "gif"
-                                        || ext_str == "bmp"
-                                        || ext_str == "tif"
-                                })
-                            })
-                            .collect::<Vec<_>>()
-                            .len())
-                        .unwrap_or(0)
-                ));
+                self.scale_controls(ui);
+                self.language_controls(ui);
             });
+        });
 
-            ui.horizontal(|ui| {
-                ui.label("Output Directory:");
-                ui.text_edit_singleline(&mut self.output_dir.to_string_lossy());
-                if ui.button("Open Output Directory").clicked() {
-                    let ctx = self.context.clone();
-                    let tx = self.tx.clone();
-                    self.rt.spawn(async move {
-                        let path = FileDialog::new().pick_folder();
-                        if let Some(path) = path {
-                            let _ = tx.send(MessageResult::OutputUpdate(path));
+        // egui keeps the width of the panel with the rest of its memory.
+        egui::SidePanel::left("settings")
+            .resizable(true)
+            .default_width(440.0)
+            .width_range(280.0..=900.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.mode_controls(ui);
+                    let advanced = self.ui_settings.advanced;
+                    if self.processing {
+                        ui.label(
+                            egui::RichText::new(t!("settings-locked"))
+                                .small()
+                                .color(ui.visuals().warn_fg_color),
+                        );
+                    }
+                    // The running batch keeps the settings it started with.
+                    ui.add_enabled_ui(!self.processing, |ui| {
+                        if advanced {
+                            egui::CollapsingHeader::new(t!("section-presets"))
+                                .id_salt("section_presets")
+                                .show(ui, |ui| {
+                                    self.preset_controls(ui);
+
+                                    egui::CollapsingHeader::new(t!("section-preset-rules"))
+                                        .id_salt("section_preset_rules")
+                                        .show(ui, |ui| {
+                                            rules_ui::show(
+                                                &mut self.preset_rules,
+                                                ui,
+                                                &self.presets,
+                                            );
+
+                                            if self.preset_rules.enabled {
+                                                if ui.button(t!("rules-show")).clicked() {
+                                                    self.plan_rules();
+                                                }
+                                                if let Some(plan) = &self.rule_plan {
+                                                    if let Some(folder) = self.run_folder_example()
+                                                    {
+                                                        ui.label(t!(
+                                                            "rules-run-folder",
+                                                            folder = folder
+                                                        ));
+                                                    }
+                                                    egui::ScrollArea::vertical()
+                                                        .max_height(150.0)
+                                                        .show(ui, |ui| {
+                                                            for file in plan {
+                                                                let output = match &file.output {
+                                                                    Ok(output) => output,
+                                                                    Err(e) => e,
+                                                                };
+                                                                ui.label(format!(
+                                                                    "{} → {}: {}",
+                                                                    display_name(&file.path),
+                                                                    output,
+                                                                    file.applied
+                                                                ));
+                                                            }
+                                                        });
+                                                }
+                                            }
+                                        });
+
+                                    ui.horizontal(|ui| {
+                                        if ui.button(t!("settings-export")).clicked() {
+                                            self.export_settings();
+                                        }
+                                        if ui.button(t!("settings-import")).clicked() {
+                                            self.import_settings();
+                                        }
+                                    });
+                                });
+                        }
+
+                        egui::CollapsingHeader::new(t!("section-io"))
+                            .id_salt("section_io")
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    let label = ui.label(t!("input-dir"));
+                                    if ui
+                                        .text_edit_singleline(&mut self.input_text)
+                                        .labelled_by(label.id)
+                                        .lost_focus()
+                                    {
+                                        self.commit_input_text();
+                                    }
+                                    if ui
+                                        .button(t!("input-open"))
+                                        .on_hover_text("Ctrl+O")
+                                        .clicked()
+                                    {
+                                        self.pick_input_dir();
+                                    }
+                                    ui.label(t!(
+                                        "input-found",
+                                        count = i18n::number(self.image_paths.len())
+                                    ));
+                                    if ui
+                                        .add_enabled(
+                                            !self.input_dir.as_os_str().is_empty(),
+                                            egui::Button::new(t!("input-rescan")),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.load_images();
+                                    }
+                                });
+                                if let Some(hint) = &self.input_hint {
+                                    ui.colored_label(ui.visuals().error_fg_color, hint);
+                                }
+                                if !self.image_paths.is_empty() {
+                                    self.show_order(ui);
+                                }
+                                if self.image_paths.len() >= combine::MIN_IMAGES {
+                                    self.show_combine(ui);
+                                }
+                                if !self.image_paths.is_empty() {
+                                    self.show_split(ui);
+                                }
+
+                                ui.horizontal(|ui| {
+                                    let label = ui.label(t!("output-dir"));
+                                    if ui
+                                        .text_edit_singleline(&mut self.output_text)
+                                        .labelled_by(label.id)
+                                        .lost_focus()
+                                    {
+                                        self.commit_output_text();
+                                    }
+                                    if ui
+                                        .add_enabled(
+                                            !self.processing
+                                                && !self.output_dir.as_os_str().is_empty(),
+                                            egui::Button::new(t!("output-clear")),
+                                        )
+                                        .on_hover_text(t!("output-clear-hover"))
+                                        .clicked()
+                                    {
+                                        self.plan_clear();
+                                    }
+                                    if ui
+                                        .button(t!("output-open"))
+                                        .on_hover_text("Ctrl+Shift+O")
+                                        .clicked()
+                                    {
+                                        self.pick_output_dir();
+                                    }
+                                });
+                                if let Some(hint) = &self.output_hint {
+                                    ui.colored_label(ui.visuals().error_fg_color, hint);
+                                }
+                                if let Some(overlap) = self.overlap {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        overlap_warning(overlap),
+                                    );
+                                }
+                            });
+
+                        if advanced {
+                            egui::CollapsingHeader::new(t!("section-files"))
+                                .id_salt("section_files")
+                                .show(ui, |ui| {
+                                    ui.horizontal(|ui| {
+                                        let label = ui.label(t!("names-label"));
+                                        ui.text_edit_singleline(&mut self.name_template)
+                                            .labelled_by(label.id)
+                                            .on_hover_text(t!("template-help"));
+                                        if ui
+                                            .add_enabled(
+                                                self.name_template != DEFAULT_TEMPLATE,
+                                                egui::Button::new(t!("button-reset")),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.name_template = DEFAULT_TEMPLATE.to_string();
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        let label = ui.label(t!("suffix-label"));
+                                        let suffix = ui
+                                            .text_edit_singleline(&mut self.suffix)
+                                            .labelled_by(label.id)
+                                            .on_hover_text(t!("suffix-hover"));
+                                        if suffix.lost_focus() {
+                                            self.load_images();
+                                        }
+                                        if ui
+                                            .add_enabled(
+                                                self.suffix != DEFAULT_SUFFIX,
+                                                egui::Button::new(t!("button-reset")),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.suffix = DEFAULT_SUFFIX.to_string();
+                                            self.load_images();
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .checkbox(
+                                                &mut self.folders.include_subfolders,
+                                                t!("folders-subfolders"),
+                                            )
+                                            .changed()
+                                        {
+                                            self.load_images();
+                                        }
+                                        ui.add_enabled(
+                                            self.folders.include_subfolders,
+                                            egui::Checkbox::new(
+                                                &mut self.folders.keep_structure,
+                                                t!("folders-keep"),
+                                            ),
+                                        )
+                                        .on_hover_text(t!("folders-keep-hover"));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        let checkbox = ui.checkbox(
+                                            &mut self.folders.by_date,
+                                            t!("folders-by-date"),
+                                        );
+                                        ui.add_enabled(
+                                            self.folders.by_date,
+                                            egui::TextEdit::singleline(
+                                                &mut self.folders.date_pattern,
+                                            ),
+                                        )
+                                        .labelled_by(checkbox.id)
+                                        .on_hover_text(t!("folders-date-hover"));
+                                        if ui
+                                            .add_enabled(
+                                                self.folders.by_date
+                                                    && self.folders.date_pattern
+                                                        != DEFAULT_DATE_FOLDERS,
+                                                egui::Button::new(t!("button-reset")),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.folders.date_pattern =
+                                                DEFAULT_DATE_FOLDERS.to_string();
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        let checkbox = ui
+                                            .checkbox(&mut self.folders.per_run, t!("folders-run"));
+                                        ui.add_enabled(
+                                            self.folders.per_run,
+                                            egui::TextEdit::singleline(
+                                                &mut self.folders.run_pattern,
+                                            ),
+                                        )
+                                        .labelled_by(checkbox.id)
+                                        .on_hover_text(t!("folders-run-hover"));
+                                        if ui
+                                            .add_enabled(
+                                                self.folders.per_run
+                                                    && self.folders.run_pattern
+                                                        != DEFAULT_RUN_FOLDER,
+                                                egui::Button::new(t!("button-reset")),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.folders.run_pattern =
+                                                DEFAULT_RUN_FOLDER.to_string();
+                                        }
+                                        if let Some(folder) = self.run_folder_example() {
+                                            ui.label(format!("→ {}", folder));
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(
+                                            &mut self.folders.variants.by_format,
+                                            t!("folders-format"),
+                                        )
+                                        .on_hover_text(t!("folders-format-hover"));
+                                        ui.checkbox(
+                                            &mut self.folders.variants.by_size,
+                                            t!("folders-size"),
+                                        )
+                                        .on_hover_text(t!("folders-size-hover"));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label(t!("existing-label"));
+                                        ui.radio_value(
+                                            &mut self.overwrite,
+                                            OverwritePolicy::Overwrite,
+                                            t!("existing-replace"),
+                                        );
+                                        ui.radio_value(
+                                            &mut self.overwrite,
+                                            OverwritePolicy::Rename,
+                                            t!("existing-number"),
+                                        )
+                                        .on_hover_text(t!("existing-number-hover"));
+                                        ui.radio_value(
+                                            &mut self.overwrite,
+                                            OverwritePolicy::Skip,
+                                            t!("existing-skip"),
+                                        );
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label(t!("originals-label"));
+                                        ui.radio_value(
+                                            &mut self.originals,
+                                            None,
+                                            t!("originals-no"),
+                                        );
+                                        ui.radio_value(
+                                            &mut self.originals,
+                                            Some(OriginalsPlacement::Beside),
+                                            t!("originals-beside"),
+                                        );
+                                        ui.radio_value(
+                                            &mut self.originals,
+                                            Some(OriginalsPlacement::Subfolder),
+                                            t!("originals-subfolder", folder = ORIGINALS_FOLDER),
+                                        );
+                                    })
+                                    .response
+                                    .on_hover_text(t!("originals-hover"));
+                                    ui.horizontal(|ui| {
+                                        ui.label(t!("manifest-label"));
+                                        ui.radio_value(
+                                            &mut self.manifest,
+                                            None,
+                                            t!("manifest-none"),
+                                        );
+                                        ui.radio_value(
+                                            &mut self.manifest,
+                                            Some(ManifestFormat::Csv),
+                                            "CSV",
+                                        );
+                                        ui.radio_value(
+                                            &mut self.manifest,
+                                            Some(ManifestFormat::Json),
+                                            "JSON",
+                                        )
+                                        .on_hover_text(t!("manifest-json-hover"));
+                                    })
+                                    .response
+                                    .on_hover_text(t!("manifest-hover"));
+                                    ui.add_enabled_ui(self.manifest.is_some(), |ui| {
+                                        ui.horizontal(|ui| {
+                                            ui.label(t!("placeholder-label"));
+                                            ui.radio_value(
+                                                &mut self.placeholder,
+                                                None,
+                                                t!("placeholder-none"),
+                                            );
+                                            ui.radio_value(
+                                                &mut self.placeholder,
+                                                Some(PlaceholderKind::Blurhash),
+                                                "BlurHash",
+                                            );
+                                            ui.radio_value(
+                                                &mut self.placeholder,
+                                                Some(PlaceholderKind::Thumbhash),
+                                                "ThumbHash",
+                                            )
+                                            .on_hover_text(t!("placeholder-thumbhash-hover"));
+                                        })
+                                        .response
+                                        .on_hover_text(t!("placeholder-hover"));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        let checkbox = ui.checkbox(
+                                            &mut self.create_archive,
+                                            t!("archive-create"),
+                                        );
+                                        ui.add_enabled_ui(self.create_archive, |ui| {
+                                            ui.add(
+                                                egui::TextEdit::singleline(&mut self.archive.name)
+                                                    .desired_width(120.0),
+                                            )
+                                            .labelled_by(checkbox.id)
+                                            .on_hover_text(t!("archive-name-hover"));
+                                            ui.label(".zip");
+                                            ui.radio_value(
+                                                &mut self.archive.compression,
+                                                ZipCompression::Store,
+                                                t!("archive-store"),
+                                            )
+                                            .on_hover_text(t!("archive-store-hover"));
+                                            ui.radio_value(
+                                                &mut self.archive.compression,
+                                                ZipCompression::Deflate,
+                                                "Deflate",
+                                            );
+                                        });
+                                    });
+                                    match self.name_example() {
+                                        Some(Ok(example)) => {
+                                            ui.label(t!("name-example", name = example));
+                                        }
+                                        Some(Err(e)) => {
+                                            ui.colored_label(ui.visuals().error_fg_color, e);
+                                        }
+                                        None => {}
+                                    }
+                                });
+                        }
+
+                        egui::CollapsingHeader::new(t!("section-transform"))
+                            .id_salt("section_transform")
+                            .show(ui, |ui| self.transform_settings(ui));
+
+                        egui::CollapsingHeader::new(t!("section-border"))
+                            .id_salt("section_border")
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                self.border_width_settings(ui);
+                                self.border_color_settings(ui);
+                                self.border_fill_settings(ui);
+                                self.canvas_aspect_settings(ui);
+                                self.frame_settings(ui);
+                                self.corner_settings(ui);
+                                self.keyline_settings(ui);
+                                self.shadow_settings(ui);
+
+                                if advanced {
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .add_enabled(
+                                                self.border_sides.is_none()
+                                                    && self.canvas_aspect.is_none(),
+                                                egui::Checkbox::new(
+                                                    &mut self.symmetrical_border,
+                                                    t!("border-symmetrical"),
+                                                ),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.request_preview();
+                                        }
+                                        self.help.button(ui, help::Topic::BorderMode);
+                                    });
+                                    self.border_sides_settings(ui);
+                                }
+
+                                self.trim_settings(ui, advanced);
+                            });
+
+                        egui::CollapsingHeader::new(t!("section-resize"))
+                            .id_salt("section_resize")
+                            .show(ui, |ui| {
+                                if ui
+                                    .checkbox(&mut self.resize_images, t!("resize-images"))
+                                    .changed()
+                                {
+                                    self.request_preview();
+                                }
+
+                                if self.resize_images {
+                                    let mut changed = false;
+
+                                    ui.horizontal(|ui| {
+                                        let label = ui.label(t!("resize-longest"));
+                                        changed |= ui
+                                            .add(
+                                                egui::DragValue::new(
+                                                    &mut self.resize_longest_dimension,
+                                                )
+                                                .speed(1.0)
+                                                .range(1..=MAX_DIMENSION),
+                                            )
+                                            .labelled_by(label.id)
+                                            .changed();
+                                    });
+
+                                    if advanced {
+                                        ui.horizontal(|ui| {
+                                            ui.label(t!("resize-algorithm"));
+                                            self.help.button(ui, help::Topic::ResizeFilter);
+                                        });
+                                        ui.vertical(|ui| {
+                                            ui.horizontal(|ui| {
+                                                changed |= ui
+                                                    .radio_value(
+                                                        &mut self.resize_filter,
+                                                        FilterType::Nearest,
+                                                        "Nearest",
+                                                    )
+                                                    .changed();
+                                                ui.label(t!("filter-nearest-hint"));
+                                            });
+                                            ui.horizontal(|ui| {
+                                                changed |= ui
+                                                    .radio_value(
+                                                        &mut self.resize_filter,
+                                                        FilterType::Triangle,
+                                                        "Triangle",
+                                                    )
+                                                    .changed();
+                                                ui.label(t!("filter-triangle-hint"));
+                                            });
+                                            ui.horizontal(|ui| {
+                                                changed |= ui
+                                                    .radio_value(
+                                                        &mut self.resize_filter,
+                                                        FilterType::CatmullRom,
+                                                        "CatmullRom",
+                                                    )
+                                                    .changed();
+                                                ui.label(t!("filter-catmullrom-hint"));
+                                            });
+                                            ui.horizontal(|ui| {
+                                                changed |= ui
+                                                    .radio_value(
+                                                        &mut self.resize_filter,
+                                                        FilterType::Lanczos3,
+                                                        "Lanczos3",
+                                                    )
+                                                    .changed();
+                                                ui.label(t!("filter-lanczos3-hint"));
+                                            });
+                                        });
+                                    }
+
+                                    if changed {
+                                        self.request_preview();
+                                    }
+                                }
+                            });
+
+                        egui::CollapsingHeader::new(t!("section-format"))
+                            .id_salt("section_format")
+                            .show(ui, |ui| {
+                                ui.label(t!("format-output"));
+                                ui.horizontal(|ui| {
+                                    ui.radio_value(
+                                        &mut self.output_format,
+                                        OutputFormat::Png,
+                                        "PNG",
+                                    );
+                                    ui.radio_value(
+                                        &mut self.output_format,
+                                        OutputFormat::Jpeg,
+                                        "JPEG",
+                                    );
+                                    ui.radio_value(
+                                        &mut self.output_format,
+                                        OutputFormat::Tiff,
+                                        "TIFF",
+                                    );
+                                    ui.radio_value(
+                                        &mut self.output_format,
+                                        OutputFormat::Avif,
+                                        "AVIF",
+                                    );
+                                    ui.radio_value(
+                                        &mut self.output_format,
+                                        OutputFormat::Webp,
+                                        "WEBP",
+                                    );
+                                });
+
+                                let settings = &mut self.format_settings;
+                                match self.output_format {
+                                    OutputFormat::Png if !advanced => {
+                                        ui.label(t!("format-lossless"));
+                                    }
+                                    OutputFormat::Png => {
+                                        let compression = &mut settings.png.compression;
+                                        ui.horizontal(|ui| {
+                                            ui.label(t!("png-compression"));
+                                            ui.radio_value(
+                                                compression,
+                                                PngCompression::Fast,
+                                                t!("png-fast"),
+                                            );
+                                            ui.radio_value(
+                                                compression,
+                                                PngCompression::Default,
+                                                t!("png-default"),
+                                            );
+                                            ui.radio_value(
+                                                compression,
+                                                PngCompression::Best,
+                                                t!("png-best"),
+                                            );
+                                            self.help.button(ui, help::Topic::PngCompression);
+                                        });
+                                    }
+                                    OutputFormat::Jpeg => {
+                                        ui.horizontal(|ui| {
+                                            let label = ui.label(t!("jpeg-quality"));
+                                            ui.add(egui::Slider::new(
+                                                &mut settings.jpeg.quality,
+                                                1..=100,
+                                            ))
+                                            .labelled_by(label.id);
+                                            self.help.button(ui, help::Topic::JpegQuality);
+                                        });
+                                    }
+                                    OutputFormat::Avif => {
+                                        // Stacked, as one row is too wide for the panel.
+                                        if advanced {
+                                            ui.horizontal(|ui| {
+                                                let label = ui.label(t!("avif-speed"));
+                                                ui.add(egui::Slider::new(
+                                                    &mut settings.avif.speed,
+                                                    1..=10,
+                                                ))
+                                                .labelled_by(label.id)
+                                                .on_hover_text(t!("avif-speed-hover"));
+                                            });
+                                        }
+                                        ui.horizontal(|ui| {
+                                            let label = ui.label(t!("avif-quality"));
+                                            ui.add(egui::Slider::new(
+                                                &mut settings.avif.quality,
+                                                1..=100,
+                                            ))
+                                            .labelled_by(label.id);
+                                            self.help.button(ui, help::Topic::AvifQuality);
+                                        });
+                                    }
+                                    OutputFormat::Tiff | OutputFormat::Webp => {
+                                        ui.label(t!("format-lossless"));
+                                    }
+                                }
+                            });
+
+                        if advanced {
+                            egui::CollapsingHeader::new(t!("section-metadata"))
+                                .id_salt("section_metadata")
+                                .show(ui, |ui| {
+                                    ui.checkbox(
+                                        &mut self.preserve_timestamps,
+                                        t!("metadata-timestamps"),
+                                    )
+                                    .on_hover_text(t!("metadata-timestamps-hover"));
+                                });
+                        }
+                        #[cfg(any(feature = "s3", feature = "sftp"))]
+                        if advanced {
+                            egui::CollapsingHeader::new(t!("section-upload"))
+                                .id_salt("section_upload")
+                                .show(ui, |ui| {
+                                    self.uploads.show_settings(ui, &self.rt, &self.tx, ctx);
+                                });
+                        }
+                        if advanced {
+                            egui::CollapsingHeader::new(t!("section-srcset"))
+                                .id_salt("section_srcset")
+                                .show(ui, |ui| self.srcset_settings(ui));
+                        }
+                        if advanced {
+                            egui::CollapsingHeader::new(t!("section-hook"))
+                                .id_salt("section_hook")
+                                .show(ui, |ui| self.hook_settings(ui));
+                        }
+                        #[cfg(feature = "webhook")]
+                        if advanced {
+                            egui::CollapsingHeader::new(t!("section-webhook"))
+                                .id_salt("section_webhook")
+                                .show(ui, |ui| self.webhook_settings(ui));
                         }
-                        ctx.request_repaint();
                     });
-                }
+
+                    egui::CollapsingHeader::new(t!("section-about"))
+                        .id_salt("section_about")
+                        .show(ui, |ui| {
+                            ui.label(format!("Image Finalizer {}", env!("CARGO_PKG_VERSION")));
+                            ui.label(t!(
+                                "about-storage",
+                                folder = storage::config_dir().display()
+                            ));
+                            if storage::is_portable() {
+                                ui.label(t!("about-portable"));
+                            } else {
+                                ui.label(t!("about-portable-hint"));
+                            }
+                        });
+                });
             });
 
-            if ui
-                .checkbox(&mut self.symmetrical_border, "Symmetrical Border")
-                .clicked()
-            {
-                if let Some(handle) = self.current_preview.take() {
-                    handle.abort();
-                }
-                if let Some(img) = &self.original_image {
-                    let img_clone = img.clone();
-                    let sym = self.symmetrical_border;
-                    let border_perc = self.border_percentage;
-                    let tx = self.tx.clone();
-                    let ctx = self.context.clone();
-                    let task = self.rt.spawn(async move {
-                        let res = update_preview_image(
-                            &img_clone,
-                            BorderInfo {
-                                symmetrical_border: sym,
-                                border_percentage: border_perc,
-                            },
-                        );
-                        let _ = tx.send(MessageResult::PreviewResult { data: res });
-                        ctx.request_repaint();
+        egui::TopBottomPanel::bottom("batch")
+            .resizable(true)
+            .default_height(160.0)
+            .height_range(60.0..=600.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if !self.processing {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    self.can_start(),
+                                    egui::Button::new(t!("start-button")),
+                                )
+                                .on_hover_text("Ctrl+Enter")
+                                .clicked()
+                            {
+                                self.start();
+                            }
+                            ui.vertical(|ui| {
+                                if self.start_blockers.is_empty() {
+                                    self.show_estimate(ui);
+                                }
+                                for blocker in &self.start_blockers {
+                                    ui.label(
+                                        egui::RichText::new(blocker)
+                                            .small()
+                                            .color(ui.visuals().warn_fg_color),
+                                    );
+                                }
+                            });
+                        });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.notify_when_done, t!("notify-done"))
+                                .on_hover_text(t!("notify-done-hover"));
+                            if self.tray.is_available() {
+                                ui.checkbox(
+                                    &mut self.ui_settings.minimize_to_tray,
+                                    t!("minimize-to-tray"),
+                                )
+                                .on_hover_text(t!("minimize-to-tray-hover"));
+                            }
+                            if self.ui_settings.advanced {
+                                let label = ui.label(t!("preflight-summary-threshold"));
+                                ui.add(
+                                    egui::DragValue::new(&mut self.ui_settings.confirm_from)
+                                        .range(1..=1_000_000),
+                                )
+                                .labelled_by(label.id);
+                            }
+                        });
+                    } else {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button(t!("button-cancel"))
+                                .on_hover_text("Esc")
+                                .clicked()
+                            {
+                                self.cancel_batch();
+                            }
+                            let (label, paused) = if self.paused {
+                                (t!("button-resume"), false)
+                            } else {
+                                (t!("button-pause"), true)
+                            };
+                            if ui
+                                .add_enabled(
+                                    self.archive_progress.is_none(),
+                                    egui::Button::new(label),
+                                )
+                                .on_hover_text(t!("button-pause-hover"))
+                                .clicked()
+                            {
+                                self.set_paused(paused);
+                            }
+                            if let Some((done, total)) = self.archive_progress {
+                                ui.add(ProgressBar::new(done as f32 / total.max(1) as f32).text(
+                                    t!(
+                                        "archive-progress",
+                                        done = i18n::number(done),
+                                        total = i18n::number(total)
+                                    ),
+                                ));
+                            } else {
+                                self.show_batch_progress(ui);
+                            }
+                        });
+                        if self.archive_progress.is_none() {
+                            ui.label(self.batch_counts());
+                        }
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label(&self.status_message);
+                        if !self.processing
+                            && !self.results.is_empty()
+                            && ui.button(t!("results-open-folder")).clicked()
+                        {
+                            self.launch(&self.batch_dir, false);
+                        }
+                        if !self.processing
+                            && !self.failures.is_empty()
+                            && ui.button(t!("show-failures")).clicked()
+                        {
+                            self.reveal_failures = true;
+                        }
+                    });
+                    if !self.thumbnails.is_empty() {
+                        egui::CollapsingHeader::new(t!("thumbnails-header"))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                if let Some(path) = self.thumbnails.show(ui) {
+                                    self.launch(&path, true);
+                                }
+                            });
+                    }
+                    if !self.results.is_empty() {
+                        self.show_results(ui);
+                    }
+                    #[cfg(any(feature = "s3", feature = "sftp"))]
+                    match self.uploads.show_summary(ui) {
+                        Some(upload_ui::SummaryAction::RetryFailed) => {
+                            self.uploads.retry_failed(&self.rt, &self.tx, ctx)
+                        }
+                        Some(upload_ui::SummaryAction::Cancel) => self.uploads.cancel(),
+                        None => {}
+                    }
+                    if !self.failures.is_empty() {
+                        let reveal = std::mem::take(&mut self.reveal_failures);
+                        let response = egui::CollapsingHeader::new(t!(
+                            "failures-header",
+                            count = i18n::number(self.failures.len())
+                        ))
+                        .open(reveal.then_some(true))
+                        .show(ui, |ui| {
+                            egui::ScrollArea::vertical()
+                                .max_height(150.0)
+                                .show(ui, |ui| {
+                                    for failure in &self.failures {
+                                        ui.colored_label(ui.visuals().error_fg_color, failure);
+                                    }
+                                });
+                            ui.label(log_hint());
+                        });
+                        if reveal {
+                            response
+                                .header_response
+                                .scroll_to_me(Some(egui::Align::Min));
+                        }
+                    }
+
+                    egui::CollapsingHeader::new(t!("log-header")).show(ui, |ui| {
+                        self.show_log(ui);
                     });
-                    self.current_preview = Some(task);
+                });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if !self.image_paths.is_empty() {
+                let count = self.image_paths.len();
+                let mut selected = self.preview_index;
+
+                ui.horizontal(|ui| {
+                    if a11y::icon_button(ui, "◀", t!("preview-previous")).clicked() {
+                        selected = (selected + count - 1) % count;
+                    }
+                    a11y::named(
+                        egui::ComboBox::from_id_salt("preview_file")
+                            .selected_text(display_name(&self.image_paths[selected]))
+                            .show_ui(ui, |ui| {
+                                for (index, path) in self.image_paths.iter().enumerate() {
+                                    ui.selectable_value(&mut selected, index, display_name(path));
+                                }
+                            })
+                            .response,
+                        t!("preview-file"),
+                    );
+                    if a11y::icon_button(ui, "▶", t!("preview-next")).clicked() {
+                        selected = (selected + 1) % count;
+                    }
+                    ui.label(format!("{} / {}", selected + 1, count));
+                });
+
+                if selected != self.preview_index {
+                    self.select_preview(selected);
                 }
             }
 
-            ui.separator();
-
-            ui.checkbox(&mut self.resize_images, "Resize Images");
+            if let Some(texture) = &self.preview_texture {
+                ui.heading(t!("preview-heading"));
 
-            if self.resize_images {
+                let backdrop = &mut self.ui_settings.preview_backdrop;
                 ui.horizontal(|ui| {
-                    ui.label("Longest Dimension:");
-                    ui.add(egui::DragValue::new(&mut self.resize_longest_dimension).speed(1.0));
+                    ui.label(t!("backdrop-label"));
+                    ui.radio_value(backdrop, PreviewBackdrop::White, t!("backdrop-white"));
+                    ui.radio_value(backdrop, PreviewBackdrop::Gray, t!("backdrop-gray"));
+                    ui.radio_value(backdrop, PreviewBackdrop::Black, t!("backdrop-black"));
+                    if ui
+                        .radio(
+                            matches!(backdrop, PreviewBackdrop::Custom(_)),
+                            t!("backdrop-custom"),
+                        )
+                        .clicked()
+                    {
+                        *backdrop = PreviewBackdrop::Custom(backdrop.color());
+                    }
+                    if let PreviewBackdrop::Custom(color) = backdrop {
+                        a11y::named(ui.color_edit_button_srgba(color), t!("backdrop-color"));
+                    }
                 });
 
-                ui.label("Resize Algorithm:");
-                ui.vertical(|ui| {
-                    ui.horizontal(|ui| {
-                        ui.radio_value(&mut self.resize_filter, FilterType::Nearest, "Nearest");
-                        ui.label("Fastest, lowest quality.");
-                    });
-                    ui.horizontal(|ui| {
-                        ui.radio_value(&mut self.resize_filter, FilterType::Triangle, "Triangle");
-                        ui.label("Fast, decent quality.");
-                    });
-                    ui.horizontal(|ui| {
-                        ui.radio_value(
-                            &mut self.resize_filter,
-                            FilterType::CatmullRom,
-                            "CatmullRom",
-                        );
-                        ui.label("Good quality, moderate speed.");
-                    });
-                    ui.horizontal(|ui| {
-                        ui.radio_value(&mut self.resize_filter, FilterType::Lanczos3, "Lanczos3");
-                        ui.label("Best quality, slowest.");
-                    });
+                let mut pin = false;
+                let mut swap = false;
+                let mut save = false;
+                let mut copy = false;
+                let mut edit = false;
+                ui.horizontal(|ui| {
+                    let pin_label = if self.comparison.is_some() {
+                        t!("compare-repin")
+                    } else {
+                        t!("compare-pin")
+                    };
+                    pin = ui.button(pin_label).clicked();
+                    save = ui.button(t!("preview-save")).clicked();
+                    copy = ui
+                        .button(t!("preview-copy"))
+                        .on_hover_text(t!("preview-copy-hover", side = PREVIEW_WORKING_SIZE))
+                        .clicked();
+                    if ui
+                        .add_enabled(
+                            self.combine.previewed.is_none(),
+                            egui::Button::new(t!("crop-open")),
+                        )
+                        .on_hover_text(t!("crop-open-hover"))
+                        .clicked()
+                    {
+                        edit = true;
+                    }
+                    if self.comparison.is_some() {
+                        swap = ui.button(t!("compare-swap")).clicked();
+                        if ui.button(t!("compare-clear")).clicked() {
+                            self.comparison = None;
+                        }
+                    }
                 });
-            }
 
-            ui.separator();
+                self.ui_settings.guides.controls(ui);
 
-            ui.label("Output Format:");
-            ui.horizontal(|ui| {
-                ui.radio_value(&mut self.output_format, OutputFormat::Png, "PNG");
-                ui.radio_value(&mut self.output_format, OutputFormat::Jpeg, "JPEG");
-                ui.radio_value(&mut self.output_format, OutputFormat::Tiff, "TIFF");
-                ui.radio_value(&mut self.output_format, OutputFormat::Avif, "AVIF");
-                ui.radio_value(&mut self.output_format, OutputFormat::Webp, "WEBP");
-            });
+                let live_alt = self.preview_alt(
+                    &self
+                        .preview_info
+                        .clone()
+                        .unwrap_or_else(|| self.previewed_info()),
+                );
+                let pinned_alt = self
+                    .comparison
+                    .as_ref()
+                    .map(|pinned| self.preview_alt(&pinned.info))
+                    .unwrap_or_default();
+                let settings = &self.ui_settings;
+                let busy = self.preview_pending;
+                let outdated = self.preview_info != Some(self.previewed_info());
+                let mut live_response = None;
 
-            match self.output_format {
-                OutputFormat::Jpeg => {
-                    ui.horizontal(|ui| {
-                        ui.label("JPEG Quality (1-100):");
-                        ui.add(egui::Slider::new(&mut self.jpeg_quality, 1..=100));
-                    });
+                if self.preview_metadata.is_some() || self.preview_histogram.is_some() {
+                    egui::SidePanel::right("image_info")
+                        .resizable(true)
+                        .default_width(260.0)
+                        .show_inside(ui, |ui| {
+                            egui::ScrollArea::vertical().show(ui, |ui| {
+                                if let Some(metadata) = &self.preview_metadata {
+                                    egui::CollapsingHeader::new(t!("info-header")).show(ui, |ui| {
+                                        egui::Grid::new("image_info").striped(true).show(
+                                            ui,
+                                            |ui| {
+                                                for (label, value) in metadata.rows() {
+                                                    ui.label(label);
+                                                    ui.label(value);
+                                                    ui.end_row();
+                                                }
+                                            },
+                                        );
+                                    });
+                                }
+                                if let Some(histogram) = &self.preview_histogram {
+                                    egui::CollapsingHeader::new(t!("histogram")).show(ui, |ui| {
+                                        histogram.show(ui);
+                                    });
+                                }
+                            });
+                        });
                 }
-                OutputFormat::Avif => {
-                    ui.horizontal(|ui| {
-                        ui.label("AVIF Speed (1-10) 1 = Slowest, better compression, 10 = Fastest");
-                        ui.add(egui::Slider::new(&mut self.avif_speed, 1..=10));
-                        ui.label("AVIF Quality (1-100):");
-                        ui.add(egui::Slider::new(&mut self.avif_quality, 1..=100));
-                    });
+
+                let space = ui.available_size();
+                if let Some((source, _)) = &self.split.texture {
+                    let alt = t!(
+                        "split-preview-alt",
+                        count = i18n::number(self.split.settings.tile_count())
+                    );
+                    let response = show_on_backdrop(ui, source, alt, settings, space);
+                    paint_tiles(ui, response.rect, source.size(), &self.split.settings);
+                } else {
+                    match (&self.comparison, &self.preview_info) {
+                        (Some(pinned), Some(current)) => {
+                            let (a_summary, b_summary) =
+                                describe_differences(&pinned.info, current);
+                            // A and B go side by side or stacked, whichever draws
+                            // them larger.
+                            let [width, height] = texture.size().map(|side| side as f32);
+                            let captions = 2.0 * ui.spacing().interact_size.y;
+                            let beside = egui::vec2(space.x / 2.0, space.y - captions);
+                            let stacked = egui::vec2(space.x, space.y / 2.0 - captions);
+                            let scale = |size: egui::Vec2| (size.x / width).min(size.y / height);
+                            let (layout, max_size) = if scale(stacked) > scale(beside) {
+                                (egui::Layout::top_down(egui::Align::Min), stacked)
+                            } else {
+                                (egui::Layout::left_to_right(egui::Align::Min), beside)
+                            };
+
+                            ui.with_layout(layout, |ui| {
+                                ui.vertical(|ui| {
+                                    ui.strong("A");
+                                    ui.label(a_summary);
+                                    show_on_backdrop(
+                                        ui,
+                                        &pinned.texture,
+                                        pinned_alt,
+                                        settings,
+                                        max_size,
+                                    );
+                                });
+                                ui.vertical(|ui| {
+                                    ui.strong("B");
+                                    ui.label(b_summary);
+                                    let response =
+                                        show_on_backdrop(ui, texture, live_alt, settings, max_size);
+                                    paint_preview_state(ui, response.rect, busy, outdated);
+                                    live_response = Some(response);
+                                });
+                            });
+                        }
+                        _ => {
+                            let response = show_on_backdrop(ui, texture, live_alt, settings, space);
+                            paint_preview_state(ui, response.rect, busy, outdated);
+                            live_response = Some(response);
+                        }
+                    }
+                }
+
+                if pin {
+                    self.comparison = self.snapshot_preview();
                 }
-                _ => {}
+                if swap {
+                    self.swap_comparison();
+                }
+                if save {
+                    self.save_preview();
+                }
+                if copy {
+                    if let Some(composite) = self.preview_composite.clone() {
+                        self.copy_image(ImageSource::Canvas(composite), None);
+                    }
+                }
+                if edit {
+                    self.open_crop_editor();
+                }
+                if let Some(response) = live_response {
+                    self.show_pixel_inspector(&response);
+                }
+            } else if self.image_paths.is_empty() {
+                self.show_empty_state(ui);
+            } else {
+                ui.label(t!("preview-none"));
             }
+        });
 
-            ui.separator();
+        self.show_import_dialog(ctx);
+        self.show_preflight_dialog(ctx);
+        self.show_recovery_dialog(ctx);
+        self.show_clear_dialog(ctx);
+        self.show_close_prompt(ctx);
+        self.show_disk_full_dialog(ctx);
+        self.show_large_copy_dialog(ctx);
+        self.show_crop_editor(ctx);
+        self.show_print_dialog(ctx);
+        self.show_duplicates_dialog(ctx);
+        self.show_shortcuts_overlay(ctx);
+        let progress = self.processing.then_some(taskbar::Progress {
+            done: self.completed_images as u64,
+            total: self.max_images as u64,
+            failed: self.failed_images as u64,
+        });
+        self.taskbar.show(progress);
+        self.tray.show(progress, self.paused);
+        self.autosave_session();
 
-            if ui
-                .add(Slider::new(&mut self.border_percentage, 0.0..=50.0).text("Border Percentage"))
-                .changed()
-            {
-                if let Some(handle) = self.current_preview.take() {
-                    handle.abort();
-                }
-                // Update the preview when the slider changes
-                if let Some(img) = &self.original_image {
-                    let img_clone = img.clone();
-                    let sym = self.symmetrical_border;
-                    let border_perc = self.border_percentage;
-                    let tx = self.tx.clone();
-                    let ctx = self.context.clone();
-                    let task = self.rt.spawn(async move {
-                        let res = update_preview_image(
-                            &img_clone,
-                            BorderInfo {
-                                symmetrical_border: sym,
-                                border_percentage: border_perc,
-                            },
-                        );
-                        let _ = tx.send(MessageResult::PreviewResult { data: res });
-                        ctx.request_repaint();
-                    });
-                    self.current_preview = Some(task);
-                }
+        // Snapshot once the pointer is released so a slider drag is a single
+        // undo step.
+        if !ctx.input(|i| i.pointer.any_down()) {
+            self.history.record(self.process_info());
+        }
+    }
+}
+
+/// What a batch of `count` images into `folder` writes, line by line, for
+/// confirming it before it starts. `estimate` is used for its size and
+/// duration where there is one.
+fn batch_summary(
+    validation: &Validation,
+    count: usize,
+    folder: &Path,
+    estimate: Option<&SizeEstimate>,
+) -> Vec<String> {
+    let mut lines = vec![t!(
+        "preflight-summary-files",
+        count = i18n::number(count),
+        folder = folder.display()
+    )];
+    for (&(format, bits), group) in &validation.outputs {
+        let size = |(width, height): (u32, u32)| format!("{}×{}", width, height);
+        let dimensions = if group.smallest == group.largest {
+            size(group.largest)
+        } else {
+            format!("{} – {}", size(group.smallest), size(group.largest))
+        };
+        lines.push(t!(
+            "preflight-summary-format",
+            count = i18n::number(group.count),
+            format = format,
+            bits = bits,
+            dimensions = dimensions
+        ));
+    }
+    match estimate {
+        Some(estimate) => {
+            lines.push(t!(
+                "preflight-summary-size",
+                size = i18n::bytes(estimate.total)
+            ));
+            if let Some(duration) = estimate.duration {
+                lines.push(t!(
+                    "preflight-summary-duration",
+                    duration = notify::format_duration(duration)
+                ));
             }
+        }
+        None => lines.push(t!(
+            "preflight-summary-size-rough",
+            size = i18n::bytes(validation.expected_bytes)
+        )),
+    }
+    lines
+}
 
-            if let Some(texture) = &self.preview_texture {
-                ui.heading("Preview");
-                ui.image(texture);
-            } else {
-                ui.label("No preview available. Load images first.");
+/// Exit code of a headless run in which some images could not be processed.
+const EXIT_IMAGES_FAILED: i32 = 1;
+/// Exit code of a headless run that was refused by the pre-flight checks.
+const EXIT_INVALID: i32 = 3;
+/// Exit code of a large headless batch the user didn't confirm.
+const EXIT_DECLINED: i32 = 4;
+
+/// Processes the configured input folder without a window, returning the
+/// process exit code. Warnings are reported but don't stop the run. Large
+/// batches are confirmed on the terminal first, unless `yes`.
+#[cfg_attr(not(feature = "sftp"), allow(unused_variables))]
+fn run_headless(config: Config, yes: bool, trust_host_key: bool) -> i32 {
+    let input_dir = config.input.unwrap_or_default();
+    let output_dir = config.output.unwrap_or_default();
+
+    let suffix = config.suffix.unwrap_or_else(|| DEFAULT_SUFFIX.to_string());
+    let recursive = config.recursive.unwrap_or(false);
+    let image_paths = match scan_inputs(&input_dir, &output_dir, &suffix, recursive) {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!("Error: cannot read the input directory: {}", e);
+            return EXIT_INVALID;
+        }
+    };
+
+    let naming = BatchNaming {
+        template: config
+            .name_template
+            .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+        suffix,
+        manual_preset: None,
+        mirror_root: (recursive && config.preserve_folders.unwrap_or(false))
+            .then(|| input_dir.clone()),
+        date_folders: config.date_folders,
+        overwrite: config.overwrite.unwrap_or_default(),
+        originals: config.originals,
+        preserve_timestamps: config.preserve_timestamps.unwrap_or(false),
+        variant_folders: config.variant_folders.unwrap_or_default(),
+        run_folder: config.run_folder,
+        srcset: config.srcset,
+    };
+    let output_dir = naming
+        .run_dir(&output_dir, dates::now())
+        .unwrap_or(output_dir);
+    let mut validation = validation::validate(
+        &input_dir,
+        &output_dir,
+        &image_paths,
+        &[],
+        config.process.clone(),
+        &naming,
+    );
+    validation.check_space(&output_dir, validation.expected_bytes);
+    if let Some(Err(e)) = config
+        .hook
+        .as_ref()
+        .map(|hook| hook::parse_command(&hook.command))
+    {
+        validation
+            .errors
+            .push(format!("the hook command is invalid: {}", e));
+    }
+    for warning in &validation.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    for error in &validation.errors {
+        eprintln!("Error: {}", error);
+    }
+    if !validation.errors.is_empty() {
+        return EXIT_INVALID;
+    }
+    if image_paths.len() >= LARGE_BATCH && !yes && io::stdin().is_terminal() {
+        let estimate = estimate::estimate(
+            &image_paths,
+            &[],
+            config.process.clone(),
+            &AtomicBool::new(false),
+        )
+        .ok()
+        .flatten();
+        let summary = batch_summary(
+            &validation,
+            image_paths.len(),
+            &output_dir,
+            estimate.as_ref(),
+        );
+        for line in summary {
+            println!("{}", line);
+        }
+        if !confirm("Start? [y/N] ") {
+            return EXIT_DECLINED;
+        }
+    }
+    #[cfg(not(feature = "s3"))]
+    if config.upload.is_some() {
+        eprintln!("Warning: this build cannot upload; `upload` needs the `s3` feature");
+    }
+    #[cfg(not(feature = "sftp"))]
+    if config.sftp.is_some() {
+        eprintln!("Warning: this build cannot upload; `sftp` needs the `sftp` feature");
+    }
+    #[cfg(not(feature = "webhook"))]
+    if config.webhook.is_some() {
+        eprintln!("Warning: this build cannot report to a webhook; it needs the `webhook` feature");
+    }
+    if config.upload.is_some() && config.sftp.is_some() {
+        eprintln!("Error: configure either `upload` or `sftp`, not both");
+        return EXIT_INVALID;
+    }
+    #[cfg(any(feature = "s3", feature = "sftp"))]
+    let target = match (config.upload.clone(), config.sftp.clone()) {
+        #[cfg(feature = "s3")]
+        (Some(settings), _) => Some(upload_ui::Connect::S3(settings)),
+        #[cfg(feature = "sftp")]
+        (_, Some(settings)) => Some(upload_ui::Connect::Sftp(settings)),
+        _ => None,
+    };
+    #[cfg(any(feature = "s3", feature = "sftp"))]
+    let uploads = match target {
+        Some(target) => {
+            let rt = Runtime::new().expect("Failed to create runtime");
+            #[cfg(feature = "sftp")]
+            if let (upload_ui::Connect::Sftp(settings), true) = (&target, trust_host_key) {
+                match rt.block_on(upload_ui::trust_unknown_host(settings)) {
+                    Ok(Some(fingerprint)) => println!(
+                        "Trusting the host key {} of {}",
+                        fingerprint,
+                        settings.address()
+                    ),
+                    Ok(None) => {}
+                    Err(e) => {
+                        eprintln!("Error: cannot upload to {}: {}", settings.address(), e);
+                        return EXIT_INVALID;
+                    }
+                }
+            }
+            let name = target.name();
+            match rt.block_on(upload_ui::connect(target)) {
+                Ok(destination) => Some((destination, rt)),
+                Err(e) => {
+                    eprintln!("Error: cannot upload to {}: {}", name, e);
+                    return EXIT_INVALID;
+                }
             }
+        }
+        None => None,
+    };
+    #[cfg(any(feature = "s3", feature = "sftp"))]
+    let mut uploading = vec![];
+    #[cfg(any(feature = "s3", feature = "sftp"))]
+    let mut upload = |outputs: &[PathBuf]| {
+        let Some((destination, rt)) = &uploads else {
+            return;
+        };
+        for path in outputs {
+            let key = destination.key(&output_dir, path);
+            let (destination, path) = (destination.clone(), path.clone());
+            uploading.push(rt.spawn(async move {
+                let result = destination.upload(&path, &key, |_| {}).await;
+                (path, key, result)
+            }));
+        }
+    };
+    if naming.run_folder.is_some() {
+        println!("Writing into {}", output_dir.display());
+    }
 
-            if !self.processing {
-                if ui.button("Start Processing").clicked() {
-                    self.process_images();
+    let total = image_paths.len();
+    #[cfg(feature = "webhook")]
+    let (started, mut tally) = (Instant::now(), webhook::BatchTally::new(total));
+    let (events, finished) = mpsc::channel();
+    let worker = Worker::spawn(move |event| {
+        let _ = events.send(event);
+    });
+    worker.send(Command::StartBatch {
+        paths: image_paths,
+        output_dir: output_dir.clone(),
+        rules: vec![],
+        manual: config.process,
+        edits: HashMap::new(),
+        naming,
+        manifest: config.manifest,
+        placeholder: config.placeholder,
+        archive: config.archive,
+        hook: config.hook,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+
+    let mut failed = 0;
+    for event in finished {
+        match event {
+            Event::FileDone {
+                path,
+                applied,
+                result,
+            } => {
+                if !report_file(&path, &applied, &result) {
+                    failed += 1;
                 }
-            } else {
-                ui.add(
-                    ProgressBar::new(self.completed_images as f32 / self.max_images as f32).text(
-                        format!(
-                            "{:.1}%",
-                            (self.completed_images as f32 / self.max_images as f32) * 100.0
-                        ),
-                    ),
+                #[cfg(feature = "webhook")]
+                tally.add(&result);
+                #[cfg(any(feature = "s3", feature = "sftp"))]
+                if let Ok(outcome) = &result {
+                    upload(&outcome.output_paths);
+                }
+            }
+            Event::DiskFull { path } => {
+                // Nobody is there to free space and resume.
+                eprintln!(
+                    "Error: {} does not fit on the drive of {}, cancelling",
+                    path.display(),
+                    output_dir.display()
                 );
+                worker.send(Command::CancelBatch);
+            }
+            #[cfg(feature = "webhook")]
+            Event::BatchFinished { cancelled } => {
+                tally.cancelled = cancelled;
+                break;
+            }
+            #[cfg(not(feature = "webhook"))]
+            Event::BatchFinished { .. } => break,
+            Event::ArchiveDone { result } => match result {
+                Ok(Some(path)) => {
+                    println!("Outputs zipped into {}", path.display());
+                    #[cfg(any(feature = "s3", feature = "sftp"))]
+                    upload(std::slice::from_ref(&path));
+                }
+                Ok(None) => println!("The archive exists already and was left alone"),
+                Err(e) => {
+                    eprintln!("Error: could not write the archive: {}", e);
+                    failed += 1;
+                }
+            },
+            Event::Preview { .. }
+            | Event::Thumbnail { .. }
+            | Event::ArchiveProgress { .. }
+            | Event::SizeEstimate { .. }
+            | Event::DuplicateProgress { .. }
+            | Event::Duplicates { .. } => {}
+        }
+    }
+
+    #[cfg(any(feature = "s3", feature = "sftp"))]
+    if let Some((destination, rt)) = &uploads {
+        let mut uploaded = 0;
+        for handle in uploading {
+            match rt.block_on(handle) {
+                Ok((_, key, Ok(()))) => {
+                    println!("Uploaded {}", destination.url(&key));
+                    uploaded += 1;
+                }
+                Ok((path, _, Err(e))) => {
+                    eprintln!("Error: could not upload {}: {}", path.display(), e);
+                    failed += 1;
+                }
+                Err(e) => {
+                    eprintln!("Error: an upload panicked: {}", e);
+                    failed += 1;
+                }
             }
+        }
+        println!("{} files uploaded to {}", uploaded, destination.name());
+    }
 
-            ui.label(&self.status_message);
-        });
+    #[cfg(feature = "webhook")]
+    if let Some(settings) = config.webhook.filter(WebhookSettings::is_complete) {
+        let report = tally.report(&output_dir, started.elapsed());
+        let rt = Runtime::new().expect("Failed to create runtime");
+        // Like in the app, a report that doesn't arrive fails nothing.
+        match rt.block_on(webhook::send(&settings.url, &report)) {
+            Ok(()) => println!("Batch reported to the webhook"),
+            Err(e) => eprintln!("Warning: could not report the batch to the webhook: {}", e),
+        }
+    }
+
+    if failed > 0 {
+        eprintln!("{} of {} images failed", failed, total);
+        EXIT_IMAGES_FAILED
+    } else {
+        0
+    }
+}
+
+/// Asks `question` on the terminal, true once answered with "y" or "yes".
+fn confirm(question: &str) -> bool {
+    print!("{}", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Offers to copy the regular settings into a portable folder that is used
+/// for the first time.
+fn offer_portable_migration() {
+    let eframe_file = eframe::storage_dir(storage::APP_NAME).map(|dir| dir.join("app.ron"));
+    if !storage::offers_migration(eframe_file.as_deref()) {
+        return;
+    }
+
+    let copy = rfd::MessageDialog::new()
+        .set_title("Portable mode")
+        .set_description(format!(
+            "Copy your existing settings and presets into {}?",
+            storage::config_dir().display()
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show()
+        == rfd::MessageDialogResult::Yes;
+
+    let result = if copy {
+        storage::migrate_to_portable(eframe_file.as_deref())
+    } else {
+        storage::skip_migration()
+    };
+    if let Err(e) = result {
+        eprintln!("Warning: could not set up the portable folder: {}", e);
     }
 }
 
 fn main() {
-    let native_options = eframe::NativeOptions::default();
+    let cli = Cli::parse();
+    storage::init(cli.portable);
+    if !cli.headless && !cli.print_config {
+        offer_portable_migration();
+    }
+
+    let config_path = cli.config.clone().unwrap_or_else(Config::default_path);
+    let config_file = if cli.config.is_some() || config_path.exists() {
+        match Config::load(&config_path) {
+            Ok((config, unknown)) => {
+                for key in unknown {
+                    eprintln!(
+                        "Warning: ignoring unknown key `{}` in {}",
+                        key,
+                        config_path.display()
+                    );
+                }
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
+    if cli.print_config {
+        let mut config = config_file.unwrap_or_default();
+        cli.apply_overrides(&mut config);
+        print!("{}", config.to_toml());
+        return;
+    }
+
+    let (log, log_guard) = logging::init();
+    tracing::info!("Image Finalizer {} started", env!("CARGO_PKG_VERSION"));
+
+    if cli.headless {
+        let mut config = config_file.unwrap_or_default();
+        cli.apply_overrides(&mut config);
+        let code = run_headless(config, cli.yes, cli.trust_host_key);
+        // `exit` skips destructors, and the guard flushes the log file.
+        drop(log_guard);
+        std::process::exit(code);
+    }
+
+    let native_options = eframe::NativeOptions {
+        persistence_path: storage::eframe_storage_file(),
+        ..Default::default()
+    };
     run_native(
-        "Image Border App",
+        storage::APP_NAME,
         native_options,
-        Box::new(|cc| Ok(Box::new(BorderApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(BorderApp::new(cc, config_file, &cli, log)))),
     )
     .unwrap();
 }
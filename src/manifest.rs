@@ -0,0 +1,327 @@
+//! Machine-readable record of a batch, written next to its outputs.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    duplicates::Duplicate,
+    naming,
+    placeholder::{self, PlaceholderKind},
+    ChosenSettings, ProcessError, ProcessInfo, ProcessOutcome,
+};
+
+/// Start of the names of manifests.
+pub const MANIFEST_PREFIX: &str = "manifest-";
+
+/// File format of the manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// One JSON object per line, so the file stays valid while it grows.
+    Json,
+}
+
+impl ManifestFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ManifestFormat::Csv => "csv",
+            ManifestFormat::Json => "jsonl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestStatus {
+    Written,
+    Skipped,
+    Failed,
+    /// Left out of the batch as a duplicate of `duplicate_of`.
+    Duplicate,
+}
+
+impl ManifestStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            ManifestStatus::Written => "written",
+            ManifestStatus::Skipped => "skipped",
+            ManifestStatus::Failed => "failed",
+            ManifestStatus::Duplicate => "duplicate",
+        }
+    }
+}
+
+/// What happened to one input. Paths that aren't valid Unicode are
+/// recorded with replacement characters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source: String,
+    /// Every file written for the input. CSV puts them on separate lines of
+    /// one field.
+    pub outputs: Vec<String>,
+    pub width: u32,
+    pub height: u32,
+    pub format: String,
+    /// Encoder quality, for the formats that have one.
+    pub quality: Option<u8>,
+    /// Total size of `outputs`.
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub status: ManifestStatus,
+    /// Why the input failed or was skipped.
+    pub message: Option<String>,
+    /// Where the settings came from.
+    pub settings: String,
+    /// `settings_hash` of the settings, equal for equal settings.
+    pub settings_hash: String,
+    /// The input kept instead of this one, if it was left out as a
+    /// duplicate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+    /// Placeholders of the output, when the batch asked for them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blurhash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbhash: Option<String>,
+}
+
+/// Header row of CSV manifests, in the order of the `ManifestEntry` fields.
+const CSV_HEADER: [&str; 15] = [
+    "source",
+    "outputs",
+    "width",
+    "height",
+    "format",
+    "quality",
+    "bytes",
+    "duration_ms",
+    "status",
+    "message",
+    "settings",
+    "settings_hash",
+    "duplicate_of",
+    "blurhash",
+    "thumbhash",
+];
+
+impl ManifestEntry {
+    /// The entry of `path`, processed with `chosen`.
+    pub fn new(
+        path: &Path,
+        chosen: &ChosenSettings,
+        result: &Result<ProcessOutcome, ProcessError>,
+    ) -> Self {
+        let info = &chosen.info;
+        let mut entry = ManifestEntry {
+            source: path.to_string_lossy().into_owned(),
+            outputs: vec![],
+            width: 0,
+            height: 0,
+            format: info.output_format.encoder().name().to_lowercase(),
+            quality: info.format_settings.quality(info.output_format),
+            bytes: 0,
+            duration_ms: 0,
+            status: ManifestStatus::Failed,
+            message: None,
+            settings: chosen.applied.clone(),
+            settings_hash: settings_hash(info),
+            duplicate_of: None,
+            blurhash: None,
+            thumbhash: None,
+        };
+        match result {
+            Ok(outcome) => {
+                entry.outputs = outcome
+                    .output_paths
+                    .iter()
+                    .map(|output| output.to_string_lossy().into_owned())
+                    .collect();
+                (entry.width, entry.height) = outcome.final_dimensions;
+                entry.bytes = outcome.bytes_written;
+                entry.duration_ms = outcome.durations.total().as_millis() as u64;
+                match outcome.skipped {
+                    Some(reason) => {
+                        entry.status = ManifestStatus::Skipped;
+                        entry.message = Some(reason.to_string());
+                    }
+                    None => entry.status = ManifestStatus::Written,
+                }
+            }
+            Err(e) => entry.message = Some(e.to_string()),
+        }
+        entry
+    }
+
+    /// The entry of an input left out of the batch in favor of the one
+    /// `duplicate` kept.
+    pub fn duplicate(duplicate: &Duplicate) -> Self {
+        ManifestEntry {
+            source: duplicate.path.to_string_lossy().into_owned(),
+            outputs: vec![],
+            width: 0,
+            height: 0,
+            format: String::new(),
+            quality: None,
+            bytes: 0,
+            duration_ms: 0,
+            status: ManifestStatus::Duplicate,
+            message: None,
+            settings: String::new(),
+            settings_hash: String::new(),
+            duplicate_of: Some(duplicate.kept.to_string_lossy().into_owned()),
+            blurhash: None,
+            thumbhash: None,
+        }
+    }
+
+    /// Records the placeholder of `kind` of the output `thumbnail` shows.
+    pub fn add_placeholder(&mut self, kind: PlaceholderKind, thumbnail: &RgbaImage) {
+        let hash = Some(placeholder::placeholder(thumbnail, kind));
+        match kind {
+            PlaceholderKind::Blurhash => self.blurhash = hash,
+            PlaceholderKind::Thumbhash => self.thumbhash = hash,
+        }
+    }
+
+    fn csv_record(&self) -> [String; 15] {
+        [
+            self.source.clone(),
+            self.outputs.join("\n"),
+            self.width.to_string(),
+            self.height.to_string(),
+            self.format.clone(),
+            self.quality.map(|q| q.to_string()).unwrap_or_default(),
+            self.bytes.to_string(),
+            self.duration_ms.to_string(),
+            self.status.as_str().to_string(),
+            self.message.clone().unwrap_or_default(),
+            self.settings.clone(),
+            self.settings_hash.clone(),
+            self.duplicate_of.clone().unwrap_or_default(),
+            self.blurhash.clone().unwrap_or_default(),
+            self.thumbhash.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// FNV-1a of the serialized settings, in hex. Stable across runs and
+/// platforms, unlike the standard library's hasher.
+pub fn settings_hash(info: &ProcessInfo) -> String {
+    let bytes = serde_json::to_vec(info).unwrap_or_default();
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+enum Writer {
+    Csv(Box<csv::Writer<fs::File>>),
+    Json(fs::File),
+}
+
+/// A manifest being written. Every entry is flushed as it is added, so a
+/// batch that crashes still leaves the records of its finished files.
+pub struct Manifest {
+    path: PathBuf,
+    writer: Writer,
+}
+
+/// The outputs that the manifest at `path` lists, of either format.
+pub fn listed_outputs(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut outputs = vec![];
+    if path
+        .extension()
+        .is_some_and(|ext| ext == ManifestFormat::Csv.extension())
+    {
+        let mut reader = csv::Reader::from_path(path)?;
+        let column = reader
+            .headers()?
+            .iter()
+            .position(|column| column == "outputs");
+        for record in reader.records() {
+            if let Some(field) = column.and_then(|column| record.as_ref().ok()?.get(column)) {
+                outputs.extend(field.lines().map(PathBuf::from));
+            }
+        }
+    } else {
+        for line in fs::read_to_string(path)?.lines() {
+            // Lines of a crashed run may be cut short.
+            if let Ok(entry) = serde_json::from_str::<ManifestEntry>(line) {
+                outputs.extend(entry.outputs.into_iter().map(PathBuf::from));
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+impl Manifest {
+    /// Starts `manifest-DATE-TIME` in `output_dir`, numbered when a batch
+    /// of the same second already wrote one.
+    pub fn create(output_dir: &Path, format: ManifestFormat) -> io::Result<Manifest> {
+        fs::create_dir_all(output_dir)?;
+        let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+        let name = format!(
+            "{}{:04}{:02}{:02}-{:02}{:02}{:02}.{}",
+            MANIFEST_PREFIX,
+            now.year(),
+            u8::from(now.month()),
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second(),
+            format.extension()
+        );
+
+        let mut number = 0;
+        let (path, file) = loop {
+            let path = match number {
+                0 => output_dir.join(&name),
+                number => output_dir.join(naming::numbered_name(name.as_ref(), number)),
+            };
+            match fs::File::create_new(&path) {
+                Ok(file) => break (path, file),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => number += 1,
+                Err(e) => return Err(e),
+            }
+        };
+
+        let writer = match format {
+            ManifestFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                writer.write_record(CSV_HEADER)?;
+                writer.flush()?;
+                Writer::Csv(Box::new(writer))
+            }
+            ManifestFormat::Json => Writer::Json(file),
+        };
+        Ok(Manifest { path, writer })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `entry`.
+    pub fn record(&mut self, entry: &ManifestEntry) -> io::Result<()> {
+        match &mut self.writer {
+            Writer::Csv(writer) => {
+                writer.write_record(entry.csv_record())?;
+                writer.flush()
+            }
+            Writer::Json(file) => {
+                let mut line = serde_json::to_vec(entry)?;
+                line.push(b'\n');
+                io::Write::write_all(file, &line)
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{fs, io::BufReader, path::Path};
+
+use exif::{Exif, In, Tag};
+use image::{ImageDecoder, ImageReader};
+use image_finalizer::{i18n, t};
+
+/// Key facts about an input file, shown next to the preview.
+#[derive(Debug, Clone, Default)]
+pub struct ImageMetadata {
+    pub dimensions: Option<(u32, u32)>,
+    pub file_size: u64,
+    pub capture_date: Option<String>,
+    pub camera: Option<String>,
+    pub lens: Option<String>,
+    pub iso: Option<String>,
+    pub aperture: Option<String>,
+    pub shutter: Option<String>,
+    pub color_profile: Option<String>,
+    pub orientation: Option<String>,
+}
+
+impl ImageMetadata {
+    /// Reads the file header and EXIF block. Missing or unreadable metadata
+    /// leaves the corresponding fields empty rather than failing.
+    pub fn read(path: &Path) -> Self {
+        let mut metadata = ImageMetadata {
+            dimensions: image::image_dimensions(path).ok(),
+            file_size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            ..Default::default()
+        };
+
+        if let Some(exif) = read_exif(path) {
+            let field = |tag| {
+                exif.get_field(tag, In::PRIMARY)
+                    .map(|f| f.display_value().with_unit(&exif).to_string())
+                    .map(|value| value.trim_matches('"').trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+
+            metadata.capture_date = field(Tag::DateTimeOriginal).or_else(|| field(Tag::DateTime));
+            metadata.camera = match (field(Tag::Make), field(Tag::Model)) {
+                (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+                (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+                (make, model) => make.or(model),
+            };
+            metadata.lens = field(Tag::LensModel);
+            metadata.iso = field(Tag::PhotographicSensitivity);
+            metadata.aperture = field(Tag::FNumber);
+            metadata.shutter = field(Tag::ExposureTime);
+            metadata.color_profile = field(Tag::ColorSpace);
+            metadata.orientation = field(Tag::Orientation);
+        }
+
+        if metadata.color_profile.is_none() && has_icc_profile(path) {
+            metadata.color_profile = Some(t!("info-icc-profile").to_string());
+        }
+
+        metadata
+    }
+
+    /// Label/value pairs for every field that is present.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![];
+
+        if let Some((width, height)) = self.dimensions {
+            rows.push((
+                t!("info-dimensions"),
+                format!("{} × {} px", i18n::number(width), i18n::number(height)),
+            ));
+        }
+        rows.push((t!("info-file-size"), i18n::bytes(self.file_size)));
+
+        for (label, value) in [
+            (t!("info-captured"), &self.capture_date),
+            (t!("info-camera"), &self.camera),
+            (t!("info-lens"), &self.lens),
+            (t!("info-iso"), &self.iso),
+            (t!("info-aperture"), &self.aperture),
+            (t!("info-shutter"), &self.shutter),
+            (t!("info-color-profile"), &self.color_profile),
+            (t!("info-orientation"), &self.orientation),
+        ] {
+            if let Some(value) = value {
+                rows.push((label, value.clone()));
+            }
+        }
+
+        rows
+    }
+}
+
+fn read_exif(path: &Path) -> Option<Exif> {
+    let file = fs::File::open(path).ok()?;
+    exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()
+}
+
+fn has_icc_profile(path: &Path) -> bool {
+    ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .ok()
+        .and_then(|reader| reader.into_decoder().ok())
+        .and_then(|mut decoder| decoder.icc_profile().ok().flatten())
+        .is_some()
+}
@@ -0,0 +1,846 @@
+//! Construction of output file names and paths that are valid wherever they
+//! are written, whatever the input was called.
+
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use time::PrimitiveDateTime;
+
+use crate::{dates, format::OutputFormat, srcset::SrcsetSettings, t};
+
+/// Characters Windows doesn't allow in file names, besides control
+/// characters.
+const WINDOWS_FORBIDDEN: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of case and extension.
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM0", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7",
+    "COM8", "COM9", "COM¹", "COM²", "COM³", "LPT0", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9", "LPT¹", "LPT²", "LPT³",
+];
+
+/// What `bordered_name` appends to the input stem, and `{suffix}` unless
+/// the user picks another.
+pub const DEFAULT_SUFFIX: &str = "_bordered";
+
+/// Template reproducing `bordered_name`.
+pub const DEFAULT_TEMPLATE: &str = "{name}{suffix}.{ext}";
+
+/// `{preset}` of files processed with settings that aren't a preset.
+pub const MANUAL_PRESET: &str = "manual";
+
+/// `{date}` without a pattern.
+const DEFAULT_DATE_PATTERN: &str = "%Y%m%d";
+/// `{seq}` without a width.
+const DEFAULT_SEQ_WIDTH: usize = 3;
+/// Widest `{seq}`, enough for any `u64`.
+const MAX_SEQ_WIDTH: usize = 20;
+
+/// Longest path the classic Windows APIs accept for a folder; files allow a
+/// few more characters, but output folders are created too.
+const WINDOWS_MAX_PATH: usize = 248;
+
+/// Which file system rules a generated name has to follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameRules {
+    /// Anything but `/` and NUL, in any encoding.
+    Unix,
+    Windows,
+}
+
+impl NameRules {
+    /// The rules of the platform this build runs on.
+    pub const fn native() -> Self {
+        if cfg!(windows) {
+            NameRules::Windows
+        } else {
+            NameRules::Unix
+        }
+    }
+}
+
+/// A generated file name, and whether it had to be changed to be valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputName {
+    pub name: OsString,
+    pub mangled: bool,
+}
+
+/// `name` made usable as a file name under `rules`. Names that aren't valid
+/// Unicode are kept byte for byte under Unix rules. Under Windows rules,
+/// forbidden characters and trailing dots and spaces become `_`, and device
+/// names such as `aux.tar` get a leading `_`.
+pub fn sanitize_file_name(name: &OsStr, rules: NameRules) -> OutputName {
+    match rules {
+        // A file name never contains a separator, and NUL can't reach us.
+        NameRules::Unix => OutputName {
+            name: name.to_os_string(),
+            mangled: false,
+        },
+        NameRules::Windows => {
+            let original = name.to_string_lossy();
+            let mut safe: String = original
+                .chars()
+                .map(|c| {
+                    if c.is_control() || WINDOWS_FORBIDDEN.contains(&c) {
+                        '_'
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+
+            let kept = safe.trim_end_matches(['.', ' ']).len();
+            let trailing = safe.len() - kept;
+            safe.truncate(kept);
+            safe.extend(std::iter::repeat_n('_', trailing));
+
+            let base = safe.split('.').next().unwrap_or_default().trim_end();
+            if WINDOWS_RESERVED
+                .iter()
+                .any(|reserved| reserved.eq_ignore_ascii_case(base))
+            {
+                safe.insert(0, '_');
+            }
+
+            OutputName {
+                mangled: safe != original || name.to_str().is_none(),
+                name: safe.into(),
+            }
+        }
+    }
+}
+
+/// `{stem}_bordered.{extension}` for the input stem `stem`.
+pub fn bordered_name(stem: &OsStr, extension: &str, rules: NameRules) -> OutputName {
+    let mut name = stem.to_os_string();
+    name.push(DEFAULT_SUFFIX);
+    name.push(".");
+    name.push(extension);
+    sanitize_file_name(&name, rules)
+}
+
+/// Whether `path` is named like an output with `suffix`, possibly numbered
+/// by `numbered_name`. Without a suffix, outputs can't be told from other
+/// files.
+pub fn has_output_suffix(path: &Path, suffix: &str) -> bool {
+    !suffix.is_empty()
+        && path
+            .file_stem()
+            .is_some_and(|stem| strip_number(stem.as_encoded_bytes()).ends_with(suffix.as_bytes()))
+}
+
+/// `stem` without the ` (number)` that `numbered_name` adds.
+fn strip_number(stem: &[u8]) -> &[u8] {
+    let Some(open) = stem.strip_suffix(b")") else {
+        return stem;
+    };
+    match open.iter().rposition(|&byte| !byte.is_ascii_digit()) {
+        Some(start) if start + 1 < open.len() && open[..=start].ends_with(b" (") => {
+            &open[..start - 1]
+        }
+        _ => stem,
+    }
+}
+
+/// What happens when the name of an output is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum OverwritePolicy {
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+    /// Add ` (1)`, ` (2)`, … to the name until it is free.
+    Rename,
+    /// Keep the existing file and don't write the output.
+    Skip,
+}
+
+/// Where the untouched copy of an input goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum OriginalsPlacement {
+    /// In the same folder as the output.
+    #[default]
+    Beside,
+    /// In an `originals` folder inside the output's folder.
+    Subfolder,
+}
+
+/// Folder of `OriginalsPlacement::Subfolder`.
+pub const ORIGINALS_FOLDER: &str = "originals";
+
+impl OriginalsPlacement {
+    /// Folder of the copy of an input whose output goes into `folder`.
+    pub fn folder(self, folder: &Path) -> PathBuf {
+        match self {
+            OriginalsPlacement::Beside => folder.to_path_buf(),
+            OriginalsPlacement::Subfolder => folder.join(ORIGINALS_FOLDER),
+        }
+    }
+}
+
+/// Subfolders each output is sorted into after what it is, so that the
+/// formats and sizes of a batch don't mix. Nothing is sorted by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VariantFolders {
+    /// `jpeg/`, or `png-16bit/` for outputs with more bits per channel.
+    pub by_format: bool,
+    /// `2048px/`, after the longest side.
+    pub by_size: bool,
+}
+
+impl VariantFolders {
+    /// Folder of an output in `format` that is `dimensions` large and has
+    /// `bits` per channel.
+    pub fn folder(self, format: OutputFormat, bits: u8, (width, height): (u32, u32)) -> PathBuf {
+        let mut folder = PathBuf::new();
+        if self.by_format {
+            let name = format.encoder().name().to_ascii_lowercase();
+            if bits > 8 {
+                folder.push(format!("{}-{}bit", name, bits));
+            } else {
+                folder.push(name);
+            }
+        }
+        if self.by_size {
+            folder.push(format!("{}px", width.max(height)));
+        }
+        folder
+    }
+}
+
+/// Numbers `OverwritePolicy::Rename` tries before giving up.
+pub const MAX_RENAME_NUMBER: u32 = 9_999;
+
+/// `name` with ` (number)` before its extension.
+pub fn numbered_name(name: &OsStr, number: u32) -> OsString {
+    let path = Path::new(name);
+    let mut numbered = path.file_stem().unwrap_or(name).to_os_string();
+    numbered.push(format!(" ({})", number));
+    if let Some(extension) = path.extension() {
+        numbered.push(".");
+        numbered.push(extension);
+    }
+    numbered
+}
+
+/// `path` in a form that may exceed the classic Windows length limit: long
+/// absolute paths get the `\\?\` prefix, which also turns off Windows' own
+/// normalization, so separators and `.`/`..` are resolved here. The standard
+/// library does the same for its own calls, but paths handed to other
+/// programs need it spelled out. Other paths are returned unchanged.
+pub fn extended_length_path(path: &Path, rules: NameRules) -> PathBuf {
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    if rules != NameRules::Windows || text.len() < WINDOWS_MAX_PATH || text.starts_with(r"\\?\") {
+        return path.to_path_buf();
+    }
+
+    let text = text.replace('/', r"\");
+    // The drive, or the server and share, can't be climbed out of.
+    let (prefix, rest, root) = if let Some(share) = text.strip_prefix(r"\\") {
+        (r"\\?\UNC\", share, 2)
+    } else if text.as_bytes().get(1) == Some(&b':') && text.as_bytes().get(2) == Some(&b'\\') {
+        (r"\\?\", text.as_str(), 1)
+    } else {
+        // Relative paths can't be made verbatim without the current folder.
+        return path.to_path_buf();
+    };
+
+    let mut components: Vec<&str> = vec![];
+    for component in rest.split('\\') {
+        match component {
+            "" | "." => {}
+            ".." if components.len() > root => {
+                components.pop();
+            }
+            ".." => {}
+            component => components.push(component),
+        }
+    }
+
+    PathBuf::from(format!("{}{}", prefix, components.join(r"\")))
+}
+
+/// Output file names built from text and placeholders:
+///
+/// - `{name}`: the input file name without its extension
+/// - `{suffix}`: text set apart from the template, `_bordered` by default
+/// - `{ext}`: the extension of the output format
+/// - `{format}`: the output format, e.g. `jpeg`
+/// - `{width}`, `{height}`: size of the output in pixels
+/// - `{date}`, `{date:PATTERN}`: capture date, or the file date without one,
+///   formatted with `%Y`, `%y`, `%m`, `%d`, `%H`, `%M`, `%S`, `%j` and `%%`;
+///   `%Y%m%d` by default
+/// - `{seq}`, `{seq:WIDTH}`, `{seq:WIDTH:START}`: position in the batch,
+///   zero-padded to `WIDTH` digits (3) and counting from `START` (1)
+/// - `{preset}`: the preset the file is processed with
+/// - `{name1}`, `{name2}`, `{name3}`: names of the photos of a combined
+///   output in order, empty where there are fewer
+/// - `{names}`: the names of all photos of a combined output joined by
+///   `+`, or `{name}` for a single photo
+///
+/// Tiles of a split photo get their position before the extension, like
+/// `_1of4`, whatever the template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTemplate {
+    text: String,
+    parts: Vec<Part>,
+    suffix: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Name,
+    Suffix,
+    Ext,
+    Format,
+    Width,
+    Height,
+    Date(String),
+    Seq {
+        width: usize,
+        start: u64,
+    },
+    Preset,
+    /// `{name1}` is 0.
+    NameAt(usize),
+    Names,
+}
+
+/// Why a name template can't be used.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TemplateError {
+    #[error("{}", t!("template-empty"))]
+    Empty,
+    #[error("{}", t!("template-empty-name"))]
+    EmptyName,
+    #[error("{}", t!("template-unclosed"))]
+    Unclosed,
+    #[error("{}", t!("template-unknown-placeholder", placeholder = braced(.0)))]
+    UnknownPlaceholder(String),
+    #[error("{}", t!("template-invalid-sequence", placeholder = braced(.0)))]
+    InvalidSequence(String),
+    #[error("{}", t!("template-sequence-overflow", max = u64::MAX))]
+    SequenceOverflow,
+    #[error("{}", t!("template-invalid-date", field = .0))]
+    InvalidDate(char),
+    #[error("{}", t!("template-illegal-character", character = format!("{:?}", .0)))]
+    IllegalCharacter(char),
+    #[error("{}", t!("template-empty-folder"))]
+    EmptyFolder,
+    #[error("{}", t!("template-not-for-run-folder", placeholder = braced(.0)))]
+    NotForRunFolder(String),
+}
+
+/// `name` in braces, as it is written in a template.
+fn braced(name: &str) -> String {
+    format!("{{{}}}", name)
+}
+
+impl Default for NameTemplate {
+    fn default() -> Self {
+        DEFAULT_TEMPLATE
+            .parse()
+            .expect("the default template is valid")
+    }
+}
+
+impl fmt::Display for NameTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for NameTemplate {
+    type Err = TemplateError;
+
+    fn from_str(text: &str) -> Result<Self, TemplateError> {
+        if text.trim().is_empty() {
+            return Err(TemplateError::Empty);
+        }
+
+        let mut parts = vec![];
+        let mut rest = text;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                parts.push(Part::Text(literal(&rest[..open])?));
+            }
+            let close = rest[open..].find('}').ok_or(TemplateError::Unclosed)? + open;
+            parts.push(placeholder(&rest[open + 1..close])?);
+            rest = &rest[close + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(literal(rest)?));
+        }
+
+        Ok(NameTemplate {
+            text: text.to_string(),
+            parts,
+            suffix: DEFAULT_SUFFIX.to_string(),
+        })
+    }
+}
+
+/// Text between placeholders, which has to be valid in a file name on every
+/// platform so templates can be shared.
+fn literal(text: &str) -> Result<String, TemplateError> {
+    match text
+        .chars()
+        .find(|&c| c.is_control() || WINDOWS_FORBIDDEN.contains(&c))
+    {
+        Some(c) => Err(TemplateError::IllegalCharacter(c)),
+        None => Ok(text.to_string()),
+    }
+}
+
+fn placeholder(inner: &str) -> Result<Part, TemplateError> {
+    let (key, argument) = match inner.split_once(':') {
+        Some((key, argument)) => (key, Some(argument)),
+        None => (inner, None),
+    };
+
+    match (key, argument) {
+        ("name", None) => Ok(Part::Name),
+        ("suffix", None) => Ok(Part::Suffix),
+        ("ext", None) => Ok(Part::Ext),
+        ("format", None) => Ok(Part::Format),
+        ("width", None) => Ok(Part::Width),
+        ("height", None) => Ok(Part::Height),
+        ("preset", None) => Ok(Part::Preset),
+        ("name1", None) => Ok(Part::NameAt(0)),
+        ("name2", None) => Ok(Part::NameAt(1)),
+        ("name3", None) => Ok(Part::NameAt(2)),
+        ("names", None) => Ok(Part::Names),
+        ("date", pattern) => {
+            let pattern = pattern.unwrap_or(DEFAULT_DATE_PATTERN);
+            // Formatting any date shows every field the pattern uses.
+            format_date(pattern, PrimitiveDateTime::MIN)?;
+            literal(pattern)?;
+            Ok(Part::Date(pattern.to_string()))
+        }
+        ("seq", argument) => {
+            let invalid = || TemplateError::InvalidSequence(inner.to_string());
+            let (width, start) = match argument.map(|argument| argument.split_once(':')) {
+                None => (DEFAULT_SEQ_WIDTH.to_string(), "1"),
+                Some(None) => (argument.unwrap_or_default().to_string(), "1"),
+                Some(Some((width, start))) => (width.to_string(), start),
+            };
+            let width = width.parse().map_err(|_| invalid())?;
+            if width > MAX_SEQ_WIDTH {
+                return Err(invalid());
+            }
+            Ok(Part::Seq {
+                width,
+                start: start.parse().map_err(|_| invalid())?,
+            })
+        }
+        _ => Err(TemplateError::UnknownPlaceholder(inner.to_string())),
+    }
+}
+
+/// `date` formatted with the `strftime` fields `NameTemplate` supports.
+fn format_date(pattern: &str, date: PrimitiveDateTime) -> Result<String, TemplateError> {
+    let mut formatted = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        let field = match chars.next() {
+            Some('Y') => format!("{:04}", date.year()),
+            Some('y') => format!("{:02}", date.year().rem_euclid(100)),
+            Some('m') => format!("{:02}", date.month() as u8),
+            Some('d') => format!("{:02}", date.day()),
+            Some('H') => format!("{:02}", date.hour()),
+            Some('M') => format!("{:02}", date.minute()),
+            Some('S') => format!("{:02}", date.second()),
+            Some('j') => format!("{:03}", date.ordinal()),
+            Some('%') => "%".to_string(),
+            Some(other) => return Err(TemplateError::InvalidDate(other)),
+            None => return Err(TemplateError::InvalidDate(' ')),
+        };
+        formatted.push_str(&field);
+    }
+    Ok(formatted)
+}
+
+/// Default pattern of `DateFolders`: a folder per year, holding one per day.
+pub const DEFAULT_DATE_FOLDERS: &str = "%Y/%Y-%m-%d";
+
+/// Where `DateFolders` puts files without any date.
+pub const UNDATED_FOLDER: &str = "undated";
+
+/// Output subfolders named after the capture date of each file, or its file
+/// date without one. The pattern uses the fields of `{date:PATTERN}` and
+/// separates nested folders with `/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateFolders {
+    levels: Vec<String>,
+}
+
+impl FromStr for DateFolders {
+    type Err = TemplateError;
+
+    fn from_str(text: &str) -> Result<Self, TemplateError> {
+        let levels = text
+            .split('/')
+            .map(|level| {
+                let formatted = format_date(level, PrimitiveDateTime::MIN)?;
+                literal(level)?;
+                match formatted.trim() {
+                    "" | "." | ".." => Err(TemplateError::EmptyFolder),
+                    _ => Ok(level.to_string()),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(DateFolders { levels })
+    }
+}
+
+impl DateFolders {
+    /// Folder of a file from `date`, relative to the output folder.
+    pub fn folder(&self, date: Option<PrimitiveDateTime>) -> PathBuf {
+        let Some(date) = date else {
+            return PathBuf::from(UNDATED_FOLDER);
+        };
+        self.levels
+            .iter()
+            .map(|level| format_date(level, date).expect("checked when parsed"))
+            .collect()
+    }
+}
+
+/// Default pattern of `RunFolder`: the start of the batch, then the preset.
+pub const DEFAULT_RUN_FOLDER: &str = "{date:%Y-%m-%d_%H%M}_{preset}";
+
+/// Pattern of a folder that holds all outputs of one batch, like
+/// `2024-06-15_1432_web/`. Only `{date}`, which is the start of the batch,
+/// and `{preset}` can be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunFolder {
+    parts: Vec<Part>,
+}
+
+impl FromStr for RunFolder {
+    type Err = TemplateError;
+
+    fn from_str(text: &str) -> Result<Self, TemplateError> {
+        let template: NameTemplate = text.parse()?;
+        for part in &template.parts {
+            let key = match part {
+                Part::Text(_) | Part::Date(_) | Part::Preset => continue,
+                Part::Name => "name",
+                Part::Suffix => "suffix",
+                Part::Ext => "ext",
+                Part::Format => "format",
+                Part::Width => "width",
+                Part::Height => "height",
+                Part::Seq { .. } => "seq",
+                Part::NameAt(0) => "name1",
+                Part::NameAt(1) => "name2",
+                Part::NameAt(_) => "name3",
+                Part::Names => "names",
+            };
+            return Err(TemplateError::NotForRunFolder(key.to_string()));
+        }
+        Ok(RunFolder {
+            parts: template.parts,
+        })
+    }
+}
+
+impl RunFolder {
+    /// Name of the folder of a batch that `started` with `preset`.
+    pub fn name(
+        &self,
+        started: PrimitiveDateTime,
+        preset: &str,
+    ) -> Result<OsString, TemplateError> {
+        let mut name = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => name.push_str(text),
+                Part::Date(pattern) => name.push_str(&format_date(pattern, started)?),
+                // Preset names may hold anything, separators included.
+                Part::Preset => name.push_str(&preset.replace(['/', '\\'], "_")),
+                _ => unreachable!("rejected when parsed"),
+            }
+        }
+        match name.trim() {
+            "" | "." | ".." => Err(TemplateError::EmptyFolder),
+            _ => Ok(sanitize_file_name(name.as_ref(), NameRules::native()).name),
+        }
+    }
+}
+
+/// How the outputs of a batch are named.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchNaming {
+    /// Text of a `NameTemplate`, checked by the pre-flight validation.
+    pub template: String,
+    /// `{suffix}` of the template.
+    pub suffix: String,
+    /// `{preset}` of files that no rule picks a preset for, when the manual
+    /// settings came from one.
+    pub manual_preset: Option<String>,
+    /// Input folder whose subfolders are recreated in the output folder.
+    /// Without it, every output goes into the output folder itself.
+    pub mirror_root: Option<PathBuf>,
+    /// Pattern of the `DateFolders` to sort the outputs into, checked by the
+    /// pre-flight validation.
+    pub date_folders: Option<String>,
+    pub overwrite: OverwritePolicy,
+    /// Copies each input next to its output as well, as it is.
+    pub originals: Option<OriginalsPlacement>,
+    /// Gives outputs and copies the modification time of their input.
+    pub preserve_timestamps: bool,
+    pub variant_folders: VariantFolders,
+    /// Pattern of the `RunFolder` that holds the batch, checked by the
+    /// pre-flight validation. Callers resolve it with `run_dir` and pass the
+    /// result as the output folder.
+    pub run_folder: Option<String>,
+    /// Writes scaled-down copies of every output for websites, listed in a
+    /// `srcset.json` in the output folder.
+    pub srcset: Option<SrcsetSettings>,
+}
+
+impl Default for BatchNaming {
+    fn default() -> Self {
+        BatchNaming {
+            template: DEFAULT_TEMPLATE.to_string(),
+            suffix: DEFAULT_SUFFIX.to_string(),
+            manual_preset: None,
+            mirror_root: None,
+            date_folders: None,
+            overwrite: OverwritePolicy::default(),
+            originals: None,
+            preserve_timestamps: false,
+            variant_folders: VariantFolders::default(),
+            run_folder: None,
+            srcset: None,
+        }
+    }
+}
+
+impl BatchNaming {
+    /// The template with the suffix filled in.
+    pub fn parse_template(&self) -> Result<NameTemplate, TemplateError> {
+        self.template
+            .parse::<NameTemplate>()?
+            .with_suffix(&self.suffix)
+    }
+
+    /// `{preset}` of a file whose settings came from `preset`, or from the
+    /// manual settings.
+    pub fn preset<'a>(&'a self, preset: Option<&'a str>) -> &'a str {
+        preset
+            .or(self.manual_preset.as_deref())
+            .unwrap_or(MANUAL_PRESET)
+    }
+
+    /// The date folders to sort the outputs into, if any.
+    pub fn parse_date_folders(&self) -> Result<Option<DateFolders>, TemplateError> {
+        self.date_folders.as_deref().map(str::parse).transpose()
+    }
+
+    /// The run folder to put the batch into, if any.
+    pub fn parse_run_folder(&self) -> Result<Option<RunFolder>, TemplateError> {
+        self.run_folder.as_deref().map(str::parse).transpose()
+    }
+
+    /// Folder in `output_dir` that holds a batch which `started` now, or
+    /// `output_dir` itself without a run folder.
+    pub fn run_dir(
+        &self,
+        output_dir: &Path,
+        started: PrimitiveDateTime,
+    ) -> Result<PathBuf, TemplateError> {
+        match self.parse_run_folder()? {
+            Some(folder) => Ok(output_dir.join(folder.name(started, self.preset(None))?)),
+            None => Ok(output_dir.to_path_buf()),
+        }
+    }
+
+    /// Subfolder of the input folder that `path` is in, when it is
+    /// recreated in the output folder.
+    pub fn folder<'a>(&self, path: &'a Path) -> &'a Path {
+        self.mirror_root
+            .as_deref()
+            .and_then(|root| path.parent()?.strip_prefix(root).ok())
+            .unwrap_or(Path::new(""))
+    }
+
+    /// Subfolder of the output folder that the output of `path` goes into:
+    /// its date folder from `date_folders`, holding its `folder`.
+    pub fn output_folder(&self, path: &Path, date_folders: Option<&DateFolders>) -> PathBuf {
+        match date_folders {
+            Some(date_folders) => date_folders
+                .folder(dates::file_date(path))
+                .join(self.folder(path)),
+            None => self.folder(path).to_path_buf(),
+        }
+    }
+}
+
+/// How one file of a batch is named.
+#[derive(Debug, Clone, Copy)]
+pub struct FileNaming<'a> {
+    pub template: &'a NameTemplate,
+    /// Position of the file in the batch, from 0.
+    pub index: usize,
+    pub preset: &'a str,
+    /// Subfolder of the output folder the file is written to, empty for the
+    /// output folder itself.
+    pub folder: &'a Path,
+    pub overwrite: OverwritePolicy,
+    pub originals: Option<OriginalsPlacement>,
+    pub preserve_timestamps: bool,
+    /// Subfolders of `folder` the output goes into after what it is.
+    pub variant_folders: VariantFolders,
+    /// Scaled-down copies written next to the output.
+    pub srcset: Option<&'a SrcsetSettings>,
+}
+
+impl<'a> FileNaming<'a> {
+    /// How the file at `index` of a batch named by `naming` is named: by
+    /// the parsed `template`, with the `preset` its settings came from,
+    /// into `folder`.
+    pub fn for_batch(
+        naming: &'a BatchNaming,
+        template: &'a NameTemplate,
+        index: usize,
+        preset: Option<&'a str>,
+        folder: &'a Path,
+    ) -> Self {
+        FileNaming {
+            template,
+            index,
+            preset: naming.preset(preset),
+            folder,
+            overwrite: naming.overwrite,
+            originals: naming.originals,
+            preserve_timestamps: naming.preserve_timestamps,
+            variant_folders: naming.variant_folders,
+            srcset: naming.srcset.as_ref(),
+        }
+    }
+
+    /// Folder of the output relative to the output folder, when it is
+    /// written in `format`, `dimensions` large, from an input with `bits`
+    /// per channel.
+    pub fn output_folder(&self, format: OutputFormat, bits: u8, dimensions: (u32, u32)) -> PathBuf {
+        let bits = if format.encoder().supports_bit_depth(bits) {
+            bits
+        } else {
+            8
+        };
+        self.folder
+            .join(self.variant_folders.folder(format, bits, dimensions))
+    }
+}
+
+/// Values of the placeholders for one file.
+#[derive(Debug, Clone)]
+pub struct NameVars<'a> {
+    pub stem: &'a OsStr,
+    pub extension: &'a str,
+    pub format: &'a str,
+    pub width: u32,
+    pub height: u32,
+    /// Files without any date get `undated`.
+    pub date: Option<PrimitiveDateTime>,
+    /// Position of the file in the batch, from 0.
+    pub index: usize,
+    pub preset: &'a str,
+    /// Names of the photos combined with this one, after it.
+    pub combined_with: &'a [&'a OsStr],
+    /// Position of a tile of a split photo, from 1, and the number of tiles.
+    pub tile: Option<(usize, usize)>,
+}
+
+impl NameTemplate {
+    /// Uses `suffix` for `{suffix}`. It may be empty, but has to be valid in
+    /// file names like the rest of the text.
+    pub fn with_suffix(self, suffix: &str) -> Result<Self, TemplateError> {
+        Ok(NameTemplate {
+            suffix: literal(suffix)?,
+            ..self
+        })
+    }
+
+    /// Whether expanding needs the date of the file, which takes reading it.
+    pub fn uses_date(&self) -> bool {
+        self.parts.iter().any(|part| matches!(part, Part::Date(_)))
+    }
+
+    /// The file name for `vars`, made valid under `rules`. Fails when
+    /// nothing but dots and spaces is left.
+    pub fn expand(&self, vars: &NameVars, rules: NameRules) -> Result<OutputName, TemplateError> {
+        let mut name = OsString::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => name.push(text),
+                Part::Name => name.push(vars.stem),
+                Part::Suffix => name.push(&self.suffix),
+                Part::Ext => name.push(vars.extension),
+                Part::Format => name.push(vars.format),
+                Part::Width => name.push(vars.width.to_string()),
+                Part::Height => name.push(vars.height.to_string()),
+                Part::Date(pattern) => match vars.date {
+                    Some(date) => name.push(format_date(pattern, date)?),
+                    None => name.push("undated"),
+                },
+                Part::Seq { width, start } => {
+                    let number = start
+                        .checked_add(vars.index as u64)
+                        .ok_or(TemplateError::SequenceOverflow)?;
+                    name.push(format!("{:0width$}", number, width = *width))
+                }
+                // A preset name may contain anything; it is sanitized below.
+                Part::Preset => name.push(vars.preset.replace(['/', '\\'], "_")),
+                Part::NameAt(0) => name.push(vars.stem),
+                Part::NameAt(index) => {
+                    if let Some(stem) = vars.combined_with.get(index - 1) {
+                        name.push(stem);
+                    }
+                }
+                Part::Names => {
+                    name.push(vars.stem);
+                    for stem in vars.combined_with {
+                        name.push("+");
+                        name.push(stem);
+                    }
+                }
+            }
+        }
+
+        if name.to_string_lossy().trim_matches(['.', ' ']).is_empty() {
+            return Err(TemplateError::EmptyName);
+        }
+        if let Some((tile, tiles)) = vars.tile {
+            let path = Path::new(&name);
+            let mut tiled = path.file_stem().unwrap_or_default().to_os_string();
+            tiled.push(format!("_{}of{}", tile, tiles));
+            if let Some(extension) = path.extension() {
+                tiled.push(".");
+                tiled.push(extension);
+            }
+            name = tiled;
+        }
+        Ok(sanitize_file_name(&name, rules))
+    }
+}
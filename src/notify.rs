@@ -0,0 +1,79 @@
+//! Desktop notifications about finished batches.
+
+use std::{path::Path, time::Duration};
+
+use image_finalizer::{i18n, t};
+use notify_rust::Notification;
+
+/// Text of the notification about a batch of `total` images that ended
+/// after `elapsed`, like "348 images done, 2 failed, 14 min".
+pub fn batch_summary(
+    completed: usize,
+    total: usize,
+    failed: usize,
+    cancelled: bool,
+    elapsed: Duration,
+) -> String {
+    let mut summary = if cancelled {
+        t!(
+            "notify-cancelled",
+            done = i18n::number(completed),
+            total = i18n::number(total)
+        )
+    } else if total == 1 {
+        t!("notify-done-one").to_string()
+    } else {
+        t!("notify-done-many", count = i18n::number(total))
+    };
+    if failed > 0 {
+        summary.push_str(", ");
+        summary.push_str(&t!("counts-failed", count = i18n::number(failed)));
+    }
+    summary.push_str(&format!(", {}", format_duration(elapsed)));
+    summary
+}
+
+/// `elapsed` to the minute, or to the second below one.
+pub fn format_duration(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    if seconds < 60 {
+        t!("duration-seconds", seconds = seconds)
+    } else if seconds < 3600 {
+        t!("duration-minutes", minutes = seconds / 60)
+    } else {
+        t!(
+            "duration-hours",
+            hours = seconds / 3600,
+            minutes = seconds % 3600 / 60
+        )
+    }
+}
+
+/// Shows `body` as a notification of the app. Where notifications can be
+/// clicked, a click opens `output_dir`; there this blocks until the
+/// notification is gone.
+pub fn batch_finished(body: &str, output_dir: &Path) -> notify_rust::error::Result<()> {
+    let mut notification = Notification::new();
+    notification.summary("Image Finalizer").body(body);
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let handle = notification
+            .action("default", t!("results-open-folder"))
+            .show()?;
+        handle.wait_for_action(|action| {
+            if action == "default" {
+                if let Err(e) = crate::reveal::open(output_dir) {
+                    tracing::warn!("could not open {}: {}", output_dir.display(), e);
+                }
+            }
+        });
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        // Windows and macOS notifications can't carry an action here.
+        let _ = output_dir;
+        notification.show()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use image::RgbaImage;
+
+use crate::{t, transform::Transform, trim::Margins};
+
+/// Longest side of `ProcessOutcome::thumbnail`.
+pub const THUMBNAIL_SIZE: u32 = 128;
+
+/// What processing one input produced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessOutcome {
+    pub input: PathBuf,
+    /// Every file written for the input, in the order they were written.
+    pub output_paths: Vec<PathBuf>,
+    /// Size of the written image after border and resize.
+    pub final_dimensions: (u32, u32),
+    /// Total size of `output_paths` on disk.
+    pub bytes_written: u64,
+    pub durations: StageTimings,
+    /// Set instead of writing anything when the input was left alone.
+    pub skipped: Option<SkipReason>,
+    /// The written image scaled down to `THUMBNAIL_SIZE`, for showing
+    /// progress. `None` when nothing was written.
+    pub thumbnail: Option<RgbaImage>,
+    /// Width and path of each scaled-down copy for websites, smallest
+    /// first. They are in `output_paths` as well.
+    pub srcset: Vec<(u32, PathBuf)>,
+    /// Turn and mirroring applied after the EXIF orientation.
+    pub transform: Transform,
+    /// Margin cut off each side before the border, as sides of the turned
+    /// photo.
+    pub trimmed: Margins,
+}
+
+impl ProcessOutcome {
+    /// The main output, if anything was written.
+    pub fn output_path(&self) -> Option<&Path> {
+        self.output_paths.first().map(PathBuf::as_path)
+    }
+
+    /// What was done to the photo besides the border, like "rotated 90°",
+    /// for listing next to the output.
+    pub fn adjustments(&self) -> Option<String> {
+        let notes: Vec<String> = [self.trimmed.describe(), self.transform.describe()]
+            .into_iter()
+            .flatten()
+            .collect();
+        (!notes.is_empty()).then(|| notes.join(", "))
+    }
+}
+
+/// How long each stage of processing one input took.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StageTimings {
+    pub decode: Duration,
+    /// Every pipeline step by name, in the order they ran.
+    pub steps: Vec<(&'static str, Duration)>,
+    pub encode: Duration,
+}
+
+impl StageTimings {
+    pub fn total(&self) -> Duration {
+        self.decode + self.steps.iter().map(|(_, took)| *took).sum::<Duration>() + self.encode
+    }
+}
+
+/// Why an input was not processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SkipReason {
+    /// The output name was taken and `OverwritePolicy::Skip` was chosen.
+    #[error("{}", t!("skip-output-exists"))]
+    OutputExists,
+}
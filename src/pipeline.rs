@@ -0,0 +1,255 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use image::{imageops, imageops::FilterType, ColorType, DynamicImage, GenericImageView, Rgba};
+
+use crate::{
+    border_geometry, corners::CornerRadius, error::ProcessError, fill::BorderFill, fit_aspect,
+    keyline::Keyline, pixel_geometry, resize_target, shadow::DropShadow, sides_geometry,
+    BorderMode, BorderSides, CanvasAspect, FramePlacement, Geometry, PixelBorder, ProcessInfo,
+};
+
+/// What a step knows about the file besides its pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct FileContext<'a> {
+    /// The input file, for error messages and file based decisions.
+    pub path: &'a Path,
+}
+
+/// One transformation of the decoded image. Steps are built from the
+/// settings up front, so `apply` only sees the pixels and the file.
+pub trait ProcessingStep: Send + Sync {
+    /// Short name used for timings and logs.
+    fn name(&self) -> &'static str;
+
+    fn apply(&self, img: DynamicImage, ctx: &FileContext) -> Result<DynamicImage, ProcessError>;
+}
+
+/// Places the photo on a canvas filled with `fill`, starting from `color`.
+#[derive(Debug, Clone)]
+pub struct Border {
+    pub mode: BorderMode,
+    pub percentage: f32,
+    /// Widths of every side, used instead of `mode` and `percentage`.
+    pub sides: Option<BorderSides>,
+    /// A width in pixels, used instead of `percentage`.
+    pub pixels: Option<PixelBorder>,
+    /// Longest side of the resized output, which `pixels` may be measured
+    /// on.
+    pub resize: Option<u32>,
+    /// Shape the canvas is padded to after the border.
+    pub aspect: Option<CanvasAspect>,
+    /// Rounds the corners of the photo before it is laid on the canvas.
+    pub corners: Option<CornerRadius>,
+    /// Cast by the photo onto the canvas, under it.
+    pub shadow: Option<DropShadow>,
+    /// Drawn around the photo, over the shadow.
+    pub keyline: Option<Keyline>,
+    pub color: Rgba<u8>,
+    pub fill: BorderFill,
+}
+
+impl Border {
+    /// The borders `info` asks for, from the photo outwards: the main
+    /// border with a frame inside or around it. The outermost one pads
+    /// the canvas to its aspect, so the frame can't spoil it.
+    pub fn layers(info: &ProcessInfo) -> Vec<Border> {
+        let mut main = Border {
+            mode: info.border_mode(),
+            percentage: info.border_percentage,
+            sides: info.border_sides,
+            pixels: info.border_pixels,
+            resize: info.resize_longest(),
+            aspect: info.canvas_aspect,
+            corners: info.corner_radius,
+            shadow: info.drop_shadow,
+            keyline: info.keyline,
+            color: info.border_color,
+            fill: info.border_fill.clone(),
+        };
+        let Some(frame) = info.frame else {
+            return vec![main];
+        };
+        let frame_layer = Border {
+            mode: BorderMode::Symmetrical,
+            percentage: 0.0,
+            sides: Some(BorderSides {
+                top: frame.width,
+                right: frame.width,
+                bottom: frame.width,
+                left: frame.width,
+            }),
+            pixels: None,
+            resize: None,
+            aspect: None,
+            corners: None,
+            shadow: None,
+            keyline: None,
+            color: frame.color,
+            fill: BorderFill::Solid,
+        };
+        match frame.placement {
+            FramePlacement::Inside => vec![frame_layer, main],
+            FramePlacement::Outside => {
+                let frame_layer = Border {
+                    aspect: main.aspect.take(),
+                    ..frame_layer
+                };
+                vec![main, frame_layer]
+            }
+        }
+    }
+
+    /// Where a `width`×`height` image goes on the canvas of this layer.
+    pub fn geometry(&self, width: u32, height: u32) -> Geometry {
+        let geometry = match (self.sides, self.pixels) {
+            (Some(sides), _) => sides_geometry(width, height, sides),
+            (None, Some(pixels)) => {
+                let pixels =
+                    pixels.source_width(width, height, self.mode, self.aspect, self.resize);
+                pixel_geometry(width, height, self.mode, pixels)
+            }
+            (None, None) => border_geometry(width, height, self.mode, self.percentage),
+        };
+        match self.aspect {
+            Some(aspect) => fit_aspect(geometry, aspect),
+            None => geometry,
+        }
+    }
+}
+
+impl ProcessingStep for Border {
+    fn name(&self) -> &'static str {
+        "border"
+    }
+
+    fn apply(&self, img: DynamicImage, _ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        let (width, height) = img.dimensions();
+        let geometry = self.geometry(width, height);
+
+        let (x, y) = (geometry.x_offset as i64, geometry.y_offset as i64);
+        let mut canvas = self.fill.canvas(
+            &img,
+            geometry.canvas_width,
+            geometry.canvas_height,
+            self.color,
+        )?;
+        let radius = match self.corners {
+            Some(corners) => corners.pixels(width, height),
+            None => 0.0,
+        };
+        let img = match self.corners {
+            Some(corners) => corners.apply(&img),
+            None => img,
+        };
+        if let Some(shadow) = self.shadow {
+            let (shadow, (dx, dy)) = shadow.cast(&img);
+            imageops::overlay(&mut canvas, &shadow, x + dx, y + dy);
+        }
+        if let Some(keyline) = self.keyline {
+            keyline.draw(&mut canvas, (x, y), (width, height), radius);
+        }
+
+        // Deep images keep their precision for the encoders that can store it.
+        if bit_depth(img.color()) > 8 {
+            let mut canvas = DynamicImage::ImageRgba8(canvas).into_rgba16();
+            imageops::overlay(&mut canvas, &img.to_rgba16(), x, y);
+            return Ok(canvas.into());
+        }
+
+        let mut canvas = DynamicImage::ImageRgba8(canvas);
+        imageops::overlay(&mut canvas, &img, x, y);
+
+        Ok(canvas)
+    }
+}
+
+/// Bits per channel of `color`.
+pub fn bit_depth(color: ColorType) -> u8 {
+    color.bytes_per_pixel() * 8 / color.channel_count()
+}
+
+/// Scales the image so its longest side is `longest_dimension`.
+#[derive(Debug, Clone, Copy)]
+pub struct Resize {
+    pub longest_dimension: u32,
+    pub filter: FilterType,
+}
+
+impl ProcessingStep for Resize {
+    fn name(&self) -> &'static str {
+        "resize"
+    }
+
+    fn apply(&self, img: DynamicImage, _ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        let (width, height) = img.dimensions();
+        let (new_width, new_height) = resize_target(width, height, self.longest_dimension);
+        Ok(img.resize(new_width, new_height, self.filter))
+    }
+}
+
+/// The steps between decoding and encoding, run in order. Shared by the
+/// preview and the batch so both always produce the same pixels.
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Box<dyn ProcessingStep>>,
+}
+
+impl Pipeline {
+    pub fn new(steps: Vec<Box<dyn ProcessingStep>>) -> Self {
+        Pipeline { steps }
+    }
+
+    /// The steps `info` asks for. The trim, the transform and the crop
+    /// come first, so the border is laid out for what is left of the
+    /// photo, turned.
+    pub fn for_info(info: &ProcessInfo) -> Self {
+        let mut steps: Vec<Box<dyn ProcessingStep>> = vec![];
+        if info.trim.enabled {
+            steps.push(Box::new(info.trim));
+        }
+        if !info.transform.is_identity() {
+            steps.push(Box::new(info.transform));
+        }
+        if !info.crop.is_identity() {
+            steps.push(Box::new(info.crop));
+        }
+        for layer in Border::layers(info) {
+            steps.push(Box::new(layer));
+        }
+        if info.resize_images {
+            steps.push(Box::new(Resize {
+                longest_dimension: info.resize_longest_dimension,
+                filter: info.resize_filter,
+            }));
+        }
+        Pipeline { steps }
+    }
+
+    /// Names of the steps in the order they run.
+    pub fn step_names(&self) -> Vec<&'static str> {
+        self.steps.iter().map(|step| step.name()).collect()
+    }
+
+    pub fn run(&self, img: DynamicImage, ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        self.run_timed(img, ctx, |_, _| {})
+    }
+
+    /// Like `run`, but reports how long every step took. Stops at the first
+    /// step that fails.
+    pub fn run_timed(
+        &self,
+        mut img: DynamicImage,
+        ctx: &FileContext,
+        mut on_step: impl FnMut(&'static str, Duration),
+    ) -> Result<DynamicImage, ProcessError> {
+        for step in &self.steps {
+            let started = Instant::now();
+            img = step.apply(img, ctx)?;
+            on_step(step.name(), started.elapsed());
+        }
+        Ok(img)
+    }
+}
@@ -0,0 +1,46 @@
+//! Tiny blurred stand-ins for outputs, for web pages to show while the
+//! real image loads. They are computed from a copy of at most `MAX_SIDE`
+//! pixels, so they cost next to nothing per file.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops::FilterType, DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// Longest side of the copy the placeholders are computed from.
+pub const MAX_SIDE: u32 = 64;
+
+/// Kind of placeholder recorded for each output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderKind {
+    /// BlurHash with 4 × 3 components, or 3 × 4 for portraits.
+    Blurhash,
+    /// ThumbHash in base64. Keeps transparency and the aspect ratio.
+    Thumbhash,
+}
+
+/// The placeholder of `kind` for `img`, which is scaled down to
+/// `MAX_SIDE` first if larger.
+pub fn placeholder(img: &RgbaImage, kind: PlaceholderKind) -> String {
+    let small = if img.width().max(img.height()) > MAX_SIDE {
+        DynamicImage::from(img.clone())
+            .resize(MAX_SIDE, MAX_SIDE, FilterType::Triangle)
+            .into_rgba8()
+    } else {
+        img.clone()
+    };
+    let (width, height) = small.dimensions();
+    match kind {
+        PlaceholderKind::Blurhash => {
+            let (x, y) = if width >= height { (4, 3) } else { (3, 4) };
+            // Only fails for component counts outside 1..=9.
+            blurhash::encode(x, y, width, height, small.as_raw()).unwrap_or_default()
+        }
+        PlaceholderKind::Thumbhash => STANDARD.encode(thumbhash::rgba_to_thumb_hash(
+            width as usize,
+            height as usize,
+            small.as_raw(),
+        )),
+    }
+}
@@ -0,0 +1,115 @@
+//! Named settings bundles stored on disk.
+
+use std::{fmt, fs, io, path::PathBuf};
+
+use crate::{t, ProcessInfo};
+
+const PRESET_EXTENSION: &str = "toml";
+
+/// Why a preset could not be read, written or renamed.
+#[derive(Debug)]
+pub enum PresetError {
+    InvalidName(String),
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetError::InvalidName(name) => {
+                write!(f, "{}", t!("preset-invalid-name", name = name))
+            }
+            PresetError::Io(e) => write!(f, "{}", e),
+            PresetError::Parse(e) => write!(f, "{}", t!("preset-read", error = e)),
+            PresetError::Serialize(e) => write!(f, "{}", t!("preset-write", error = e)),
+        }
+    }
+}
+
+impl From<io::Error> for PresetError {
+    fn from(e: io::Error) -> Self {
+        PresetError::Io(e)
+    }
+}
+
+/// Named settings bundles, stored one TOML file per preset.
+#[derive(Debug, Clone)]
+pub struct PresetStore {
+    dir: PathBuf,
+}
+
+impl PresetStore {
+    /// A store keeping its presets in `dir`, created on the first save.
+    pub fn new(dir: PathBuf) -> Self {
+        PresetStore { dir }
+    }
+
+    /// Names of all stored presets, sorted case-insensitively.
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == PRESET_EXTENSION))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        names
+    }
+
+    /// Loads a preset. Fields missing from the file keep their defaults and
+    /// fields this version does not know about are ignored, so presets written
+    /// by other versions still load.
+    pub fn load(&self, name: &str) -> Result<ProcessInfo, PresetError> {
+        let text = fs::read_to_string(self.path(name)?)?;
+        toml::from_str(&text).map_err(PresetError::Parse)
+    }
+
+    /// Writes `info` as preset `name`, replacing an existing one.
+    pub fn save(&self, name: &str, info: &ProcessInfo) -> Result<(), PresetError> {
+        let path = self.path(name)?;
+        let text = toml::to_string_pretty(info).map_err(PresetError::Serialize)?;
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Renames a preset, refusing to replace another one.
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), PresetError> {
+        let target = self.path(to)?;
+        if target.exists() {
+            return Err(PresetError::Io(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("a preset named `{}` already exists", to.trim()),
+            )));
+        }
+        fs::rename(self.path(from)?, target)?;
+        Ok(())
+    }
+
+    /// Removes a preset.
+    pub fn delete(&self, name: &str) -> Result<(), PresetError> {
+        fs::remove_file(self.path(name)?)?;
+        Ok(())
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, PresetError> {
+        let name = name.trim();
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && !name
+                .chars()
+                .any(|c| c.is_control() || r#"/\:*?"<>|"#.contains(c));
+
+        if !valid {
+            return Err(PresetError::InvalidName(name.to_string()));
+        }
+
+        Ok(self.dir.join(format!("{}.{}", name, PRESET_EXTENSION)))
+    }
+}
@@ -0,0 +1,383 @@
+//! Proof prints of finished outputs: one page per image, scaled to fit the
+//! paper inside its margins. The pages are written as a PDF, which every
+//! system can print.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::jpeg::JpegEncoder, imageops, imageops::FilterType, DynamicImage, Rgba, RgbaImage,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ProcessError, t, transform::open_oriented};
+
+/// Widest margin in millimeters, which still leaves room on A5.
+pub const MAX_MARGIN: f32 = 50.0;
+
+/// Points per millimeter, the unit of PDF pages.
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// JPEG quality of the images on the pages, high enough for a proof.
+const JPEG_QUALITY: u8 = 92;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    A3,
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+}
+
+impl PaperSize {
+    pub const ALL: [PaperSize; 5] = [
+        PaperSize::A3,
+        PaperSize::A4,
+        PaperSize::A5,
+        PaperSize::Letter,
+        PaperSize::Legal,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PaperSize::A3 => "A3",
+            PaperSize::A4 => "A4",
+            PaperSize::A5 => "A5",
+            PaperSize::Letter => t!("print-paper-letter"),
+            PaperSize::Legal => t!("print-paper-legal"),
+        }
+    }
+
+    /// Width and height in millimeters, upright.
+    pub fn millimeters(self) -> (f32, f32) {
+        match self {
+            PaperSize::A3 => (297.0, 420.0),
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::A5 => (148.0, 210.0),
+            PaperSize::Letter => (215.9, 279.4),
+            PaperSize::Legal => (215.9, 355.6),
+        }
+    }
+
+    /// Name of the size for the system print queue.
+    pub fn media(self) -> &'static str {
+        match self {
+            PaperSize::A3 => "A3",
+            PaperSize::A4 => "A4",
+            PaperSize::A5 => "A5",
+            PaperSize::Letter => "Letter",
+            PaperSize::Legal => "Legal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// How outputs are put on paper.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrintSettings {
+    pub paper: PaperSize,
+    pub orientation: Orientation,
+    /// Empty paper on every side, in millimeters up to `MAX_MARGIN`.
+    pub margin: f32,
+    /// Fills the paper inside the margins with the border color, so the
+    /// image sits centered on its own border instead of on white.
+    pub fill_border: bool,
+}
+
+impl Default for PrintSettings {
+    fn default() -> Self {
+        PrintSettings {
+            paper: PaperSize::default(),
+            orientation: Orientation::default(),
+            margin: 10.0,
+            fill_border: false,
+        }
+    }
+}
+
+/// A rectangle on the page in points, from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Where an image goes on a page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageLayout {
+    /// Size of the page in points.
+    pub width: f32,
+    pub height: f32,
+    /// The page inside the margins.
+    pub printable: PageRect,
+    /// The image, as large as the printable area allows and centered in
+    /// it.
+    pub image: PageRect,
+}
+
+impl PrintSettings {
+    /// Width and height of the page in points, turned as the orientation
+    /// says.
+    pub fn page_size(&self) -> (f32, f32) {
+        let (width, height) = self.paper.millimeters();
+        let (width, height) = match self.orientation {
+            Orientation::Portrait => (width, height),
+            Orientation::Landscape => (height, width),
+        };
+        (width * POINTS_PER_MM, height * POINTS_PER_MM)
+    }
+
+    /// Lays out a `width`×`height` image on a page.
+    pub fn layout(&self, width: u32, height: u32) -> PageLayout {
+        let (page_width, page_height) = self.page_size();
+        let margin = self.margin.clamp(0.0, MAX_MARGIN) * POINTS_PER_MM;
+        let printable = PageRect {
+            x: margin,
+            y: margin,
+            width: page_width - 2.0 * margin,
+            height: page_height - 2.0 * margin,
+        };
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        let scale = (printable.width / width).min(printable.height / height);
+        let (image_width, image_height) = (width * scale, height * scale);
+        PageLayout {
+            width: page_width,
+            height: page_height,
+            printable,
+            image: PageRect {
+                x: printable.x + (printable.width - image_width) / 2.0,
+                y: printable.y + (printable.height - image_height) / 2.0,
+                width: image_width,
+                height: image_height,
+            },
+        }
+    }
+}
+
+/// The page of `img` as it prints, `pixels_per_point` pixels per point, to
+/// show before printing.
+pub fn render_page(
+    img: &DynamicImage,
+    settings: &PrintSettings,
+    border_color: Rgba<u8>,
+    pixels_per_point: f32,
+) -> RgbaImage {
+    let layout = settings.layout(img.width(), img.height());
+    let pixels = |points: f32| (points * pixels_per_point).round().max(1.0) as u32;
+    let mut page =
+        RgbaImage::from_pixel(pixels(layout.width), pixels(layout.height), Rgba([255; 4]));
+    if settings.fill_border {
+        let fill = RgbaImage::from_pixel(
+            pixels(layout.printable.width),
+            pixels(layout.printable.height),
+            border_color,
+        );
+        let (x, y) = (layout.printable.x, layout.printable.y);
+        imageops::overlay(&mut page, &fill, pixels(x) as i64, pixels(y) as i64);
+    }
+    let image = img.resize_exact(
+        pixels(layout.image.width),
+        pixels(layout.image.height),
+        FilterType::Triangle,
+    );
+    imageops::overlay(
+        &mut page,
+        &image.to_rgba8(),
+        pixels(layout.image.x) as i64,
+        pixels(layout.image.y) as i64,
+    );
+    page
+}
+
+/// Writes a PDF to `path` with a page for each of `images`, in order.
+/// Images are embedded at their full resolution as JPEG, transparency
+/// flattened onto what is behind it on paper.
+pub fn write_pdf(
+    path: &Path,
+    images: &[PathBuf],
+    settings: &PrintSettings,
+    border_color: Rgba<u8>,
+) -> Result<(), ProcessError> {
+    let file = File::create(path).map_err(|e| ProcessError::io(path, e))?;
+    let mut pdf = PdfWriter {
+        out: BufWriter::new(file),
+        position: 0,
+        offsets: vec![],
+    };
+    let io_error = |e| ProcessError::io(path, e);
+
+    pdf.write(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n")
+        .map_err(io_error)?;
+    // The catalog and the page tree come first; every page then takes
+    // three objects: itself, its contents and its image.
+    let page = |index: usize| 3 + 3 * index;
+    pdf.object(1, b"<< /Type /Catalog /Pages 2 0 R >>")
+        .map_err(io_error)?;
+    let kids: Vec<String> = (0..images.len())
+        .map(|index| format!("{} 0 R", page(index)))
+        .collect();
+    pdf.object(
+        2,
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            images.len()
+        )
+        .as_bytes(),
+    )
+    .map_err(io_error)?;
+
+    for (index, image_path) in images.iter().enumerate() {
+        let img = open_oriented(image_path).map_err(|e| ProcessError::decode(image_path, e))?;
+        let layout = settings.layout(img.width(), img.height());
+        let behind = if settings.fill_border {
+            border_color
+        } else {
+            Rgba([255; 4])
+        };
+        let jpeg = flattened_jpeg(&img, behind).map_err(|e| ProcessError::encode(path, e))?;
+
+        let id = page(index);
+        pdf.object(
+            id,
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+                layout.width,
+                layout.height,
+                id + 2,
+                id + 1
+            )
+            .as_bytes(),
+        )
+        .map_err(io_error)?;
+        pdf.stream(
+            id + 1,
+            "",
+            contents(&layout, settings, border_color).as_bytes(),
+        )
+        .map_err(io_error)?;
+        pdf.stream(
+            id + 2,
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode ",
+                img.width(),
+                img.height()
+            ),
+            &jpeg,
+        )
+        .map_err(io_error)?;
+    }
+
+    pdf.finish().map_err(io_error)
+}
+
+/// Drawing commands of a page. PDF counts from the bottom left corner.
+fn contents(layout: &PageLayout, settings: &PrintSettings, border_color: Rgba<u8>) -> String {
+    let mut contents = String::new();
+    if settings.fill_border {
+        let [r, g, b, _] = border_color.0.map(|channel| channel as f32 / 255.0);
+        let area = layout.printable;
+        contents.push_str(&format!(
+            "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+            r,
+            g,
+            b,
+            area.x,
+            layout.height - area.y - area.height,
+            area.width,
+            area.height
+        ));
+    }
+    let image = layout.image;
+    contents.push_str(&format!(
+        "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im0 Do Q\n",
+        image.width,
+        image.height,
+        image.x,
+        layout.height - image.y - image.height
+    ));
+    contents
+}
+
+fn flattened_jpeg(img: &DynamicImage, behind: Rgba<u8>) -> image::ImageResult<Vec<u8>> {
+    let rgb = if img.color().has_alpha() {
+        let mut flat = RgbaImage::from_pixel(img.width(), img.height(), behind);
+        imageops::overlay(&mut flat, &img.to_rgba8(), 0, 0);
+        DynamicImage::ImageRgba8(flat).into_rgb8()
+    } else {
+        img.to_rgb8()
+    };
+    let mut jpeg = vec![];
+    JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY).encode_image(&rgb)?;
+    Ok(jpeg)
+}
+
+/// Writes numbered objects and remembers where each starts, for the
+/// cross-reference table at the end.
+struct PdfWriter<W: Write> {
+    out: W,
+    position: usize,
+    offsets: Vec<usize>,
+}
+
+impl<W: Write> PdfWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.out.write_all(bytes)?;
+        self.position += bytes.len();
+        Ok(())
+    }
+
+    /// Starts object `id`, which has to be the next one.
+    fn begin(&mut self, id: usize) -> io::Result<()> {
+        debug_assert_eq!(id, self.offsets.len() + 1);
+        self.offsets.push(self.position);
+        self.write(format!("{} 0 obj\n", id).as_bytes())
+    }
+
+    fn object(&mut self, id: usize, body: &[u8]) -> io::Result<()> {
+        self.begin(id)?;
+        self.write(body)?;
+        self.write(b"\nendobj\n")
+    }
+
+    /// A stream object with `entries` added to its dictionary.
+    fn stream(&mut self, id: usize, entries: &str, data: &[u8]) -> io::Result<()> {
+        self.begin(id)?;
+        self.write(format!("<< {}/Length {} >>\nstream\n", entries, data.len()).as_bytes())?;
+        self.write(data)?;
+        self.write(b"\nendstream\nendobj\n")
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        let xref = self.position;
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            table.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            xref
+        ));
+        self.write(table.as_bytes())?;
+        self.out.flush()
+    }
+}
@@ -0,0 +1,1015 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Instant, SystemTime},
+};
+
+use image::{DynamicImage, GenericImageView};
+
+use crate::{
+    combine::{self, CombineSettings},
+    crop::{Crop, CropError},
+    dates,
+    edit::ImageEdit,
+    error::ProcessError,
+    format::OutputFormat,
+    naming::{
+        self, FileNaming, NameRules, NameTemplate, NameVars, OutputName, OverwritePolicy,
+        MANUAL_PRESET, MAX_RENAME_NUMBER,
+    },
+    output_dimensions,
+    pipeline::{bit_depth, FileContext, Pipeline},
+    rules::{self, ResolvedRule},
+    split::{self, SplitSettings},
+    srcset::{self, SrcsetEntry, SrcsetSettings},
+    t,
+    transform::{open_oriented, Transform},
+    trim::{Margins, Trim},
+    validation, ProcessInfo, ProcessOutcome, SkipReason, StageTimings, THUMBNAIL_SIZE,
+};
+
+/// Extensions of the files `scan_images` picks up, in lower case.
+const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "tif"];
+
+/// Whether `path` has the extension of an image this app processes.
+pub fn is_supported_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            SUPPORTED_EXTENSIONS
+                .iter()
+                .any(|supported| ext.eq_ignore_ascii_case(supported))
+        })
+}
+
+/// The supported images directly inside `dir`, skipping folders, sorted by
+/// name so batches always run in the same order.
+#[tracing::instrument(skip_all, fields(dir = %dir.display()))]
+pub fn scan_images(dir: &Path) -> Result<Vec<PathBuf>, ProcessError> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| ProcessError::io(dir, e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .map(|entry| entry.path())
+        .filter(|path| is_supported_image(path))
+        .collect();
+    paths.sort();
+    tracing::debug!(images = paths.len(), "scanned");
+    Ok(paths)
+}
+
+/// Like `scan_images`, but also goes through every subfolder of `dir` except
+/// `skip`. Links to folders aren't followed, and subfolders that can't be
+/// read are left out with a warning.
+#[tracing::instrument(skip_all, fields(dir = %dir.display()))]
+pub fn scan_images_recursive(dir: &Path, skip: &Path) -> Result<Vec<PathBuf>, ProcessError> {
+    let skip = validation::canonical(skip);
+    let mut paths = vec![];
+    let mut folders = vec![dir.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let entries = match fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(e) if folder == dir => return Err(ProcessError::io(dir, e)),
+            Err(e) => {
+                tracing::warn!("{} is left out: {}", folder.display(), e);
+                continue;
+            }
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                if validation::canonical(&path) != skip {
+                    folders.push(path);
+                }
+            } else if is_supported_image(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    tracing::debug!(images = paths.len(), "scanned");
+    Ok(paths)
+}
+
+/// The images in `input_dir` to process into `output_dir`, including those
+/// in subfolders when `recursive` is set. When the outputs land inside the
+/// input folder, files ending in the output `suffix` are left out, so a
+/// rerun doesn't border its own earlier results.
+pub fn scan_inputs(
+    input_dir: &Path,
+    output_dir: &Path,
+    suffix: &str,
+    recursive: bool,
+) -> Result<Vec<PathBuf>, ProcessError> {
+    let mut paths = if recursive {
+        scan_images_recursive(input_dir, output_dir)?
+    } else {
+        scan_images(input_dir)?
+    };
+    if validation::output_inside_input(input_dir, output_dir) {
+        let before = paths.len();
+        paths.retain(|path| !naming::has_output_suffix(path, suffix));
+        tracing::debug!(skipped = before - paths.len(), "left out earlier outputs");
+    }
+    Ok(paths)
+}
+
+/// `paths` rearranged after `order`, an order chosen for an earlier scan.
+/// Paths that are gone since are dropped, and new ones follow at the end
+/// in the order they came.
+pub fn apply_order(paths: Vec<PathBuf>, order: &[PathBuf]) -> Vec<PathBuf> {
+    let (mut known, new): (Vec<_>, Vec<_>) =
+        paths.into_iter().partition(|path| order.contains(path));
+    known.sort_by_key(|path| order.iter().position(|ordered| ordered == path));
+    known.extend(new);
+    known
+}
+
+/// Moves the path at `from` in front of the one at `to`, or to the end
+/// when `to` is the length of `paths`.
+pub fn move_path(paths: &mut Vec<PathBuf>, from: usize, to: usize) {
+    if from >= paths.len() || to > paths.len() {
+        return;
+    }
+    let path = paths.remove(from);
+    let to = if to > from { to - 1 } else { to };
+    paths.insert(to, path);
+}
+
+/// Name of the file `image_path` is written to in the output folder. Works
+/// on names that aren't valid Unicode and changes names the platform doesn't
+/// allow.
+pub fn output_file_name(
+    image_path: &Path,
+    format: OutputFormat,
+) -> Result<OutputName, ProcessError> {
+    let stem = image_path
+        .file_stem()
+        .ok_or_else(|| ProcessError::InvalidPath {
+            path: image_path.to_path_buf(),
+        })?;
+    Ok(naming::bordered_name(
+        stem,
+        format.extension(),
+        NameRules::native(),
+    ))
+}
+
+/// Settings `choose_settings` picked for one input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChosenSettings {
+    pub info: ProcessInfo,
+    /// Where the settings came from, for the user.
+    pub applied: String,
+    /// Preset of the rule that matched, if any.
+    pub preset: Option<String>,
+}
+
+impl ChosenSettings {
+    /// The settings with what `edit` sets for the image instead of their
+    /// own, if given.
+    pub fn with_edit(mut self, edit: Option<&ImageEdit>) -> Self {
+        if let Some(edit) = edit {
+            if let Some(transform) = edit.transform {
+                self.info.transform = transform;
+            }
+            self.info.crop = edit.crop;
+        }
+        self
+    }
+}
+
+/// Settings for one input according to the preset rules, plus a description
+/// of where they came from. Falls back to `manual` when no rule matches.
+pub fn choose_settings(
+    image_path: &Path,
+    rules: &[ResolvedRule],
+    manual: ProcessInfo,
+) -> ChosenSettings {
+    let manual = |applied: &str| ChosenSettings {
+        info: manual,
+        applied: applied.to_string(),
+        preset: None,
+    };
+    if rules.is_empty() {
+        return manual(t!("applied-manual"));
+    }
+
+    match validation::read_header(image_path) {
+        Some(((width, height), _)) => match rules::matching_rule(rules, width, height) {
+            Some(rule) => ChosenSettings {
+                info: rule.info.clone(),
+                applied: t!(
+                    "applied-rule",
+                    rule = rule.description,
+                    preset = rule.preset
+                ),
+                preset: Some(rule.preset.clone()),
+            },
+            None => manual(t!("applied-manual-unmatched")),
+        },
+        None => manual(t!("applied-manual-size-unknown")),
+    }
+}
+
+/// Name of the output of `image_path` according to `naming`, once
+/// processed to `dimensions`.
+pub fn templated_file_name(
+    image_path: &Path,
+    format: OutputFormat,
+    dimensions: (u32, u32),
+    naming: &FileNaming,
+) -> Result<OutputName, ProcessError> {
+    combined_file_name(&[image_path], format, dimensions, naming)
+}
+
+/// Like `templated_file_name`, for the output of `inputs` combined. The
+/// first input stands in for the others where only one can.
+pub fn combined_file_name(
+    inputs: &[&Path],
+    format: OutputFormat,
+    dimensions: (u32, u32),
+    naming: &FileNaming,
+) -> Result<OutputName, ProcessError> {
+    file_name(inputs, None, format, dimensions, naming)
+}
+
+/// Like `combined_file_name`, for the `tile` of a split photo if set.
+fn file_name(
+    inputs: &[&Path],
+    tile: Option<(usize, usize)>,
+    format: OutputFormat,
+    dimensions: (u32, u32),
+    naming: &FileNaming,
+) -> Result<OutputName, ProcessError> {
+    let stems = inputs
+        .iter()
+        .map(|path| {
+            path.file_stem().ok_or_else(|| ProcessError::InvalidPath {
+                path: path.to_path_buf(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let (Some(&image_path), Some((stem, combined_with))) = (inputs.first(), stems.split_first())
+    else {
+        return Err(ProcessError::InvalidPath {
+            path: PathBuf::new(),
+        });
+    };
+    let encoder = format.encoder();
+    let vars = NameVars {
+        stem,
+        extension: encoder.extension(),
+        format: &encoder.name().to_lowercase(),
+        width: dimensions.0,
+        height: dimensions.1,
+        date: if naming.template.uses_date() {
+            dates::file_date(image_path)
+        } else {
+            None
+        },
+        index: naming.index,
+        preset: naming.preset,
+        combined_with,
+        tile,
+    };
+    naming
+        .template
+        .expand(&vars, NameRules::native())
+        .map_err(|source| ProcessError::Template {
+            path: image_path.to_path_buf(),
+            source,
+        })
+}
+
+/// Decodes `image_path`, adds the border and writes the result into
+/// `output_dir`, which is created when missing. Reports what was written.
+pub fn process_file(
+    image_path: &Path,
+    info: ProcessInfo,
+    output_dir: &Path,
+) -> Result<ProcessOutcome, ProcessError> {
+    let naming = FileNaming {
+        template: &NameTemplate::default(),
+        index: 0,
+        preset: MANUAL_PRESET,
+        folder: Path::new(""),
+        overwrite: OverwritePolicy::Overwrite,
+        originals: None,
+        preserve_timestamps: false,
+        variant_folders: Default::default(),
+        srcset: None,
+    };
+    process_file_named(image_path, info, output_dir, &naming)
+}
+
+/// Like `process_file`, naming the output after `naming`. The subfolder of
+/// `naming` is created in `output_dir` when missing, and a taken name is
+/// handled by the overwrite policy of `naming`.
+#[tracing::instrument(skip_all, fields(path = %image_path.display()))]
+pub fn process_file_named(
+    image_path: &Path,
+    info: ProcessInfo,
+    output_dir: &Path,
+    naming: &FileNaming,
+) -> Result<ProcessOutcome, ProcessError> {
+    // Decoding would fail on these too, but less clearly.
+    if image_path.file_stem().is_none() {
+        return Err(ProcessError::InvalidPath {
+            path: image_path.to_path_buf(),
+        });
+    }
+
+    let mut durations = StageTimings::default();
+
+    let started = Instant::now();
+    let img = open_oriented(image_path).map_err(|e| ProcessError::decode(image_path, e))?;
+    durations.decode = started.elapsed();
+
+    write_processed(
+        &[image_path],
+        None,
+        img,
+        info,
+        output_dir,
+        naming,
+        durations,
+    )
+}
+
+/// Decodes `paths`, puts them on one canvas after `combine` and writes it
+/// like `process_file_named` writes a single photo. The first path is the
+/// input the outcome reports, and whose date and timestamps are used.
+#[tracing::instrument(skip_all, fields(photos = paths.len()))]
+pub fn process_combined(
+    paths: &[PathBuf],
+    info: ProcessInfo,
+    combine: &CombineSettings,
+    output_dir: &Path,
+    naming: &FileNaming,
+) -> Result<ProcessOutcome, ProcessError> {
+    let mut durations = StageTimings::default();
+
+    let started = Instant::now();
+    let images = paths
+        .iter()
+        .map(|path| {
+            if path.file_stem().is_none() {
+                return Err(ProcessError::InvalidPath { path: path.clone() });
+            }
+            open_oriented(path).map_err(|e| ProcessError::decode(path, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    durations.decode = started.elapsed();
+
+    let started = Instant::now();
+    // Each photo is trimmed and turned before it is placed, rather than
+    // the canvas.
+    let (images, margins): (Vec<DynamicImage>, Vec<Margins>) = images
+        .into_iter()
+        .zip(paths)
+        .map(|(img, path)| {
+            prepare_photo(img, &info).map_err(|source| ProcessError::Crop {
+                path: path.clone(),
+                source,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    let img = combine::combine(&images, combine, info.border_color);
+    durations.steps.push(("combine", started.elapsed()));
+
+    let inputs: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    let mut outcome = write_processed(
+        &inputs,
+        None,
+        img,
+        prepared_info(info.clone()),
+        output_dir,
+        naming,
+        durations,
+    )?;
+    if outcome.skipped.is_none() {
+        outcome.transform = info.transform.normalized();
+        outcome.trimmed = margins[0];
+    }
+    Ok(outcome)
+}
+
+/// Decodes `image_path`, splits it into tiles after `split` and writes
+/// each like `process_file_named` writes a whole photo, numbered from the
+/// left. The outcome lists the tiles in that order and has the size of the
+/// first. The original is copied once, with the first tile.
+#[tracing::instrument(skip_all, fields(path = %image_path.display()))]
+pub fn process_split(
+    image_path: &Path,
+    info: ProcessInfo,
+    split: &SplitSettings,
+    output_dir: &Path,
+    naming: &FileNaming,
+) -> Result<ProcessOutcome, ProcessError> {
+    if image_path.file_stem().is_none() {
+        return Err(ProcessError::InvalidPath {
+            path: image_path.to_path_buf(),
+        });
+    }
+    let mut durations = StageTimings::default();
+
+    let started = Instant::now();
+    let img = open_oriented(image_path).map_err(|e| ProcessError::decode(image_path, e))?;
+    durations.decode = started.elapsed();
+
+    let started = Instant::now();
+    // The whole photo is trimmed and turned, not each tile.
+    let (img, margins) = prepare_photo(img, &info).map_err(|source| ProcessError::Crop {
+        path: image_path.to_path_buf(),
+        source,
+    })?;
+    let tiles = split::split(&img, split, info.border_color);
+    durations.steps.push(("split", started.elapsed()));
+    let upright = prepared_info(info.clone());
+
+    let count = tiles.len();
+    let mut outcome: Option<ProcessOutcome> = None;
+    for (index, tile) in tiles.into_iter().enumerate() {
+        let naming = FileNaming {
+            originals: naming.originals.filter(|_| index == 0),
+            ..*naming
+        };
+        let durations = std::mem::take(&mut durations);
+        let written = write_processed(
+            &[image_path],
+            Some((index + 1, count)),
+            tile,
+            upright.clone(),
+            output_dir,
+            &naming,
+            durations,
+        )?;
+        outcome = Some(match outcome {
+            None => written,
+            Some(mut outcome) => {
+                outcome.output_paths.extend(written.output_paths);
+                outcome.srcset.extend(written.srcset);
+                outcome.bytes_written += written.bytes_written;
+                outcome.durations.steps.extend(written.durations.steps);
+                outcome.durations.encode += written.durations.encode;
+                // Written as long as any tile was.
+                if outcome.skipped.is_some() {
+                    outcome.skipped = written.skipped;
+                    outcome.final_dimensions = written.final_dimensions;
+                    outcome.thumbnail = written.thumbnail;
+                }
+                outcome
+            }
+        });
+    }
+    let mut outcome = outcome.ok_or_else(|| ProcessError::InvalidPath {
+        path: image_path.to_path_buf(),
+    })?;
+    if outcome.skipped.is_none() {
+        outcome.transform = info.transform.normalized();
+        outcome.trimmed = margins;
+    }
+    Ok(outcome)
+}
+
+/// `img` trimmed, turned and cropped as `info` says, for the modes that
+/// do so before the pipeline, and the margins cut off, as sides of the
+/// turned photo.
+pub fn prepare_photo(
+    img: DynamicImage,
+    info: &ProcessInfo,
+) -> Result<(DynamicImage, Margins), CropError> {
+    let margins = info.trim.margins(&img);
+    Ok((
+        info.crop.apply(info.transform.apply(margins.cut(img)))?,
+        margins.transformed(&info.transform),
+    ))
+}
+
+/// `info` for photos `prepare_photo` was done with, so they are not
+/// trimmed, turned or cropped again.
+pub fn prepared_info(info: ProcessInfo) -> ProcessInfo {
+    ProcessInfo {
+        trim: Trim {
+            enabled: false,
+            ..info.trim
+        },
+        transform: Transform::default(),
+        crop: Crop::default(),
+        ..info
+    }
+}
+
+/// Runs the pipeline of `info` on `img`, decoded from `inputs`, and writes
+/// the result into `output_dir` as `naming` says, as the `tile` of a split
+/// photo if set.
+fn write_processed(
+    inputs: &[&Path],
+    tile: Option<(usize, usize)>,
+    img: DynamicImage,
+    info: ProcessInfo,
+    output_dir: &Path,
+    naming: &FileNaming,
+    mut durations: StageTimings,
+) -> Result<ProcessOutcome, ProcessError> {
+    let image_path = inputs[0];
+    // The trim step finds the same margins again; they are needed up front
+    // for the size in the name.
+    let margins = info.trim.margins(&img);
+    let (width, height) = margins.inside(img.width(), img.height());
+    let dimensions = output_dimensions(width, height, &info);
+    let bits = bit_depth(img.color());
+    let output_name = file_name(inputs, tile, info.output_format, dimensions, naming)?;
+    if output_name.mangled {
+        tracing::warn!(
+            "{} is saved as {}, a name this system allows",
+            image_path.display(),
+            Path::new(&output_name.name).display()
+        );
+    }
+    let output_folder = output_dir.join(naming.output_folder(info.output_format, bits, dimensions));
+
+    // Saves the work, but only creating the file settles it.
+    if naming.overwrite == OverwritePolicy::Skip
+        && output_path(&output_folder, &output_name.name).exists()
+    {
+        return Ok(skipped(image_path, durations));
+    }
+
+    let ctx = FileContext { path: image_path };
+    let processed = Pipeline::for_info(&info).run_timed(img, &ctx, |step, took| {
+        tracing::debug!(step, ?took, "step done");
+        durations.steps.push((step, took));
+    })?;
+
+    let started = Instant::now();
+    fs::create_dir_all(&output_folder).map_err(|e| ProcessError::io(&output_folder, e))?;
+    let intended = output_path(&output_folder, &output_name.name);
+    let Some(output_path) = write_output(
+        &output_folder,
+        &output_name.name,
+        naming.overwrite,
+        |file| encode(&processed, &info, file, &intended),
+    )?
+    else {
+        return Ok(skipped(image_path, durations));
+    };
+    durations.encode = started.elapsed();
+
+    if naming.preserve_timestamps {
+        set_modified(&output_path, modified(image_path)?)?;
+    }
+
+    let mut output_paths = vec![output_path.clone()];
+    let mut srcset = vec![];
+    if let Some(settings) = naming.srcset {
+        let started = Instant::now();
+        srcset = write_srcset(
+            &processed,
+            &info,
+            settings,
+            &output_folder,
+            &output_path,
+            naming,
+        )?;
+        output_paths.extend(srcset.iter().map(|(_, path)| path.clone()));
+        if settings.html {
+            let stem = Path::new(&output_name.name).file_stem().unwrap_or_default();
+            let entry = SrcsetEntry::new(
+                image_path,
+                &output_path,
+                processed.dimensions(),
+                &srcset,
+                &output_folder,
+            );
+            let html = write_output(
+                &output_folder,
+                &settings.html_name(stem),
+                naming.overwrite,
+                |file| {
+                    file.write_all(entry.html().as_bytes())
+                        .map_err(|e| ProcessError::io(&output_path, e))
+                },
+            )?;
+            output_paths.extend(html);
+        }
+        durations.steps.push(("srcset", started.elapsed()));
+    }
+
+    // Only after the output is written, so failures leave no lone copies.
+    if let Some(placement) = naming.originals {
+        let folder = placement.folder(&output_dir.join(naming.folder));
+        for &input in inputs {
+            if let Some(copy_path) = copy_original(input, &folder, naming.overwrite)? {
+                if naming.preserve_timestamps {
+                    set_modified(&copy_path, modified(input)?)?;
+                }
+                output_paths.push(copy_path);
+            }
+        }
+    }
+
+    let mut bytes_written = 0;
+    for path in &output_paths {
+        bytes_written += fs::metadata(path)
+            .map_err(|e| ProcessError::io(path, e))?
+            .len();
+    }
+
+    Ok(ProcessOutcome {
+        input: image_path.to_path_buf(),
+        output_paths,
+        final_dimensions: processed.dimensions(),
+        bytes_written,
+        durations,
+        skipped: None,
+        thumbnail: Some(
+            processed
+                .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+                .into_rgba8(),
+        ),
+        srcset,
+        transform: info.transform.normalized(),
+        trimmed: margins.transformed(&info.transform),
+    })
+}
+
+/// Writes the copies of `processed`, written to `output_path`, that
+/// `settings` asks for into `output_folder`. Returns the width and path of
+/// each, leaving out those skipped for taken names.
+fn write_srcset(
+    processed: &DynamicImage,
+    info: &ProcessInfo,
+    settings: &SrcsetSettings,
+    output_folder: &Path,
+    output_path: &Path,
+    naming: &FileNaming,
+) -> Result<Vec<(u32, PathBuf)>, ProcessError> {
+    let stem = output_path.file_stem().unwrap_or_default();
+    let mut copies = vec![];
+    for width in settings.widths_below(processed.width()) {
+        let copy = srcset::scaled(processed, width, info.resize_filter);
+        let name = settings.copy_name(stem, width);
+        let path = output_folder.join(&name);
+        let written = write_output(output_folder, &name, naming.overwrite, |file| {
+            let mut writer = io::BufWriter::new(file);
+            srcset::encode_webp(&copy, settings.quality, &mut writer)
+                .map_err(|e| ProcessError::encode(&path, e))?;
+            writer.flush().map_err(|e| ProcessError::io(&path, e))
+        })?;
+        if let Some(written) = written {
+            if naming.preserve_timestamps {
+                set_modified(&written, modified(output_path)?)?;
+            }
+            copies.push((width, written));
+        }
+    }
+    Ok(copies)
+}
+
+/// Path of the output `name` in `folder`. Returned to callers that may
+/// hand it to other programs, so it is usable even when long.
+fn output_path(folder: &Path, name: &OsStr) -> PathBuf {
+    naming::extended_length_path(&folder.join(name), NameRules::native())
+}
+
+fn skipped(image_path: &Path, durations: StageTimings) -> ProcessOutcome {
+    ProcessOutcome {
+        input: image_path.to_path_buf(),
+        output_paths: vec![],
+        final_dimensions: (0, 0),
+        bytes_written: 0,
+        durations,
+        skipped: Some(SkipReason::OutputExists),
+        thumbnail: None,
+        srcset: vec![],
+        transform: Transform::default(),
+        trimmed: Margins::default(),
+    }
+}
+
+/// Writes the output `name` in `folder` with `write`, which is handed a
+/// temporary file in `folder`. Only once it is written and synced is it
+/// moved to `name`, so a crash never leaves a partial output behind. A
+/// taken name is handled by `policy`: replaced, numbered or skipped. Names
+/// are claimed by linking, so concurrent batch threads never pick the same
+/// one. Returns where the output went, or `None` when it was skipped.
+pub(crate) fn write_output(
+    folder: &Path,
+    name: &OsStr,
+    policy: OverwritePolicy,
+    write: impl FnOnce(&mut fs::File) -> Result<(), ProcessError>,
+) -> Result<Option<PathBuf>, ProcessError> {
+    remove_stale_temp_files(folder);
+    let (temp_path, mut file) = create_temp(folder, name)?;
+    let written = write(&mut file).and_then(|()| {
+        file.sync_all()
+            .map_err(|e| ProcessError::io(output_path(folder, name), e))
+    });
+    drop(file);
+    let placed = written.and_then(|()| place(&temp_path, folder, name, policy));
+    if !matches!(placed, Ok(Some(_))) || policy != OverwritePolicy::Overwrite {
+        // Already gone after a rename; a link leaves it behind.
+        let _ = fs::remove_file(&temp_path);
+    }
+    placed
+}
+
+/// Marks temporary outputs: `.<name>.tmp-<pid>`, with `-<n>` added when
+/// two threads of one run write the same name.
+const TEMP_MARKER: &str = ".tmp-";
+
+/// Longest part of an output's name kept in the name of its temporary
+/// file, so the marker still fits within the limits of file systems.
+const TEMP_NAME_CHARS: usize = 100;
+
+fn create_temp(folder: &Path, name: &OsStr) -> Result<(PathBuf, fs::File), ProcessError> {
+    let name: String = name
+        .to_string_lossy()
+        .chars()
+        .take(TEMP_NAME_CHARS)
+        .collect();
+    let pid = std::process::id();
+    for attempt in 0..=MAX_RENAME_NUMBER {
+        let temp_name = match attempt {
+            0 => format!(".{}{}{}", name, TEMP_MARKER, pid),
+            n => format!(".{}{}{}-{}", name, TEMP_MARKER, pid, n),
+        };
+        let path = output_path(folder, temp_name.as_ref());
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(ProcessError::io(&path, e)),
+        }
+    }
+    let path = output_path(folder, name.as_ref());
+    Err(ProcessError::io(
+        &path,
+        io::Error::new(io::ErrorKind::AlreadyExists, "no temporary name is free"),
+    ))
+}
+
+/// Moves the written `temp_path` to `name` in `folder` as `policy` says.
+fn place(
+    temp_path: &Path,
+    folder: &Path,
+    name: &OsStr,
+    policy: OverwritePolicy,
+) -> Result<Option<PathBuf>, ProcessError> {
+    match policy {
+        OverwritePolicy::Overwrite => {
+            let path = output_path(folder, name);
+            replace(temp_path, &path).map_err(|e| ProcessError::io(&path, e))?;
+            Ok(Some(path))
+        }
+        OverwritePolicy::Skip => {
+            let path = output_path(folder, name);
+            match link_new(temp_path, &path) {
+                Ok(()) => Ok(Some(path)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(None),
+                Err(e) => Err(ProcessError::io(&path, e)),
+            }
+        }
+        OverwritePolicy::Rename => {
+            for number in 0..=MAX_RENAME_NUMBER {
+                let path = if number == 0 {
+                    output_path(folder, name)
+                } else {
+                    output_path(folder, &naming::numbered_name(name, number))
+                };
+                match link_new(temp_path, &path) {
+                    Ok(()) => {
+                        if number > 0 {
+                            tracing::info!(
+                                "{} exists already, saving as {}",
+                                Path::new(name).display(),
+                                path.display()
+                            );
+                        }
+                        return Ok(Some(path));
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                    Err(e) => return Err(ProcessError::io(&path, e)),
+                }
+            }
+            let path = output_path(folder, name);
+            Err(ProcessError::io(
+                &path,
+                io::Error::new(io::ErrorKind::AlreadyExists, "every numbered name is taken"),
+            ))
+        }
+    }
+}
+
+/// Renames `from` over `to`. Windows refuses to replace a file that is
+/// read-only or held open without delete sharing, so there the old file is
+/// removed first when the rename fails.
+fn replace(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if cfg!(windows) && to.exists() => {
+            tracing::debug!(
+                "replacing {} failed ({}), removing it first",
+                to.display(),
+                e
+            );
+            fs::remove_file(to)?;
+            fs::rename(from, to)
+        }
+        result => result,
+    }
+}
+
+/// Gives the file at `from` the new name `to`, failing with `AlreadyExists`
+/// when `to` is taken. File systems without hard links get an empty file
+/// claiming the name, which the rename then replaces.
+fn link_new(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::hard_link(from, to) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => {
+            tracing::debug!("linking {} failed ({}), renaming it", to.display(), e);
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(to)?;
+            replace(from, to)
+        }
+        result => result,
+    }
+}
+
+/// Folders the current batch already cleared of stale temporary outputs.
+static SWEPT_FOLDERS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Lets the next write to every folder look for stale temporary outputs
+/// again, at the start of a batch.
+pub(crate) fn forget_swept_folders() {
+    SWEPT_FOLDERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
+/// Removes the temporary outputs that runs which crashed or were killed
+/// left in `folder`, once per batch. Those of runs still going, like a
+/// second instance writing to the same folder, are left alone.
+fn remove_stale_temp_files(folder: &Path) {
+    {
+        let mut swept = SWEPT_FOLDERS.lock().unwrap_or_else(|e| e.into_inner());
+        if swept.iter().any(|done| done == folder) {
+            return;
+        }
+        swept.push(folder.to_path_buf());
+    }
+    let Ok(entries) = fs::read_dir(folder) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if !entry.file_type().is_ok_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let Some(pid) = temp_pid(&entry.file_name()) else {
+            continue;
+        };
+        if is_running(pid) {
+            continue;
+        }
+        let path = entry.path();
+        match fs::remove_file(&path) {
+            Ok(()) => tracing::info!("removed the stale {}", path.display()),
+            Err(e) => tracing::warn!("could not remove {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Whether `name` is that of a temporary output.
+pub(crate) fn is_temp_name(name: &OsStr) -> bool {
+    temp_pid(name).is_some()
+}
+
+/// The process that wrote the temporary output `name`, or `None` if it
+/// isn't one.
+fn temp_pid(name: &OsStr) -> Option<u32> {
+    let name = name.to_string_lossy();
+    let (_, tail) = name
+        .strip_prefix('.')
+        .and_then(|rest| rest.rsplit_once(TEMP_MARKER))?;
+    let (pid, attempt) = tail.split_once('-').unwrap_or((tail, "0"));
+    let is_number = |number: &str| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(pid) || !is_number(attempt) {
+        return None;
+    }
+    // Too long for a pid, so none running.
+    Some(pid.parse().unwrap_or(u32::MAX))
+}
+
+/// Whether the process `pid` exists, which also holds for those of other
+/// users.
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    // 0 and negative numbers would stand for process groups.
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // Signal 0 only checks that the process could be signalled.
+    // SAFETY: no signal is sent and no memory is passed.
+    let signalled = unsafe { libc::kill(pid, 0) } == 0;
+    signalled || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Whether the process `pid` exists, which also holds for those of other
+/// users.
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, GetLastError, ERROR_ACCESS_DENIED, STILL_ACTIVE},
+        System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+    };
+    // SAFETY: the handle is checked before use and closed once done.
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        let mut code = 0;
+        let running = GetExitCodeProcess(process, &mut code) != 0 && code == STILL_ACTIVE as u32;
+        CloseHandle(process);
+        running
+    }
+}
+
+/// Other systems can't tell, so every temporary output may still be
+/// written.
+#[cfg(not(any(unix, windows)))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
+
+/// Copies `image_path` as it is into `folder`, following `policy` when its
+/// name is taken there. Returns where the copy went, or `None` when it was
+/// skipped, including when `folder` holds the input itself.
+fn copy_original(
+    image_path: &Path,
+    folder: &Path,
+    policy: OverwritePolicy,
+) -> Result<Option<PathBuf>, ProcessError> {
+    let Some(name) = image_path.file_name() else {
+        return Ok(None);
+    };
+    // Replacing the input with its own copy gains nothing.
+    if validation::canonical(&folder.join(name)) == validation::canonical(image_path) {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(folder).map_err(|e| ProcessError::io(folder, e))?;
+    let mut source = fs::File::open(image_path).map_err(|e| ProcessError::io(image_path, e))?;
+    write_output(folder, name, policy, |file| {
+        io::copy(&mut source, file)
+            .map(|_| ())
+            .map_err(|e| ProcessError::io(output_path(folder, name), e))
+    })
+}
+
+/// When the file at `path` was last modified.
+fn modified(path: &Path) -> Result<SystemTime, ProcessError> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| ProcessError::io(path, e))
+}
+
+/// Sets the modification time of the file at `path`.
+fn set_modified(path: &Path, modified: SystemTime) -> Result<(), ProcessError> {
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(modified))
+        .map_err(|e| ProcessError::io(path, e))
+}
+
+/// Writes `img` to `file`, the output at `output_path`, in the output
+/// format of `info`.
+fn encode(
+    img: &DynamicImage,
+    info: &ProcessInfo,
+    file: &mut fs::File,
+    output_path: &Path,
+) -> Result<(), ProcessError> {
+    let mut writer = io::BufWriter::new(file);
+    info.output_format
+        .encoder()
+        .encode(img, &mut writer, &info.format_settings)
+        .map_err(|e| ProcessError::encode(output_path, e))?;
+    // Dropping the writer would ignore a failure to write the rest.
+    writer.flush().map_err(|e| ProcessError::io(output_path, e))
+}
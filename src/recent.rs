@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// How many folder pairs are remembered.
+const LIMIT: usize = 10;
+
+/// An input folder and the output folder last used with it, which is empty
+/// until one was chosen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderPair {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+impl FolderPair {
+    /// Whether the input folder is still there. Missing output folders are
+    /// created by a batch, so they don't count.
+    pub fn exists(&self) -> bool {
+        self.input.is_dir()
+    }
+}
+
+/// The most recently used folder pairs, newest first. Each input folder is
+/// listed once, with the output folder it was last used with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentFolders(Vec<FolderPair>);
+
+impl RecentFolders {
+    /// Moves the pair of `input` to the top with `output`, dropping the
+    /// oldest pair once there are too many.
+    pub fn record(&mut self, input: &Path, output: &Path) {
+        if input.as_os_str().is_empty() {
+            return;
+        }
+        self.0.retain(|pair| pair.input != input);
+        self.0.insert(
+            0,
+            FolderPair {
+                input: input.to_path_buf(),
+                output: output.to_path_buf(),
+            },
+        );
+        self.0.truncate(LIMIT);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.0.len() {
+            self.0.remove(index);
+        }
+    }
+
+    pub fn pairs(&self) -> &[FolderPair] {
+        &self.0
+    }
+}
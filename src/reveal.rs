@@ -0,0 +1,123 @@
+use std::{
+    io,
+    path::Path,
+    process::{Command, ExitStatus},
+};
+
+/// Opens `path` with the default application for it: a viewer for an
+/// image, the file manager for a folder. Blocks until the launcher exits.
+pub fn open(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        // Explorer exits with 1 even when it succeeds.
+        Command::new("explorer").arg(path).status().map(|_| ())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        check(Command::new("open").arg(path).status()?)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        check(Command::new("xdg-open").arg(path).status()?)
+    }
+}
+
+/// Shows `path` selected in the system file manager. Where the file
+/// manager can't select files, its folder is opened instead.
+pub fn reveal(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // Explorer parses "/select," itself, so the path is quoted by hand.
+        Command::new("explorer")
+            .raw_arg(format!("/select,\"{}\"", path.display()))
+            .status()
+            .map(|_| ())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        check(Command::new("open").arg("-R").arg(path).status()?)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let shown = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--type=method_call",
+                "--dest=org.freedesktop.FileManager1",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", file_uri(path)))
+            .arg("string:")
+            .status()
+            .is_ok_and(|status| status.success());
+        if shown {
+            return Ok(());
+        }
+        match path.parent() {
+            Some(parent) => open(parent),
+            None => open(path),
+        }
+    }
+}
+
+/// Sends the PDF at `path` to the printer. Windows hands it to the print
+/// command of the PDF viewer; elsewhere it goes to the default CUPS queue
+/// on `media` paper. Blocks until the command exits.
+pub fn print(path: &Path, media: &str) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = media;
+        let path = path.display().to_string().replace('\'', "''");
+        check(
+            Command::new("powershell")
+                .args(["-NoProfile", "-NonInteractive", "-Command"])
+                .arg(format!("Start-Process -FilePath '{}' -Verb Print", path))
+                .status()?,
+        )
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = Command::new("lp")
+            .args(["-o", &format!("media={}", media)])
+            .arg(path)
+            .output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            // lp says why, like that there is no default printer.
+            let error = String::from_utf8_lossy(&output.stderr);
+            Err(io::Error::other(error.trim().to_string()))
+        }
+    }
+}
+
+fn check(status: ExitStatus) -> io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "the launcher failed with {}",
+            status
+        )))
+    }
+}
+
+/// `file://` URI of an absolute `path`, with everything but unreserved
+/// characters and separators percent-encoded byte by byte.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn file_uri(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
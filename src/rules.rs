@@ -0,0 +1,123 @@
+//! Per-image preset selection.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{presets::PresetStore, t, ProcessInfo};
+
+/// Picks a preset per image from its orientation or size. The first matching
+/// rule wins; images no rule matches use the manual settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSet {
+    pub enabled: bool,
+    /// Evaluated in order.
+    pub rules: Vec<Rule>,
+}
+
+/// Uses `preset` for images matching `condition`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub condition: RuleCondition,
+    pub preset: String,
+}
+
+/// Test on the dimensions of an input image.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleCondition {
+    Portrait,
+    Landscape,
+    Square,
+    LongestSideBelow { pixels: u32 },
+    LongestSideAtLeast { pixels: u32 },
+}
+
+impl RuleCondition {
+    /// Whether an image of `width`×`height` satisfies the condition.
+    pub fn matches(self, width: u32, height: u32) -> bool {
+        match self {
+            RuleCondition::Portrait => height > width,
+            RuleCondition::Landscape => width > height,
+            RuleCondition::Square => width == height,
+            RuleCondition::LongestSideBelow { pixels } => width.max(height) < pixels,
+            RuleCondition::LongestSideAtLeast { pixels } => width.max(height) >= pixels,
+        }
+    }
+
+    /// Name of the condition without its parameters.
+    pub fn label(self) -> &'static str {
+        match self {
+            RuleCondition::Portrait => t!("rule-portrait"),
+            RuleCondition::Landscape => t!("rule-landscape"),
+            RuleCondition::Square => t!("rule-square"),
+            RuleCondition::LongestSideBelow { .. } => t!("rule-longest-below"),
+            RuleCondition::LongestSideAtLeast { .. } => t!("rule-longest-at-least"),
+        }
+    }
+}
+
+impl fmt::Display for RuleCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleCondition::LongestSideBelow { pixels }
+            | RuleCondition::LongestSideAtLeast { pixels } => {
+                write!(f, "{} {} px", self.label(), pixels)
+            }
+            _ => write!(f, "{}", self.label()),
+        }
+    }
+}
+
+/// A rule whose preset has been loaded, ready to be evaluated off-thread.
+#[derive(Debug, Clone)]
+pub struct ResolvedRule {
+    pub description: String,
+    pub condition: RuleCondition,
+    pub preset: String,
+    pub info: ProcessInfo,
+}
+
+impl RuleSet {
+    /// Loads the preset of every rule. Rules whose preset cannot be loaded are
+    /// skipped and reported in the returned error list.
+    pub fn resolve(&self, store: &PresetStore) -> (Vec<ResolvedRule>, Vec<String>) {
+        if !self.enabled {
+            return (vec![], vec![]);
+        }
+
+        let mut resolved = vec![];
+        let mut errors = vec![];
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            match store.load(&rule.preset) {
+                Ok(info) => resolved.push(ResolvedRule {
+                    description: t!(
+                        "rule-description",
+                        number = index + 1,
+                        condition = rule.condition
+                    ),
+                    condition: rule.condition,
+                    preset: rule.preset.clone(),
+                    info,
+                }),
+                Err(e) => errors.push(t!(
+                    "rule-skipped",
+                    number = index + 1,
+                    preset = rule.preset,
+                    error = e
+                )),
+            }
+        }
+
+        (resolved, errors)
+    }
+}
+
+/// The first rule matching an image of `width`×`height`.
+pub fn matching_rule(rules: &[ResolvedRule], width: u32, height: u32) -> Option<&ResolvedRule> {
+    rules
+        .iter()
+        .find(|rule| rule.condition.matches(width, height))
+}
@@ -0,0 +1,98 @@
+use image_finalizer::{
+    rules::{Rule, RuleCondition, RuleSet},
+    t,
+};
+
+use crate::a11y;
+
+/// Editor for the preset rules.
+pub fn show(rule_set: &mut RuleSet, ui: &mut egui::Ui, presets: &[String]) {
+    ui.checkbox(&mut rule_set.enabled, t!("rules-enabled"));
+    if !rule_set.enabled {
+        return;
+    }
+
+    let mut remove = None;
+
+    egui::Grid::new("preset_rules")
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, rule) in rule_set.rules.iter_mut().enumerate() {
+                ui.label(format!("{}.", index + 1));
+
+                let number = index + 1;
+                a11y::named(
+                    egui::ComboBox::from_id_salt(("rule_condition", index))
+                        .selected_text(rule.condition.label())
+                        .show_ui(ui, |ui| {
+                            let pixels = match rule.condition {
+                                RuleCondition::LongestSideBelow { pixels }
+                                | RuleCondition::LongestSideAtLeast { pixels } => pixels,
+                                _ => 1500,
+                            };
+                            for condition in [
+                                RuleCondition::Portrait,
+                                RuleCondition::Landscape,
+                                RuleCondition::Square,
+                                RuleCondition::LongestSideBelow { pixels },
+                                RuleCondition::LongestSideAtLeast { pixels },
+                            ] {
+                                ui.selectable_value(
+                                    &mut rule.condition,
+                                    condition,
+                                    condition.label(),
+                                );
+                            }
+                        })
+                        .response,
+                    &t!("rule-condition-name", number = number),
+                );
+
+                match &mut rule.condition {
+                    RuleCondition::LongestSideBelow { pixels }
+                    | RuleCondition::LongestSideAtLeast { pixels } => {
+                        a11y::named(
+                            ui.add(egui::DragValue::new(pixels).range(1..=65535).suffix(" px")),
+                            &t!("rule-pixels-name", number = number),
+                        );
+                    }
+                    _ => {
+                        ui.label("");
+                    }
+                }
+
+                ui.label("→");
+                a11y::named(
+                    egui::ComboBox::from_id_salt(("rule_preset", index))
+                        .selected_text(if rule.preset.is_empty() {
+                            t!("preset-select")
+                        } else {
+                            &rule.preset
+                        })
+                        .show_ui(ui, |ui| {
+                            for name in presets {
+                                ui.selectable_value(&mut rule.preset, name.clone(), name);
+                            }
+                        })
+                        .response,
+                    &t!("rule-preset-name", number = number),
+                );
+
+                if ui.button(t!("rules-remove")).clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(index) = remove {
+        rule_set.rules.remove(index);
+    }
+
+    if ui.button(t!("rules-add")).clicked() {
+        rule_set.rules.push(Rule {
+            condition: RuleCondition::Landscape,
+            preset: presets.first().cloned().unwrap_or_default(),
+        });
+    }
+}
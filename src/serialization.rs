@@ -0,0 +1,68 @@
+//! Serde adapters for foreign types stored in settings files.
+
+/// Serializes `image::imageops::FilterType` by its lowercase name.
+pub mod filter_type {
+    use image::imageops::FilterType;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    /// Writes the lowercase filter name.
+    pub fn serialize<S: Serializer>(filter: &FilterType, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match filter {
+            FilterType::Nearest => "nearest",
+            FilterType::Triangle => "triangle",
+            FilterType::CatmullRom => "catmullrom",
+            FilterType::Gaussian => "gaussian",
+            FilterType::Lanczos3 => "lanczos3",
+        })
+    }
+
+    /// Reads a filter name, see [`from_name`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<FilterType, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        from_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown resize filter `{}`", name)))
+    }
+
+    /// Parses a filter name case-insensitively.
+    pub fn from_name(name: &str) -> Option<FilterType> {
+        match name.to_lowercase().as_str() {
+            "nearest" => Some(FilterType::Nearest),
+            "triangle" => Some(FilterType::Triangle),
+            "catmullrom" => Some(FilterType::CatmullRom),
+            "gaussian" => Some(FilterType::Gaussian),
+            "lanczos3" => Some(FilterType::Lanczos3),
+            _ => None,
+        }
+    }
+}
+
+/// Serializes `image::Rgba<u8>` as a `#RRGGBBAA` hex string.
+pub mod hex_color {
+    use image::Rgba;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    /// Writes the color as `#RRGGBBAA`.
+    pub fn serialize<S: Serializer>(color: &Rgba<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        let [r, g, b, a] = color.0;
+        serializer.serialize_str(&format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a))
+    }
+
+    /// Reads a color, see [`parse`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rgba<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).ok_or_else(|| D::Error::custom(format!("invalid color `{}`", text)))
+    }
+
+    /// Parses `#RRGGBB` or `#RRGGBBAA`; the leading `#` is optional.
+    pub fn parse(text: &str) -> Option<Rgba<u8>> {
+        let hex = text.trim().trim_start_matches('#');
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok();
+        let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+
+        Some(Rgba([channel(0)?, channel(1)?, channel(2)?, alpha]))
+    }
+}
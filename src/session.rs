@@ -0,0 +1,78 @@
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use image_finalizer::{edit::ImageEdit, ProcessInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+/// Snapshot of the running app, written periodically and removed on a clean
+/// exit. Finding one at startup means the previous session crashed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub settings: ProcessInfo,
+    pub input_dir: PathBuf,
+    pub output_dir: PathBuf,
+    /// What was set for single images, by path.
+    #[serde(default)]
+    pub edits: HashMap<PathBuf, ImageEdit>,
+    /// The batch that was running, if any.
+    pub batch: Option<BatchState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchState {
+    pub files: Vec<PathBuf>,
+    pub completed: Vec<PathBuf>,
+    /// Folder the batch writes into, which a run folder makes differ from
+    /// the output folder of the session.
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+}
+
+impl BatchState {
+    /// Files of the batch that were not processed yet.
+    pub fn remaining(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|file| !self.completed.contains(file))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Session {
+    fn path() -> PathBuf {
+        storage::config_dir().join("session.json")
+    }
+
+    /// The session left behind by a previous run, if it didn't exit cleanly.
+    pub fn load_stale() -> Option<Session> {
+        let text = fs::read_to_string(Session::path()).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Writes the session through a temporary file so a crash mid-write
+    /// never leaves a truncated session behind.
+    pub fn save(&self) -> io::Result<()> {
+        let path = Session::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, json)?;
+        fs::rename(&temp, &path)
+    }
+
+    /// Removes the session file and any interrupted write of it.
+    pub fn remove() {
+        let path = Session::path();
+        for path in [path.with_extension("json.tmp"), path] {
+            if path.exists() {
+                if let Err(e) = fs::remove_file(&path) {
+                    tracing::warn!("could not remove {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
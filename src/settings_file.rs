@@ -0,0 +1,168 @@
+//! Versioned JSON export of the settings.
+
+use std::{
+    fmt, fs, io,
+    path::{self, Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{fill::BorderFill, hook::HookSettings, t, webhook::WebhookSettings, ProcessInfo};
+
+/// Marker identifying a settings export among other JSON files.
+const SETTINGS_KIND: &str = "image-finalizer-settings";
+/// Bumped whenever the export layout changes incompatibly.
+const SETTINGS_VERSION: u32 = 1;
+
+/// Self-describing settings export meant to be shared between machines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsFile {
+    pub kind: String,
+    pub version: u32,
+    /// Set along with `hook`, so anyone opening the file sees right at the
+    /// top that importing it can run a command.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub contains_command: bool,
+    /// Version of the app that wrote the file, for troubleshooting.
+    pub app_version: String,
+    pub settings: ProcessInfo,
+    /// Command run for every output. Never applied without asking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook: Option<HookSettings>,
+    /// Where finished batches are reported to. Only exported when asked
+    /// for, as the URL of a webhook is often all it takes to post to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookSettings>,
+}
+
+/// Why a settings export could not be written or read.
+#[derive(Debug)]
+pub enum SettingsFileError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    NotSettings,
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SettingsFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsFileError::Io(e) => write!(f, "{}", e),
+            SettingsFileError::Json(e) => write!(f, "{}", t!("settings-invalid", error = e)),
+            SettingsFileError::NotSettings => write!(f, "{}", t!("settings-not-settings")),
+            SettingsFileError::UnsupportedVersion(version) => write!(
+                f,
+                "{}",
+                t!(
+                    "settings-unsupported-version",
+                    version = version,
+                    supported = SETTINGS_VERSION
+                )
+            ),
+        }
+    }
+}
+
+impl SettingsFile {
+    /// Wraps `settings` for export by this version of the app.
+    pub fn new(settings: ProcessInfo) -> Self {
+        SettingsFile {
+            kind: SETTINGS_KIND.to_string(),
+            version: SETTINGS_VERSION,
+            contains_command: false,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            settings,
+            hook: None,
+            webhook: None,
+        }
+    }
+
+    /// Adds the hook command to the export, flagging that it has one.
+    pub fn with_hook(mut self, hook: Option<HookSettings>) -> Self {
+        self.contains_command = hook.is_some();
+        self.hook = hook;
+        self
+    }
+
+    /// Adds the webhook to the export.
+    pub fn with_webhook(mut self, webhook: Option<WebhookSettings>) -> Self {
+        self.webhook = webhook;
+        self
+    }
+
+    /// Writes the export as pretty-printed JSON. Files it refers to, like
+    /// a border texture, are stored relative to `path` where they can be,
+    /// so they are found again next to a copy of the export.
+    pub fn write(&self, path: &Path) -> Result<(), SettingsFileError> {
+        let mut file = self.clone();
+        if let (BorderFill::Texture(texture), Some(folder)) =
+            (&mut file.settings.border_fill, path.parent())
+        {
+            if let Some(relative) = relative_to(&texture.path, folder) {
+                texture.path = relative;
+            }
+        }
+        let json = serde_json::to_string_pretty(&file).map_err(SettingsFileError::Json)?;
+        fs::write(path, json).map_err(SettingsFileError::Io)
+    }
+
+    /// Reads and validates an export before any of it is applied.
+    pub fn read(path: &Path) -> Result<SettingsFile, SettingsFileError> {
+        let text = fs::read_to_string(path).map_err(SettingsFileError::Io)?;
+        let value: serde_json::Value =
+            serde_json::from_str(&text).map_err(SettingsFileError::Json)?;
+
+        if value.get("kind").and_then(|kind| kind.as_str()) != Some(SETTINGS_KIND) {
+            return Err(SettingsFileError::NotSettings);
+        }
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        if version > SETTINGS_VERSION {
+            return Err(SettingsFileError::UnsupportedVersion(version));
+        }
+
+        let mut file: SettingsFile =
+            serde_json::from_value(value).map_err(SettingsFileError::Json)?;
+        // The flag alone is no promise.
+        file.contains_command = file.hook.is_some();
+        if let (BorderFill::Texture(texture), Some(folder)) =
+            (&mut file.settings.border_fill, path.parent())
+        {
+            if texture.path.is_relative() {
+                texture.path = folder.join(&texture.path);
+            }
+        }
+        Ok(file)
+    }
+
+    /// Files the settings refer to that aren't there, like a texture that
+    /// stayed on another machine.
+    pub fn missing_files(&self) -> Vec<&Path> {
+        match &self.settings.border_fill {
+            BorderFill::Texture(texture) if !texture.path.is_file() => vec![&texture.path],
+            _ => vec![],
+        }
+    }
+}
+
+/// `path` relative to the folder `base`, going up with `..` where needed.
+/// `None` when the two have nothing but the root in common, like paths
+/// on different drives, where an absolute path is the better guess.
+fn relative_to(path: &Path, base: &Path) -> Option<PathBuf> {
+    let (path, base) = (path::absolute(path).ok()?, path::absolute(base).ok()?);
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let root = base
+        .iter()
+        .take_while(|component| matches!(component, Component::Prefix(_) | Component::RootDir))
+        .count();
+    if common <= root {
+        return None;
+    }
+    let mut relative: PathBuf = base[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .collect();
+    relative.extend(&path[common..]);
+    Some(relative)
+}
@@ -0,0 +1,489 @@
+//! Uploading the outputs of a batch to an SFTP server. The settings, remote
+//! paths and known hosts are always available; the upload itself needs the
+//! `sftp` feature.
+//!
+//! Host keys are trusted on first use: a server is only uploaded to once
+//! the fingerprint of its key was stored in `KnownHosts`, and a key that
+//! differs from the stored one is refused.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::upload;
+
+pub const DEFAULT_PORT: u16 = 22;
+
+/// Retries of a failed transfer before it is given up on, unless set.
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Where the outputs are uploaded to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SftpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    /// Private key to sign in with. The SSH agent is asked without one.
+    pub key_file: Option<PathBuf>,
+    /// Folder the outputs go into, like `/srv/photos`. Relative paths start
+    /// in the home folder of `username`.
+    pub base_path: String,
+    /// Retries of a failed transfer.
+    pub retries: u32,
+}
+
+impl Default for SftpSettings {
+    fn default() -> Self {
+        SftpSettings {
+            host: String::new(),
+            port: DEFAULT_PORT,
+            username: String::new(),
+            key_file: None,
+            base_path: String::new(),
+            retries: DEFAULT_RETRIES,
+        }
+    }
+}
+
+impl SftpSettings {
+    /// Whether there is a host and a user to sign in as.
+    pub fn is_complete(&self) -> bool {
+        !self.host.trim().is_empty() && !self.username.trim().is_empty()
+    }
+
+    /// `host:port`, under which the host key is stored.
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host.trim(), self.port)
+    }
+
+    /// Path on the server of the output at `path`: its path below
+    /// `output_dir` behind `base_path`, so the server gets the same folders.
+    /// Outputs outside of `output_dir` keep only their file name.
+    pub fn remote_path(&self, output_dir: &Path, path: &Path) -> String {
+        let relative = upload::object_key("", output_dir, path);
+        match self.base_path.trim() {
+            "" => relative,
+            "/" => format!("/{}", relative),
+            base => format!("{}/{}", base.trim_end_matches('/'), relative),
+        }
+    }
+
+    /// Address of `remote`, to copy and share.
+    pub fn url(&self, remote: &str) -> String {
+        let port = match self.port {
+            DEFAULT_PORT => String::new(),
+            port => format!(":{}", port),
+        };
+        let remote = match remote.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("~/{}", remote),
+        };
+        format!(
+            "sftp://{}@{}{}/{}",
+            self.username.trim(),
+            self.host.trim(),
+            port,
+            remote
+        )
+    }
+}
+
+/// Folders to create on the server before `remote` can be written, from the
+/// outermost in.
+pub fn remote_dirs(remote: &str) -> Vec<String> {
+    remote
+        .match_indices('/')
+        .filter(|(index, _)| *index > 0)
+        .map(|(index, _)| remote[..index].to_string())
+        .collect()
+}
+
+/// How the key a server presented compares to the stored one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostKey {
+    Trusted,
+    /// No key is stored for the server yet.
+    Unknown,
+    /// The server presented another key than the stored one.
+    Changed {
+        trusted: String,
+    },
+}
+
+/// Fingerprints of the host keys trusted so far, by `host:port`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownHosts {
+    hosts: BTreeMap<String, String>,
+}
+
+impl KnownHosts {
+    /// The hosts stored at `path`; none if there is no file yet.
+    pub fn load(path: &Path) -> io::Result<KnownHosts> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(KnownHosts::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    /// The fingerprint trusted for `address`, if any.
+    pub fn get(&self, address: &str) -> Option<&str> {
+        self.hosts.get(address).map(String::as_str)
+    }
+
+    pub fn check(&self, address: &str, fingerprint: &str) -> HostKey {
+        match self.get(address) {
+            None => HostKey::Unknown,
+            Some(trusted) if trusted == fingerprint => HostKey::Trusted,
+            Some(trusted) => HostKey::Changed {
+                trusted: trusted.to_string(),
+            },
+        }
+    }
+
+    /// Trusts `fingerprint` for `address`, replacing any key trusted before.
+    pub fn trust(&mut self, address: &str, fingerprint: &str) {
+        self.hosts
+            .insert(address.to_string(), fingerprint.to_string());
+    }
+}
+
+#[cfg(feature = "sftp")]
+pub use client::*;
+
+#[cfg(feature = "sftp")]
+mod client {
+    use std::{
+        collections::HashSet,
+        path::Path,
+        sync::{Arc, Mutex as SyncMutex},
+        time::Duration,
+    };
+
+    use russh::{
+        client::{self, Handle},
+        keys::{
+            agent::client::AgentClient, HashAlg, PrivateKeyWithHashAlg, PublicKeyOrCertificate,
+        },
+    };
+    use russh_sftp::client::SftpSession;
+    use tokio::{
+        io::AsyncWriteExt,
+        sync::{Mutex, Semaphore},
+    };
+
+    use super::{remote_dirs, SftpSettings};
+    use crate::{
+        t,
+        upload::{backoff, MAX_CONCURRENT_UPLOADS},
+    };
+
+    /// Named pipe of the OpenSSH agent that ships with Windows.
+    #[cfg(windows)]
+    const AGENT_PIPE: &str = r"\\.\pipe\openssh-ssh-agent";
+
+    /// Why an output could not be uploaded.
+    #[derive(Debug, thiserror::Error)]
+    pub enum SftpError {
+        #[error("{}", t!("sftp-incomplete"))]
+        Incomplete,
+        #[error("{}", t!("sftp-unknown-host", fingerprint = .fingerprint))]
+        UnknownHost { fingerprint: String },
+        #[error("{}", t!("sftp-host-changed", trusted = .trusted, fingerprint = .fingerprint))]
+        HostChanged {
+            trusted: String,
+            fingerprint: String,
+        },
+        #[error("{}", t!("sftp-rejected"))]
+        Rejected,
+        #[error("{}", t!("sftp-key", error = .0))]
+        Key(russh::keys::Error),
+        #[error("{}", t!("sftp-agent", error = .0))]
+        Agent(String),
+        #[error("{}", t!("sftp-transfer", error = .0))]
+        Transfer(#[from] std::io::Error),
+        #[error("{}", t!("sftp-server", error = .0))]
+        Server(#[from] russh_sftp::client::error::Error),
+        #[error(transparent)]
+        Ssh(#[from] russh::Error),
+    }
+
+    /// Compares the key of the server to the trusted one, remembering the
+    /// key it saw.
+    struct HostCheck {
+        trusted: Option<String>,
+        seen: Arc<SyncMutex<Option<String>>>,
+    }
+
+    impl client::Handler for HostCheck {
+        type Error = russh::Error;
+
+        async fn check_server_key(
+            &mut self,
+            key: &PublicKeyOrCertificate,
+        ) -> Result<bool, Self::Error> {
+            let fingerprint = key.public_key().fingerprint(HashAlg::Sha256).to_string();
+            let trusted = self.trusted.as_deref() == Some(fingerprint.as_str());
+            *self.seen.lock().expect("host check poisoned") = Some(fingerprint);
+            Ok(trusted)
+        }
+    }
+
+    /// Opens an SSH connection, failing unless the host presents the key
+    /// with the `trusted` fingerprint.
+    async fn handshake(
+        settings: &SftpSettings,
+        trusted: Option<&str>,
+    ) -> Result<Handle<HostCheck>, SftpError> {
+        if !settings.is_complete() {
+            return Err(SftpError::Incomplete);
+        }
+        let seen = Arc::new(SyncMutex::new(None));
+        let handler = HostCheck {
+            trusted: trusted.map(str::to_string),
+            seen: seen.clone(),
+        };
+        let config = Arc::new(client::Config {
+            inactivity_timeout: Some(Duration::from_secs(60)),
+            ..Default::default()
+        });
+        let address = (settings.host.trim(), settings.port);
+        let result = client::connect(config, address, handler).await;
+        let seen = seen.lock().expect("host check poisoned").take();
+        match (result, seen, trusted) {
+            (Ok(handle), _, _) => Ok(handle),
+            (Err(_), Some(fingerprint), None) => Err(SftpError::UnknownHost { fingerprint }),
+            (Err(_), Some(fingerprint), Some(trusted)) if fingerprint != trusted => {
+                Err(SftpError::HostChanged {
+                    trusted: trusted.to_string(),
+                    fingerprint,
+                })
+            }
+            (Err(e), _, _) => Err(e.into()),
+        }
+    }
+
+    /// SHA-256 fingerprint of the key the host of `settings` presents, to
+    /// be compared and trusted before anything is uploaded there.
+    pub async fn host_fingerprint(settings: &SftpSettings) -> Result<String, SftpError> {
+        match handshake(settings, None).await {
+            Err(SftpError::UnknownHost { fingerprint }) => Ok(fingerprint),
+            Err(e) => Err(e),
+            Ok(_) => unreachable!("a host is never trusted without a fingerprint"),
+        }
+    }
+
+    async fn authenticate(
+        handle: &mut Handle<HostCheck>,
+        settings: &SftpSettings,
+    ) -> Result<(), SftpError> {
+        let user = settings.username.trim();
+        let hash = handle.best_supported_rsa_hash().await?.flatten();
+        let accepted = match &settings.key_file {
+            Some(path) => {
+                let key = russh::keys::load_secret_key(path, None).map_err(SftpError::Key)?;
+                let key = PrivateKeyWithHashAlg::new(Arc::new(key), hash);
+                handle.authenticate_publickey(user, key).await?.success()
+            }
+            None => {
+                #[cfg(unix)]
+                let agent = AgentClient::connect_env().await;
+                #[cfg(windows)]
+                let agent = AgentClient::connect_named_pipe(AGENT_PIPE).await;
+                let mut agent = agent.map_err(|e| SftpError::Agent(e.to_string()))?;
+                let identities = agent
+                    .request_identities()
+                    .await
+                    .map_err(|e| SftpError::Agent(e.to_string()))?;
+                let mut accepted = false;
+                for identity in identities {
+                    let key = identity.public_key().into_owned();
+                    let hash = hash.filter(|_| key.algorithm().is_rsa());
+                    accepted = handle
+                        .authenticate_publickey_with(user, key, hash, &mut agent)
+                        .await
+                        .map_err(|e| SftpError::Agent(e.to_string()))?
+                        .success();
+                    if accepted {
+                        break;
+                    }
+                }
+                accepted
+            }
+        };
+        if accepted {
+            Ok(())
+        } else {
+            Err(SftpError::Rejected)
+        }
+    }
+
+    /// A signed-in SFTP session and the folders it created.
+    struct Connection {
+        handle: Handle<HostCheck>,
+        sftp: SftpSession,
+        created: SyncMutex<HashSet<String>>,
+    }
+
+    async fn open(settings: &SftpSettings, trusted: &str) -> Result<Connection, SftpError> {
+        let mut handle = handshake(settings, Some(trusted)).await?;
+        authenticate(&mut handle, settings).await?;
+        let channel = handle.channel_open_session().await?;
+        channel.request_subsystem(true, "sftp").await?;
+        let sftp = SftpSession::new(channel.into_stream()).await?;
+        Ok(Connection {
+            handle,
+            sftp,
+            created: SyncMutex::new(HashSet::new()),
+        })
+    }
+
+    impl Connection {
+        /// Creates the folders `remote` goes into, like `mkdir -p`.
+        async fn create_dirs(&self, remote: &str) -> Result<(), SftpError> {
+            for dir in remote_dirs(remote) {
+                if self
+                    .created
+                    .lock()
+                    .expect("folders poisoned")
+                    .contains(&dir)
+                {
+                    continue;
+                }
+                if !self.sftp.try_exists(dir.clone()).await? {
+                    // Another upload may have created it in the meantime.
+                    if let Err(e) = self.sftp.create_dir(dir.clone()).await {
+                        if !self.sftp.try_exists(dir.clone()).await? {
+                            return Err(e.into());
+                        }
+                    }
+                }
+                self.created.lock().expect("folders poisoned").insert(dir);
+            }
+            Ok(())
+        }
+
+        /// Writes `path` to `remote` through a `.part` file, so an
+        /// interrupted transfer never leaves a truncated output behind.
+        async fn put(&self, path: &Path, remote: &str) -> Result<(), SftpError> {
+            self.create_dirs(remote).await?;
+            let part = format!("{}.part", remote);
+            let mut local = tokio::fs::File::open(path).await?;
+            let mut file = self.sftp.create(part.clone()).await?;
+            tokio::io::copy(&mut local, &mut file).await?;
+            file.shutdown().await?;
+            // Renaming onto an existing file fails over SFTP.
+            if self.sftp.try_exists(remote).await? {
+                self.sftp.remove_file(remote).await?;
+            }
+            self.sftp.rename(part, remote).await?;
+            Ok(())
+        }
+    }
+
+    /// Uploads files to one server over a shared connection,
+    /// `MAX_CONCURRENT_UPLOADS` at a time. Cheap to clone; clones share the
+    /// connection and the limit.
+    #[derive(Clone)]
+    pub struct SftpUploader {
+        settings: SftpSettings,
+        fingerprint: String,
+        connection: Arc<Mutex<Option<Arc<Connection>>>>,
+        permits: Arc<Semaphore>,
+    }
+
+    impl std::fmt::Debug for SftpUploader {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("SftpUploader")
+                .field("address", &self.settings.address())
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl SftpUploader {
+        /// Connects and signs in, if the host presents the key with the
+        /// `trusted` fingerprint.
+        pub async fn connect(
+            settings: &SftpSettings,
+            trusted: Option<&str>,
+        ) -> Result<SftpUploader, SftpError> {
+            let Some(trusted) = trusted else {
+                return Err(host_fingerprint(settings)
+                    .await
+                    .map(|fingerprint| SftpError::UnknownHost { fingerprint })
+                    .unwrap_or_else(|e| e));
+            };
+            let connection = open(settings, trusted).await?;
+            Ok(SftpUploader {
+                settings: settings.clone(),
+                fingerprint: trusted.to_string(),
+                connection: Arc::new(Mutex::new(Some(Arc::new(connection)))),
+                permits: Arc::new(Semaphore::new(MAX_CONCURRENT_UPLOADS)),
+            })
+        }
+
+        pub fn settings(&self) -> &SftpSettings {
+            &self.settings
+        }
+
+        /// Uploads the file at `path` to `remote` once a slot is free,
+        /// retrying `SftpSettings::retries` times with `backoff` in between.
+        /// `on_attempt` is called with the number of each try as it starts.
+        pub async fn upload(
+            &self,
+            path: &Path,
+            remote: &str,
+            mut on_attempt: impl FnMut(u32),
+        ) -> Result<(), SftpError> {
+            let _permit = self
+                .permits
+                .acquire()
+                .await
+                .expect("the upload limit is never closed");
+            let mut attempt = 1;
+            loop {
+                on_attempt(attempt);
+                let result = match self.connection().await {
+                    Ok(connection) => connection.put(path, remote).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => return Ok(()),
+                    // A host that changed its key won't change it back.
+                    Err(e @ SftpError::HostChanged { .. }) => return Err(e),
+                    Err(e) if attempt <= self.settings.retries => {
+                        tracing::debug!("upload of {} failed, retrying: {}", path.display(), e);
+                        tokio::time::sleep(backoff(attempt)).await;
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        /// The shared connection, opened again if it was lost.
+        async fn connection(&self) -> Result<Arc<Connection>, SftpError> {
+            let mut connection = self.connection.lock().await;
+            if let Some(open) = connection.as_ref().filter(|open| !open.handle.is_closed()) {
+                return Ok(open.clone());
+            }
+            *connection = None;
+            let open = Arc::new(open(&self.settings, &self.fingerprint).await?);
+            *connection = Some(open.clone());
+            Ok(open)
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! A drop shadow under the photo, so it seems to float above the border.
+
+use image::{
+    imageops, imageops::FilterType, DynamicImage, GenericImageView, GrayImage, Luma, Rgba,
+    RgbaImage,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::serialization::hex_color;
+
+/// Blur radius, in pixels of the small copy, that the shadow is worked out
+/// at. Blurrier shadows are drawn smaller and scaled up, which looks the
+/// same and keeps large photos fast.
+const WORKING_BLUR: f32 = 4.0;
+
+/// Shadow cast by the photo onto the canvas. Lengths are in pixels of the
+/// photo.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DropShadow {
+    /// How far the shadow falls to the right; negative to the left.
+    pub offset_x: f32,
+    /// How far the shadow falls down; negative up.
+    pub offset_y: f32,
+    /// Radius of the blur softening its edges.
+    pub blur: f32,
+    /// In percent; 100 is as dark as `color` itself.
+    pub opacity: f32,
+    #[serde(with = "hex_color")]
+    pub color: Rgba<u8>,
+}
+
+impl Default for DropShadow {
+    fn default() -> Self {
+        DropShadow {
+            offset_x: 8.0,
+            offset_y: 12.0,
+            blur: 16.0,
+            opacity: 40.0,
+            color: Rgba([0, 0, 0, 255]),
+        }
+    }
+}
+
+impl DropShadow {
+    /// The shadow `img` casts, and where its top left corner lies relative
+    /// to that of `img`. Transparent parts of `img`, like rounded corners,
+    /// cast none.
+    pub fn cast(&self, img: &DynamicImage) -> (RgbaImage, (i64, i64)) {
+        let (width, height) = img.dimensions();
+        // As in CSS, the radius is twice the deviation of the blur.
+        let sigma = self.blur.max(0.0) / 2.0;
+        let pad = (sigma * 3.0).ceil() as u32;
+        let mut mask = GrayImage::from_pixel(width + 2 * pad, height + 2 * pad, Luma([0]));
+        if img.color().has_alpha() {
+            let alpha = img.to_luma_alpha8();
+            for (x, y, pixel) in alpha.enumerate_pixels() {
+                mask.put_pixel(x + pad, y + pad, Luma([pixel.0[1]]));
+            }
+        } else {
+            imageops::replace(
+                &mut mask,
+                &GrayImage::from_pixel(width, height, Luma([255])),
+                pad as i64,
+                pad as i64,
+            );
+        }
+
+        if sigma > 0.0 {
+            let (mask_width, mask_height) = mask.dimensions();
+            let scale = (WORKING_BLUR / sigma).min(1.0);
+            let small = |side: u32| ((side as f32 * scale).round() as u32).max(1);
+            let blurred = imageops::blur(
+                &imageops::resize(
+                    &mask,
+                    small(mask_width),
+                    small(mask_height),
+                    FilterType::Triangle,
+                ),
+                sigma * scale,
+            );
+            mask = imageops::resize(&blurred, mask_width, mask_height, FilterType::Triangle);
+        }
+
+        let opacity = (self.opacity / 100.0).clamp(0.0, 1.0);
+        let [r, g, b, a] = self.color.0;
+        let shadow = RgbaImage::from_fn(mask.width(), mask.height(), |x, y| {
+            let coverage = mask.get_pixel(x, y).0[0] as f32 / 255.0;
+            Rgba([r, g, b, (a as f32 * coverage * opacity).round() as u8])
+        });
+        let position = (
+            self.offset_x.round() as i64 - pad as i64,
+            self.offset_y.round() as i64 - pad as i64,
+        );
+        (shadow, position)
+    }
+}
@@ -0,0 +1,72 @@
+//! Whether the drive of the output folder has room for a batch.
+
+use std::{io, path::Path};
+
+use crate::format::OutputFormat;
+
+/// Less than this left over after a batch is cutting it close, however
+/// small the batch.
+const MIN_HEADROOM: u64 = 512 * 1024 * 1024;
+
+/// How the free space on a drive compares with what a batch writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Space {
+    Enough,
+    /// The batch fits, with little to spare for anything else.
+    Tight,
+    /// The batch doesn't fit.
+    Short,
+}
+
+/// Compares `needed` bytes with `available` ones. Less than a tenth of
+/// `needed`, or `MIN_HEADROOM` if that is more, to spare is tight.
+pub fn compare(needed: u64, available: u64) -> Space {
+    if needed > available {
+        Space::Short
+    } else if available - needed < (needed / 10).max(MIN_HEADROOM) {
+        Space::Tight
+    } else {
+        Space::Enough
+    }
+}
+
+/// Bytes the user may still write to the drive of `path`. A folder that
+/// doesn't exist yet is looked up through the nearest one above it, as a
+/// batch creates it on that drive.
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|dir| !dir.as_os_str().is_empty() && dir.exists())
+        .unwrap_or(Path::new("."));
+    fs4::available_space(existing)
+}
+
+/// A generous guess at the size of one output, for when no estimate is at
+/// hand: lossless formats as if stored uncompressed, lossy ones at a byte
+/// per pixel, or the size of the input if that is more.
+pub fn rough_output_size(
+    format: OutputFormat,
+    (width, height): (u32, u32),
+    bits: u8,
+    input_bytes: u64,
+) -> u64 {
+    // Absurd borders make canvases too large to ever write, but the
+    // guess still has to come out.
+    let pixels = (width as u64).saturating_mul(height as u64);
+    match format {
+        OutputFormat::Png | OutputFormat::Tiff | OutputFormat::Webp => {
+            let channel_bytes = if bits > 8 { 2 } else { 1 };
+            pixels.saturating_mul(4 * channel_bytes)
+        }
+        OutputFormat::Jpeg | OutputFormat::Avif => pixels.max(input_bytes),
+    }
+}
+
+/// Whether writing failed because the drive is full, or the user's quota
+/// on it used up.
+pub fn is_disk_full(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded
+    )
+}
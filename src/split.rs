@@ -0,0 +1,143 @@
+//! Splitting a wide photo into tiles of the same shape for a swipeable
+//! carousel. Each tile then gets the border like any single photo.
+
+use image::{imageops, DynamicImage, ImageBuffer, Rgba};
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::bit_depth;
+
+/// Fewest and most tiles a photo can be split into. Carousels take up to
+/// ten.
+pub const MIN_TILES: u32 = 2;
+pub const MAX_TILES: u32 = 10;
+
+/// Shape of every tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TileAspect {
+    /// 1:1.
+    #[default]
+    Square,
+    /// 4:5, the tallest a carousel shows uncropped.
+    Portrait,
+}
+
+impl TileAspect {
+    /// Width and height of the shape.
+    pub fn ratio(self) -> (u32, u32) {
+        match self {
+            TileAspect::Square => (1, 1),
+            TileAspect::Portrait => (4, 5),
+        }
+    }
+}
+
+/// How a photo that doesn't have the shape of the tiles side by side is
+/// made to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitFit {
+    /// Cut off what sticks out, evenly on both sides.
+    #[default]
+    Crop,
+    /// Add the border color where it falls short, evenly on both sides.
+    Pad,
+}
+
+/// How a photo is split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitSettings {
+    pub tiles: u32,
+    pub aspect: TileAspect,
+    pub fit: SplitFit,
+}
+
+impl Default for SplitSettings {
+    fn default() -> Self {
+        SplitSettings {
+            tiles: 3,
+            aspect: TileAspect::default(),
+            fit: SplitFit::default(),
+        }
+    }
+}
+
+impl SplitSettings {
+    /// `tiles` within `MIN_TILES..=MAX_TILES`.
+    pub fn tile_count(&self) -> u32 {
+        self.tiles.clamp(MIN_TILES, MAX_TILES)
+    }
+}
+
+/// The area of a photo that is split, and its tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitLayout {
+    /// Left and top edge of the area on the photo, negative where it is
+    /// padded.
+    pub x: i64,
+    pub y: i64,
+    pub width: u32,
+    pub height: u32,
+    /// Left edge in the area and width of every tile, from the left.
+    pub columns: Vec<(u32, u32)>,
+}
+
+/// Lays out the tiles of a `width` × `height` photo after `settings`. A
+/// width that doesn't divide evenly makes the first tiles a pixel wider.
+pub fn layout(width: u32, height: u32, settings: &SplitSettings) -> SplitLayout {
+    let tiles = settings.tile_count();
+    let (aspect_width, aspect_height) = settings.aspect.ratio();
+    let (width, height) = (width.max(1), height.max(1));
+    // Whether the photo is wider than the tiles side by side.
+    let wider = width as u64 * aspect_height as u64 > height as u64 * (tiles * aspect_width) as u64;
+    let keep_height = wider == (settings.fit == SplitFit::Crop);
+    let (area_width, area_height) = if keep_height {
+        let area_width = height as f64 * (tiles * aspect_width) as f64 / aspect_height as f64;
+        (area_width.round() as u32, height)
+    } else {
+        let area_height = width as f64 * aspect_height as f64 / (tiles * aspect_width) as f64;
+        (width, area_height.round() as u32)
+    };
+    let (area_width, area_height) = (area_width.max(tiles), area_height.max(1));
+
+    let base = area_width / tiles;
+    let wider_tiles = area_width % tiles;
+    let mut columns = vec![];
+    let mut x = 0;
+    for tile in 0..tiles {
+        let tile_width = base + u32::from(tile < wider_tiles);
+        columns.push((x, tile_width));
+        x += tile_width;
+    }
+
+    SplitLayout {
+        x: (width as i64 - area_width as i64) / 2,
+        y: (height as i64 - area_height as i64) / 2,
+        width: area_width,
+        height: area_height,
+        columns,
+    }
+}
+
+/// The tiles of `img` after `settings`, from the left, padded with
+/// `color`. Deep images keep their precision, as in `Border`.
+pub fn split(img: &DynamicImage, settings: &SplitSettings, color: Rgba<u8>) -> Vec<DynamicImage> {
+    let layout = layout(img.width(), img.height(), settings);
+    let area: DynamicImage = if bit_depth(img.color()) > 8 {
+        let color = Rgba(color.0.map(|channel| channel as u16 * 257));
+        let mut area = ImageBuffer::from_pixel(layout.width, layout.height, color);
+        imageops::overlay(&mut area, &img.to_rgba16(), -layout.x, -layout.y);
+        area.into()
+    } else {
+        let mut area: DynamicImage =
+            ImageBuffer::from_pixel(layout.width, layout.height, color).into();
+        imageops::overlay(&mut area, img, -layout.x, -layout.y);
+        area
+    };
+    layout
+        .columns
+        .iter()
+        .map(|&(x, width)| area.crop_imm(x, 0, width, layout.height))
+        .collect()
+}
@@ -0,0 +1,291 @@
+//! Responsive image sets for websites: next to each output, copies scaled
+//! down to a few widths as WebP, listed with the output in a `srcset.json`
+//! in the output folder and, if asked for, in an HTML snippet per output.
+//!
+//! The map looks like this, with paths relative to the output folder:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "images": [
+//!     {
+//!       "source": "/photos/Café 1.jpg",
+//!       "src": "Caf%C3%A9%201_bordered.jpg",
+//!       "width": 3000,
+//!       "height": 2000,
+//!       "widths": {
+//!         "480": "Caf%C3%A9%201_bordered-480w.webp",
+//!         "960": "Caf%C3%A9%201_bordered-960w.webp",
+//!         "1920": "Caf%C3%A9%201_bordered-1920w.webp"
+//!       },
+//!       "srcset": "Caf%C3%A9%201_bordered-480w.webp 480w, …, Caf%C3%A9%201_bordered.jpg 3000w"
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use image::{
+    error::{EncodingError, ImageFormatHint},
+    imageops::FilterType,
+    DynamicImage, ImageError, ImageFormat,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{naming::OverwritePolicy, process::write_output, ProcessError};
+
+/// Widths of the copies unless set.
+pub const DEFAULT_WIDTHS: [u32; 3] = [480, 960, 1920];
+
+/// Name of the map in the output folder.
+pub const MAP_NAME: &str = "srcset.json";
+
+/// Version of the map layout. New versions only ever add fields.
+pub const MAP_VERSION: u32 = 1;
+
+/// How names of copies and paths in the map and snippets are made safe
+/// for URLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UrlNames {
+    /// Copies are named like their output, and paths percent-encode what
+    /// URLs don't allow, like `Caf%C3%A9%201.webp`.
+    #[default]
+    Percent,
+    /// Copies get plain lower-case names, like `cafe-1.webp`. Paths are
+    /// still percent-encoded, for outputs whose own names need it.
+    Slug,
+}
+
+/// Which copies are written along with each output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SrcsetSettings {
+    /// Widths of the copies in pixels. Those not below the width of the
+    /// output are left out, as the output itself serves them.
+    pub widths: Vec<u32>,
+    /// WebP quality of the copies, 1 to 100.
+    pub quality: u8,
+    /// Also writes an `<img>` snippet next to each output.
+    pub html: bool,
+    pub url_names: UrlNames,
+}
+
+impl Default for SrcsetSettings {
+    fn default() -> Self {
+        SrcsetSettings {
+            widths: DEFAULT_WIDTHS.to_vec(),
+            quality: 80,
+            html: false,
+            url_names: UrlNames::default(),
+        }
+    }
+}
+
+impl SrcsetSettings {
+    /// The widths of copies of an output `full_width` wide, smallest
+    /// first, each once.
+    pub fn widths_below(&self, full_width: u32) -> Vec<u32> {
+        let mut widths: Vec<u32> = self
+            .widths
+            .iter()
+            .copied()
+            .filter(|&width| width > 0 && width < full_width)
+            .collect();
+        widths.sort_unstable();
+        widths.dedup();
+        widths
+    }
+
+    /// Name of the copy `width` wide of the output named `stem`.
+    pub fn copy_name(&self, stem: &OsStr, width: u32) -> OsString {
+        let mut name = self.stem(stem);
+        name.push(format!("-{}w.webp", width));
+        name
+    }
+
+    /// Name of the snippet of the output named `stem`.
+    pub fn html_name(&self, stem: &OsStr) -> OsString {
+        let mut name = self.stem(stem);
+        name.push(".html");
+        name
+    }
+
+    fn stem(&self, stem: &OsStr) -> OsString {
+        match self.url_names {
+            UrlNames::Percent => stem.to_os_string(),
+            UrlNames::Slug => slug(&stem.to_string_lossy()).into(),
+        }
+    }
+}
+
+/// `name` in lower-case ASCII letters and digits, with `-` between words:
+/// `Café Ölberg (2)` becomes `cafe-olberg-2`.
+pub fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in deunicode::deunicode(name).chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "image".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// `segment` with every byte URLs don't allow in a path as `%XX`.
+pub fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::new();
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// `path` relative to `base` as a URL path, or its file name when it is
+/// elsewhere.
+pub fn url_path(base: &Path, path: &Path) -> String {
+    let relative = path
+        .strip_prefix(base)
+        .ok()
+        .or_else(|| path.file_name().map(Path::new))
+        .unwrap_or(path);
+    relative
+        .components()
+        .map(|component| percent_encode(&component.as_os_str().to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `img` scaled to `width`, keeping its shape.
+pub fn scaled(img: &DynamicImage, width: u32, filter: FilterType) -> DynamicImage {
+    let height = (img.height() as f64 * width as f64 / img.width().max(1) as f64).round() as u32;
+    img.resize_exact(width, height.max(1), filter)
+}
+
+/// Writes `img` to `writer` as lossy WebP of `quality`, keeping
+/// transparency. Deep images are brought down to 8 bits.
+pub fn encode_webp(
+    img: &DynamicImage,
+    quality: u8,
+    writer: &mut dyn Write,
+) -> Result<(), ImageError> {
+    let (width, height) = (img.width(), img.height());
+    let quality = quality.clamp(1, 100) as f32;
+    let encoded = if img.color().has_alpha() {
+        let pixels = img.to_rgba8();
+        webp::Encoder::from_rgba(&pixels, width, height).encode_simple(false, quality)
+    } else {
+        let pixels = img.to_rgb8();
+        webp::Encoder::from_rgb(&pixels, width, height).encode_simple(false, quality)
+    };
+    let encoded = encoded.map_err(|e| {
+        ImageError::Encoding(EncodingError::new(
+            ImageFormatHint::Exact(ImageFormat::WebP),
+            format!("{:?}", e),
+        ))
+    })?;
+    writer.write_all(&encoded).map_err(ImageError::IoError)
+}
+
+/// One output and its copies, as listed in the map.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SrcsetEntry {
+    /// Path of the input.
+    pub source: String,
+    /// URL path of the output.
+    pub src: String,
+    pub width: u32,
+    pub height: u32,
+    /// URL path of the copy of each width.
+    pub widths: BTreeMap<u32, String>,
+    /// Value for the `srcset` attribute: every copy, then the output.
+    pub srcset: String,
+}
+
+impl SrcsetEntry {
+    /// Entry of the `output` of `source`, `dimensions` large, with its
+    /// `copies` of each width. Paths are relative to `base`.
+    pub fn new(
+        source: &Path,
+        output: &Path,
+        dimensions: (u32, u32),
+        copies: &[(u32, PathBuf)],
+        base: &Path,
+    ) -> Self {
+        let src = url_path(base, output);
+        let widths: BTreeMap<u32, String> = copies
+            .iter()
+            .map(|(width, path)| (*width, url_path(base, path)))
+            .collect();
+        let srcset = widths
+            .iter()
+            .map(|(width, path)| (*width, path))
+            .chain([(dimensions.0, &src)])
+            .map(|(width, path)| format!("{} {}w", path, width))
+            .collect::<Vec<_>>()
+            .join(", ");
+        SrcsetEntry {
+            source: source.display().to_string(),
+            src,
+            width: dimensions.0,
+            height: dimensions.1,
+            widths,
+            srcset,
+        }
+    }
+
+    /// An `<img>` element showing the entry, to paste into a page.
+    pub fn html(&self) -> String {
+        format!(
+            "<img src=\"{}\"\n     srcset=\"{}\"\n     sizes=\"100vw\" width=\"{}\" height=\"{}\" alt=\"\" loading=\"lazy\">\n",
+            self.src, self.srcset, self.width, self.height
+        )
+    }
+}
+
+/// The map of a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SrcsetMap {
+    pub version: u32,
+    pub images: Vec<SrcsetEntry>,
+}
+
+/// Writes the map of `images` into `output_dir` as `MAP_NAME`, sorted by
+/// output. A taken name is handled by `policy`; `None` means the map was
+/// skipped.
+pub fn write_map(
+    output_dir: &Path,
+    mut images: Vec<SrcsetEntry>,
+    policy: OverwritePolicy,
+) -> Result<Option<PathBuf>, ProcessError> {
+    images.sort_by(|a, b| a.src.cmp(&b.src));
+    let map = SrcsetMap {
+        version: MAP_VERSION,
+        images,
+    };
+    let path = output_dir.join(MAP_NAME);
+    fs::create_dir_all(output_dir).map_err(|e| ProcessError::io(output_dir, e))?;
+    write_output(output_dir, MAP_NAME.as_ref(), policy, |file| {
+        serde_json::to_writer_pretty(&mut *file, &map)
+            .map_err(|e| ProcessError::io(&path, e.into()))?;
+        file.write_all(b"\n")
+            .map_err(|e| ProcessError::io(&path, e))
+    })
+}
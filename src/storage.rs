@@ -0,0 +1,122 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const APP_DIR_NAME: &str = "image-finalizer";
+
+/// Title of the window, which eframe also uses to name its storage folder.
+pub const APP_NAME: &str = "Image Border App";
+
+/// File next to the executable that turns on portable mode.
+const PORTABLE_MARKER: &str = "portable.txt";
+
+/// Folder next to the executable that holds everything in portable mode.
+const PORTABLE_DIR_NAME: &str = "image-finalizer-data";
+
+/// Name of eframe's own storage file.
+const EFRAME_FILE: &str = "app.ron";
+
+static PORTABLE: OnceLock<bool> = OnceLock::new();
+
+/// Decides once per run whether storage is portable: either because it was
+/// `requested` or because the marker file sits next to the executable. Call
+/// before anything is read from or written to storage.
+pub fn init(requested: bool) {
+    let marker = exe_dir().is_some_and(|dir| dir.join(PORTABLE_MARKER).exists());
+    let _ = PORTABLE.set(requested || marker);
+}
+
+pub fn is_portable() -> bool {
+    PORTABLE.get().copied().unwrap_or(false)
+}
+
+/// Directory holding everything the app persists outside of eframe's own
+/// storage (presets and the like).
+pub fn config_dir() -> PathBuf {
+    if is_portable() {
+        portable_dir()
+    } else {
+        platform_config_dir()
+    }
+}
+
+/// Directory of the log files, which users attach to bug reports.
+pub fn log_dir() -> PathBuf {
+    if is_portable() {
+        portable_dir().join("logs")
+    } else {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(APP_DIR_NAME)
+            .join("logs")
+    }
+}
+
+/// Where eframe should keep the window and UI state, when its default
+/// location must not be used.
+pub fn eframe_storage_file() -> Option<PathBuf> {
+    is_portable().then(|| portable_dir().join(EFRAME_FILE))
+}
+
+/// Whether to offer copying the regular storage into a portable folder that
+/// is used for the first time. `eframe_file` is eframe's regular storage file.
+pub fn offers_migration(eframe_file: Option<&Path>) -> bool {
+    is_portable()
+        && !portable_dir().exists()
+        && (platform_config_dir().exists() || eframe_file.is_some_and(Path::exists))
+}
+
+/// Copies the regular storage into the portable folder, leaving the
+/// originals in place.
+pub fn migrate_to_portable(eframe_file: Option<&Path>) -> io::Result<()> {
+    let target = portable_dir();
+    fs::create_dir_all(&target)?;
+
+    let source = platform_config_dir();
+    if source.exists() {
+        copy_dir(&source, &target)?;
+    }
+    if let Some(file) = eframe_file.filter(|file| file.exists()) {
+        fs::copy(file, target.join(EFRAME_FILE))?;
+    }
+    Ok(())
+}
+
+/// Creates the portable folder without copying anything, so the migration
+/// isn't offered again.
+pub fn skip_migration() -> io::Result<()> {
+    fs::create_dir_all(portable_dir())
+}
+
+fn platform_config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(APP_DIR_NAME)
+}
+
+fn portable_dir() -> PathBuf {
+    exe_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(PORTABLE_DIR_NAME)
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Batch progress on the icon of the app: the Windows taskbar button, or
+//! the launcher entry of Linux docks that speak the Unity protocol.
+//! Elsewhere, or when the platform refuses, nothing is shown.
+
+use eframe::CreationContext;
+
+/// Progress of a running batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub done: u64,
+    pub total: u64,
+    /// Files that could not be processed so far.
+    pub failed: u64,
+}
+
+pub struct Taskbar {
+    backend: Option<backend::Backend>,
+    /// What the icon shows now, to only talk to the platform on changes.
+    shown: Option<Progress>,
+}
+
+impl Taskbar {
+    pub fn new(cc: &CreationContext<'_>) -> Self {
+        let backend = backend::Backend::connect(cc);
+        if backend.is_none() {
+            tracing::debug!("no taskbar progress on this platform");
+        }
+        Taskbar {
+            backend,
+            shown: None,
+        }
+    }
+
+    /// Shows `progress` on the icon, or clears it for `None`.
+    pub fn show(&mut self, progress: Option<Progress>) {
+        if progress == self.shown {
+            return;
+        }
+        self.shown = progress;
+        if let Some(backend) = &self.backend {
+            if let Err(e) = backend.show(progress) {
+                tracing::warn!("could not update the taskbar progress: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod backend {
+    use eframe::CreationContext;
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use windows::{
+        core::IUnknown,
+        Win32::{
+            Foundation::HWND,
+            System::Com::{
+                CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+            },
+            UI::Shell::{ITaskbarList3, TaskbarList, TBPF_ERROR, TBPF_NOPROGRESS, TBPF_NORMAL},
+        },
+    };
+
+    use super::Progress;
+
+    pub struct Backend {
+        list: ITaskbarList3,
+        window: HWND,
+    }
+
+    impl Backend {
+        pub fn connect(cc: &CreationContext<'_>) -> Option<Self> {
+            let RawWindowHandle::Win32(handle) = cc.window_handle().ok()?.as_raw() else {
+                return None;
+            };
+            // winit has usually set up COM on this thread already.
+            let _ = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
+            let list: ITaskbarList3 =
+                unsafe { CoCreateInstance(&TaskbarList, None::<&IUnknown>, CLSCTX_INPROC_SERVER) }
+                    .ok()?;
+            unsafe { list.HrInit() }.ok()?;
+            Some(Backend {
+                list,
+                window: HWND(handle.hwnd.get() as _),
+            })
+        }
+
+        pub fn show(&self, progress: Option<Progress>) -> windows::core::Result<()> {
+            let Some(progress) = progress else {
+                return unsafe { self.list.SetProgressState(self.window, TBPF_NOPROGRESS) };
+            };
+            unsafe {
+                self.list
+                    .SetProgressValue(self.window, progress.done, progress.total.max(1))?;
+                self.list.SetProgressState(
+                    self.window,
+                    if progress.failed > 0 {
+                        TBPF_ERROR
+                    } else {
+                        TBPF_NORMAL
+                    },
+                )
+            }
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod backend {
+    use std::collections::HashMap;
+
+    use eframe::CreationContext;
+    use zbus::{blocking::Connection, zvariant::Value};
+
+    use super::Progress;
+
+    /// Desktop entry the dock matches the signal against.
+    const APP_URI: &str = "application://image-processor.desktop";
+
+    pub struct Backend {
+        connection: Connection,
+    }
+
+    impl Backend {
+        pub fn connect(_: &CreationContext<'_>) -> Option<Self> {
+            let connection = Connection::session().ok()?;
+            Some(Backend { connection })
+        }
+
+        /// Failures show as a count badge, as the protocol has no error
+        /// state.
+        pub fn show(&self, progress: Option<Progress>) -> zbus::Result<()> {
+            let mut properties: HashMap<&str, Value<'_>> = HashMap::new();
+            properties.insert("progress-visible", progress.is_some().into());
+            let failed = progress.map_or(0, |progress| progress.failed);
+            properties.insert("count-visible", (failed > 0).into());
+            properties.insert("count", (failed as i64).into());
+            if let Some(progress) = progress {
+                let fraction = progress.done as f64 / progress.total.max(1) as f64;
+                properties.insert("progress", fraction.into());
+            }
+            self.connection.emit_signal(
+                None::<&str>,
+                "/computer/zoeys/ImageFinalizer",
+                "com.canonical.Unity.LauncherEntry",
+                "Update",
+                &(APP_URI, properties),
+            )
+        }
+    }
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+mod backend {
+    use std::convert::Infallible;
+
+    use eframe::CreationContext;
+
+    use super::Progress;
+
+    pub enum Backend {}
+
+    impl Backend {
+        pub fn connect(_: &CreationContext<'_>) -> Option<Self> {
+            None
+        }
+
+        pub fn show(&self, _: Option<Progress>) -> Result<(), Infallible> {
+            match *self {}
+        }
+    }
+}
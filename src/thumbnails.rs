@@ -0,0 +1,130 @@
+//! The grid of outputs a running batch has written so far.
+
+use std::{collections::VecDeque, path::PathBuf};
+
+use egui::{vec2, Align2, Color32, FontId, StrokeKind, TextureHandle, TextureOptions, Ui};
+use image_finalizer::{t, THUMBNAIL_SIZE};
+
+use crate::a11y;
+
+/// Tiles beyond this many drop the oldest, so long batches don't pile up
+/// textures.
+const LIMIT: usize = 300;
+
+/// Side of a tile on screen.
+const TILE_SIZE: f32 = THUMBNAIL_SIZE as f32 * 0.75;
+
+enum Picture {
+    Output(TextureHandle),
+    Failed(String),
+}
+
+struct Tile {
+    /// The output, or the input that failed.
+    path: PathBuf,
+    picture: Picture,
+}
+
+#[derive(Default)]
+pub struct Thumbnails {
+    tiles: VecDeque<Tile>,
+}
+
+impl Thumbnails {
+    pub fn push_output(&mut self, ctx: &egui::Context, output: PathBuf, image: egui::ColorImage) {
+        let texture = ctx.load_texture(
+            format!("thumbnail_{}", output.display()),
+            image,
+            TextureOptions::LINEAR,
+        );
+        self.push(Tile {
+            path: output,
+            picture: Picture::Output(texture),
+        });
+    }
+
+    pub fn push_failed(&mut self, input: PathBuf, error: String) {
+        self.push(Tile {
+            path: input,
+            picture: Picture::Failed(error),
+        });
+    }
+
+    fn push(&mut self, tile: Tile) {
+        self.tiles.push_back(tile);
+        while self.tiles.len() > LIMIT {
+            self.tiles.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Draws the tiles, newest last. Returns the file of a clicked tile.
+    pub fn show(&self, ui: &mut Ui) -> Option<PathBuf> {
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .max_height(TILE_SIZE * 2.5)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for tile in &self.tiles {
+                        let text = match &tile.picture {
+                            Picture::Output(_) => {
+                                t!("thumbnail-hover", path = tile.path.display())
+                            }
+                            Picture::Failed(error) => t!("thumbnail-failed", error = error),
+                        };
+                        let (rect, response) =
+                            a11y::allocate_button(ui, vec2(TILE_SIZE, TILE_SIZE), &text);
+                        let painter = ui.painter_at(rect);
+                        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+                        match &tile.picture {
+                            Picture::Output(texture) => {
+                                let size = texture.size_vec2();
+                                let scale = TILE_SIZE / size.x.max(size.y);
+                                let image =
+                                    egui::Rect::from_center_size(rect.center(), size * scale);
+                                painter.image(
+                                    texture.id(),
+                                    image,
+                                    egui::Rect::from_min_max(
+                                        egui::pos2(0.0, 0.0),
+                                        egui::pos2(1.0, 1.0),
+                                    ),
+                                    Color32::WHITE,
+                                );
+                            }
+                            Picture::Failed(_) => {
+                                painter.text(
+                                    rect.center(),
+                                    Align2::CENTER_CENTER,
+                                    "⚠",
+                                    FontId::proportional(TILE_SIZE * 0.4),
+                                    ui.visuals().error_fg_color,
+                                );
+                            }
+                        }
+                        let response = response.on_hover_text(text);
+                        if response.hovered() {
+                            painter.rect_stroke(
+                                rect,
+                                2.0,
+                                ui.visuals().selection.stroke,
+                                StrokeKind::Inside,
+                            );
+                        }
+                        if response.clicked() {
+                            clicked = Some(tile.path.clone());
+                        }
+                    }
+                });
+            });
+        clicked
+    }
+}
@@ -0,0 +1,200 @@
+//! Turning and mirroring photos that were scanned or exported the wrong way
+//! round. The camera's own orientation from EXIF is applied while decoding;
+//! a `Transform` comes after it, before the border.
+
+use std::path::Path;
+
+use image::{DynamicImage, ImageDecoder, ImageReader, ImageResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ProcessError, pipeline::FileContext, pipeline::ProcessingStep, t};
+
+/// Clockwise rotation in quarter turns. Stored as degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(into = "u16", try_from = "u16")]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [
+        Rotation::None,
+        Rotation::Cw90,
+        Rotation::Cw180,
+        Rotation::Cw270,
+    ];
+
+    pub fn degrees(self) -> u16 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Cw90 => 90,
+            Rotation::Cw180 => 180,
+            Rotation::Cw270 => 270,
+        }
+    }
+
+    fn quarter_turns(self) -> u16 {
+        self.degrees() / 90
+    }
+
+    fn from_quarter_turns(turns: u16) -> Self {
+        Rotation::ALL[(turns % 4) as usize]
+    }
+
+    /// Whether width and height trade places.
+    pub fn swaps_sides(self) -> bool {
+        matches!(self, Rotation::Cw90 | Rotation::Cw270)
+    }
+}
+
+impl From<Rotation> for u16 {
+    fn from(rotation: Rotation) -> Self {
+        rotation.degrees()
+    }
+}
+
+impl TryFrom<u16> for Rotation {
+    type Error = String;
+
+    fn try_from(degrees: u16) -> Result<Self, Self::Error> {
+        match degrees {
+            0 | 90 | 180 | 270 => Ok(Rotation::from_quarter_turns(degrees / 90)),
+            _ => Err(format!(
+                "rotation must be 0, 90, 180 or 270 degrees, not {}",
+                degrees
+            )),
+        }
+    }
+}
+
+/// A rotation, then mirroring of the rotated photo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transform {
+    pub rotation: Rotation,
+    /// Mirrors left and right.
+    pub flip_horizontal: bool,
+    /// Mirrors top and bottom.
+    pub flip_vertical: bool,
+}
+
+impl Transform {
+    /// Whether the photo is left as it is.
+    pub fn is_identity(&self) -> bool {
+        self.normalized() == Transform::default()
+    }
+
+    /// The same transform with both flips turned into half a turn, so
+    /// equal transforms compare equal.
+    pub fn normalized(self) -> Self {
+        if self.flip_horizontal && self.flip_vertical {
+            Transform {
+                rotation: Rotation::from_quarter_turns(self.rotation.quarter_turns() + 2),
+                flip_horizontal: false,
+                flip_vertical: false,
+            }
+        } else {
+            self
+        }
+    }
+
+    /// This transform followed by a quarter turn clockwise, or counter-
+    /// clockwise.
+    pub fn rotated(self, clockwise: bool) -> Self {
+        let turns = if clockwise { 1 } else { 3 };
+        // Mirroring and then turning by a quarter is turning and then
+        // mirroring the other way.
+        Transform {
+            rotation: Rotation::from_quarter_turns(self.rotation.quarter_turns() + turns),
+            flip_horizontal: self.flip_vertical,
+            flip_vertical: self.flip_horizontal,
+        }
+        .normalized()
+    }
+
+    /// This transform followed by mirroring left and right.
+    pub fn flipped_horizontally(self) -> Self {
+        Transform {
+            flip_horizontal: !self.flip_horizontal,
+            ..self
+        }
+        .normalized()
+    }
+
+    /// This transform followed by mirroring top and bottom.
+    pub fn flipped_vertically(self) -> Self {
+        Transform {
+            flip_vertical: !self.flip_vertical,
+            ..self
+        }
+        .normalized()
+    }
+
+    /// Size of a `width`×`height` photo once transformed.
+    pub fn dimensions(&self, width: u32, height: u32) -> (u32, u32) {
+        if self.rotation.swaps_sides() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    pub fn apply(&self, img: DynamicImage) -> DynamicImage {
+        let img = match self.rotation {
+            Rotation::None => img,
+            Rotation::Cw90 => img.rotate90(),
+            Rotation::Cw180 => img.rotate180(),
+            Rotation::Cw270 => img.rotate270(),
+        };
+        match (self.flip_horizontal, self.flip_vertical) {
+            (false, false) => img,
+            (true, false) => img.fliph(),
+            (false, true) => img.flipv(),
+            (true, true) => img.fliph().flipv(),
+        }
+    }
+
+    /// Short description like "rotated 90°, flipped horizontally", or
+    /// `None` for the identity.
+    pub fn describe(&self) -> Option<String> {
+        let transform = self.normalized();
+        let mut parts = vec![];
+        if transform.rotation != Rotation::None {
+            parts.push(t!(
+                "transform-rotated",
+                degrees = transform.rotation.degrees()
+            ));
+        }
+        if transform.flip_horizontal {
+            parts.push(t!("transform-flipped-horizontally").to_string());
+        }
+        if transform.flip_vertical {
+            parts.push(t!("transform-flipped-vertically").to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+impl ProcessingStep for Transform {
+    fn name(&self) -> &'static str {
+        "transform"
+    }
+
+    fn apply(&self, img: DynamicImage, _ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        Ok(Transform::apply(self, img))
+    }
+}
+
+/// Decodes `path` like `image::open`, turned upright as its EXIF
+/// orientation says.
+pub fn open_oriented(path: &Path) -> ImageResult<DynamicImage> {
+    let mut decoder = ImageReader::open(path)?.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
@@ -0,0 +1,238 @@
+//! The icon in the system tray that holds the window while it is hidden,
+//! with the progress of the batch in its tooltip. Only Windows and macOS
+//! have one; elsewhere the window is minimized instead.
+
+use image_finalizer::{i18n, t};
+
+use crate::taskbar::Progress;
+
+/// What the menu of the tray icon asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub enum TrayAction {
+    Show,
+    Pause,
+    Resume,
+    Cancel,
+    Quit,
+}
+
+/// What the icon shows: the progress of a running batch and whether it is
+/// paused.
+type State = (Option<Progress>, bool);
+
+pub struct Tray {
+    backend: Option<backend::Backend>,
+    shown: Option<State>,
+}
+
+impl Tray {
+    /// Sets up the icon, hidden until the window goes into the tray.
+    /// `on_action` is called from whatever thread the platform reports
+    /// menu clicks on.
+    pub fn new(on_action: impl Fn(TrayAction) + Send + Sync + 'static) -> Self {
+        let backend = backend::Backend::create(on_action);
+        if backend.is_none() {
+            tracing::debug!("no tray icon on this platform");
+        }
+        Tray {
+            backend,
+            shown: None,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.backend.is_some()
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        if let Some(backend) = &self.backend {
+            if let Err(e) = backend.set_visible(visible) {
+                tracing::warn!("could not show the tray icon: {}", e);
+            }
+        }
+    }
+
+    /// Shows `progress` in the tooltip and offers to pause or resume.
+    pub fn show(&mut self, progress: Option<Progress>, paused: bool) {
+        let state = (progress, paused);
+        if self.shown == Some(state) {
+            return;
+        }
+        self.shown = Some(state);
+        if let Some(backend) = &self.backend {
+            if let Err(e) = backend.show(&tooltip(progress, paused), progress.is_some(), paused) {
+                tracing::warn!("could not update the tray icon: {}", e);
+            }
+        }
+    }
+}
+
+/// Like "Processing 214/800 (27%)".
+fn tooltip(progress: Option<Progress>, paused: bool) -> String {
+    let Some(progress) = progress else {
+        return "Image Finalizer".to_string();
+    };
+    let done = i18n::number(progress.done);
+    let total = i18n::number(progress.total);
+    let percent = progress.done * 100 / progress.total.max(1);
+    if paused {
+        t!("tray-paused", done = done, total = total, percent = percent)
+    } else {
+        t!(
+            "tray-progress",
+            done = done,
+            total = total,
+            percent = percent
+        )
+    }
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+mod backend {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use image_finalizer::t;
+    use tray_icon::{
+        menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+        Icon, MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent,
+    };
+
+    use super::TrayAction;
+
+    /// Side of the icon, drawn rather than shipped.
+    const ICON_SIZE: u32 = 32;
+
+    pub struct Backend {
+        icon: TrayIcon,
+        pause: MenuItem,
+        cancel: MenuItem,
+        /// Whether the pause item resumes, read where clicks arrive.
+        paused: Arc<AtomicBool>,
+    }
+
+    impl Backend {
+        pub fn create(on_action: impl Fn(TrayAction) + Send + Sync + 'static) -> Option<Self> {
+            let show = MenuItem::with_id("show", t!("tray-show"), true, None);
+            let pause = MenuItem::with_id("pause", t!("tray-pause"), false, None);
+            let cancel = MenuItem::with_id("cancel", t!("button-cancel"), false, None);
+            let quit = MenuItem::with_id("quit", t!("tray-quit"), true, None);
+            let menu = Menu::new();
+            let built = menu.append_items(&[
+                &show,
+                &pause,
+                &cancel,
+                &PredefinedMenuItem::separator(),
+                &quit,
+            ]);
+            if let Err(e) = built {
+                tracing::warn!("no tray menu: {}", e);
+                return None;
+            }
+
+            let icon = TrayIconBuilder::new()
+                .with_menu(Box::new(menu))
+                .with_tooltip("Image Finalizer")
+                .with_icon(icon())
+                .build()
+                .inspect_err(|e| tracing::warn!("no tray icon: {}", e))
+                .ok()?;
+            if let Err(e) = icon.set_visible(false) {
+                tracing::warn!("could not hide the tray icon: {}", e);
+            }
+
+            let paused = Arc::new(AtomicBool::new(false));
+            let on_action = Arc::new(on_action);
+            let on_menu = on_action.clone();
+            let menu_paused = paused.clone();
+            MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+                let action = match event.id.0.as_str() {
+                    "show" => TrayAction::Show,
+                    "pause" if menu_paused.load(Ordering::Relaxed) => TrayAction::Resume,
+                    "pause" => TrayAction::Pause,
+                    "cancel" => TrayAction::Cancel,
+                    "quit" => TrayAction::Quit,
+                    _ => return,
+                };
+                on_menu(action);
+            }));
+            TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+                if let TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } = event
+                {
+                    on_action(TrayAction::Show);
+                }
+            }));
+
+            Some(Backend {
+                icon,
+                pause,
+                cancel,
+                paused,
+            })
+        }
+
+        pub fn set_visible(&self, visible: bool) -> tray_icon::Result<()> {
+            self.icon.set_visible(visible)
+        }
+
+        pub fn show(&self, tooltip: &str, running: bool, paused: bool) -> tray_icon::Result<()> {
+            self.paused.store(paused, Ordering::Relaxed);
+            self.pause.set_text(if paused {
+                t!("tray-resume")
+            } else {
+                t!("tray-pause")
+            });
+            self.pause.set_enabled(running);
+            self.cancel.set_enabled(running);
+            self.icon.set_tooltip(Some(tooltip))
+        }
+    }
+
+    /// A white picture frame around a blue photo, the app in a nutshell.
+    fn icon() -> Icon {
+        let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+        for y in 0..ICON_SIZE {
+            for x in 0..ICON_SIZE {
+                let edge = x.min(y).min(ICON_SIZE - 1 - x).min(ICON_SIZE - 1 - y);
+                let pixel = match edge {
+                    0 => [60, 60, 60, 255],
+                    1..=5 => [255, 255, 255, 255],
+                    _ => [40, 90, 150, 255],
+                };
+                rgba.extend_from_slice(&pixel);
+            }
+        }
+        Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).expect("the tray icon is square RGBA")
+    }
+}
+
+/// Trays on Linux would need GTK, which the app doesn't otherwise use.
+#[cfg(not(any(windows, target_os = "macos")))]
+mod backend {
+    use std::convert::Infallible;
+
+    use super::TrayAction;
+
+    pub enum Backend {}
+
+    impl Backend {
+        pub fn create(_: impl Fn(TrayAction) + Send + Sync + 'static) -> Option<Self> {
+            None
+        }
+
+        pub fn set_visible(&self, _: bool) -> Result<(), Infallible> {
+            match *self {}
+        }
+
+        pub fn show(&self, _: &str, _: bool, _: bool) -> Result<(), Infallible> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Cutting off margins an input already has, like the white border of an
+//! earlier export, so the new border doesn't add to it.
+
+use image::{DynamicImage, GenericImageView, Rgba};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ProcessError,
+    pipeline::{FileContext, ProcessingStep},
+    t,
+    transform::Transform,
+};
+
+/// Which color a margin has to have to be cut off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "app", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum MarginColor {
+    #[default]
+    White,
+    Black,
+    /// Whatever color the top left pixel has, transparency included.
+    Corner,
+}
+
+impl MarginColor {
+    pub const ALL: [MarginColor; 3] = [MarginColor::White, MarginColor::Black, MarginColor::Corner];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MarginColor::White => t!("trim-color-white"),
+            MarginColor::Black => t!("trim-color-black"),
+            MarginColor::Corner => t!("trim-color-corner"),
+        }
+    }
+}
+
+/// Settings of the trim before the border. Off by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Trim {
+    pub enabled: bool,
+    pub color: MarginColor,
+    /// How far each channel of a margin pixel may be from `color`, so
+    /// compression noise and paper texture still count as margin.
+    pub tolerance: u8,
+}
+
+impl Default for Trim {
+    fn default() -> Self {
+        Trim {
+            enabled: false,
+            color: MarginColor::White,
+            tolerance: 24,
+        }
+    }
+}
+
+impl Trim {
+    pub fn is_off(&self) -> bool {
+        !self.enabled
+    }
+
+    /// The margin on every side of `img`: whole rows and columns at its
+    /// edges within `tolerance` of the margin color. An image that is
+    /// nothing but margin has none, as there is nothing to keep.
+    pub fn margins(&self, img: &DynamicImage) -> Margins {
+        let (width, height) = img.dimensions();
+        if !self.enabled || width == 0 || height == 0 {
+            return Margins::default();
+        }
+        let reference = match self.color {
+            MarginColor::White => Rgba([255, 255, 255, 255]),
+            MarginColor::Black => Rgba([0, 0, 0, 255]),
+            MarginColor::Corner => img.get_pixel(0, 0),
+        };
+        let is_margin = |x, y| {
+            img.get_pixel(x, y)
+                .0
+                .iter()
+                .zip(reference.0)
+                .all(|(channel, wanted)| channel.abs_diff(wanted) <= self.tolerance)
+        };
+
+        let row = |y| (0..width).all(|x| is_margin(x, y));
+        let top = (0..height).take_while(|&y| row(y)).count() as u32;
+        if top == height {
+            return Margins::default();
+        }
+        let bottom = (top..height).rev().take_while(|&y| row(y)).count() as u32;
+
+        // Some row in between has a pixel that is not margin, so neither
+        // side can take all columns.
+        let rows = top..height - bottom;
+        let column = |x| rows.clone().all(|y| is_margin(x, y));
+        let left = (0..width).take_while(|&x| column(x)).count() as u32;
+        let right = (left..width).rev().take_while(|&x| column(x)).count() as u32;
+
+        Margins {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+}
+
+impl ProcessingStep for Trim {
+    fn name(&self) -> &'static str {
+        "trim"
+    }
+
+    fn apply(&self, img: DynamicImage, _ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        Ok(self.margins(&img).cut(img))
+    }
+}
+
+/// Pixels cut off each side of a photo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Margins {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Margins {
+    pub fn is_empty(&self) -> bool {
+        *self == Margins::default()
+    }
+
+    /// Size of what is left of a `width`×`height` photo.
+    pub fn inside(&self, width: u32, height: u32) -> (u32, u32) {
+        (
+            width.saturating_sub(self.left + self.right),
+            height.saturating_sub(self.top + self.bottom),
+        )
+    }
+
+    /// What is left of `img` with the margins cut off. An image without
+    /// margins is passed through untouched.
+    pub fn cut(&self, img: DynamicImage) -> DynamicImage {
+        if self.is_empty() {
+            return img;
+        }
+        let (width, height) = self.inside(img.width(), img.height());
+        img.crop_imm(self.left, self.top, width, height)
+    }
+
+    /// The same margins as sides of the photo once `transform` turned it.
+    pub fn transformed(self, transform: &Transform) -> Self {
+        let mut margins = self;
+        for _ in 0..transform.rotation.degrees() / 90 {
+            // A quarter turn clockwise brings the left side to the top.
+            margins = Margins {
+                top: margins.left,
+                right: margins.top,
+                bottom: margins.right,
+                left: margins.bottom,
+            };
+        }
+        if transform.flip_horizontal {
+            std::mem::swap(&mut margins.left, &mut margins.right);
+        }
+        if transform.flip_vertical {
+            std::mem::swap(&mut margins.top, &mut margins.bottom);
+        }
+        margins
+    }
+
+    /// Short description like "trimmed 12 px top, 0 right, 12 bottom,
+    /// 3 left", or `None` if nothing was cut off.
+    pub fn describe(&self) -> Option<String> {
+        (!self.is_empty()).then(|| {
+            t!(
+                "trim-trimmed",
+                top = self.top,
+                right = self.right,
+                bottom = self.bottom,
+                left = self.left
+            )
+        })
+    }
+}
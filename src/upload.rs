@@ -0,0 +1,312 @@
+//! Uploading the outputs of a batch to an S3-compatible bucket, like
+//! Backblaze B2 or MinIO. The settings and keys are always available; the
+//! upload itself needs the `s3` feature.
+//!
+//! Credentials are never part of `UploadSettings`, so they can't end up in
+//! saved settings or config files. They come from the environment or the
+//! system keyring instead.
+
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+/// Uploads running at the same time.
+pub const MAX_CONCURRENT_UPLOADS: usize = 4;
+
+/// Tries of one upload before it is given up on.
+pub const MAX_ATTEMPTS: u32 = 4;
+
+/// Variables the credentials are read from before the keyring is asked.
+pub const ACCESS_KEY_VARIABLE: &str = "AWS_ACCESS_KEY_ID";
+pub const SECRET_KEY_VARIABLE: &str = "AWS_SECRET_ACCESS_KEY";
+
+/// Where the outputs are uploaded to.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadSettings {
+    /// Like `https://s3.us-west-004.backblazeb2.com`.
+    pub endpoint: String,
+    /// Region of the bucket. Many services accept anything here.
+    pub region: String,
+    pub bucket: String,
+    /// Put in front of every key, like `clients/smith/`.
+    pub prefix: String,
+}
+
+impl UploadSettings {
+    /// Whether there is an endpoint and a bucket to upload to.
+    pub fn is_complete(&self) -> bool {
+        !self.endpoint.trim().is_empty() && !self.bucket.trim().is_empty()
+    }
+
+    /// Name of the keyring entry holding the credentials for the bucket.
+    pub fn keyring_user(&self) -> String {
+        format!("{}@{}", self.bucket.trim(), self.endpoint.trim())
+    }
+
+    /// Address of `key`, in the path style every S3-compatible service
+    /// understands. Only reachable without signing in public buckets.
+    pub fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.endpoint.trim().trim_end_matches('/'),
+            self.bucket.trim(),
+            key
+        )
+    }
+}
+
+/// Key of the output at `path` in the bucket: its path below `output_dir`
+/// with forward slashes, behind `prefix`. Outputs outside of `output_dir`
+/// keep only their file name.
+pub fn object_key(prefix: &str, output_dir: &Path, path: &Path) -> String {
+    let relative = path
+        .strip_prefix(output_dir)
+        .ok()
+        .or_else(|| path.file_name().map(Path::new))
+        .unwrap_or(path);
+    let relative: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    let prefix = prefix.trim().trim_start_matches('/');
+    match prefix {
+        "" => relative.join("/"),
+        prefix => format!("{}/{}", prefix.trim_end_matches('/'), relative.join("/")),
+    }
+}
+
+/// MIME type sent along with an output of `path`.
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("tif" | "tiff") => "image/tiff",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("zip") => "application/zip",
+        Some("json") => "application/json",
+        Some("csv") => "text/csv",
+        _ => "application/octet-stream",
+    }
+}
+
+/// How long to wait before trying again after `failed` failed tries:
+/// 1 s, then 2 s, 4 s and so on.
+pub fn backoff(failed: u32) -> Duration {
+    Duration::from_secs(1 << failed.saturating_sub(1).min(6))
+}
+
+#[cfg(feature = "s3")]
+pub use client::*;
+
+#[cfg(feature = "s3")]
+mod client {
+    use std::{path::Path, sync::Arc};
+
+    use s3::{creds, Bucket, Region};
+    use tokio::sync::Semaphore;
+
+    use super::{
+        backoff, content_type, UploadSettings, ACCESS_KEY_VARIABLE, MAX_ATTEMPTS,
+        MAX_CONCURRENT_UPLOADS, SECRET_KEY_VARIABLE,
+    };
+    use crate::t;
+
+    /// Service of the keyring entries, next to `UploadSettings::keyring_user`.
+    const KEYRING_SERVICE: &str = "image-finalizer";
+
+    /// Why an output could not be uploaded.
+    #[derive(Debug, thiserror::Error)]
+    pub enum UploadError {
+        #[error("{}", t!("upload-no-credentials"))]
+        NoCredentials,
+        #[error("{}", t!("upload-incomplete"))]
+        Incomplete,
+        #[error("{}", t!("upload-keyring", error = .0))]
+        Keyring(#[from] keyring::Error),
+        #[error("{}", t!("upload-read", error = .0))]
+        Read(#[from] std::io::Error),
+        #[error("{}", t!("upload-status", status = .0))]
+        Status(u16),
+        #[error(transparent)]
+        S3(#[from] s3::error::S3Error),
+    }
+
+    /// Where `Credentials` were found.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CredentialSource {
+        Environment,
+        Keyring,
+    }
+
+    /// Access key pair of a bucket.
+    #[derive(Clone, PartialEq, Eq)]
+    pub struct Credentials {
+        pub access_key: String,
+        pub secret_key: String,
+    }
+
+    // Keeps the secret out of logs.
+    impl std::fmt::Debug for Credentials {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Credentials")
+                .field("access_key", &self.access_key)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl Credentials {
+        /// The credentials in `AWS_ACCESS_KEY_ID` and
+        /// `AWS_SECRET_ACCESS_KEY`, if both are set.
+        pub fn from_env() -> Option<Credentials> {
+            Some(Credentials {
+                access_key: std::env::var(ACCESS_KEY_VARIABLE).ok()?,
+                secret_key: std::env::var(SECRET_KEY_VARIABLE).ok()?,
+            })
+        }
+
+        /// The credentials the environment or the keyring has for the
+        /// bucket of `settings`, in that order.
+        ///
+        /// Blocks on the keyring. Call it on a thread of its own, not on
+        /// one of an async runtime, where the Secret Service can deadlock.
+        pub fn find(
+            settings: &UploadSettings,
+        ) -> Result<Option<(Credentials, CredentialSource)>, UploadError> {
+            if let Some(credentials) = Credentials::from_env() {
+                return Ok(Some((credentials, CredentialSource::Environment)));
+            }
+            let secret = match keyring_entry(settings)?.get_password() {
+                Ok(secret) => secret,
+                Err(keyring::Error::NoEntry) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            // Access key IDs never contain a colon.
+            Ok(secret.split_once(':').map(|(access_key, secret_key)| {
+                (
+                    Credentials {
+                        access_key: access_key.to_string(),
+                        secret_key: secret_key.to_string(),
+                    },
+                    CredentialSource::Keyring,
+                )
+            }))
+        }
+
+        /// Stores the credentials in the keyring for the bucket of
+        /// `settings`. Blocks like `find`.
+        pub fn save(&self, settings: &UploadSettings) -> Result<(), UploadError> {
+            let secret = format!("{}:{}", self.access_key.trim(), self.secret_key.trim());
+            Ok(keyring_entry(settings)?.set_password(&secret)?)
+        }
+
+        /// Removes the credentials of the bucket of `settings` from the
+        /// keyring. Blocks like `find`.
+        pub fn forget(settings: &UploadSettings) -> Result<(), UploadError> {
+            match keyring_entry(settings)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(e.into()),
+            }
+        }
+    }
+
+    fn keyring_entry(settings: &UploadSettings) -> Result<keyring::Entry, UploadError> {
+        Ok(keyring::Entry::new(
+            KEYRING_SERVICE,
+            &settings.keyring_user(),
+        )?)
+    }
+
+    /// Uploads files to one bucket, `MAX_CONCURRENT_UPLOADS` at a time.
+    /// Cheap to clone; clones share the limit.
+    #[derive(Clone)]
+    pub struct Uploader {
+        bucket: Arc<Bucket>,
+        permits: Arc<Semaphore>,
+    }
+
+    impl std::fmt::Debug for Uploader {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Uploader")
+                .field("bucket", &self.bucket.name)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl Uploader {
+        pub fn new(
+            settings: &UploadSettings,
+            credentials: Credentials,
+        ) -> Result<Uploader, UploadError> {
+            if !settings.is_complete() {
+                return Err(UploadError::Incomplete);
+            }
+            let region = Region::Custom {
+                region: match settings.region.trim() {
+                    "" => "us-east-1".to_string(),
+                    region => region.to_string(),
+                },
+                endpoint: settings.endpoint.trim().trim_end_matches('/').to_string(),
+            };
+            let credentials = creds::Credentials::new(
+                Some(&credentials.access_key),
+                Some(&credentials.secret_key),
+                None,
+                None,
+                None,
+            )
+            .map_err(s3::error::S3Error::from)?;
+            let bucket =
+                Bucket::new(settings.bucket.trim(), region, credentials)?.with_path_style();
+            Ok(Uploader {
+                bucket: Arc::from(bucket),
+                permits: Arc::new(Semaphore::new(MAX_CONCURRENT_UPLOADS)),
+            })
+        }
+
+        /// Uploads the file at `path` as `key` once a slot is free, trying
+        /// up to `MAX_ATTEMPTS` times with `backoff` in between.
+        /// `on_attempt` is called with the number of each try as it starts.
+        pub async fn upload(
+            &self,
+            path: &Path,
+            key: &str,
+            mut on_attempt: impl FnMut(u32),
+        ) -> Result<(), UploadError> {
+            let _permit = self
+                .permits
+                .acquire()
+                .await
+                .expect("the upload limit is never closed");
+            let mut attempt = 1;
+            loop {
+                on_attempt(attempt);
+                match self.put(path, key).await {
+                    Ok(()) => return Ok(()),
+                    Err(e) if attempt < MAX_ATTEMPTS => {
+                        tracing::debug!("upload of {} failed, retrying: {}", path.display(), e);
+                        tokio::time::sleep(backoff(attempt)).await;
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        async fn put(&self, path: &Path, key: &str) -> Result<(), UploadError> {
+            let mut file = tokio::fs::File::open(path).await?;
+            let response = self
+                .bucket
+                .put_object_stream_with_content_type(&mut file, key, content_type(path))
+                .await?;
+            match response.status_code() {
+                200..=299 => Ok(()),
+                status => Err(UploadError::Status(status)),
+            }
+        }
+    }
+}
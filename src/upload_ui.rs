@@ -0,0 +1,927 @@
+//! Uploading the outputs of a batch to a bucket or an SFTP server as they
+//! are written, what became of each upload, and the settings for it.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+#[cfg(feature = "s3")]
+use std::{mem, thread};
+
+#[cfg(feature = "sftp")]
+use image_finalizer::sftp::{self, HostKey, KnownHosts, SftpSettings, SftpUploader};
+#[cfg(feature = "s3")]
+use image_finalizer::upload::{
+    self, CredentialSource, Credentials, UploadError, UploadSettings, Uploader,
+};
+use image_finalizer::{i18n, t};
+#[cfg(feature = "sftp")]
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use tokio::{runtime::Runtime, sync::mpsc::UnboundedSender, task::AbortHandle};
+use tracing::Level;
+
+use crate::{config::Config, MessageResult};
+
+#[cfg(feature = "s3")]
+const UPLOAD_KEY: &str = "upload";
+const UPLOAD_ENABLED_KEY: &str = "upload_enabled";
+const UPLOAD_TARGET_KEY: &str = "upload_target";
+#[cfg(feature = "sftp")]
+const SFTP_KEY: &str = "sftp";
+
+/// Kind of server the outputs are uploaded to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Target {
+    #[default]
+    S3,
+    Sftp,
+}
+
+impl Target {
+    /// The targets this build can upload to.
+    fn available() -> Vec<Target> {
+        vec![
+            #[cfg(feature = "s3")]
+            Target::S3,
+            #[cfg(feature = "sftp")]
+            Target::Sftp,
+        ]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Target::S3 => t!("upload-target-s3"),
+            Target::Sftp => t!("upload-target-sftp"),
+        }
+    }
+}
+
+/// Settings to connect with before a batch.
+#[derive(Debug, Clone)]
+pub enum Connect {
+    #[cfg(feature = "s3")]
+    S3(UploadSettings),
+    #[cfg(feature = "sftp")]
+    Sftp(SftpSettings),
+}
+
+impl Connect {
+    /// The bucket or server, for messages.
+    pub fn name(&self) -> String {
+        match self {
+            #[cfg(feature = "s3")]
+            Connect::S3(settings) => settings.bucket.clone(),
+            #[cfg(feature = "sftp")]
+            Connect::Sftp(settings) => settings.address(),
+        }
+    }
+}
+
+/// A bucket or server the pre-flight checks connected to.
+#[derive(Debug, Clone)]
+pub enum Destination {
+    #[cfg(feature = "s3")]
+    S3 {
+        uploader: Uploader,
+        settings: UploadSettings,
+    },
+    #[cfg(feature = "sftp")]
+    Sftp(SftpUploader),
+}
+
+impl Destination {
+    /// Key or remote path of the output at `path`.
+    pub fn key(&self, output_dir: &Path, path: &Path) -> String {
+        match self {
+            #[cfg(feature = "s3")]
+            Destination::S3 { settings, .. } => {
+                upload::object_key(&settings.prefix, output_dir, path)
+            }
+            #[cfg(feature = "sftp")]
+            Destination::Sftp(uploader) => uploader.settings().remote_path(output_dir, path),
+        }
+    }
+
+    /// Address of the upload at `key`.
+    pub fn url(&self, key: &str) -> String {
+        match self {
+            #[cfg(feature = "s3")]
+            Destination::S3 { settings, .. } => settings.object_url(key),
+            #[cfg(feature = "sftp")]
+            Destination::Sftp(uploader) => uploader.settings().url(key),
+        }
+    }
+
+    /// The bucket or server, for the summary.
+    pub fn name(&self) -> String {
+        match self {
+            #[cfg(feature = "s3")]
+            Destination::S3 { settings, .. } => settings.bucket.clone(),
+            #[cfg(feature = "sftp")]
+            Destination::Sftp(uploader) => uploader.settings().host.clone(),
+        }
+    }
+
+    pub async fn upload(
+        &self,
+        path: &Path,
+        key: &str,
+        on_attempt: impl FnMut(u32),
+    ) -> Result<(), String> {
+        match self {
+            #[cfg(feature = "s3")]
+            Destination::S3 { uploader, .. } => uploader
+                .upload(path, key, on_attempt)
+                .await
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "sftp")]
+            Destination::Sftp(uploader) => uploader
+                .upload(path, key, on_attempt)
+                .await
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// What became of the upload of one output.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadState {
+    Queued,
+    /// `attempt` counts from 1; later ones are retries.
+    Uploading {
+        attempt: u32,
+    },
+    Done,
+    Failed(String),
+}
+
+/// News for `Uploads` from the threads and tasks it started.
+#[derive(Debug)]
+pub enum Message {
+    /// The pre-flight checks connected to the bucket or server.
+    Ready(Destination),
+    /// `generation` tells reports of cancelled uploads apart.
+    State {
+        path: PathBuf,
+        state: UploadState,
+        generation: u32,
+    },
+    /// A keyring or known hosts change finished, with the text to show.
+    Notice(Result<String, String>),
+    /// The key file to sign in to the SFTP server with was picked.
+    #[cfg(feature = "sftp")]
+    KeyFile(PathBuf),
+    /// The host key of `address` was fetched, as a fingerprint.
+    #[cfg(feature = "sftp")]
+    HostKey {
+        address: String,
+        result: Result<String, String>,
+    },
+}
+
+/// What to do after `Uploads::show_summary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryAction {
+    RetryFailed,
+    Cancel,
+}
+
+struct Upload {
+    key: String,
+    state: UploadState,
+}
+
+pub struct Uploads {
+    pub enabled: bool,
+    target: Target,
+    #[cfg(feature = "s3")]
+    settings: UploadSettings,
+    /// Typed in to be saved in the keyring; never persisted.
+    #[cfg(feature = "s3")]
+    access_key: String,
+    #[cfg(feature = "s3")]
+    secret_key: String,
+    #[cfg(feature = "sftp")]
+    sftp: SftpSettings,
+    #[cfg(feature = "sftp")]
+    known_hosts: KnownHosts,
+    /// The server whose key was last fetched, and the fingerprint once it
+    /// arrived.
+    #[cfg(feature = "sftp")]
+    host_key: Option<(String, Option<Result<String, String>>)>,
+    /// Connected by the pre-flight checks, for the batch about to start.
+    pending: Option<Destination>,
+    /// Where the running or last batch is uploaded to, if it is.
+    destination: Option<Destination>,
+    output_dir: PathBuf,
+    /// Outputs in the order they were queued.
+    order: Vec<PathBuf>,
+    uploads: HashMap<PathBuf, Upload>,
+    tasks: Vec<AbortHandle>,
+    /// Counts cancellations, so late reports of cancelled uploads are
+    /// ignored.
+    generation: u32,
+}
+
+impl Uploads {
+    /// The settings from `config`, or those of the last session.
+    pub fn load(storage: Option<&dyn eframe::Storage>, config: &Config) -> Uploads {
+        let configured = match (&config.upload, &config.sftp) {
+            (Some(_), _) => Some(Target::S3),
+            (None, Some(_)) => Some(Target::Sftp),
+            (None, None) => None,
+        };
+        let enabled = configured.is_some()
+            || storage
+                .and_then(|storage| eframe::get_value(storage, UPLOAD_ENABLED_KEY))
+                .unwrap_or(false);
+        let available = Target::available();
+        let target = configured
+            .or_else(|| storage.and_then(|storage| eframe::get_value(storage, UPLOAD_TARGET_KEY)))
+            .filter(|target| available.contains(target))
+            .unwrap_or(available[0]);
+        Uploads {
+            enabled,
+            target,
+            #[cfg(feature = "s3")]
+            settings: config.upload.clone().unwrap_or_else(|| {
+                storage
+                    .and_then(|storage| eframe::get_value(storage, UPLOAD_KEY))
+                    .unwrap_or_default()
+            }),
+            #[cfg(feature = "s3")]
+            access_key: String::new(),
+            #[cfg(feature = "s3")]
+            secret_key: String::new(),
+            #[cfg(feature = "sftp")]
+            sftp: config.sftp.clone().unwrap_or_else(|| {
+                storage
+                    .and_then(|storage| eframe::get_value(storage, SFTP_KEY))
+                    .unwrap_or_default()
+            }),
+            #[cfg(feature = "sftp")]
+            known_hosts: load_known_hosts(),
+            #[cfg(feature = "sftp")]
+            host_key: None,
+            pending: None,
+            destination: None,
+            output_dir: PathBuf::new(),
+            order: vec![],
+            uploads: HashMap::new(),
+            tasks: vec![],
+            generation: 0,
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, UPLOAD_ENABLED_KEY, &self.enabled);
+        eframe::set_value(storage, UPLOAD_TARGET_KEY, &self.target);
+        #[cfg(feature = "s3")]
+        eframe::set_value(storage, UPLOAD_KEY, &self.settings);
+        #[cfg(feature = "sftp")]
+        eframe::set_value(storage, SFTP_KEY, &self.sftp);
+    }
+
+    /// The settings to connect with before a batch, if it is uploaded.
+    pub fn to_connect(&self) -> Option<Connect> {
+        if !self.enabled {
+            return None;
+        }
+        match self.target {
+            #[cfg(feature = "s3")]
+            Target::S3 => Some(Connect::S3(self.settings.clone())),
+            #[cfg(feature = "sftp")]
+            Target::Sftp => Some(Connect::Sftp(self.sftp.clone())),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Starts uploading the outputs of a batch into `output_dir`, if the
+    /// pre-flight checks connected to the bucket or server.
+    pub fn begin(&mut self, output_dir: &Path) {
+        self.order.clear();
+        self.uploads.clear();
+        self.tasks.clear();
+        self.destination = self.pending.take().filter(|_| self.enabled);
+        if self.enabled && self.destination.is_none() {
+            tracing::info!("not uploading a batch that skipped the pre-flight checks");
+        }
+        self.output_dir = output_dir.to_path_buf();
+    }
+
+    /// Queues `outputs` for upload, if the batch is uploaded.
+    pub fn queue(
+        &mut self,
+        outputs: &[PathBuf],
+        rt: &Runtime,
+        tx: &UnboundedSender<MessageResult>,
+        ctx: &egui::Context,
+    ) {
+        let Some(destination) = &self.destination else {
+            return;
+        };
+        for path in outputs {
+            let key = destination.key(&self.output_dir, path);
+            let task = spawn(destination, path, &key, self.generation, rt, tx, ctx);
+            self.tasks.push(task);
+            self.order.push(path.clone());
+            self.uploads.insert(
+                path.clone(),
+                Upload {
+                    key,
+                    state: UploadState::Queued,
+                },
+            );
+        }
+    }
+
+    /// Uploads `path` again from its file, without processing its input
+    /// again.
+    pub fn retry(
+        &mut self,
+        path: &Path,
+        rt: &Runtime,
+        tx: &UnboundedSender<MessageResult>,
+        ctx: &egui::Context,
+    ) {
+        let (Some(destination), Some(upload)) = (&self.destination, self.uploads.get_mut(path))
+        else {
+            return;
+        };
+        upload.state = UploadState::Queued;
+        let task = spawn(destination, path, &upload.key, self.generation, rt, tx, ctx);
+        self.tasks.retain(|task| !task.is_finished());
+        self.tasks.push(task);
+    }
+
+    /// Whether any upload has yet to finish.
+    pub fn is_uploading(&self) -> bool {
+        self.uploads.values().any(|upload| {
+            matches!(
+                upload.state,
+                UploadState::Queued | UploadState::Uploading { .. }
+            )
+        })
+    }
+
+    /// Stops every upload that has yet to finish. They can be retried.
+    pub fn cancel(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        self.generation += 1;
+        for upload in self.uploads.values_mut() {
+            if matches!(
+                upload.state,
+                UploadState::Queued | UploadState::Uploading { .. }
+            ) {
+                upload.state = UploadState::Failed(t!("upload-cancelled").to_string());
+            }
+        }
+    }
+
+    /// Applies `message`, returning a status to show, if any.
+    pub fn handle(&mut self, message: Message) -> Option<(Level, String)> {
+        match message {
+            Message::Ready(destination) => self.pending = Some(destination),
+            Message::State {
+                path,
+                state,
+                generation,
+            } => {
+                if generation != self.generation {
+                    return None;
+                }
+                if let UploadState::Failed(e) = &state {
+                    tracing::warn!("could not upload {}: {}", path.display(), e);
+                }
+                if let Some(upload) = self.uploads.get_mut(&path) {
+                    upload.state = state;
+                }
+            }
+            Message::Notice(Ok(text)) => return Some((Level::INFO, text)),
+            Message::Notice(Err(text)) => return Some((Level::WARN, text)),
+            #[cfg(feature = "sftp")]
+            Message::KeyFile(path) => self.sftp.key_file = Some(path),
+            #[cfg(feature = "sftp")]
+            Message::HostKey { address, result } => {
+                // Trusted from another window or a headless run meanwhile.
+                self.known_hosts = load_known_hosts();
+                self.host_key = Some((address, Some(result)));
+            }
+        }
+        None
+    }
+
+    /// The upload of `output` in a row of the results, returning whether
+    /// retrying it was asked for.
+    pub fn show_state(&self, ui: &mut egui::Ui, output: &Path) -> bool {
+        let (Some(upload), Some(destination)) = (self.uploads.get(output), &self.destination)
+        else {
+            return false;
+        };
+        match &upload.state {
+            UploadState::Queued => {
+                ui.weak(t!("upload-queued"));
+            }
+            UploadState::Uploading { attempt: 1 } => {
+                ui.weak(t!("upload-uploading"));
+            }
+            UploadState::Uploading { attempt } => {
+                ui.weak(t!("upload-retrying", attempt = attempt));
+            }
+            UploadState::Done => {
+                ui.label(t!("upload-done"))
+                    .on_hover_text(destination.url(&upload.key));
+            }
+            UploadState::Failed(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, t!("upload-failed"))
+                    .on_hover_text(e);
+                return ui.small_button(t!("upload-retry")).clicked();
+            }
+        }
+        false
+    }
+
+    /// What was uploaded so far, with the keys and addresses to copy.
+    pub fn show_summary(&self, ui: &mut egui::Ui) -> Option<SummaryAction> {
+        let destination = self.destination.as_ref()?;
+        if self.order.is_empty() {
+            return None;
+        }
+        let done: Vec<&str> = self
+            .order
+            .iter()
+            .filter_map(|path| self.uploads.get(path))
+            .filter(|upload| upload.state == UploadState::Done)
+            .map(|upload| upload.key.as_str())
+            .collect();
+        let failed = self.failed().count();
+        let mut action = None;
+        egui::CollapsingHeader::new(t!(
+            "upload-header",
+            done = i18n::number(done.len()),
+            total = i18n::number(self.order.len()),
+            bucket = destination.name()
+        ))
+        .id_salt("upload_summary")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!done.is_empty(), egui::Button::new(t!("upload-copy-urls")))
+                    .clicked()
+                {
+                    let urls: Vec<_> = done.iter().map(|key| destination.url(key)).collect();
+                    ui.ctx().copy_text(urls.join("\n"));
+                }
+                if ui
+                    .add_enabled(!done.is_empty(), egui::Button::new(t!("upload-copy-keys")))
+                    .clicked()
+                {
+                    ui.ctx().copy_text(done.join("\n"));
+                }
+                if failed > 0
+                    && ui
+                        .button(t!("upload-retry-failed", count = i18n::number(failed)))
+                        .clicked()
+                {
+                    action = Some(SummaryAction::RetryFailed);
+                }
+                if self.is_uploading() && ui.button(t!("upload-cancel")).clicked() {
+                    action = Some(SummaryAction::Cancel);
+                }
+            });
+            let row_height = ui.spacing().interact_size.y;
+            egui::ScrollArea::vertical()
+                .id_salt("upload_keys")
+                .max_height(150.0)
+                .show_rows(ui, row_height, done.len(), |ui, rows| {
+                    for key in &done[rows] {
+                        ui.label(*key).on_hover_text(destination.url(key));
+                    }
+                });
+        });
+        action
+    }
+
+    /// Uploads every output whose upload failed again.
+    pub fn retry_failed(
+        &mut self,
+        rt: &Runtime,
+        tx: &UnboundedSender<MessageResult>,
+        ctx: &egui::Context,
+    ) {
+        let failed: Vec<PathBuf> = self.failed().cloned().collect();
+        for path in failed {
+            self.retry(&path, rt, tx, ctx);
+        }
+    }
+
+    fn failed(&self) -> impl Iterator<Item = &PathBuf> {
+        self.order.iter().filter(|path| {
+            self.uploads
+                .get(*path)
+                .is_some_and(|upload| matches!(upload.state, UploadState::Failed(_)))
+        })
+    }
+
+    /// Where to upload to, and how to sign in there.
+    #[cfg_attr(not(feature = "sftp"), allow(unused_variables))]
+    pub fn show_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+        rt: &Runtime,
+        tx: &UnboundedSender<MessageResult>,
+        ctx: &egui::Context,
+    ) {
+        ui.checkbox(&mut self.enabled, t!("upload-enabled"))
+            .on_hover_text(t!("upload-enabled-hover"));
+        if !self.enabled {
+            return;
+        }
+
+        let available = Target::available();
+        if available.len() > 1 {
+            ui.horizontal(|ui| {
+                for target in available {
+                    ui.radio_value(&mut self.target, target, target.name());
+                }
+            });
+        }
+        match self.target {
+            #[cfg(feature = "s3")]
+            Target::S3 => self.show_s3_settings(ui, tx, ctx),
+            #[cfg(feature = "sftp")]
+            Target::Sftp => self.show_sftp_settings(ui, rt, tx, ctx),
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+
+    /// The bucket and the credentials for it.
+    #[cfg(feature = "s3")]
+    fn show_s3_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+        tx: &UnboundedSender<MessageResult>,
+        ctx: &egui::Context,
+    ) {
+        egui::Grid::new("upload_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (name, value, hint) in [
+                    (
+                        t!("upload-endpoint"),
+                        &mut self.settings.endpoint,
+                        "https://s3.example.com",
+                    ),
+                    (t!("upload-region"), &mut self.settings.region, "us-east-1"),
+                    (t!("upload-bucket"), &mut self.settings.bucket, ""),
+                    (t!("upload-prefix"), &mut self.settings.prefix, "photos/"),
+                ] {
+                    let label = ui.label(name);
+                    ui.add(egui::TextEdit::singleline(value).hint_text(hint))
+                        .labelled_by(label.id);
+                    ui.end_row();
+                }
+            });
+
+        ui.label(t!(
+            "upload-credentials",
+            access = upload::ACCESS_KEY_VARIABLE,
+            secret = upload::SECRET_KEY_VARIABLE
+        ));
+        egui::Grid::new("upload_credentials")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let label = ui.label(t!("upload-access-key"));
+                ui.text_edit_singleline(&mut self.access_key)
+                    .labelled_by(label.id);
+                ui.end_row();
+                let label = ui.label(t!("upload-secret-key"));
+                ui.add(egui::TextEdit::singleline(&mut self.secret_key).password(true))
+                    .labelled_by(label.id);
+                ui.end_row();
+            });
+        ui.horizontal(|ui| {
+            let complete = self.settings.is_complete();
+            let typed = !self.access_key.trim().is_empty() && !self.secret_key.trim().is_empty();
+            if ui
+                .add_enabled(
+                    complete && typed,
+                    egui::Button::new(t!("upload-save-credentials")),
+                )
+                .clicked()
+            {
+                let credentials = Credentials {
+                    access_key: mem::take(&mut self.access_key),
+                    secret_key: mem::take(&mut self.secret_key),
+                };
+                let settings = self.settings.clone();
+                on_keyring(tx, ctx, move || {
+                    credentials.save(&settings)?;
+                    Ok(t!(
+                        "upload-credentials-saved",
+                        bucket = settings.keyring_user()
+                    ))
+                });
+            }
+            if ui
+                .add_enabled(complete, egui::Button::new(t!("upload-check-credentials")))
+                .clicked()
+            {
+                let settings = self.settings.clone();
+                on_keyring(tx, ctx, move || {
+                    Ok(match Credentials::find(&settings)? {
+                        Some((_, CredentialSource::Environment)) => {
+                            t!("upload-credentials-environment").to_string()
+                        }
+                        Some((_, CredentialSource::Keyring)) => {
+                            t!("upload-credentials-keyring").to_string()
+                        }
+                        None => return Err(UploadError::NoCredentials),
+                    })
+                });
+            }
+            if ui
+                .add_enabled(complete, egui::Button::new(t!("upload-forget-credentials")))
+                .clicked()
+            {
+                let settings = self.settings.clone();
+                on_keyring(tx, ctx, move || {
+                    Credentials::forget(&settings)?;
+                    Ok(t!(
+                        "upload-credentials-forgotten",
+                        bucket = settings.keyring_user()
+                    ))
+                });
+            }
+        });
+    }
+
+    /// The server, how to sign in there and whether its key is trusted.
+    #[cfg(feature = "sftp")]
+    fn show_sftp_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+        rt: &Runtime,
+        tx: &UnboundedSender<MessageResult>,
+        ctx: &egui::Context,
+    ) {
+        egui::Grid::new("sftp_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let label = ui.label(t!("sftp-host"));
+                ui.add(egui::TextEdit::singleline(&mut self.sftp.host).hint_text("example.com"))
+                    .labelled_by(label.id);
+                ui.end_row();
+                let label = ui.label(t!("sftp-port"));
+                ui.add(egui::DragValue::new(&mut self.sftp.port).range(1..=u16::MAX))
+                    .labelled_by(label.id);
+                ui.end_row();
+                let label = ui.label(t!("sftp-username"));
+                ui.add(egui::TextEdit::singleline(&mut self.sftp.username))
+                    .labelled_by(label.id);
+                ui.end_row();
+                let label = ui.label(t!("sftp-base-path"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.sftp.base_path).hint_text("/srv/photos"),
+                )
+                .labelled_by(label.id)
+                .on_hover_text(t!("sftp-base-path-hover"));
+                ui.end_row();
+                let label = ui.label(t!("sftp-retries"));
+                ui.add(egui::DragValue::new(&mut self.sftp.retries).range(0..=10))
+                    .labelled_by(label.id);
+                ui.end_row();
+            });
+
+        let mut use_key_file = self.sftp.key_file.is_some();
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut use_key_file, false, t!("sftp-use-agent"))
+                .on_hover_text(t!("sftp-agent-hover"));
+            ui.radio_value(&mut use_key_file, true, t!("sftp-key-file"));
+        });
+        if !use_key_file {
+            self.sftp.key_file = None;
+        } else if self.sftp.key_file.is_none() {
+            self.sftp.key_file = Some(PathBuf::new());
+        }
+        if let Some(key_file) = &self.sftp.key_file {
+            ui.horizontal(|ui| {
+                ui.label(key_file.display().to_string());
+                if ui.button(t!("sftp-choose-key")).clicked() {
+                    let (tx, ctx) = (tx.clone(), ctx.clone());
+                    rt.spawn(async move {
+                        if let Some(path) = FileDialog::new().pick_file() {
+                            let _ = tx.send(MessageResult::Upload(Message::KeyFile(path)));
+                        }
+                        ctx.request_repaint();
+                    });
+                }
+            });
+        }
+
+        let address = self.sftp.address();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    self.sftp.is_complete(),
+                    egui::Button::new(t!("sftp-check-host")),
+                )
+                .clicked()
+            {
+                self.host_key = Some((address.clone(), None));
+                let settings = self.sftp.clone();
+                let (tx, ctx) = (tx.clone(), ctx.clone());
+                rt.spawn(async move {
+                    let result = sftp::host_fingerprint(&settings)
+                        .await
+                        .map_err(|e| e.to_string());
+                    let address = settings.address();
+                    let _ = tx.send(MessageResult::Upload(Message::HostKey { address, result }));
+                    ctx.request_repaint();
+                });
+            }
+            if self.known_hosts.get(&address).is_some() {
+                ui.weak(t!("sftp-host-known"));
+            }
+        });
+        let Some((checked, result)) = &self.host_key else {
+            return;
+        };
+        if *checked != address {
+            return;
+        }
+        let fingerprint = match result {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(t!("sftp-connecting"));
+                });
+                return;
+            }
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+                return;
+            }
+            Some(Ok(fingerprint)) => fingerprint.clone(),
+        };
+        let trust = match self.known_hosts.check(&address, &fingerprint) {
+            HostKey::Trusted => {
+                ui.label(t!("sftp-host-trusted", fingerprint = &fingerprint));
+                false
+            }
+            HostKey::Unknown => {
+                ui.label(t!("sftp-host-unknown", fingerprint = &fingerprint));
+                ui.button(t!("sftp-trust")).clicked()
+            }
+            HostKey::Changed { trusted } => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    t!(
+                        "sftp-host-changed",
+                        trusted = trusted,
+                        fingerprint = &fingerprint
+                    ),
+                );
+                ui.button(t!("sftp-trust-changed")).clicked()
+            }
+        };
+        if trust {
+            self.known_hosts.trust(&address, &fingerprint);
+            let result = self
+                .known_hosts
+                .save(&known_hosts_path())
+                .map(|()| t!("sftp-trusted", address = &address))
+                .map_err(|e| t!("sftp-known-hosts-error", error = e.to_string()));
+            let _ = tx.send(MessageResult::Upload(Message::Notice(result)));
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// Runs `work` with the keyring on a thread of its own, as the Secret
+/// Service can deadlock on the threads of an async runtime, and reports
+/// its outcome.
+#[cfg(feature = "s3")]
+fn on_keyring(
+    tx: &UnboundedSender<MessageResult>,
+    ctx: &egui::Context,
+    work: impl FnOnce() -> Result<String, UploadError> + Send + 'static,
+) {
+    let (tx, ctx) = (tx.clone(), ctx.clone());
+    thread::spawn(move || {
+        let result = work().map_err(|e| e.to_string());
+        let _ = tx.send(MessageResult::Upload(Message::Notice(result)));
+        ctx.request_repaint();
+    });
+}
+
+fn spawn(
+    destination: &Destination,
+    path: &Path,
+    key: &str,
+    generation: u32,
+    rt: &Runtime,
+    tx: &UnboundedSender<MessageResult>,
+    ctx: &egui::Context,
+) -> AbortHandle {
+    let (destination, path, key) = (destination.clone(), path.to_path_buf(), key.to_string());
+    let (tx, ctx) = (tx.clone(), ctx.clone());
+    rt.spawn(async move {
+        let report = |path: &Path, state| {
+            let path = path.to_path_buf();
+            let _ = tx.send(MessageResult::Upload(Message::State {
+                path,
+                state,
+                generation,
+            }));
+            ctx.request_repaint();
+        };
+        let result = destination
+            .upload(&path, &key, |attempt| {
+                report(&path, UploadState::Uploading { attempt })
+            })
+            .await;
+        let state = match result {
+            Ok(()) => UploadState::Done,
+            Err(e) => UploadState::Failed(e),
+        };
+        report(&path, state);
+    })
+    .abort_handle()
+}
+
+/// Where trusted SFTP host keys are kept.
+#[cfg(feature = "sftp")]
+pub fn known_hosts_path() -> PathBuf {
+    crate::storage::config_dir().join("known_hosts.json")
+}
+
+#[cfg(feature = "sftp")]
+fn load_known_hosts() -> KnownHosts {
+    KnownHosts::load(&known_hosts_path()).unwrap_or_else(|e| {
+        tracing::warn!("could not read the known hosts: {}", e);
+        KnownHosts::default()
+    })
+}
+
+/// Trusts the key the server of `settings` presents if none was trusted for
+/// it yet, returning its fingerprint then. A key that differs from the
+/// trusted one is never replaced here.
+#[cfg(feature = "sftp")]
+pub async fn trust_unknown_host(settings: &SftpSettings) -> Result<Option<String>, String> {
+    let mut known_hosts = load_known_hosts();
+    if known_hosts.get(&settings.address()).is_some() {
+        return Ok(None);
+    }
+    let fingerprint = sftp::host_fingerprint(settings)
+        .await
+        .map_err(|e| e.to_string())?;
+    known_hosts.trust(&settings.address(), &fingerprint);
+    known_hosts
+        .save(&known_hosts_path())
+        .map_err(|e| t!("sftp-known-hosts-error", error = e.to_string()))?;
+    Ok(Some(fingerprint))
+}
+
+/// Connects to the bucket or server of `target`. For a bucket, looks up
+/// the credentials on a thread of its own like `on_keyring`.
+pub async fn connect(target: Connect) -> Result<Destination, String> {
+    match target {
+        #[cfg(feature = "s3")]
+        Connect::S3(settings) => {
+            let lookup = settings.clone();
+            let found = thread::spawn(move || Credentials::find(&lookup))
+                .join()
+                .expect("the keyring lookup panicked");
+            match found {
+                Ok(Some((credentials, source))) => {
+                    tracing::info!(
+                        "uploading to {} with credentials from the {:?}",
+                        settings.keyring_user(),
+                        source
+                    );
+                    let uploader =
+                        Uploader::new(&settings, credentials).map_err(|e| e.to_string())?;
+                    Ok(Destination::S3 { uploader, settings })
+                }
+                Ok(None) => Err(UploadError::NoCredentials.to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        #[cfg(feature = "sftp")]
+        Connect::Sftp(settings) => {
+            let known_hosts = load_known_hosts();
+            let trusted = known_hosts.get(&settings.address());
+            tracing::info!("uploading to {}", settings.address());
+            SftpUploader::connect(&settings, trusted)
+                .await
+                .map(Destination::Sftp)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Pre-flight checks of a batch.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{metadata::Orientation, ColorType, ImageDecoder, ImageReader};
+
+use crate::{
+    choose_settings,
+    fill::BorderFill,
+    i18n,
+    naming::{BatchNaming, FileNaming, OverwritePolicy},
+    output_dimensions,
+    pipeline::bit_depth,
+    rules::ResolvedRule,
+    space::{self, Space},
+    t, templated_file_name, ProcessError, ProcessInfo, MAX_BORDER_PERCENTAGE, MAX_DIMENSION,
+    MAX_FRAME_PERCENTAGE,
+};
+
+/// Outputs above this many pixels are slow to encode and too large for most
+/// viewers.
+const HUGE_CANVAS_PIXELS: u64 = 100_000_000;
+/// Longest side most encoders and browsers still accept.
+const HUGE_CANVAS_SIDE: u32 = 16_384;
+
+/// Problems found before a batch starts. Errors make the run impossible;
+/// warnings only need the user's confirmation.
+#[derive(Debug, Clone, Default)]
+pub struct Validation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    /// How the output folder overlaps the input folder, which needs to be
+    /// confirmed on its own.
+    pub overlap: Option<Overlap>,
+    /// A generous guess at the bytes the batch writes, for checking the
+    /// free space when there is no better estimate.
+    pub expected_bytes: u64,
+    /// What the batch writes, by format and bits per channel.
+    pub outputs: BTreeMap<(&'static str, u8), OutputGroup>,
+}
+
+/// Outputs of a batch in one format and bit depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputGroup {
+    pub count: usize,
+    /// Dimensions of the output with the fewest pixels.
+    pub smallest: (u32, u32),
+    /// Dimensions of the output with the most pixels.
+    pub largest: (u32, u32),
+}
+
+impl OutputGroup {
+    fn add(&mut self, (width, height): (u32, u32)) {
+        let pixels = |(width, height): (u32, u32)| width as u64 * height as u64;
+        self.count += 1;
+        if pixels((width, height)) < pixels(self.smallest) {
+            self.smallest = (width, height);
+        }
+        if pixels((width, height)) > pixels(self.largest) {
+            self.largest = (width, height);
+        }
+    }
+}
+
+impl Validation {
+    /// Whether nothing at all was found.
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    /// Reports a drive of `output_dir` without room for `needed` bytes as
+    /// an error, and one with little to spare as a warning. Drives whose
+    /// free space can't be read are assumed to have room.
+    pub fn check_space(&mut self, output_dir: &Path, needed: u64) {
+        let available = match space::available_space(output_dir) {
+            Ok(available) => available,
+            Err(e) => {
+                tracing::debug!("free space of {} unknown: {}", output_dir.display(), e);
+                return;
+            }
+        };
+        let (needed_text, available_text) = (i18n::bytes(needed), i18n::bytes(available));
+        match space::compare(needed, available) {
+            Space::Short => self.errors.push(t!(
+                "check-disk-short",
+                needed = needed_text,
+                available = available_text
+            )),
+            Space::Tight => self.warnings.push(t!(
+                "check-disk-tight",
+                needed = needed_text,
+                available = available_text
+            )),
+            Space::Enough => {}
+        }
+    }
+}
+
+/// Checks a batch without writing any output. Reads the header of every
+/// input, so run it off the UI thread. The free space is left to
+/// `Validation::check_space`, as callers may know better than
+/// `expected_bytes` what the batch writes.
+pub fn validate(
+    input_dir: &Path,
+    output_dir: &Path,
+    image_paths: &[PathBuf],
+    rules: &[ResolvedRule],
+    manual: ProcessInfo,
+    naming: &BatchNaming,
+) -> Validation {
+    let mut validation = Validation {
+        errors: blockers(input_dir, output_dir, image_paths.len(), &manual, naming),
+        ..Validation::default()
+    };
+    // Reported by `blockers`.
+    let template = naming.parse_template().ok();
+    let date_folders = naming.parse_date_folders().ok().flatten();
+
+    validation.overlap = overlap(input_dir, output_dir);
+    match validation.overlap {
+        Some(Overlap::Same) => validation
+            .warnings
+            .push(t!("check-output-is-input").to_string()),
+        Some(Overlap::Inside) => validation
+            .warnings
+            .push(t!("check-output-inside-input").to_string()),
+        None => {}
+    }
+
+    let inputs: HashSet<PathBuf> = image_paths.iter().map(|path| canonical(path)).collect();
+    let output_root = canonical(output_dir);
+    let mut outputs: HashMap<PathBuf, Vec<&Path>> = HashMap::new();
+    let mut reduced_depth: BTreeMap<(&str, u8), Vec<&Path>> = BTreeMap::new();
+    let mut opaque_borders = BTreeSet::new();
+    let mut missing_textures = BTreeSet::new();
+    let policy = naming.overwrite;
+    // Inputs whose output name is taken by an existing file.
+    let mut taken = vec![];
+    for (index, path) in image_paths.iter().enumerate() {
+        let chosen = choose_settings(path, rules, manual.clone());
+        let info = chosen.info;
+        let encoder = info.output_format.encoder();
+        if info.border_color[3] < u8::MAX && !encoder.supports_alpha() {
+            opaque_borders.insert(encoder.name());
+        }
+        if let BorderFill::Texture(texture) = &info.border_fill {
+            if !texture.path.is_file() {
+                missing_textures.insert(texture.path.clone());
+            }
+        }
+
+        let header = read_header(path);
+        // Files that can't be read fail on their own; size placeholders
+        // just can't be predicted for them.
+        let dimensions = header.map_or((0, 0), |((width, height), _)| {
+            output_dimensions(width, height, &info)
+        });
+
+        if let Some(template) = &template {
+            let folder = naming.output_folder(path, date_folders.as_ref());
+            let naming =
+                FileNaming::for_batch(naming, template, index, chosen.preset.as_deref(), &folder);
+            match templated_file_name(path, info.output_format, dimensions, &naming) {
+                Ok(output) => {
+                    let bits = header.map_or(8, |(_, color)| bit_depth(color));
+                    let relative = naming
+                        .output_folder(info.output_format, bits, dimensions)
+                        .join(&output.name);
+                    let target = output_root.join(&relative);
+                    if policy != OverwritePolicy::Overwrite {
+                        if target.exists() {
+                            taken.push(path.as_path());
+                        }
+                    } else if target == canonical(path) {
+                        validation
+                            .errors
+                            .push(t!("check-overwrites-itself", path = path.display()));
+                    } else if inputs.contains(&target) {
+                        validation.errors.push(t!(
+                            "check-overwrites-input",
+                            target = target.display(),
+                            path = path.display()
+                        ));
+                    }
+                    if output.mangled {
+                        validation.warnings.push(t!(
+                            "check-name-mangled",
+                            path = path.display(),
+                            name = Path::new(&output.name).display()
+                        ));
+                    }
+                    outputs.entry(relative).or_default().push(path);
+                    if let (Some(placement), Some(name)) = (naming.originals, path.file_name()) {
+                        let copy = placement.folder(naming.folder).join(name);
+                        // Copying a file onto itself is skipped.
+                        if output_root.join(&copy) != canonical(path) {
+                            outputs.entry(copy).or_default().push(path);
+                        }
+                    }
+                }
+                Err(ProcessError::Template { source, .. }) => {
+                    validation
+                        .errors
+                        .push(format!("{}: {}", path.display(), source))
+                }
+                // Unusable names are reported by the file's own error later on.
+                Err(_) => {}
+            }
+        }
+
+        let Some((_, color)) = header else {
+            continue;
+        };
+
+        let bits = bit_depth(color);
+        let input_bytes = fs::metadata(path).map_or(0, |metadata| metadata.len());
+        validation.expected_bytes =
+            validation
+                .expected_bytes
+                .saturating_add(space::rough_output_size(
+                    info.output_format,
+                    dimensions,
+                    bits,
+                    input_bytes,
+                ));
+        if naming.originals.is_some() {
+            validation.expected_bytes = validation.expected_bytes.saturating_add(input_bytes);
+        }
+        let stored_bits = if encoder.supports_bit_depth(bits) {
+            bits
+        } else {
+            8
+        };
+        validation
+            .outputs
+            .entry((encoder.name(), stored_bits))
+            .or_insert(OutputGroup {
+                count: 0,
+                smallest: dimensions,
+                largest: dimensions,
+            })
+            .add(dimensions);
+        if !encoder.supports_bit_depth(bits) {
+            reduced_depth
+                .entry((encoder.name(), bits))
+                .or_default()
+                .push(path);
+        }
+
+        let (out_width, out_height) = dimensions;
+        if out_width as u64 * out_height as u64 > HUGE_CANVAS_PIXELS
+            || out_width.max(out_height) > HUGE_CANVAS_SIDE
+        {
+            validation.warnings.push(t!(
+                "check-huge-canvas",
+                path = path.display(),
+                width = out_width,
+                height = out_height
+            ));
+        }
+    }
+
+    for ((format, bits), sources) in reduced_depth {
+        validation.warnings.push(t!(
+            "check-reduced-depth",
+            paths = list_paths(&sources),
+            bits = bits,
+            format = format
+        ));
+    }
+    if !taken.is_empty() {
+        let paths = list_paths(&taken);
+        validation.warnings.push(match policy {
+            OverwritePolicy::Rename => t!("check-taken-renamed", paths = paths),
+            _ => t!("check-taken-skipped", paths = paths),
+        });
+    }
+    for format in opaque_borders {
+        validation
+            .warnings
+            .push(t!("check-transparent-border", format = format));
+    }
+    for texture in missing_textures {
+        validation
+            .errors
+            .push(t!("check-missing-texture", path = texture.display()));
+    }
+
+    let mut collisions: Vec<_> = outputs
+        .into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .collect();
+    collisions.sort();
+    for (name, sources) in collisions {
+        let (paths, name) = (list_paths(&sources), name.display());
+        validation.warnings.push(match policy {
+            OverwritePolicy::Overwrite => t!("check-collision-kept", paths = paths, name = name),
+            OverwritePolicy::Rename => t!("check-collision-renamed", paths = paths, name = name),
+            OverwritePolicy::Skip => t!("check-collision-skipped", paths = paths, name = name),
+        });
+    }
+
+    validation
+}
+
+/// Size and pixel type of `path`, from its header. The size is that of the
+/// photo turned upright as its EXIF orientation says.
+pub fn read_header(path: &Path) -> Option<((u32, u32), ColorType)> {
+    let mut decoder = ImageReader::open(path).ok()?.into_decoder().ok()?;
+    let (width, height) = decoder.dimensions();
+    let dimensions = match decoder.orientation().unwrap_or(Orientation::NoTransforms) {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => (height, width),
+        _ => (width, height),
+    };
+    Some((dimensions, decoder.color_type()))
+}
+
+fn list_paths(paths: &[&Path]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// What keeps a batch of `image_count` inputs from starting at all, found
+/// without reading them: unset folders, an output folder that can't be
+/// written, patterns that can't be parsed and sizes in `info` outside what
+/// the settings offer. `validate` reports these too.
+pub fn blockers(
+    input_dir: &Path,
+    output_dir: &Path,
+    image_count: usize,
+    info: &ProcessInfo,
+    naming: &BatchNaming,
+) -> Vec<String> {
+    let mut blockers = vec![];
+
+    // Files and the command line can hold anything, NaN included. Only
+    // the border width that applies is checked, as the layers pick it.
+    let percentages = match (info.border_sides, info.border_pixels) {
+        (Some(sides), _) => vec![sides.top, sides.right, sides.bottom, sides.left],
+        (None, Some(pixels)) => {
+            if pixels.width > MAX_DIMENSION / 2 {
+                blockers.push(t!(
+                    "check-border-pixels-range",
+                    value = pixels.width,
+                    max = MAX_DIMENSION / 2
+                ));
+            }
+            vec![]
+        }
+        (None, None) => vec![info.border_percentage],
+    };
+    for value in percentages {
+        if !(0.0..=MAX_BORDER_PERCENTAGE).contains(&value) {
+            blockers.push(t!(
+                "check-border-range",
+                value = value,
+                max = MAX_BORDER_PERCENTAGE
+            ));
+        }
+    }
+    if let Some(frame) = info.frame {
+        if !(0.0..=MAX_FRAME_PERCENTAGE).contains(&frame.width) {
+            blockers.push(t!(
+                "check-frame-range",
+                value = frame.width,
+                max = MAX_FRAME_PERCENTAGE
+            ));
+        }
+    }
+    if info.resize_images && !(1..=MAX_DIMENSION).contains(&info.resize_longest_dimension) {
+        blockers.push(t!(
+            "check-resize-range",
+            value = info.resize_longest_dimension,
+            max = MAX_DIMENSION
+        ));
+    }
+
+    if let Err(e) = naming.parse_template() {
+        blockers.push(t!("check-template", error = e));
+    }
+    if let Err(e) = naming.parse_run_folder() {
+        blockers.push(t!("check-run-folder", error = e));
+    }
+    if let Err(e) = naming.parse_date_folders() {
+        blockers.push(t!("check-date-folders", error = e));
+    }
+
+    if input_dir.as_os_str().is_empty() {
+        blockers.push(t!("check-no-input").to_string());
+    } else if image_count == 0 {
+        blockers.push(t!("check-no-images", folder = input_dir.display()));
+    }
+
+    if output_dir.as_os_str().is_empty() {
+        blockers.push(t!("check-no-output").to_string());
+    } else if let Err(e) = check_writable(output_dir) {
+        blockers.push(e);
+    }
+
+    blockers
+}
+
+/// Whether outputs written to `output_dir` end up in `input_dir` or one of
+/// its subfolders, comparing the resolved paths.
+pub fn output_inside_input(input_dir: &Path, output_dir: &Path) -> bool {
+    overlap(input_dir, output_dir).is_some()
+}
+
+/// How an output folder overlaps the input folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    /// Both are the same folder.
+    Same,
+    /// The output folder is somewhere inside the input folder.
+    Inside,
+}
+
+/// How `output_dir` overlaps `input_dir`, comparing the resolved paths, or
+/// `None` when either is unset or they are apart.
+pub fn overlap(input_dir: &Path, output_dir: &Path) -> Option<Overlap> {
+    if input_dir.as_os_str().is_empty() || output_dir.as_os_str().is_empty() {
+        return None;
+    }
+    let input = canonical(input_dir);
+    let output = canonical(output_dir);
+    if output == input {
+        Some(Overlap::Same)
+    } else if output.starts_with(&input) {
+        Some(Overlap::Inside)
+    } else {
+        None
+    }
+}
+
+/// Confirms files can be created in `dir`, or in the closest existing parent
+/// when `dir` doesn't exist yet.
+fn check_writable(dir: &Path) -> Result<(), String> {
+    let existing = dir
+        .ancestors()
+        .find(|path| !path.as_os_str().is_empty() && path.exists())
+        .unwrap_or(Path::new("."));
+
+    if !existing.is_dir() {
+        return Err(t!("check-not-a-directory", path = existing.display()));
+    }
+
+    let probe = existing.join(".image-finalizer-write-test");
+    fs::write(&probe, b"")
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|e| t!("check-not-writable", path = existing.display(), error = e))
+}
+
+/// Resolves `path` as far as it exists, so folders that will only be created
+/// by the run can still be compared.
+pub(crate) fn canonical(path: &Path) -> PathBuf {
+    let mut missing = vec![];
+    let mut current = path;
+    loop {
+        if let Ok(resolved) = current.canonicalize() {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |resolved, part| resolved.join(part));
+        }
+        match (current.parent(), current.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                current = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
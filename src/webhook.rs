@@ -0,0 +1,253 @@
+//! Telling a chat or another service that a batch ended, by POSTing a JSON
+//! `BatchReport` to a webhook URL. The settings and the report are always
+//! available; sending needs the `webhook` feature.
+//!
+//! The report is an interface others build on. New versions only ever add
+//! fields, and bump `version` when they do. A report looks like this:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "text": "Batch 2024-06-15_1432 completed: 12 written, 0 skipped, 0 failed, 34.5 MB in 1m 02s",
+//!   "status": "completed",
+//!   "name": "2024-06-15_1432",
+//!   "output_dir": "/home/studio/exports/2024-06-15_1432",
+//!   "total": 12,
+//!   "written": 12,
+//!   "skipped": 0,
+//!   "failed": 0,
+//!   "bytes": 34512345,
+//!   "duration_secs": 62.4,
+//!   "failures": []
+//! }
+//! ```
+//!
+//! `status` is `completed`, `failed` when any file failed, or `cancelled`.
+//! `failures` holds the first `MAX_FAILURES` messages. `text` sums it all
+//! up in English, which is what Slack and similar chat hooks show.
+
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ProcessError, ProcessOutcome};
+
+/// Version of the report layout.
+pub const REPORT_VERSION: u32 = 1;
+
+/// Failure messages sent along at most.
+pub const MAX_FAILURES: usize = 5;
+
+/// Tries of one report before it is given up on.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// How long one try may take.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where finished batches are reported to.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    /// Like `https://hooks.slack.com/services/…`.
+    pub url: String,
+}
+
+impl WebhookSettings {
+    /// Whether the URL is one that can be posted to.
+    pub fn is_complete(&self) -> bool {
+        let url = self.url.trim();
+        url.starts_with("https://") || url.starts_with("http://")
+    }
+}
+
+/// How a batch ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchStatus {
+    Completed,
+    /// Ran to the end, but some files failed.
+    Failed,
+    Cancelled,
+}
+
+/// How many files of a batch went which way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchCounts {
+    pub total: usize,
+    pub written: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// What is posted when a batch ends. See the module documentation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub version: u32,
+    pub text: String,
+    pub status: BatchStatus,
+    /// Name of the output folder, which is the run folder if there is one.
+    pub name: String,
+    pub output_dir: String,
+    pub total: usize,
+    pub written: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Size of everything written.
+    pub bytes: u64,
+    pub duration_secs: f64,
+    pub failures: Vec<String>,
+}
+
+impl BatchReport {
+    /// Report of a batch into `output_dir`, keeping the first of `failures`.
+    pub fn new(
+        output_dir: &Path,
+        cancelled: bool,
+        counts: BatchCounts,
+        failures: &[String],
+        bytes: u64,
+        duration: Duration,
+    ) -> Self {
+        let status = if cancelled {
+            BatchStatus::Cancelled
+        } else if counts.failed > 0 {
+            BatchStatus::Failed
+        } else {
+            BatchStatus::Completed
+        };
+        let name = output_dir
+            .file_name()
+            .unwrap_or(output_dir.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        let seconds = duration.as_secs();
+        let text = format!(
+            "Batch {} {}: {} written, {} skipped, {} failed, {:.1} MB in {}m {:02}s",
+            name,
+            match status {
+                BatchStatus::Completed => "completed",
+                BatchStatus::Failed => "finished with failures",
+                BatchStatus::Cancelled => "was cancelled",
+            },
+            counts.written,
+            counts.skipped,
+            counts.failed,
+            bytes as f64 / 1e6,
+            seconds / 60,
+            seconds % 60
+        );
+        BatchReport {
+            version: REPORT_VERSION,
+            text,
+            status,
+            name,
+            output_dir: output_dir.display().to_string(),
+            total: counts.total,
+            written: counts.written,
+            skipped: counts.skipped,
+            failed: counts.failed,
+            bytes,
+            duration_secs: duration.as_secs_f64(),
+            failures: failures.iter().take(MAX_FAILURES).cloned().collect(),
+        }
+    }
+}
+
+/// Sums up a batch for its report as its files finish.
+#[derive(Debug, Clone, Default)]
+pub struct BatchTally {
+    pub counts: BatchCounts,
+    pub bytes: u64,
+    /// The first `MAX_FAILURES` failure messages.
+    pub failures: Vec<String>,
+    pub cancelled: bool,
+}
+
+impl BatchTally {
+    /// Tally of a batch of `total` files.
+    pub fn new(total: usize) -> Self {
+        BatchTally {
+            counts: BatchCounts {
+                total,
+                ..BatchCounts::default()
+            },
+            ..BatchTally::default()
+        }
+    }
+
+    /// Counts a finished file.
+    pub fn add(&mut self, result: &Result<ProcessOutcome, ProcessError>) {
+        match result {
+            Ok(outcome) if outcome.skipped.is_some() => self.counts.skipped += 1,
+            Ok(outcome) => {
+                self.counts.written += 1;
+                self.bytes += outcome.bytes_written;
+            }
+            Err(e) => {
+                self.counts.failed += 1;
+                if self.failures.len() < MAX_FAILURES {
+                    self.failures.push(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Report of the batch into `output_dir` after it ran for `duration`.
+    pub fn report(&self, output_dir: &Path, duration: Duration) -> BatchReport {
+        BatchReport::new(
+            output_dir,
+            self.cancelled,
+            self.counts,
+            &self.failures,
+            self.bytes,
+            duration,
+        )
+    }
+}
+
+#[cfg(feature = "webhook")]
+pub use client::*;
+
+#[cfg(feature = "webhook")]
+mod client {
+    use super::{BatchReport, MAX_ATTEMPTS, TIMEOUT};
+    use crate::{t, upload::backoff};
+
+    /// Why a report could not be delivered.
+    #[derive(Debug, thiserror::Error)]
+    pub enum WebhookError {
+        #[error("{}", t!("webhook-status", status = .0))]
+        Status(u16),
+        #[error(transparent)]
+        Request(#[from] reqwest::Error),
+    }
+
+    /// Posts `report` to `url`, trying up to `MAX_ATTEMPTS` times with
+    /// `upload::backoff` in between. Answers other than server errors and
+    /// rate limits are not tried again.
+    pub async fn send(url: &str, report: &BatchReport) -> Result<(), WebhookError> {
+        let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+        let mut attempt = 1;
+        loop {
+            let error = match client.post(url.trim()).json(report).send().await {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => {
+                    let status = response.status();
+                    let error = WebhookError::Status(status.as_u16());
+                    if !status.is_server_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+                    {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(e) => e.into(),
+            };
+            if attempt >= MAX_ATTEMPTS {
+                return Err(error);
+            }
+            tracing::debug!("webhook failed, retrying: {}", error);
+            tokio::time::sleep(backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,656 @@
+//! Background processing driven through a command channel.
+//!
+//! The GUI, the headless mode and tests all talk to the same `Worker`: they
+//! send `Command`s and receive `Event`s through the callback the worker was
+//! spawned with. The callback runs on the worker's threads, so it can do
+//! further work on the results without blocking its caller.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use image::{DynamicImage, RgbaImage};
+
+use crate::{
+    archive::{self, ArchiveSettings},
+    batch,
+    combine::CombineSettings,
+    duplicates::{self, Duplicate, DuplicateGroup},
+    edit::ImageEdit,
+    estimate::{self, SizeEstimate},
+    hook::{self, HookFailure, HookSettings},
+    manifest::{Manifest, ManifestEntry, ManifestFormat},
+    naming::BatchNaming,
+    pipeline::{FileContext, Pipeline},
+    placeholder::PlaceholderKind,
+    rules::ResolvedRule,
+    split::SplitSettings,
+    srcset::{self, SrcsetEntry},
+    ChosenSettings, ProcessError, ProcessInfo, ProcessOutcome,
+};
+
+// Sent once per batch, so boxing the batch settings would gain nothing.
+#[allow(clippy::large_enum_variant)]
+pub enum Command {
+    /// Processes `paths` into `output_dir`, naming the outputs after
+    /// `naming`, running the `hook` for each and recording them in a
+    /// `manifest` of that format, along with the `duplicates` left out.
+    /// With `combine`, `paths` are combined into a single output with the
+    /// `manual` settings instead, and with `split` each is split into tiles
+    /// with them. Inputs in `edits` are turned and cropped as set there
+    /// instead of as their settings say. With `placeholder`, the manifest records
+    /// one of that kind for every output. Unless cancelled, the outputs are
+    /// then zipped into an `archive`. A batch that is still running is
+    /// finished first.
+    StartBatch {
+        paths: Vec<PathBuf>,
+        output_dir: PathBuf,
+        rules: Vec<ResolvedRule>,
+        manual: ProcessInfo,
+        edits: HashMap<PathBuf, ImageEdit>,
+        naming: BatchNaming,
+        manifest: Option<ManifestFormat>,
+        placeholder: Option<PlaceholderKind>,
+        archive: Option<ArchiveSettings>,
+        hook: Option<HookSettings>,
+        duplicates: Vec<Duplicate>,
+        combine: Option<CombineSettings>,
+        split: Option<SplitSettings>,
+    },
+    /// Stops the running batch after the files that are already being
+    /// processed. Takes effect immediately, even while a preview renders.
+    CancelBatch,
+    /// Holds the running batch after the files that are already being
+    /// processed, until it is resumed or cancelled.
+    PauseBatch,
+    ResumeBatch,
+    /// Runs the pipeline of `info` on `source`, the decoded `path`. Requests
+    /// that are superseded by a newer one before they start are dropped.
+    RequestPreview {
+        generation: u64,
+        path: PathBuf,
+        source: Arc<DynamicImage>,
+        info: ProcessInfo,
+        /// Size of `source` relative to the photo, which lengths set in
+        /// pixels are scaled by.
+        scale: f32,
+    },
+    /// Estimates the size of the outputs of a batch of `paths`, answered
+    /// with a `SizeEstimate` carrying `key`. Cancels the estimate that is
+    /// still running.
+    EstimateSize {
+        key: String,
+        paths: Vec<PathBuf>,
+        rules: Vec<ResolvedRule>,
+        manual: ProcessInfo,
+    },
+    /// Stops the running estimate without an answer.
+    CancelEstimate,
+    /// Hashes `paths` and groups those within `threshold` bits of each
+    /// other, answered with `Duplicates`. Cancels the search that is still
+    /// running.
+    FindDuplicates {
+        paths: Vec<PathBuf>,
+        threshold: u32,
+    },
+    /// Stops the running search without an answer.
+    CancelDuplicates,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    /// One file of the batch is done, with a description of the settings
+    /// it was processed with. Its thumbnail was sent as a `Thumbnail`.
+    FileDone {
+        path: PathBuf,
+        applied: String,
+        result: Result<ProcessOutcome, ProcessError>,
+    },
+    /// The output of `input` at `output` scaled down, sent before the
+    /// `FileDone` of `input`.
+    Thumbnail {
+        input: PathBuf,
+        output: PathBuf,
+        image: RgbaImage,
+    },
+    /// The output of `path` didn't fit on the drive. The batch pauses until
+    /// it is resumed, which tries the file again, or cancelled, which
+    /// reports it as failed.
+    DiskFull { path: PathBuf },
+    /// `done` of the `total` outputs are in the archive.
+    ArchiveProgress { done: usize, total: usize },
+    /// The archive is written, or skipped as `None` because its name was
+    /// taken.
+    ArchiveDone {
+        result: Result<Option<PathBuf>, ProcessError>,
+    },
+    /// Sent once per batch, after the last `FileDone` and the archive.
+    BatchFinished { cancelled: bool },
+    Preview {
+        generation: u64,
+        info: ProcessInfo,
+        result: Result<DynamicImage, ProcessError>,
+    },
+    /// Answer to `EstimateSize`, unless it was cancelled.
+    SizeEstimate {
+        key: String,
+        result: Result<SizeEstimate, ProcessError>,
+    },
+    /// `done` of the `total` files of `FindDuplicates` are hashed.
+    DuplicateProgress { done: usize, total: usize },
+    /// Answer to `FindDuplicates`, unless it was cancelled.
+    Duplicates { groups: Vec<DuplicateGroup> },
+}
+
+type EventSink = Arc<dyn Fn(Event) + Send + Sync>;
+
+/// What the worker thread is asked to do. Unlike `Command`, batches carry
+/// their own cancellation flag so a cancel can never reach the wrong batch.
+enum Job {
+    Batch {
+        paths: Vec<PathBuf>,
+        output_dir: PathBuf,
+        rules: Vec<ResolvedRule>,
+        manual: Box<ProcessInfo>,
+        edits: HashMap<PathBuf, ImageEdit>,
+        naming: Box<BatchNaming>,
+        manifest: Option<ManifestFormat>,
+        placeholder: Option<PlaceholderKind>,
+        archive: Option<ArchiveSettings>,
+        hook: Option<HookSettings>,
+        duplicates: Vec<Duplicate>,
+        combine: Option<CombineSettings>,
+        split: Option<SplitSettings>,
+        cancel: Arc<AtomicBool>,
+        pause: Arc<Pause>,
+    },
+    Preview {
+        generation: u64,
+        path: PathBuf,
+        source: Arc<DynamicImage>,
+        info: ProcessInfo,
+        scale: f32,
+    },
+    Estimate {
+        key: String,
+        paths: Vec<PathBuf>,
+        rules: Vec<ResolvedRule>,
+        manual: ProcessInfo,
+        cancel: Arc<AtomicBool>,
+    },
+    Duplicates {
+        paths: Vec<PathBuf>,
+        threshold: u32,
+        cancel: Arc<AtomicBool>,
+    },
+}
+
+/// Holds the threads of a paused batch between files.
+#[derive(Default)]
+struct Pause {
+    paused: Mutex<bool>,
+    resumed: Condvar,
+}
+
+impl Pause {
+    fn set(&self, paused: bool) {
+        *self.paused.lock().unwrap() = paused;
+        self.resumed.notify_all();
+    }
+
+    /// Blocks the calling thread while the batch is paused.
+    fn wait(&self) {
+        let paused = self.paused.lock().unwrap();
+        drop(self.resumed.wait_while(paused, |paused| *paused).unwrap());
+    }
+}
+
+/// Owns the processing threads. Dropping it cancels the running batch and
+/// waits for the files in progress.
+pub struct Worker {
+    jobs: Option<Sender<Job>>,
+    /// Cancellation flag of the most recently started batch.
+    cancel: Mutex<Arc<AtomicBool>>,
+    /// Pause of the most recently started batch.
+    pause: Mutex<Arc<Pause>>,
+    /// Cancellation flag of the most recent estimate.
+    cancel_estimate: Mutex<Arc<AtomicBool>>,
+    /// Cancellation flag of the most recent search for duplicates.
+    cancel_duplicates: Mutex<Arc<AtomicBool>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    pub fn spawn(on_event: impl Fn(Event) + Send + Sync + 'static) -> Self {
+        let (jobs, receiver) = mpsc::channel();
+        let on_event: EventSink = Arc::new(on_event);
+        let thread = thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(move || work(receiver, on_event))
+            .expect("failed to start the worker thread");
+
+        Worker {
+            jobs: Some(jobs),
+            cancel: Mutex::new(Arc::default()),
+            pause: Mutex::new(Arc::default()),
+            cancel_estimate: Mutex::new(Arc::default()),
+            cancel_duplicates: Mutex::new(Arc::default()),
+            thread: Some(thread),
+        }
+    }
+
+    pub fn send(&self, command: Command) {
+        let job = match command {
+            Command::StartBatch {
+                paths,
+                output_dir,
+                rules,
+                manual,
+                edits,
+                naming,
+                manifest,
+                placeholder,
+                archive,
+                hook,
+                duplicates,
+                combine,
+                split,
+            } => {
+                let cancel = Arc::new(AtomicBool::new(false));
+                *self.cancel.lock().unwrap() = cancel.clone();
+                let pause = Arc::new(Pause::default());
+                *self.pause.lock().unwrap() = pause.clone();
+                Job::Batch {
+                    paths,
+                    output_dir,
+                    rules,
+                    manual: Box::new(manual),
+                    edits,
+                    naming: Box::new(naming),
+                    manifest,
+                    placeholder,
+                    archive,
+                    hook,
+                    duplicates,
+                    combine,
+                    split,
+                    cancel,
+                    pause,
+                }
+            }
+            Command::CancelBatch => {
+                self.cancel.lock().unwrap().store(true, Ordering::Relaxed);
+                // Held threads have to move on to see the cancel.
+                self.pause.lock().unwrap().set(false);
+                return;
+            }
+            Command::PauseBatch => {
+                self.pause.lock().unwrap().set(true);
+                return;
+            }
+            Command::ResumeBatch => {
+                self.pause.lock().unwrap().set(false);
+                return;
+            }
+            Command::RequestPreview {
+                generation,
+                path,
+                source,
+                info,
+                scale,
+            } => Job::Preview {
+                generation,
+                path,
+                source,
+                info,
+                scale,
+            },
+            Command::EstimateSize {
+                key,
+                paths,
+                rules,
+                manual,
+            } => {
+                let cancel = Arc::new(AtomicBool::new(false));
+                let previous =
+                    std::mem::replace(&mut *self.cancel_estimate.lock().unwrap(), cancel.clone());
+                previous.store(true, Ordering::Relaxed);
+                Job::Estimate {
+                    key,
+                    paths,
+                    rules,
+                    manual,
+                    cancel,
+                }
+            }
+            Command::CancelEstimate => {
+                self.cancel_estimate
+                    .lock()
+                    .unwrap()
+                    .store(true, Ordering::Relaxed);
+                return;
+            }
+            Command::FindDuplicates { paths, threshold } => {
+                let cancel = Arc::new(AtomicBool::new(false));
+                let previous =
+                    std::mem::replace(&mut *self.cancel_duplicates.lock().unwrap(), cancel.clone());
+                previous.store(true, Ordering::Relaxed);
+                Job::Duplicates {
+                    paths,
+                    threshold,
+                    cancel,
+                }
+            }
+            Command::CancelDuplicates => {
+                self.cancel_duplicates
+                    .lock()
+                    .unwrap()
+                    .store(true, Ordering::Relaxed);
+                return;
+            }
+        };
+
+        if let Some(jobs) = &self.jobs {
+            // The worker thread only ends once `jobs` is dropped.
+            let _ = jobs.send(job);
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.send(Command::CancelBatch);
+        self.send(Command::CancelEstimate);
+        self.send(Command::CancelDuplicates);
+        self.jobs = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn work(receiver: Receiver<Job>, on_event: EventSink) {
+    let mut batch: Option<JoinHandle<()>> = None;
+    let mut estimate: Option<JoinHandle<()>> = None;
+    let mut duplicates: Option<JoinHandle<()>> = None;
+
+    while let Ok(mut job) = receiver.recv() {
+        // Skip previews that a queued request already supersedes.
+        while matches!(job, Job::Preview { .. }) {
+            match receiver.try_recv() {
+                Ok(next) if matches!(next, Job::Preview { .. }) => job = next,
+                Ok(next) => {
+                    run_job(job, &on_event, &mut batch, &mut estimate, &mut duplicates);
+                    job = next;
+                }
+                Err(_) => break,
+            }
+        }
+        run_job(job, &on_event, &mut batch, &mut estimate, &mut duplicates);
+    }
+
+    for thread in [batch, estimate, duplicates].into_iter().flatten() {
+        let _ = thread.join();
+    }
+}
+
+fn run_job(
+    job: Job,
+    on_event: &EventSink,
+    batch: &mut Option<JoinHandle<()>>,
+    estimate: &mut Option<JoinHandle<()>>,
+    duplicates: &mut Option<JoinHandle<()>>,
+) {
+    match job {
+        Job::Batch {
+            paths,
+            output_dir,
+            rules,
+            manual,
+            edits,
+            naming,
+            manifest,
+            placeholder,
+            archive,
+            hook,
+            duplicates,
+            combine,
+            split,
+            cancel,
+            pause,
+        } => {
+            if let Some(previous) = batch.take() {
+                let _ = previous.join();
+            }
+
+            let on_event = on_event.clone();
+            *batch = Some(thread::spawn(move || {
+                let _span = tracing::info_span!("batch", files = paths.len()).entered();
+                let manifest =
+                    manifest.and_then(|format| match Manifest::create(&output_dir, format) {
+                        Ok(manifest) => Some(Mutex::new(manifest)),
+                        Err(e) => {
+                            tracing::warn!("no manifest is written: {}", e);
+                            None
+                        }
+                    });
+                if let Some(manifest) = &manifest {
+                    let mut manifest = manifest.lock().unwrap();
+                    for duplicate in &duplicates {
+                        if let Err(e) = manifest.record(&ManifestEntry::duplicate(duplicate)) {
+                            tracing::warn!(
+                                "{} is missing from {}: {}",
+                                duplicate.path.display(),
+                                manifest.path().display(),
+                                e
+                            );
+                        }
+                    }
+                }
+                // Only this batch's outputs go into the archive.
+                let written = Mutex::new(vec![]);
+                let srcset = Mutex::new(vec![]);
+                let on_file =
+                    |path: &Path,
+                     chosen: &ChosenSettings,
+                     mut result: Result<ProcessOutcome, ProcessError>| {
+                        // On this thread, so hooks share the limit of files
+                        // processed at once.
+                        if let (Some(hook), Ok(outcome)) = (&hook, &result) {
+                            if let Err(source) = hook::run_for(hook, chosen, outcome) {
+                                tracing::warn!("hook failed for {}: {}", path.display(), source);
+                                if hook.on_failure == HookFailure::Fail {
+                                    result = Err(ProcessError::Hook {
+                                        path: path.to_path_buf(),
+                                        source,
+                                    });
+                                }
+                            }
+                        }
+                        if let Some(manifest) = &manifest {
+                            let mut entry = ManifestEntry::new(path, chosen, &result);
+                            let thumbnail = result.as_ref().ok().and_then(|o| o.thumbnail.as_ref());
+                            if let (Some(kind), Some(thumbnail)) = (placeholder, thumbnail) {
+                                entry.add_placeholder(kind, thumbnail);
+                            }
+                            let mut manifest = manifest.lock().unwrap();
+                            if let Err(e) = manifest.record(&entry) {
+                                tracing::warn!(
+                                    "{} is missing from {}: {}",
+                                    path.display(),
+                                    manifest.path().display(),
+                                    e
+                                );
+                            }
+                        }
+                        if let (Some(_), Ok(outcome)) = (&naming.srcset, &result) {
+                            if let Some(output) = outcome.output_path() {
+                                srcset.lock().unwrap().push(SrcsetEntry::new(
+                                    path,
+                                    output,
+                                    outcome.final_dimensions,
+                                    &outcome.srcset,
+                                    &output_dir,
+                                ));
+                            }
+                        }
+                        if let (Some(_), Ok(outcome)) = (&archive, &result) {
+                            written
+                                .lock()
+                                .unwrap()
+                                .extend(outcome.output_paths.iter().cloned());
+                        }
+                        if let Ok(outcome) = &mut result {
+                            let output = outcome.output_path().map(Path::to_path_buf);
+                            if let (Some(output), Some(image)) = (output, outcome.thumbnail.take())
+                            {
+                                on_event(Event::Thumbnail {
+                                    input: path.to_path_buf(),
+                                    output,
+                                    image,
+                                });
+                            }
+                        }
+                        on_event(Event::FileDone {
+                            path: path.to_path_buf(),
+                            applied: chosen.applied.clone(),
+                            result,
+                        });
+                        // Before the thread takes its next file.
+                        pause.wait();
+                    };
+                let cancelled = match (&combine, &split) {
+                    (Some(combine), _) => {
+                        let (chosen, result) =
+                            batch::run_combined(&paths, &output_dir, *manual, combine, &naming);
+                        let first = paths.first().map_or(Path::new(""), PathBuf::as_path);
+                        on_file(first, &chosen, result);
+                        false
+                    }
+                    (None, Some(split)) => batch::run_split(
+                        &paths,
+                        &output_dir,
+                        *manual,
+                        &edits,
+                        split,
+                        &naming,
+                        &cancel,
+                        on_file,
+                    ),
+                    (None, None) => batch::run_batch_retrying(
+                        &paths,
+                        &output_dir,
+                        &rules,
+                        *manual,
+                        &edits,
+                        &naming,
+                        &cancel,
+                        |path| {
+                            // Rather than failing every file that is left.
+                            tracing::warn!("the drive is full at {}", path.display());
+                            pause.set(true);
+                            on_event(Event::DiskFull {
+                                path: path.to_path_buf(),
+                            });
+                            pause.wait();
+                            !cancel.load(Ordering::Relaxed)
+                        },
+                        on_file,
+                    ),
+                };
+                if let Some(manifest) = manifest {
+                    let manifest = manifest.into_inner().unwrap();
+                    tracing::info!("manifest written to {}", manifest.path().display());
+                }
+                let mut written = written.into_inner().unwrap();
+                if naming.srcset.is_some() {
+                    let entries = srcset.into_inner().unwrap();
+                    match srcset::write_map(&output_dir, entries, naming.overwrite) {
+                        Ok(Some(path)) => {
+                            tracing::info!("srcset map written to {}", path.display());
+                            written.push(path);
+                        }
+                        Ok(None) => tracing::info!("the srcset map exists already"),
+                        Err(e) => tracing::warn!("no srcset map is written: {}", e),
+                    }
+                }
+                if let Some(archive) = archive.filter(|_| !cancelled) {
+                    written.sort();
+                    let result = archive::create_zip(
+                        &output_dir,
+                        &written,
+                        &archive,
+                        naming.overwrite,
+                        |done, total| on_event(Event::ArchiveProgress { done, total }),
+                    );
+                    on_event(Event::ArchiveDone { result });
+                }
+                tracing::info!(cancelled, "batch finished");
+                on_event(Event::BatchFinished { cancelled });
+            }));
+        }
+        Job::Preview {
+            generation,
+            path,
+            source,
+            info,
+            scale,
+        } => {
+            let _span =
+                tracing::debug_span!("preview", path = %path.display(), generation).entered();
+            let ctx = FileContext { path: &path };
+            let result = Pipeline::for_info(&info.scaled(scale)).run((*source).clone(), &ctx);
+            on_event(Event::Preview {
+                generation,
+                info,
+                result,
+            });
+        }
+        Job::Estimate {
+            key,
+            paths,
+            rules,
+            manual,
+            cancel,
+        } => {
+            // A superseded estimate is cancelled already and left to end on
+            // its own, so previews don't wait for its samples.
+            let on_event = on_event.clone();
+            *estimate = Some(thread::spawn(move || {
+                let _span = tracing::debug_span!("estimate", files = paths.len()).entered();
+                let result = match estimate::estimate(&paths, &rules, manual, &cancel) {
+                    Ok(Some(estimate)) => Ok(estimate),
+                    Ok(None) => return,
+                    Err(e) => Err(e),
+                };
+                on_event(Event::SizeEstimate { key, result });
+            }));
+        }
+        Job::Duplicates {
+            paths,
+            threshold,
+            cancel,
+        } => {
+            // Like estimates, a superseded search ends on its own.
+            let on_event = on_event.clone();
+            *duplicates = Some(thread::spawn(move || {
+                let _span = tracing::debug_span!("duplicates", files = paths.len()).entered();
+                let found =
+                    duplicates::find_duplicates(&paths, threshold, &cancel, |done, total| {
+                        on_event(Event::DuplicateProgress { done, total })
+                    });
+                if let Some(groups) = found {
+                    tracing::info!(groups = groups.len(), "duplicates found");
+                    on_event(Event::Duplicates { groups });
+                }
+            }));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::{fs, path::Path};
+
+/// Widgets that screen readers announce without a name unless one is given.
+const UNNAMED: &[&str] = &[
+    "Slider::new(",
+    "DragValue::new(",
+    "text_edit_singleline(",
+    "TextEdit::singleline(",
+    "TextEdit::multiline(",
+    "ComboBox::from_id_salt(",
+    "color_edit_button_srgba(",
+    "toggle_value(",
+    "Image::new(",
+    "Sense::click(",
+    "Sense::drag(",
+];
+
+/// Ways of naming a widget: its own text, a label next to it, or the
+/// helpers in src/a11y.rs.
+const NAMING: &[&str] = &[".text(", ".labelled_by(", ".alt_text(", "a11y::"];
+
+/// The statement around `at`: from the `;` or brace before
+/// it to the `;` or brace after it, skipping over whatever is nested in
+/// brackets and string literals in between.
+fn statement(source: &str, at: usize) -> &str {
+    let bytes = source.as_bytes();
+    let skip_string = |mut i: usize, step: isize| loop {
+        i = (i as isize + step) as usize;
+        if bytes[i] == b'"' && bytes[i - 1] != b'\\' {
+            return i;
+        }
+    };
+
+    let mut start = at;
+    let mut depth = 0;
+    while start > 0 {
+        let i = start - 1;
+        match bytes[i] {
+            b'"' => {
+                start = skip_string(i, -1);
+                continue;
+            }
+            b')' | b']' => depth += 1,
+            b'}' if depth > 0 => depth += 1,
+            b'(' | b'[' | b'{' if depth > 0 => depth -= 1,
+            b';' | b'{' | b'}' if depth == 0 => break,
+            _ => {}
+        }
+        start = i;
+    }
+
+    let mut end = at;
+    let mut depth = 0;
+    while end < bytes.len() {
+        match bytes[end] {
+            b'"' => end = skip_string(end, 1),
+            b'(' | b'[' => depth += 1,
+            b'{' if depth > 0 => depth += 1,
+            b')' | b']' => depth -= 1,
+            b'}' if depth > 0 => depth -= 1,
+            b';' | b'{' | b'}' if depth <= 0 => break,
+            _ => {}
+        }
+        end += 1;
+    }
+    &source[start..end]
+}
+
+/// Buttons whose text is a literal without any letters, like "▶".
+fn icon_buttons(source: &str) -> impl Iterator<Item = usize> + '_ {
+    ["button(\"", "small_button(\""]
+        .into_iter()
+        .flat_map(move |call| {
+            source.match_indices(call).filter_map(move |(at, _)| {
+                let literal = &source[at + call.len()..];
+                let text = &literal[..literal.find('"')?];
+                (!text.chars().any(char::is_alphanumeric)).then_some(at)
+            })
+        })
+}
+
+#[test]
+fn every_interactive_widget_has_an_accessible_name() {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut unnamed = vec![];
+    let mut checked = 0;
+    for entry in fs::read_dir(src).unwrap() {
+        let path = entry.unwrap().path();
+        let file = path.file_name().unwrap().to_string_lossy().into_owned();
+        if !file.ends_with(".rs") || file == "a11y.rs" {
+            continue;
+        }
+        let source = fs::read_to_string(&path).unwrap();
+        let widgets = UNNAMED
+            .iter()
+            .flat_map(|widget| source.match_indices(widget).map(|(at, _)| at))
+            .chain(icon_buttons(&source));
+        for at in widgets {
+            checked += 1;
+            let statement = statement(&source, at);
+            if !NAMING.iter().any(|naming| statement.contains(naming)) {
+                let line = source[..at].lines().count();
+                unnamed.push(format!("{}:{}: {}", file, line, statement.trim()));
+            }
+        }
+    }
+    assert!(checked > 20, "found only {} widgets", checked);
+    assert!(
+        unnamed.is_empty(),
+        "no accessible name:\n{}",
+        unnamed.join("\n\n")
+    );
+}
+
+#[test]
+fn statements_end_at_the_braces_around_them() {
+    let source = "fn f() { let a = 1; ui.add(Slider::new(&mut x, 0..=1)).labelled_by(id); b(); }";
+    let at = source.find("Slider").unwrap();
+    assert_eq!(
+        statement(source, at),
+        " ui.add(Slider::new(&mut x, 0..=1)).labelled_by(id)"
+    );
+
+    let source = "if ui.add(Slider::new(&mut x, 0..=1)).changed() { a(\"}\"); } c.labelled_by(id);";
+    let at = source.find("Slider").unwrap();
+    assert_eq!(
+        statement(source, at),
+        "if ui.add(Slider::new(&mut x, 0..=1)).changed() "
+    );
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::{
+    archive::{ArchiveSettings, ZipCompression},
+    naming::{BatchNaming, OverwritePolicy},
+    worker::{Command, Event, Worker},
+    ProcessInfo,
+};
+
+/// Inputs in the root and a subfolder of `dir`, plus one that can't be decoded.
+fn inputs(dir: &Path) -> Vec<PathBuf> {
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    let mut paths: Vec<PathBuf> = ["a.png", "sub/b.png"]
+        .iter()
+        .map(|name| {
+            let path = dir.join(name);
+            RgbaImage::from_pixel(4, 3, Rgba([9, 9, 9, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect();
+    let broken = dir.join("broken.png");
+    fs::write(&broken, "not a png").unwrap();
+    paths.push(broken);
+    paths
+}
+
+/// Runs a batch that zips its outputs, returning the archive's result and
+/// the progress reported on the way.
+fn run(
+    input_dir: &Path,
+    output_dir: &Path,
+    archive: ArchiveSettings,
+    overwrite: OverwritePolicy,
+) -> (Option<PathBuf>, Vec<(usize, usize)>) {
+    let (tx, events) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let worker = Worker::spawn(move |event| {
+        let _ = tx.lock().unwrap().send(event);
+    });
+    worker.send(Command::StartBatch {
+        paths: inputs(input_dir),
+        output_dir: output_dir.to_path_buf(),
+        rules: vec![],
+        manual: ProcessInfo::default(),
+        edits: HashMap::new(),
+        naming: BatchNaming {
+            mirror_root: Some(input_dir.to_path_buf()),
+            overwrite,
+            ..BatchNaming::default()
+        },
+        manifest: None,
+        placeholder: None,
+        archive: Some(archive),
+        hook: None,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+
+    let mut progress = Vec::new();
+    let mut archive = None;
+    loop {
+        match events.recv_timeout(Duration::from_secs(30)).unwrap() {
+            Event::ArchiveProgress { done, total } => progress.push((done, total)),
+            Event::ArchiveDone { result } => archive = Some(result.unwrap()),
+            Event::BatchFinished { .. } => break,
+            _ => {}
+        }
+    }
+    (archive.expect("no archive was reported"), progress)
+}
+
+#[test]
+fn outputs_of_the_batch_are_zipped_with_their_folders() {
+    let input_dir = tempfile::tempdir().unwrap();
+    let output_dir = tempfile::tempdir().unwrap();
+    fs::write(output_dir.path().join("unrelated.txt"), "not ours").unwrap();
+
+    let settings = ArchiveSettings {
+        name: "batch".to_string(),
+        compression: ZipCompression::Deflate,
+    };
+    let (path, progress) = run(
+        input_dir.path(),
+        output_dir.path(),
+        settings.clone(),
+        OverwritePolicy::Overwrite,
+    );
+    let path = path.unwrap();
+    assert_eq!(path, output_dir.path().join("batch.zip"));
+    assert_eq!(progress.last(), Some(&(2, 2)));
+
+    let mut zip = zip::ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+    let names: BTreeSet<String> = zip
+        .file_names()
+        .map(|name| name.unwrap().into_owned())
+        .collect();
+    let expected: BTreeSet<String> = ["a_bordered.png", "sub/b_bordered.png"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    assert_eq!(names, expected);
+
+    let mut zipped = Vec::new();
+    zip.by_name("sub/b_bordered.png")
+        .unwrap()
+        .read_to_end(&mut zipped)
+        .unwrap();
+    assert_eq!(
+        zipped,
+        fs::read(output_dir.path().join("sub/b_bordered.png")).unwrap()
+    );
+
+    // A second batch follows the overwrite policy for the archive too.
+    let (renamed, _) = run(
+        input_dir.path(),
+        output_dir.path(),
+        settings.clone(),
+        OverwritePolicy::Rename,
+    );
+    assert_eq!(renamed, Some(output_dir.path().join("batch (1).zip")));
+
+    let before = fs::read(&path).unwrap();
+    let (skipped, _) = run(
+        input_dir.path(),
+        output_dir.path(),
+        settings,
+        OverwritePolicy::Skip,
+    );
+    assert_eq!(skipped, None);
+    assert_eq!(fs::read(&path).unwrap(), before);
+}
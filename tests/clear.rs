@@ -0,0 +1,114 @@
+use std::{fs, path::Path};
+
+use image_finalizer::{
+    clear::{clear, plan_clear, ClearError},
+    manifest::{ManifestEntry, ManifestStatus},
+    naming::DEFAULT_SUFFIX,
+};
+
+fn names(paths: impl Iterator<Item = impl AsRef<Path>>, root: &Path) -> Vec<String> {
+    let mut names: Vec<String> = paths
+        .map(|path| {
+            path.as_ref()
+                .strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn refuses_folders_holding_the_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("photos");
+    fs::create_dir_all(&input).unwrap();
+
+    assert!(matches!(
+        plan_clear(&input, &input, DEFAULT_SUFFIX, "outputs"),
+        Err(ClearError::SameAsInput)
+    ));
+    assert!(matches!(
+        plan_clear(dir.path(), &input, DEFAULT_SUFFIX, "outputs"),
+        Err(ClearError::ContainsInput)
+    ));
+    assert!(matches!(
+        plan_clear(Path::new("/"), &input, DEFAULT_SUFFIX, "outputs"),
+        Err(ClearError::Root(_))
+    ));
+}
+
+#[test]
+fn lists_unknown_files_and_deletes_only_inside_the_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("photos");
+    let output = dir.path().join("out");
+    fs::create_dir_all(&input).unwrap();
+    fs::create_dir_all(output.join("sub")).unwrap();
+
+    fs::write(output.join("a_bordered.png"), "output").unwrap();
+    fs::write(output.join("sub").join("custom.png"), "named by a template").unwrap();
+    fs::write(output.join("outputs.zip"), "archive").unwrap();
+    fs::write(output.join("notes.txt"), "not ours").unwrap();
+    let entry = ManifestEntry {
+        source: input.join("custom.jpg").display().to_string(),
+        outputs: vec![output.join("sub").join("custom.png").display().to_string()],
+        width: 1,
+        height: 1,
+        format: "png".to_string(),
+        quality: None,
+        bytes: 19,
+        duration_ms: 0,
+        status: ManifestStatus::Written,
+        message: None,
+        settings: "manual settings".to_string(),
+        settings_hash: String::new(),
+        duplicate_of: None,
+        blurhash: None,
+        thumbhash: None,
+    };
+    fs::write(
+        output.join("manifest-20240615-143205.jsonl"),
+        serde_json::to_string(&entry).unwrap() + "\n",
+    )
+    .unwrap();
+
+    let outside = dir.path().join("elsewhere");
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("keep.png"), "outside").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&outside, output.join("link")).unwrap();
+
+    let plan = plan_clear(&output, &input, DEFAULT_SUFFIX, "outputs").unwrap();
+    let mut unknown = vec!["notes.txt"];
+    if cfg!(unix) {
+        unknown.insert(0, "link");
+    }
+    assert_eq!(
+        names(plan.unknown().map(|entry| &entry.path), &output),
+        unknown
+    );
+    assert_eq!(plan.entries.len(), unknown.len() + 4);
+    assert_eq!(names(plan.folders.iter(), &output), ["sub"]);
+
+    // Added after the plan, so kept.
+    fs::write(output.join("new.txt"), "late").unwrap();
+    let mut progress = vec![];
+    let outcome = clear(&plan, |done, total| progress.push((done, total)));
+    assert!(outcome.errors.is_empty(), "{:?}", outcome.errors);
+    assert_eq!(outcome.deleted, plan.entries.len());
+    assert_eq!(
+        progress.last(),
+        Some(&(plan.entries.len(), plan.entries.len()))
+    );
+
+    let left: Vec<_> = fs::read_dir(&output)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(names(left.iter(), &output), ["new.txt"]);
+    assert!(outside.join("keep.png").exists());
+    assert!(input.exists());
+}
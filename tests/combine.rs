@@ -0,0 +1,118 @@
+use std::fs;
+
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage, Rgba, RgbaImage};
+use image_finalizer::{
+    batch,
+    combine::{self, CombineAlign, CombineSettings, Placement},
+    format::OutputFormat,
+    naming::BatchNaming,
+    ProcessError, ProcessInfo,
+};
+
+fn settings(align: CombineAlign, gutter_percentage: f32) -> CombineSettings {
+    CombineSettings {
+        align,
+        gutter_percentage,
+        ..CombineSettings::default()
+    }
+}
+
+#[test]
+fn photos_are_scaled_to_the_smallest_shared_side() {
+    let layout = combine::layout(
+        &[(400, 300), (200, 400)],
+        &settings(CombineAlign::Heights, 10.0),
+    );
+    assert_eq!((layout.width, layout.height), (400 + 30 + 150, 300));
+    assert_eq!(
+        layout.placements,
+        [
+            Placement {
+                x: 0,
+                y: 0,
+                width: 400,
+                height: 300
+            },
+            Placement {
+                x: 430,
+                y: 0,
+                width: 150,
+                height: 300
+            },
+        ]
+    );
+
+    let layout = combine::layout(
+        &[(400, 300), (200, 400), (300, 300)],
+        &settings(CombineAlign::Widths, 0.0),
+    );
+    assert_eq!((layout.width, layout.height), (200, 150 + 400 + 200));
+    let ys: Vec<u32> = layout.placements.iter().map(|place| place.y).collect();
+    assert_eq!(ys, [0, 150, 550]);
+    assert!(layout.placements.iter().all(|place| place.width == 200));
+}
+
+#[test]
+fn gutters_take_the_border_color() {
+    let red = DynamicImage::from(RgbImage::from_pixel(10, 10, Rgb([255, 0, 0])));
+    let blue = DynamicImage::from(RgbImage::from_pixel(20, 20, Rgb([0, 0, 255])));
+    let white = Rgba([255, 255, 255, 255]);
+    let combined = combine::combine(&[red, blue], &settings(CombineAlign::Heights, 20.0), white);
+    assert_eq!(combined.dimensions(), (22, 10));
+    assert_eq!(combined.get_pixel(5, 5), Rgba([255, 0, 0, 255]));
+    assert_eq!(combined.get_pixel(10, 5), white);
+    assert_eq!(combined.get_pixel(11, 5), white);
+    assert_eq!(combined.get_pixel(17, 5), Rgba([0, 0, 255, 255]));
+}
+
+#[test]
+fn combined_output_is_named_after_every_photo() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths: Vec<_> = ["left", "right"]
+        .iter()
+        .map(|name| {
+            let path = dir.path().join(format!("{}.png", name));
+            RgbaImage::from_pixel(40, 30, Rgba([0, 0, 0, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect();
+    let output_dir = dir.path().join("out");
+    let manual = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 0.0,
+        output_format: OutputFormat::Png,
+        ..ProcessInfo::default()
+    };
+    let combine = settings(CombineAlign::Heights, 0.0);
+
+    let (_, result) = batch::run_combined(
+        &paths,
+        &output_dir,
+        manual.clone(),
+        &combine,
+        &BatchNaming::default(),
+    );
+    let outcome = result.unwrap();
+    assert_eq!(outcome.output_paths.len(), 1);
+    let output = &outcome.output_paths[0];
+    assert_eq!(
+        output.file_name().unwrap(),
+        format!("left+right{}.png", BatchNaming::default().suffix).as_str()
+    );
+    assert_eq!(image::image_dimensions(output).unwrap(), (80, 30));
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 1);
+
+    let (_, result) = batch::run_combined(
+        &paths[..1],
+        &output_dir,
+        manual,
+        &combine,
+        &BatchNaming::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(ProcessError::CombineCount { count: 1, .. })
+    ));
+}
@@ -0,0 +1,160 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use image_finalizer::{
+    batch,
+    format::OutputFormat,
+    output_dimensions,
+    pipeline::{FileContext, Pipeline},
+    process_file, ProcessInfo,
+};
+
+fn gradient(width: u32, height: u32) -> DynamicImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        Rgba([(x * 255 / width) as u8, (y * 255 / height) as u8, 128, 255])
+    })
+    .into()
+}
+
+/// What the preview shows for `img`.
+fn compose_bordered(img: &DynamicImage, info: &ProcessInfo) -> DynamicImage {
+    let ctx = FileContext {
+        path: Path::new("photo.png"),
+    };
+    Pipeline::for_info(info).run(img.clone(), &ctx).unwrap()
+}
+
+fn settings() -> Vec<ProcessInfo> {
+    let mut all = vec![];
+    for symmetrical_border in [false, true] {
+        for resize_images in [false, true] {
+            for border_percentage in [0.0, 7.5, 25.0] {
+                all.push(ProcessInfo {
+                    symmetrical_border,
+                    resize_images,
+                    border_percentage,
+                    resize_longest_dimension: 50,
+                    ..ProcessInfo::default()
+                });
+            }
+        }
+    }
+    all
+}
+
+#[test]
+fn output_dimensions_match_composite() {
+    for (width, height) in [(40, 30), (30, 40), (33, 33)] {
+        let img = gradient(width, height);
+        for info in settings() {
+            assert_eq!(
+                compose_bordered(&img, &info).dimensions(),
+                output_dimensions(width, height, &info),
+                "{}x{} with {:?}",
+                width,
+                height,
+                info
+            );
+        }
+    }
+}
+
+#[test]
+fn written_file_matches_composite() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("photo.png");
+    let img = gradient(40, 30);
+    img.save(&input).unwrap();
+
+    for info in settings() {
+        let info = ProcessInfo {
+            output_format: OutputFormat::Png,
+            ..info
+        };
+        let outcome = process_file(&input, info.clone(), &dir.path().join("out")).unwrap();
+        let output = &outcome.output_paths[0];
+
+        let written = image::open(output).unwrap();
+        assert_eq!(
+            written.to_rgba8(),
+            compose_bordered(&img, &info).to_rgba8(),
+            "{:?}",
+            info
+        );
+    }
+}
+
+#[test]
+fn batch_reports_every_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let inputs: Vec<PathBuf> = ["a.png", "b.png", "c.png"]
+        .iter()
+        .map(|name| {
+            let path = dir.path().join(name);
+            gradient(20, 10).save(&path).unwrap();
+            path
+        })
+        .collect();
+    let output_dir = dir.path().join("out");
+
+    let done = Mutex::new(vec![]);
+    batch::run_batch(
+        &inputs,
+        &output_dir,
+        &[],
+        ProcessInfo::default(),
+        |path, _, result| {
+            let outcome = result.unwrap();
+            assert!(outcome.output_paths[0].exists());
+            done.lock().unwrap().push(path.to_path_buf());
+        },
+    );
+
+    let mut done = done.into_inner().unwrap();
+    done.sort();
+    assert_eq!(done, inputs);
+}
+
+#[test]
+fn outcome_describes_the_written_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("photo.png");
+    gradient(40, 30).save(&input).unwrap();
+
+    for format in [OutputFormat::Png, OutputFormat::Jpeg, OutputFormat::Webp] {
+        let info = ProcessInfo {
+            output_format: format,
+            symmetrical_border: true,
+            resize_images: true,
+            resize_longest_dimension: 50,
+            ..ProcessInfo::default()
+        };
+        let outcome = process_file(&input, info.clone(), &dir.path().join("out")).unwrap();
+
+        assert_eq!(outcome.input, input);
+        assert_eq!(outcome.output_paths.len(), 1);
+        let output = outcome.output_path().unwrap();
+        assert_eq!(
+            outcome.bytes_written,
+            std::fs::metadata(output).unwrap().len()
+        );
+        assert_eq!(outcome.final_dimensions, output_dimensions(40, 30, &info));
+        assert_eq!(
+            image::open(output).unwrap().dimensions(),
+            outcome.final_dimensions
+        );
+        assert_eq!(
+            outcome
+                .durations
+                .steps
+                .iter()
+                .map(|(step, _)| *step)
+                .collect::<Vec<_>>(),
+            ["border", "resize"]
+        );
+        assert!(outcome.skipped.is_none());
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use image::{DynamicImage, GenericImageView, ImageBuffer, Rgb, RgbImage, Rgba};
+use image_finalizer::{
+    corners::{CornerRadius, RadiusUnit},
+    pipeline::{FileContext, Pipeline},
+    ProcessInfo,
+};
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+fn pixels(radius: f32) -> CornerRadius {
+    CornerRadius {
+        radius,
+        unit: RadiusUnit::Pixels,
+    }
+}
+
+fn alpha(img: &DynamicImage, x: u32, y: u32) -> u8 {
+    img.get_pixel(x, y).0[3]
+}
+
+#[test]
+fn radius_is_in_pixels_or_percent_of_the_shortest_side() {
+    assert_eq!(pixels(24.0).pixels(400, 300), 24.0);
+    let percent = CornerRadius {
+        radius: 10.0,
+        unit: RadiusUnit::Percent,
+    };
+    assert_eq!(percent.pixels(400, 300), 30.0);
+    assert_eq!(percent.pixels(300, 400), 30.0);
+    // Never more than a circle fits.
+    assert_eq!(pixels(500.0).pixels(400, 300), 150.0);
+}
+
+#[test]
+fn corners_are_cut_and_edges_kept() {
+    let photo: DynamicImage = RgbImage::from_pixel(100, 60, Rgb([255, 0, 0])).into();
+    let rounded = pixels(20.0).apply(&photo);
+    assert_eq!(rounded.dimensions(), (100, 60));
+    for (x, y) in [(0, 0), (99, 0), (0, 59), (99, 59), (3, 3), (96, 56)] {
+        assert_eq!(alpha(&rounded, x, y), 0, "({}, {})", x, y);
+    }
+    for (x, y) in [(20, 0), (0, 20), (50, 30), (99, 30), (50, 59), (10, 10)] {
+        assert_eq!(alpha(&rounded, x, y), 255, "({}, {})", x, y);
+    }
+}
+
+#[test]
+fn the_rounding_is_antialiased() {
+    let photo: DynamicImage = RgbImage::from_pixel(100, 100, Rgb([255, 0, 0])).into();
+    let rounded = pixels(40.0).apply(&photo);
+    // Along the diagonal the edge falls between full and empty pixels.
+    let diagonal: Vec<u8> = (0..40).map(|i| alpha(&rounded, i, i)).collect();
+    assert!(diagonal.iter().any(|&a| a > 0 && a < 255), "{:?}", diagonal);
+    assert!(diagonal.windows(2).all(|pair| pair[0] <= pair[1]));
+    // The four corners match.
+    for (x, y) in [(12, 14), (14, 12)] {
+        let a = alpha(&rounded, x, y);
+        assert_eq!(alpha(&rounded, 99 - x, y), a);
+        assert_eq!(alpha(&rounded, x, 99 - y), a);
+        assert_eq!(alpha(&rounded, 99 - x, 99 - y), a);
+    }
+}
+
+#[test]
+fn no_radius_leaves_the_photo_alone() {
+    let photo: DynamicImage = RgbImage::from_pixel(10, 10, Rgb([1, 2, 3])).into();
+    assert_eq!(pixels(0.0).apply(&photo), photo);
+}
+
+#[test]
+fn deep_photos_stay_deep() {
+    let photo: DynamicImage = ImageBuffer::from_pixel(40, 40, Rgba([65535u16, 0, 0, 65535])).into();
+    let rounded = pixels(10.0).apply(&photo);
+    assert_eq!(rounded.color(), image::ColorType::Rgba16);
+    assert_eq!(rounded.as_rgba16().unwrap().get_pixel(0, 0).0[3], 0);
+    assert_eq!(rounded.as_rgba16().unwrap().get_pixel(20, 20).0[3], 65535);
+}
+
+#[test]
+fn the_border_shows_through_the_corners() {
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 20.0,
+        border_color: WHITE,
+        corner_radius: Some(pixels(10.0)),
+        ..ProcessInfo::default()
+    };
+    let ctx = FileContext {
+        path: Path::new("photo.png"),
+    };
+    let photo: DynamicImage = RgbImage::from_pixel(60, 40, Rgb([255, 0, 0])).into();
+    let out = Pipeline::for_info(&info)
+        .run(photo, &ctx)
+        .unwrap()
+        .to_rgba8();
+    assert_eq!(out.dimensions(), (72, 52));
+    // The photo starts at (6, 6).
+    assert_eq!(*out.get_pixel(6, 6), WHITE);
+    assert_eq!(*out.get_pixel(65, 45), WHITE);
+    assert_eq!(*out.get_pixel(36, 6), RED);
+    assert_eq!(*out.get_pixel(16, 16), RED);
+    // Blended into the border rather than cut hard.
+    let corner: Vec<Rgba<u8>> = (6..16)
+        .flat_map(|y| (6..16).map(move |x| (x, y)))
+        .map(|(x, y)| *out.get_pixel(x, y))
+        .collect();
+    assert!(corner.iter().any(|pixel| *pixel != WHITE && *pixel != RED));
+    // `overlay` rounds the blended alpha down, a step short of opaque at
+    // worst.
+    assert!(corner.iter().all(|pixel| pixel.0[3] >= 254));
+}
+
+#[test]
+fn radii_parse_from_text() {
+    assert_eq!("24".parse::<CornerRadius>().unwrap(), pixels(24.0));
+    assert_eq!("24px".parse::<CornerRadius>().unwrap(), pixels(24.0));
+    assert_eq!(
+        " 5% ".parse::<CornerRadius>().unwrap(),
+        CornerRadius {
+            radius: 5.0,
+            unit: RadiusUnit::Percent
+        }
+    );
+    for bad in ["", "%", "-3", "round", "nan"] {
+        assert!(bad.parse::<CornerRadius>().is_err(), "{}", bad);
+    }
+    for radius in [pixels(12.5), "7%".parse().unwrap()] {
+        assert_eq!(radius.to_string().parse::<CornerRadius>().unwrap(), radius);
+    }
+}
+
+#[test]
+fn radii_round_trip_through_toml() {
+    let info = ProcessInfo {
+        corner_radius: Some(CornerRadius {
+            radius: 4.0,
+            unit: RadiusUnit::Percent,
+        }),
+        ..ProcessInfo::default()
+    };
+    let text = toml::to_string_pretty(&info).unwrap();
+    assert!(text.contains("[corner_radius]"), "{}", text);
+    assert!(text.contains("unit = \"percent\""), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    assert!(!toml::to_string(&ProcessInfo::default())
+        .unwrap()
+        .contains("corner_radius"));
+}
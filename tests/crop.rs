@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Mutex},
+};
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use image_finalizer::{
+    batch,
+    crop::{self, Crop, CropRect},
+    edit::ImageEdit,
+    naming::BatchNaming,
+    output_dimensions,
+    pipeline::Pipeline,
+    transform::{Rotation, Transform},
+    trim::Trim,
+    ProcessInfo,
+};
+
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+fn gradient(width: u32, height: u32) -> DynamicImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        Rgba([(x * 255 / width) as u8, (y * 255 / height) as u8, 128, 255])
+    })
+    .into()
+}
+
+/// Settings that leave the photo as it is but for `crop`.
+fn cropping(crop: Crop) -> ProcessInfo {
+    ProcessInfo {
+        crop,
+        symmetrical_border: true,
+        border_percentage: 0.0,
+        ..ProcessInfo::default()
+    }
+}
+
+#[test]
+fn straightening_is_a_resampled_clockwise_turn() {
+    let img = gradient(31, 31);
+    // A quarter turn lands on whole pixels, so it matches the exact one.
+    let turned = crop::straighten(img.clone(), 90.0).unwrap().to_rgba8();
+    let exact = img.rotate90().to_rgba8();
+    assert_eq!(turned.dimensions(), exact.dimensions());
+    for (ours, theirs) in turned.pixels().zip(exact.pixels()) {
+        for (a, b) in ours.0.iter().zip(theirs.0) {
+            assert!(a.abs_diff(b) <= 1, "{:?} != {:?}", ours, theirs);
+        }
+    }
+
+    assert_eq!(crop::straighten(img.clone(), 0.0).unwrap(), img);
+    // 16 bits per channel stay 16 bits.
+    let deep = DynamicImage::ImageRgba16(gradient(8, 8).to_rgba16());
+    assert_eq!(
+        crop::straighten(deep, 3.0).unwrap().color(),
+        image::ColorType::Rgba16
+    );
+}
+
+#[test]
+fn straightening_cuts_off_the_empty_corners() {
+    assert_eq!(crop::straightened_size(100, 100, 0.0), (100, 100));
+    // 1 / (cos 10° + sin 10°) of the side.
+    assert_eq!(crop::straightened_size(100, 100, 10.0), (86, 86));
+    assert_eq!(crop::straightened_size(100, 100, -10.0), (86, 86));
+    let (width, height) = crop::straightened_size(300, 200, 2.0);
+    assert!((width as f32 / height as f32 - 1.5).abs() < 0.02);
+
+    // Nothing of the transparent surroundings shows in the result.
+    let opaque: DynamicImage = RgbaImage::from_pixel(60, 40, RED).into();
+    let straightened = crop::straighten(opaque, -7.5).unwrap().to_rgba8();
+    assert!(straightened.pixels().all(|pixel| *pixel == RED));
+}
+
+#[test]
+fn dragged_crops_stay_inside_and_keep_the_ratio() {
+    let rect = CropRect::from_drag((0.9, 0.9), (0.5, 0.1), (200, 100), None);
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+    assert!(close(rect.x, 0.5) && close(rect.y, 0.1));
+    assert!(close(rect.width, 0.4) && close(rect.height, 0.8));
+
+    let outside = CropRect::from_drag((0.5, 0.5), (1.5, -0.2), (200, 100), None);
+    assert_eq!((outside.x, outside.y), (0.5, 0.0));
+    assert_eq!((outside.width, outside.height), (0.5, 0.5));
+
+    // 160×40 pixels dragged, kept square on the shorter side.
+    let square = CropRect::from_drag((0.1, 0.1), (0.9, 0.5), (200, 100), Some(1.0));
+    assert_eq!(square.pixels(200, 100), (20, 10, 40, 40));
+
+    assert_eq!(CropRect::FULL.pixels(200, 100), (0, 0, 200, 100));
+    // Never less than a pixel.
+    let empty = CropRect::from_drag((0.5, 0.5), (0.5, 0.5), (200, 100), None);
+    assert_eq!(empty.pixels(200, 100), (100, 50, 1, 1));
+}
+
+#[test]
+fn the_crop_comes_after_the_turn_and_before_the_border() {
+    let info = ProcessInfo {
+        trim: Trim {
+            enabled: true,
+            ..Trim::default()
+        },
+        transform: Transform {
+            rotation: Rotation::Cw90,
+            ..Transform::default()
+        },
+        crop: Crop {
+            angle: 1.0,
+            rect: CropRect::FULL,
+        },
+        ..ProcessInfo::default()
+    };
+    assert_eq!(
+        Pipeline::for_info(&info).step_names(),
+        ["trim", "transform", "crop", "border"]
+    );
+
+    let crop = Crop {
+        angle: 4.0,
+        rect: CropRect {
+            x: 0.25,
+            y: 0.0,
+            width: 0.5,
+            height: 0.5,
+        },
+    };
+    assert_eq!(crop.describe().unwrap(), "straightened +4.0°, cropped");
+    let info = cropping(crop);
+    let out = Pipeline::for_info(&info)
+        .run(
+            gradient(120, 80),
+            &image_finalizer::pipeline::FileContext {
+                path: std::path::Path::new("gradient.png"),
+            },
+        )
+        .unwrap();
+    assert_eq!(out.dimensions(), output_dimensions(120, 80, &info));
+}
+
+#[test]
+fn crops_are_set_per_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths: Vec<PathBuf> = ["a.png", "b.png"]
+        .iter()
+        .map(|name| {
+            let path = dir.path().join(name);
+            RgbaImage::from_fn(40, 20, |x, _| if x < 20 { RED } else { BLUE })
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect();
+    let left_half = Crop {
+        angle: 0.0,
+        rect: CropRect {
+            x: 0.0,
+            y: 0.0,
+            width: 0.5,
+            height: 1.0,
+        },
+    };
+    let edits = HashMap::from([(
+        paths[0].clone(),
+        ImageEdit {
+            crop: left_half,
+            ..ImageEdit::default()
+        },
+    )]);
+
+    let outcomes = Mutex::new(HashMap::new());
+    batch::run_batch_retrying(
+        &paths,
+        &dir.path().join("out"),
+        &[],
+        cropping(Crop::default()),
+        &edits,
+        &BatchNaming::default(),
+        &AtomicBool::new(false),
+        |_| false,
+        |path, _, result| {
+            outcomes
+                .lock()
+                .unwrap()
+                .insert(path.to_path_buf(), result.unwrap());
+        },
+    );
+
+    let outcomes = outcomes.into_inner().unwrap();
+    let cropped = &outcomes[&paths[0]];
+    assert_eq!(cropped.final_dimensions, (20, 20));
+    let output = image::open(cropped.output_path().unwrap())
+        .unwrap()
+        .to_rgba8();
+    assert!(output.pixels().all(|pixel| *pixel == RED));
+    assert_eq!(outcomes[&paths[1]].final_dimensions, (40, 20));
+}
+
+#[test]
+fn edits_are_stored_compactly() {
+    assert_eq!(serde_json::to_string(&ImageEdit::default()).unwrap(), "{}");
+    let edit = ImageEdit {
+        transform: None,
+        crop: Crop {
+            angle: -1.5,
+            rect: CropRect::FULL,
+        },
+    };
+    let text = serde_json::to_string(&edit).unwrap();
+    assert_eq!(serde_json::from_str::<ImageEdit>(&text).unwrap(), edit);
+    assert!(!toml::to_string(&ProcessInfo::default())
+        .unwrap()
+        .contains("crop"));
+}
+
+#[test]
+fn angles_that_are_not_finite_are_refused() {
+    for angle in ["nan", "inf", "-inf"] {
+        let text = format!("[crop]\nangle = {}\n", angle);
+        assert!(toml::from_str::<ImageEdit>(&text).is_err(), "{}", angle);
+    }
+    // Finite ones beyond the slider are kept to it.
+    let edit: ImageEdit = toml::from_str("[crop]\nangle = 45.0\n").unwrap();
+    assert_eq!(edit.crop.angle, crop::MAX_ANGLE);
+
+    // Set in code, they fail the file instead of panicking.
+    assert!(matches!(
+        crop::straighten(gradient(8, 8), f32::NAN),
+        Err(crop::CropError::InvalidAngle(angle)) if angle.is_nan()
+    ));
+    let crop = Crop {
+        angle: f32::INFINITY,
+        rect: CropRect::FULL,
+    };
+    let result = Pipeline::for_info(&cropping(crop)).run(
+        gradient(8, 8),
+        &image_finalizer::pipeline::FileContext {
+            path: std::path::Path::new("a.png"),
+        },
+    );
+    assert!(matches!(
+        result,
+        Err(image_finalizer::ProcessError::Crop { .. })
+    ));
+}
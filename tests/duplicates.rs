@@ -0,0 +1,117 @@
+use std::{fs, path::Path, sync::atomic::AtomicBool, time::SystemTime};
+
+use image::{imageops::FilterType, DynamicImage, Rgb, RgbImage};
+use image_finalizer::duplicates::{
+    self, DuplicateGroup, KeepPolicy, ScannedFile, DEFAULT_THRESHOLD,
+};
+
+/// A photo-like image: a gradient falling to the right, or rising if
+/// `mirrored`, with bright stripes.
+fn scene(mirrored: bool) -> DynamicImage {
+    RgbImage::from_fn(240, 160, |x, y| {
+        let x = if mirrored { 239 - x } else { x };
+        if (x / 30 + y / 40) % 3 == 0 {
+            Rgb([250, 240, 230])
+        } else {
+            let v = 255 - x as u8;
+            Rgb([v, v / 2, 255 - v])
+        }
+    })
+    .into()
+}
+
+fn scanned(path: &str, size: (u32, u32), bytes: u64, modified: u64) -> ScannedFile {
+    ScannedFile {
+        path: path.into(),
+        hash: 0,
+        width: size.0,
+        height: size.1,
+        bytes,
+        modified: Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(modified)),
+    }
+}
+
+#[test]
+fn hashes_survive_resizing_but_not_other_photos() {
+    let original = scene(false);
+    let smaller = original.resize(120, 80, FilterType::Triangle);
+    let other = scene(true);
+    let hash = duplicates::dhash(&original);
+
+    assert!(duplicates::distance(hash, duplicates::dhash(&smaller)) <= DEFAULT_THRESHOLD);
+    assert!(duplicates::distance(hash, duplicates::dhash(&other)) > DEFAULT_THRESHOLD);
+    assert_eq!(duplicates::distance(0b1011, 0b0110), 3);
+}
+
+#[test]
+fn groups_chain_through_close_hashes() {
+    // 0 and 2 are too far apart, but both close to 1.
+    let hashes = [0, 0b1111, 0b1111_1111, u64::MAX, 0xffff << 40];
+    assert_eq!(duplicates::group(&hashes, 4), [vec![0, 1, 2]]);
+    assert_eq!(duplicates::group(&hashes, 1), Vec::<Vec<usize>>::new());
+    assert_eq!(duplicates::group(&hashes, 64), [vec![0, 1, 2, 3, 4]]);
+}
+
+#[test]
+fn keep_picks_by_policy() {
+    let group = DuplicateGroup {
+        files: vec![
+            scanned("a.jpg", (4000, 3000), 5_000_000, 10),
+            scanned("b.jpg", (4000, 3000), 6_000_000, 5),
+            scanned("c.jpg", (2000, 1500), 9_000_000, 30),
+        ],
+    };
+    assert_eq!(group.keep(KeepPolicy::Largest), 1);
+    assert_eq!(group.keep(KeepPolicy::Newest), 2);
+
+    let decisions = group.decisions(1);
+    assert_eq!(decisions.len(), 2);
+    assert!(decisions
+        .iter()
+        .all(|duplicate| duplicate.kept == Path::new("b.jpg")));
+}
+
+#[test]
+fn folders_are_searched_with_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    let save = |name: &str, img: &DynamicImage| {
+        let path = dir.path().join(name);
+        img.save(&path).unwrap();
+        path
+    };
+    let paths = vec![
+        save("burst-1.png", &scene(false)),
+        save("other.png", &scene(true)),
+        save(
+            "burst-2.jpg",
+            &scene(false).resize(180, 120, FilterType::Triangle),
+        ),
+        dir.path().join("broken.png"),
+    ];
+    fs::write(&paths[3], "not a png").unwrap();
+
+    let reported = std::sync::Mutex::new(vec![]);
+    let groups = duplicates::find_duplicates(
+        &paths,
+        DEFAULT_THRESHOLD,
+        &AtomicBool::new(false),
+        |done, total| reported.lock().unwrap().push((done, total)),
+    )
+    .unwrap();
+
+    assert_eq!(groups.len(), 1);
+    let names: Vec<_> = groups[0].files.iter().map(|file| &file.path).collect();
+    assert_eq!(names, [&paths[0], &paths[2]]);
+    assert_eq!(groups[0].keep(KeepPolicy::Largest), 0);
+    let mut reported = reported.into_inner().unwrap();
+    reported.sort();
+    assert_eq!(reported, [(1, 4), (2, 4), (3, 4), (4, 4)]);
+
+    assert!(duplicates::find_duplicates(
+        &paths,
+        DEFAULT_THRESHOLD,
+        &AtomicBool::new(true),
+        |_, _| {}
+    )
+    .is_none());
+}
@@ -0,0 +1,174 @@
+use image::{DynamicImage, GenericImageView, ImageBuffer, ImageFormat, Rgb, Rgba, RgbaImage};
+use image_finalizer::{
+    format::{FormatSettings, OutputFormat},
+    naming::BatchNaming,
+    validation, ProcessInfo,
+};
+
+const FORMATS: [OutputFormat; 5] = [
+    OutputFormat::Png,
+    OutputFormat::Jpeg,
+    OutputFormat::Tiff,
+    OutputFormat::Avif,
+    OutputFormat::Webp,
+];
+
+/// Half transparent on the left, opaque on the right.
+fn photo() -> DynamicImage {
+    RgbaImage::from_fn(16, 8, |x, _| {
+        Rgba([200, 100, 50, if x < 8 { 128 } else { 255 }])
+    })
+    .into()
+}
+
+fn encode(format: OutputFormat, img: &DynamicImage) -> Vec<u8> {
+    let mut bytes = vec![];
+    format
+        .encoder()
+        .encode(img, &mut bytes, &FormatSettings::default())
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn every_encoder_writes_its_format() {
+    for format in FORMATS {
+        let encoder = format.encoder();
+        assert_eq!(encoder.extension(), format.extension());
+        let bytes = encode(format, &photo());
+
+        // This build can't decode AVIF, so only check the container.
+        if format == OutputFormat::Avif {
+            assert_eq!(&bytes[4..12], b"ftypavif");
+            continue;
+        }
+
+        let expected = ImageFormat::from_extension(encoder.extension()).unwrap();
+        assert_eq!(
+            image::guess_format(&bytes).unwrap(),
+            expected,
+            "{:?}",
+            format
+        );
+        let decoded = image::load_from_memory(&bytes).unwrap();
+        assert_eq!(decoded.dimensions(), (16, 8), "{:?}", format);
+        assert_eq!(
+            decoded.color().has_alpha(),
+            encoder.supports_alpha(),
+            "{:?}",
+            format
+        );
+    }
+}
+
+#[test]
+fn capabilities_match_what_is_written() {
+    let deep: DynamicImage = ImageBuffer::from_pixel(4, 4, Rgb([1000u16, 2000, 3000])).into();
+    for format in FORMATS
+        .into_iter()
+        .filter(|&format| format != OutputFormat::Avif)
+    {
+        let encoder = format.encoder();
+        assert!(encoder.supports_bit_depth(8));
+        let decoded = image::load_from_memory(&encode(format, &deep)).unwrap();
+        let kept = decoded.color().bytes_per_pixel() / decoded.color().channel_count() == 2;
+        assert_eq!(encoder.supports_bit_depth(16), kept, "{:?}", format);
+    }
+}
+
+#[test]
+fn validation_warns_about_lost_precision_and_transparency() {
+    let dir = tempfile::tempdir().unwrap();
+    let deep = dir.path().join("deep.png");
+    ImageBuffer::from_pixel(4, 4, Rgb([1000u16, 2000, 3000]))
+        .save(&deep)
+        .unwrap();
+    let plain = dir.path().join("plain.png");
+    photo().save(&plain).unwrap();
+    let paths = [deep.clone(), plain];
+
+    let output = tempfile::tempdir().unwrap();
+    let check = |info: ProcessInfo| {
+        validation::validate(
+            dir.path(),
+            output.path(),
+            &paths,
+            &[],
+            info,
+            &BatchNaming::default(),
+        )
+        .warnings
+    };
+
+    assert_eq!(check(ProcessInfo::default()), Vec::<String>::new());
+
+    let jpeg = ProcessInfo {
+        output_format: OutputFormat::Jpeg,
+        ..ProcessInfo::default()
+    };
+    assert_eq!(
+        check(jpeg.clone()),
+        vec![format!(
+            "{}: 16 bits per channel, but JPEG stores 8",
+            deep.display()
+        )]
+    );
+
+    let warnings = check(ProcessInfo {
+        border_color: Rgba([255, 255, 255, 0]),
+        ..jpeg
+    });
+    assert!(
+        warnings.contains(
+            &"The border color is partly transparent, but JPEG can't store transparency"
+                .to_string()
+        ),
+        "{:?}",
+        warnings
+    );
+    assert!(check(ProcessInfo {
+        border_color: Rgba([255, 255, 255, 0]),
+        ..ProcessInfo::default()
+    })
+    .is_empty());
+}
+
+#[test]
+fn validation_counts_outputs_by_the_depth_they_are_stored_in() {
+    let dir = tempfile::tempdir().unwrap();
+    let deep = dir.path().join("deep.png");
+    ImageBuffer::from_pixel(4, 4, Rgb([1000u16, 2000, 3000]))
+        .save(&deep)
+        .unwrap();
+    let plain = dir.path().join("plain.png");
+    photo().save(&plain).unwrap();
+    let paths = [deep, plain];
+
+    let output = tempfile::tempdir().unwrap();
+    let outputs = |info: ProcessInfo| {
+        validation::validate(
+            dir.path(),
+            output.path(),
+            &paths,
+            &[],
+            info,
+            &BatchNaming::default(),
+        )
+        .outputs
+    };
+
+    let png = outputs(ProcessInfo::default());
+    assert_eq!(
+        png.keys().copied().collect::<Vec<_>>(),
+        vec![("PNG", 8), ("PNG", 16)]
+    );
+
+    let jpeg = outputs(ProcessInfo {
+        output_format: OutputFormat::Jpeg,
+        ..ProcessInfo::default()
+    });
+    let group = jpeg[&("JPEG", 8)];
+    assert_eq!(jpeg.len(), 1);
+    assert_eq!(group.count, 2);
+    assert!(group.smallest.0 < group.largest.0);
+}
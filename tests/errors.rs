@@ -0,0 +1,108 @@
+use std::{fs, path::Path};
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::{process_file, scan_images, ProcessError, ProcessInfo};
+
+fn write_image(path: &Path) {
+    RgbaImage::from_pixel(8, 6, Rgba([10, 20, 30, 255]))
+        .save(path)
+        .unwrap();
+}
+
+#[test]
+fn missing_input_is_an_io_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = process_file(
+        &dir.path().join("gone.png"),
+        ProcessInfo::default(),
+        dir.path(),
+    );
+
+    assert!(
+        matches!(result, Err(ProcessError::Io { .. })),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn corrupt_input_is_a_decode_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("corrupt.png");
+    fs::write(&input, b"\x89PNG\r\n\x1a\nnot really a png").unwrap();
+
+    let result = process_file(&input, ProcessInfo::default(), &dir.path().join("out"));
+
+    assert!(
+        matches!(result, Err(ProcessError::Decode { .. })),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn wrong_extension_is_a_decode_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("text.jpg");
+    fs::write(&input, "just some text").unwrap();
+
+    let result = process_file(&input, ProcessInfo::default(), &dir.path().join("out"));
+
+    assert!(
+        matches!(result, Err(ProcessError::Decode { .. })),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn path_without_file_name_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    for path in [Path::new(".."), Path::new("/")] {
+        let result = process_file(path, ProcessInfo::default(), dir.path());
+        assert!(
+            matches!(result, Err(ProcessError::InvalidPath { .. })),
+            "{}: {:?}",
+            path.display(),
+            result
+        );
+    }
+}
+
+#[test]
+fn output_dir_that_is_a_file_is_an_io_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("photo.png");
+    write_image(&input);
+    let blocker = dir.path().join("out");
+    fs::write(&blocker, "").unwrap();
+
+    let result = process_file(&input, ProcessInfo::default(), &blocker);
+
+    match result {
+        Err(ProcessError::Io { path, .. }) => assert_eq!(path, blocker),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn scanning_a_missing_folder_is_an_io_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("nope");
+
+    match scan_images(&missing) {
+        Err(ProcessError::Io { path, .. }) => assert_eq!(path, missing),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn errors_name_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("corrupt.png");
+    fs::write(&input, "garbage").unwrap();
+
+    let error = process_file(&input, ProcessInfo::default(), dir.path()).unwrap_err();
+
+    assert!(error.to_string().contains("corrupt.png"), "{}", error);
+}
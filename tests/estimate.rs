@@ -0,0 +1,125 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+    time::Duration,
+};
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::{
+    estimate::{encoded_size, estimate, extrapolate, extrapolate_duration, pick_samples},
+    format::OutputFormat,
+    ProcessInfo,
+};
+
+/// Noisy images of `sides`, so they don't all compress alike.
+fn inputs(dir: &Path, sides: &[u32]) -> Vec<PathBuf> {
+    sides
+        .iter()
+        .enumerate()
+        .map(|(i, &side)| {
+            let path = dir.join(format!("{}.png", i));
+            RgbaImage::from_fn(side, side, |x, y| {
+                Rgba([(x * 37 + y * 11) as u8, (x ^ y) as u8, (x * y) as u8, 255])
+            })
+            .save(&path)
+            .unwrap();
+            path
+        })
+        .collect()
+}
+
+#[test]
+fn samples_are_the_smallest_median_and_largest() {
+    assert_eq!(pick_samples(&[50, 10, 40, 30, 20]), vec![1, 3, 0]);
+    assert_eq!(pick_samples(&[7, 7]), vec![0, 1]);
+    assert_eq!(pick_samples(&[7]), vec![0]);
+    assert!(pick_samples(&[]).is_empty());
+}
+
+#[test]
+fn extrapolation_spans_the_sample_ratios() {
+    let sizes = [100, 200, 300, 400];
+    let estimate = extrapolate(&sizes, &[(100, 50), (400, 400)]).unwrap();
+    assert_eq!(estimate.low, 500);
+    assert_eq!(estimate.high, 1000);
+    assert_eq!(estimate.total, 750);
+    assert_eq!(estimate.sampled, 2);
+    assert!(!estimate.exact);
+    assert_eq!(extrapolate(&sizes, &[]), None);
+}
+
+#[test]
+fn durations_scale_with_input_bytes_and_workers() {
+    let sizes = [100, 200, 300, 400];
+    let samples = [(100, Duration::from_secs(1)), (400, Duration::from_secs(3))];
+    assert_eq!(
+        extrapolate_duration(&sizes, &samples, 1),
+        Some(Duration::from_secs(8))
+    );
+    assert_eq!(
+        extrapolate_duration(&sizes, &samples, 4),
+        Some(Duration::from_secs(2))
+    );
+    assert_eq!(extrapolate_duration(&sizes, &[], 4), None);
+}
+
+#[test]
+fn sampling_every_input_is_exact() {
+    let estimate = extrapolate(&[100, 200], &[(100, 30), (200, 90)]).unwrap();
+    assert!(estimate.exact);
+    assert_eq!(
+        (estimate.low, estimate.total, estimate.high),
+        (120, 120, 120)
+    );
+}
+
+#[test]
+fn small_batches_are_estimated_exactly() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), &[8, 16, 24]);
+    let info = ProcessInfo {
+        output_format: OutputFormat::Jpeg,
+        ..ProcessInfo::default()
+    };
+
+    let estimate = estimate(&paths, &[], info.clone(), &AtomicBool::new(false))
+        .unwrap()
+        .unwrap();
+    let encoded: u64 = paths
+        .iter()
+        .map(|path| encoded_size(path, &info).unwrap())
+        .sum();
+    assert!(estimate.exact);
+    assert_eq!(estimate.total, encoded);
+}
+
+#[test]
+fn larger_batches_are_a_range_around_the_total() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), &[8, 12, 16, 20, 24, 28]);
+    let info = ProcessInfo::default();
+
+    let estimate = estimate(&paths, &[], info, &AtomicBool::new(false))
+        .unwrap()
+        .unwrap();
+    assert_eq!(estimate.sampled, 3);
+    assert!(!estimate.exact);
+    assert!(estimate.low <= estimate.total && estimate.total <= estimate.high);
+}
+
+#[test]
+fn cancelled_estimates_have_no_result() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), &[8, 16]);
+
+    let estimate = estimate(&paths, &[], ProcessInfo::default(), &AtomicBool::new(true));
+    assert_eq!(estimate.unwrap(), None);
+}
+
+#[test]
+fn missing_inputs_are_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = vec![dir.path().join("gone.png")];
+
+    assert!(estimate(&paths, &[], ProcessInfo::default(), &AtomicBool::new(false)).is_err());
+}
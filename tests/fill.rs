@@ -0,0 +1,351 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use image::{DynamicImage, GenericImageView, ImageBuffer, Rgba, RgbaImage};
+use image_finalizer::{
+    fill::{BlurFill, BorderFill, Gradient, GradientShape, TextureFill, TextureMode},
+    naming::BatchNaming,
+    pipeline::{FileContext, Pipeline},
+    validation, ProcessInfo,
+};
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+fn gradient(shape: GradientShape, angle: f32) -> Gradient {
+    Gradient {
+        shape,
+        color: BLACK,
+        angle,
+    }
+}
+
+fn gray(pixel: &Rgba<u8>) -> u8 {
+    pixel.0[0]
+}
+
+#[test]
+fn linear_gradients_run_along_the_angle() {
+    let across = gradient(GradientShape::Linear, 0.0).render(100, 40, WHITE);
+    assert!(gray(across.get_pixel(0, 20)) > 250);
+    assert!(gray(across.get_pixel(99, 20)) < 5);
+    assert_eq!(across.get_pixel(30, 0), across.get_pixel(30, 39));
+
+    let down = gradient(GradientShape::Linear, 90.0).render(40, 100, WHITE);
+    assert!(gray(down.get_pixel(20, 0)) > 250);
+    assert!(gray(down.get_pixel(20, 99)) < 5);
+    assert_eq!(down.get_pixel(0, 30), down.get_pixel(39, 30));
+
+    let back = gradient(GradientShape::Linear, 180.0).render(100, 40, WHITE);
+    assert!(gray(back.get_pixel(0, 20)) < 5);
+    assert!(gray(back.get_pixel(99, 20)) > 250);
+}
+
+#[test]
+fn diagonal_gradients_reach_both_corners() {
+    let canvas = gradient(GradientShape::Linear, 45.0).render(80, 60, WHITE);
+    assert!(gray(canvas.get_pixel(0, 0)) > 250);
+    assert!(gray(canvas.get_pixel(79, 59)) < 5);
+    let middle = gray(canvas.get_pixel(40, 30));
+    assert!((120..=135).contains(&middle), "{}", middle);
+}
+
+#[test]
+fn radial_gradients_go_out_to_the_corners() {
+    let canvas = gradient(GradientShape::Radial, 0.0).render(101, 101, WHITE);
+    assert!(gray(canvas.get_pixel(50, 50)) > 250);
+    for (x, y) in [(0, 0), (100, 0), (0, 100), (100, 100)] {
+        assert!(gray(canvas.get_pixel(x, y)) < 5);
+    }
+    assert_eq!(canvas.get_pixel(50, 10), canvas.get_pixel(10, 50));
+}
+
+#[test]
+fn gradients_blend_alpha_too() {
+    let fade = Gradient {
+        color: Rgba([255, 255, 255, 0]),
+        ..gradient(GradientShape::Linear, 0.0)
+    };
+    let canvas = fade.render(100, 1, WHITE);
+    assert_eq!(canvas.get_pixel(0, 0).0[3], 254);
+    assert_eq!(canvas.get_pixel(99, 0).0[3], 1);
+}
+
+#[test]
+fn solid_fill_is_the_border_color() {
+    let canvas = BorderFill::Solid.canvas(&photo(), 10, 10, RED).unwrap();
+    assert!(canvas.pixels().all(|pixel| *pixel == RED));
+    assert!(BorderFill::Solid.describe().is_none());
+}
+
+fn photo() -> DynamicImage {
+    RgbaImage::from_pixel(60, 40, RED).into()
+}
+
+fn run(info: &ProcessInfo, img: DynamicImage) -> DynamicImage {
+    let ctx = FileContext {
+        path: Path::new("photo.png"),
+    };
+    Pipeline::for_info(info).run(img, &ctx).unwrap()
+}
+
+fn with_gradient() -> ProcessInfo {
+    ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 20.0,
+        border_color: WHITE,
+        border_fill: BorderFill::Gradient(gradient(GradientShape::Linear, 90.0)),
+        ..ProcessInfo::default()
+    }
+}
+
+#[test]
+fn gradient_fills_only_the_border() {
+    let out = run(&with_gradient(), photo()).to_rgba8();
+    assert_eq!(out.dimensions(), (72, 52));
+    assert!(gray(out.get_pixel(36, 0)) > 240);
+    assert!(gray(out.get_pixel(36, 51)) < 15);
+    assert_ne!(out.get_pixel(0, 3), out.get_pixel(0, 48));
+    for (x, y) in [(6, 6), (65, 6), (6, 45), (65, 45)] {
+        assert_eq!(*out.get_pixel(x, y), RED);
+    }
+}
+
+#[test]
+fn deep_images_keep_the_gradient() {
+    let deep: DynamicImage = ImageBuffer::from_pixel(60, 40, Rgba([65535u16, 0, 0, 65535])).into();
+    let out = run(&with_gradient(), deep);
+    assert_eq!(out.color(), image::ColorType::Rgba16);
+    let eight = run(&with_gradient(), photo()).to_rgba8();
+    assert_eq!(out.to_rgba8(), eight);
+    assert_eq!(out.dimensions(), eight.dimensions());
+}
+
+/// Left half red, right half blue.
+fn halves() -> DynamicImage {
+    RgbaImage::from_fn(60, 40, |x, _| if x < 30 { RED } else { BLUE }).into()
+}
+
+fn with_blur(tint: f32) -> ProcessInfo {
+    ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 20.0,
+        border_color: WHITE,
+        border_fill: BorderFill::Blur(BlurFill {
+            strength: 5.0,
+            tint,
+        }),
+        ..ProcessInfo::default()
+    }
+}
+
+#[test]
+fn blur_fill_extends_the_photo() {
+    let out = run(&with_blur(0.0), halves()).to_rgba8();
+    assert_eq!(out.dimensions(), (72, 52));
+    // The border takes after the half of the photo next to it.
+    let left = out.get_pixel(1, 26);
+    let right = out.get_pixel(70, 26);
+    assert!(left.0[0] > 200 && left.0[2] < 55, "{:?}", left);
+    assert!(right.0[2] > 200 && right.0[0] < 55, "{:?}", right);
+    // Blurred, so the halves meet in a blend.
+    let seam = out.get_pixel(36, 1);
+    assert!(seam.0[0] > 60 && seam.0[2] > 60, "{:?}", seam);
+    // The photo itself stays sharp.
+    assert_eq!(*out.get_pixel(35, 26), RED);
+    assert_eq!(*out.get_pixel(36, 26), BLUE);
+}
+
+#[test]
+fn blur_fill_is_tinted_towards_the_border_color() {
+    let plain = run(&with_blur(0.0), photo()).to_rgba8();
+    let tinted = run(&with_blur(50.0), photo()).to_rgba8();
+    assert_eq!(*plain.get_pixel(0, 0), RED);
+    assert_eq!(*tinted.get_pixel(0, 0), Rgba([255, 128, 128, 255]));
+    assert_eq!(*tinted.get_pixel(36, 26), RED);
+}
+
+#[test]
+fn blur_fill_covers_large_canvases() {
+    let fill = BlurFill::default();
+    let canvas = fill.render(&halves(), 1200, 300, WHITE);
+    assert_eq!(canvas.dimensions(), (1200, 300));
+    assert!(canvas.pixels().all(|pixel| pixel.0[3] == 255));
+}
+
+#[test]
+fn gradients_round_trip_through_toml() {
+    let info = ProcessInfo {
+        border_fill: BorderFill::Gradient(Gradient {
+            shape: GradientShape::Radial,
+            color: Rgba([16, 32, 48, 255]),
+            angle: 30.0,
+        }),
+        ..ProcessInfo::default()
+    };
+    let text = toml::to_string_pretty(&info).unwrap();
+    assert!(text.contains("[border_fill]"), "{}", text);
+    assert!(text.contains("kind = \"gradient\""), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    let blur = with_blur(10.0);
+    let text = toml::to_string_pretty(&blur).unwrap();
+    assert!(text.contains("kind = \"blur\""), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), blur);
+    assert!(!toml::to_string(&ProcessInfo::default())
+        .unwrap()
+        .contains("border_fill"));
+}
+
+#[test]
+fn gradients_are_summarized() {
+    let summary = with_gradient().summary();
+    let (_, fill) = summary
+        .iter()
+        .find(|(_, value)| value.contains("#000000"))
+        .expect("a fill entry");
+    assert!(fill.contains("90"), "{}", fill);
+}
+
+/// A 4×4 texture, blue in the top left quarter and black elsewhere, saved
+/// in `dir`.
+fn texture(dir: &Path) -> PathBuf {
+    let path = dir.join("paper.png");
+    RgbaImage::from_fn(4, 4, |x, y| if x < 2 && y < 2 { BLUE } else { BLACK })
+        .save(&path)
+        .unwrap();
+    path
+}
+
+fn with_texture(path: PathBuf, mode: TextureMode) -> ProcessInfo {
+    ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 20.0,
+        border_color: WHITE,
+        border_fill: BorderFill::Texture(TextureFill {
+            path,
+            mode,
+            ..TextureFill::default()
+        }),
+        ..ProcessInfo::default()
+    }
+}
+
+#[test]
+fn textures_are_tiled_across_the_border() {
+    let dir = tempfile::tempdir().unwrap();
+    let info = with_texture(texture(dir.path()), TextureMode::Tile);
+    let out = run(&info, photo()).to_rgba8();
+    assert_eq!(out.dimensions(), (72, 52));
+    for (x, y) in [(0, 0), (4, 0), (68, 48), (1, 49)] {
+        assert_eq!(*out.get_pixel(x, y), BLUE, "({}, {})", x, y);
+    }
+    for (x, y) in [(2, 0), (0, 2), (71, 51), (70, 3)] {
+        assert_eq!(*out.get_pixel(x, y), BLACK, "({}, {})", x, y);
+    }
+    assert_eq!(*out.get_pixel(36, 26), RED);
+}
+
+#[test]
+fn textures_can_be_stretched_over_the_canvas() {
+    let dir = tempfile::tempdir().unwrap();
+    let info = with_texture(texture(dir.path()), TextureMode::Stretch);
+    let out = run(&info, photo()).to_rgba8();
+    // The blue quarter covers the top left quarter of the canvas.
+    assert_eq!(*out.get_pixel(3, 3), BLUE);
+    assert_eq!(*out.get_pixel(68, 48), BLACK);
+    assert_eq!(*out.get_pixel(3, 48), BLACK);
+    assert_eq!(*out.get_pixel(36, 26), RED);
+}
+
+#[test]
+fn the_border_color_shows_through_textures() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("clear.png");
+    RgbaImage::from_pixel(3, 3, Rgba([0, 0, 0, 0]))
+        .save(&path)
+        .unwrap();
+    let canvas = TextureFill {
+        path,
+        mode: TextureMode::Tile,
+        ..TextureFill::default()
+    }
+    .render(10, 10, WHITE)
+    .unwrap();
+    assert!(canvas.pixels().all(|pixel| *pixel == WHITE));
+}
+
+#[test]
+fn changed_textures_are_read_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("changing.png");
+    let fill = TextureFill {
+        path: path.clone(),
+        mode: TextureMode::Stretch,
+        ..TextureFill::default()
+    };
+    // Set apart by whole seconds, as some file systems keep no less.
+    let modified = |seconds: u64| {
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    };
+    RgbaImage::from_pixel(2, 2, BLUE).save(&path).unwrap();
+    modified(1_000_000);
+    assert_eq!(*fill.render(4, 4, WHITE).unwrap().get_pixel(0, 0), BLUE);
+    RgbaImage::from_pixel(2, 2, BLACK).save(&path).unwrap();
+    modified(2_000_000);
+    assert_eq!(*fill.render(4, 4, WHITE).unwrap().get_pixel(0, 0), BLACK);
+}
+
+#[test]
+fn missing_textures_fail_the_photo() {
+    let dir = tempfile::tempdir().unwrap();
+    let info = with_texture(dir.path().join("missing.png"), TextureMode::Tile);
+    let ctx = FileContext {
+        path: Path::new("photo.png"),
+    };
+    let error = Pipeline::for_info(&info).run(photo(), &ctx).unwrap_err();
+    assert!(error.to_string().contains("missing.png"), "{}", error);
+
+    let input = dir.path().join("photo.png");
+    photo().save(&input).unwrap();
+    let checked = validation::validate(
+        dir.path(),
+        &dir.path().join("out"),
+        &[input],
+        &[],
+        info,
+        &BatchNaming::default(),
+    );
+    assert!(
+        checked
+            .errors
+            .iter()
+            .any(|error| error.contains("missing.png")),
+        "{:?}",
+        checked.errors
+    );
+}
+
+#[test]
+fn textures_round_trip_through_toml_and_are_summarized() {
+    let info = with_texture(PathBuf::from("scans/paper.jpg"), TextureMode::Stretch);
+    let text = toml::to_string_pretty(&info).unwrap();
+    assert!(text.contains("kind = \"texture\""), "{}", text);
+    assert!(text.contains("mode = \"stretch\""), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    let summary = info.summary();
+    assert!(
+        summary.iter().any(|(_, value)| value.contains("paper.jpg")),
+        "{:?}",
+        summary
+    );
+}
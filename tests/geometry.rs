@@ -0,0 +1,289 @@
+use image_finalizer::{
+    border_geometry, fit_aspect, output_dimensions, pixel_geometry, resize_target, sides_geometry,
+    BorderMode, BorderSides, CanvasAspect, Geometry, PixelBorder, ProcessInfo,
+};
+
+use BorderMode::{Square, Symmetrical};
+
+/// (width, height, mode, percentage) → (canvas width, canvas height, x, y)
+type Case = (u32, u32, BorderMode, f32, (u32, u32, u32, u32));
+
+const CASES: &[Case] = &[
+    // Landscape, portrait and square inputs.
+    (100, 50, Square, 10.0, (110, 110, 5, 30)),
+    (50, 100, Square, 10.0, (110, 110, 30, 5)),
+    (100, 100, Square, 10.0, (110, 110, 5, 5)),
+    (100, 50, Symmetrical, 10.0, (110, 60, 5, 5)),
+    (50, 100, Symmetrical, 10.0, (60, 110, 5, 5)),
+    (100, 100, Symmetrical, 10.0, (110, 110, 5, 5)),
+    // No border: square mode still pads to a square.
+    (100, 50, Square, 0.0, (100, 100, 0, 25)),
+    (100, 50, Symmetrical, 0.0, (100, 50, 0, 0)),
+    (64, 64, Square, 0.0, (64, 64, 0, 0)),
+    // Fractional percentages truncate the canvas to whole pixels.
+    (200, 100, Square, 7.5, (215, 215, 7, 57)),
+    (101, 50, Square, 2.5, (103, 103, 1, 26)),
+    (101, 50, Symmetrical, 2.5, (103, 52, 1, 1)),
+    (999, 10, Symmetrical, 0.1, (999, 10, 0, 0)),
+    // Odd padding puts the extra pixel after the photo.
+    (10, 5, Square, 30.0, (13, 13, 1, 4)),
+    // 1×1 images only grow once the border reaches a whole pixel.
+    (1, 1, Square, 10.0, (1, 1, 0, 0)),
+    (1, 1, Square, 100.0, (2, 2, 0, 0)),
+    (1, 1, Symmetrical, 200.0, (3, 3, 1, 1)),
+    // Extreme aspect ratios.
+    (10000, 1, Square, 10.0, (11000, 11000, 500, 5499)),
+    (10000, 1, Symmetrical, 10.0, (11000, 1001, 500, 500)),
+    (1, 10000, Symmetrical, 10.0, (1001, 11000, 500, 500)),
+    // Negative borders are treated as none instead of underflowing.
+    (100, 50, Symmetrical, -10.0, (100, 50, 0, 0)),
+    (100, 50, Square, -10.0, (100, 100, 0, 25)),
+];
+
+#[test]
+fn border_geometry_table() {
+    for &(width, height, mode, percentage, (canvas_width, canvas_height, x, y)) in CASES {
+        assert_eq!(
+            border_geometry(width, height, mode, percentage),
+            Geometry {
+                canvas_width,
+                canvas_height,
+                x_offset: x,
+                y_offset: y,
+            },
+            "{}x{} {:?} {}%",
+            width,
+            height,
+            mode,
+            percentage
+        );
+    }
+}
+
+#[test]
+fn photo_always_fits_on_canvas() {
+    for &(width, height, mode, percentage, _) in CASES {
+        let geometry = border_geometry(width, height, mode, percentage);
+        assert!(geometry.x_offset + width <= geometry.canvas_width);
+        assert!(geometry.y_offset + height <= geometry.canvas_height);
+    }
+}
+
+#[test]
+fn resize_target_keeps_the_longest_side() {
+    assert_eq!(resize_target(110, 60, 800), (800, 436));
+    assert_eq!(resize_target(60, 110, 800), (436, 800));
+    assert_eq!(resize_target(110, 110, 800), (800, 800));
+    // The short side never collapses to zero.
+    assert_eq!(resize_target(11000, 1001, 10), (10, 1));
+    assert_eq!(resize_target(100000, 1, 10), (10, 1));
+}
+
+#[test]
+fn output_dimensions_apply_resize_after_border() {
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 10.0,
+        resize_images: true,
+        resize_longest_dimension: 220,
+        ..ProcessInfo::default()
+    };
+    assert_eq!(output_dimensions(100, 50, &info), (220, 120));
+
+    let info = ProcessInfo {
+        resize_images: false,
+        ..info
+    };
+    assert_eq!(output_dimensions(100, 50, &info), (110, 60));
+}
+
+#[test]
+fn every_side_gets_its_own_width() {
+    // A gallery mat, heavier at the bottom.
+    let mat = BorderSides {
+        top: 5.0,
+        right: 5.0,
+        bottom: 15.0,
+        left: 5.0,
+    };
+    assert_eq!(
+        sides_geometry(200, 100, mat),
+        Geometry {
+            canvas_width: 220,
+            canvas_height: 140,
+            x_offset: 10,
+            y_offset: 10,
+        }
+    );
+    // Sides truncate on their own and negative ones count as none.
+    let odd = BorderSides {
+        top: 0.9,
+        right: -4.0,
+        bottom: 2.5,
+        left: 1.0,
+    };
+    assert_eq!(
+        sides_geometry(100, 50, odd),
+        Geometry {
+            canvas_width: 101,
+            canvas_height: 52,
+            x_offset: 1,
+            y_offset: 0,
+        }
+    );
+
+    // Splitting a symmetrical border between the sides changes nothing.
+    assert_eq!(
+        sides_geometry(100, 50, BorderSides::symmetrical(10.0)),
+        border_geometry(100, 50, BorderMode::Symmetrical, 10.0)
+    );
+}
+
+#[test]
+fn sides_replace_the_border_mode() {
+    let info = ProcessInfo {
+        symmetrical_border: false,
+        border_percentage: 30.0,
+        border_sides: Some(BorderSides {
+            bottom: 20.0,
+            ..BorderSides::default()
+        }),
+        ..ProcessInfo::default()
+    };
+    assert_eq!(output_dimensions(100, 50, &info), (100, 70));
+}
+
+#[test]
+fn pixel_borders_are_the_same_on_every_photo() {
+    for (width, height) in [(100, 50), (3000, 2000), (7, 9)] {
+        let geometry = pixel_geometry(width, height, Symmetrical, 5);
+        assert_eq!(
+            (geometry.canvas_width, geometry.canvas_height),
+            (width + 10, height + 10)
+        );
+        assert_eq!((geometry.x_offset, geometry.y_offset), (5, 5));
+    }
+    assert_eq!(
+        pixel_geometry(100, 50, Square, 5),
+        Geometry {
+            canvas_width: 110,
+            canvas_height: 110,
+            x_offset: 5,
+            y_offset: 30,
+        }
+    );
+    // The same as a percentage that comes to as many pixels.
+    assert_eq!(
+        pixel_geometry(200, 100, Square, 10),
+        border_geometry(200, 100, Square, 10.0)
+    );
+}
+
+#[test]
+fn pixel_borders_can_be_measured_after_the_resize() {
+    let border = PixelBorder {
+        width: 10,
+        after_resize: true,
+    };
+    let source_width =
+        |border: PixelBorder, resize| border.source_width(1000, 500, Square, None, resize);
+    // 1000 + 2 × 50 pixels scaled to 220 leave 10 on each side.
+    assert_eq!(source_width(border, Some(220)), 50);
+    assert_eq!(source_width(border, None), 10);
+    // A resize smaller than the border itself leaves it as it is.
+    assert_eq!(source_width(border, Some(20)), 10);
+    let before = PixelBorder {
+        after_resize: false,
+        ..border
+    };
+    assert_eq!(source_width(before, Some(220)), 10);
+    // Padded to 2:1, the canvas is twice as wide as the 500 + 2 × 56
+    // pixels of its height, and those 1224 are scaled to 220.
+    let padded = border.source_width(
+        500,
+        500,
+        Symmetrical,
+        Some(CanvasAspect::new(2, 1)),
+        Some(220),
+    );
+    assert_eq!(padded, 56);
+
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_pixels: Some(border),
+        resize_images: true,
+        resize_longest_dimension: 220,
+        ..ProcessInfo::default()
+    };
+    // A 200×100 photo with 10 pixels around it.
+    assert_eq!(output_dimensions(1000, 500, &info), (220, 120));
+    assert_eq!(output_dimensions(4000, 2000, &info), (220, 120));
+}
+
+#[test]
+fn canvases_are_padded_to_the_aspect_ratio() {
+    let bordered = border_geometry(100, 50, Symmetrical, 10.0);
+    // 110×60 padded above and below to 4:5, and to a width of 28 × 4.
+    assert_eq!(
+        fit_aspect(bordered, CanvasAspect::new(4, 5)),
+        Geometry {
+            canvas_width: 112,
+            canvas_height: 140,
+            x_offset: 6,
+            y_offset: 45,
+        }
+    );
+    // A portrait photo is too narrow for 16:9 and padded on the sides.
+    let portrait = border_geometry(54, 99, Symmetrical, 0.0);
+    assert_eq!(
+        fit_aspect(portrait, CanvasAspect::new(16, 9)),
+        Geometry {
+            canvas_width: 176,
+            canvas_height: 99,
+            x_offset: 61,
+            y_offset: 0,
+        }
+    );
+    // Ratios are reduced first, so 8:10 is as fine as 4:5.
+    assert_eq!(
+        fit_aspect(bordered, CanvasAspect::new(8, 10)),
+        fit_aspect(bordered, CanvasAspect::new(4, 5))
+    );
+    // The right shape already is left alone.
+    let square = border_geometry(64, 64, Symmetrical, 0.0);
+    assert_eq!(fit_aspect(square, CanvasAspect::new(1, 1)), square);
+
+    let info = ProcessInfo {
+        canvas_aspect: Some(CanvasAspect::new(4, 5)),
+        // Replaced by the aspect, which starts from the symmetrical border.
+        symmetrical_border: false,
+        border_percentage: 10.0,
+        resize_images: true,
+        resize_longest_dimension: 1350,
+        ..ProcessInfo::default()
+    };
+    assert_eq!(output_dimensions(100, 50, &info), (1080, 1350));
+    assert_eq!(output_dimensions(333, 777, &info), (1080, 1350));
+    assert_eq!(output_dimensions(4000, 5000, &info), (1080, 1350));
+    assert_eq!(info.canvas_ratio(), Some(0.8));
+}
+
+#[test]
+fn aspect_ratios_are_written_like_on_paper() {
+    let aspect: CanvasAspect = "16:9".parse().unwrap();
+    assert_eq!(aspect, CanvasAspect::new(16, 9));
+    assert_eq!(aspect.to_string(), "16:9");
+    assert!(" 4 : 5 ".parse::<CanvasAspect>().is_ok());
+    for wrong in ["4x5", "0:1", "4:", "a:b"] {
+        assert!(wrong.parse::<CanvasAspect>().is_err(), "{}", wrong);
+    }
+
+    let info = ProcessInfo {
+        canvas_aspect: Some(CanvasAspect::new(4, 5)),
+        ..ProcessInfo::default()
+    };
+    let text = toml::to_string(&info).unwrap();
+    assert!(text.contains("canvas_aspect = \"4:5\""), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    assert!(toml::from_str::<ProcessInfo>("canvas_aspect = \"wide\"").is_err());
+}
@@ -0,0 +1,196 @@
+//! Runs the fixture images through `process_file` in every output format,
+//! then decodes the results and checks their size and pixels.
+//!
+//! The files written by deterministic encoders are also hashed and compared
+//! with `tests/golden/<format>.txt`. After an intended output change,
+//! regenerate those with `UPDATE_GOLDEN=1 cargo test --test golden`.
+
+use std::{env, fs, path::Path};
+
+use image::{imageops::FilterType, GenericImageView, Rgba};
+use image_finalizer::{
+    compute_geometry, format::OutputFormat, output_file_name, process_file, ProcessInfo,
+};
+
+const FIXTURES: &[&str] = &["landscape.png", "portrait.png"];
+
+const BORDER_COLOR: Rgba<u8> = Rgba([250, 20, 60, 255]);
+
+/// Named settings every fixture is processed with.
+fn settings(format: OutputFormat) -> Vec<(&'static str, ProcessInfo)> {
+    let wide = ProcessInfo {
+        symmetrical_border: false,
+        border_percentage: 100.0,
+        border_color: BORDER_COLOR,
+        resize_images: false,
+        output_format: format,
+        ..ProcessInfo::default()
+    };
+    let resized = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 25.0,
+        resize_images: true,
+        resize_longest_dimension: 40,
+        resize_filter: FilterType::Lanczos3,
+        ..wide.clone()
+    };
+    vec![("wide", wide), ("resized", resized)]
+}
+
+/// Expected output size per fixture and settings.
+fn expected_dimensions(fixture: &str, settings: &str) -> (u32, u32) {
+    match (fixture, settings) {
+        ("landscape.png", "wide") => (64, 64),
+        ("landscape.png", "resized") => (40, 32),
+        ("portrait.png", "wide") => (56, 56),
+        ("portrait.png", "resized") => (31, 40),
+        _ => unreachable!(),
+    }
+}
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// FNV-1a, stable across platforms and Rust versions.
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn assert_close(actual: Rgba<u8>, expected: Rgba<u8>, tolerance: u8, what: &str) {
+    for channel in 0..3 {
+        let difference = actual[channel].abs_diff(expected[channel]);
+        assert!(
+            difference <= tolerance,
+            "{}: {:?} is not within {} of {:?}",
+            what,
+            actual,
+            tolerance,
+            expected
+        );
+    }
+}
+
+/// Processes every fixture in `format`, checks the decoded output when
+/// `tolerance` is given and compares file hashes when `hashed`.
+fn check_format(format: OutputFormat, tolerance: Option<u8>, hashed: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let mut hashes = String::new();
+
+    for name in FIXTURES {
+        let input = fixture(name);
+        let source = image::open(&input).unwrap();
+
+        for (label, info) in settings(format) {
+            let output_dir = dir.path().join(label);
+            let outcome = process_file(&input, info.clone(), &output_dir).unwrap();
+            let output = outcome.output_paths[0].clone();
+            assert_eq!(
+                output,
+                output_dir.join(output_file_name(&input, format).unwrap().name)
+            );
+
+            let bytes = fs::read(&output).unwrap();
+            hashes.push_str(&format!("{} {} {:016x}\n", name, label, hash(&bytes)));
+
+            let Some(tolerance) = tolerance else {
+                continue;
+            };
+
+            let written = image::load_from_memory(&bytes).unwrap();
+            let what = format!("{} {} {:?}", name, label, format);
+            assert_eq!(
+                written.dimensions(),
+                expected_dimensions(name, label),
+                "{}",
+                what
+            );
+
+            // Only the unresized layout has borders wide enough for lossy
+            // encoders to keep the corners clean.
+            if label == "wide" {
+                let geometry = compute_geometry(source.width(), source.height(), &info);
+                let (cx, cy) = (geometry.canvas_width / 2, geometry.canvas_height / 2);
+
+                assert_close(written.get_pixel(0, 0), BORDER_COLOR, tolerance, &what);
+                assert_close(
+                    written.get_pixel(geometry.canvas_width - 1, geometry.canvas_height - 1),
+                    BORDER_COLOR,
+                    tolerance,
+                    &what,
+                );
+                assert_close(
+                    written.get_pixel(cx, cy),
+                    source.get_pixel(cx - geometry.x_offset, cy - geometry.y_offset),
+                    tolerance,
+                    &what,
+                );
+            }
+        }
+    }
+
+    if !hashed {
+        return;
+    }
+
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", format.extension()));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden.parent().unwrap()).unwrap();
+        fs::write(&golden, &hashes).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&golden).unwrap_or_else(|e| {
+        panic!(
+            "{}: {}; run with UPDATE_GOLDEN=1 to create it",
+            golden.display(),
+            e
+        )
+    });
+    assert_eq!(
+        hashes, expected,
+        "output of {:?} changed; if intended, run with UPDATE_GOLDEN=1",
+        format
+    );
+}
+
+#[test]
+fn png() {
+    check_format(OutputFormat::Png, Some(0), true);
+}
+
+#[test]
+fn jpeg() {
+    check_format(OutputFormat::Jpeg, Some(12), true);
+}
+
+#[test]
+fn tiff() {
+    check_format(OutputFormat::Tiff, Some(0), true);
+}
+
+#[test]
+fn webp() {
+    check_format(OutputFormat::Webp, Some(0), true);
+}
+
+/// This build can't decode AVIF, so only the container is checked.
+#[test]
+fn avif() {
+    check_format(OutputFormat::Avif, None, false);
+
+    let dir = tempfile::tempdir().unwrap();
+    for name in FIXTURES {
+        for (_, info) in settings(OutputFormat::Avif) {
+            let outcome = process_file(&fixture(name), info, dir.path()).unwrap();
+            let bytes = fs::read(outcome.output_path().unwrap()).unwrap();
+            assert_eq!(&bytes[4..12], b"ftypavif", "{}", name);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use image_finalizer::hook::{self, HookError, HookFile};
+
+fn file() -> HookFile<'static> {
+    HookFile {
+        output: Path::new("out/my photo.jpg"),
+        source: Path::new("in/my photo.png"),
+        format: "jpeg",
+        width: 2048,
+        height: 1365,
+    }
+}
+
+#[test]
+fn commands_split_at_whitespace_outside_of_quotes() {
+    assert_eq!(
+        hook::parse_command(r#"exiftool  "-Artist=Ana Lima" '-Copyright=©' {output}"#).unwrap(),
+        ["exiftool", "-Artist=Ana Lima", "-Copyright=©", "{output}"]
+    );
+    assert_eq!(
+        hook::parse_command(r"C:\Tools\copy.exe {output} D:\Hot").unwrap(),
+        [r"C:\Tools\copy.exe", "{output}", r"D:\Hot"]
+    );
+    assert_eq!(hook::parse_command(r#"echo """#).unwrap(), ["echo", ""]);
+    assert!(matches!(hook::parse_command("  "), Err(HookError::Empty)));
+    assert!(matches!(
+        hook::parse_command("echo 'open"),
+        Err(HookError::UnclosedQuote)
+    ));
+}
+
+#[test]
+fn placeholders_are_filled_in_per_word() {
+    let words =
+        hook::parse_command("tool {output} --from={source} {format}-{width}x{height}").unwrap();
+    assert_eq!(
+        hook::expand(&words, &file()),
+        [
+            "tool",
+            "out/my photo.jpg",
+            "--from=in/my photo.png",
+            "jpeg-2048x1365"
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_and_slow_hooks_are_reported() {
+    let settings = |command: &str, timeout_secs| hook::HookSettings {
+        command: command.to_string(),
+        timeout_secs,
+        ..hook::HookSettings::default()
+    };
+    assert!(hook::run(&settings("true", 10), &file()).is_ok());
+    assert!(matches!(
+        hook::run(&settings("sh -c 'echo broken >&2; exit 3'", 10), &file()),
+        Err(HookError::Exit { stderr, .. }) if stderr == "broken"
+    ));
+    assert!(matches!(
+        hook::run(&settings("sleep 5", 1), &file()),
+        Err(HookError::Timeout(1))
+    ));
+    assert!(matches!(
+        hook::run(&settings("no-such-program-here", 10), &file()),
+        Err(HookError::Spawn { .. })
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn every_split_tile_gets_the_hook() {
+    use std::fs;
+
+    use image::{Rgba, RgbaImage};
+    use image_finalizer::{
+        naming::{FileNaming, NameTemplate},
+        process_split,
+        split::{SplitFit, SplitSettings, TileAspect},
+        ChosenSettings, ProcessInfo,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pano.png");
+    RgbaImage::from_pixel(302, 100, Rgba([255, 0, 0, 255]))
+        .save(&path)
+        .unwrap();
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 0.0,
+        ..ProcessInfo::default()
+    };
+    let template: NameTemplate = "{name}.{ext}".parse().unwrap();
+    let naming = FileNaming {
+        template: &template,
+        index: 0,
+        preset: "manual",
+        folder: "".as_ref(),
+        overwrite: Default::default(),
+        originals: None,
+        preserve_timestamps: false,
+        variant_folders: Default::default(),
+        srcset: None,
+    };
+    let split = SplitSettings {
+        tiles: 3,
+        aspect: TileAspect::Square,
+        fit: SplitFit::Pad,
+    };
+    let outcome = process_split(
+        &path,
+        info.clone(),
+        &split,
+        &dir.path().join("out"),
+        &naming,
+    )
+    .unwrap();
+
+    let log = dir.path().join("hook.log");
+    let settings = hook::HookSettings {
+        command: format!(
+            "sh -c 'echo \"$0 $1\" >> {}' {{output}} {{format}}-{{width}}x{{height}}",
+            log.display()
+        ),
+        ..hook::HookSettings::default()
+    };
+    let chosen = ChosenSettings {
+        info,
+        applied: "manual".to_string(),
+        preset: None,
+    };
+    hook::run_for(&settings, &chosen, &outcome).unwrap();
+
+    let runs: Vec<String> = fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let (output, size) = line.split_once(' ').unwrap();
+            let name = Path::new(output).file_name().unwrap().to_string_lossy();
+            format!("{} {}", name, size)
+        })
+        .collect();
+    // 302 doesn't divide by three, so the first tile is a pixel wider.
+    assert_eq!(
+        runs,
+        [
+            "pano_1of3.png png-101x101",
+            "pano_2of3.png png-101x101",
+            "pano_3of3.png png-100x101",
+        ]
+    );
+}
@@ -0,0 +1,120 @@
+use std::{collections::BTreeSet, fs, path::Path};
+
+use image_finalizer::i18n::{self, Language};
+use time::macros::datetime;
+
+/// Keys of every `t!` in the sources, which the macro takes as literals.
+fn used_keys() -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    for entry in fs::read_dir(src).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|extension| extension != "rs") {
+            continue;
+        }
+        let source = fs::read_to_string(&path).unwrap();
+        for (start, _) in source.match_indices("t!(") {
+            // Not the end of `format!(` and the like.
+            let before = source[..start].chars().next_back();
+            if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                continue;
+            }
+            let Some(literal) = source[start + 3..].trim_start().strip_prefix('"') else {
+                continue;
+            };
+            let key = &literal[..literal.find('"').unwrap()];
+            keys.insert(key.to_string());
+        }
+    }
+    keys
+}
+
+/// Names of the `{ $name }` arguments of a text.
+fn arguments(text: &str) -> BTreeSet<&str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|placeable| placeable.split('}').next()?.trim().strip_prefix('$'))
+        .collect()
+}
+
+#[test]
+fn every_key_in_the_code_has_a_text_in_every_language() {
+    let keys = used_keys();
+    assert!(keys.len() > 100, "found only {} keys", keys.len());
+    for language in Language::ALL {
+        let missing: Vec<_> = keys
+            .iter()
+            .filter(|key| i18n::lookup(language, key).is_none())
+            .collect();
+        assert!(missing.is_empty(), "{:?} lacks {:?}", language, missing);
+    }
+}
+
+#[test]
+fn every_text_is_used() {
+    let used = used_keys();
+    let unused: Vec<_> = i18n::keys(Language::English)
+        .into_iter()
+        .filter(|key| !used.contains(*key))
+        .collect();
+    assert!(unused.is_empty(), "unused: {:?}", unused);
+}
+
+#[test]
+fn languages_have_the_same_keys_and_arguments() {
+    let english = i18n::keys(Language::English);
+    for language in Language::ALL {
+        assert_eq!(i18n::keys(language), english, "{:?}", language);
+        for key in &english {
+            let expected = arguments(i18n::lookup(Language::English, key).unwrap());
+            let actual = arguments(i18n::lookup(language, key).unwrap());
+            assert_eq!(actual, expected, "{:?} {}", language, key);
+        }
+    }
+}
+
+#[test]
+fn continued_lines_join_with_newlines() {
+    let help = i18n::lookup(Language::English, "template-help").unwrap();
+    assert!(help.starts_with("{name}: input name without extension\n{suffix}:"));
+    assert!(!help.contains("  "));
+}
+
+#[test]
+fn arguments_are_filled_in_and_other_braces_kept() {
+    assert_eq!(
+        i18n::tr_args("input-found", &[("count", &12)]),
+        "Found 12 images"
+    );
+    assert_eq!(
+        i18n::tr_args("suffix-hover", &[]),
+        "Added to the name by {suffix}; may be empty"
+    );
+    assert_eq!(i18n::tr("no-such-key"), "no-such-key");
+}
+
+#[test]
+fn locales_map_to_bundled_languages() {
+    assert_eq!(Language::from_locale("de-AT"), Some(Language::German));
+    assert_eq!(Language::from_locale("de_DE.UTF-8"), Some(Language::German));
+    assert_eq!(Language::from_locale("EN-us"), Some(Language::English));
+    assert_eq!(Language::from_locale("fr-FR"), None);
+    assert_eq!(Language::from_locale(""), None);
+}
+
+#[test]
+fn numbers_are_grouped_per_language() {
+    assert_eq!(Language::English.number(0), "0");
+    assert_eq!(Language::English.number(999), "999");
+    assert_eq!(Language::English.number(1_234_567), "1,234,567");
+    assert_eq!(Language::German.number(12_345), "12.345");
+    assert_eq!(Language::English.decimal(2.25, 1), "2.2");
+    assert_eq!(Language::German.decimal(99.95, 1), "100,0");
+}
+
+#[test]
+fn dates_are_written_per_language() {
+    let time = datetime!(2026-03-05 09:07:00);
+    assert_eq!(Language::English.date_time(time), "5 Mar 2026, 09:07");
+    assert_eq!(Language::German.date_time(time), "05.03.2026, 09:07");
+}
@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use image::{DynamicImage, Rgb, RgbImage, Rgba, RgbaImage};
+use image_finalizer::{
+    corners::{CornerRadius, RadiusUnit},
+    keyline::Keyline,
+    pipeline::{FileContext, Pipeline},
+    ProcessInfo,
+};
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+fn keyline(width: f32) -> Keyline {
+    Keyline {
+        width,
+        color: BLACK,
+    }
+}
+
+fn run(info: ProcessInfo) -> RgbaImage {
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 50.0,
+        border_color: WHITE,
+        ..info
+    };
+    let ctx = FileContext {
+        path: Path::new("photo.png"),
+    };
+    let photo: DynamicImage = RgbImage::from_pixel(60, 40, Rgb([255, 0, 0])).into();
+    Pipeline::for_info(&info)
+        .run(photo, &ctx)
+        .unwrap()
+        .to_rgba8()
+}
+
+#[test]
+fn the_line_runs_right_around_the_photo() {
+    let out = run(ProcessInfo {
+        keyline: Some(keyline(2.0)),
+        ..ProcessInfo::default()
+    });
+    // A symmetrical border of 30 pixels puts the photo at (15, 15).
+    assert_eq!(*out.get_pixel(15, 15), RED);
+    assert_eq!(*out.get_pixel(74, 54), RED);
+    for (x, y) in [(14, 30), (13, 30), (75, 30), (76, 30), (40, 13), (40, 56)] {
+        assert_eq!(*out.get_pixel(x, y), BLACK, "({}, {})", x, y);
+    }
+    for (x, y) in [(12, 30), (77, 30), (40, 12), (40, 57)] {
+        assert_eq!(*out.get_pixel(x, y), WHITE, "({}, {})", x, y);
+    }
+    // Square corners stay square.
+    assert_eq!(*out.get_pixel(13, 13), BLACK);
+    assert_eq!(*out.get_pixel(76, 56), BLACK);
+}
+
+#[test]
+fn fractional_widths_are_antialiased() {
+    let mut canvas = RgbaImage::from_pixel(20, 20, WHITE);
+    keyline(1.5).draw(&mut canvas, (5, 5), (10, 10), 0.0);
+    assert_eq!(*canvas.get_pixel(4, 10), BLACK);
+    let outer = canvas.get_pixel(3, 10);
+    assert!(outer.0[0] > 100 && outer.0[0] < 155, "{:?}", outer);
+    assert_eq!(*canvas.get_pixel(2, 10), WHITE);
+    // Nothing is drawn where the photo goes.
+    assert_eq!(*canvas.get_pixel(5, 10), WHITE);
+    assert_eq!(*canvas.get_pixel(10, 10), WHITE);
+}
+
+#[test]
+fn the_line_follows_rounded_corners() {
+    let out = run(ProcessInfo {
+        keyline: Some(keyline(2.0)),
+        corner_radius: Some(CornerRadius {
+            radius: 10.0,
+            unit: RadiusUnit::Pixels,
+        }),
+        ..ProcessInfo::default()
+    });
+    // The corners of the photo and around them are left to the border.
+    assert_eq!(*out.get_pixel(15, 15), WHITE);
+    assert_eq!(*out.get_pixel(13, 13), WHITE);
+    // The line hugs the rounding along the diagonal.
+    let diagonal: Vec<Rgba<u8>> = (13..25).map(|i| *out.get_pixel(i, i)).collect();
+    assert!(diagonal.contains(&BLACK), "{:?}", diagonal);
+    assert_eq!(*out.get_pixel(40, 14), BLACK);
+    assert_eq!(*out.get_pixel(40, 15), RED);
+}
+
+#[test]
+fn lines_stay_inside_the_canvas() {
+    let mut canvas = RgbaImage::from_pixel(10, 10, WHITE);
+    keyline(3.0).draw(&mut canvas, (0, 0), (10, 10), 0.0);
+    keyline(3.0).draw(&mut canvas, (-4, 6), (8, 8), 2.0);
+    assert_eq!(canvas.dimensions(), (10, 10));
+}
+
+#[test]
+fn keylines_round_trip_through_toml() {
+    let info = ProcessInfo {
+        keyline: Some(Keyline {
+            width: 1.5,
+            color: Rgba([16, 32, 48, 255]),
+        }),
+        ..ProcessInfo::default()
+    };
+    let text = toml::to_string_pretty(&info).unwrap();
+    assert!(text.contains("[keyline]"), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    assert!(info
+        .summary()
+        .iter()
+        .any(|(_, value)| value.contains("1.5 px #102030")));
+    assert!(!toml::to_string(&ProcessInfo::default())
+        .unwrap()
+        .contains("keyline"));
+}
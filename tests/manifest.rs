@@ -0,0 +1,263 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::{
+    duplicates::Duplicate,
+    manifest::{settings_hash, ManifestEntry, ManifestFormat, ManifestStatus},
+    naming::BatchNaming,
+    placeholder::PlaceholderKind,
+    worker::{Command, Event, Worker},
+    ProcessInfo,
+};
+
+/// Inputs with names that need quoting, plus one that can't be decoded.
+fn inputs(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = ["plain.png", "a, \"quoted\" name.png", "café 🌅.png"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let path = dir.join(name);
+            RgbaImage::from_pixel(4 + i as u32, 3, Rgba([9, 9, 9, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect();
+    let broken = dir.join("broken.png");
+    fs::write(&broken, "not a png").unwrap();
+    paths.push(broken);
+    paths
+}
+
+/// Runs a batch with a manifest of `format`, returning the manifest's path.
+fn run(
+    paths: Vec<PathBuf>,
+    output_dir: &Path,
+    format: ManifestFormat,
+    placeholder: Option<PlaceholderKind>,
+    duplicates: Vec<Duplicate>,
+) -> PathBuf {
+    let (tx, events) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let worker = Worker::spawn(move |event| {
+        let _ = tx.lock().unwrap().send(event);
+    });
+    worker.send(Command::StartBatch {
+        paths,
+        output_dir: output_dir.to_path_buf(),
+        rules: vec![],
+        manual: ProcessInfo::default(),
+        edits: HashMap::new(),
+        naming: BatchNaming::default(),
+        manifest: Some(format),
+        placeholder,
+        archive: None,
+        hook: None,
+        duplicates,
+        combine: None,
+        split: None,
+    });
+    while !matches!(
+        events.recv_timeout(Duration::from_secs(30)).unwrap(),
+        Event::BatchFinished { .. }
+    ) {}
+
+    let manifests: Vec<PathBuf> = fs::read_dir(output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("manifest-")
+        })
+        .collect();
+    assert_eq!(manifests.len(), 1, "{:?}", manifests);
+    manifests[0].clone()
+}
+
+/// Compares the entries with the inputs and the files on disk.
+fn cross_check(entries: &[ManifestEntry], paths: &[PathBuf]) {
+    let sources: BTreeSet<&str> = entries.iter().map(|entry| entry.source.as_str()).collect();
+    let expected: BTreeSet<&str> = paths.iter().map(|path| path.to_str().unwrap()).collect();
+    assert_eq!(sources, expected);
+
+    for entry in entries {
+        assert_eq!(entry.format, "png");
+        assert_eq!(entry.quality, None);
+        assert_eq!(entry.settings, "manual settings");
+        assert_eq!(entry.settings_hash, settings_hash(&ProcessInfo::default()));
+        if entry.source.ends_with("broken.png") {
+            assert_eq!(entry.status, ManifestStatus::Failed);
+            assert!(entry.message.is_some());
+            assert!(entry.outputs.is_empty());
+            continue;
+        }
+
+        assert_eq!(entry.status, ManifestStatus::Written, "{:?}", entry);
+        assert_eq!(entry.outputs.len(), 1);
+        let output = Path::new(&entry.outputs[0]);
+        assert_eq!(fs::metadata(output).unwrap().len(), entry.bytes);
+        assert_eq!(
+            image::image_dimensions(output).unwrap(),
+            (entry.width, entry.height)
+        );
+    }
+}
+
+#[test]
+fn csv_manifest_matches_the_written_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path());
+    let manifest = run(
+        paths.clone(),
+        &dir.path().join("out"),
+        ManifestFormat::Csv,
+        None,
+        vec![],
+    );
+    assert_eq!(manifest.extension().unwrap(), "csv");
+
+    let mut reader = csv::Reader::from_path(&manifest).unwrap();
+    let header = reader.headers().unwrap().clone();
+    assert_eq!(&header[0], "source");
+    let entries: Vec<ManifestEntry> = reader
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            let field = |name: &str| {
+                let index = header.iter().position(|column| column == name).unwrap();
+                record[index].to_string()
+            };
+            let optional = |name: &str| Some(field(name)).filter(|value| !value.is_empty());
+            ManifestEntry {
+                source: field("source"),
+                outputs: optional("outputs")
+                    .map(|outputs| outputs.lines().map(String::from).collect())
+                    .unwrap_or_default(),
+                width: field("width").parse().unwrap(),
+                height: field("height").parse().unwrap(),
+                format: field("format"),
+                quality: optional("quality").map(|quality| quality.parse().unwrap()),
+                bytes: field("bytes").parse().unwrap(),
+                duration_ms: field("duration_ms").parse().unwrap(),
+                status: match field("status").as_str() {
+                    "written" => ManifestStatus::Written,
+                    "skipped" => ManifestStatus::Skipped,
+                    "failed" => ManifestStatus::Failed,
+                    "duplicate" => ManifestStatus::Duplicate,
+                    other => panic!("{}", other),
+                },
+                message: optional("message"),
+                settings: field("settings"),
+                settings_hash: field("settings_hash"),
+                duplicate_of: optional("duplicate_of"),
+                blurhash: optional("blurhash"),
+                thumbhash: optional("thumbhash"),
+            }
+        })
+        .collect();
+    cross_check(&entries, &paths);
+}
+
+#[test]
+fn json_manifest_matches_the_written_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path());
+    let manifest = run(
+        paths.clone(),
+        &dir.path().join("out"),
+        ManifestFormat::Json,
+        None,
+        vec![],
+    );
+
+    let entries: Vec<ManifestEntry> = fs::read_to_string(&manifest)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    cross_check(&entries, &paths);
+}
+
+#[test]
+fn left_out_duplicates_are_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut paths = inputs(dir.path());
+    let left_out = paths.pop().unwrap();
+    let duplicate = Duplicate {
+        path: left_out.clone(),
+        kept: paths[0].clone(),
+    };
+    let manifest = run(
+        paths.clone(),
+        &dir.path().join("out"),
+        ManifestFormat::Json,
+        None,
+        vec![duplicate],
+    );
+
+    let entries: Vec<ManifestEntry> = fs::read_to_string(&manifest)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let (duplicates, processed): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .partition(|entry| entry.status == ManifestStatus::Duplicate);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].source, left_out.to_str().unwrap());
+    assert_eq!(
+        duplicates[0].duplicate_of.as_deref(),
+        Some(paths[0].to_str().unwrap())
+    );
+    assert!(duplicates[0].outputs.is_empty());
+    assert!(processed.iter().all(|entry| entry.duplicate_of.is_none()));
+    assert_eq!(processed.len(), paths.len());
+}
+
+#[test]
+fn settings_hash_follows_the_settings() {
+    let info = ProcessInfo::default();
+    let wider = ProcessInfo {
+        border_percentage: info.border_percentage + 1.0,
+        ..info.clone()
+    };
+    assert_eq!(settings_hash(&info), settings_hash(&info.clone()));
+    assert_ne!(settings_hash(&info), settings_hash(&wider));
+    assert_eq!(settings_hash(&info).len(), 16);
+}
+
+#[test]
+fn placeholders_are_recorded_for_written_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path());
+    let manifest = run(
+        paths,
+        &dir.path().join("out"),
+        ManifestFormat::Json,
+        Some(PlaceholderKind::Thumbhash),
+        vec![],
+    );
+
+    let entries: Vec<ManifestEntry> = fs::read_to_string(&manifest)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    for entry in entries {
+        assert_eq!(entry.blurhash, None);
+        assert_eq!(
+            entry.thumbhash.is_some(),
+            entry.status == ManifestStatus::Written,
+            "{:?}",
+            entry
+        );
+    }
+}
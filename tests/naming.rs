@@ -0,0 +1,516 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use image::{Rgb, RgbImage, Rgba, RgbaImage};
+use image_finalizer::{
+    batch::run_batch_cancellable,
+    format::OutputFormat,
+    naming::{
+        bordered_name, extended_length_path, has_output_suffix, numbered_name, sanitize_file_name,
+        BatchNaming, NameRules, OriginalsPlacement, OutputName, OverwritePolicy,
+    },
+    output_file_name, process_file, scan_images, validation, ProcessInfo, ProcessOutcome,
+    SkipReason,
+};
+
+fn write_image(path: &Path) {
+    RgbaImage::from_pixel(6, 4, Rgba([40, 80, 120, 255]))
+        .save_with_format(path, image::ImageFormat::Png)
+        .unwrap();
+}
+
+#[test]
+fn keeps_spaces_emoji_and_inner_dots() {
+    for (input, output) in [
+        ("my photo.png", "my photo_bordered.png"),
+        ("final.v2.edit.jpg", "final.v2.edit_bordered.png"),
+        ("🌅 sunset.jpeg", "🌅 sunset_bordered.png"),
+        (".hidden.png", ".hidden_bordered.png"),
+        ("Ünïcödé.tif", "Ünïcödé_bordered.png"),
+    ] {
+        assert_eq!(
+            output_file_name(Path::new(input), OutputFormat::Png).unwrap(),
+            OutputName {
+                name: output.into(),
+                mangled: false
+            },
+            "{}",
+            input
+        );
+    }
+}
+
+fn windows(name: &str) -> (String, bool) {
+    let output = sanitize_file_name(OsStr::new(name), NameRules::Windows);
+    (output.name.into_string().unwrap(), output.mangled)
+}
+
+#[test]
+fn windows_rules_replace_forbidden_characters() {
+    assert_eq!(
+        windows(r#"a<b>c:d"e\f|g?h*i.png"#),
+        ("a_b_c_d_e_f_g_h_i.png".to_string(), true)
+    );
+    assert_eq!(windows("tab\there.png"), ("tab_here.png".to_string(), true));
+    assert_eq!(
+        bordered_name(OsStr::new("what?"), "jpg", NameRules::Windows),
+        OutputName {
+            name: "what__bordered.jpg".into(),
+            mangled: true
+        }
+    );
+    // Unix allows all of these.
+    assert_eq!(
+        sanitize_file_name(OsStr::new("what?:*.png"), NameRules::Unix),
+        OutputName {
+            name: "what?:*.png".into(),
+            mangled: false
+        }
+    );
+}
+
+#[test]
+fn windows_rules_avoid_device_names() {
+    for (name, safe) in [
+        ("CON.png", "_CON.png"),
+        ("aux.tar.png", "_aux.tar.png"),
+        ("nul", "_nul"),
+        ("Com1.jpg", "_Com1.jpg"),
+        ("lpt9 .png", "_lpt9 .png"),
+        ("COM¹.png", "_COM¹.png"),
+    ] {
+        assert_eq!(windows(name), (safe.to_string(), true), "{}", name);
+    }
+
+    // Only the whole first part is reserved.
+    for name in ["console.png", "CON_bordered.png", "my aux.png", "COM10.png"] {
+        assert_eq!(windows(name), (name.to_string(), false), "{}", name);
+    }
+
+    // Inputs named after a device get a suffix, so their outputs are fine.
+    assert!(!bordered_name(OsStr::new("CON"), "png", NameRules::Windows).mangled);
+}
+
+#[test]
+fn windows_rules_replace_trailing_dots_and_spaces() {
+    assert_eq!(windows("photo. ."), ("photo___".to_string(), true));
+    assert_eq!(windows("photo .png"), ("photo .png".to_string(), false));
+    assert_eq!(windows("..."), ("___".to_string(), true));
+}
+
+#[test]
+fn long_windows_paths_become_verbatim() {
+    let deep = "C:\\Clients\\".to_string() + &"folder\\".repeat(40) + "photo_bordered.png";
+    let long = extended_length_path(Path::new(&deep), NameRules::Windows);
+    assert_eq!(long, PathBuf::from(format!("\\\\?\\{}", deep)));
+
+    // Separators and dot components are resolved, since Windows won't.
+    let messy = format!("C:/Clients/./x/../{}", "folder/".repeat(40));
+    let long = extended_length_path(Path::new(&messy), NameRules::Windows);
+    assert_eq!(
+        long,
+        PathBuf::from(format!(
+            "\\\\?\\C:\\Clients\\{}",
+            "folder\\".repeat(40).trim_end_matches('\\')
+        ))
+    );
+
+    let share = format!("\\\\server\\share\\..\\{}", "folder\\".repeat(40));
+    let long = extended_length_path(Path::new(&share), NameRules::Windows);
+    assert!(
+        long.to_str()
+            .unwrap()
+            .starts_with("\\\\?\\UNC\\server\\share\\folder"),
+        "{}",
+        long.display()
+    );
+}
+
+#[test]
+fn short_relative_or_unix_paths_are_unchanged() {
+    let deep = "C:\\".to_string() + &"folder\\".repeat(40);
+    for (path, rules) in [
+        ("C:\\photos\\photo.png", NameRules::Windows),
+        (&*"folder\\".repeat(40), NameRules::Windows),
+        (&*format!("\\\\?\\{}", deep), NameRules::Windows),
+        (&*deep, NameRules::Unix),
+        (&*"/folder".repeat(40), NameRules::Unix),
+    ] {
+        assert_eq!(
+            extended_length_path(Path::new(path), rules),
+            Path::new(path)
+        );
+    }
+}
+
+#[test]
+fn emoji_and_spaces_survive_a_batch() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("🌅 my photo.v2.png");
+    write_image(&input);
+
+    assert_eq!(scan_images(dir.path()).unwrap(), vec![input.clone()]);
+    let output = process_file(&input, ProcessInfo::default(), &dir.path().join("out"))
+        .unwrap()
+        .output_paths
+        .remove(0);
+    assert_eq!(output.file_name().unwrap(), "🌅 my photo.v2_bordered.png");
+    assert!(output.exists());
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    use super::*;
+
+    /// "caf\xe9", Latin-1 as found in old archives.
+    fn latin1_stem() -> OsString {
+        OsString::from_vec(b"caf\xe9".to_vec())
+    }
+
+    #[test]
+    fn non_utf8_names_are_kept_byte_for_byte() {
+        let mut name = latin1_stem();
+        name.push(".png");
+
+        let output = output_file_name(Path::new(&name), OutputFormat::Jpeg).unwrap();
+        assert!(!output.mangled);
+        assert_eq!(output.name.into_vec(), b"caf\xe9_bordered.jpg");
+    }
+
+    #[test]
+    fn non_utf8_names_can_be_processed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut name = latin1_stem();
+        name.push(".png");
+        let input = dir.path().join(name);
+        // Some file systems only accept UTF-8 names.
+        if fs::write(&input, b"").is_err() {
+            return;
+        }
+        write_image(&input);
+
+        assert_eq!(scan_images(dir.path()).unwrap(), vec![input.clone()]);
+        let output_dir = dir.path().join("out");
+        let output = process_file(&input, ProcessInfo::default(), &output_dir)
+            .unwrap()
+            .output_paths
+            .remove(0);
+        assert_eq!(
+            output.file_name().unwrap().to_os_string().into_vec(),
+            b"caf\xe9_bordered.png"
+        );
+        assert!(output.exists());
+
+        // Validation reports collisions with the lossy name instead of failing.
+        let copy = dir.path().join(OsString::from_vec(b"caf\xe9.jpg".to_vec()));
+        fs::copy(&input, &copy).unwrap();
+        let checked = validation::validate(
+            dir.path(),
+            &output_dir,
+            &[input, copy],
+            &[],
+            ProcessInfo::default(),
+            &BatchNaming::default(),
+        );
+        assert!(
+            checked
+                .warnings
+                .iter()
+                .any(|warning| warning.contains("caf\u{FFFD}_bordered.png")),
+            "{:?}",
+            checked.warnings
+        );
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn writes_beyond_the_classic_path_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("photo.png");
+    write_image(&input);
+
+    let output_dir = (0..30).fold(dir.path().to_path_buf(), |path, _| path.join("folder"));
+    let output = process_file(&input, ProcessInfo::default(), &output_dir)
+        .unwrap()
+        .output_paths
+        .remove(0);
+    assert!(output.to_str().unwrap().starts_with(r"\\?\"));
+    assert!(output.exists());
+}
+
+#[test]
+fn numbered_names_keep_the_extension() {
+    assert_eq!(
+        numbered_name(OsStr::new("a_bordered.png"), 2),
+        "a_bordered (2).png"
+    );
+    assert_eq!(numbered_name(OsStr::new(".hidden"), 1), ".hidden (1)");
+    assert_eq!(numbered_name(OsStr::new("v1.2.jpg"), 10), "v1.2 (10).jpg");
+
+    for (name, output) in [
+        ("a_bordered (3).png", true),
+        ("a_bordered(3).png", false),
+        ("a_bordered ().png", false),
+        ("a_bordered (x).png", false),
+        ("a (1)_bordered.png", true),
+    ] {
+        assert_eq!(
+            has_output_suffix(Path::new(name), "_bordered"),
+            output,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn taken_names_follow_the_overwrite_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_dir = dir.path().join("out");
+    let paths = vec![
+        dir.path().join("IMG_0001.jpg"),
+        dir.path().join("IMG_0001.png"),
+    ];
+    for path in &paths {
+        RgbImage::from_pixel(6, 4, Rgb([40, 80, 120]))
+            .save(path)
+            .unwrap();
+    }
+
+    let run = |overwrite| {
+        let naming = BatchNaming {
+            overwrite,
+            ..BatchNaming::default()
+        };
+        let outcomes = Mutex::new(vec![]);
+        run_batch_cancellable(
+            &paths,
+            &output_dir,
+            &[],
+            ProcessInfo::default(),
+            &naming,
+            &AtomicBool::new(false),
+            |_, _, result| outcomes.lock().unwrap().push(result.unwrap()),
+        );
+        let validation = validation::validate(
+            dir.path(),
+            &output_dir,
+            &paths,
+            &[],
+            ProcessInfo::default(),
+            &naming,
+        );
+        (outcomes.into_inner().unwrap(), validation.warnings)
+    };
+    let names = |outcomes: &[ProcessOutcome]| {
+        let mut names: Vec<String> = outcomes
+            .iter()
+            .flat_map(|outcome| &outcome.output_paths)
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    };
+
+    let (outcomes, warnings) = run(OverwritePolicy::Rename);
+    assert_eq!(
+        names(&outcomes),
+        ["IMG_0001_bordered (1).png", "IMG_0001_bordered.png"]
+    );
+    // Checked after the run, so the name is taken on top of the collision.
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.ends_with("all but one of them get a number")),
+        "{:?}",
+        warnings
+    );
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.ends_with("the output name is taken, so a number is added")),
+        "{:?}",
+        warnings
+    );
+
+    let (outcomes, _) = run(OverwritePolicy::Skip);
+    assert!(names(&outcomes).is_empty());
+    assert!(outcomes
+        .iter()
+        .all(|outcome| outcome.skipped == Some(SkipReason::OutputExists)));
+
+    let (outcomes, _) = run(OverwritePolicy::Rename);
+    assert_eq!(
+        names(&outcomes),
+        ["IMG_0001_bordered (2).png", "IMG_0001_bordered (3).png"]
+    );
+
+    let (outcomes, warnings) = run(OverwritePolicy::Overwrite);
+    assert_eq!(names(&outcomes), ["IMG_0001_bordered.png"; 2]);
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.ends_with("only one of them is kept")),
+        "{:?}",
+        warnings
+    );
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 4);
+}
+
+#[test]
+fn originals_are_copied_after_their_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_dir = dir.path().join("out");
+    let input = dir.path().join("photo.png");
+    write_image(&input);
+    let taken = SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+    fs::File::options()
+        .write(true)
+        .open(&input)
+        .unwrap()
+        .set_modified(taken)
+        .unwrap();
+    let broken = dir.path().join("broken.png");
+    fs::write(&broken, "not a png").unwrap();
+
+    let run = |output_dir: &Path, naming: &BatchNaming| {
+        let outcomes = Mutex::new(vec![]);
+        run_batch_cancellable(
+            &[input.clone(), broken.clone()],
+            output_dir,
+            &[],
+            ProcessInfo::default(),
+            naming,
+            &AtomicBool::new(false),
+            |_, _, result| outcomes.lock().unwrap().push(result),
+        );
+        let mut outcomes: Vec<ProcessOutcome> = outcomes
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect();
+        assert_eq!(outcomes.len(), 1);
+        outcomes.remove(0)
+    };
+
+    let naming = BatchNaming {
+        originals: Some(OriginalsPlacement::Subfolder),
+        preserve_timestamps: true,
+        ..BatchNaming::default()
+    };
+    let outcome = run(&output_dir, &naming);
+    let copy = output_dir.join("originals").join("photo.png");
+    assert_eq!(
+        outcome.output_paths,
+        [output_dir.join("photo_bordered.png"), copy.clone()]
+    );
+    assert_eq!(fs::read(&copy).unwrap(), fs::read(&input).unwrap());
+    let sizes: u64 = outcome
+        .output_paths
+        .iter()
+        .map(|path| fs::metadata(path).unwrap().len())
+        .sum();
+    assert_eq!(outcome.bytes_written, sizes);
+    for path in &outcome.output_paths {
+        assert_eq!(fs::metadata(path).unwrap().modified().unwrap(), taken);
+    }
+    // The input that failed left no copy behind.
+    assert!(!output_dir.join("originals").join("broken.png").exists());
+
+    // Copying into the input's own folder would copy it onto itself.
+    let naming = BatchNaming {
+        originals: Some(OriginalsPlacement::Beside),
+        ..BatchNaming::default()
+    };
+    let before = fs::read(&input).unwrap();
+    let outcome = run(dir.path(), &naming);
+    assert_eq!(
+        outcome.output_paths,
+        [dir.path().join("photo_bordered.png")]
+    );
+    assert_eq!(fs::read(&input).unwrap(), before);
+}
+
+#[test]
+fn stale_temporary_outputs_are_removed_and_none_are_left() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_dir = dir.path().join("out");
+    fs::create_dir_all(output_dir.join("sub")).unwrap();
+    // Beyond the pid range of every system, so no process is running.
+    let dead = 999_999_999;
+    let stale = [
+        output_dir.join(format!(".photo_bordered.png.tmp-{}", dead)),
+        output_dir.join(format!(".other.png.tmp-{}-3", dead)),
+        output_dir.join(".photo_bordered.png.tmp-99999999999"),
+    ];
+    let kept = [
+        output_dir.join(".hidden"),
+        output_dir.join(".notes.tmp-draft"),
+        output_dir.join(format!("photo.tmp-{}", dead)),
+        // Still being written by a running process, this one.
+        output_dir.join(format!(".other.png.tmp-{}", std::process::id())),
+        // Nothing is written there.
+        output_dir
+            .join("sub")
+            .join(format!(".other.png.tmp-{}", dead)),
+    ];
+    for path in stale.iter().chain(&kept) {
+        fs::write(path, "partial").unwrap();
+    }
+    let paths = vec![dir.path().join("photo.png"), dir.path().join("other.png")];
+    for path in &paths {
+        write_image(path);
+    }
+
+    for overwrite in [OverwritePolicy::Overwrite, OverwritePolicy::Rename] {
+        let naming = BatchNaming {
+            overwrite,
+            ..BatchNaming::default()
+        };
+        run_batch_cancellable(
+            &paths,
+            &output_dir,
+            &[],
+            ProcessInfo::default(),
+            &naming,
+            &AtomicBool::new(false),
+            |_, _, result| {
+                result.unwrap();
+            },
+        );
+    }
+
+    for path in &stale {
+        assert!(!path.exists(), "{} is left", path.display());
+    }
+    for path in &kept {
+        assert!(path.exists(), "{} is gone", path.display());
+    }
+    let mut names: Vec<String> = fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.') && !name.starts_with("photo.tmp-") && name != "sub")
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "other_bordered (1).png",
+            "other_bordered.png",
+            "photo_bordered (1).png",
+            "photo_bordered.png"
+        ]
+    );
+    for name in &names {
+        image::open(output_dir.join(name)).unwrap();
+    }
+}
@@ -0,0 +1,461 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use image::{
+    imageops::FilterType, DynamicImage, GenericImageView, ImageBuffer, Rgb, Rgba, RgbaImage,
+};
+use image_finalizer::{
+    compute_geometry,
+    corners::{CornerRadius, RadiusUnit},
+    fill::{BorderFill, TextureFill, TextureMode},
+    keyline::Keyline,
+    output_dimensions,
+    pipeline::{Border, FileContext, Pipeline, ProcessingStep, Resize},
+    shadow::DropShadow,
+    BorderMode, BorderSides, CanvasAspect, Frame, FramePlacement, PixelBorder, ProcessError,
+    ProcessInfo,
+};
+
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+fn ctx() -> FileContext<'static> {
+    FileContext {
+        path: Path::new("photo.png"),
+    }
+}
+
+fn solid(width: u32, height: u32, color: Rgba<u8>) -> DynamicImage {
+    RgbaImage::from_pixel(width, height, color).into()
+}
+
+/// Records the order steps ran in.
+struct Probe {
+    name: &'static str,
+    log: &'static Mutex<Vec<&'static str>>,
+}
+
+impl ProcessingStep for Probe {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn apply(&self, img: DynamicImage, _ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        self.log.lock().unwrap().push(self.name);
+        Ok(img)
+    }
+}
+
+struct Fail;
+
+impl ProcessingStep for Fail {
+    fn name(&self) -> &'static str {
+        "fail"
+    }
+
+    fn apply(&self, _img: DynamicImage, ctx: &FileContext) -> Result<DynamicImage, ProcessError> {
+        Err(ProcessError::InvalidPath {
+            path: ctx.path.to_path_buf(),
+        })
+    }
+}
+
+#[test]
+fn border_step_centers_the_photo() {
+    let border = Border {
+        mode: BorderMode::Square,
+        percentage: 50.0,
+        sides: None,
+        pixels: None,
+        resize: None,
+        aspect: None,
+        corners: None,
+        shadow: None,
+        keyline: None,
+        fill: BorderFill::Solid,
+        color: BLUE,
+    };
+    let img = border.apply(solid(20, 10, RED), &ctx()).unwrap();
+
+    assert_eq!(img.dimensions(), (30, 30));
+    assert_eq!(img.get_pixel(0, 0), BLUE);
+    assert_eq!(img.get_pixel(29, 29), BLUE);
+    assert_eq!(img.get_pixel(15, 15), RED);
+}
+
+#[test]
+fn border_step_places_the_photo_by_its_sides() {
+    let border = Border {
+        mode: BorderMode::Square,
+        percentage: 50.0,
+        sides: Some(BorderSides {
+            top: 10.0,
+            right: 0.0,
+            bottom: 30.0,
+            left: 20.0,
+        }),
+        pixels: None,
+        resize: None,
+        aspect: None,
+        corners: None,
+        shadow: None,
+        keyline: None,
+        fill: BorderFill::Solid,
+        color: BLUE,
+    };
+    let img = border.apply(solid(20, 10, RED), &ctx()).unwrap();
+
+    assert_eq!(img.dimensions(), (24, 18));
+    assert_eq!(img.get_pixel(3, 2), BLUE);
+    assert_eq!(img.get_pixel(4, 2), RED);
+    assert_eq!(img.get_pixel(23, 11), RED);
+    assert_eq!(img.get_pixel(23, 12), BLUE);
+}
+
+#[test]
+fn pixel_borders_come_out_as_wide_as_asked() {
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_pixels: Some(PixelBorder {
+            width: 4,
+            after_resize: true,
+        }),
+        border_color: BLUE,
+        resize_images: true,
+        resize_longest_dimension: 48,
+        resize_filter: FilterType::Nearest,
+        ..ProcessInfo::default()
+    };
+    let img = Pipeline::for_info(&info)
+        .run(solid(400, 200, RED), &ctx())
+        .unwrap();
+
+    assert_eq!(img.dimensions(), (48, 28));
+    assert_eq!(img.get_pixel(3, 14), BLUE);
+    assert_eq!(img.get_pixel(4, 14), RED);
+    assert_eq!(img.get_pixel(43, 14), RED);
+    assert_eq!(img.get_pixel(44, 14), BLUE);
+}
+
+/// A red photo with a blue quarter, so misplaced borders show.
+fn quartered(width: u32, height: u32) -> DynamicImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        if x < width / 2 && y < height / 2 {
+            BLUE
+        } else {
+            RED
+        }
+    })
+    .into()
+}
+
+/// Checks that `info` on a quarter size copy of a photo, as the preview
+/// runs it, looks like the output scaled down.
+fn assert_preview_matches(info: &ProcessInfo) {
+    let photo = quartered(1200, 800);
+    let output = Pipeline::for_info(info).run(photo.clone(), &ctx()).unwrap();
+    let preview = Pipeline::for_info(&info.scaled(0.25))
+        .run(photo.resize_exact(300, 200, FilterType::Triangle), &ctx())
+        .unwrap()
+        .to_rgba8();
+    let (width, height) = preview.dimensions();
+    assert_eq!((width * 4, height * 4), output.dimensions());
+    let scaled = output
+        .resize_exact(width, height, FilterType::Triangle)
+        .to_rgba8();
+    let difference = scaled
+        .as_raw()
+        .iter()
+        .zip(preview.as_raw())
+        .map(|(a, b)| a.abs_diff(*b) as u64)
+        .sum::<u64>()
+        / scaled.as_raw().len() as u64;
+    assert!(difference <= 3, "differs by {} on average", difference);
+}
+
+#[test]
+fn previews_scale_pixel_borders() {
+    assert_preview_matches(&ProcessInfo {
+        symmetrical_border: true,
+        border_pixels: Some(PixelBorder {
+            width: 40,
+            after_resize: false,
+        }),
+        ..ProcessInfo::default()
+    });
+}
+
+#[test]
+fn previews_scale_rounded_corners() {
+    assert_preview_matches(&ProcessInfo {
+        corner_radius: Some(CornerRadius {
+            radius: 120.0,
+            unit: RadiusUnit::Pixels,
+        }),
+        ..ProcessInfo::default()
+    });
+}
+
+#[test]
+fn previews_scale_drop_shadows() {
+    assert_preview_matches(&ProcessInfo {
+        drop_shadow: Some(DropShadow {
+            offset_x: 32.0,
+            offset_y: 48.0,
+            blur: 64.0,
+            opacity: 100.0,
+            ..DropShadow::default()
+        }),
+        ..ProcessInfo::default()
+    });
+}
+
+#[test]
+fn previews_scale_keylines() {
+    assert_preview_matches(&ProcessInfo {
+        keyline: Some(Keyline {
+            width: 24.0,
+            ..Keyline::default()
+        }),
+        ..ProcessInfo::default()
+    });
+}
+
+#[test]
+fn previews_scale_texture_tiles() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("paper.png");
+    quartered(64, 64).save(&path).unwrap();
+    assert_preview_matches(&ProcessInfo {
+        border_fill: BorderFill::Texture(TextureFill {
+            path,
+            mode: TextureMode::Tile,
+            ..TextureFill::default()
+        }),
+        ..ProcessInfo::default()
+    });
+}
+
+fn framed(placement: FramePlacement) -> ProcessInfo {
+    ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 50.0,
+        border_color: BLUE,
+        frame: Some(Frame {
+            width: 5.0,
+            color: Rgba([0, 0, 0, 255]),
+            placement,
+        }),
+        ..ProcessInfo::default()
+    }
+}
+
+#[test]
+fn frames_go_inside_the_border() {
+    let info = framed(FramePlacement::Inside);
+    let pipeline = Pipeline::for_info(&info);
+    assert_eq!(pipeline.step_names(), ["border", "border"]);
+    let img = pipeline.run(solid(40, 20, RED), &ctx()).unwrap();
+
+    // Two pixels of frame around the photo, then 11 of border around both.
+    assert_eq!(img.dimensions(), (66, 46));
+    assert_eq!(img.get_pixel(10, 23), BLUE);
+    assert_eq!(img.get_pixel(11, 23), Rgba([0, 0, 0, 255]));
+    assert_eq!(img.get_pixel(12, 23), Rgba([0, 0, 0, 255]));
+    assert_eq!(img.get_pixel(13, 23), RED);
+    assert_eq!(img.get_pixel(52, 23), RED);
+    assert_eq!(img.get_pixel(54, 23), Rgba([0, 0, 0, 255]));
+    assert_eq!(img.get_pixel(55, 23), BLUE);
+}
+
+#[test]
+fn frames_go_around_the_border() {
+    let img = Pipeline::for_info(&framed(FramePlacement::Outside))
+        .run(solid(40, 20, RED), &ctx())
+        .unwrap();
+
+    // 10 pixels of border around the photo, then 3 of frame around both.
+    assert_eq!(img.dimensions(), (66, 46));
+    assert_eq!(img.get_pixel(2, 23), Rgba([0, 0, 0, 255]));
+    assert_eq!(img.get_pixel(3, 23), BLUE);
+    assert_eq!(img.get_pixel(12, 23), BLUE);
+    assert_eq!(img.get_pixel(13, 23), RED);
+    assert_eq!(img.get_pixel(52, 23), RED);
+    assert_eq!(img.get_pixel(53, 23), BLUE);
+    assert_eq!(img.get_pixel(62, 23), BLUE);
+    assert_eq!(img.get_pixel(63, 23), Rgba([0, 0, 0, 255]));
+}
+
+#[test]
+fn frames_are_counted_in_the_output_size() {
+    for placement in [FramePlacement::Inside, FramePlacement::Outside] {
+        let info = framed(placement);
+        let img = Pipeline::for_info(&info)
+            .run(solid(40, 20, RED), &ctx())
+            .unwrap();
+        assert_eq!(output_dimensions(40, 20, &info), img.dimensions());
+        let geometry = compute_geometry(40, 20, &info);
+        assert_eq!(
+            img.get_pixel(geometry.x_offset, geometry.y_offset),
+            RED,
+            "{:?}",
+            placement
+        );
+        assert_ne!(img.get_pixel(geometry.x_offset - 1, geometry.y_offset), RED);
+    }
+}
+
+#[test]
+fn pixel_borders_after_the_resize_allow_for_the_aspect() {
+    let info = ProcessInfo {
+        border_pixels: Some(PixelBorder {
+            width: 10,
+            after_resize: true,
+        }),
+        canvas_aspect: Some(CanvasAspect::new(16, 9)),
+        border_color: BLUE,
+        resize_images: true,
+        resize_longest_dimension: 400,
+        resize_filter: FilterType::Nearest,
+        ..ProcessInfo::default()
+    };
+    let img = Pipeline::for_info(&info)
+        .run(solid(250, 500, RED), &ctx())
+        .unwrap();
+
+    // The photo is as tall as the canvas but for the border, while the
+    // aspect pads it across.
+    assert_eq!(img.dimensions(), (400, 225));
+    assert_eq!(img.get_pixel(200, 9), BLUE);
+    assert_eq!(img.get_pixel(200, 10), RED);
+    assert_eq!(img.get_pixel(200, 214), RED);
+    assert_eq!(img.get_pixel(200, 215), BLUE);
+}
+
+#[test]
+fn frames_keep_the_canvas_aspect() {
+    for placement in [FramePlacement::Inside, FramePlacement::Outside] {
+        let info = ProcessInfo {
+            canvas_aspect: Some(CanvasAspect::new(4, 5)),
+            frame: Some(Frame {
+                width: 2.0,
+                color: BLUE,
+                placement,
+            }),
+            ..ProcessInfo::default()
+        };
+        let img = Pipeline::for_info(&info)
+            .run(solid(400, 400, RED), &ctx())
+            .unwrap();
+        let (width, height) = img.dimensions();
+        assert_eq!(
+            width * 5,
+            height * 4,
+            "{}×{} {:?}",
+            width,
+            height,
+            placement
+        );
+        assert_eq!(output_dimensions(400, 400, &info), (width, height));
+        assert_eq!(info.canvas_ratio(), Some(width as f32 / height as f32));
+    }
+}
+
+#[test]
+fn border_step_keeps_16_bit_channels() {
+    let border = Border {
+        mode: BorderMode::Symmetrical,
+        percentage: 50.0,
+        sides: None,
+        pixels: None,
+        resize: None,
+        aspect: None,
+        corners: None,
+        shadow: None,
+        keyline: None,
+        fill: BorderFill::Solid,
+        color: BLUE,
+    };
+    let photo = ImageBuffer::from_pixel(4, 2, Rgb([1000u16, 2000, 3000]));
+    let img = border.apply(photo.into(), &ctx()).unwrap();
+
+    let img = img.as_rgba16().expect("16-bit output");
+    assert_eq!(img.get_pixel(0, 0), &Rgba([0, 0, 65535, 65535]));
+    assert_eq!(img.get_pixel(2, 2), &Rgba([1000, 2000, 3000, 65535]));
+}
+
+#[test]
+fn resize_step_scales_the_longest_side() {
+    let resize = Resize {
+        longest_dimension: 10,
+        filter: FilterType::Nearest,
+    };
+    let img = resize.apply(solid(40, 20, RED), &ctx()).unwrap();
+
+    assert_eq!(img.dimensions(), (10, 5));
+    assert_eq!(img.get_pixel(5, 2), RED);
+}
+
+#[test]
+fn steps_follow_the_settings() {
+    let info = ProcessInfo {
+        resize_images: false,
+        ..ProcessInfo::default()
+    };
+    assert_eq!(Pipeline::for_info(&info).step_names(), ["border"]);
+
+    let info = ProcessInfo {
+        resize_images: true,
+        ..info
+    };
+    assert_eq!(Pipeline::for_info(&info).step_names(), ["border", "resize"]);
+}
+
+#[test]
+fn steps_run_in_order() {
+    static LOG: Mutex<Vec<&str>> = Mutex::new(vec![]);
+    let pipeline = Pipeline::new(vec![
+        Box::new(Probe {
+            name: "first",
+            log: &LOG,
+        }),
+        Box::new(Probe {
+            name: "second",
+            log: &LOG,
+        }),
+    ]);
+
+    let mut timed = vec![];
+    pipeline
+        .run_timed(solid(2, 2, RED), &ctx(), |name, _| timed.push(name))
+        .unwrap();
+
+    assert_eq!(*LOG.lock().unwrap(), ["first", "second"]);
+    assert_eq!(timed, ["first", "second"]);
+}
+
+#[test]
+fn failing_step_stops_the_run() {
+    static LOG: Mutex<Vec<&str>> = Mutex::new(vec![]);
+    let pipeline = Pipeline::new(vec![
+        Box::new(Fail),
+        Box::new(Probe {
+            name: "after",
+            log: &LOG,
+        }),
+    ]);
+
+    match pipeline.run(solid(2, 2, RED), &ctx()) {
+        Err(ProcessError::InvalidPath { path }) => assert_eq!(path, PathBuf::from("photo.png")),
+        other => panic!("{:?}", other.map(|img| img.dimensions())),
+    }
+    assert!(LOG.lock().unwrap().is_empty());
+}
+
+#[test]
+fn empty_pipeline_keeps_the_image() {
+    let img = solid(3, 4, RED);
+    assert_eq!(Pipeline::default().run(img.clone(), &ctx()).unwrap(), img);
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::placeholder::{placeholder, PlaceholderKind};
+
+fn fixture(name: &str) -> RgbaImage {
+    image::open(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+    .into_rgba8()
+}
+
+#[test]
+fn hashes_match_known_good_ones() {
+    let img = fixture("landscape.png");
+    assert_eq!(
+        placeholder(&img, PlaceholderKind::Blurhash),
+        "LxHBn,2rwxX7mHWWjtf7gJfjfQfj"
+    );
+    assert_eq!(
+        placeholder(&img, PlaceholderKind::Thumbhash),
+        "nBgKNZqAh3eAiIiIiHiIh4BwCPiI"
+    );
+}
+
+#[test]
+fn large_images_are_scaled_down_first() {
+    // A flat color survives scaling unchanged, so a large image must hash
+    // like a small one.
+    let color = Rgba([40, 120, 200, 255]);
+    let large = RgbaImage::from_pixel(1000, 750, color);
+    let small = RgbaImage::from_pixel(64, 48, color);
+    for kind in [PlaceholderKind::Blurhash, PlaceholderKind::Thumbhash] {
+        assert_eq!(placeholder(&large, kind), placeholder(&small, kind));
+    }
+}
+
+#[test]
+fn portraits_swap_the_blurhash_components() {
+    let hash = placeholder(&fixture("portrait.png"), PlaceholderKind::Blurhash);
+    // The first character holds the component counts: 3 × 4 is 2 + 3 · 9.
+    assert!(hash.starts_with('T'), "{}", hash);
+    assert_eq!(hash.len(), 4 + 2 * 3 * 4);
+}
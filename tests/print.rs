@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::{
+    print::{self, Orientation, PaperSize, PrintSettings, MAX_MARGIN},
+    ProcessError,
+};
+
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const GREEN: Rgba<u8> = Rgba([0, 160, 0, 255]);
+
+/// Points in `mm` millimeters.
+fn points(mm: f32) -> f32 {
+    mm * 72.0 / 25.4
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.01
+}
+
+#[test]
+fn images_are_scaled_to_fit_inside_the_margins_and_centered() {
+    let settings = PrintSettings::default();
+    let layout = settings.layout(3000, 2000);
+    assert!(close(layout.width, points(210.0)) && close(layout.height, points(297.0)));
+    assert!(close(layout.image.x, points(10.0)));
+    assert!(close(layout.image.width, points(190.0)));
+    assert!(close(layout.image.height, points(190.0) * 2.0 / 3.0));
+    // The space left over is shared above and below.
+    let below = layout.height - layout.image.y - layout.image.height;
+    assert!(close(layout.image.y, below));
+
+    let landscape = PrintSettings {
+        paper: PaperSize::Letter,
+        orientation: Orientation::Landscape,
+        margin: 1000.0,
+        ..settings
+    };
+    let layout = landscape.layout(100, 100);
+    assert!(close(layout.width, 792.0) && close(layout.height, 612.0));
+    assert!(close(layout.printable.y, points(MAX_MARGIN)));
+    assert!(close(layout.image.height, 612.0 - 2.0 * points(MAX_MARGIN)));
+    assert!(close(layout.image.x * 2.0 + layout.image.width, 792.0));
+}
+
+#[test]
+fn the_preview_shows_the_border_color_inside_the_margins() {
+    let img = RgbaImage::from_pixel(40, 10, RED).into();
+    let settings = PrintSettings {
+        margin: 20.0,
+        fill_border: true,
+        ..PrintSettings::default()
+    };
+    let page = print::render_page(&img, &settings, GREEN, 1.0);
+    assert_eq!(page.dimensions(), (595, 842));
+    assert_eq!(*page.get_pixel(5, 5), Rgba([255; 4]));
+    let margin = points(20.0).round() as u32;
+    assert_eq!(*page.get_pixel(margin + 2, margin + 2), GREEN);
+    assert_eq!(*page.get_pixel(297, 421), RED);
+
+    let plain = print::render_page(&img, &PrintSettings::default(), GREEN, 1.0);
+    assert_eq!(*plain.get_pixel(40, 40), Rgba([255; 4]));
+}
+
+#[test]
+fn every_image_gets_a_page_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let images: Vec<PathBuf> = [(30, 20), (20, 40)]
+        .iter()
+        .enumerate()
+        .map(|(index, &(width, height))| {
+            let path = dir.path().join(format!("{}.png", index));
+            RgbaImage::from_pixel(width, height, Rgba([0, 0, 255, 128]))
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect();
+    let pdf_path = dir.path().join("print.pdf");
+    print::write_pdf(&pdf_path, &images, &PrintSettings::default(), GREEN).unwrap();
+
+    let pdf = std::fs::read(&pdf_path).unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.starts_with("%PDF-1.4"));
+    assert!(text.trim_end().ends_with("%%EOF"));
+    assert!(text.contains("/Count 2"));
+    assert_eq!(text.matches("/Type /Page ").count(), 2);
+    assert_eq!(text.matches("/MediaBox [0 0 595.28 841.89]").count(), 2);
+    let first = text.find("/Width 30 /Height 20").unwrap();
+    let second = text.find("/Width 20 /Height 40").unwrap();
+    assert!(first < second);
+
+    // Every object starts where the cross-reference table says.
+    let xref = text.rfind("\nxref\n").unwrap();
+    let offsets: Vec<usize> = text[xref..]
+        .lines()
+        .filter(|line| line.ends_with(" n "))
+        .map(|line| line[..10].parse().unwrap())
+        .collect();
+    assert_eq!(offsets.len(), 8);
+    for (index, offset) in offsets.iter().enumerate() {
+        let object = format!("{} 0 obj", index + 1);
+        assert!(pdf[*offset..].starts_with(object.as_bytes()), "{}", object);
+    }
+}
+
+#[test]
+fn unreadable_images_fail_the_print() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.png");
+    let error = print::write_pdf(
+        &dir.path().join("print.pdf"),
+        std::slice::from_ref(&missing),
+        &PrintSettings::default(),
+        GREEN,
+    )
+    .unwrap_err();
+    assert!(matches!(error, ProcessError::Io { path, .. } if path == missing));
+}
+
+#[test]
+fn settings_fill_in_what_is_missing() {
+    let settings: PrintSettings = serde_json::from_str(r#"{"paper":"letter"}"#).unwrap();
+    assert_eq!(
+        settings,
+        PrintSettings {
+            paper: PaperSize::Letter,
+            ..PrintSettings::default()
+        }
+    );
+}
@@ -0,0 +1,82 @@
+use image_finalizer::{
+    border_geometry, output_dimensions, resize_target, BorderMode, ProcessInfo, MAX_DIMENSION,
+};
+use proptest::prelude::*;
+
+fn side() -> impl Strategy<Value = u32> {
+    1..=MAX_DIMENSION
+}
+
+fn mode() -> impl Strategy<Value = BorderMode> {
+    prop_oneof![Just(BorderMode::Square), Just(BorderMode::Symmetrical)]
+}
+
+fn percentage() -> impl Strategy<Value = f32> {
+    0.0f32..=400.0
+}
+
+proptest! {
+    #[test]
+    fn canvas_holds_the_photo(
+        width in side(),
+        height in side(),
+        mode in mode(),
+        percentage in percentage(),
+    ) {
+        let geometry = border_geometry(width, height, mode, percentage);
+        prop_assert!(geometry.canvas_width >= width);
+        prop_assert!(geometry.canvas_height >= height);
+        prop_assert!(geometry.x_offset + width <= geometry.canvas_width);
+        prop_assert!(geometry.y_offset + height <= geometry.canvas_height);
+    }
+
+    #[test]
+    fn photo_is_centered(
+        width in side(),
+        height in side(),
+        mode in mode(),
+        percentage in percentage(),
+    ) {
+        let geometry = border_geometry(width, height, mode, percentage);
+        let right = geometry.canvas_width - width - geometry.x_offset;
+        let bottom = geometry.canvas_height - height - geometry.y_offset;
+        prop_assert!(right - geometry.x_offset <= 1);
+        prop_assert!(bottom - geometry.y_offset <= 1);
+    }
+
+    #[test]
+    fn resize_respects_the_longest_side(
+        width in side(),
+        height in side(),
+        longest in side(),
+    ) {
+        let (resized_width, resized_height) = resize_target(width, height, longest);
+        prop_assert!(resized_width >= 1 && resized_height >= 1);
+        prop_assert!(resized_width.max(resized_height).abs_diff(longest) <= 1);
+    }
+
+    #[test]
+    fn output_dimensions_never_overflow(
+        width in side(),
+        height in side(),
+        mode in mode(),
+        percentage in percentage(),
+        resize in proptest::option::of(side()),
+    ) {
+        let info = ProcessInfo {
+            symmetrical_border: mode == BorderMode::Symmetrical,
+            border_percentage: percentage,
+            resize_images: resize.is_some(),
+            resize_longest_dimension: resize.unwrap_or(1),
+            ..ProcessInfo::default()
+        };
+        let (out_width, out_height) = output_dimensions(width, height, &info);
+        prop_assert!(out_width >= 1 && out_height >= 1);
+        if let Some(longest) = resize {
+            prop_assert!(out_width.max(out_height).abs_diff(longest) <= 1);
+        } else {
+            // Five times the longest side is the most a 400% border adds.
+            prop_assert!(out_width.max(out_height) <= width.max(height) * 5);
+        }
+    }
+}
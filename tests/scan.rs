@@ -0,0 +1,506 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Mutex},
+};
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::{
+    apply_order,
+    batch::run_batch_cancellable,
+    is_supported_image, move_path,
+    naming::{has_output_suffix, BatchNaming, DEFAULT_SUFFIX},
+    scan_images, scan_inputs,
+    validation::{self, Overlap},
+    BorderSides, Frame, PixelBorder, ProcessInfo, MAX_DIMENSION,
+};
+
+#[test]
+fn supported_extensions_ignore_case() {
+    for name in [
+        "a.png", "b.JPG", "c.Jpeg", "d.gif", "e.bmp", "f.TIF", "g.h.png",
+    ] {
+        assert!(is_supported_image(Path::new(name)), "{}", name);
+    }
+    for name in ["a", "b.txt", "c.png.txt", ".png", "d.pngx", "e.jp"] {
+        assert!(!is_supported_image(Path::new(name)), "{}", name);
+    }
+}
+
+#[test]
+fn scan_matches_the_helper() {
+    let dir = tempfile::tempdir().unwrap();
+    let names = [
+        "a.png",
+        "b.JPG",
+        "notes.txt",
+        "c.tif",
+        "README",
+        "d.png.bak",
+    ];
+    for name in names {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    // Folders are never images, whatever they are called.
+    fs::create_dir(dir.path().join("folder.png")).unwrap();
+
+    let mut found = scan_images(dir.path()).unwrap();
+    found.sort();
+    let mut expected: Vec<_> = names
+        .iter()
+        .map(|name| dir.path().join(name))
+        .filter(|path| is_supported_image(path))
+        .collect();
+    expected.sort();
+
+    assert_eq!(found, expected);
+    assert_eq!(found.len(), 3);
+}
+
+#[test]
+fn outputs_inside_the_input_are_not_rescanned() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["a.png", "a_bordered.png", "b_bordered.jpg", "bordered.png"] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+
+    let mut same = scan_inputs(dir.path(), dir.path(), DEFAULT_SUFFIX, false).unwrap();
+    same.sort();
+    assert_eq!(
+        same,
+        vec![dir.path().join("a.png"), dir.path().join("bordered.png")]
+    );
+
+    // Through another spelling of the same folder, and into a subfolder.
+    let dotted = dir.path().join("sub").join("..");
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    assert_eq!(
+        scan_inputs(dir.path(), &dotted, DEFAULT_SUFFIX, false)
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        scan_inputs(
+            dir.path(),
+            &dir.path().join("out/new"),
+            DEFAULT_SUFFIX,
+            false
+        )
+        .unwrap()
+        .len(),
+        2
+    );
+
+    // Elsewhere, everything is an input.
+    let elsewhere = tempfile::tempdir().unwrap();
+    assert_eq!(
+        scan_inputs(dir.path(), elsewhere.path(), DEFAULT_SUFFIX, false)
+            .unwrap()
+            .len(),
+        4
+    );
+
+    assert!(has_output_suffix(
+        Path::new("x/photo_bordered.webp"),
+        DEFAULT_SUFFIX
+    ));
+    assert!(!has_output_suffix(
+        Path::new("photo_bordered.png.bak"),
+        DEFAULT_SUFFIX
+    ));
+    assert!(has_output_suffix(Path::new("photo-web.jpg"), "-web"));
+    assert!(!has_output_suffix(Path::new("photo.png"), ""));
+}
+
+#[test]
+fn overwriting_an_input_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = vec![
+        dir.path().join("foo.png"),
+        dir.path().join("foo_bordered.png"),
+    ];
+    for path in &paths {
+        fs::write(path, "").unwrap();
+    }
+
+    let checked = validation::validate(
+        dir.path(),
+        dir.path(),
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &BatchNaming::default(),
+    );
+    assert_eq!(checked.errors.len(), 1, "{:?}", checked.errors);
+    assert!(checked.errors[0].contains("foo_bordered.png"));
+
+    // Left out by the scan, the earlier output is simply replaced.
+    let paths = scan_inputs(dir.path(), dir.path(), DEFAULT_SUFFIX, false).unwrap();
+    let checked = validation::validate(
+        dir.path(),
+        dir.path(),
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &BatchNaming::default(),
+    );
+    assert!(checked.errors.is_empty(), "{:?}", checked.errors);
+}
+
+#[test]
+fn blockers_are_found_without_inputs_and_reported_by_validation() {
+    let dir = tempfile::tempdir().unwrap();
+    let naming = BatchNaming::default();
+
+    let info = ProcessInfo::default();
+    let blockers = validation::blockers(Path::new(""), Path::new(""), 0, &info, &naming);
+    assert_eq!(
+        blockers,
+        vec![
+            "No input directory selected",
+            "No output directory selected"
+        ]
+    );
+    assert!(
+        validation::blockers(dir.path(), &dir.path().join("new"), 1, &info, &naming).is_empty()
+    );
+
+    let broken = BatchNaming {
+        template: "{nope}".to_string(),
+        ..BatchNaming::default()
+    };
+    let blockers = validation::blockers(dir.path(), &dir.path().join("new"), 0, &info, &broken);
+    assert_eq!(blockers.len(), 2, "{:?}", blockers);
+    let checked = validation::validate(
+        dir.path(),
+        &dir.path().join("new"),
+        &[],
+        &[],
+        ProcessInfo::default(),
+        &broken,
+    );
+    assert_eq!(checked.errors, blockers);
+}
+
+/// Blockers of a batch that is fine but for `info`.
+fn setting_blockers(info: &ProcessInfo) -> Vec<String> {
+    let dir = tempfile::tempdir().unwrap();
+    validation::blockers(
+        dir.path(),
+        &dir.path().join("new"),
+        1,
+        info,
+        &BatchNaming::default(),
+    )
+}
+
+#[test]
+fn borders_outside_the_slider_are_blockers() {
+    for percentage in [f32::NAN, f32::INFINITY, 1e30, 50.5, -1.0] {
+        let info = ProcessInfo {
+            border_percentage: percentage,
+            ..ProcessInfo::default()
+        };
+        assert_eq!(
+            setting_blockers(&info),
+            vec![format!(
+                "The border of {} % is outside 0 to 50 %",
+                percentage
+            )]
+        );
+    }
+    let info = ProcessInfo {
+        border_percentage: 50.0,
+        ..ProcessInfo::default()
+    };
+    assert!(setting_blockers(&info).is_empty());
+
+    // From a settings file, where `nan` is valid TOML.
+    let info: ProcessInfo = toml::from_str("border_percentage = nan").unwrap();
+    assert_eq!(setting_blockers(&info).len(), 1);
+}
+
+#[test]
+fn every_side_frame_and_pixel_border_is_checked() {
+    let info = ProcessInfo {
+        border_sides: Some(BorderSides {
+            top: 1.0,
+            right: f32::NAN,
+            bottom: 60.0,
+            left: 1.0,
+        }),
+        ..ProcessInfo::default()
+    };
+    assert_eq!(setting_blockers(&info).len(), 2);
+
+    let info = ProcessInfo {
+        border_percentage: f32::NAN,
+        border_pixels: Some(PixelBorder {
+            width: MAX_DIMENSION,
+            after_resize: false,
+        }),
+        ..ProcessInfo::default()
+    };
+    // The percentage is replaced, so only the pixels count.
+    assert_eq!(
+        setting_blockers(&info),
+        vec!["The border of 65535 px is wider than 32767 px"]
+    );
+
+    let info = ProcessInfo {
+        frame: Some(Frame {
+            width: f32::INFINITY,
+            ..Frame::default()
+        }),
+        ..ProcessInfo::default()
+    };
+    assert_eq!(
+        setting_blockers(&info),
+        vec!["The frame of inf % is outside 0 to 25 %"]
+    );
+}
+
+#[test]
+fn resizing_outside_the_setting_is_a_blocker() {
+    for longest in [0, MAX_DIMENSION + 1] {
+        let info = ProcessInfo {
+            resize_images: true,
+            resize_longest_dimension: longest,
+            ..ProcessInfo::default()
+        };
+        assert_eq!(
+            setting_blockers(&info),
+            vec![format!(
+                "Resizing to {} px is outside 1 to 65535 px",
+                longest
+            )]
+        );
+    }
+    // Left alone while resizing is off.
+    let info = ProcessInfo {
+        resize_images: false,
+        resize_longest_dimension: 0,
+        ..ProcessInfo::default()
+    };
+    assert!(setting_blockers(&info).is_empty());
+}
+
+#[test]
+fn overlapping_folders_are_recognised_through_any_spelling() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in");
+    fs::create_dir_all(input.join("sub")).unwrap();
+    fs::create_dir(dir.path().join("out")).unwrap();
+
+    assert_eq!(
+        validation::overlap(&input, &input.join("sub").join("..")),
+        Some(Overlap::Same)
+    );
+    // Not created yet, but it would be inside.
+    assert_eq!(
+        validation::overlap(&input, &input.join("sub/new")),
+        Some(Overlap::Inside)
+    );
+    assert_eq!(validation::overlap(&input, &dir.path().join("out")), None);
+    // A sibling whose name starts like the input is apart.
+    assert_eq!(validation::overlap(&input, &dir.path().join("in2")), None);
+    assert_eq!(validation::overlap(Path::new(""), &input), None);
+
+    let checked = validation::validate(
+        &input,
+        &input,
+        &[],
+        &[],
+        ProcessInfo::default(),
+        &BatchNaming::default(),
+    );
+    assert_eq!(checked.overlap, Some(Overlap::Same));
+}
+
+#[test]
+fn outputs_without_a_suffix_must_not_replace_their_input() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["a.png", "b.jpg", "c-web.png"] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    let naming = |suffix: &str| BatchNaming {
+        suffix: suffix.to_string(),
+        ..BatchNaming::default()
+    };
+
+    // A PNG keeps its own name, a JPEG gets another extension.
+    let paths = scan_inputs(dir.path(), dir.path(), "", false).unwrap();
+    assert_eq!(paths.len(), 3);
+    let checked = validation::validate(
+        dir.path(),
+        dir.path(),
+        &paths[..2],
+        &[],
+        ProcessInfo::default(),
+        &naming(""),
+    );
+    assert_eq!(checked.errors.len(), 1, "{:?}", checked.errors);
+    assert!(
+        checked.errors[0].contains("a.png") && checked.errors[0].contains("its own output"),
+        "{}",
+        checked.errors[0]
+    );
+
+    // Other suffixes name the outputs and leave them out of a rescan.
+    let paths = scan_inputs(dir.path(), dir.path(), "-web", false).unwrap();
+    assert_eq!(paths.len(), 2);
+    let checked = validation::validate(
+        dir.path(),
+        dir.path(),
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &naming("-web"),
+    );
+    assert!(checked.errors.is_empty(), "{:?}", checked.errors);
+
+    let bad = validation::validate(
+        dir.path(),
+        dir.path(),
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &naming("a/b"),
+    );
+    assert_eq!(bad.errors.len(), 1, "{:?}", bad.errors);
+}
+
+#[test]
+fn subfolders_are_scanned_and_can_be_mirrored() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path();
+    let output = input.join("out");
+    for name in ["a.png", "sub/a.png", "sub/deep/b.png", "out/old.png"] {
+        let path = input.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        RgbaImage::from_pixel(4, 3, Rgba([0, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+    }
+
+    // The output folder is never scanned, even when it is inside the input.
+    let paths = scan_inputs(input, &output, DEFAULT_SUFFIX, true).unwrap();
+    assert_eq!(
+        paths,
+        vec![
+            input.join("a.png"),
+            input.join("sub/a.png"),
+            input.join("sub/deep/b.png")
+        ]
+    );
+    assert_eq!(
+        scan_inputs(input, &output, DEFAULT_SUFFIX, false)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Flat, the two a.png collide.
+    let flat = BatchNaming::default();
+    let checked = validation::validate(input, &output, &paths, &[], ProcessInfo::default(), &flat);
+    assert!(
+        checked
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("a_bordered.png; only one")),
+        "{:?}",
+        checked.warnings
+    );
+
+    let mirrored = BatchNaming {
+        mirror_root: Some(input.to_path_buf()),
+        ..BatchNaming::default()
+    };
+    assert_eq!(mirrored.folder(&paths[2]), Path::new("sub/deep"));
+    let checked = validation::validate(
+        input,
+        &output,
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &mirrored,
+    );
+    assert!(
+        !checked
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("only one")),
+        "{:?}",
+        checked.warnings
+    );
+
+    // A file in the way of a subfolder only fails the files that go there.
+    fs::create_dir(&output).ok();
+    fs::write(output.join("sub"), "").unwrap();
+    let results = Mutex::new(vec![]);
+    run_batch_cancellable(
+        &paths,
+        &output,
+        &[],
+        ProcessInfo::default(),
+        &mirrored,
+        &AtomicBool::new(false),
+        |path, _, result| {
+            results
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), result.is_ok()))
+        },
+    );
+    let mut results = results.into_inner().unwrap();
+    results.sort();
+    assert_eq!(
+        results,
+        vec![
+            (paths[0].clone(), true),
+            (paths[1].clone(), false),
+            (paths[2].clone(), false)
+        ]
+    );
+    assert!(output.join("a_bordered.png").exists());
+
+    fs::remove_file(output.join("sub")).unwrap();
+    run_batch_cancellable(
+        &paths,
+        &output,
+        &[],
+        ProcessInfo::default(),
+        &mirrored,
+        &AtomicBool::new(false),
+        |_, _, result| assert!(result.is_ok()),
+    );
+    assert!(output.join("sub/a_bordered.png").exists());
+    assert!(output.join("sub/deep/b_bordered.png").exists());
+}
+
+#[test]
+fn moved_paths_land_in_front_of_the_target() {
+    let paths =
+        |names: &str| -> Vec<PathBuf> { names.chars().map(|c| c.to_string().into()).collect() };
+
+    let mut order = paths("abcd");
+    move_path(&mut order, 0, 2);
+    assert_eq!(order, paths("bacd"));
+    move_path(&mut order, 3, 0);
+    assert_eq!(order, paths("dbac"));
+    move_path(&mut order, 1, 4);
+    assert_eq!(order, paths("dacb"));
+    move_path(&mut order, 9, 0);
+    assert_eq!(order, paths("dacb"));
+}
+
+#[test]
+fn a_chosen_order_survives_rescans() {
+    let paths =
+        |names: &str| -> Vec<PathBuf> { names.chars().map(|c| c.to_string().into()).collect() };
+
+    // "b" is gone and "e" and "f" are new.
+    assert_eq!(apply_order(paths("acdef"), &paths("dcba")), paths("dcaef"));
+    assert_eq!(apply_order(paths("abc"), &[]), paths("abc"));
+}
@@ -0,0 +1,297 @@
+use std::path::{Path, PathBuf};
+
+use image::{imageops::FilterType, Rgba};
+use image_finalizer::{
+    crop::Crop,
+    fill::{BorderFill, TextureFill},
+    format::{
+        AvifSettings, FormatSettings, JpegSettings, OutputFormat, PngCompression, PngSettings,
+    },
+    hook::HookSettings,
+    serialization::{filter_type, hex_color},
+    settings_file::SettingsFile,
+    transform::Transform,
+    trim::Trim,
+    webhook::WebhookSettings,
+    BorderMode, BorderSides, ProcessInfo,
+};
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// The settings stored in the `*_v1` fixtures.
+fn custom() -> ProcessInfo {
+    ProcessInfo {
+        trim: Trim::default(),
+        transform: Transform::default(),
+        crop: Crop::default(),
+        symmetrical_border: true,
+        border_percentage: 12.5,
+        border_sides: None,
+        border_pixels: None,
+        canvas_aspect: None,
+        frame: None,
+        corner_radius: None,
+        drop_shadow: None,
+        keyline: None,
+        border_color: Rgba([16, 32, 48, 255]),
+        border_fill: BorderFill::Solid,
+        resize_images: true,
+        resize_longest_dimension: 2048,
+        resize_filter: FilterType::CatmullRom,
+        output_format: OutputFormat::Jpeg,
+        format_settings: FormatSettings {
+            png: PngSettings {
+                compression: PngCompression::Best,
+            },
+            jpeg: JpegSettings { quality: 92 },
+            avif: AvifSettings {
+                quality: 70,
+                speed: 6,
+            },
+        },
+    }
+}
+
+#[test]
+fn round_trips_through_toml() {
+    for info in [ProcessInfo::default(), custom()] {
+        let text = toml::to_string_pretty(&info).unwrap();
+        assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    }
+}
+
+#[test]
+fn border_sides_are_stored_only_when_set() {
+    let info = ProcessInfo {
+        border_sides: Some(BorderSides {
+            top: 4.0,
+            right: 4.0,
+            bottom: 12.0,
+            left: 4.0,
+        }),
+        ..custom()
+    };
+    let text = toml::to_string_pretty(&info).unwrap();
+    assert!(text.contains("[border_sides]"), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    assert!(!toml::to_string(&custom()).unwrap().contains("border_sides"));
+}
+
+#[test]
+fn round_trips_through_json() {
+    for info in [ProcessInfo::default(), custom()] {
+        let text = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<ProcessInfo>(&text).unwrap(), info);
+    }
+}
+
+#[test]
+fn field_names_are_stable() {
+    let table = toml::Table::try_from(ProcessInfo::default()).unwrap();
+    let keys: Vec<&str> = table.keys().map(String::as_str).collect();
+    let mut expected = vec![
+        "border_color",
+        "border_percentage",
+        "format_settings",
+        "output_format",
+        "resize_filter",
+        "resize_images",
+        "resize_longest_dimension",
+        "symmetrical_border",
+    ];
+    expected.sort();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn reads_v1_preset() {
+    let text = std::fs::read_to_string(fixture("preset_v1.toml")).unwrap();
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), custom());
+}
+
+#[test]
+fn reads_v1_settings_export() {
+    let file = SettingsFile::read(&fixture("settings_v1.json")).unwrap();
+    assert_eq!(file.version, 1);
+    assert_eq!(file.settings, custom());
+    assert!(!file.contains_command);
+}
+
+#[test]
+fn exports_with_a_hook_say_so() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+    let hook = HookSettings {
+        command: "exiftool {output}".to_string(),
+        ..HookSettings::default()
+    };
+    SettingsFile::new(custom())
+        .with_hook(Some(hook.clone()))
+        .write(&path)
+        .unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("\"contains_command\": true"), "{}", text);
+    let file = SettingsFile::read(&path).unwrap();
+    assert!(file.contains_command);
+    assert_eq!(file.hook, Some(hook));
+}
+
+#[test]
+fn exports_leave_out_the_webhook_unless_given() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+    SettingsFile::new(custom()).write(&path).unwrap();
+    assert!(!std::fs::read_to_string(&path).unwrap().contains("webhook"));
+
+    let webhook = WebhookSettings {
+        url: "https://hooks.example.com/batch".to_string(),
+    };
+    SettingsFile::new(custom())
+        .with_webhook(Some(webhook.clone()))
+        .write(&path)
+        .unwrap();
+    assert_eq!(SettingsFile::read(&path).unwrap().webhook, Some(webhook));
+}
+
+#[test]
+fn missing_fields_use_defaults() {
+    let text = std::fs::read_to_string(fixture("preset_legacy.toml")).unwrap();
+    let info: ProcessInfo = toml::from_str(&text).unwrap();
+
+    assert_eq!(
+        info,
+        ProcessInfo {
+            border_percentage: 5.0,
+            border_color: Rgba([0, 0, 0, 255]),
+            ..ProcessInfo::default()
+        }
+    );
+}
+
+#[test]
+fn unknown_fields_are_ignored() {
+    let info: ProcessInfo =
+        toml::from_str("border_percentage = 3.0\nfrom_the_future = true\n").unwrap();
+    assert_eq!(info.border_percentage, 3.0);
+}
+
+#[test]
+fn filter_names_round_trip() {
+    for filter in [
+        FilterType::Nearest,
+        FilterType::Triangle,
+        FilterType::CatmullRom,
+        FilterType::Gaussian,
+        FilterType::Lanczos3,
+    ] {
+        let info = ProcessInfo {
+            resize_filter: filter,
+            ..ProcessInfo::default()
+        };
+        let text = serde_json::to_string(&info).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProcessInfo>(&text)
+                .unwrap()
+                .resize_filter,
+            filter
+        );
+    }
+
+    assert_eq!(
+        filter_type::from_name("LANCZOS3"),
+        Some(FilterType::Lanczos3)
+    );
+    assert!(toml::from_str::<ProcessInfo>("resize_filter = \"bicubic\"").is_err());
+}
+
+#[test]
+fn hex_colors() {
+    assert_eq!(hex_color::parse("#102030"), Some(Rgba([16, 32, 48, 255])));
+    assert_eq!(hex_color::parse("10203040"), Some(Rgba([16, 32, 48, 64])));
+    assert_eq!(
+        hex_color::parse(" #ffFFff "),
+        Some(Rgba([255, 255, 255, 255]))
+    );
+    assert_eq!(hex_color::parse("#12345"), None);
+    assert_eq!(hex_color::parse("#GG0000"), None);
+    assert_eq!(hex_color::parse("#ééé"), None);
+    assert!(toml::from_str::<ProcessInfo>("border_color = \"red\"").is_err());
+}
+
+#[test]
+fn enums_use_lowercase_names() {
+    assert_eq!(
+        serde_json::to_string(&OutputFormat::Webp).unwrap(),
+        "\"webp\""
+    );
+    assert_eq!(
+        serde_json::to_string(&PngCompression::Default).unwrap(),
+        "\"default\""
+    );
+    assert_eq!(
+        serde_json::to_string(&BorderMode::Symmetrical).unwrap(),
+        "\"symmetrical\""
+    );
+    assert_eq!(
+        serde_json::from_str::<BorderMode>("\"square\"").unwrap(),
+        BorderMode::Square
+    );
+}
+
+fn textured(path: PathBuf) -> ProcessInfo {
+    ProcessInfo {
+        border_fill: BorderFill::Texture(TextureFill {
+            path,
+            ..TextureFill::default()
+        }),
+        ..custom()
+    }
+}
+
+fn texture_path(info: &ProcessInfo) -> &Path {
+    match &info.border_fill {
+        BorderFill::Texture(texture) => &texture.path,
+        fill => panic!("not a texture: {:?}", fill),
+    }
+}
+
+#[test]
+fn textures_are_exported_relative_to_the_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    let textures = dir.path().join("textures");
+    std::fs::create_dir(&textures).unwrap();
+    std::fs::write(textures.join("paper.png"), b"").unwrap();
+    let exports = dir.path().join("exports");
+    std::fs::create_dir(&exports).unwrap();
+    let path = exports.join("settings.json");
+    SettingsFile::new(textured(textures.join("paper.png")))
+        .write(&path)
+        .unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let relative = Path::new("..").join("textures").join("paper.png");
+    let stored = serde_json::to_string(&relative).unwrap();
+    assert!(text.contains(&stored), "{}", text);
+
+    // Resolved next to the export, wherever it was copied.
+    let file = SettingsFile::read(&path).unwrap();
+    assert_eq!(texture_path(&file.settings), exports.join(&relative));
+    assert!(file.missing_files().is_empty());
+}
+
+#[test]
+fn missing_textures_are_listed_on_import() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+    SettingsFile::new(textured(dir.path().join("gone.png")))
+        .write(&path)
+        .unwrap();
+    let file = SettingsFile::read(&path).unwrap();
+    assert_eq!(file.missing_files(), [dir.path().join("gone.png")]);
+    assert!(SettingsFile::new(custom()).missing_files().is_empty());
+}
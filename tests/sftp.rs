@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use image_finalizer::sftp::{self, HostKey, KnownHosts, SftpSettings};
+
+fn settings(base_path: &str) -> SftpSettings {
+    SftpSettings {
+        host: "photos.example.com".to_string(),
+        username: "ana".to_string(),
+        base_path: base_path.to_string(),
+        ..SftpSettings::default()
+    }
+}
+
+#[test]
+fn remote_paths_keep_the_folders_below_the_output_folder() {
+    let output_dir = Path::new("out");
+    let path = Path::new("out").join("2024").join("photo.jpg");
+    assert_eq!(
+        settings("").remote_path(output_dir, &path),
+        "2024/photo.jpg"
+    );
+    assert_eq!(
+        settings("/srv/photos/").remote_path(output_dir, &path),
+        "/srv/photos/2024/photo.jpg"
+    );
+    assert_eq!(
+        settings("/").remote_path(output_dir, &path),
+        "/2024/photo.jpg"
+    );
+}
+
+#[test]
+fn parent_folders_are_created_from_the_outside_in() {
+    assert_eq!(
+        sftp::remote_dirs("/srv/photos/2024/photo.jpg"),
+        ["/srv", "/srv/photos", "/srv/photos/2024"]
+    );
+    assert_eq!(sftp::remote_dirs("2024/photo.jpg"), ["2024"]);
+    assert!(sftp::remote_dirs("photo.jpg").is_empty());
+}
+
+#[test]
+fn addresses_name_the_user_and_leave_out_the_default_port() {
+    let mut settings = settings("");
+    assert!(settings.is_complete());
+    assert_eq!(
+        settings.url("/srv/photo.jpg"),
+        "sftp://ana@photos.example.com/srv/photo.jpg"
+    );
+    settings.port = 2222;
+    assert_eq!(settings.address(), "photos.example.com:2222");
+    assert_eq!(
+        settings.url("photo.jpg"),
+        "sftp://ana@photos.example.com:2222/~/photo.jpg"
+    );
+    assert!(!SftpSettings::default().is_complete());
+}
+
+#[test]
+fn host_keys_are_trusted_on_first_use_only() {
+    let mut hosts = KnownHosts::default();
+    assert_eq!(hosts.check("host:22", "SHA256:a"), HostKey::Unknown);
+    hosts.trust("host:22", "SHA256:a");
+    assert_eq!(hosts.check("host:22", "SHA256:a"), HostKey::Trusted);
+    assert_eq!(
+        hosts.check("host:22", "SHA256:b"),
+        HostKey::Changed {
+            trusted: "SHA256:a".to_string()
+        }
+    );
+    assert_eq!(hosts.check("host:2222", "SHA256:a"), HostKey::Unknown);
+}
+
+#[test]
+fn known_hosts_survive_a_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings").join("known_hosts.json");
+    assert_eq!(KnownHosts::load(&path).unwrap(), KnownHosts::default());
+    let mut hosts = KnownHosts::default();
+    hosts.trust("host:22", "SHA256:a");
+    hosts.save(&path).unwrap();
+    assert_eq!(
+        KnownHosts::load(&path).unwrap().get("host:22"),
+        Some("SHA256:a")
+    );
+}
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use image::{DynamicImage, ImageBuffer, Rgb, RgbImage, Rgba};
+use image_finalizer::{
+    corners::{CornerRadius, RadiusUnit},
+    pipeline::{FileContext, Pipeline},
+    shadow::DropShadow,
+    ProcessInfo,
+};
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+fn photo() -> DynamicImage {
+    RgbImage::from_pixel(60, 40, Rgb([255, 0, 0])).into()
+}
+
+fn hard(offset_x: f32, offset_y: f32) -> DropShadow {
+    DropShadow {
+        offset_x,
+        offset_y,
+        blur: 0.0,
+        opacity: 100.0,
+        color: Rgba([0, 0, 0, 255]),
+    }
+}
+
+fn run(shadow: DropShadow, img: DynamicImage) -> image::RgbaImage {
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 50.0,
+        border_color: WHITE,
+        drop_shadow: Some(shadow),
+        ..ProcessInfo::default()
+    };
+    let ctx = FileContext {
+        path: Path::new("photo.png"),
+    };
+    Pipeline::for_info(&info).run(img, &ctx).unwrap().to_rgba8()
+}
+
+#[test]
+fn hard_shadows_are_the_photo_moved() {
+    let (shadow, position) = hard(5.0, -3.0).cast(&photo());
+    assert_eq!(shadow.dimensions(), (60, 40));
+    assert_eq!(position, (5, -3));
+    assert!(shadow.pixels().all(|pixel| *pixel == Rgba([0, 0, 0, 255])));
+}
+
+#[test]
+fn blurred_shadows_fade_out_past_the_photo() {
+    let shadow = DropShadow {
+        blur: 10.0,
+        ..hard(0.0, 0.0)
+    };
+    let (cast, (x, y)) = shadow.cast(&photo());
+    // Padded by three deviations of the blur on every side.
+    assert_eq!((x, y), (-15, -15));
+    assert_eq!(cast.dimensions(), (90, 70));
+    let alpha = |x: u32, y: u32| cast.get_pixel(x, y).0[3];
+    assert!(alpha(0, 35) < 5);
+    assert!(alpha(45, 35) > 250);
+    // Half covered right on the edge of the photo.
+    assert!((100..=160).contains(&alpha(15, 35)), "{}", alpha(15, 35));
+    let row: Vec<u8> = (0..=45).map(|x| alpha(x, 35)).collect();
+    assert!(row.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", row);
+}
+
+#[test]
+fn opacity_and_color_tint_the_shadow() {
+    let shadow = DropShadow {
+        opacity: 50.0,
+        color: Rgba([0, 0, 255, 255]),
+        ..hard(0.0, 0.0)
+    };
+    let (cast, _) = shadow.cast(&photo());
+    assert_eq!(*cast.get_pixel(30, 20), Rgba([0, 0, 255, 128]));
+}
+
+#[test]
+fn transparent_parts_cast_no_shadow() {
+    let rounded = CornerRadius {
+        radius: 10.0,
+        unit: RadiusUnit::Pixels,
+    }
+    .apply(&photo());
+    let (cast, _) = hard(0.0, 0.0).cast(&rounded);
+    assert_eq!(cast.get_pixel(0, 0).0[3], 0);
+    assert_eq!(cast.get_pixel(30, 20).0[3], 255);
+}
+
+#[test]
+fn the_shadow_falls_between_border_and_photo() {
+    let out = run(hard(6.0, 4.0), photo());
+    // A symmetrical border of 30 pixels puts the photo at (15, 15).
+    assert_eq!(out.dimensions(), (90, 70));
+    assert_eq!(*out.get_pixel(15, 15), RED);
+    assert_eq!(*out.get_pixel(74, 54), RED);
+    // Below and right of the photo lies the shadow, the border elsewhere.
+    assert_eq!(*out.get_pixel(77, 57), Rgba([0, 0, 0, 255]));
+    assert_eq!(*out.get_pixel(80, 30), Rgba([0, 0, 0, 255]));
+    assert_eq!(*out.get_pixel(81, 30), WHITE);
+    assert_eq!(*out.get_pixel(18, 12), WHITE);
+    assert_eq!(*out.get_pixel(12, 30), WHITE);
+}
+
+#[test]
+fn deep_photos_keep_their_depth() {
+    let deep: DynamicImage = ImageBuffer::from_pixel(60, 40, Rgba([65535u16, 0, 0, 65535])).into();
+    let info = ProcessInfo {
+        drop_shadow: Some(DropShadow::default()),
+        ..ProcessInfo::default()
+    };
+    let ctx = FileContext {
+        path: Path::new("photo.png"),
+    };
+    let out = Pipeline::for_info(&info).run(deep, &ctx).unwrap();
+    assert_eq!(out.color(), image::ColorType::Rgba16);
+}
+
+#[test]
+fn shadows_round_trip_through_toml() {
+    let info = ProcessInfo {
+        drop_shadow: Some(DropShadow {
+            color: Rgba([16, 32, 48, 255]),
+            ..DropShadow::default()
+        }),
+        ..ProcessInfo::default()
+    };
+    let text = toml::to_string_pretty(&info).unwrap();
+    assert!(text.contains("[drop_shadow]"), "{}", text);
+    assert!(text.contains("color = \"#102030FF\""), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+    assert!(!toml::to_string(&ProcessInfo::default())
+        .unwrap()
+        .contains("drop_shadow"));
+}
@@ -0,0 +1,154 @@
+use std::{fs, io};
+
+use image::{Rgba, RgbaImage};
+use image_finalizer::{
+    format::OutputFormat,
+    naming::{BatchNaming, OriginalsPlacement},
+    space::{self, Space},
+    validation::{self, Validation},
+    ProcessInfo,
+};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+#[test]
+fn small_batches_still_want_headroom() {
+    assert_eq!(space::compare(GIB, 10 * GIB), Space::Enough);
+    assert_eq!(space::compare(10_000_000, 400_000_000), Space::Tight);
+    assert_eq!(space::compare(10_000_000, 600_000_000), Space::Enough);
+    assert_eq!(space::compare(GIB + 1, GIB), Space::Short);
+}
+
+#[test]
+fn large_batches_want_a_tenth_to_spare() {
+    assert_eq!(space::compare(100 * GIB, 105 * GIB), Space::Tight);
+    assert_eq!(space::compare(100 * GIB, 111 * GIB), Space::Enough);
+    assert_eq!(space::compare(100 * GIB, 100 * GIB), Space::Tight);
+}
+
+#[test]
+fn lossless_outputs_are_guessed_uncompressed() {
+    let size = (1000, 500);
+    assert_eq!(
+        space::rough_output_size(OutputFormat::Tiff, size, 8, 10),
+        2_000_000
+    );
+    assert_eq!(
+        space::rough_output_size(OutputFormat::Png, size, 16, 10),
+        4_000_000
+    );
+    assert_eq!(
+        space::rough_output_size(OutputFormat::Jpeg, size, 8, 10),
+        500_000
+    );
+    assert_eq!(
+        space::rough_output_size(OutputFormat::Avif, size, 8, 900_000),
+        900_000
+    );
+}
+
+#[test]
+fn missing_output_folders_are_measured_on_their_drive() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("not").join("yet");
+    assert!(space::available_space(&missing).unwrap() > 0);
+    assert!(!missing.exists());
+}
+
+#[test]
+fn only_running_out_of_space_counts_as_disk_full() {
+    assert!(space::is_disk_full(&io::Error::from(
+        io::ErrorKind::StorageFull
+    )));
+    assert!(space::is_disk_full(&io::Error::from(
+        io::ErrorKind::QuotaExceeded
+    )));
+    assert!(!space::is_disk_full(&io::Error::from(
+        io::ErrorKind::PermissionDenied
+    )));
+}
+
+#[test]
+fn batches_that_cannot_fit_are_blocked() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut validation = Validation::default();
+    validation.check_space(dir.path(), 1);
+    assert!(validation.is_clean());
+
+    validation.check_space(dir.path(), u64::MAX);
+    assert_eq!(validation.errors.len(), 1);
+    assert!(validation.warnings.is_empty());
+}
+
+#[test]
+fn validation_guesses_what_a_batch_writes() {
+    let input = tempfile::tempdir().unwrap();
+    let path = input.path().join("photo.png");
+    RgbaImage::from_pixel(100, 50, Rgba([10, 20, 30, 255]))
+        .save(&path)
+        .unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let info = ProcessInfo {
+        output_format: OutputFormat::Tiff,
+        ..ProcessInfo::default()
+    };
+    let validation = validation::validate(
+        input.path(),
+        output.path(),
+        &[path],
+        &[],
+        info.clone(),
+        &BatchNaming::default(),
+    );
+    // A square canvas 10% larger than the longest side.
+    assert_eq!(validation.expected_bytes, 110 * 110 * 4);
+
+    let copies = BatchNaming {
+        originals: Some(OriginalsPlacement::Subfolder),
+        ..BatchNaming::default()
+    };
+    let paths = vec![input.path().join("photo.png")];
+    let with_originals =
+        validation::validate(input.path(), output.path(), &paths, &[], info, &copies);
+    let input_bytes = fs::metadata(&paths[0]).unwrap().len();
+    assert_eq!(
+        with_originals.expected_bytes,
+        validation.expected_bytes + input_bytes
+    );
+}
+
+#[test]
+fn absurd_canvases_are_guessed_without_overflowing() {
+    let largest = (u32::MAX, u32::MAX);
+    assert_eq!(
+        space::rough_output_size(OutputFormat::Png, largest, 16, 10),
+        u64::MAX
+    );
+
+    let input = tempfile::tempdir().unwrap();
+    let paths: Vec<_> = ["a.png", "b.png"]
+        .iter()
+        .map(|name| {
+            let path = input.path().join(name);
+            RgbaImage::from_pixel(100, 50, Rgba([10, 20, 30, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect();
+    let output = tempfile::tempdir().unwrap();
+    let info = ProcessInfo {
+        output_format: OutputFormat::Tiff,
+        border_percentage: 1e30,
+        ..ProcessInfo::default()
+    };
+    let validation = validation::validate(
+        input.path(),
+        output.path(),
+        &paths,
+        &[],
+        info,
+        &BatchNaming::default(),
+    );
+    assert_eq!(validation.expected_bytes, u64::MAX);
+}
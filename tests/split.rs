@@ -0,0 +1,117 @@
+use std::fs;
+
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage, Rgba, RgbaImage};
+use image_finalizer::{
+    naming::{FileNaming, NameTemplate},
+    process_split,
+    split::{self, SplitFit, SplitSettings, TileAspect},
+    ProcessInfo,
+};
+
+fn settings(tiles: u32, aspect: TileAspect, fit: SplitFit) -> SplitSettings {
+    SplitSettings { tiles, aspect, fit }
+}
+
+#[test]
+fn remainder_pixels_go_to_the_first_tiles() {
+    // Exactly three squares wide, but 302 doesn't divide by three.
+    let layout = split::layout(302, 100, &settings(3, TileAspect::Square, SplitFit::Pad));
+    assert_eq!((layout.width, layout.height), (302, 101));
+    assert_eq!(layout.columns, [(0, 101), (101, 101), (202, 100)]);
+    let widths: u32 = layout.columns.iter().map(|&(_, width)| width).sum();
+    assert_eq!(widths, layout.width);
+}
+
+#[test]
+fn photos_are_cropped_or_padded_to_the_tiles() {
+    // Wider than four squares.
+    let crop = split::layout(1000, 200, &settings(4, TileAspect::Square, SplitFit::Crop));
+    assert_eq!(
+        (crop.x, crop.y, crop.width, crop.height),
+        (100, 0, 800, 200)
+    );
+    let pad = split::layout(1000, 200, &settings(4, TileAspect::Square, SplitFit::Pad));
+    assert_eq!((pad.x, pad.y, pad.width, pad.height), (0, -25, 1000, 250));
+
+    // Narrower than two portrait tiles of 4:5.
+    let crop = split::layout(400, 400, &settings(2, TileAspect::Portrait, SplitFit::Crop));
+    assert_eq!((crop.x, crop.y, crop.width, crop.height), (0, 75, 400, 250));
+    let pad = split::layout(400, 400, &settings(2, TileAspect::Portrait, SplitFit::Pad));
+    assert_eq!((pad.x, pad.y, pad.width, pad.height), (-120, 0, 640, 400));
+    assert_eq!(pad.columns, [(0, 320), (320, 320)]);
+}
+
+#[test]
+fn tiles_are_cut_left_to_right() {
+    // Red, green and blue thirds.
+    let img = DynamicImage::from(RgbImage::from_fn(30, 10, |x, _| match x / 10 {
+        0 => Rgb([255, 0, 0]),
+        1 => Rgb([0, 255, 0]),
+        _ => Rgb([0, 0, 255]),
+    }));
+    let white = Rgba([255, 255, 255, 255]);
+    let tiles = split::split(
+        &img,
+        &settings(3, TileAspect::Square, SplitFit::Crop),
+        white,
+    );
+    let colors: Vec<Rgba<u8>> = tiles.iter().map(|tile| tile.get_pixel(5, 5)).collect();
+    assert_eq!(
+        colors,
+        [
+            Rgba([255, 0, 0, 255]),
+            Rgba([0, 255, 0, 255]),
+            Rgba([0, 0, 255, 255])
+        ]
+    );
+    assert!(tiles.iter().all(|tile| tile.dimensions() == (10, 10)));
+
+    // Padding takes the border color.
+    let tiles = split::split(&img, &settings(2, TileAspect::Square, SplitFit::Pad), white);
+    assert_eq!(tiles[0].dimensions(), (15, 15));
+    assert_eq!(tiles[0].get_pixel(0, 0), white);
+}
+
+#[test]
+fn each_tile_is_written_and_numbered() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pano.png");
+    RgbaImage::from_pixel(300, 100, Rgba([0, 0, 0, 255]))
+        .save(&path)
+        .unwrap();
+    let info = ProcessInfo {
+        symmetrical_border: true,
+        border_percentage: 0.0,
+        ..ProcessInfo::default()
+    };
+    let template: NameTemplate = "{name}.{ext}".parse().unwrap();
+    let naming = FileNaming {
+        template: &template,
+        index: 0,
+        preset: "manual",
+        folder: "".as_ref(),
+        overwrite: Default::default(),
+        originals: None,
+        preserve_timestamps: false,
+        variant_folders: Default::default(),
+        srcset: None,
+    };
+    let output_dir = dir.path().join("out");
+
+    let outcome = process_split(
+        &path,
+        info,
+        &settings(3, TileAspect::Square, SplitFit::Crop),
+        &output_dir,
+        &naming,
+    )
+    .unwrap();
+    let names: Vec<String> = outcome
+        .output_paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, ["pano_1of3.png", "pano_2of3.png", "pano_3of3.png"]);
+    assert_eq!(outcome.final_dimensions, (100, 100));
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 3);
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use image::{ImageFormat, Rgba, RgbaImage};
+use image_finalizer::{
+    naming::BatchNaming,
+    srcset::{self, SrcsetMap, SrcsetSettings, UrlNames, MAP_NAME},
+    worker::{Command, Event, Worker},
+    ProcessInfo,
+};
+
+/// Runs a batch of `paths` into `output_dir` with `srcset`.
+fn run(paths: Vec<std::path::PathBuf>, output_dir: &Path, srcset: SrcsetSettings) {
+    let (tx, events) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let worker = Worker::spawn(move |event| {
+        let _ = tx.lock().unwrap().send(event);
+    });
+    worker.send(Command::StartBatch {
+        paths,
+        output_dir: output_dir.to_path_buf(),
+        rules: vec![],
+        manual: ProcessInfo {
+            symmetrical_border: true,
+            border_percentage: 0.0,
+            ..ProcessInfo::default()
+        },
+        edits: HashMap::new(),
+        naming: BatchNaming {
+            srcset: Some(srcset),
+            ..BatchNaming::default()
+        },
+        manifest: None,
+        placeholder: None,
+        archive: None,
+        hook: None,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+    loop {
+        match events.recv_timeout(Duration::from_secs(30)).unwrap() {
+            Event::FileDone { result, .. } => {
+                result.unwrap();
+            }
+            Event::BatchFinished { .. } => break,
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn names_are_made_safe_for_urls() {
+    assert_eq!(srcset::slug("Café Ölberg (2)"), "cafe-olberg-2");
+    assert_eq!(srcset::slug("  __ "), "image");
+    assert_eq!(srcset::percent_encode("Café 1.jpg"), "Caf%C3%A9%201.jpg");
+    assert_eq!(
+        srcset::url_path(
+            Path::new("out"),
+            &Path::new("out").join("a b").join("c&d.webp")
+        ),
+        "a%20b/c%26d.webp"
+    );
+}
+
+#[test]
+fn only_widths_below_the_output_are_copied() {
+    let settings = SrcsetSettings {
+        widths: vec![1920, 480, 0, 960, 480],
+        ..SrcsetSettings::default()
+    };
+    assert_eq!(settings.widths_below(1000), [480, 960]);
+    assert_eq!(settings.widths_below(480), [] as [u32; 0]);
+}
+
+#[test]
+fn copies_are_listed_in_the_map() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("Café 1.png");
+    RgbaImage::from_pixel(400, 200, Rgba([200, 100, 50, 255]))
+        .save(&input)
+        .unwrap();
+    let output_dir = dir.path().join("out");
+    run(
+        vec![input.clone()],
+        &output_dir,
+        SrcsetSettings {
+            widths: vec![100, 200, 800],
+            quality: 60,
+            html: true,
+            url_names: UrlNames::Percent,
+        },
+    );
+
+    let copy = output_dir.join("Café 1_bordered-100w.webp");
+    assert_eq!(ImageFormat::from_path(&copy).unwrap(), ImageFormat::WebP);
+    assert_eq!(image::image_dimensions(&copy).unwrap(), (100, 50));
+    assert!(output_dir.join("Café 1_bordered-200w.webp").exists());
+    assert!(!output_dir.join("Café 1_bordered-800w.webp").exists());
+
+    let map: SrcsetMap =
+        serde_json::from_str(&fs::read_to_string(output_dir.join(MAP_NAME)).unwrap()).unwrap();
+    assert_eq!(map.version, 1);
+    let entry = &map.images[0];
+    assert_eq!(entry.src, "Caf%C3%A9%201_bordered.png");
+    assert_eq!((entry.width, entry.height), (400, 200));
+    assert_eq!(entry.widths.keys().copied().collect::<Vec<_>>(), [100, 200]);
+    assert_eq!(
+        entry.srcset,
+        "Caf%C3%A9%201_bordered-100w.webp 100w, Caf%C3%A9%201_bordered-200w.webp 200w, \
+         Caf%C3%A9%201_bordered.png 400w"
+    );
+
+    let html = fs::read_to_string(output_dir.join("Café 1_bordered.html")).unwrap();
+    assert!(
+        html.contains(&format!("srcset=\"{}\"", entry.srcset)),
+        "{}",
+        html
+    );
+}
+
+#[test]
+fn slugs_name_the_copies() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("Café 1.png");
+    RgbaImage::from_pixel(300, 300, Rgba([0, 0, 0, 255]))
+        .save(&input)
+        .unwrap();
+    let output_dir = dir.path().join("out");
+    run(
+        vec![input],
+        &output_dir,
+        SrcsetSettings {
+            widths: vec![150],
+            url_names: UrlNames::Slug,
+            ..SrcsetSettings::default()
+        },
+    );
+
+    assert!(output_dir.join("cafe-1-bordered-150w.webp").exists());
+    let map: SrcsetMap =
+        serde_json::from_str(&fs::read_to_string(output_dir.join(MAP_NAME)).unwrap()).unwrap();
+    assert_eq!(map.images[0].widths[&150], "cafe-1-bordered-150w.webp");
+}
@@ -0,0 +1,479 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use image::{ImageBuffer, Rgba, RgbaImage};
+use image_finalizer::{
+    batch,
+    dates::parse_exif_date,
+    format::OutputFormat,
+    naming::{
+        BatchNaming, DateFolders, NameRules, NameTemplate, NameVars, RunFolder, TemplateError,
+        VariantFolders, DEFAULT_DATE_FOLDERS, DEFAULT_RUN_FOLDER, DEFAULT_TEMPLATE,
+    },
+    output_dimensions, validation, ProcessInfo,
+};
+use time::macros::datetime;
+
+fn vars() -> NameVars<'static> {
+    NameVars {
+        stem: OsStr::new("IMG_0042"),
+        extension: "jpg",
+        format: "jpeg",
+        width: 2048,
+        height: 1365,
+        date: Some(datetime!(2024-06-15 14:32:05)),
+        index: 4,
+        preset: "web",
+        combined_with: &[],
+        tile: None,
+    }
+}
+
+fn expand(template: &str) -> Result<String, TemplateError> {
+    let template: NameTemplate = template.parse()?;
+    let name = template.expand(&vars(), NameRules::Unix)?;
+    Ok(name.name.into_string().unwrap())
+}
+
+#[test]
+fn placeholders_expand() {
+    for (template, expected) in [
+        (DEFAULT_TEMPLATE, "IMG_0042_bordered.jpg"),
+        ("{name}_{width}x{height}.{ext}", "IMG_0042_2048x1365.jpg"),
+        ("{format}_{name}", "jpeg_IMG_0042"),
+        ("{date}_{name}.{ext}", "20240615_IMG_0042.jpg"),
+        (
+            "{date:%Y-%m-%d_%H%M%S} {name}",
+            "2024-06-15_143205 IMG_0042",
+        ),
+        ("{date:%y%j%%}", "24167%"),
+        ("{preset}-{name}.{ext}", "web-IMG_0042.jpg"),
+        ("{seq}.{ext}", "005.jpg"),
+        ("{seq:1}", "5"),
+        ("{seq:5:0}", "00004"),
+        ("{seq:2:100}", "104"),
+    ] {
+        assert_eq!(expand(template).as_deref(), Ok(expected), "{}", template);
+    }
+}
+
+#[test]
+fn suffix_can_be_changed_or_dropped() {
+    let template: NameTemplate = DEFAULT_TEMPLATE.parse().unwrap();
+    for (suffix, expected) in [("", "IMG_0042.jpg"), (" (web)", "IMG_0042 (web).jpg")] {
+        let name = template
+            .clone()
+            .with_suffix(suffix)
+            .unwrap()
+            .expand(&vars(), NameRules::Unix)
+            .unwrap();
+        assert_eq!(name.name, expected);
+    }
+    assert_eq!(
+        template.with_suffix("a/b").unwrap_err(),
+        TemplateError::IllegalCharacter('/')
+    );
+}
+
+#[test]
+fn invalid_templates_are_rejected() {
+    for (template, error) in [
+        ("", TemplateError::Empty),
+        ("  ", TemplateError::Empty),
+        ("{name", TemplateError::Unclosed),
+        (
+            "{nmae}.{ext}",
+            TemplateError::UnknownPlaceholder("nmae".to_string()),
+        ),
+        (
+            "{name:x}",
+            TemplateError::UnknownPlaceholder("name:x".to_string()),
+        ),
+        ("{date:%Q}", TemplateError::InvalidDate('Q')),
+        ("{date:%H:%M}", TemplateError::IllegalCharacter(':')),
+        ("{seq:}", TemplateError::InvalidSequence("seq:".to_string())),
+        (
+            "{seq:3:-1}",
+            TemplateError::InvalidSequence("seq:3:-1".to_string()),
+        ),
+        (
+            "{seq:99}",
+            TemplateError::InvalidSequence("seq:99".to_string()),
+        ),
+        ("a?{name}", TemplateError::IllegalCharacter('?')),
+        ("{name}\\x", TemplateError::IllegalCharacter('\\')),
+        ("{format}/{name}", TemplateError::IllegalCharacter('/')),
+    ] {
+        assert_eq!(
+            template.parse::<NameTemplate>(),
+            Err(error.clone()),
+            "{}",
+            template
+        );
+    }
+}
+
+#[test]
+fn sequences_that_count_past_the_largest_number_fail() {
+    let start = u64::MAX - 3;
+    assert_eq!(
+        expand(&format!("{{seq:1:{}}}", start)),
+        Err(TemplateError::SequenceOverflow)
+    );
+    // The files before still fit.
+    let template: NameTemplate = format!("{{seq:1:{}}}", start).parse().unwrap();
+    let last = NameVars { index: 3, ..vars() };
+    let name = template.expand(&last, NameRules::Unix).unwrap();
+    assert_eq!(name.name, OsStr::new(&u64::MAX.to_string()));
+}
+
+#[test]
+fn empty_names_and_missing_dates() {
+    let template: NameTemplate = "{date}".parse().unwrap();
+    let undated = NameVars {
+        date: None,
+        ..vars()
+    };
+    assert_eq!(
+        template.expand(&undated, NameRules::Unix).unwrap().name,
+        "undated"
+    );
+
+    let template: NameTemplate = "{name}".parse().unwrap();
+    let dots = NameVars {
+        stem: OsStr::new(".."),
+        ..vars()
+    };
+    assert_eq!(
+        template.expand(&dots, NameRules::Unix),
+        Err(TemplateError::EmptyName)
+    );
+}
+
+#[test]
+fn exif_dates_parse() {
+    assert_eq!(
+        parse_exif_date(b"2024:06:15 14:32:05"),
+        Some(datetime!(2024-06-15 14:32:05))
+    );
+    assert_eq!(parse_exif_date(b"0000:00:00 00:00:00"), None);
+    assert_eq!(parse_exif_date(b"    :  :     :  :  "), None);
+    assert_eq!(parse_exif_date(b"2024:13:01 00:00:00"), None);
+}
+
+fn inputs(dir: &Path, names: &[&str]) -> Vec<PathBuf> {
+    names
+        .iter()
+        .map(|name| {
+            let path = dir.join(name);
+            RgbaImage::from_pixel(4, 2, Rgba([0, 0, 0, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect()
+}
+
+#[test]
+fn sequence_follows_the_batch_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), &["c.png", "a.png", "b.png"]);
+    let naming = BatchNaming {
+        template: "{seq:2}_{name}_{width}.{ext}".to_string(),
+        ..BatchNaming::default()
+    };
+
+    let written = Mutex::new(vec![]);
+    batch::run_batch_cancellable(
+        &paths,
+        &dir.path().join("out"),
+        &[],
+        ProcessInfo::default(),
+        &naming,
+        &AtomicBool::new(false),
+        |_, _, result| {
+            let outcome = result.unwrap();
+            let name = outcome.output_paths[0].file_name().unwrap().to_owned();
+            written.lock().unwrap().push(name.into_string().unwrap());
+        },
+    );
+
+    let mut written = written.into_inner().unwrap();
+    written.sort();
+    assert_eq!(written, ["01_c_4.png", "02_a_4.png", "03_b_4.png"]);
+}
+
+#[test]
+fn validation_checks_the_template() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), &["a.png", "b.png"]);
+    let output = tempfile::tempdir().unwrap();
+    let check = |template: &str| {
+        let naming = BatchNaming {
+            template: template.to_string(),
+            manual_preset: Some("web".to_string()),
+            ..BatchNaming::default()
+        };
+        validation::validate(
+            dir.path(),
+            output.path(),
+            &paths,
+            &[],
+            ProcessInfo::default(),
+            &naming,
+        )
+    };
+
+    let checked = check("{name}.{ext");
+    assert_eq!(
+        checked.errors,
+        ["The name template can't be used: a { in the name template is never closed"]
+    );
+
+    // Without anything per file, every output gets the same name.
+    let checked = check("{preset}.{ext}");
+    assert!(checked.errors.is_empty());
+    assert_eq!(checked.warnings.len(), 1);
+    assert!(checked.warnings[0].ends_with("are all written to web.png; only one of them is kept"));
+
+    let checked = check("{preset}_{seq}.{ext}");
+    assert!(checked.is_clean(), "{:?}", checked);
+
+    let jpeg = ProcessInfo {
+        output_format: OutputFormat::Jpeg,
+        ..ProcessInfo::default()
+    };
+    let naming = BatchNaming {
+        template: "{name}.{format}".to_string(),
+        ..BatchNaming::default()
+    };
+    let checked = validation::validate(dir.path(), output.path(), &paths, &[], jpeg, &naming);
+    assert!(checked.is_clean(), "{:?}", checked);
+}
+
+#[test]
+fn date_folders_follow_the_pattern() {
+    let folders: DateFolders = DEFAULT_DATE_FOLDERS.parse().unwrap();
+    let date = Some(datetime!(2024-06-15 14:32:05));
+    assert_eq!(folders.folder(date), Path::new("2024/2024-06-15"));
+    assert_eq!(folders.folder(None), Path::new("undated"));
+    assert_eq!(
+        "%y/%j %Hh".parse::<DateFolders>().unwrap().folder(date),
+        Path::new("24/167 14h")
+    );
+
+    for (pattern, error) in [
+        ("", TemplateError::EmptyFolder),
+        ("%Y//%m", TemplateError::EmptyFolder),
+        ("%Y/..", TemplateError::EmptyFolder),
+        ("%Y/%Q", TemplateError::InvalidDate('Q')),
+        ("%Y\\%m", TemplateError::IllegalCharacter('\\')),
+    ] {
+        assert_eq!(pattern.parse::<DateFolders>(), Err(error), "{}", pattern);
+    }
+}
+
+#[test]
+fn outputs_are_sorted_by_date() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), &["a.png", "b.png"]);
+    // Noon, so the local date is the same in every time zone.
+    let noon = SystemTime::UNIX_EPOCH + Duration::from_secs(1_718_452_800);
+    fs::File::options()
+        .write(true)
+        .open(&paths[0])
+        .unwrap()
+        .set_modified(noon)
+        .unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let naming = BatchNaming {
+        date_folders: Some(DEFAULT_DATE_FOLDERS.to_string()),
+        ..BatchNaming::default()
+    };
+
+    let checked = validation::validate(
+        dir.path(),
+        output.path(),
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &naming,
+    );
+    assert!(checked.is_clean(), "{:?}", checked);
+    batch::run_batch_cancellable(
+        &paths,
+        output.path(),
+        &[],
+        ProcessInfo::default(),
+        &naming,
+        &AtomicBool::new(false),
+        |_, _, result| assert!(result.is_ok()),
+    );
+    assert!(output
+        .path()
+        .join("2024/2024-06-15/a_bordered.png")
+        .exists());
+
+    let broken = BatchNaming {
+        date_folders: Some("%Y/%k".to_string()),
+        ..BatchNaming::default()
+    };
+    let checked = validation::validate(
+        dir.path(),
+        output.path(),
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &broken,
+    );
+    assert_eq!(
+        checked.errors,
+        ["The date folder pattern can't be used: %k is not a date field"]
+    );
+}
+
+#[test]
+fn variants_are_sorted_by_format_and_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut paths = inputs(dir.path(), &["a.png"]);
+    let deep = dir.path().join("deep.png");
+    ImageBuffer::from_pixel(4, 2, Rgba([0u16, 0, 0, u16::MAX]))
+        .save(&deep)
+        .unwrap();
+    paths.push(deep);
+    let output = tempfile::tempdir().unwrap();
+    let naming = BatchNaming {
+        variant_folders: VariantFolders {
+            by_format: true,
+            by_size: true,
+        },
+        ..BatchNaming::default()
+    };
+    let jpeg = ProcessInfo {
+        output_format: OutputFormat::Jpeg,
+        resize_images: true,
+        resize_longest_dimension: 20,
+        ..ProcessInfo::default()
+    };
+
+    for info in [ProcessInfo::default(), jpeg] {
+        let checked = validation::validate(
+            dir.path(),
+            output.path(),
+            &paths,
+            &[],
+            info.clone(),
+            &naming,
+        );
+        // Only the 16-bit input is warned about, when saved as JPEG.
+        assert!(checked.errors.is_empty(), "{:?}", checked);
+        batch::run_batch_cancellable(
+            &paths,
+            output.path(),
+            &[],
+            info,
+            &naming,
+            &AtomicBool::new(false),
+            |_, _, result| assert!(result.is_ok()),
+        );
+    }
+
+    let (width, height) = output_dimensions(4, 2, &ProcessInfo::default());
+    let size = format!("{}px", width.max(height));
+    for path in [
+        Path::new("png").join(&size).join("a_bordered.png"),
+        Path::new("png-16bit").join(&size).join("deep_bordered.png"),
+        Path::new("jpeg/20px/a_bordered.jpg").to_path_buf(),
+        Path::new("jpeg/20px/deep_bordered.jpg").to_path_buf(),
+    ] {
+        assert!(
+            output.path().join(&path).exists(),
+            "{} is missing",
+            path.display()
+        );
+    }
+    assert!(!output.path().join("a_bordered.png").exists());
+}
+
+#[test]
+fn run_folders_are_named_after_the_start_and_preset() {
+    let started = datetime!(2024-06-15 14:32:05);
+    let folder: RunFolder = DEFAULT_RUN_FOLDER.parse().unwrap();
+    assert_eq!(folder.name(started, "web").unwrap(), "2024-06-15_1432_web");
+    // A preset name can't add folders.
+    assert_eq!(
+        folder.name(started, "web/large").unwrap(),
+        "2024-06-15_1432_web_large"
+    );
+    assert_eq!(
+        "{date}_{name}".parse::<RunFolder>(),
+        Err(TemplateError::NotForRunFolder("name".to_string()))
+    );
+    assert_eq!(
+        "{preset}".parse::<RunFolder>().unwrap().name(started, " "),
+        Err(TemplateError::EmptyFolder)
+    );
+
+    let output = Path::new("out");
+    let naming = BatchNaming {
+        manual_preset: Some("print".to_string()),
+        run_folder: Some("{date:%Y%m%d}-{preset}".to_string()),
+        ..BatchNaming::default()
+    };
+    assert_eq!(
+        naming.run_dir(output, started).unwrap(),
+        output.join("20240615-print")
+    );
+    assert_eq!(
+        BatchNaming::default().run_dir(output, started).unwrap(),
+        output
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), &["a.png"]);
+    let broken = BatchNaming {
+        run_folder: Some("{seq}".to_string()),
+        ..BatchNaming::default()
+    };
+    let checked = validation::validate(
+        dir.path(),
+        &dir.path().join("out"),
+        &paths,
+        &[],
+        ProcessInfo::default(),
+        &broken,
+    );
+    assert_eq!(
+        checked.errors,
+        ["The run folder pattern can't be used: {seq} can't name a run folder, only {date} and {preset} can"]
+    );
+}
+
+#[test]
+fn combined_names_expand() {
+    let others = [OsStr::new("IMG_0043"), OsStr::new("IMG_0050")];
+    let combined = NameVars {
+        combined_with: &others,
+        ..vars()
+    };
+    for (template, expected) in [
+        ("{names}", "IMG_0042+IMG_0043+IMG_0050"),
+        ("{name1}+{name3}", "IMG_0042+IMG_0050"),
+        ("{name2}_{name}", "IMG_0043_IMG_0042"),
+    ] {
+        let template: NameTemplate = template.parse().unwrap();
+        let name = template.expand(&combined, NameRules::Unix).unwrap();
+        assert_eq!(name.name, expected);
+    }
+    // Alone, a photo is its own set of names.
+    assert_eq!(expand("{names}-{name3}").as_deref(), Ok("IMG_0042-"));
+    assert_eq!(
+        "{names}".parse::<RunFolder>(),
+        Err(TemplateError::NotForRunFolder("names".to_string()))
+    );
+}
@@ -0,0 +1,229 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Mutex},
+};
+
+use image::{GenericImageView, ImageFormat, Rgba, RgbaImage};
+use image_finalizer::{
+    batch,
+    edit::ImageEdit,
+    naming::BatchNaming,
+    output_dimensions, process_file,
+    transform::{Rotation, Transform},
+    validation, ProcessInfo,
+};
+
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+/// A 40×20 photo, red on the left half and blue on the right.
+fn halves(path: &Path) {
+    RgbaImage::from_fn(40, 20, |x, _| if x < 20 { RED } else { BLUE })
+        .save(path)
+        .unwrap();
+}
+
+/// Settings that leave the photo as it is but for `transform`.
+fn bare(transform: Transform) -> ProcessInfo {
+    ProcessInfo {
+        transform,
+        symmetrical_border: true,
+        border_percentage: 0.0,
+        ..ProcessInfo::default()
+    }
+}
+
+fn turned(rotation: Rotation) -> Transform {
+    Transform {
+        rotation,
+        ..Transform::default()
+    }
+}
+
+/// `jpeg` with an EXIF block saying it is stored turned by `orientation`.
+fn with_orientation(jpeg: &[u8], orientation: u8) -> Vec<u8> {
+    let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+    // One entry: Orientation, a SHORT, then no further IFD.
+    tiff.extend([1, 0, 0x12, 0x01, 3, 0, 1, 0, 0, 0, orientation, 0, 0, 0]);
+    tiff.extend([0, 0, 0, 0]);
+    let mut app1 = b"Exif\0\0".to_vec();
+    app1.extend(tiff);
+
+    let mut out = jpeg[..2].to_vec();
+    out.extend([0xFF, 0xE1]);
+    out.extend(((app1.len() + 2) as u16).to_be_bytes());
+    out.extend(app1);
+    out.extend(&jpeg[2..]);
+    out
+}
+
+#[test]
+fn turns_compose_like_the_real_thing() {
+    let identity = Transform::default();
+    assert!(identity.is_identity());
+    assert!(identity.rotated(true).rotated(false).is_identity());
+    assert_eq!(
+        identity.rotated(true).rotated(true),
+        turned(Rotation::Cw180)
+    );
+    // Both mirrors are half a turn.
+    assert_eq!(
+        identity.flipped_horizontally().flipped_vertically(),
+        turned(Rotation::Cw180)
+    );
+    assert!(identity
+        .flipped_horizontally()
+        .flipped_horizontally()
+        .is_identity());
+
+    let img: image::DynamicImage =
+        RgbaImage::from_fn(3, 2, |x, y| Rgba([x as u8, y as u8, 0, 255])).into();
+    let mut transform = identity;
+    for step in 0..6 {
+        let next = match step % 3 {
+            0 => transform.rotated(true),
+            1 => transform.flipped_horizontally(),
+            _ => transform.rotated(false),
+        };
+        let one_by_one = match step % 3 {
+            0 => turned(Rotation::Cw90).apply(transform.apply(img.clone())),
+            1 => transform.apply(img.clone()).fliph(),
+            _ => turned(Rotation::Cw270).apply(transform.apply(img.clone())),
+        };
+        assert_eq!(next.apply(img.clone()), one_by_one, "{:?}", next);
+        transform = next;
+    }
+}
+
+#[test]
+fn rotation_is_stored_in_degrees() {
+    let info = bare(Transform {
+        rotation: Rotation::Cw270,
+        flip_horizontal: true,
+        flip_vertical: false,
+    });
+    let text = toml::to_string(&info).unwrap();
+    assert!(text.contains("rotation = 270"), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+
+    assert!(toml::from_str::<ProcessInfo>("[transform]\nrotation = 45\n").is_err());
+    assert!(!toml::to_string(&ProcessInfo::default())
+        .unwrap()
+        .contains("transform"));
+}
+
+#[test]
+fn sides_swap_before_the_border() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("halves.png");
+    halves(&input);
+
+    let info = ProcessInfo {
+        border_percentage: 10.0,
+        ..bare(turned(Rotation::Cw90))
+    };
+    let outcome = process_file(&input, info.clone(), &dir.path().join("out")).unwrap();
+    assert_eq!(outcome.final_dimensions, (24, 44));
+    assert_eq!(outcome.final_dimensions, output_dimensions(40, 20, &info));
+    assert_eq!(outcome.transform, turned(Rotation::Cw90));
+
+    // Turned clockwise, the left half ends up on top.
+    let output = image::open(outcome.output_path().unwrap())
+        .unwrap()
+        .to_rgba8();
+    assert_eq!(*output.get_pixel(12, 5), RED);
+    assert_eq!(*output.get_pixel(12, 38), BLUE);
+}
+
+#[test]
+fn manual_turns_come_after_the_exif_orientation() {
+    let dir = tempfile::tempdir().unwrap();
+    let png = dir.path().join("halves.png");
+    halves(&png);
+    let mut jpeg = vec![];
+    image::open(&png)
+        .unwrap()
+        .to_rgb8()
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+        .unwrap();
+    let input = dir.path().join("scan.jpg");
+    // 6: stored turned a quarter counterclockwise, so shown turned clockwise.
+    fs::write(&input, with_orientation(&jpeg, 6)).unwrap();
+
+    let (upright, _) = validation::read_header(&input).unwrap();
+    assert_eq!(upright, (20, 40));
+
+    let output_dir = dir.path().join("out");
+    let outcome = process_file(&input, bare(Transform::default()), &output_dir).unwrap();
+    assert_eq!(outcome.final_dimensions, (20, 40));
+    let output = image::open(outcome.output_path().unwrap()).unwrap();
+    let top = output.get_pixel(10, 5);
+    assert!(top[0] > 200 && top[2] < 60, "{:?}", top);
+
+    let outcome = process_file(&input, bare(turned(Rotation::Cw90)), &output_dir).unwrap();
+    assert_eq!(outcome.final_dimensions, (40, 20));
+    let output = image::open(outcome.output_path().unwrap()).unwrap();
+    // Half a turn from the stored photo: red ends up on the right.
+    let right = output.get_pixel(35, 10);
+    assert!(right[0] > 200 && right[2] < 60, "{:?}", right);
+}
+
+#[test]
+fn images_can_be_turned_on_their_own() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths: Vec<PathBuf> = ["a.png", "b.png"]
+        .iter()
+        .map(|name| {
+            let path = dir.path().join(name);
+            halves(&path);
+            path
+        })
+        .collect();
+    let flipped = Transform::default().flipped_vertically().rotated(true);
+    let edits = HashMap::from([(
+        paths[1].clone(),
+        ImageEdit {
+            transform: Some(flipped),
+            ..ImageEdit::default()
+        },
+    )]);
+
+    let outcomes = Mutex::new(HashMap::new());
+    batch::run_batch_retrying(
+        &paths,
+        &dir.path().join("out"),
+        &[],
+        bare(turned(Rotation::Cw180)),
+        &edits,
+        &BatchNaming::default(),
+        &AtomicBool::new(false),
+        |_| false,
+        |path, chosen, result| {
+            assert_eq!(
+                chosen.info.transform,
+                edits
+                    .get(path)
+                    .and_then(|edit| edit.transform)
+                    .unwrap_or(turned(Rotation::Cw180))
+            );
+            outcomes
+                .lock()
+                .unwrap()
+                .insert(path.to_path_buf(), result.unwrap());
+        },
+    );
+
+    let outcomes = outcomes.into_inner().unwrap();
+    let a = &outcomes[&paths[0]];
+    assert_eq!(a.final_dimensions, (40, 20));
+    assert_eq!(a.transform, turned(Rotation::Cw180));
+    let b = &outcomes[&paths[1]];
+    assert_eq!(b.final_dimensions, (20, 40));
+    assert_eq!(b.transform, flipped);
+    assert_eq!(
+        b.transform.describe().unwrap(),
+        "rotated 90°, flipped horizontally"
+    );
+}
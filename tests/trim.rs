@@ -0,0 +1,175 @@
+use std::path::Path;
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use image_finalizer::{
+    output_dimensions,
+    pipeline::{FileContext, Pipeline},
+    process_file,
+    transform::{Rotation, Transform},
+    trim::{MarginColor, Margins, Trim},
+    ProcessInfo,
+};
+
+const RED: Rgba<u8> = Rgba([200, 30, 30, 255]);
+
+/// A 40×30 image of `margin` with a 20×10 red photo whose top left corner
+/// is at (8, 5).
+fn framed(margin: Rgba<u8>) -> DynamicImage {
+    RgbaImage::from_fn(40, 30, |x, y| {
+        if (8..28).contains(&x) && (5..15).contains(&y) {
+            RED
+        } else {
+            margin
+        }
+    })
+    .into()
+}
+
+const FRAMED: Margins = Margins {
+    top: 5,
+    right: 12,
+    bottom: 15,
+    left: 8,
+};
+
+fn trim(color: MarginColor, tolerance: u8) -> Trim {
+    Trim {
+        enabled: true,
+        color,
+        tolerance,
+    }
+}
+
+/// Settings that only trim, with no border to speak of.
+fn trimming(trim: Trim) -> ProcessInfo {
+    ProcessInfo {
+        trim,
+        symmetrical_border: true,
+        border_percentage: 0.0,
+        ..ProcessInfo::default()
+    }
+}
+
+fn run(info: &ProcessInfo, img: DynamicImage) -> DynamicImage {
+    let ctx = FileContext {
+        path: Path::new("framed.png"),
+    };
+    Pipeline::for_info(info).run(img, &ctx).unwrap()
+}
+
+#[test]
+fn near_white_margins_are_cut_off() {
+    let white = trim(MarginColor::White, 24);
+    let img = framed(Rgba([240, 245, 250, 255]));
+    assert_eq!(white.margins(&img), FRAMED);
+
+    let trimmed = run(&trimming(white), img.clone());
+    assert_eq!(trimmed.dimensions(), (20, 10));
+    assert!(trimmed.to_rgba8().pixels().all(|pixel| *pixel == RED));
+
+    // Too far from white with a tight tolerance.
+    assert!(trim(MarginColor::White, 10).margins(&img).is_empty());
+    assert!(trim(MarginColor::Black, 24).margins(&img).is_empty());
+}
+
+#[test]
+fn other_margin_colors_can_be_picked() {
+    let black = framed(Rgba([8, 8, 8, 255]));
+    assert_eq!(trim(MarginColor::Black, 24).margins(&black), FRAMED);
+    assert!(trim(MarginColor::White, 24).margins(&black).is_empty());
+
+    let transparent = framed(Rgba([0, 0, 0, 0]));
+    assert_eq!(trim(MarginColor::Corner, 0).margins(&transparent), FRAMED);
+}
+
+#[test]
+fn images_without_margins_pass_through_untouched() {
+    let info = trimming(trim(MarginColor::White, 24));
+    let photo: DynamicImage = RgbaImage::from_fn(30, 20, |x, y| {
+        if x == 0 || y == 19 {
+            Rgba([255, 255, 255, 255])
+        } else {
+            RED
+        }
+    })
+    .into();
+    // The white column and row don't reach across.
+    let mut edged = photo.to_rgba8();
+    edged.put_pixel(0, 0, RED);
+    edged.put_pixel(29, 19, RED);
+    let edged = DynamicImage::from(edged);
+    assert!(info.trim.margins(&edged).is_empty());
+    assert_eq!(run(&info, edged.clone()), edged);
+
+    // Nothing but margin leaves nothing to keep, so it is kept whole.
+    let blank: DynamicImage = RgbaImage::from_pixel(16, 9, Rgba([255, 255, 255, 255])).into();
+    assert!(info.trim.margins(&blank).is_empty());
+    assert_eq!(run(&info, blank.clone()), blank);
+
+    // Off, even a clear margin stays.
+    assert!(Trim::default().margins(&framed(Rgba([255; 4]))).is_empty());
+    assert_eq!(Pipeline::for_info(&info).step_names(), ["trim", "border"]);
+}
+
+#[test]
+fn the_new_border_goes_around_what_is_left() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("framed.png");
+    framed(Rgba([255; 4])).save(&input).unwrap();
+
+    let info = ProcessInfo {
+        border_percentage: 10.0,
+        ..trimming(trim(MarginColor::White, 24))
+    };
+    let outcome = process_file(&input, info.clone(), &dir.path().join("out")).unwrap();
+    assert_eq!(outcome.final_dimensions, (22, 12));
+    assert_eq!(outcome.final_dimensions, output_dimensions(20, 10, &info));
+    assert_eq!(outcome.trimmed, FRAMED);
+    assert_eq!(
+        outcome.adjustments().unwrap(),
+        "trimmed top 5, right 12, bottom 15, left 8 px"
+    );
+}
+
+#[test]
+fn trimmed_sides_are_reported_as_turned() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("framed.png");
+    framed(Rgba([255; 4])).save(&input).unwrap();
+
+    let transform = Transform {
+        rotation: Rotation::Cw90,
+        flip_horizontal: true,
+        flip_vertical: false,
+    };
+    let info = ProcessInfo {
+        transform,
+        ..trimming(trim(MarginColor::White, 24))
+    };
+    let outcome = process_file(&input, info, &dir.path().join("out")).unwrap();
+    assert_eq!(outcome.final_dimensions, (10, 20));
+
+    // Turned, the left margin is at the top and the bottom one on the
+    // left, then mirrored to the right.
+    let expected = Margins {
+        top: 8,
+        right: 15,
+        bottom: 12,
+        left: 5,
+    };
+    assert_eq!(outcome.trimmed, expected);
+    assert_eq!(FRAMED.transformed(&transform), expected);
+    assert_eq!(FRAMED.transformed(&Transform::default()), FRAMED);
+}
+
+#[test]
+fn trim_is_stored_only_while_on() {
+    let info = trimming(trim(MarginColor::Corner, 40));
+    let text = toml::to_string(&info).unwrap();
+    assert!(text.contains("color = \"corner\""), "{}", text);
+    assert_eq!(toml::from_str::<ProcessInfo>(&text).unwrap(), info);
+
+    assert!(!toml::to_string(&ProcessInfo::default())
+        .unwrap()
+        .contains("trim"));
+}
@@ -0,0 +1,64 @@
+use std::{path::Path, time::Duration};
+
+use image_finalizer::upload::{self, UploadSettings};
+
+#[test]
+fn keys_keep_the_folders_below_the_output_folder() {
+    let output_dir = Path::new("out");
+    let path = Path::new("out").join("2024").join("photo.jpg");
+    assert_eq!(upload::object_key("", output_dir, &path), "2024/photo.jpg");
+    assert_eq!(
+        upload::object_key("clients/smith/", output_dir, &path),
+        "clients/smith/2024/photo.jpg"
+    );
+    assert_eq!(
+        upload::object_key(" /clients ", output_dir, &path),
+        "clients/2024/photo.jpg"
+    );
+}
+
+#[test]
+fn outputs_elsewhere_keep_their_file_name() {
+    let path = Path::new("elsewhere").join("photo.jpg");
+    assert_eq!(
+        upload::object_key("prefix", Path::new("out"), &path),
+        "prefix/photo.jpg"
+    );
+}
+
+#[test]
+fn outputs_are_sent_with_their_type() {
+    assert_eq!(upload::content_type(Path::new("a.JPG")), "image/jpeg");
+    assert_eq!(upload::content_type(Path::new("a.avif")), "image/avif");
+    assert_eq!(
+        upload::content_type(Path::new("outputs.zip")),
+        "application/zip"
+    );
+    assert_eq!(
+        upload::content_type(Path::new("a")),
+        "application/octet-stream"
+    );
+}
+
+#[test]
+fn retries_back_off_exponentially() {
+    let waits: Vec<_> = (1..=4).map(upload::backoff).collect();
+    assert_eq!(waits, [1, 2, 4, 8].map(Duration::from_secs).to_vec());
+    assert_eq!(upload::backoff(100), Duration::from_secs(64));
+}
+
+#[test]
+fn addresses_are_path_style() {
+    let settings = UploadSettings {
+        endpoint: "https://s3.us-west-004.backblazeb2.com/".to_string(),
+        region: String::new(),
+        bucket: "photos".to_string(),
+        prefix: String::new(),
+    };
+    assert!(settings.is_complete());
+    assert_eq!(
+        settings.object_url("2024/photo.jpg"),
+        "https://s3.us-west-004.backblazeb2.com/photos/2024/photo.jpg"
+    );
+    assert!(!UploadSettings::default().is_complete());
+}
@@ -0,0 +1,117 @@
+use std::{io, path::PathBuf, time::Duration};
+
+use image_finalizer::{
+    transform::Transform,
+    trim::Margins,
+    webhook::{BatchCounts, BatchReport, BatchStatus, BatchTally, MAX_FAILURES},
+    ProcessError, ProcessOutcome, SkipReason, StageTimings,
+};
+
+fn outcome(bytes_written: u64, skipped: Option<SkipReason>) -> ProcessOutcome {
+    ProcessOutcome {
+        input: PathBuf::from("in.png"),
+        output_paths: vec![PathBuf::from("out.png")],
+        final_dimensions: (10, 10),
+        bytes_written,
+        durations: StageTimings::default(),
+        skipped,
+        thumbnail: None,
+        srcset: vec![],
+        transform: Transform::default(),
+        trimmed: Margins::default(),
+    }
+}
+
+fn failure(i: usize) -> ProcessError {
+    ProcessError::Io {
+        path: PathBuf::from(format!("{}.png", i)),
+        source: io::Error::other("gone"),
+    }
+}
+
+#[test]
+fn tally_counts_every_file_but_keeps_few_failures() {
+    let mut tally = BatchTally::new(10);
+    tally.add(&Ok(outcome(1_000, None)));
+    tally.add(&Ok(outcome(2_500, None)));
+    tally.add(&Ok(outcome(0, Some(SkipReason::OutputExists))));
+    for i in 0..MAX_FAILURES + 2 {
+        tally.add(&Err(failure(i)));
+    }
+
+    let report = tally.report(
+        &PathBuf::from("exports").join("2024-06-15_1432"),
+        Duration::from_secs(62),
+    );
+    assert_eq!(report.status, BatchStatus::Failed);
+    assert_eq!(report.name, "2024-06-15_1432");
+    assert_eq!((report.total, report.written, report.skipped), (10, 2, 1));
+    assert_eq!(report.failed, MAX_FAILURES + 2);
+    assert_eq!(report.failures.len(), MAX_FAILURES);
+    assert!(
+        report.failures[0].starts_with("0.png"),
+        "{}",
+        report.failures[0]
+    );
+    assert_eq!(report.bytes, 3_500);
+    assert!(report.text.contains("1m 02s"), "{}", report.text);
+}
+
+#[test]
+fn cancelling_wins_over_failures() {
+    let counts = BatchCounts {
+        total: 4,
+        written: 1,
+        skipped: 0,
+        failed: 1,
+    };
+    let report = BatchReport::new(
+        &PathBuf::from("out"),
+        true,
+        counts,
+        &["broken".to_string()],
+        0,
+        Duration::ZERO,
+    );
+    assert_eq!(report.status, BatchStatus::Cancelled);
+
+    let clean = BatchCounts {
+        failed: 0,
+        ..counts
+    };
+    let report = BatchReport::new(&PathBuf::from("out"), false, clean, &[], 0, Duration::ZERO);
+    assert_eq!(report.status, BatchStatus::Completed);
+}
+
+#[test]
+fn report_fields_are_stable() {
+    let report = BatchTally::new(1).report(&PathBuf::from("out"), Duration::from_millis(1500));
+    let json = serde_json::to_value(&report).unwrap();
+    let mut keys: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort_unstable();
+    assert_eq!(
+        keys,
+        [
+            "bytes",
+            "duration_secs",
+            "failed",
+            "failures",
+            "name",
+            "output_dir",
+            "skipped",
+            "status",
+            "text",
+            "total",
+            "version",
+            "written"
+        ]
+    );
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["status"], "completed");
+    assert_eq!(json["duration_secs"], 1.5);
+}
@@ -0,0 +1,415 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use image_finalizer::{
+    naming::BatchNaming,
+    worker::{Command, Event, Worker},
+    ProcessInfo, THUMBNAIL_SIZE,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn inputs(dir: &std::path::Path, count: usize) -> Vec<PathBuf> {
+    (0..count)
+        .map(|i| {
+            let path = dir.join(format!("{}.png", i));
+            RgbaImage::from_pixel(4, 3, Rgba([i as u8, 0, 0, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        })
+        .collect()
+}
+
+fn spawn() -> (Worker, Receiver<Event>) {
+    let (tx, rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let worker = Worker::spawn(move |event| {
+        let _ = tx.lock().unwrap().send(event);
+    });
+    (worker, rx)
+}
+
+/// Collects the outcome of every file up to the end of the batch.
+fn finish_batch(events: &Receiver<Event>) -> (Vec<PathBuf>, bool) {
+    let mut done = vec![];
+    loop {
+        match events.recv_timeout(TIMEOUT).unwrap() {
+            Event::FileDone { path, result, .. } => {
+                assert!(result.unwrap().output_paths[0].exists());
+                done.push(path);
+            }
+            Event::BatchFinished { cancelled } => return (done, cancelled),
+            Event::Preview { .. }
+            | Event::Thumbnail { .. }
+            | Event::ArchiveProgress { .. }
+            | Event::ArchiveDone { .. }
+            | Event::SizeEstimate { .. }
+            | Event::DiskFull { .. }
+            | Event::DuplicateProgress { .. }
+            | Event::Duplicates { .. } => {}
+        }
+    }
+}
+
+#[test]
+fn batch_reports_every_file_then_finishes() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), 5);
+    let (worker, events) = spawn();
+
+    worker.send(Command::StartBatch {
+        paths: paths.clone(),
+        output_dir: dir.path().join("out"),
+        rules: vec![],
+        manual: ProcessInfo::default(),
+        edits: HashMap::new(),
+        naming: BatchNaming::default(),
+        manifest: None,
+        placeholder: None,
+        archive: None,
+        hook: None,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+
+    let (mut done, cancelled) = finish_batch(&events);
+    done.sort();
+    assert_eq!(done, paths);
+    assert!(!cancelled);
+}
+
+#[test]
+fn each_output_sends_a_thumbnail_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wide.png");
+    RgbaImage::from_pixel(400, 200, Rgba([0, 0, 255, 255]))
+        .save(&path)
+        .unwrap();
+    let (worker, events) = spawn();
+
+    worker.send(Command::StartBatch {
+        paths: vec![path.clone()],
+        output_dir: dir.path().join("out"),
+        rules: vec![],
+        manual: ProcessInfo::default(),
+        edits: HashMap::new(),
+        naming: BatchNaming::default(),
+        manifest: None,
+        placeholder: None,
+        archive: None,
+        hook: None,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+
+    match events.recv_timeout(TIMEOUT).unwrap() {
+        Event::Thumbnail {
+            input,
+            output,
+            image,
+        } => {
+            assert_eq!(input, path);
+            assert!(output.exists());
+            assert_eq!(image.width().max(image.height()), THUMBNAIL_SIZE);
+        }
+        other => panic!("expected a thumbnail, got {:?}", other),
+    }
+    match events.recv_timeout(TIMEOUT).unwrap() {
+        Event::FileDone { result, .. } => assert!(result.unwrap().thumbnail.is_none()),
+        other => panic!("expected the file to be done, got {:?}", other),
+    }
+}
+
+#[test]
+fn batches_run_one_after_another() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), 3);
+    let (worker, events) = spawn();
+
+    for output in ["first", "second"] {
+        worker.send(Command::StartBatch {
+            paths: paths.clone(),
+            output_dir: dir.path().join(output),
+            rules: vec![],
+            manual: ProcessInfo::default(),
+            edits: HashMap::new(),
+            naming: BatchNaming::default(),
+            manifest: None,
+            placeholder: None,
+            archive: None,
+            hook: None,
+            duplicates: vec![],
+            combine: None,
+            split: None,
+        });
+    }
+
+    assert_eq!(finish_batch(&events).0.len(), 3);
+    assert_eq!(finish_batch(&events).0.len(), 3);
+}
+
+#[test]
+fn cancel_stops_starting_new_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let workers = thread::available_parallelism().unwrap().get();
+    let paths = inputs(dir.path(), workers * 2 + 1);
+
+    // The first finished file holds every batch thread at the callback until
+    // the cancel has been sent.
+    let (started, wait_started) = mpsc::channel();
+    let (resume, wait_resume) = mpsc::channel::<()>();
+    let (tx, events) = mpsc::channel();
+    let gate = Mutex::new((Some(started), wait_resume, tx));
+    let worker = Worker::spawn(move |event| {
+        let mut gate = gate.lock().unwrap();
+        if let Some(started) = gate.0.take() {
+            started.send(()).unwrap();
+            gate.1.recv().unwrap();
+        }
+        let _ = gate.2.send(event);
+    });
+
+    worker.send(Command::StartBatch {
+        paths: paths.clone(),
+        output_dir: dir.path().join("out"),
+        rules: vec![],
+        manual: ProcessInfo::default(),
+        edits: HashMap::new(),
+        naming: BatchNaming::default(),
+        manifest: None,
+        placeholder: None,
+        archive: None,
+        hook: None,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+    wait_started.recv_timeout(TIMEOUT).unwrap();
+    worker.send(Command::CancelBatch);
+    resume.send(()).unwrap();
+
+    let (done, cancelled) = finish_batch(&events);
+    assert!(cancelled);
+    assert!(done.len() <= workers, "{} files done", done.len());
+}
+
+#[test]
+fn paused_batches_hold_until_resumed() {
+    let dir = tempfile::tempdir().unwrap();
+    let workers = thread::available_parallelism().unwrap().get();
+    let paths = inputs(dir.path(), workers * 2 + 1);
+
+    // As above, the first event holds the batch until the pause is sent.
+    let (started, wait_started) = mpsc::channel();
+    let (resume, wait_resume) = mpsc::channel::<()>();
+    let (tx, events) = mpsc::channel();
+    let gate = Mutex::new((Some(started), wait_resume, tx));
+    let worker = Worker::spawn(move |event| {
+        let mut gate = gate.lock().unwrap();
+        if let Some(started) = gate.0.take() {
+            started.send(()).unwrap();
+            gate.1.recv().unwrap();
+        }
+        let _ = gate.2.send(event);
+    });
+
+    worker.send(Command::StartBatch {
+        paths: paths.clone(),
+        output_dir: dir.path().join("out"),
+        rules: vec![],
+        manual: ProcessInfo::default(),
+        edits: HashMap::new(),
+        naming: BatchNaming::default(),
+        manifest: None,
+        placeholder: None,
+        archive: None,
+        hook: None,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+    wait_started.recv_timeout(TIMEOUT).unwrap();
+    worker.send(Command::PauseBatch);
+    resume.send(()).unwrap();
+
+    let mut held = 0;
+    while let Ok(event) = events.recv_timeout(Duration::from_millis(500)) {
+        match event {
+            Event::FileDone { .. } => held += 1,
+            Event::BatchFinished { .. } => panic!("a paused batch finished"),
+            _ => {}
+        }
+    }
+    assert!(held <= workers, "{} files done while paused", held);
+
+    worker.send(Command::ResumeBatch);
+    let (done, cancelled) = finish_batch(&events);
+    assert!(!cancelled);
+    assert_eq!(held + done.len(), paths.len());
+}
+
+#[test]
+fn preview_runs_the_pipeline() {
+    let (worker, events) = spawn();
+    let source: DynamicImage = RgbaImage::from_pixel(20, 10, Rgba([0, 0, 0, 255])).into();
+    let info = ProcessInfo {
+        border_percentage: 50.0,
+        ..ProcessInfo::default()
+    };
+
+    worker.send(Command::RequestPreview {
+        generation: 7,
+        path: PathBuf::from("photo.png"),
+        source: Arc::new(source),
+        info: info.clone(),
+        scale: 1.0,
+    });
+
+    match events.recv_timeout(TIMEOUT).unwrap() {
+        Event::Preview {
+            generation,
+            info: used,
+            result,
+        } => {
+            assert_eq!(generation, 7);
+            assert_eq!(used, info);
+            assert_eq!(result.unwrap().dimensions(), (30, 30));
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn latest_preview_is_always_delivered() {
+    let (worker, events) = spawn();
+    let source = Arc::new(DynamicImage::from(RgbaImage::new(8, 8)));
+
+    for generation in 1..=20 {
+        worker.send(Command::RequestPreview {
+            generation,
+            path: PathBuf::from("photo.png"),
+            source: source.clone(),
+            info: ProcessInfo::default(),
+            scale: 1.0,
+        });
+    }
+
+    let mut generations = vec![];
+    while generations.last() != Some(&20) {
+        match events.recv_timeout(TIMEOUT).unwrap() {
+            Event::Preview { generation, .. } => generations.push(generation),
+            other => panic!("{:?}", other),
+        }
+    }
+    assert!(generations.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn estimates_answer_with_their_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), 2);
+    let (worker, events) = spawn();
+
+    worker.send(Command::EstimateSize {
+        key: "settings".to_string(),
+        paths,
+        rules: vec![],
+        manual: ProcessInfo::default(),
+    });
+
+    match events.recv_timeout(TIMEOUT).unwrap() {
+        Event::SizeEstimate { key, result } => {
+            assert_eq!(key, "settings");
+            assert!(result.unwrap().exact);
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn dropping_the_worker_waits_for_the_batch() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), 3);
+    let (worker, events) = spawn();
+
+    worker.send(Command::StartBatch {
+        paths,
+        output_dir: dir.path().join("out"),
+        rules: vec![],
+        manual: ProcessInfo::default(),
+        edits: HashMap::new(),
+        naming: BatchNaming::default(),
+        manifest: None,
+        placeholder: None,
+        archive: None,
+        hook: None,
+        duplicates: vec![],
+        combine: None,
+        split: None,
+    });
+    drop(worker);
+
+    // Dropping cancels, so how many files are written depends on timing,
+    // but the batch always reports its end before the drop returns.
+    let events: Vec<Event> = events.try_iter().collect();
+    assert!(matches!(events.last(), Some(Event::BatchFinished { .. })));
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_hooks_fail_their_file_when_asked_to() {
+    use image_finalizer::hook::{HookFailure, HookSettings};
+
+    let dir = tempfile::tempdir().unwrap();
+    let paths = inputs(dir.path(), 2);
+    let (worker, events) = spawn();
+    let marker = dir.path().join("ran");
+    for on_failure in [HookFailure::Warn, HookFailure::Fail] {
+        worker.send(Command::StartBatch {
+            paths: paths.clone(),
+            output_dir: dir.path().join(format!("{:?}", on_failure)),
+            rules: vec![],
+            manual: ProcessInfo::default(),
+            edits: HashMap::new(),
+            naming: BatchNaming::default(),
+            manifest: None,
+            placeholder: None,
+            archive: None,
+            hook: Some(HookSettings {
+                command: format!("sh -c 'echo {{width}} >> {}; exit 1'", marker.display()),
+                on_failure,
+                ..HookSettings::default()
+            }),
+            duplicates: vec![],
+            combine: None,
+            split: None,
+        });
+        let mut failed = 0;
+        loop {
+            match events.recv_timeout(TIMEOUT).unwrap() {
+                Event::FileDone { result, .. } => failed += usize::from(result.is_err()),
+                Event::BatchFinished { .. } => break,
+                _ => {}
+            }
+        }
+        let expected = match on_failure {
+            HookFailure::Warn => 0,
+            HookFailure::Fail => paths.len(),
+        };
+        assert_eq!(failed, expected, "{:?}", on_failure);
+    }
+    // Once per output of both batches.
+    assert_eq!(std::fs::read_to_string(&marker).unwrap().lines().count(), 4);
+}